@@ -1,9 +1,26 @@
 //! Build script for kty.
 
 static PH_VAR: &str = "POSTHOG_API_KEY";
+static COMMIT_VAR: &str = "KTY_COMMIT";
 
 fn main() {
     if let Some(key) = std::env::var_os(PH_VAR) {
         println!("cargo:rustc-env={}={}", PH_VAR, key.to_string_lossy());
     }
+
+    // Builds outside of a checkout, such as from a source tarball, can pass it
+    // in instead.
+    let commit = std::env::var(COMMIT_VAR).ok().or_else(|| {
+        std::process::Command::new("git")
+            .args(["rev-parse", "--short", "HEAD"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map(|commit| commit.trim().to_string())
+    });
+
+    if let Some(commit) = commit {
+        println!("cargo:rustc-env={COMMIT_VAR}={commit}");
+    }
 }
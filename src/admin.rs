@@ -0,0 +1,347 @@
+use std::{result::Result, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use kube::{
+    api::{DeleteParams, ListParams},
+    Api, ResourceExt,
+};
+use serde::{Deserialize, Serialize};
+use warp::{
+    filters::BoxedFilter,
+    http::StatusCode,
+    reject::{self, Reject},
+    reply::{self, Response},
+    Filter, Rejection, Reply,
+};
+
+use crate::{
+    health,
+    identity::Key,
+    ssh::{registry::Kind, Controller},
+};
+
+#[derive(Debug)]
+struct Unauthorized;
+
+impl Reject for Unauthorized {}
+
+#[derive(Debug)]
+struct ApiError(String);
+
+impl Reject for ApiError {}
+
+impl From<eyre::Report> for ApiError {
+    fn from(err: eyre::Report) -> Self {
+        Self(err.to_string())
+    }
+}
+
+impl From<kube::Error> for ApiError {
+    fn from(err: kube::Error) -> Self {
+        Self(err.to_string())
+    }
+}
+
+fn reject(err: impl Into<ApiError>) -> Rejection {
+    reject::custom(err.into())
+}
+
+#[derive(Deserialize)]
+struct Message {
+    message: String,
+}
+
+/// Why sessions are being terminated, recorded in the audit log.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Terminate {
+    pub reason: Option<String>,
+}
+
+impl Terminate {
+    fn action(&self) -> String {
+        match &self.reason {
+            Some(reason) => format!("terminated by an admin: {reason}"),
+            None => "terminated by an admin".to_string(),
+        }
+    }
+}
+
+/// How many sessions were affected by a request.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Sent {
+    pub sessions: usize,
+}
+
+#[derive(Serialize)]
+struct KeyInfo {
+    name: String,
+    user: String,
+    groups: Vec<String>,
+    expiration: DateTime<Utc>,
+    approved: bool,
+}
+
+impl From<Key> for KeyInfo {
+    fn from(key: Key) -> Self {
+        Self {
+            approved: key.approved(),
+            name: key.name_any(),
+            user: key.spec.user,
+            groups: key.spec.groups,
+            expiration: key.spec.expiration,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Count {
+    pub name: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TabUsage {
+    pub tab: String,
+    pub views: u64,
+    pub seconds: f64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SessionUsage {
+    pub count: u64,
+    pub minutes: f64,
+    /// Sessions that lasted at most this many minutes, cumulative.
+    pub buckets: Vec<(f64, u64)>,
+}
+
+/// What the dashboard is used for, since the server started. This comes from
+/// the same metrics as `/metrics`, none of which have a user or session in
+/// them.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Usage {
+    pub sessions: SessionUsage,
+    pub tabs: Vec<TabUsage>,
+    /// Widgets that have been opened, as `resource/type`.
+    pub views: Vec<Count>,
+    pub operations: Vec<Count>,
+}
+
+impl Usage {
+    fn gather() -> Self {
+        let mut usage = Self::default();
+
+        for family in prometheus::gather() {
+            for metric in family.get_metric() {
+                let label = |name: &str| {
+                    metric
+                        .get_label()
+                        .iter()
+                        .find(|l| l.get_name() == name)
+                        .map(|l| l.get_value().to_string())
+                        .unwrap_or_default()
+                };
+
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let count = metric.get_counter().get_value() as u64;
+
+                match family.get_name() {
+                    "session_duration_minutes" => {
+                        let histogram = metric.get_histogram();
+
+                        usage.sessions = SessionUsage {
+                            count: histogram.get_sample_count(),
+                            minutes: histogram.get_sample_sum(),
+                            buckets: histogram
+                                .get_bucket()
+                                .iter()
+                                .map(|b| (b.get_upper_bound(), b.get_cumulative_count()))
+                                .collect(),
+                        };
+                    }
+                    "tab_duration_seconds" => usage.tabs.push(TabUsage {
+                        tab: label("tab"),
+                        views: metric.get_histogram().get_sample_count(),
+                        seconds: metric.get_histogram().get_sample_sum(),
+                    }),
+                    "widget_views_total" if count > 0 => usage.views.push(Count {
+                        name: format!("{}/{}", label("resource"), label("type")),
+                        count,
+                    }),
+                    "bulk_operations_total" => usage.operations.push(Count {
+                        name: label("operation"),
+                        count,
+                    }),
+                    _ => {}
+                }
+            }
+        }
+
+        usage.tabs.sort_by(|a, b| b.seconds.total_cmp(&a.seconds));
+        usage.views.sort_by(|a, b| b.count.cmp(&a.count));
+        usage.operations.sort_by(|a, b| b.count.cmp(&a.count));
+
+        usage
+    }
+}
+
+/// Routes for managing the server, all of them live under `/admin` and require
+/// `token` as a bearer token.
+pub fn routes(token: String, ctrl: Arc<Controller>) -> BoxedFilter<(impl Reply,)> {
+    let auth = warp::header::optional::<String>("authorization")
+        .and_then(move |header: Option<String>| {
+            let authorized = health::bearer(header.as_deref(), &token);
+
+            async move {
+                if authorized {
+                    Ok(())
+                } else {
+                    Err(reject::custom(Unauthorized))
+                }
+            }
+        })
+        .untuple_one();
+
+    let ctrl = warp::any().map(move || ctrl.clone());
+
+    let sessions = warp::path!("sessions")
+        .and(warp::get())
+        .and(ctrl.clone())
+        .map(|ctrl: Arc<Controller>| reply::json(&ctrl.sessions().sessions()));
+
+    let terminate = warp::path!("sessions" / String)
+        .and(warp::delete())
+        .and(ctrl.clone())
+        .map(|id: String, ctrl: Arc<Controller>| {
+            if ctrl.sessions().terminate(&id) {
+                StatusCode::NO_CONTENT
+            } else {
+                StatusCode::NOT_FOUND
+            }
+        });
+
+    let terminate_user = warp::path!("users" / String / "sessions")
+        .and(warp::delete())
+        .and(warp::query::<Terminate>())
+        .and(ctrl.clone())
+        .map(
+            |user: String, terminate: Terminate, ctrl: Arc<Controller>| {
+                reply::json(&Sent {
+                    sessions: ctrl.sessions().terminate_user(&user, &terminate.action()),
+                })
+            },
+        );
+
+    let messages = warp::path!("messages")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(ctrl.clone())
+        .and_then(message);
+
+    let audit = warp::path!("audit")
+        .and(warp::get())
+        .and(ctrl.clone())
+        .map(|ctrl: Arc<Controller>| reply::json(&ctrl.sessions().records()));
+
+    let usage = warp::path!("usage")
+        .and(warp::get())
+        .map(|| reply::json(&Usage::gather()));
+
+    let list_keys = warp::path!("keys")
+        .and(warp::get())
+        .and(ctrl.clone())
+        .and_then(list_keys);
+
+    let approve_key = warp::path!("keys" / String / "approve")
+        .and(warp::post())
+        .and(ctrl.clone())
+        .and_then(approve_key);
+
+    let revoke_key = warp::path!("keys" / String)
+        .and(warp::delete())
+        .and(ctrl)
+        .and_then(revoke_key);
+
+    warp::path("admin")
+        .and(auth)
+        .and(
+            sessions
+                .or(terminate)
+                .or(terminate_user)
+                .or(messages)
+                .or(audit)
+                .or(usage)
+                .or(list_keys)
+                .or(approve_key)
+                .or(revoke_key),
+        )
+        .recover(recover)
+        .boxed()
+}
+
+async fn message(body: Message, ctrl: Arc<Controller>) -> Result<impl Reply, Rejection> {
+    Ok(reply::json(&ctrl.sessions().message(body.message).await))
+}
+
+async fn list_keys(ctrl: Arc<Controller>) -> Result<impl Reply, Rejection> {
+    let keys = Api::<Key>::default_namespaced(ctrl.client().map_err(reject)?)
+        .list(&ListParams::default())
+        .await
+        .map_err(reject)?;
+
+    Ok(reply::json(
+        &keys.into_iter().map(KeyInfo::from).collect::<Vec<_>>(),
+    ))
+}
+
+async fn approve_key(name: String, ctrl: Arc<Controller>) -> Result<impl Reply, Rejection> {
+    Key::approve(ctrl.client().map_err(reject)?, &name)
+        .await
+        .map_err(reject)?;
+
+    ctrl.sessions()
+        .audit(Kind::KeyApproved, format!("key approved: {name}"));
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Sessions that logged in with the key are disconnected as well, otherwise
+// revoking it wouldn't take effect until they reconnect.
+async fn revoke_key(name: String, ctrl: Arc<Controller>) -> Result<Response, Rejection> {
+    let result = Api::<Key>::default_namespaced(ctrl.client().map_err(reject)?)
+        .delete(&name, &DeleteParams::default())
+        .await;
+
+    match result {
+        Ok(_) => {}
+        Err(kube::Error::Api(err)) if err.code == StatusCode::NOT_FOUND.as_u16() => {
+            return Ok(StatusCode::NOT_FOUND.into_response());
+        }
+        Err(err) => return Err(reject(err)),
+    }
+
+    ctrl.sessions()
+        .audit(Kind::KeyRevoked, format!("key revoked: {name}"));
+
+    Ok(reply::json(&Sent {
+        sessions: ctrl.sessions().terminate_key(&name),
+    })
+    .into_response())
+}
+
+async fn recover(err: Rejection) -> Result<impl Reply, Rejection> {
+    if err.find::<Unauthorized>().is_some() {
+        return Ok(reply::with_status(
+            "unauthorized".to_string(),
+            StatusCode::UNAUTHORIZED,
+        ));
+    }
+
+    if let Some(ApiError(msg)) = err.find::<ApiError>() {
+        return Ok(reply::with_status(
+            msg.clone(),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ));
+    }
+
+    Err(err)
+}
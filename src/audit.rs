@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::dashboard::context::Context;
+
+/// Transcripts stop growing past this, the session itself carries on.
+pub static TRANSCRIPT_LIMIT: usize = 1024 * 1024;
+
+/// Where shells opened from the dashboard are recorded. What was typed is left
+/// out unless transcripts have been turned on.
+pub trait Audit: Send + Sync {
+    /// A shell has exited.
+    fn exec(&self, session: ExecSession);
+
+    /// Whether everything written to the terminal should be kept as well.
+    fn transcripts(&self) -> bool;
+}
+
+/// A shell in a container, as it shows up in the audit log.
+#[derive(Clone, Debug, Serialize)]
+pub struct ExecSession {
+    pub namespace: String,
+    pub pod: String,
+    pub container: String,
+    /// The command line that was run, not anything typed into it.
+    pub command: String,
+    pub started: DateTime<Utc>,
+    pub ended: DateTime<Utc>,
+    /// Unset when the exit code couldn't be determined, such as when the
+    /// connection went away first.
+    pub exit_code: Option<i32>,
+    /// Bytes sent to the container.
+    pub bytes_in: u64,
+    /// Bytes received from the container.
+    pub bytes_out: u64,
+    /// The terminal output, including what was echoed back while typing. Only
+    /// kept when transcripts are on, up to [`TRANSCRIPT_LIMIT`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transcript: Option<String>,
+}
+
+/// The audit for the current dashboard, if there is one.
+pub(crate) fn current() -> Option<Arc<dyn Audit>> {
+    Context::with(|context| context.audit.clone())
+}
@@ -1,34 +1,29 @@
 mod dev;
+mod doctor;
+mod output;
 mod resources;
 mod serve;
+mod sessions;
 mod users;
 
-use std::sync::{Mutex, OnceLock};
+use std::sync::Mutex;
 
 use cata::{
     telemetry::{posthog, Telemetry},
     Command, Container,
 };
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use clap_verbosity_flag::Verbosity;
 use clio::Output;
 use eyre::{eyre, Result};
 use kube::Config;
-use tracing::metadata::LevelFilter;
 use tracing_error::ErrorLayer;
 use tracing_log::AsTrace;
 use tracing_subscriber::{filter::EnvFilter, prelude::*};
 
-static PH_KEY: Option<&str> = option_env!("POSTHOG_API_KEY");
+use crate::{LEVEL, OFFLINE};
 
-// While tracing allows for you to get the global log filter
-// (`tracing::metadata::LevelFilter::current()`), the
-// `tracing_subscriber::registry::Registry` doesn't actually set it. The
-// `Subscriber` interface exposes an interface to check for `enabled()` but that
-// doesn't look at the individual layers of the registry. This effectively
-// copies how the global LevelFilter is set and allows other things to check
-// against it in a similar fashion.
-pub(crate) static LEVEL: OnceLock<LevelFilter> = OnceLock::new();
+static PH_KEY: Option<&str> = option_env!("POSTHOG_API_KEY");
 
 #[derive(Parser, Container)]
 #[command(about, version)]
@@ -46,17 +41,54 @@ pub struct Root {
     #[arg(long, default_value="--", value_parser = allow_stderr)]
     log_file: Output,
 
+    /// Format of log output. With `json`, everything logged on behalf of an
+    /// SSH connection includes a `session_id` field for correlation.
+    #[arg(long, value_enum, default_value_t = LogFormat::Pretty)]
+    log_format: LogFormat,
+
+    /// Export traces to an OpenTelemetry collector at this gRPC endpoint, such
+    /// as `http://localhost:4317`. Every SSH connection gets a `session` span
+    /// and every request to the kube API an `HTTP` span within it.
+    #[cfg(feature = "otel")]
+    #[arg(long, env = "OTEL_EXPORTER_OTLP_ENDPOINT")]
+    otlp_endpoint: Option<String>,
+
+    /// Filter for the spans that are exported, this is separate from the
+    /// verbosity so that kube API requests can be exported without logging
+    /// them.
+    #[cfg(feature = "otel")]
+    #[arg(
+        long,
+        env = "KTY_OTLP_FILTER",
+        default_value = "info,kube_client=debug"
+    )]
+    otlp_filter: String,
+
     /// Disable telemetry
     #[arg(long, global = true)]
     no_telemetry: bool,
+
+    /// Run in an air-gapped cluster. This disables telemetry and only allows
+    /// HTTP requests to local hosts, the `OpenID` provider must be running
+    /// inside the cluster or network.
+    #[arg(long, global = true, env = "KTY_OFFLINE")]
+    offline: bool,
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+enum LogFormat {
+    Pretty,
+    Json,
 }
 
 #[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Container)]
 enum RootCmd {
     Dev(dev::Dev),
+    Doctor(doctor::Doctor),
     Resources(resources::Resources),
     Serve(serve::Serve),
+    Sessions(sessions::Sessions),
     Users(users::Users),
 }
 
@@ -70,20 +102,36 @@ impl Command for Root {
             return Err(eyre!("log level already set"));
         }
 
+        if OFFLINE.set(self.offline).is_err() {
+            return Err(eyre!("offline mode already set"));
+        }
+
         let filter = EnvFilter::builder()
             .with_default_directive(self.verbosity.log_level_filter().as_trace().into())
             .from_env_lossy();
 
-        let fmt = tracing_subscriber::fmt::layer()
-            .pretty()
-            .with_writer(Mutex::new(self.log_file.clone()))
-            .with_filter(filter);
+        let fmt = tracing_subscriber::fmt::layer().with_writer(Mutex::new(self.log_file.clone()));
+
+        let fmt = match self.log_format {
+            LogFormat::Pretty => fmt.pretty().boxed(),
+            LogFormat::Json => fmt.json().flatten_event(true).boxed(),
+        }
+        .with_filter(filter);
 
         let registry = tracing_subscriber::registry()
             .with(fmt)
             .with(ErrorLayer::default());
 
-        if self.no_telemetry {
+        #[cfg(feature = "otel")]
+        let registry = registry.with(self.otlp_endpoint.as_deref().map(tracer).transpose()?.map(
+            |tracer| {
+                tracing_opentelemetry::layer()
+                    .with_tracer(tracer)
+                    .with_filter(EnvFilter::new(&self.otlp_filter))
+            },
+        ));
+
+        if self.no_telemetry || self.offline {
             registry.init();
         } else {
             let telemetry = Telemetry::new(posthog::Posthog::new(PH_KEY.unwrap_or_default()))
@@ -97,6 +145,38 @@ impl Command for Root {
     }
 }
 
+#[cfg(feature = "otel")]
+fn tracer(endpoint: &str) -> Result<opentelemetry_sdk::trace::Tracer> {
+    use opentelemetry::{trace::TracerProvider as _, KeyValue};
+    use opentelemetry_otlp::WithExportConfig;
+
+    let provider = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(opentelemetry_sdk::trace::Config::default().with_resource(
+            opentelemetry_sdk::Resource::new([
+                KeyValue::new("service.name", "kty"),
+                KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+            ]),
+        ))
+        .install_batch(opentelemetry_sdk::runtime::Tokio)?;
+
+    let tracer = provider.tracer("kty");
+    opentelemetry::global::set_tracer_provider(provider);
+
+    Ok(tracer)
+}
+
+/// Flush anything that hasn't been exported yet, call this before exiting.
+pub async fn shutdown() {
+    #[cfg(feature = "otel")]
+    let _ = tokio::task::spawn_blocking(opentelemetry::global::shutdown_tracer_provider).await;
+}
+
 fn allow_stderr(val: &str) -> Result<Output, clio::Error> {
     if val == "--" {
         return Ok(Output::std_err());
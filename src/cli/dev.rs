@@ -1,6 +1,8 @@
 mod agent;
 mod authz;
 mod dashboard;
+mod mock;
+mod replay;
 mod shell;
 mod stdin;
 
@@ -19,6 +21,7 @@ enum DevCmd {
     Agent(agent::Agent),
     Authz(authz::Authz),
     Dashboard(dashboard::Dashboard),
+    Replay(replay::Replay),
     Shell(shell::Shell),
     Stdin(stdin::Stdin),
 }
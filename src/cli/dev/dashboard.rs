@@ -1,8 +1,10 @@
-use std::{io::Read, iter::Iterator, os::fd::AsRawFd, pin::Pin, task::Context};
+use std::{io::Read, iter::Iterator, os::fd::AsRawFd, path::PathBuf, pin::Pin, task::Context};
 
 use cata::{Command, Container};
+use chrono::{Local, Offset};
 use clap::Parser;
 use eyre::Result;
+use kube::config::{KubeConfigOptions, Kubeconfig};
 use mio::{unix::SourceFd, Events, Interest, Poll};
 use ratatui::{backend::WindowSize, layout::Size};
 use tokio::{
@@ -11,7 +13,19 @@ use tokio::{
     time::Duration,
 };
 
-use crate::{dashboard::Dashboard as UIDashboard, events::Event, io::Writer};
+use super::mock;
+use crate::{
+    dashboard::Dashboard as UIDashboard,
+    events::Event,
+    io::Writer,
+    resources::{alerts::AlertSource, redaction::Redaction},
+    widget::{
+        caps::Capabilities,
+        clock::Clock,
+        link::Link,
+        locale::{self, Locale},
+    },
+};
 
 static STDIN_TOKEN: mio::Token = mio::Token(0);
 
@@ -22,6 +36,70 @@ pub struct Dashboard {
 
     #[arg(long)]
     route: Vec<String>,
+
+    /// View to open on start, eg. `pods/default/nginx`.
+    #[arg(long)]
+    link: Option<Link>,
+
+    /// Only draw ASCII, defaults to what `TERM` supports.
+    #[arg(long, env = "KTY_ASCII")]
+    ascii: Option<bool>,
+
+    /// Mark statuses with symbols in addition to color.
+    #[arg(long, env = "KTY_SYMBOLS")]
+    symbols: bool,
+
+    /// Write what changed as lines of text instead of drawing the screen.
+    #[arg(long, env = "KTY_LINEAR")]
+    linear: bool,
+
+    /// Only use the 16 ANSI colors, defaults to what `TERM` and `COLORTERM`
+    /// support.
+    #[arg(long, env = "KTY_BASIC_COLORS")]
+    basic_colors: Option<bool>,
+
+    /// Animate things like switching tabs.
+    #[arg(long, env = "KTY_ANIMATIONS")]
+    animations: Option<bool>,
+
+    /// Run against a fake, in-memory cluster instead of the current kubeconfig.
+    /// Pods are replaced and crash every few seconds, handy for demos.
+    #[arg(long)]
+    mock: bool,
+
+    /// Kubeconfig to read instead of `KUBECONFIG` or `~/.kube/config`.
+    #[arg(long)]
+    kubeconfig: Option<PathBuf>,
+
+    /// Context from the kubeconfig to use instead of the current one. It is
+    /// shown in the status bar and terminal title.
+    #[arg(long)]
+    context: Option<String>,
+
+    /// Namespace to use as the default instead of the context's, such as for
+    /// where snapshots are saved.
+    #[arg(short, long)]
+    namespace: Option<String>,
+
+    /// Directory that snapshots (`ctrl-\`) are written to, view them with `kty
+    /// dev replay`.
+    #[arg(long, default_value = ".")]
+    snapshots: PathBuf,
+
+    /// Mask matches in logs, `aws`, `bearer`, `password` or a regular
+    /// expression. Can be repeated.
+    #[arg(long)]
+    redact: Vec<String>,
+
+    /// Alertmanager service to show alerts from, as
+    /// `<namespace>/<name>:<port>`.
+    #[arg(long)]
+    alertmanager: Option<String>,
+
+    /// Directory with plugins to add as tabs, see `kty serve --plugins`.
+    #[cfg(feature = "plugins")]
+    #[arg(long)]
+    plugins: Option<PathBuf>,
 }
 
 struct Stdin {
@@ -88,6 +166,46 @@ impl AsyncRead for Stdin {
     }
 }
 
+impl Dashboard {
+    // Without a kubeconfig or context, this works the same as
+    // `Client::try_default()`, which includes running in a pod.
+    async fn client(&self) -> Result<(kube::Client, Option<String>)> {
+        let (mut cfg, context) = if self.kubeconfig.is_none() && self.context.is_none() {
+            let context = Kubeconfig::read()
+                .ok()
+                .and_then(|kubeconfig| kubeconfig.current_context);
+
+            (kube::Config::infer().await?, context)
+        } else {
+            let kubeconfig = match &self.kubeconfig {
+                Some(path) => Kubeconfig::read_from(path)?,
+                None => Kubeconfig::read()?,
+            };
+
+            let context = self
+                .context
+                .clone()
+                .or_else(|| kubeconfig.current_context.clone());
+
+            let options = KubeConfigOptions {
+                context: self.context.clone(),
+                ..KubeConfigOptions::default()
+            };
+
+            (
+                kube::Config::from_custom_kubeconfig(kubeconfig, &options).await?,
+                context,
+            )
+        };
+
+        if let Some(namespace) = &self.namespace {
+            cfg.default_namespace.clone_from(namespace);
+        }
+
+        Ok((kube::Client::try_from(cfg)?, context))
+    }
+}
+
 #[async_trait::async_trait]
 impl Command for Dashboard {
     async fn run(&self) -> Result<()> {
@@ -96,8 +214,57 @@ impl Command for Dashboard {
 
         let (stop_tx, mut stop_rx) = unbounded_channel::<()>();
 
+        let (client, context, churn) = if self.mock {
+            let cluster = mock::Cluster::default();
+
+            (cluster.client(), None, Some(tokio::spawn(cluster.churn())))
+        } else {
+            let (client, context) = self.client().await?;
+
+            (client, context, None)
+        };
+
+        let default = Capabilities::from_term(&std::env::var("TERM").unwrap_or_default());
+
+        #[cfg(feature = "plugins")]
+        let plugins = match &self.plugins {
+            Some(dir) => crate::plugin::load(dir)?,
+            None => Vec::new(),
+        };
+        #[cfg(not(feature = "plugins"))]
+        let plugins = Vec::new();
+
         let dashboard = UIDashboard::builder()
-            .client(kube::Client::try_default().await?)
+            .client(client)
+            .maybe_cluster(context)
+            .maybe_link(self.link.clone())
+            .caps(Capabilities {
+                ascii: self.ascii.unwrap_or(default.ascii),
+                symbols: self.symbols,
+                linear: self.linear,
+                basic_colors: self.basic_colors.unwrap_or(
+                    default.basic_colors
+                        && !matches!(
+                            std::env::var("COLORTERM").as_deref(),
+                            Ok("truecolor" | "24bit")
+                        ),
+                ),
+                still: !self.animations.unwrap_or(true),
+            })
+            .clock(Clock {
+                offset: Local::now().offset().fix(),
+                ..Clock::default()
+            })
+            .locale(
+                locale::VARIABLES
+                    .iter()
+                    .find_map(|name| Locale::from_posix(&std::env::var(name).ok()?))
+                    .unwrap_or_default(),
+            )
+            .snapshots(self.snapshots.clone())
+            .redaction(Redaction::new(&self.redact))
+            .maybe_alerts(self.alertmanager.clone().map(AlertSource::Alertmanager))
+            .plugins(plugins)
             .build()
             .start(Stdin::new()?, LocalWriter { stop: stop_tx })?;
 
@@ -116,6 +283,10 @@ impl Command for Dashboard {
 
         stop_rx.recv().await;
 
+        if let Some(churn) = churn {
+            churn.abort();
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,794 @@
+//! An API server that only exists in memory, for `kty dev dashboard --mock`.
+//! It knows just enough of the kube API for the dashboard to list, get, watch
+//! and delete resources. Everything else is refused.
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    sync::{Arc, Mutex},
+};
+
+use bytes::Bytes;
+use chrono::{SecondsFormat, TimeDelta, Utc};
+use futures::stream;
+use http::{header::CONTENT_TYPE, HeaderValue, Method, Request, Response, StatusCode};
+use http_body::Frame;
+use http_body_util::{combinators::UnsyncBoxBody, BodyExt, Full, StreamBody};
+use kube::client::Body;
+use serde_json::{json, Value};
+use tokio::{
+    sync::broadcast::{self, error::RecvError},
+    time::Duration,
+};
+
+type ResponseBody = UnsyncBoxBody<Bytes, Infallible>;
+
+// Key for every object, (plural, namespace, name).
+type Key = (String, Option<String>, String);
+
+static NODES: usize = 4;
+static CHURN: Duration = Duration::from_secs(3);
+
+// (namespace, name, image, replicas)
+static DEPLOYMENTS: [(&str, &str, &str, usize); 6] = [
+    ("default", "frontend", "nginx:1.27", 3),
+    ("default", "redis", "redis:7.4", 1),
+    ("payments", "api", "ghcr.io/example/payments:1.4.2", 4),
+    (
+        "payments",
+        "worker",
+        "ghcr.io/example/payments-worker:1.4.2",
+        2,
+    ),
+    ("search", "indexer", "ghcr.io/example/indexer:0.9.0", 2),
+    ("search", "query", "ghcr.io/example/query:0.9.0", 5),
+];
+
+static SUFFIX: &[u8] = b"bcdfghjklmnpqrstvwxz2456789";
+
+#[derive(Clone)]
+struct Change {
+    kind: &'static str,
+    plural: String,
+    namespace: Option<String>,
+    object: Value,
+}
+
+struct State {
+    version: u64,
+    seed: u64,
+    objects: BTreeMap<Key, Value>,
+}
+
+impl State {
+    // xorshift, good enough to shuffle some pods around.
+    fn random(&mut self, n: usize) -> usize {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+
+        usize::try_from(self.seed % n.max(1) as u64).unwrap_or_default()
+    }
+
+    fn suffix(&mut self, len: usize) -> String {
+        (0..len)
+            .map(|_| SUFFIX[self.random(SUFFIX.len())] as char)
+            .collect()
+    }
+
+    fn insert(&mut self, plural: &str, mut object: Value) -> Change {
+        self.version += 1;
+        object["metadata"]["resourceVersion"] = json!(self.version.to_string());
+
+        let namespace = object["metadata"]["namespace"]
+            .as_str()
+            .map(ToString::to_string);
+        let name = object["metadata"]["name"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+
+        let kind = match self.objects.insert(
+            (plural.to_string(), namespace.clone(), name),
+            object.clone(),
+        ) {
+            Some(_) => "MODIFIED",
+            None => "ADDED",
+        };
+
+        Change {
+            kind,
+            plural: plural.to_string(),
+            namespace,
+            object,
+        }
+    }
+
+    fn remove(&mut self, key: &Key) -> Option<Change> {
+        let mut object = self.objects.remove(key)?;
+
+        self.version += 1;
+        object["metadata"]["resourceVersion"] = json!(self.version.to_string());
+
+        Some(Change {
+            kind: "DELETED",
+            plural: key.0.clone(),
+            namespace: key.1.clone(),
+            object,
+        })
+    }
+
+    fn keys(&self, plural: &str, pred: impl Fn(&Value) -> bool) -> Vec<Key> {
+        self.objects
+            .iter()
+            .filter(|((p, ..), object)| p == plural && pred(object))
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    fn pick(&mut self, plural: &str, pred: impl Fn(&Value) -> bool) -> Option<Key> {
+        let mut keys = self.keys(plural, pred);
+
+        if keys.is_empty() {
+            return None;
+        }
+
+        let idx = self.random(keys.len());
+
+        Some(keys.swap_remove(idx))
+    }
+
+    fn populate(&mut self) {
+        for i in 1..=NODES {
+            self.insert("nodes", node(&format!("mock-node-{i}")));
+        }
+
+        for (namespace, app, image, replicas) in DEPLOYMENTS {
+            let hash = self.suffix(10);
+
+            self.insert("deployments", deployment(namespace, app, image, replicas));
+            self.insert(
+                "replicasets",
+                replicaset(namespace, app, &hash, image, replicas),
+            );
+
+            for _ in 0..replicas {
+                let mut pod = self.pod(namespace, app, &hash, image);
+                running(&mut pod);
+
+                self.insert("pods", pod);
+            }
+        }
+
+        // Something for the housekeeping tab to find.
+        let mut debug = self.pod("default", "debug", "", "busybox:1.36");
+        debug["metadata"]["name"] = json!("debug");
+        debug["metadata"]["uid"] = json!(uid("pods", "default", "debug"));
+        debug["metadata"]["labels"] = json!({"run": "debug"});
+        debug["metadata"]["ownerReferences"] = json!([]);
+        running(&mut debug);
+
+        self.insert("pods", debug);
+
+        let crashing = self.pick("pods", is_owned);
+        if let Some(key) = crashing {
+            if let Some(mut pod) = self.objects.get(&key).cloned() {
+                crash(&mut pod);
+                self.insert("pods", pod);
+            }
+        }
+    }
+
+    fn pod(&mut self, namespace: &str, app: &str, hash: &str, image: &str) -> Value {
+        let name = format!("{app}-{hash}-{}", self.suffix(5));
+        let owner = format!("{app}-{hash}");
+        let node = format!("mock-node-{}", self.random(NODES) + 1);
+
+        json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": {
+                "name": name,
+                "namespace": namespace,
+                "uid": uid("pods", namespace, &name),
+                "creationTimestamp": timestamp(TimeDelta::zero()),
+                "labels": {
+                    "app": app,
+                    "pod-template-hash": hash,
+                },
+                "ownerReferences": [{
+                    "apiVersion": "apps/v1",
+                    "kind": "ReplicaSet",
+                    "name": owner,
+                    "uid": uid("replicasets", namespace, &owner),
+                    "controller": true,
+                    "blockOwnerDeletion": true,
+                }],
+            },
+            "spec": {
+                "nodeName": node,
+                "containers": [{
+                    "name": app,
+                    "image": image,
+                    "resources": {
+                        "requests": {"cpu": "100m", "memory": "128Mi"},
+                        "limits": {"memory": "256Mi"},
+                    },
+                }],
+            },
+            "status": {
+                "phase": "Pending",
+                "qosClass": "Burstable",
+            },
+        })
+    }
+
+    // Every tick, pending pods start, then one thing goes wrong (or right).
+    fn churn(&mut self) -> Vec<Change> {
+        let mut changes = Vec::new();
+
+        for key in self.keys("pods", |pod| pod["status"]["phase"] == "Pending") {
+            if let Some(mut pod) = self.objects.get(&key).cloned() {
+                running(&mut pod);
+                changes.push(self.insert("pods", pod));
+            }
+        }
+
+        match self.random(4) {
+            // A pod was evicted and replaced.
+            0 => {
+                let Some(key) = self.pick("pods", is_owned) else {
+                    return changes;
+                };
+
+                let Some(change) = self.remove(&key) else {
+                    return changes;
+                };
+
+                let labels = &change.object["metadata"]["labels"];
+                let app = labels["app"].as_str().unwrap_or_default().to_string();
+                let hash = labels["pod-template-hash"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string();
+                let image = change.object["spec"]["containers"][0]["image"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string();
+                let namespace = key.1.clone().unwrap_or_default();
+
+                changes.push(change);
+
+                let pod = self.pod(&namespace, &app, &hash, &image);
+                changes.push(self.insert("pods", pod));
+            }
+            // A container exited and is now in a crash loop.
+            1 => {
+                if let Some(key) = self.pick("pods", is_owned) {
+                    if let Some(mut pod) = self.objects.get(&key).cloned() {
+                        crash(&mut pod);
+                        changes.push(self.insert("pods", pod));
+                    }
+                }
+            }
+            // A crash loop sorted itself out.
+            2 => {
+                if let Some(key) = self.pick("pods", |pod| !is_ready(pod)) {
+                    if let Some(mut pod) = self.objects.get(&key).cloned() {
+                        running(&mut pod);
+                        changes.push(self.insert("pods", pod));
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        changes.extend(self.rollup());
+
+        changes
+    }
+
+    // Deployments and replica sets report how many of their pods are ready.
+    fn rollup(&mut self) -> Vec<Change> {
+        let mut changes = Vec::new();
+
+        for (namespace, app, ..) in DEPLOYMENTS {
+            let ready = self
+                .keys("pods", |pod| {
+                    pod["metadata"]["namespace"] == namespace
+                        && pod["metadata"]["labels"]["app"] == app
+                        && is_ready(pod)
+                })
+                .len();
+
+            for plural in ["deployments", "replicasets"] {
+                for key in self.keys(plural, |obj| {
+                    obj["metadata"]["namespace"] == namespace
+                        && obj["metadata"]["labels"]["app"] == app
+                }) {
+                    let Some(mut obj) = self.objects.get(&key).cloned() else {
+                        continue;
+                    };
+
+                    if obj["status"]["readyReplicas"] == json!(ready) {
+                        continue;
+                    }
+
+                    obj["status"]["readyReplicas"] = json!(ready);
+                    obj["status"]["availableReplicas"] = json!(ready);
+
+                    changes.push(self.insert(plural, obj));
+                }
+            }
+        }
+
+        changes
+    }
+}
+
+fn uid(plural: &str, namespace: &str, name: &str) -> String {
+    format!("mock-{plural}-{namespace}-{name}")
+}
+
+fn timestamp(ago: TimeDelta) -> String {
+    (Utc::now() - ago).to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn is_owned(pod: &Value) -> bool {
+    pod["metadata"]["ownerReferences"]
+        .as_array()
+        .is_some_and(|owners| !owners.is_empty())
+}
+
+fn is_ready(pod: &Value) -> bool {
+    pod["status"]["containerStatuses"][0]["ready"] == true
+}
+
+fn node(name: &str) -> Value {
+    json!({
+        "apiVersion": "v1",
+        "kind": "Node",
+        "metadata": {
+            "name": name,
+            "uid": uid("nodes", "", name),
+            "creationTimestamp": timestamp(TimeDelta::days(30)),
+            "labels": {
+                "kubernetes.io/hostname": name,
+                "kubernetes.io/os": "linux",
+            },
+        },
+        "status": {
+            "capacity": {"cpu": "8", "memory": "32Gi", "pods": "110"},
+            "allocatable": {"cpu": "8", "memory": "32Gi", "pods": "110"},
+            "conditions": [{
+                "type": "Ready",
+                "status": "True",
+                "reason": "KubeletReady",
+                "message": "kubelet is posting ready status",
+            }],
+        },
+    })
+}
+
+fn deployment(namespace: &str, app: &str, image: &str, replicas: usize) -> Value {
+    json!({
+        "apiVersion": "apps/v1",
+        "kind": "Deployment",
+        "metadata": {
+            "name": app,
+            "namespace": namespace,
+            "uid": uid("deployments", namespace, app),
+            "creationTimestamp": timestamp(TimeDelta::days(3)),
+            "labels": {"app": app},
+        },
+        "spec": {
+            "replicas": replicas,
+            "selector": {"matchLabels": {"app": app}},
+            "template": {
+                "metadata": {"labels": {"app": app}},
+                "spec": {"containers": [{"name": app, "image": image}]},
+            },
+        },
+        "status": {
+            "replicas": replicas,
+            "updatedReplicas": replicas,
+            "readyReplicas": replicas,
+            "availableReplicas": replicas,
+        },
+    })
+}
+
+fn replicaset(namespace: &str, app: &str, hash: &str, image: &str, replicas: usize) -> Value {
+    let name = format!("{app}-{hash}");
+
+    json!({
+        "apiVersion": "apps/v1",
+        "kind": "ReplicaSet",
+        "metadata": {
+            "name": name,
+            "namespace": namespace,
+            "uid": uid("replicasets", namespace, &name),
+            "creationTimestamp": timestamp(TimeDelta::days(3)),
+            "labels": {"app": app, "pod-template-hash": hash},
+            "ownerReferences": [{
+                "apiVersion": "apps/v1",
+                "kind": "Deployment",
+                "name": app,
+                "uid": uid("deployments", namespace, app),
+                "controller": true,
+                "blockOwnerDeletion": true,
+            }],
+        },
+        "spec": {
+            "replicas": replicas,
+            "selector": {"matchLabels": {"app": app, "pod-template-hash": hash}},
+            "template": {
+                "metadata": {"labels": {"app": app, "pod-template-hash": hash}},
+                "spec": {"containers": [{"name": app, "image": image}]},
+            },
+        },
+        "status": {
+            "replicas": replicas,
+            "readyReplicas": replicas,
+            "availableReplicas": replicas,
+        },
+    })
+}
+
+fn running(pod: &mut Value) {
+    let container = &pod["spec"]["containers"][0];
+    let name = container["name"].clone();
+    let image = container["image"].clone();
+    let restarts = pod["status"]["containerStatuses"][0]["restartCount"]
+        .as_i64()
+        .unwrap_or_default();
+
+    pod["status"] = json!({
+        "phase": "Running",
+        "qosClass": "Burstable",
+        "startTime": timestamp(TimeDelta::zero()),
+        "conditions": [{"type": "Ready", "status": "True"}],
+        "containerStatuses": [{
+            "name": name,
+            "image": image,
+            "imageID": "",
+            "ready": true,
+            "started": true,
+            "restartCount": restarts,
+            "state": {"running": {"startedAt": timestamp(TimeDelta::zero())}},
+        }],
+    });
+}
+
+fn crash(pod: &mut Value) {
+    if pod["status"]["containerStatuses"][0].is_null() {
+        running(pod);
+    }
+
+    let status = &mut pod["status"]["containerStatuses"][0];
+    let restarts = status["restartCount"].as_i64().unwrap_or_default() + 1;
+
+    status["ready"] = json!(false);
+    status["started"] = json!(false);
+    status["restartCount"] = json!(restarts);
+    status["state"] = json!({
+        "waiting": {
+            "reason": "CrashLoopBackOff",
+            "message": "back-off 5m0s restarting failed container",
+        },
+    });
+    status["lastState"] = json!({
+        "terminated": {
+            "exitCode": 1,
+            "reason": "Error",
+            "startedAt": timestamp(TimeDelta::minutes(1)),
+            "finishedAt": timestamp(TimeDelta::zero()),
+        },
+    });
+
+    pod["status"]["conditions"] = json!([{"type": "Ready", "status": "False"}]);
+}
+
+// Where a request is going, eg. `/apis/apps/v1/namespaces/default/deployments`
+// or `/api/v1/namespaces/default/pods/nginx/log`.
+#[derive(Clone)]
+struct Route {
+    plural: String,
+    namespace: Option<String>,
+    name: Option<String>,
+    subresource: Option<String>,
+}
+
+impl Route {
+    fn parse(path: &str) -> Option<Self> {
+        let parts: Vec<_> = path.trim_matches('/').split('/').collect();
+
+        let rest = match parts.as_slice() {
+            ["api", _, rest @ ..] | ["apis", _, _, rest @ ..] => rest,
+            _ => return None,
+        };
+
+        let (namespace, rest) = match rest {
+            ["namespaces", namespace, rest @ ..] if !rest.is_empty() => {
+                (Some((*namespace).to_string()), rest)
+            }
+            rest => (None, rest),
+        };
+
+        let [plural, rest @ ..] = rest else {
+            return None;
+        };
+
+        Some(Self {
+            plural: (*plural).to_string(),
+            namespace,
+            name: rest.first().map(ToString::to_string),
+            subresource: rest.get(1).map(ToString::to_string),
+        })
+    }
+
+    fn matches(&self, plural: &str, namespace: Option<&String>, name: &str) -> bool {
+        self.plural == plural
+            && (self.namespace.is_none() || self.namespace.as_ref() == namespace)
+            && self.name.as_ref().map_or(true, |n| n == name)
+    }
+
+    fn key(&self) -> Key {
+        (
+            self.plural.clone(),
+            self.namespace.clone(),
+            self.name.clone().unwrap_or_default(),
+        )
+    }
+}
+
+/// A fake cluster with a handful of nodes, deployments and their pods. Pods
+/// come and go and crash every few seconds via [`Cluster::churn`].
+#[derive(Clone)]
+pub struct Cluster {
+    state: Arc<Mutex<State>>,
+    changes: broadcast::Sender<Change>,
+}
+
+impl Default for Cluster {
+    fn default() -> Self {
+        let mut state = State {
+            version: 0,
+            seed: 0x2545_f491_4f6c_dd1d,
+            objects: BTreeMap::new(),
+        };
+
+        state.populate();
+
+        let (changes, _) = broadcast::channel(1024);
+
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            changes,
+        }
+    }
+}
+
+impl Cluster {
+    pub fn client(&self) -> kube::Client {
+        let cluster = self.clone();
+
+        kube::Client::new(
+            tower::service_fn(move |req: Request<Body>| {
+                let response = cluster.handle(&req);
+
+                async move { Ok::<_, Infallible>(response) }
+            }),
+            "default",
+        )
+    }
+
+    pub async fn churn(self) {
+        loop {
+            tokio::time::sleep(CHURN).await;
+
+            let changes = match self.state.lock() {
+                Ok(mut state) => state.churn(),
+                Err(_) => return,
+            };
+
+            for change in changes {
+                // Nobody might be watching, that's fine.
+                let _ = self.changes.send(change);
+            }
+        }
+    }
+
+    fn handle(&self, req: &Request<Body>) -> Response<ResponseBody> {
+        tracing::debug!(method = %req.method(), uri = %req.uri(), "mock request");
+
+        let Some(mut route) = Route::parse(req.uri().path()) else {
+            return status(StatusCode::NOT_FOUND, "NotFound", "not found");
+        };
+
+        let query: Vec<_> = req
+            .uri()
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .collect();
+
+        let watch = query
+            .iter()
+            .any(|(k, v)| *k == "watch" && (*v == "true" || *v == "1"));
+
+        // Watching a single object is a list filtered to a specific name.
+        if let Some((_, selector)) = query.iter().find(|(k, _)| *k == "fieldSelector") {
+            if let Some(name) = selector.replace("%3D", "=").strip_prefix("metadata.name=") {
+                route.name = Some(name.to_string());
+                return match (req.method(), watch) {
+                    (&Method::GET, true) => self.watch(route),
+                    (&Method::GET, false) => self.list(&route),
+                    _ => forbidden(),
+                };
+            }
+        }
+
+        let subresource = route.subresource.clone();
+
+        match (req.method(), route.name.is_some(), subresource.as_deref()) {
+            (&Method::GET, false, _) if watch => self.watch(route),
+            (&Method::GET, false, _) => self.list(&route),
+            (&Method::GET, true, None) => self.get(&route),
+            (&Method::GET, true, Some("log")) => logs(route.name.as_deref().unwrap_or_default()),
+            (&Method::DELETE, true, None) => self.delete(&route),
+            _ => forbidden(),
+        }
+    }
+
+    fn list(&self, route: &Route) -> Response<ResponseBody> {
+        let Ok(state) = self.state.lock() else {
+            return status(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "InternalError",
+                "poisoned",
+            );
+        };
+
+        let items: Vec<_> = state
+            .objects
+            .iter()
+            .filter(|((plural, namespace, name), _)| {
+                route.matches(plural, namespace.as_ref(), name)
+            })
+            .map(|(_, object)| object.clone())
+            .collect();
+
+        respond(
+            StatusCode::OK,
+            &json!({
+                "apiVersion": "v1",
+                "kind": "List",
+                "metadata": {"resourceVersion": state.version.to_string()},
+                "items": items,
+            }),
+        )
+    }
+
+    fn get(&self, route: &Route) -> Response<ResponseBody> {
+        let object = self
+            .state
+            .lock()
+            .ok()
+            .and_then(|state| state.objects.get(&route.key()).cloned());
+
+        match object {
+            Some(object) => respond(StatusCode::OK, &object),
+            None => status(StatusCode::NOT_FOUND, "NotFound", "not found"),
+        }
+    }
+
+    fn delete(&self, route: &Route) -> Response<ResponseBody> {
+        let change = self
+            .state
+            .lock()
+            .ok()
+            .and_then(|mut state| state.remove(&route.key()));
+
+        let Some(change) = change else {
+            return status(StatusCode::NOT_FOUND, "NotFound", "not found");
+        };
+
+        let response = respond(StatusCode::OK, &change.object);
+        let _ = self.changes.send(change);
+
+        response
+    }
+
+    // Watches never send the initial state, clients list first and then watch
+    // from the version they got back.
+    fn watch(&self, route: Route) -> Response<ResponseBody> {
+        let events = stream::unfold(
+            (self.changes.subscribe(), route),
+            |(mut rx, route)| async move {
+                loop {
+                    match rx.recv().await {
+                        Ok(change) => {
+                            let name = change.object["metadata"]["name"]
+                                .as_str()
+                                .unwrap_or_default();
+
+                            if !route.matches(&change.plural, change.namespace.as_ref(), name) {
+                                continue;
+                            }
+
+                            let mut line = serde_json::to_vec(&json!({
+                                "type": change.kind,
+                                "object": change.object,
+                            }))
+                            .unwrap_or_default();
+                            line.push(b'\n');
+
+                            return Some((
+                                Ok::<_, Infallible>(Frame::data(Bytes::from(line))),
+                                (rx, route),
+                            ));
+                        }
+                        Err(RecvError::Lagged(_)) => {}
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            },
+        );
+
+        let mut response = Response::new(StreamBody::new(events).boxed_unsync());
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        response
+    }
+}
+
+fn logs(name: &str) -> Response<ResponseBody> {
+    let lines: String = (0..50)
+        .rev()
+        .map(|i| {
+            let ts = (Utc::now() - TimeDelta::seconds(i * 30))
+                .to_rfc3339_opts(SecondsFormat::Nanos, true);
+
+            format!("{ts} {name} GET /healthz 200 {}ms\n", i % 7 + 1)
+        })
+        .collect();
+
+    Response::new(Full::new(Bytes::from(lines)).boxed_unsync())
+}
+
+fn respond(code: StatusCode, body: &Value) -> Response<ResponseBody> {
+    let body = serde_json::to_vec(body).unwrap_or_default();
+
+    let mut response = Response::new(Full::new(Bytes::from(body)).boxed_unsync());
+    *response.status_mut() = code;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+    response
+}
+
+fn status(code: StatusCode, reason: &str, message: &str) -> Response<ResponseBody> {
+    respond(
+        code,
+        &json!({
+            "apiVersion": "v1",
+            "kind": "Status",
+            "status": "Failure",
+            "message": message,
+            "reason": reason,
+            "code": code.as_u16(),
+        }),
+    )
+}
+
+fn forbidden() -> Response<ResponseBody> {
+    status(
+        StatusCode::FORBIDDEN,
+        "Forbidden",
+        "the mock cluster is read-only",
+    )
+}
@@ -0,0 +1,56 @@
+use std::path::PathBuf;
+
+use cata::{Command, Container};
+use clap::Parser;
+use eyre::Result;
+
+use crate::resources::snapshot::Snapshot;
+
+#[derive(Parser, Container)]
+/// Render a snapshot taken with `ctrl-\`, as it was on screen.
+pub struct Replay {
+    /// Snapshot file. For snapshots saved as a secret, fetch it with `kubectl
+    /// get secret <name> -o jsonpath='{.data.snapshot\.yaml}' | base64 -d`.
+    path: PathBuf,
+
+    /// Also print the widget tree and the events leading up to the snapshot.
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+#[async_trait::async_trait]
+impl Command for Replay {
+    async fn run(&self) -> Result<()> {
+        let snapshot = Snapshot::read(&self.path)?;
+
+        println!(
+            "kty {} at {}, {}x{}: {}",
+            snapshot.version, snapshot.taken, snapshot.width, snapshot.height, snapshot.title
+        );
+
+        let width = usize::from(snapshot.width);
+
+        println!("┌{}┐", "─".repeat(width));
+        for line in &snapshot.frame {
+            println!(
+                "│{line}{}│",
+                " ".repeat(width.saturating_sub(line.chars().count()))
+            );
+        }
+        println!("└{}┘", "─".repeat(width));
+
+        if self.verbose {
+            println!("\nwidgets:");
+            for widget in &snapshot.widgets {
+                println!("  {widget}");
+            }
+
+            println!("\nevents:");
+            for event in &snapshot.events {
+                println!("  {event}");
+            }
+        }
+
+        Ok(())
+    }
+}
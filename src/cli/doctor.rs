@@ -0,0 +1,147 @@
+use cata::{Command, Container};
+use clap::Parser;
+use eyre::{eyre, Result};
+use k8s_openapi::{
+    api::authorization::v1::{
+        ResourceAttributes, SelfSubjectAccessReview, SelfSubjectAccessReviewSpec,
+    },
+    apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition,
+};
+use kube::{
+    api::{Api, PostParams},
+    CustomResourceExt,
+};
+
+use crate::{
+    identity::Key,
+    openid::{self, Fetch},
+};
+
+/// Check the environment the server will run in. This verifies that the kube
+/// API is reachable and reports which functionality is available with the
+/// current permissions. Run this with the same credentials as the server.
+#[derive(Parser, Container)]
+pub struct Doctor {
+    /// URL to the `OpenID` configuration the server will use. When set, the
+    /// provider is checked as well.
+    #[clap(long, env = "KTY_OID_CONFIG_URL")]
+    openid_configuration: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl Command for Doctor {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(err, skip(self), fields(activity = "doctor"))]
+    async fn run(&self) -> Result<()> {
+        report(
+            "offline mode",
+            &Ok(if openid::offline() {
+                "on, telemetry is disabled and only local hosts are contacted".to_string()
+            } else {
+                "off".to_string()
+            }),
+        );
+
+        if let Some(url) = &self.openid_configuration {
+            report("openid provider reachable", &provider(url).await);
+        }
+
+        let client = kube::Client::try_default().await?;
+        let namespace = client.default_namespace().to_string();
+
+        let version = client.apiserver_version().await?;
+        report(
+            "kube API reachable",
+            &Ok(format!("{}.{}", version.major, version.minor)),
+        );
+
+        let impersonate = allowed(&client, "impersonate", "", "users", None).await;
+        report("impersonate users", &impersonate);
+
+        report(
+            "key CRD installed",
+            &Api::<CustomResourceDefinition>::all(client.clone())
+                .get_opt(Key::crd_name())
+                .await
+                .map_err(Into::into)
+                .and_then(|crd| {
+                    crd.map(|_| "yes".to_string()).ok_or_else(|| {
+                        eyre!("missing, install with `kty resources crd | kubectl apply -f -`")
+                    })
+                }),
+        );
+
+        report(
+            format!("read keys in {namespace}").as_str(),
+            &Key::available(client.clone())
+                .await
+                .map(|()| "yes".to_string()),
+        );
+
+        for verb in ["create", "patch"] {
+            report(
+                format!("{verb} keys in {namespace}").as_str(),
+                &allowed(&client, verb, "kty.dev", "keys", Some(&namespace)).await,
+            );
+        }
+
+        if impersonate.is_err() {
+            return Err(eyre!("the server will be unable to impersonate users"));
+        }
+
+        Ok(())
+    }
+}
+
+async fn provider(url: &str) -> Result<String> {
+    let config = openid::Config::fetch(url).await?;
+    config.check()?;
+
+    let jwks = config.jwks().await?;
+
+    Ok(format!("{} signing keys", jwks.keys.len()))
+}
+
+fn report(check: &str, result: &Result<String>) {
+    match result {
+        Ok(detail) => println!("[ok]   {check}: {detail}"),
+        Err(err) => println!("[fail] {check}: {err}"),
+    }
+}
+
+async fn allowed(
+    client: &kube::Client,
+    verb: &str,
+    group: &str,
+    resource: &str,
+    namespace: Option<&String>,
+) -> Result<String> {
+    let review = Api::<SelfSubjectAccessReview>::all(client.clone())
+        .create(
+            &PostParams::default(),
+            &SelfSubjectAccessReview {
+                spec: SelfSubjectAccessReviewSpec {
+                    resource_attributes: Some(ResourceAttributes {
+                        group: Some(group.to_string()),
+                        resource: Some(resource.to_string()),
+                        namespace: namespace.cloned(),
+                        verb: Some(verb.to_string()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        )
+        .await?;
+
+    let Some(status) = review.status else {
+        return Err(eyre!("no status found"));
+    };
+
+    if !status.allowed {
+        return Err(eyre!("denied {}", status.reason.unwrap_or_default()));
+    }
+
+    Ok("yes".to_string())
+}
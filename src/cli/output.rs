@@ -0,0 +1,80 @@
+use clap::ValueEnum;
+use eyre::Result;
+use serde::Serialize;
+
+// This would be `cata::output`, except ratatui and tabled disagree on
+// `unicode-width` versions. The CLI can be split into its own crate at that
+// point and remove the direct ratatui dependency.
+
+/// How a command prints its results. `json` is always an array and `yaml` is
+/// one document per item, so scripts get the same shape no matter how many
+/// items there are.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Table,
+    /// A table with every column.
+    Wide,
+    Json,
+    Yaml,
+}
+
+/// Something that can be printed as a row in a table. Wide tables only ever
+/// add columns, to the right of the default ones.
+pub trait Tabular: Serialize {
+    fn header(wide: bool) -> Vec<&'static str>;
+    fn row(&self, wide: bool) -> Vec<String>;
+}
+
+pub fn print<T: Tabular>(format: Format, items: &[T]) -> Result<()> {
+    match format {
+        Format::Json => println!("{}", serde_json::to_string_pretty(items)?),
+        Format::Yaml => {
+            let mut serializer = serde_yaml::Serializer::new(std::io::stdout());
+
+            for item in items {
+                item.serialize(&mut serializer)?;
+            }
+        }
+        Format::Table | Format::Wide => {
+            print!("{}", table(format == Format::Wide, items));
+        }
+    }
+
+    Ok(())
+}
+
+// Columns are as wide as their widest value, with two spaces between them. The
+// last column isn't padded.
+fn table<T: Tabular>(wide: bool, items: &[T]) -> String {
+    let header = T::header(wide)
+        .into_iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    let rows: Vec<_> = std::iter::once(header)
+        .chain(items.iter().map(|item| item.row(wide)))
+        .collect();
+
+    let widths = rows.iter().fold(Vec::new(), |mut widths, row| {
+        widths.resize(widths.len().max(row.len()), 0);
+
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+
+        widths
+    });
+
+    rows.iter()
+        .map(|row| {
+            let line = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect::<Vec<_>>()
+                .join("  ");
+
+            format!("{}\n", line.trim_end())
+        })
+        .collect()
+}
@@ -1,14 +1,23 @@
+use std::path::PathBuf;
+
 use cata::{Command, Container};
 use clap::{Parser, Subcommand};
-use eyre::Result;
+use eyre::{Result, WrapErr};
+use k8s_openapi::{
+    api::core::v1::Namespace,
+    apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition,
+};
 use kube::{
-    api::{DeleteParams, Patch, PatchParams, ResourceExt},
+    api::{ApiResource, DeleteParams, DynamicObject, Patch, PatchParams, ResourceExt},
     Client,
 };
 use serde::Serialize;
 
-use super::namespace;
-use crate::resources::{install, DynamicClient, GetGvk, MANAGER};
+use super::{
+    namespace,
+    output::{self, Format, Tabular},
+};
+use crate::resources::{install, migrate, DynamicClient, GetGvk, MANAGER};
 
 const VERSION: Option<&'static str> = option_env!("CARGO_PKG_VERSION");
 
@@ -28,24 +37,144 @@ enum ResourcesCmd {
     Crd(Crd),
     Delete(Delete),
     Install(Install),
+    Manifests(Manifests),
+    Status(Status),
 }
 
 impl Command for Resources {}
 
+impl Tabular for CustomResourceDefinition {
+    fn header(wide: bool) -> Vec<&'static str> {
+        let mut header = vec!["NAME", "KIND"];
+
+        if wide {
+            header.push("VERSIONS");
+        }
+
+        header
+    }
+
+    fn row(&self, wide: bool) -> Vec<String> {
+        let mut row = vec![self.name_any(), self.spec.names.kind.clone()];
+
+        if wide {
+            row.push(
+                self.spec
+                    .versions
+                    .iter()
+                    .map(|v| v.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+        }
+
+        row
+    }
+}
+
+// Manifests without types still get a row, with the kind left empty.
+impl Tabular for DynamicObject {
+    fn header(wide: bool) -> Vec<&'static str> {
+        let mut header = vec!["KIND", "NAMESPACE", "NAME"];
+
+        if wide {
+            header.push("API VERSION");
+        }
+
+        header
+    }
+
+    fn row(&self, wide: bool) -> Vec<String> {
+        let types = self.types.clone().unwrap_or_default();
+
+        let mut row = vec![
+            types.kind,
+            self.namespace().unwrap_or_default(),
+            self.name_any(),
+        ];
+
+        if wide {
+            row.push(types.api_version);
+        }
+
+        row
+    }
+}
+
+impl Tabular for migrate::Status {
+    fn header(wide: bool) -> Vec<&'static str> {
+        let mut header = vec!["NAME", "STATE", "INSTALLED"];
+
+        if wide {
+            header.extend(["STORAGE", "STORED"]);
+        }
+
+        header
+    }
+
+    fn row(&self, wide: bool) -> Vec<String> {
+        let mut row = vec![
+            self.name.clone(),
+            self.state.to_string(),
+            self.installed.clone().unwrap_or_default(),
+        ];
+
+        if wide {
+            row.extend([self.storage.clone(), self.stored.join(",")]);
+        }
+
+        row
+    }
+}
+
+#[derive(Serialize)]
+struct Changed {
+    kind: String,
+    namespace: Option<String>,
+    name: String,
+    action: &'static str,
+}
+
+impl Changed {
+    fn new(resource: &DynamicObject, action: &'static str) -> Result<Self> {
+        Ok(Self {
+            kind: resource.gvk()?.kind,
+            namespace: resource.namespace(),
+            name: resource.name_any(),
+            action,
+        })
+    }
+}
+
+impl Tabular for Changed {
+    fn header(_: bool) -> Vec<&'static str> {
+        vec!["KIND", "NAMESPACE", "NAME", "ACTION"]
+    }
+
+    fn row(&self, _: bool) -> Vec<String> {
+        vec![
+            self.kind.clone(),
+            self.namespace.clone().unwrap_or_default(),
+            self.name.clone(),
+            self.action.to_string(),
+        ]
+    }
+}
+
+/// Print the CRDs that kty uses, as YAML by default so that it can be piped
+/// into `kubectl apply -f -`.
 #[derive(Parser, Container)]
-pub struct Crd {}
+pub struct Crd {
+    #[arg(short, long, value_enum, default_value_t = Format::Yaml)]
+    output: Format,
+}
 
 #[async_trait::async_trait]
 impl Command for Crd {
     #[allow(clippy::blocks_in_conditions)]
     #[tracing::instrument(err, skip(self), fields(activity = "resources.crd"))]
     async fn run(&self) -> Result<()> {
-        let mut serializer = serde_yaml::Serializer::new(std::io::stdout());
-        for resource in crate::resources::all() {
-            resource.serialize(&mut serializer)?;
-        }
-
-        Ok(())
+        output::print(self.output, &crate::resources::all())
     }
 }
 
@@ -53,6 +182,9 @@ impl Command for Crd {
 pub struct Delete {
     #[arg(from_global)]
     namespace: Option<String>,
+
+    #[arg(short, long, value_enum, default_value_t)]
+    output: Format,
 }
 
 #[async_trait::async_trait]
@@ -66,6 +198,8 @@ impl Command for Delete {
 
         let resources = install::add_patches(namespace.as_str(), "", install::list()?)?;
 
+        let mut out = Vec::new();
+
         for resource in resources {
             let gvk = resource.gvk()?;
 
@@ -76,9 +210,11 @@ impl Command for Delete {
                 .await?
                 .delete(resource.name_any().as_str(), &DeleteParams::default())
                 .await?;
+
+            out.push(Changed::new(&resource, "deleted")?);
         }
 
-        Ok(())
+        output::print(self.output, &out)
     }
 }
 
@@ -93,6 +229,11 @@ pub struct Install {
 
     #[arg(long, default_value_t = format!("ghcr.io/grampelberg/kty:{}", VERSION.unwrap_or("latest")))]
     image: String,
+
+    /// Defaults to `yaml` with `--dry-run`, so that the manifests can be
+    /// applied elsewhere, and `table` otherwise.
+    #[arg(short, long, value_enum)]
+    output: Option<Format>,
 }
 
 #[async_trait::async_trait]
@@ -106,17 +247,13 @@ impl Command for Install {
             install::add_patches(namespace.as_str(), self.image.as_str(), install::list()?)?;
 
         if self.dry_run {
-            let mut serializer = serde_yaml::Serializer::new(std::io::stdout());
-
-            for resource in resources {
-                resource.serialize(&mut serializer)?;
-            }
-
-            return Ok(());
+            return output::print(self.output.unwrap_or(Format::Yaml), &resources);
         }
 
         let client = Client::try_default().await?;
 
+        let mut out = Vec::new();
+
         for resource in resources {
             let gvk = resource.gvk()?;
 
@@ -131,8 +268,106 @@ impl Command for Install {
                     &Patch::Apply(&resource),
                 )
                 .await?;
+
+            out.push(Changed::new(&resource, "applied")?);
+        }
+
+        output::print(self.output.unwrap_or_default(), &out)
+    }
+}
+
+/// Render everything `kty resources install` would apply, without talking to
+/// the cluster, so that it can be checked in or applied some other way. A new
+/// host key is generated every time.
+#[derive(Parser, Container)]
+pub struct Manifests {
+    /// Namespace for the server and everything it needs, `kty` if not set.
+    #[arg(from_global)]
+    namespace: Option<String>,
+
+    #[arg(long, default_value_t = format!("ghcr.io/grampelberg/kty:{}", VERSION.unwrap_or("latest")))]
+    image: String,
+
+    /// Include the namespace itself, for when nothing else creates it.
+    #[arg(long)]
+    create_namespace: bool,
+
+    /// Write one file per resource into this directory, named
+    /// `<kind>-<name>.yaml`, instead of printing them.
+    #[arg(short, long)]
+    dir: Option<PathBuf>,
+
+    /// Only `yaml` and `json` make sense here, the default is `yaml` so that
+    /// the output can be piped into `kubectl apply -f -`.
+    #[arg(short, long, value_enum, default_value_t = Format::Yaml)]
+    output: Format,
+}
+
+#[async_trait::async_trait]
+impl Command for Manifests {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(err, skip(self), fields(activity = "resources.manifests"))]
+    async fn run(&self) -> Result<()> {
+        let namespace = self.namespace.as_deref().unwrap_or("kty");
+
+        let mut resources = Vec::new();
+
+        if self.create_namespace {
+            resources.push(DynamicObject::new(
+                namespace,
+                &ApiResource::erase::<Namespace>(&()),
+            ));
+        }
+
+        resources.extend(install::add_patches(
+            namespace,
+            self.image.as_str(),
+            install::list()?,
+        )?);
+
+        let Some(dir) = &self.dir else {
+            return output::print(self.output, &resources);
+        };
+
+        std::fs::create_dir_all(dir)
+            .wrap_err_with(|| format!("unable to create {}", dir.display()))?;
+
+        for resource in &resources {
+            let path = dir.join(format!(
+                "{}-{}.yaml",
+                resource.gvk()?.kind.to_lowercase(),
+                resource.name_any()
+            ));
+
+            std::fs::write(&path, serde_yaml::to_string(resource)?)
+                .wrap_err_with(|| format!("unable to write {}", path.display()))?;
+
+            tracing::info!("wrote: {}", path.display());
         }
 
         Ok(())
     }
 }
+
+/// Compare the CRDs in the cluster with the ones this version expects. The
+/// server brings them up to date on startup, unless it was started with
+/// `--no-create`.
+#[derive(Parser, Container)]
+pub struct Status {
+    #[arg(short, long, value_enum, default_value_t)]
+    output: Format,
+}
+
+#[async_trait::async_trait]
+impl Command for Status {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(err, skip(self), fields(activity = "resources.status"))]
+    async fn run(&self) -> Result<()> {
+        let client = Client::try_default().await?;
+
+        output::print(
+            self.output,
+            &migrate::status(&kube::Api::all(client)).await?,
+        )
+    }
+}
@@ -1,33 +1,65 @@
-use std::{net::IpAddr, path::Path, sync::Arc};
+use std::{
+    future::ready,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use cata::{Command, Container};
 use clap::Parser;
-use eyre::{eyre, Result};
-use kube::{api::Api, runtime::events::Reporter};
-use pkcs8::{Document, PrivateKeyInfo};
+use eyre::{eyre, Result, WrapErr};
+use futures::StreamExt;
+use kube::{
+    api::Api,
+    runtime::{self, events::Reporter, reflector, watcher, WatchStreamExt},
+};
 use russh::{server::Config, MethodSet};
-use russh_keys::key::KeyPair;
-use ssh_key::PrivateKey;
-use strum::VariantArray;
-use warp::Filter;
+use ssh_key::{rand_core::OsRng, Algorithm, PrivateKey};
+use tokio::net::TcpListener;
+use warp::{Filter, Rejection, Reply};
 
+#[cfg(feature = "plugins")]
+use crate::plugin;
 use crate::{
-    health,
+    admin, dashboard, health,
+    identity::{certificate::Authorities, grant, otp::BreakGlass, Key},
     openid::{self, Fetch},
-    resources,
-    ssh::{self, ControllerBuilder, CurrentPodBuilder, Features},
+    resources::{
+        migrate,
+        node::shell,
+        policy::{Policies, Verb},
+        settings::KtySettings,
+    },
+    ssh::{
+        self,
+        export::Sink,
+        launch::RunAs,
+        leader::{Election, Leader},
+        registry::Registry,
+        Controller, ControllerBuilder, CurrentPodBuilder, Features, Forwarding,
+    },
 };
 
 static CLIENT_ID: &str = "y2KACghcNrpTlB1Q10h3MYwYYmiMShPD";
 static OID_CONFIG_URL: &str = "https://kty.us.auth0.com/.well-known/openid-configuration";
 
 static CONTROLLER_NAME: &str = "ssh.kty.dev";
+static LEASE_NAME: &str = "kty-server";
+// How often a provider that couldn't be used on startup is retried, break-glass
+// is offered until then.
+static PROVIDER_RETRY: std::time::Duration = std::time::Duration::from_secs(30);
 
 #[derive(Parser, Container)]
 pub struct Serve {
-    // TODO(thomas): fetch these from the CRD
+    /// Disconnect sessions that haven't had any input for this long. Users are
+    /// warned a minute beforehand. `AccessPolicy` can change this per user.
     #[clap(long, default_value = "1hr")]
     inactivity_timeout: humantime::Duration,
+    /// Keep dashboards running for this long after their connection drops.
+    /// Reconnecting as the same user picks the dashboard back up, with the same
+    /// tab and selection. Set to `0s` to exit along with the connection.
+    #[clap(long, default_value = "5m")]
+    detach_timeout: humantime::Duration,
     /// Client ID for the `OpenID` provider that will be used.
     #[clap(long, default_value = CLIENT_ID, env = "KTY_CLIENT_ID")]
     client_id: String,
@@ -43,6 +75,11 @@ pub struct Serve {
     #[clap(long, default_value = "127.0.0.1")]
     address: String,
 
+    /// Name of the cluster, shown in the title of users' terminals so that
+    /// sessions to different clusters can be told apart.
+    #[clap(long, env = "KTY_CLUSTER_NAME")]
+    cluster_name: Option<String>,
+
     /// Port to listen on for SSH connections.
     #[clap(long, default_value = "2222")]
     ssh_port: u16,
@@ -50,27 +87,149 @@ pub struct Serve {
     #[clap(long, default_value = "8080")]
     health_port: u16,
 
+    /// Enable the admin API on the health port. Requests to `/admin` must use
+    /// this as a bearer token. The admin API is disabled when unset.
+    #[clap(long, env = "KTY_ADMIN_TOKEN", hide_env_values = true)]
+    admin_token: Option<String>,
+
+    /// Require this as a bearer token for `/metrics`. Health checks don't need
+    /// it so that probes keep working.
+    #[clap(long, env = "KTY_METRICS_TOKEN", hide_env_values = true)]
+    metrics_token: Option<String>,
+
+    /// Directory with `tls.crt` and `tls.key` to serve the health port over
+    /// TLS, normally a mounted `kubernetes.io/tls` secret such as one issued by
+    /// cert-manager. Certificates are read on startup, restart to pick up a
+    /// renewed one.
+    #[clap(long, env = "KTY_HEALTH_TLS")]
+    health_tls: Option<PathBuf>,
+
+    /// CA bundle that clients of the health port must present a certificate
+    /// from (mTLS). Requires `--health-tls`.
+    #[clap(long, env = "KTY_HEALTH_CLIENT_CA", requires = "health_tls")]
+    health_client_ca: Option<PathBuf>,
+
     /// Path to a private Key to use. Must be in PEM format, but can either be
     /// openssl or openssh. A key is generated by default if unset. Just like
     /// any other SSH server, it is important to maintain the key between
     /// invocations so that your users have the same host key.
     #[clap(long, value_parser = load_key, default_value = "")]
-    key: KeyPair,
+    key: PrivateKey,
 
-    /// Do not create (or update) resources on startup. This allows for reduced
-    /// permissions but requires management of the CRDs out of band.
+    /// Do not create, update or migrate resources on startup. This allows for
+    /// reduced permissions but requires management of the CRDs out of band. If
+    /// the CRDs are unavailable, the server still runs but keys will not be
+    /// stored. Use `kty resources status` to see what needs to be done.
     #[clap(long)]
     no_create: bool,
 
+    /// Coordinate with other replicas via. a `Lease` named `kty-server` so that
+    /// only one of them creates resources and removes expired grants. Every
+    /// replica accepts sessions regardless. Enable this when running more than
+    /// one replica.
+    #[clap(long, env = "KTY_LEADER_ELECTION")]
+    leader_election: bool,
+
+    /// Delete grants from `kty users grant --expires` once they have expired.
+    /// This requires permission to delete any `ClusterRoleBinding`, without it
+    /// expired grants are left for an administrator but can't be used to log
+    /// in.
+    #[clap(long, env = "KTY_REAP_GRANTS")]
+    reap_grants: bool,
+
+    /// Frames per second for dashboards. Lower this to reduce CPU on servers
+    /// with many sessions. Users can ask for a different rate with `ssh -o
+    /// SetEnv=KTY_FPS=<fps>`, up to 30.
+    #[clap(long, env = "KTY_FPS", default_value_t = dashboard::FPS)]
+    fps: u16,
+
+    /// Also write the audit log as JSON lines, for collection by a SIEM. Either
+    /// a path to append to or `syslog:<socket>`, such as `syslog:/dev/log`.
+    #[clap(long, env = "KTY_AUDIT_LOG")]
+    audit_log: Option<String>,
+
+    /// Also keep what shells in containers write to the terminal in the audit
+    /// log, up to 1MiB per shell. This includes anything typed that was echoed
+    /// back, such as passwords. Only the command line, exit code and byte
+    /// counts are audited by default.
+    #[clap(long, env = "KTY_EXEC_TRANSCRIPTS")]
+    exec_transcripts: bool,
+
+    /// Directory with plugins, `.wasm` modules that each add a tab to every
+    /// dashboard. See the documentation for what they need to export.
+    #[cfg(feature = "plugins")]
+    #[clap(long, env = "KTY_PLUGINS")]
+    plugins: Option<PathBuf>,
+
+    /// Programs that `KtySettings.commands` can run on the server, such as
+    /// `stern`, by name or path. Commands are disabled unless at least one has
+    /// been allowed. Their requests impersonate the user, see
+    /// `--command-user` for who they run as on the server.
+    #[clap(
+        long = "allow-command",
+        env = "KTY_ALLOWED_COMMANDS",
+        value_delimiter = ','
+    )]
+    allowed_commands: Vec<String>,
+
+    /// Run commands as this OS user, `uid` or `uid:gid`, instead of the
+    /// server's. Otherwise, commands can read the server's service account
+    /// token. The server needs to be able to switch users, such as by running
+    /// as root, and the user must not be able to read the token.
+    #[clap(long, env = "KTY_COMMAND_USER")]
+    command_user: Option<RunAs>,
+
+    /// Turn these off for every session, whatever RBAC or an `AccessPolicy`
+    /// allows. Users are told that an administrator disabled them. SSH features
+    /// that need a disabled verb are turned off too and requests through the
+    /// API proxy are rejected by method and path.
+    #[clap(long, value_enum, env = "KTY_DISABLE", value_delimiter = ',')]
+    disable: Vec<Verb>,
+
+    /// Directory with a break-glass TOTP secret, normally a mounted `Secret`.
+    /// When the `OpenID` provider can't be used, users are asked for a code
+    /// instead and authenticate as the user in the directory. Every attempt is
+    /// audited. Disabled when unset.
+    #[clap(long, env = "KTY_BREAK_GLASS")]
+    break_glass: Option<PathBuf>,
+
+    /// File of certificate authorities, one public key per line like
+    /// `TrustedUserCAKeys` for `sshd`. User certificates they sign can be used
+    /// to log in without registering a key. Principals are the Kubernetes user,
+    /// those starting with `group:` are groups. Certificates are rejected when
+    /// unset.
+    #[clap(long, env = "KTY_USER_CA")]
+    user_ca: Option<PathBuf>,
+
     /// Features to enable for the server. See documentation for more details
-    /// about what the features do.
+    /// about what the features do. Everything except `api-proxy` is enabled by
+    /// default, it has to be asked for.
     #[clap(
         long,
         value_enum,
-        default_values_t = Features::VARIANTS,
+        value_delimiter = ',',
+        default_values_t = [
+            Features::Pty,
+            Features::IngressTunnel,
+            Features::EgressTunnel,
+            Features::Sftp,
+        ],
     )]
     features: Vec<Features>,
 
+    /// What to do when a client asks for agent or X11 forwarding. kty never
+    /// forwards either, `deny` refuses the request and `disconnect` also ends
+    /// the session. Attempts are logged with the user that made them.
+    #[clap(long, value_enum, default_value_t = Forwarding::Deny, env = "KTY_FORWARDING")]
+    forwarding: Forwarding,
+
+    /// Expect every connection to start with a PROXY protocol (v1 or v2)
+    /// header, as sent by load balancers that support it. The client address
+    /// from the header is what shows up in logs, the audit trail and the
+    /// sessions API. Connections without a valid header are closed.
+    #[clap(long, env = "KTY_PROXY_PROTOCOL")]
+    proxy_protocol: bool,
+
     /// Name of where this is running. Must be set if `egress-tunnel` is
     /// enabled. Used as part of `egress-tunnel` for the `ObjectReference`
     /// and `OwnerReference` on created `EndpointSlice`.
@@ -93,25 +252,249 @@ pub struct Serve {
 }
 
 impl Serve {
-    async fn serve_http(&self) -> Result<()> {
-        let metrics = warp::path("metrics").and_then(health::metrics);
+    async fn serve_http(&self, readiness: health::Readiness, ctrl: Arc<Controller>) -> Result<()> {
+        let token = self.metrics_token.as_deref().map(Arc::<str>::from);
+        let status_token = token.clone();
+        let metrics = warp::path("metrics")
+            .and(warp::header::optional::<String>("authorization"))
+            .and(warp::any().map(move || token.clone()))
+            .and_then(health::metrics);
+        let status_readiness = readiness.clone();
+        let status_ctrl = ctrl.clone();
+        let status = warp::path("status")
+            .and(warp::header::optional::<String>("authorization"))
+            .and(warp::any().map(move || status_token.clone()))
+            .and(warp::any().map(move || status_readiness.clone()))
+            .and(warp::any().map(move || status_ctrl.clone()))
+            .and_then(health::status);
+        let healthz = warp::path("healthz").and_then(health::healthz);
+        let readyz = warp::path("readyz")
+            .and(warp::any().map(move || readiness.clone()))
+            .and_then(health::readyz);
 
-        warp::serve(metrics)
-            .run((self.address.parse::<IpAddr>()?, self.health_port))
-            .await;
+        let routes = metrics.or(healthz).or(readyz).or(status);
+        let addr = (self.address.parse::<IpAddr>()?, self.health_port);
 
-        Ok(())
+        if let Some(token) = &self.admin_token {
+            self.listen_http(routes.or(admin::routes(token.clone(), ctrl)), addr)
+                .await
+        } else {
+            self.listen_http(routes, addr).await
+        }
     }
 
-    async fn serve_ssh(&self) -> Result<()> {
-        let cfg = kube::Config::infer().await?;
+    async fn listen_http<F>(&self, routes: F, addr: (IpAddr, u16)) -> Result<()>
+    where
+        F: Filter<Error = Rejection> + Clone + Send + Sync + 'static,
+        F::Extract: Reply,
+    {
+        let Some(dir) = &self.health_tls else {
+            warp::serve(routes).run(addr).await;
+
+            return Ok(());
+        };
+
+        // warp panics when the certificate can't be loaded, fail with something
+        // nicer first.
+        for path in [dir.join("tls.crt"), dir.join("tls.key")]
+            .iter()
+            .chain(self.health_client_ca.iter())
+        {
+            std::fs::metadata(path)
+                .wrap_err_with(|| format!("unable to read {}", path.display()))?;
+        }
+
+        let mut tls = warp::serve(routes)
+            .tls()
+            .cert_path(dir.join("tls.crt"))
+            .key_path(dir.join("tls.key"));
+
+        if let Some(ca) = &self.health_client_ca {
+            tls = tls.client_auth_required_path(ca);
+        }
+
+        tls.run(addr).await;
 
+        Ok(())
+    }
+
+    async fn controller(&self, cfg: kube::Config) -> Result<Controller> {
         let reporter = Reporter {
             controller: CONTROLLER_NAME.into(),
             instance: Some(hostname::get()?.to_string_lossy().into()),
         };
 
-        let ctrl = ControllerBuilder::default()
+        let client = kube::Client::try_from(cfg.clone())?;
+
+        // The first round of the election happens before anything else, so that
+        // whichever replica wins has created resources before it starts.
+        let leader = if self.leader_election {
+            let mut election = Election::new(
+                client.clone(),
+                &cfg.default_namespace,
+                LEASE_NAME,
+                self.pod_name.clone(),
+            );
+
+            if election.step().await && !self.no_create {
+                create(client.clone()).await;
+            }
+
+            let leader = election.leader();
+            let no_create = self.no_create;
+            let client = client.clone();
+            tokio::spawn(election.run(move || {
+                let client = client.clone();
+
+                async move {
+                    if !no_create {
+                        create(client).await;
+                    }
+                }
+            }));
+
+            leader
+        } else {
+            if !self.no_create {
+                create(client.clone()).await;
+            }
+
+            Leader::always()
+        };
+
+        if self.reap_grants {
+            tokio::spawn(grant::reaper(client.clone(), leader.clone()));
+        }
+
+        // Settings are optional, when they can't be watched sessions use the
+        // defaults.
+        let (settings, writer) = reflector::store();
+        tokio::spawn(
+            runtime::watcher(
+                Api::<KtySettings>::all(client.clone()),
+                watcher::Config::default(),
+            )
+            .default_backoff()
+            .reflect(writer)
+            .for_each(|event| {
+                if let Err(err) = event {
+                    tracing::debug!("unable to watch settings: {err}");
+                }
+
+                ready(())
+            }),
+        );
+
+        tokio::spawn(shell::reaper(
+            client.clone(),
+            leader,
+            settings.clone(),
+            cfg.default_namespace.clone(),
+        ));
+
+        // Without policies, everyone sees what RBAC allows them to. Nobody can
+        // log in until they have been listed, see `Policies::resolve`.
+        let policies = Policies::watch(client.clone());
+        policies
+            .ready()
+            .await
+            .wrap_err("unable to list access policies")?;
+
+        let keys = match Key::available(client.clone()).await {
+            Ok(()) => true,
+            Err(err) => {
+                tracing::warn!(
+                    "keys are unavailable, users will login via openid every time: {err}"
+                );
+
+                false
+            }
+        };
+
+        // Keys that already exist keep working when new ones can't be saved.
+        let keys_writable = keys
+            && match Key::writable(client.clone()).await {
+                Ok(true) => true,
+                Ok(false) => {
+                    tracing::warn!("keys can't be saved, users without one will login every time");
+
+                    false
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        "unable to check whether keys can be saved, users without one will login \
+                         every time: {err}"
+                    );
+
+                    false
+                }
+            };
+
+        // Until the first list finishes, keys are fetched from the API server
+        // instead.
+        let key_cache = keys.then(|| {
+            let (store, writer) = reflector::store();
+            tokio::spawn(
+                runtime::watcher(
+                    Api::<Key>::default_namespaced(client),
+                    watcher::Config::default(),
+                )
+                .default_backoff()
+                .reflect(writer)
+                .for_each(|event| {
+                    if let Err(err) = event {
+                        tracing::debug!("unable to watch keys: {err}");
+                    }
+
+                    ready(())
+                }),
+            );
+
+            store
+        });
+
+        let break_glass = self.break_glass.as_ref().map(BreakGlass::new).map(Arc::new);
+        if let Some(break_glass) = &break_glass {
+            break_glass
+                .check()
+                .wrap_err("--break-glass is misconfigured")?;
+
+            tracing::warn!("break-glass access is enabled");
+        }
+
+        let authorities = self
+            .user_ca
+            .as_deref()
+            .map(Authorities::load)
+            .transpose()
+            .wrap_err("--user-ca is misconfigured")?;
+
+        if !self.allowed_commands.is_empty() {
+            match self.command_user.map(RunAs::reads_token) {
+                None => tracing::warn!(
+                    "commands run as the server's user and can read its service account token, \
+                     set --command-user"
+                ),
+                Some(Ok(true)) => tracing::warn!(
+                    "--command-user can read the server's service account token, commands can use \
+                     the server's permissions"
+                ),
+                Some(Ok(false)) => {}
+                Some(Err(err)) => {
+                    tracing::debug!("unable to check the service account token: {err}");
+                }
+            }
+        }
+
+        #[cfg(feature = "plugins")]
+        let plugins = match &self.plugins {
+            Some(dir) => plugin::load(dir).wrap_err("--plugins is misconfigured")?,
+            None => Vec::new(),
+        };
+        #[cfg(not(feature = "plugins"))]
+        let plugins = Vec::new();
+
+        Ok(ControllerBuilder::default()
             .server(
                 CurrentPodBuilder::default()
                     .namespace(cfg.default_namespace.clone())
@@ -122,12 +505,70 @@ impl Serve {
             )
             .config(cfg)
             .reporter(Some(reporter.clone()))
-            .build()?;
+            .keys(keys)
+            .keys_writable(keys_writable)
+            .key_cache(key_cache)
+            .cluster(self.cluster_name.clone())
+            .settings(Some(settings))
+            .policies(Some(policies))
+            .fps(self.fps)
+            .inactivity_timeout(Some(self.inactivity_timeout.into()))
+            .detach_timeout(Some(self.detach_timeout.into()))
+            .break_glass(break_glass)
+            .authorities(authorities)
+            .exec_transcripts(self.exec_transcripts)
+            .plugins(plugins)
+            .allowed_commands(self.allowed_commands.clone())
+            .command_user(self.command_user)
+            .disabled(self.disable.clone())
+            .sessions(Arc::new(match &self.audit_log {
+                Some(target) => Registry::with_export(
+                    target
+                        .parse::<Sink>()
+                        .wrap_err_with(|| format!("unable to open audit log {target}"))?,
+                ),
+                None => Registry::default(),
+            }))
+            .build()?)
+    }
+
+    // With break-glass enabled, the server starts even if the provider can't be
+    // used. It is retried in the background until it can.
+    async fn provider(&self, oid: Option<openid::Config>) -> Result<openid::Shared> {
+        let shared = openid::Shared::default();
+
+        let mut builder = openid::ProviderBuilder::default();
+        builder
+            .claim(self.claim.clone())
+            .client_id(self.client_id.clone());
 
-        if !self.no_create {
-            resources::create(&Api::all(ctrl.client()?), true).await?;
+        match connect(&self.openid_configuration, oid.clone(), builder.clone()).await {
+            Ok(provider) => shared.set(provider),
+            Err(err) if self.break_glass.is_some() => {
+                tracing::warn!(
+                    ?err,
+                    "unable to use the OpenID provider, only break-glass can be used"
+                );
+
+                tokio::spawn(retry(
+                    self.openid_configuration.clone(),
+                    oid,
+                    builder,
+                    shared.clone(),
+                ));
+            }
+            Err(err) => return Err(err),
         }
 
+        Ok(shared)
+    }
+
+    async fn serve_ssh(
+        &self,
+        ctrl: Arc<Controller>,
+        oid: Option<openid::Config>,
+        readiness: health::Readiness,
+    ) -> Result<()> {
         if self.features.contains(&Features::EgressTunnel) && self.pod_uid.is_empty() {
             return Err(eyre!(
                 "--pod-name, --pod-uid and --pod-ip are required when egress-tunnel is enabled"
@@ -135,7 +576,10 @@ impl Serve {
         }
 
         let server_cfg = Config {
-            inactivity_timeout: Some(self.inactivity_timeout.into()),
+            // Sessions are disconnected by `Session` so that users can be warned
+            // first.
+            inactivity_timeout: None,
+            // Certificates are offered via. `publickey` too.
             methods: MethodSet::PUBLICKEY | MethodSet::KEYBOARD_INTERACTIVE,
             // TODO(thomas): how important is this? It has a negative impact on
             // UX because public key will be first, causing users to wait for
@@ -146,22 +590,19 @@ impl Serve {
             ..Default::default()
         };
 
-        let cfg = openid::Config::fetch(&self.openid_configuration).await?;
-        let jwks = cfg.jwks().await?;
+        let provider = self.provider(oid).await?;
+
+        let listener = TcpListener::bind((self.address.clone(), self.ssh_port)).await?;
+        readiness.listening();
 
         ssh::UIServerBuilder::default()
-            .controller(Arc::new(ctrl))
-            .identity_provider(Arc::new(
-                openid::ProviderBuilder::default()
-                    .claim(self.claim.clone())
-                    .client_id(self.client_id.clone())
-                    .config(cfg)
-                    .jwks(jwks)
-                    .build()?,
-            ))
+            .controller(ctrl)
+            .identity_provider(provider)
             .features(self.features.clone())
+            .forwarding(self.forwarding)
+            .proxy_protocol(self.proxy_protocol)
             .build()?
-            .run(server_cfg, (self.address.clone(), self.ssh_port))
+            .run(server_cfg, &listener)
             .await
     }
 }
@@ -171,16 +612,100 @@ impl Command for Serve {
     #[allow(clippy::blocks_in_conditions)]
     #[tracing::instrument(err, skip(self), fields(activity = "serve"))]
     async fn run(&self) -> Result<()> {
+        let cfg = kube::Config::infer().await?;
+        // Everything is checked before starting so that misconfiguration shows up
+        // immediately instead of when the first user logs in.
+        if openid::offline() {
+            openid::check(&self.openid_configuration)
+                .wrap_err("--openid-configuration must be local in offline mode")?;
+        }
+
+        let oid = match openid::Config::fetch(&self.openid_configuration).await {
+            Ok(oid) => Some(oid),
+            Err(err) if self.break_glass.is_some() => {
+                tracing::warn!(
+                    ?err,
+                    "OpenID provider unavailable, only break-glass can be used"
+                );
+
+                None
+            }
+            Err(err) => return Err(err),
+        };
+
+        if let Some(oid) = &oid {
+            oid.check()
+                .wrap_err("the OpenID provider has endpoints that aren't local")?;
+        }
+
+        let readiness = health::Readiness::new(
+            kube::Client::try_from(cfg.clone())?,
+            oid.clone(),
+            self.break_glass.is_some(),
+        );
+        let ctrl = Arc::new(self.controller(cfg).await?);
+
         tokio::select! {
-            result = self.serve_http() => result,
-            result = self.serve_ssh() => result,
+            result = self.serve_http(readiness.clone(), ctrl.clone()) => result,
+            result = self.serve_ssh(ctrl, oid, readiness) => result,
         }
     }
 }
 
-fn load_key(val: &str) -> Result<KeyPair> {
+async fn create(client: kube::Client) {
+    if let Err(err) = migrate::migrate(client).await {
+        tracing::warn!("unable to migrate CRDs, continuing without them: {err:?}");
+    }
+}
+
+// Discovery is only repeated when it failed on startup.
+async fn connect(
+    uri: &str,
+    oid: Option<openid::Config>,
+    mut builder: openid::ProviderBuilder,
+) -> Result<openid::Provider> {
+    let oid = if let Some(oid) = oid {
+        oid
+    } else {
+        let oid = openid::Config::fetch(uri).await?;
+        oid.check()
+            .wrap_err("the OpenID provider has endpoints that aren't local")?;
+
+        oid
+    };
+
+    let jwks = oid.keys().await?;
+
+    Ok(builder.config(oid).jwks(jwks).build()?)
+}
+
+async fn retry(
+    uri: String,
+    oid: Option<openid::Config>,
+    builder: openid::ProviderBuilder,
+    shared: openid::Shared,
+) {
+    let mut interval = tokio::time::interval(PROVIDER_RETRY);
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        match connect(&uri, oid.clone(), builder.clone()).await {
+            Ok(provider) => {
+                tracing::info!("OpenID provider is available, break-glass is no longer offered");
+                shared.set(provider);
+
+                return;
+            }
+            Err(err) => tracing::debug!(?err, "OpenID provider is still unavailable"),
+        }
+    }
+}
+
+fn load_key(val: &str) -> Result<PrivateKey> {
     if val.is_empty() {
-        return Ok(KeyPair::generate_ed25519().expect("key was generated"));
+        return Ok(PrivateKey::random(&mut OsRng, Algorithm::Ed25519).expect("key was generated"));
     }
 
     let pth = Path::new(val);
@@ -189,11 +714,7 @@ fn load_key(val: &str) -> Result<KeyPair> {
         return Err(eyre::eyre!("Key file does not exist: {}", val));
     }
 
-    if let Ok(key) = PrivateKey::read_openssh_file(pth) {
-        return Ok(KeyPair::try_from(&key)?);
-    }
-
-    Ok(KeyPair::try_from(PrivateKeyInfo::try_from(
-        Document::read_pem_file(pth)?.1.as_bytes(),
-    )?)?)
+    // Either OpenSSH or PKCS#8, such as what `genPrivateKey` in the Helm chart
+    // produces.
+    Ok(russh_keys::load_secret_key(pth, None)?)
 }
@@ -0,0 +1,196 @@
+use cata::{Command, Container};
+use chrono::Utc;
+use clap::{Args, Parser, Subcommand};
+use eyre::{eyre, Result};
+use reqwest::{Method, RequestBuilder, StatusCode};
+
+use crate::{admin, resources::age::Age, ssh::registry::SessionInfo};
+
+/// Manage the sessions on a running server via. its admin API, see `kty serve
+/// --admin-token`.
+#[derive(Parser, Container)]
+pub struct Sessions {
+    #[command(subcommand)]
+    command: SessionsCmd,
+}
+
+#[derive(Subcommand, Container)]
+enum SessionsCmd {
+    List(List),
+    Kill(Kill),
+    Usage(Usage),
+}
+
+impl Command for Sessions {}
+
+// Flatten with `#[clap(flatten)]`, `Container` takes any `#[command]` field to
+// be the subcommand.
+#[derive(Args)]
+pub(super) struct Server {
+    /// URL of the server's health port, use `kubectl port-forward` to reach a
+    /// server running in the cluster.
+    #[arg(long, env = "KTY_ADMIN_URL", default_value = "http://localhost:8080")]
+    pub(super) url: String,
+
+    /// Bearer token the server was started with.
+    #[arg(long, env = "KTY_ADMIN_TOKEN", hide_env_values = true)]
+    pub(super) token: String,
+}
+
+impl Server {
+    pub(super) fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        reqwest::Client::new()
+            .request(
+                method,
+                format!("{}/admin/{path}", self.url.trim_end_matches('/')),
+            )
+            .bearer_auth(&self.token)
+    }
+}
+
+/// List the active sessions, oldest first.
+#[derive(Parser, Container)]
+pub struct List {
+    #[clap(flatten)]
+    server: Server,
+}
+
+#[async_trait::async_trait]
+impl Command for List {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(err, skip(self), fields(activity = "sessions.list"))]
+    async fn run(&self) -> Result<()> {
+        let sessions: Vec<SessionInfo> = self
+            .server
+            .request(Method::GET, "sessions")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let now = Utc::now();
+
+        println!(
+            "{:<34}{:<32}{:<24}{:<10}IDLE",
+            "ID", "USER", "ADDRESS", "AGE"
+        );
+
+        for session in sessions {
+            println!(
+                "{:<34}{:<32}{:<24}{:<10}{}",
+                session.id,
+                session.user.unwrap_or_else(|| "-".to_string()),
+                session
+                    .addr
+                    .map_or_else(|| "-".to_string(), |addr| addr.to_string()),
+                (now - session.started).to_age(),
+                (now - session.last_activity).to_age(),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Disconnect a session, the ID is the one shown by `kty sessions list` and
+/// used as `session_id` in the logs.
+#[derive(Parser, Container)]
+pub struct Kill {
+    id: String,
+
+    #[clap(flatten)]
+    server: Server,
+}
+
+#[async_trait::async_trait]
+impl Command for Kill {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(err, skip(self), fields(activity = "sessions.kill"))]
+    async fn run(&self) -> Result<()> {
+        let response = self
+            .server
+            .request(Method::DELETE, &format!("sessions/{}", self.id))
+            .send()
+            .await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(eyre!("no session {} on this server", self.id));
+        }
+
+        response.error_for_status()?;
+
+        println!("terminated {}", self.id);
+
+        Ok(())
+    }
+}
+
+/// Report on how the dashboard has been used since the server started: how long
+/// sessions last, which tabs people spend their time in and what they open.
+/// Nothing in the report identifies a user.
+#[derive(Parser, Container)]
+pub struct Usage {
+    #[clap(flatten)]
+    server: Server,
+}
+
+#[async_trait::async_trait]
+impl Command for Usage {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(err, skip(self), fields(activity = "sessions.usage"))]
+    async fn run(&self) -> Result<()> {
+        let usage: admin::Usage = self
+            .server
+            .request(Method::GET, "usage")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let sessions = &usage.sessions;
+
+        #[allow(clippy::cast_precision_loss)]
+        let average = if sessions.count == 0 {
+            0.0
+        } else {
+            sessions.minutes / sessions.count as f64
+        };
+
+        println!(
+            "{} sessions, {average:.1} minutes on average\n",
+            sessions.count
+        );
+
+        println!("{:<24}SESSIONS", "MINUTES");
+
+        let mut previous = 0;
+        for (le, count) in &sessions.buckets {
+            println!("{:<24}{}", format!("<= {le}"), count - previous);
+            previous = *count;
+        }
+
+        println!("{:<24}{}", "longer", sessions.count - previous);
+
+        println!("\n{:<24}{:<10}MINUTES", "TAB", "VIEWS");
+
+        for tab in &usage.tabs {
+            println!("{:<24}{:<10}{:.1}", tab.tab, tab.views, tab.seconds / 60.0);
+        }
+
+        println!("\n{:<24}COUNT", "OPENED");
+
+        for view in &usage.views {
+            println!("{:<24}{}", view.name, view.count);
+        }
+
+        println!("\n{:<24}RUNS", "BULK OPERATION");
+
+        for operation in &usage.operations {
+            println!("{:<24}{}", operation.name, operation.count);
+        }
+
+        Ok(())
+    }
+}
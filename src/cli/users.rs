@@ -1,19 +1,28 @@
+use std::path::Path;
+
+use base64::prelude::*;
 use cata::{Command, Container};
 use chrono::{DateTime, Utc};
 use clap::{Parser, Subcommand};
 use eyre::{eyre, Result};
 use humantime::Duration;
 use itertools::Itertools;
-use k8s_openapi::api::rbac::v1::{ClusterRoleBinding, RoleRef, Subject};
+use k8s_openapi::api::rbac::v1::ClusterRoleBinding;
 use kube::{
-    api::{Api, ObjectMeta, PostParams},
+    api::{Api, DeleteParams, PostParams},
     ResourceExt,
 };
-use russh_keys::{key::PublicKey, parse_public_key_base64};
+use reqwest::Method;
 use serde::Serialize;
+use ssh_key::PublicKey;
 
+use super::{
+    output::{self, Format, Tabular},
+    sessions::Server,
+};
 use crate::{
-    identity::{key, Identity},
+    admin::{Sent, Terminate},
+    identity::{grant, key, Identity},
     resources::KubeID,
     ssh::{Authenticate, ControllerBuilder},
 };
@@ -29,17 +38,52 @@ enum UsersCmd {
     Check(Check),
     Grant(Grant),
     Key(Key),
+    Rotate(Rotate),
+    Revoke(Revoke),
 }
 
 impl Command for Users {}
 
-/// Check if the user has access to the cluster.
+#[derive(Serialize)]
+struct CheckOutput {
+    id: String,
+    groups: Vec<String>,
+    access: bool,
+}
+
+impl Tabular for CheckOutput {
+    fn header(wide: bool) -> Vec<&'static str> {
+        let mut header = vec!["ID", "ACCESS"];
+
+        if wide {
+            header.push("GROUPS");
+        }
+
+        header
+    }
+
+    fn row(&self, wide: bool) -> Vec<String> {
+        let mut row = vec![self.id.clone(), self.access.to_string()];
+
+        if wide {
+            row.push(self.groups.join(","));
+        }
+
+        row
+    }
+}
+
+/// Check if the user has access to the cluster. Exits with an error when they
+/// don't, after printing the result.
 #[derive(Parser, Container)]
 pub struct Check {
     id: String,
 
     #[arg(long)]
     groups: Vec<String>,
+
+    #[arg(short, long, value_enum, default_value_t)]
+    output: Format,
 }
 
 #[async_trait::async_trait]
@@ -53,9 +97,18 @@ impl Command for Check {
             .config(kube::Config::infer().await?)
             .build()?;
 
-        if identity.authenticate(&ctrl).await?.is_some() {
-            println!("{identity} has access");
-        } else {
+        let access = identity.authenticate(&ctrl).await?.is_some();
+
+        output::print(
+            self.output,
+            &[CheckOutput {
+                id: self.id.clone(),
+                groups: self.groups.clone(),
+                access,
+            }],
+        )?;
+
+        if !access {
             return Err(eyre!("{identity} does not have access"));
         }
 
@@ -69,6 +122,35 @@ struct GrantOutput {
     role: String,
     binding: String,
     created: DateTime<Utc>,
+    expires: Option<DateTime<Utc>>,
+}
+
+impl Tabular for GrantOutput {
+    fn header(wide: bool) -> Vec<&'static str> {
+        let mut header = vec!["ID", "ROLE", "BINDING", "EXPIRES"];
+
+        if wide {
+            header.push("CREATED");
+        }
+
+        header
+    }
+
+    fn row(&self, wide: bool) -> Vec<String> {
+        let mut row = vec![
+            self.id.clone(),
+            self.role.clone(),
+            self.binding.clone(),
+            self.expires
+                .map_or_else(|| "-".to_string(), |at| at.to_rfc3339()),
+        ];
+
+        if wide {
+            row.push(self.created.to_rfc3339());
+        }
+
+        row
+    }
 }
 
 /// Grant a role to a user by the provided ID. This will create a
@@ -84,44 +166,35 @@ pub struct Grant {
     /// configured the openid provider. By default, it is `email`.
     id: String,
 
-    /// Output the role binding instead of applying it.
-    #[arg(short, long)]
-    output: Option<Output>,
-}
+    /// Remove the grant after this long, eg. `8h`. Sessions relying on it are
+    /// disconnected once it expires.
+    #[arg(long)]
+    expires: Option<Duration>,
 
-#[derive(clap::ValueEnum, Clone, Default)]
-enum Output {
-    #[default]
-    Yaml,
+    /// Print the role binding instead of applying it, as JSON with `-o json`
+    /// and YAML otherwise.
+    #[arg(long)]
+    dry_run: bool,
+
+    #[arg(short, long, value_enum, default_value_t)]
+    output: Format,
 }
 
-// TODO: use cata::output, doesn't work because ratatui and tabled disagree on
-// `unicode-width` versions. The CLI can be split into its own crate at this
-// point and remove the direct ratatui dependency.
 #[async_trait::async_trait]
 impl Command for Grant {
     #[allow(clippy::blocks_in_conditions)]
     #[tracing::instrument(err, skip(self), fields(activity = "users.grant"))]
     async fn run(&self) -> Result<()> {
-        let binding = ClusterRoleBinding {
-            metadata: ObjectMeta {
-                name: Some(format!("kty:{}", self.id).kube_id()?),
-                ..Default::default()
-            },
-            role_ref: RoleRef {
-                kind: "ClusterRole".to_string(),
-                name: self.role.clone(),
-                ..Default::default()
-            },
-            subjects: Some(vec![Subject {
-                kind: "User".to_string(),
-                name: self.id.clone(),
-                ..Default::default()
-            }]),
-        };
+        let expires = self.expires.map(|expires| Utc::now() + *expires.as_ref());
+        let binding = grant::binding(&self.role, &self.id, expires)?;
+
+        if self.dry_run {
+            if self.output == Format::Json {
+                println!("{}", serde_json::to_string_pretty(&binding)?);
+            } else {
+                println!("{}", serde_yaml::to_string(&binding)?);
+            }
 
-        if let Some(Output::Yaml) = self.output {
-            println!("{}", serde_yaml::to_string(&binding)?);
             return Ok(());
         }
 
@@ -129,17 +202,16 @@ impl Command for Grant {
             .create(&PostParams::default(), &binding)
             .await?;
 
-        println!(
-            "{}",
-            serde_json::to_string_pretty(&GrantOutput {
+        output::print(
+            self.output,
+            &[GrantOutput {
                 id: self.id.clone(),
                 role: self.role.clone(),
                 binding: binding.name_any(),
                 created: Utc::now(),
-            })?
-        );
-
-        Ok(())
+                expires,
+            }],
+        )
     }
 }
 
@@ -147,9 +219,36 @@ impl Command for Grant {
 struct KeyOutput {
     id: String,
     key: String,
+    groups: Vec<String>,
     expiration: DateTime<Utc>,
 }
 
+impl Tabular for KeyOutput {
+    fn header(wide: bool) -> Vec<&'static str> {
+        let mut header = vec!["ID", "KEY", "EXPIRATION"];
+
+        if wide {
+            header.push("GROUPS");
+        }
+
+        header
+    }
+
+    fn row(&self, wide: bool) -> Vec<String> {
+        let mut row = vec![
+            self.id.clone(),
+            self.key.clone(),
+            self.expiration.to_rfc3339(),
+        ];
+
+        if wide {
+            row.push(self.groups.join(","));
+        }
+
+        row
+    }
+}
+
 /// Allow access to the cluster for the provided ID and SSH Key. This happens
 /// automatically for users logging in via. openid.
 #[derive(Parser, Container)]
@@ -174,6 +273,9 @@ pub struct Key {
 
     #[arg(long, default_value = "1y")]
     expiration: Duration,
+
+    #[arg(short, long, value_enum, default_value_t)]
+    output: Format,
 }
 
 #[async_trait::async_trait]
@@ -181,42 +283,220 @@ impl Command for Key {
     #[allow(clippy::blocks_in_conditions)]
     #[tracing::instrument(err, skip(self), fields(activity = "users.key"))]
     async fn run(&self) -> Result<()> {
-        let mut keys: Vec<PublicKey> = self
-            .keys
-            .iter()
-            .map(|key| parse_public_key_base64(key))
-            .try_collect()?;
-
-        if let Some(path) = &self.path {
-            keys.push(russh::keys::load_public_key(path)?);
+        let keys = public_keys(&self.keys, self.path.as_deref())?;
+
+        let out = bind(
+            kube::Client::try_default().await?,
+            keys,
+            &self.id,
+            &self.groups,
+            Utc::now() + *self.expiration.as_ref(),
+        )
+        .await?;
+
+        output::print(self.output, &out)
+    }
+}
+
+fn public_keys(keys: &[String], path: Option<&str>) -> Result<Vec<PublicKey>> {
+    let mut keys: Vec<PublicKey> = keys
+        .iter()
+        .map(|key| -> Result<PublicKey> {
+            Ok(PublicKey::from_bytes(&BASE64_STANDARD.decode(key)?)?)
+        })
+        .try_collect()?;
+
+    if let Some(path) = path {
+        keys.push(PublicKey::read_openssh_file(Path::new(path))?);
+    }
+
+    Ok(keys)
+}
+
+async fn bind(
+    client: kube::Client,
+    keys: Vec<PublicKey>,
+    id: &str,
+    groups: &[String],
+    expiration: DateTime<Utc>,
+) -> Result<Vec<KeyOutput>> {
+    let api = Api::<key::Key>::default_namespaced(client);
+
+    let mut out = Vec::new();
+
+    for key in keys {
+        let name = key.kube_id()?;
+        let resource = key::Key::new(
+            name.as_str(),
+            key::KeySpec {
+                key,
+                user: id.to_string(),
+                groups: groups.to_vec(),
+                expiration,
+            },
+        );
+
+        api.create(&PostParams::default(), &resource).await?;
+
+        out.push(KeyOutput {
+            id: id.to_string(),
+            key: name,
+            groups: groups.to_vec(),
+            expiration,
+        });
+    }
+
+    Ok(out)
+}
+
+/// Replace the keys a user has with new ones. The old keys keep working for
+/// `--grace` so that anything still using them can move over, after which
+/// they expire.
+#[derive(Parser, Container)]
+pub struct Rotate {
+    id: String,
+
+    /// Groups for the new keys, the same as the old keys when unset.
+    #[arg(long)]
+    groups: Vec<String>,
+
+    /// Path to the new public key file.
+    #[arg(long)]
+    path: Option<String>,
+
+    /// New base64 encoded public keys, see `kty users key --keys`.
+    #[arg(long)]
+    keys: Vec<String>,
+
+    /// How long the old keys keep working for.
+    #[arg(long, default_value = "1h")]
+    grace: Duration,
+
+    #[arg(long, default_value = "1y")]
+    expiration: Duration,
+
+    #[arg(short, long, value_enum, default_value_t)]
+    output: Format,
+}
+
+#[async_trait::async_trait]
+impl Command for Rotate {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(err, skip(self), fields(activity = "users.rotate"))]
+    async fn run(&self) -> Result<()> {
+        let keys = public_keys(&self.keys, self.path.as_deref())?;
+        if keys.is_empty() {
+            return Err(eyre!("a new key is required, use --path or --keys"));
         }
 
-        let client = Api::<key::Key>::default_namespaced(kube::Client::try_default().await?);
+        let client = kube::Client::try_default().await?;
+
+        let mut old = key::Key::for_user(client.clone(), &self.id).await?;
+        old.sort_by_key(|key| key.creation_timestamp());
+
+        let groups = if self.groups.is_empty() {
+            old.last()
+                .map(|key| key.spec.groups.clone())
+                .unwrap_or_default()
+        } else {
+            self.groups.clone()
+        };
+
+        let names: Vec<_> = keys.iter().map(KubeID::kube_id).try_collect()?;
+
+        let mut out = bind(
+            client.clone(),
+            keys,
+            &self.id,
+            &groups,
+            Utc::now() + *self.expiration.as_ref(),
+        )
+        .await?;
 
-        let mut out = Vec::new();
+        let until = Utc::now() + *self.grace.as_ref();
 
-        for key in keys {
-            let id = key.kube_id()?;
-            let resource = key::Key::new(
-                id.as_str(),
-                key::KeySpec {
-                    key,
-                    user: self.id.clone(),
-                    groups: self.groups.clone(),
-                    expiration: Utc::now() + *self.expiration.as_ref(),
-                },
-            );
+        for key in old {
+            let name = key.name_any();
+            if names.contains(&name) {
+                continue;
+            }
 
-            client.create(&PostParams::default(), &resource).await?;
+            // Keys that expire before the grace period is over are left alone.
+            let expiration = key.spec.expiration.min(until);
+            key::Key::expire(client.clone(), &name, expiration).await?;
 
             out.push(KeyOutput {
                 id: self.id.clone(),
-                key: id,
-                expiration: resource.spec.expiration,
+                key: name,
+                groups: key.spec.groups,
+                expiration,
             });
         }
 
-        println!("{}", serde_json::to_string_pretty(&out)?);
+        output::print(self.output, &out)
+    }
+}
+
+/// Revoke a user's keys straight away and disconnect their sessions. They can
+/// still log in again via. `OpenID` as long as RBAC allows them in, remove
+/// their role bindings as well to keep them out.
+#[derive(Parser, Container)]
+pub struct Revoke {
+    id: String,
+
+    /// Name of a key to revoke, as shown by `kty users rotate`. Every key the
+    /// user has when unset.
+    #[arg(long)]
+    key: Vec<String>,
+
+    /// URL of the server's health port, see `kty sessions`.
+    #[arg(long, env = "KTY_ADMIN_URL", default_value = "http://localhost:8080")]
+    url: String,
+
+    /// Bearer token the server was started with. Without it, only keys are
+    /// revoked and sessions that are already connected stay connected.
+    #[arg(long, env = "KTY_ADMIN_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl Command for Revoke {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(err, skip(self), fields(activity = "users.revoke"))]
+    async fn run(&self) -> Result<()> {
+        let client = kube::Client::try_default().await?;
+        let api = Api::<key::Key>::default_namespaced(client.clone());
+
+        for key in key::Key::for_user(client, &self.id).await? {
+            let name = key.name_any();
+            if !self.key.is_empty() && !self.key.contains(&name) {
+                continue;
+            }
+
+            api.delete(&name, &DeleteParams::default()).await?;
+
+            println!("revoked {name}");
+        }
+
+        let Some(token) = self.token.clone() else {
+            return Ok(());
+        };
+
+        let sent: Sent = Server {
+            url: self.url.clone(),
+            token,
+        }
+        .request(Method::DELETE, &format!("users/{}/sessions", self.id))
+        .query(&Terminate {
+            reason: Some("access revoked".to_string()),
+        })
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+        println!("disconnected {} sessions", sent.sessions);
 
         Ok(())
     }
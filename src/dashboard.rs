@@ -1,4 +1,11 @@
-use std::time::Duration;
+pub(crate) mod context;
+
+use std::{
+    collections::{HashMap, VecDeque},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use bon::Builder;
 use eyre::{eyre, Report, Result};
@@ -8,22 +15,50 @@ use prometheus::{register_int_counter, register_int_gauge, IntCounter, IntGauge}
 use ratatui::{
     backend::Backend as BackendTrait,
     buffer::Buffer,
-    layout::{Position, Rect},
-    widgets::Clear,
-    Terminal,
+    layout::{Alignment, Constraint, Layout, Position, Rect},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Clear, Paragraph},
+    Frame, Terminal,
 };
 use replace_with::replace_with_or_abort;
+use strum::IntoEnumIterator;
 use tokio::{
     io::{AsyncRead, AsyncWrite},
     runtime::Builder,
     sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
 };
 use tokio_util::io::ReaderStream;
+use tracing::Instrument;
 
+use self::context::Context;
 use crate::{
+    audit::Audit,
     events::{Broadcast, Event, Input, Keypress, StringError},
-    io::{backend::Backend, Writer},
-    widget::{apex::Apex, Raw, Widget},
+    history::{Disabled, History},
+    image::Enrichment,
+    io::{backend::Backend, capture::Captured, Writer},
+    plugin::Plugin,
+    resources::{
+        alerts::AlertSource, columns::Columns, command::Commands, idle::IdleSettings,
+        node::shell::NodeShellSettings, policy::Policy, redaction::Redaction, snapshot::Snapshot,
+    },
+    share::Share,
+    widget::{
+        apex::Apex,
+        caps::Capabilities,
+        clock::Clock,
+        error::Error,
+        keys::{self, Action},
+        link::Link,
+        locale::Locale,
+        narration::{self, Narration},
+        saved::Variables,
+        scratchpad::{self, Notes},
+        scrollback::Scrollback,
+        statusbar::{self, Slow},
+        Raw, Widget,
+    },
 };
 
 lazy_static! {
@@ -37,17 +72,120 @@ lazy_static! {
         "Number of active dashboard threads"
     )
     .unwrap();
+    static ref SKIPPED_FRAMES: IntCounter = register_int_counter!(
+        "dashboards_frames_skipped_total",
+        "Number of frames that weren't drawn because the dashboard was idle"
+    )
+    .unwrap();
+    static ref THROTTLED_FRAMES: IntCounter = register_int_counter!(
+        "dashboards_frames_throttled_total",
+        "Number of frames that weren't drawn because the client wasn't keeping up"
+    )
+    .unwrap();
 }
 
-static FPS: u16 = 10;
-pub static RENDER_INTERVAL: Duration = Duration::from_millis(1000 / FPS as u64);
+/// Number of dashboards running on this server, including detached ones.
+pub(crate) fn active() -> i64 {
+    ACTIVE_DASHBOARD_THREADS.get()
+}
 
-#[derive(Builder)]
+/// Frames per second when not configured otherwise.
+pub static FPS: u16 = 10;
+/// Upper limit for the frame rate, anything higher is clamped to this.
+pub static MAX_FPS: u16 = 30;
+// Once there hasn't been any input for this long, frames are only drawn when a
+// widget is dirty or `IDLE_INTERVAL` has passed. Tables are updated in the
+// background without being dirty, this keeps them from going stale.
+static IDLE_AFTER: Duration = Duration::from_secs(5);
+static IDLE_INTERVAL: Duration = Duration::from_secs(1);
+// While the client has a backlog, see `Writer::backlog`, frames are drawn at
+// most this often so that it can catch up and input is still handled promptly.
+static CONGESTED_INTERVAL: Duration = Duration::from_millis(500);
+// Smallest window the dashboard is drawn in, anything smaller gets a screen
+// asking for more room instead of a garbled layout.
+static MIN_SIZE: (u16, u16) = (40, 10);
+// Events kept around for snapshots, see `Action::Snapshot`.
+static RECENT_EVENTS: usize = 50;
+
+/// The kube TUI. Each dashboard is rendered on its own thread and is
+/// controlled via. the channel returned from [`Dashboard::start`].
+#[derive(Builder, Clone)]
 pub struct Dashboard {
+    /// Client used for all requests, this is how permissions are scoped.
     client: kube::Client,
+    /// Where `client` records calls that took unusually long, see [`Slow`].
+    #[builder(default)]
+    slow: Slow,
+    /// View to open on start instead of the default pod list.
+    link: Option<Link>,
+    /// Name of the cluster, shown in the terminal's title.
+    cluster: Option<String>,
+    /// The server can't save keys. Shown in the status bar so that users know
+    /// why they're asked to log in every time.
+    #[builder(default)]
+    keys_read_only: bool,
+    /// Lets others watch this dashboard.
+    share: Option<Arc<dyn Share>>,
+    /// Where shells opened from the dashboard are recorded.
+    audit: Option<Arc<dyn Audit>>,
+    /// Where commands run in containers are recorded, if the user opts in.
+    #[builder(default = Arc::new(Disabled) as Arc<dyn History>)]
+    history: Arc<dyn History>,
+    /// Values for variables in saved views, such as `user` and `group`.
+    #[builder(default)]
+    variables: Variables,
+    /// Tabs to show, in order. All of them when unset.
+    tabs: Option<Vec<String>>,
+    /// Tab to select on start when there's no link.
+    tab: Option<String>,
+    /// Frames per second while the user is active, clamped to `1..=MAX_FPS`.
+    #[builder(default = FPS)]
+    fps: u16,
+    /// What the client's terminal can draw.
+    #[builder(default)]
+    caps: Capabilities,
+    /// How timestamps are shown, the offset is the client's timezone.
+    #[builder(default)]
+    clock: Clock,
+    /// Language UI strings are shown in.
+    #[builder(default)]
+    locale: Locale,
+    /// Narrows down what can be seen and done, in addition to RBAC.
+    #[builder(default)]
+    policy: Policy,
+    /// Masks credentials in logs before they're drawn.
+    #[builder(default)]
+    redaction: Redaction,
+    /// Extra table columns set by admins.
+    #[builder(default)]
+    columns: Columns,
+    /// Where the alerts tab gets firing alerts from, there's no tab without it.
+    alerts: Option<AlertSource>,
+    /// Where the images tab looks up details about images, see [`Enrichment`].
+    #[builder(default)]
+    images: Enrichment,
+    /// What the idle tab lists, see [`IdleSettings`].
+    #[builder(default)]
+    idle: IdleSettings,
+    /// Where node shells run, see [`NodeShellSettings`].
+    #[builder(default)]
+    node_shell: NodeShellSettings,
+    /// Extra tabs, see [`Plugin`].
+    #[builder(default)]
+    plugins: Vec<Arc<dyn Plugin>>,
+    /// Programs that can be run from a pod, see [`Commands`].
+    #[builder(default)]
+    commands: Commands,
+    /// Directory snapshots are written to. They're saved as a `Secret` in the
+    /// client's default namespace otherwise.
+    snapshots: Option<PathBuf>,
 }
 
 impl Dashboard {
+    /// Start rendering, reading input from `stdin` and writing to `stdout`.
+    /// The returned channel can be used to send events, such as resizing, to
+    /// the dashboard.
+    //
     // This spins up:
     // - An tokio async thread on the current runtime to handle IO by consuming
     //   `stdin` and publishing `Event`s on a channel.
@@ -66,34 +204,20 @@ impl Dashboard {
     {
         let (tx, rx) = mpsc::unbounded_channel();
 
-        let reader_tx = tx.clone();
-        tokio::spawn(async move {
-            let stream = ReaderStream::new(stdin);
-            tokio::pin!(stream);
-
-            loop {
-                tokio::select! {
-                    () = reader_tx.closed() => {
-                        break;
-                    }
-                    Ok(Some(msg)) = stream.try_next() => {
-                        reader_tx.send(msg.into())?;
-                    }
-                }
-            }
-
-            Ok::<(), Report>(())
-        });
+        read(stdin, tx.clone());
 
         let rt = Builder::new_current_thread().enable_all().build()?;
-        let client = self.client.clone();
+        let dashboard = self.clone();
+        // The render loop runs on its own thread, carry the caller's span over so
+        // that logs can be correlated with the session that started it.
+        let span = tracing::Span::current();
 
         std::thread::spawn(move || {
             TOTAL_DASHBOARD_THREADS.inc();
             ACTIVE_DASHBOARD_THREADS.inc();
 
-            if let Err(err) = rt.block_on(run(client, rx, stdout)) {
-                tracing::error!("Unhandled dashboard error: {err:?}");
+            if let Err(err) = rt.block_on(run(dashboard, rx, stdout).instrument(span.clone())) {
+                span.in_scope(|| tracing::error!("Unhandled dashboard error: {err:?}"));
             }
 
             ACTIVE_DASHBOARD_THREADS.dec();
@@ -103,6 +227,35 @@ impl Dashboard {
     }
 }
 
+/// Publish everything read from `stdin` as events on `tx`, until the dashboard
+/// goes away. Dashboards that have been detached are given a new `stdin` this
+/// way when a connection attaches to them.
+pub fn read<R>(stdin: R, tx: UnboundedSender<Event>)
+where
+    R: AsyncRead + Send + 'static,
+{
+    tokio::spawn(
+        async move {
+            let stream = ReaderStream::new(stdin);
+            tokio::pin!(stream);
+
+            loop {
+                tokio::select! {
+                    () = tx.closed() => {
+                        break;
+                    }
+                    Ok(Some(msg)) = stream.try_next() => {
+                        tx.send(msg.into())?;
+                    }
+                }
+            }
+
+            Ok::<(), Report>(())
+        }
+        .in_current_span(),
+    );
+}
+
 impl std::fmt::Debug for Dashboard {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Dashboard").finish()
@@ -132,51 +285,266 @@ impl Mode {
 }
 
 async fn run(
-    client: kube::Client,
+    Dashboard {
+        client,
+        link,
+        cluster,
+        keys_read_only,
+        share,
+        audit,
+        history,
+        variables,
+        tabs,
+        tab,
+        fps,
+        mut caps,
+        clock,
+        locale,
+        policy,
+        redaction,
+        columns,
+        alerts,
+        images,
+        idle,
+        node_shell,
+        plugins,
+        commands,
+        snapshots,
+        slow,
+    }: Dashboard,
     mut rx: UnboundedReceiver<Event>,
 
     stdout: impl Writer,
 ) -> Result<()> {
-    let mut interval = tokio::time::interval(RENDER_INTERVAL);
+    let mut interval = tokio::time::interval(Duration::from_millis(
+        1000 / u64::from(fps.clamp(1, MAX_FPS)),
+    ));
     // Because we pause the render loop while rendering a raw widget, the ticks can
     // really back up. While this wouldn't necessarily be a bad thing (just some
     // extra CPU), it causes `Handle.data()` to deadlock if called too quickly.
     interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
+    // Loaded before the first frame so that what was entered in previous
+    // sessions can be recalled right away.
+    let inputs = history.inputs().await.unwrap_or_else(|err| {
+        tracing::warn!(?err, "failed to load input history");
+
+        HashMap::default()
+    });
+
+    Arc::new(Context {
+        caps: Mutex::new(caps),
+        clock: Mutex::new(clock),
+        locale,
+        policy: Some(policy),
+        keys_read_only,
+        redaction,
+        columns,
+        audit,
+        node_shell,
+        commands,
+        slow,
+        inputs: Mutex::new(inputs),
+        history: Some(history.clone()),
+        ..Context::default()
+    })
+    .install();
+
     let (backend, window_size) = Backend::with_size(stdout.blocking());
-    let mut term = Terminal::new(backend)?;
+    let mut term = Terminal::new(backend.linear(caps.linear))?;
+    let mut title = String::new();
+    // What was last written out in linear mode, and whether there has been input
+    // since. See `Narration::changes`.
+    let mut narrated = Narration::default();
+    let mut listening = true;
+    let mut last_input = Instant::now();
+    let mut last_draw = Instant::now();
+    let mut recent = VecDeque::with_capacity(RECENT_EVENTS);
+    // Results that need to go back to the widgets, such as a saved snapshot.
+    let mut queued = VecDeque::new();
+    // Output from the last raw mode widget, shown on top until it is dismissed.
+    let mut scrollback: Option<Box<dyn Widget>> = None;
 
     // kube::Client ends up being cloned by ~every widget, it'd be nice to Arc<> it
     // so that there's not a bunch of copying. Unfortunately, the Api interface
     // doesn't like Arc<>.
-    let mut state = Mode::UI(Box::new(Apex::new(client)));
+    //
+    // A linked resource is fetched before the first frame, everything else is
+    // deferred until the user navigates to it.
+    let detail = match &link {
+        Some(link) => link
+            .detail(client.clone(), history.clone())
+            .await
+            .unwrap_or_else(|err| Some(Error::from(err).boxed())),
+        None => None,
+    };
+
+    let notes = Notes::default();
+    let user = variables.get("user").cloned();
+
+    let mut state = Mode::UI(Box::new(
+        Apex::builder()
+            .client(client.clone())
+            .history(history)
+            .notes(notes.clone())
+            .maybe_share(share)
+            .maybe_link(link.map(|link| link.kind))
+            .maybe_detail(detail)
+            .variables(variables)
+            .maybe_tabs(tabs)
+            .maybe_tab(tab)
+            .maybe_cluster(cluster.clone())
+            .maybe_alerts(alerts)
+            .images(images)
+            .idle(idle)
+            .plugins(plugins)
+            .build(),
+    ));
 
     loop {
         // It is important that this doesn't go *too* fast. Repeatedly writing to the
         // channel causes a deadlock for some reason that I've been unable to decipher.
-        let ev = tokio::select! {
-            ev = rx.recv() => {
-                let Some(ev) = ev else {
-                    break;
-                };
+        let ev = if let Some(ev) = queued.pop_front() {
+            ev
+        } else {
+            tokio::select! {
+                ev = rx.recv() => {
+                    let Some(ev) = ev else {
+                        break;
+                    };
 
-                ev
-            }
-            _ = interval.tick() => {
-                Event::Render
+                    ev
+                }
+                _ = interval.tick() => {
+                    Event::Render
+                }
             }
         };
 
+        if matches!(ev, Event::Shutdown) {
+            break;
+        }
+
+        // Idle sessions skip frames where nothing could have changed, there can be
+        // hundreds of dashboards on a server.
+        if let (Event::Render, Mode::UI(widget)) = (&ev, &state) {
+            if last_input.elapsed() > IDLE_AFTER
+                && last_draw.elapsed() < IDLE_INTERVAL
+                && !widget.dirty()
+            {
+                SKIPPED_FRAMES.inc();
+
+                continue;
+            }
+
+            if throttled(stdout.backlog(), last_draw.elapsed()) {
+                THROTTLED_FRAMES.inc();
+
+                // Effects are a lot of output for a client that's already behind, they
+                // stay off for the rest of the session.
+                if !caps.still {
+                    caps.still = true;
+                    Capabilities::settle();
+                }
+
+                continue;
+            }
+        } else {
+            last_input = Instant::now();
+            listening = true;
+        }
+
+        last_draw = Instant::now();
+
+        if let Some(description) = describe(&ev, term.backend().typing()) {
+            if recent.len() == RECENT_EVENTS {
+                recent.pop_front();
+            }
+
+            recent.push_back(description);
+        }
+
         if let Event::Resize(area) = ev {
             let mut size = window_size.lock().unwrap();
             *size = area;
         }
 
+        // Clearing resets ratatui's idea of what is on screen, so everything is
+        // written out again on the next draw.
+        if let Event::Redraw = ev {
+            term.clear()?;
+        }
+
         let result = match state {
-            Mode::UI(ref mut widget) => draw_ui(widget, &mut term, &ev)?,
+            Mode::UI(ref mut widget) => {
+                let capture = ev.key().is_some_and(|key| Action::Snapshot.matches(key));
+                let reviewing = scrollback.is_some();
+
+                let target = match scrollback.as_mut() {
+                    Some(output) => output,
+                    None => widget,
+                };
+
+                let (mut result, buffer) = draw_ui(target, &mut term, &ev, caps, capture)?;
+
+                if reviewing && matches!(result, Broadcast::Exited) {
+                    scrollback = None;
+                    result = Broadcast::Consumed;
+                }
+
+                // Only write the title when it changes, this goes over the wire for every
+                // session.
+                let current = window_title(cluster.as_deref(), widget.title());
+
+                if let Some(buffer) = buffer {
+                    let snapshot = Snapshot::new(
+                        &buffer,
+                        &current,
+                        widget.tree(),
+                        recent.iter().cloned().collect(),
+                    );
+
+                    queued.push_back(Event::Snapshot(
+                        snapshot
+                            .save(client.clone(), snapshots.as_deref(), user.as_deref())
+                            .await
+                            .map_err(|e| StringError(e.to_string())),
+                    ));
+                }
+
+                if caps.linear {
+                    let narration = narration::take();
+                    let mut lines = narration.changes(&narrated, listening);
+
+                    if current != title {
+                        lines.insert(0, current.clone());
+                    }
+
+                    if !lines.is_empty() {
+                        term.backend_mut().say(&lines)?;
+                        listening = false;
+                    }
+
+                    narrated = narration;
+                }
+
+                if current != title {
+                    term.backend_mut().set_title(&current)?;
+                    title = current;
+                }
+
+                result
+            }
             Mode::Raw(ref mut raw_widget, ref mut current_widget) => {
-                let raw_result =
-                    draw_raw(raw_widget, &mut term, &mut rx, stdout.non_blocking()).await;
+                let captured = Captured::default();
+
+                let raw_result = draw_raw(
+                    raw_widget,
+                    &mut term,
+                    &mut rx,
+                    captured.tee(stdout.non_blocking()),
+                )
+                .await;
 
                 let area = term.get_frame().area();
 
@@ -188,6 +556,14 @@ async fn run(
 
                 state.ui();
 
+                // Everything is said again, the shell's output came after it.
+                narrated = Narration::default();
+
+                let output = captured.take();
+                if !output.is_empty() {
+                    scrollback = Scrollback::new(&output).ok().map(Widget::boxed);
+                }
+
                 result
             }
         };
@@ -199,6 +575,11 @@ async fn run(
             Broadcast::Raw(widget) => {
                 state.raw(widget);
             }
+            Broadcast::Clipboard(text) => {
+                term.backend_mut().set_clipboard(&text)?;
+                statusbar::copied(text);
+                interval.reset_immediately();
+            }
             Broadcast::Consumed => interval.reset_immediately(),
             _ => {}
         }
@@ -214,34 +595,104 @@ async fn run(
     // running from a cluster it needs a little bit of time.
     tokio::time::sleep(Duration::from_millis(10)).await;
 
-    stdout.shutdown("exiting...".to_string()).await?;
+    let msg = match export(client, &notes, user.as_deref()).await {
+        Ok(Some(location)) => format!("exiting, notes saved to configmap {location}"),
+        Ok(None) => "exiting...".to_string(),
+        Err(err) => {
+            tracing::warn!("unable to save notes: {err}");
+
+            format!("exiting, unable to save notes: {err}")
+        }
+    };
+
+    stdout.shutdown(msg).await?;
 
     Ok(())
 }
 
+// Notes are only saved when the user asked for it and wrote something.
+async fn export(client: kube::Client, notes: &Notes, user: Option<&str>) -> Result<Option<String>> {
+    let text = {
+        let note = notes.borrow();
+
+        if !note.export || note.text.trim().is_empty() {
+            return Ok(None);
+        }
+
+        note.text.clone()
+    };
+
+    scratchpad::save(client, &text, user).await.map(Some)
+}
+
+fn window_title(cluster: Option<&str>, location: Vec<String>) -> String {
+    std::iter::once("kty".to_string())
+        .chain(cluster.map(ToString::to_string))
+        .chain(location.into_iter().filter(|s| !s.is_empty()))
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
 fn draw_ui<W>(
     widget: &mut Box<dyn Widget>,
     term: &mut Terminal<Backend<W>>,
     ev: &Event,
-) -> Result<Broadcast>
+    caps: Capabilities,
+    capture: bool,
+) -> Result<(Broadcast, Option<Buffer>)>
 where
     W: std::io::Write + Send,
 {
     let mut result = Err(eyre!("no dispatch"));
+    let mut buffer = None;
 
     term.try_draw(|frame| {
         let area = frame.area();
+        let small = !caps.linear && (area.width < MIN_SIZE.0 || area.height < MIN_SIZE.1);
+
+        let draw_result = if small {
+            too_small(frame, area);
 
-        let draw_result = widget
-            .draw(frame, area)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("{e:?}")));
+            Ok(())
+        } else {
+            widget
+                .draw(frame, area)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("{e:?}")))
+        };
 
-        result = dispatch(widget, ev, frame.buffer_mut(), area);
+        // Keys would go to widgets the user can't see, only exiting works.
+        result = match ev.key() {
+            Some(key) if small && !matches!(key, Keypress::Control('c')) => Ok(Broadcast::Consumed),
+            _ => dispatch(widget, ev, frame.buffer_mut(), area),
+        };
+
+        caps.apply(frame.buffer_mut());
+
+        if capture {
+            buffer = Some(frame.buffer_mut().clone());
+        }
 
         draw_result
     })?;
 
-    result
+    Ok((result?, buffer))
+}
+
+fn too_small(frame: &mut Frame, area: Rect) {
+    let lines = vec![
+        Line::from("Terminal too small").style(Style::default().add_modifier(Modifier::BOLD)),
+        Line::from(format!("{}x{}", area.width, area.height)),
+        Line::from(format!("needs {}x{}", MIN_SIZE.0, MIN_SIZE.1)),
+    ];
+
+    let [_, body, _] = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Length(u16::try_from(lines.len()).unwrap_or_default()),
+        Constraint::Fill(1),
+    ])
+    .areas(area);
+
+    frame.render_widget(Paragraph::new(lines).alignment(Alignment::Center), body);
 }
 
 fn dispatch(
@@ -256,6 +707,11 @@ fn dispatch(
                 return Ok(Broadcast::Exited);
             }
 
+            // Taken by the run loop once the frame is drawn.
+            if Action::Snapshot.matches(key) {
+                return Ok(Broadcast::Consumed);
+            }
+
             widget.dispatch(ev, buffer, area)
         }
         Event::Render => Ok(Broadcast::Ignored),
@@ -263,6 +719,45 @@ fn dispatch(
     }
 }
 
+// Only cells that changed are sent to begin with, drawing while the client is
+// behind just queues more frames up.
+fn throttled(backlog: usize, since_draw: Duration) -> bool {
+    backlog > 0 && since_draw < CONGESTED_INTERVAL
+}
+
+// How an event shows up in a snapshot. Printable keys are masked while a text
+// input has focus, bound or not, as they're going into a filter or a shell
+// command. Otherwise, keys that aren't bound to anything are most likely
+// typed text as well.
+fn describe(ev: &Event, typing: bool) -> Option<String> {
+    let description = match ev {
+        Event::Input(Input {
+            key: Keypress::Printable(_),
+            ..
+        }) if typing => "key *".to_string(),
+        Event::Input(Input { key, .. }) => match Action::iter().find(|a| a.matches(key)) {
+            Some(action) => format!("key {} ({action:?})", keys::name(key)),
+            None if matches!(key, Keypress::Printable(_)) => "key *".to_string(),
+            None => format!("key {}", keys::name(key)),
+        },
+        Event::Resize(size) => format!(
+            "resize {}x{}",
+            size.columns_rows.width, size.columns_rows.height
+        ),
+        Event::Goto(route) => format!("goto {}", route.join("/")),
+        Event::Render | Event::Snapshot(_) => return None,
+        Event::Finished(result) => format!("finished, ok: {}", result.is_ok()),
+        Event::Tunnel(result) => format!("tunnel, ok: {}", result.is_ok()),
+        Event::Message(_) => "message".to_string(),
+        Event::Idle(remaining) => format!("idle, {}s left", remaining.as_secs()),
+        Event::Expires(at) => format!("expires {}", at.to_rfc3339()),
+        Event::Redraw => "redraw".to_string(),
+        Event::Shutdown => "shutdown".to_string(),
+    };
+
+    Some(description)
+}
+
 async fn draw_raw(
     raw_widget: &mut Box<dyn Raw>,
     term: &mut Terminal<impl BackendTrait>,
@@ -295,3 +790,22 @@ where
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{throttled, CONGESTED_INTERVAL};
+
+    #[test]
+    fn draws_when_caught_up() {
+        assert!(!throttled(0, Duration::ZERO));
+    }
+
+    #[test]
+    fn throttles_while_behind() {
+        assert!(throttled(1, Duration::ZERO));
+        assert!(throttled(1, CONGESTED_INTERVAL - Duration::from_millis(1)));
+        assert!(!throttled(1, CONGESTED_INTERVAL));
+    }
+}
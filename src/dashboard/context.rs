@@ -0,0 +1,98 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+
+use crate::{
+    audit::Audit,
+    history::History,
+    resources::{
+        columns::Columns, command::Commands, node::shell::NodeShellSettings, policy::Policy,
+        redaction::Redaction, store,
+    },
+    widget::{
+        caps::Capabilities, clock::Clock, locale::Locale, narration::Narration, statusbar::Slow,
+    },
+};
+
+thread_local! {
+    // Every dashboard renders on its own thread, see `Dashboard::start`. Other
+    // threads see the defaults unless they `enter` a dashboard's context.
+    static CURRENT: RefCell<Arc<Context>> = RefCell::new(Arc::default());
+}
+
+/// Everything about a dashboard that the code drawing it reaches for without
+/// it being passed down, such as the user's language or the policy that
+/// applies to them. It is installed once for the dashboard's thread, work that
+/// happens on another thread takes [`Context::current`] along and
+/// [`Context::enter`]s it there.
+///
+/// Modules own their part and read it through their own functions, such as
+/// [`Locale::current`].
+#[derive(Default)]
+pub(crate) struct Context {
+    // Effects are turned off for clients that can't keep up.
+    pub caps: Mutex<Capabilities>,
+    // Users can cycle through formats.
+    pub clock: Mutex<Clock>,
+    pub locale: Locale,
+    // Unset off the dashboard's thread, where nothing is allowed.
+    pub policy: Option<Policy>,
+    // Shown in the status bar, see `Dashboard::keys_read_only`.
+    pub keys_read_only: bool,
+    pub redaction: Redaction,
+    pub columns: Columns,
+    pub audit: Option<Arc<dyn Audit>>,
+    pub node_shell: NodeShellSettings,
+    pub commands: Commands,
+    pub slow: Slow,
+    // Background work shown in the status bar, see `statusbar::Pending`.
+    pub pending: Arc<AtomicUsize>,
+    // What was last put on the clipboard and when, see `statusbar::copied`.
+    pub copied: Mutex<Option<(Instant, String)>>,
+    // Set once the user stops previewing changes, see `preview::enabled`.
+    pub skip_preview: AtomicBool,
+    // Filled in while drawing a frame, see `narration::take`.
+    pub narration: Mutex<Narration>,
+    // Entries for each kind of input and where new ones are saved, see
+    // `input::remember`.
+    pub inputs: Mutex<HashMap<String, Vec<String>>>,
+    pub history: Option<Arc<dyn History>>,
+    // A `Weak<Store<K>>` for each kind and field selector so that the watch goes
+    // away with the last widget using it, see `Store::new`.
+    pub stores: Mutex<HashMap<store::Key, Box<dyn Any + Send + Sync>>>,
+}
+
+impl Context {
+    /// Use this context for everything drawn on the current thread.
+    pub fn install(self: Arc<Self>) {
+        CURRENT.set(self);
+    }
+
+    /// The context of the dashboard being drawn on this thread.
+    pub fn current() -> Arc<Self> {
+        CURRENT.with_borrow(Clone::clone)
+    }
+
+    /// Run `f` with the context of the dashboard being drawn on this thread.
+    pub fn with<T>(f: impl FnOnce(&Self) -> T) -> T {
+        CURRENT.with_borrow(|context| f(context))
+    }
+
+    /// Run `f` with this context installed, for work that happens on another
+    /// thread such as in `spawn_blocking`. Whatever was installed before is put
+    /// back afterwards.
+    pub fn enter<T>(self: &Arc<Self>, f: impl FnOnce() -> T) -> T {
+        let previous = CURRENT.replace(self.clone());
+        let result = f();
+        CURRENT.set(previous);
+
+        result
+    }
+}
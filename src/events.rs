@@ -17,20 +17,47 @@ pub enum Broadcast {
     // occurred in it. The parent is expected to handle this as part of propagating the dispatch
     // back to the apex.
     Selected(usize),
+    // Copy the text to the client's clipboard, via. OSC 52.
+    Clipboard(String),
 }
 
+/// Events are how the dashboard is driven. Everything from keyboard input to
+/// shutting down is sent over the channel returned by
+/// [`Dashboard::start`](crate::Dashboard::start).
 #[derive(Debug, Clone)]
 pub enum Event {
+    /// Raw input from the user, create this with `Event::from(&[u8])`.
     Input(Input),
+    /// The terminal has changed size.
     Resize(WindowSize),
+    /// Navigate to a specific route.
     Goto(Vec<String>),
+    /// Stop the dashboard.
     Shutdown,
+    /// Render a frame, this happens on an interval internally.
     Render,
+    /// A raw mode widget has exited with the result.
     Finished(Result<(), StringError>),
+    /// The state of a tunnel has changed.
     Tunnel(Result<tunnel::Tunnel, tunnel::Error>),
+    /// A message from the server's operators that should be shown to the user.
+    Message(String),
+    /// The session will be disconnected for inactivity after this long unless
+    /// there's input.
+    Idle(std::time::Duration),
+    /// Access granted with `kty users grant --expires` runs out at this time,
+    /// the session is disconnected then unless RBAC allows it otherwise.
+    Expires(chrono::DateTime<chrono::Utc>),
+    /// Redraw the entire screen instead of only what has changed, for example
+    /// when someone starts watching.
+    Redraw,
+    /// A snapshot of the screen was saved, with where it ended up. See
+    /// [`crate::resources::snapshot`].
+    Snapshot(Result<String, StringError>),
 }
 
 impl Event {
+    /// The key that was pressed if this is an input event.
     pub fn key(&self) -> Option<&Keypress> {
         match self {
             Event::Input(Input { key, .. }) => Some(key),
@@ -78,7 +105,7 @@ impl<'a> From<&'a Input> for &'a [u8] {
 }
 
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Keypress {
     Null,
     Control(char),
@@ -175,13 +202,17 @@ impl From<&[u8]> for Keypress {
             b'\x0F' => Keypress::ShiftIn,
             b'\x10' => Keypress::DLE,
             b'\x11' => Keypress::XON,
-            b'\x12' => Keypress::DC2,
-            b'\x13' => Keypress::XOFF,
+            b'\x12' => Keypress::Control('r'),
+            // b'\x12' => Keypress::DC2,
+            b'\x13' => Keypress::Control('s'),
+            // b'\x13' => Keypress::XOFF,
             b'\x14' => Keypress::DC4,
-            b'\x15' => Keypress::NAK,
+            b'\x15' => Keypress::Control('u'),
+            // b'\x15' => Keypress::NAK,
             b'\x16' => Keypress::SYN,
             b'\x17' => Keypress::ETB,
-            b'\x18' => Keypress::Cancel,
+            b'\x18' => Keypress::Control('x'),
+            // b'\x18' => Keypress::Cancel,
             b'\x19' => Keypress::EM,
             b'\x1A' => Keypress::Substitute,
             b'\x1b' => parse_escape(data),
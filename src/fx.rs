@@ -1,3 +1,5 @@
+use std::time::Instant;
+
 use bon::{builder, Builder};
 use eyre::Result;
 use ndarray::{s, ArrayViewMut2};
@@ -5,18 +7,25 @@ use ratatui::{buffer::Buffer, layout::Rect, Frame};
 use tachyonfx::{CellFilter, CellIterator, Duration, Effect, EffectRenderer, EffectTimer, Shader};
 
 use crate::{
-    dashboard::RENDER_INTERVAL,
     events::{Broadcast, Event},
-    widget::{BoxWidget, Placement, Widget},
+    widget::{caps::Capabilities, BoxWidget, Placement, Widget},
 };
 
 #[derive(Builder)]
 pub struct Animated {
     effect: Option<Effect>,
     widget: BoxWidget,
+    // Effects advance by the time since the last frame, the frame rate isn't
+    // fixed.
+    #[builder(skip)]
+    last: Option<Instant>,
 }
 
 impl Widget for Animated {
+    fn tree(&self) -> Vec<String> {
+        self.widget.tree()
+    }
+
     fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
         self.widget.dispatch(event, buffer, area)
     }
@@ -25,13 +34,20 @@ impl Widget for Animated {
         self.widget.draw(frame, area)?;
 
         if let Some(effect) = &mut self.effect {
-            if !effect.running() {
+            // Skipped effects aren't picked back up, it'd be odd for them to start
+            // part way through once the window is resized.
+            if !effect.running() || !Capabilities::current().animate(frame.area()) {
                 self.effect = None;
 
                 return Ok(());
             }
 
-            frame.render_effect(effect, area, RENDER_INTERVAL.into());
+            let elapsed = self
+                .last
+                .map_or(std::time::Duration::ZERO, |last| last.elapsed());
+            self.last = Some(Instant::now());
+
+            frame.render_effect(effect, area, elapsed.into());
         }
 
         Ok(())
@@ -44,6 +60,18 @@ impl Widget for Animated {
     fn zindex(&self) -> u16 {
         self.widget.zindex()
     }
+
+    fn title(&self) -> Vec<String> {
+        self.widget.title()
+    }
+
+    fn filter(&self) -> Option<String> {
+        self.widget.filter()
+    }
+
+    fn dirty(&self) -> bool {
+        self.effect.is_some() || self.widget.dirty()
+    }
 }
 
 #[derive(Clone, Default)]
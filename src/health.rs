@@ -1,22 +1,252 @@
-use std::result::Result;
+use std::{
+    collections::BTreeMap,
+    result::Result,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
+use chrono::{DateTime, Utc};
+use eyre::eyre;
+use futures::future::join3;
 use prometheus::{Encoder, TextEncoder};
+use serde::Serialize;
+use subtle::ConstantTimeEq;
+use tokio::sync::Mutex;
 use warp::{
+    http::StatusCode,
     reject::{self, Reject},
     Rejection, Reply,
 };
 
+use crate::{dashboard, openid, ssh::Controller};
+
+static CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+// Probes can be frequent, there's no reason to hit the `OpenID` provider every
+// time one comes in.
+static JWKS_MAX_AGE: Duration = Duration::from_secs(300);
+static VERSION: &str = env!("CARGO_PKG_VERSION");
+// Set by `build.rs` when building from a checkout.
+static COMMIT: Option<&str> = option_env!("KTY_COMMIT");
+
 #[allow(dead_code)]
 #[derive(Debug)]
 struct GatherError(prometheus::Error);
 
 impl Reject for GatherError {}
 
-pub async fn metrics() -> Result<impl Reply, Rejection> {
+/// Whether `authorization` is `token` as a bearer token. Compared in constant
+/// time so that how long a rejection takes says nothing about the token.
+pub(crate) fn bearer(authorization: Option<&str>, token: &str) -> bool {
+    authorization.is_some_and(|authorization| {
+        authorization
+            .as_bytes()
+            .ct_eq(format!("Bearer {token}").as_bytes())
+            .into()
+    })
+}
+
+fn authorized(authorization: Option<&str>, token: Option<&str>) -> bool {
+    token.map_or(true, |token| bearer(authorization, token))
+}
+
+/// Prometheus metrics. When `token` is set, requests must use it as a bearer
+/// token, health checks stay open so that probes work without it.
+pub async fn metrics(
+    authorization: Option<String>,
+    token: Option<Arc<str>>,
+) -> Result<impl Reply, Rejection> {
+    if !authorized(authorization.as_deref(), token.as_deref()) {
+        return Ok(warp::reply::with_status(
+            b"unauthorized".to_vec(),
+            StatusCode::UNAUTHORIZED,
+        ));
+    }
+
     let mut buffer = Vec::new();
     TextEncoder::new()
         .encode(&prometheus::gather(), &mut buffer)
         .map_err(|err| reject::custom(GatherError(err)))?;
 
-    Ok(buffer)
+    Ok(warp::reply::with_status(buffer, StatusCode::OK))
+}
+
+pub async fn healthz() -> Result<impl Reply, Rejection> {
+    Ok("ok")
+}
+
+/// Dependencies that must be available for the server to accept connections.
+#[derive(Clone)]
+pub struct Readiness {
+    client: kube::Client,
+    openid: Option<openid::Config>,
+    // The provider isn't required when break-glass access is enabled.
+    break_glass: bool,
+
+    jwks_fetched: Arc<Mutex<Option<DateTime<Utc>>>>,
+    listening: Arc<AtomicBool>,
+    started: DateTime<Utc>,
+}
+
+impl Readiness {
+    pub fn new(client: kube::Client, openid: Option<openid::Config>, break_glass: bool) -> Self {
+        Self {
+            client,
+            openid,
+            break_glass,
+            jwks_fetched: Arc::default(),
+            listening: Arc::default(),
+            started: Utc::now(),
+        }
+    }
+
+    /// Mark the SSH listener as bound.
+    pub fn listening(&self) {
+        self.listening.store(true, Ordering::Relaxed);
+    }
+
+    async fn kube(&self) -> eyre::Result<()> {
+        tokio::time::timeout(CHECK_TIMEOUT, self.client.apiserver_version()).await??;
+
+        Ok(())
+    }
+
+    async fn jwks(&self) -> eyre::Result<()> {
+        let mut fetched = self.jwks_fetched.lock().await;
+
+        if fetched.is_some_and(|at| (Utc::now() - at).to_std().unwrap_or_default() < JWKS_MAX_AGE) {
+            return Ok(());
+        }
+
+        let Some(openid) = self.openid.as_ref() else {
+            return Err(eyre!("provider was unavailable on startup"));
+        };
+
+        let jwks = tokio::time::timeout(CHECK_TIMEOUT, openid.jwks()).await??;
+
+        if jwks.keys.is_empty() {
+            return Err(eyre!("no keys returned"));
+        }
+
+        *fetched = Some(Utc::now());
+
+        Ok(())
+    }
+
+    async fn ssh(&self) -> eyre::Result<()> {
+        if !self.listening.load(Ordering::Relaxed) {
+            return Err(eyre!("listener not bound"));
+        }
+
+        Ok(())
+    }
+}
+
+pub async fn readyz(readiness: Readiness) -> Result<impl Reply, Rejection> {
+    let (kube, jwks, ssh) = join3(readiness.kube(), readiness.jwks(), readiness.ssh()).await;
+
+    let checks = [
+        ("kube", kube, true),
+        ("jwks", jwks, !readiness.break_glass),
+        ("ssh", ssh, true),
+    ];
+
+    let status = if checks
+        .iter()
+        .all(|(_, result, required)| result.is_ok() || !required)
+    {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    let body = checks
+        .iter()
+        .map(|(name, result, _)| match result {
+            Ok(()) => format!("[+]{name} ok\n"),
+            Err(err) => format!("[-]{name} failed: {err}\n"),
+        })
+        .collect::<String>();
+
+    Ok(warp::reply::with_status(body, status))
+}
+
+#[derive(Serialize)]
+struct Sessions {
+    /// Every open connection, including ones that haven't authenticated yet.
+    active: usize,
+    authenticated: usize,
+}
+
+#[derive(Serialize)]
+struct Dashboards {
+    /// Running on this server, including detached ones.
+    active: i64,
+    /// Waiting for their user to reconnect.
+    detached: usize,
+}
+
+#[derive(Serialize)]
+struct Check {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// What `/status` returns, for dashboards that would rather not parse
+/// Prometheus metrics. Fields can be added but not changed.
+#[derive(Serialize)]
+pub struct Status {
+    version: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commit: Option<&'static str>,
+    started: DateTime<Utc>,
+    uptime_seconds: i64,
+    sessions: Sessions,
+    dashboards: Dashboards,
+    /// Sessions that have authenticated since the server started, by method.
+    auth: BTreeMap<String, u64>,
+    kube: Check,
+}
+
+/// The state of this server as JSON. Like `/metrics`, this requires `token`
+/// when one is set.
+pub async fn status(
+    authorization: Option<String>,
+    token: Option<Arc<str>>,
+    readiness: Readiness,
+    ctrl: Arc<Controller>,
+) -> Result<warp::reply::Response, Rejection> {
+    if !authorized(authorization.as_deref(), token.as_deref()) {
+        return Ok(
+            warp::reply::with_status("unauthorized", StatusCode::UNAUTHORIZED).into_response(),
+        );
+    }
+
+    let sessions = ctrl.sessions().sessions();
+    let kube = readiness.kube().await;
+
+    let status = Status {
+        version: VERSION,
+        commit: COMMIT,
+        started: readiness.started,
+        uptime_seconds: (Utc::now() - readiness.started).num_seconds(),
+        sessions: Sessions {
+            active: sessions.len(),
+            authenticated: sessions.iter().filter(|s| s.user.is_some()).count(),
+        },
+        dashboards: Dashboards {
+            active: dashboard::active(),
+            detached: ctrl.detached().count(),
+        },
+        auth: crate::ssh::session::authenticated(),
+        kube: Check {
+            ok: kube.is_ok(),
+            error: kube.err().map(|err| err.to_string()),
+        },
+    };
+
+    Ok(warp::reply::json(&status).into_response())
 }
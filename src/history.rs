@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+use eyre::Result;
+
+/// Commands a user has previously run in containers. Containers rarely keep
+/// shell history around, so this is kept by kty instead and is only recorded
+/// for users that have opted in.
+#[async_trait::async_trait]
+pub trait History: Send + Sync {
+    /// Previous commands, most recent first. `None` when the user hasn't
+    /// opted in.
+    async fn load(&self) -> Result<Option<Vec<String>>>;
+
+    /// Start recording commands for the user.
+    async fn enable(&self) -> Result<()>;
+
+    /// Stop recording commands and forget everything that has been recorded.
+    async fn disable(&self) -> Result<()>;
+
+    /// Record a command, this does nothing if the user hasn't opted in.
+    async fn push(&self, command: String) -> Result<()>;
+
+    /// What has been entered in inputs such as filters, by the kind of input.
+    /// Oldest first, empty when the user hasn't opted in.
+    async fn inputs(&self) -> Result<HashMap<String, Vec<String>>> {
+        Ok(HashMap::new())
+    }
+
+    /// Record an entry for a kind of input, this does nothing if the user
+    /// hasn't opted in.
+    async fn push_input(&self, _kind: String, _entry: String) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Used when there's nowhere to store history.
+pub struct Disabled;
+
+#[async_trait::async_trait]
+impl History for Disabled {
+    async fn load(&self) -> Result<Option<Vec<String>>> {
+        Ok(None)
+    }
+
+    async fn enable(&self) -> Result<()> {
+        Err(eyre::eyre!("command history is not available"))
+    }
+
+    async fn disable(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn push(&self, _: String) -> Result<()> {
+        Ok(())
+    }
+}
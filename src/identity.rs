@@ -1,15 +1,29 @@
+#[cfg(feature = "ssh")]
+pub mod certificate;
+#[cfg(feature = "ssh")]
+pub mod grant;
+#[cfg(feature = "ssh")]
+pub mod history;
+#[cfg(feature = "ssh")]
 pub mod key;
+#[cfg(feature = "ssh")]
+pub mod otp;
 
 use std::fmt::Display;
 
-use eyre::Result;
+#[cfg(feature = "ssh")]
+use eyre::{Result, WrapErr};
+#[cfg(feature = "ssh")]
 use k8s_openapi::api::authorization::v1::{
     ResourceAttributes, SelfSubjectAccessReview, SelfSubjectAccessReviewSpec,
     SubjectAccessReviewStatus,
 };
+#[cfg(feature = "ssh")]
 pub use key::Key;
+#[cfg(feature = "ssh")]
 use kube::api::{Api, PostParams};
 
+#[cfg(feature = "ssh")]
 use crate::ssh::{Authenticate, Controller};
 
 #[derive(Clone, Debug)]
@@ -33,15 +47,42 @@ impl Identity {
         self
     }
 
+    #[cfg(feature = "ssh")]
     pub fn client(&self, ctrl: &Controller) -> Result<kube::Client, kube::Error> {
         ctrl.impersonate(self.name.clone(), self.groups.clone())
     }
 }
 
+#[cfg(feature = "ssh")]
 #[async_trait::async_trait]
 impl Authenticate for Identity {
     #[tracing::instrument(skip(self, ctrl))]
     async fn authenticate(&self, ctrl: &Controller) -> Result<Option<Identity>> {
+        // Policies can only take away, logging in without knowing them would
+        // give everything RBAC allows.
+        ctrl.policy(&self.name, &self.groups)
+            .wrap_err("unable to check access policies")?;
+
+        // Expired grants stick around until they're removed, see `grant::reaper`,
+        // and RBAC still honors them. The user's own grant having expired keeps
+        // them out until then, everything else is up to RBAC. RBAC still has the
+        // final say when the grant can't be looked up.
+        match grant::expired(ctrl.client()?, self).await {
+            Ok(true) => {
+                tracing::info!(user = self.name, "grant has expired");
+
+                return Ok(None);
+            }
+            Ok(false) => {}
+            Err(err) => {
+                tracing::warn!(
+                    ?err,
+                    user = self.name,
+                    "unable to check for an expired grant"
+                );
+            }
+        }
+
         let client = self.client(ctrl)?;
 
         let access = Api::<SelfSubjectAccessReview>::all(client.clone())
@@ -79,6 +120,7 @@ impl Display for Identity {
     }
 }
 
+#[cfg(feature = "ssh")]
 impl From<key::Key> for Identity {
     fn from(key: key::Key) -> Self {
         Self {
@@ -88,3 +130,72 @@ impl From<key::Key> for Identity {
         }
     }
 }
+
+/// Why a login didn't work, shown to the user the next time they're prompted
+/// so that they know what to do about it.
+#[derive(Clone, Debug)]
+pub enum Rejection {
+    /// The key offered isn't bound to anyone, the user goes through onboarding.
+    UnknownKey,
+    /// The key is bound but waiting for an admin to approve it.
+    KeyPending,
+    /// The device code wasn't used in time.
+    Expired,
+    /// The user declined at the provider.
+    Denied,
+    /// The provider hasn't verified the email address the identity would be
+    /// named after, anyone could have claimed it.
+    EmailUnverified,
+    /// The `id_token` doesn't have the claim identities are named after.
+    MissingClaim(String),
+    /// RBAC doesn't let the identity list pods.
+    NoAccess(Identity),
+    /// The certificate offered isn't one that can be used to log in, such as
+    /// one from an untrusted authority or that has expired.
+    Certificate(String),
+}
+
+impl Rejection {
+    /// Stable name for metrics and logs.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::UnknownKey => "unknown_key",
+            Self::KeyPending => "key_pending",
+            Self::Expired => "expired",
+            Self::Denied => "denied",
+            Self::EmailUnverified => "email_unverified",
+            Self::MissingClaim(_) => "missing_claim",
+            Self::NoAccess(_) => "no_access",
+            Self::Certificate(_) => "certificate",
+        }
+    }
+}
+
+impl Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownKey => write!(f, "This key isn't registered yet."),
+            Self::KeyPending => write!(f, "This key is waiting for an admin to approve it."),
+            Self::Expired => write!(f, "The login link expired before it was used."),
+            Self::Denied => write!(f, "Login was denied by the identity provider."),
+            Self::EmailUnverified => write!(
+                f,
+                "Your email address hasn't been verified. Verify it with the identity provider \
+                 and try again."
+            ),
+            Self::MissingClaim(claim) => write!(
+                f,
+                "The identity provider didn't include `{claim}` in the token, ask an admin to \
+                 check the server's --claim."
+            ),
+            Self::NoAccess(identity) => write!(
+                f,
+                "{identity} doesn't have access to this cluster. Ask an admin to bind a role that \
+                 can list pods."
+            ),
+            Self::Certificate(reason) => write!(f, "Your certificate was rejected: {reason}."),
+        }
+    }
+}
+
+impl std::error::Error for Rejection {}
@@ -0,0 +1,352 @@
+use std::{net::IpAddr, path::Path};
+
+use eyre::{eyre, Result, WrapErr};
+use ssh_key::{certificate::CertType, Certificate, Fingerprint, HashAlg, PublicKey};
+
+use super::Identity;
+
+/// Principals with this prefix are groups rather than users, `group:admins`
+/// puts the user in `admins`.
+pub static GROUP_PREFIX: &str = "group:";
+
+// Limits the addresses a certificate can be used from, a comma separated list
+// of addresses and CIDR ranges.
+static SOURCE_ADDRESS: &str = "source-address";
+
+/// Certificate authorities trusted to sign user certificates. This is the same
+/// as `TrustedUserCAKeys` for `sshd`, one public key per line.
+#[derive(Clone, Debug, Default)]
+pub struct Authorities {
+    fingerprints: Vec<Fingerprint>,
+}
+
+impl Authorities {
+    /// Blank lines and lines starting with `#` are skipped.
+    pub fn parse(keys: &str) -> Result<Self> {
+        let fingerprints = keys
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                PublicKey::from_openssh(line)
+                    .map(|key| key.fingerprint(HashAlg::Sha256))
+                    .wrap_err_with(|| format!("invalid certificate authority: {line}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if fingerprints.is_empty() {
+            return Err(eyre!("no certificate authorities"));
+        }
+
+        Ok(Self { fingerprints })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+            .wrap_err_with(|| format!("unable to load {}", path.display()))
+    }
+
+    /// Who `certificate` was issued to. It has to be a user certificate, signed
+    /// by one of these authorities and valid at `now` (seconds since the
+    /// epoch). Principals starting with [`GROUP_PREFIX`] are the user's groups,
+    /// the other one is the Kubernetes user. When there is more than one,
+    /// `login`, the name used to log in, picks which. Otherwise, the login name
+    /// is free to request what the session shows like with any other login.
+    ///
+    /// Certificates without principals are rejected, `sshd` treats those as
+    /// valid for anyone. Like `sshd`, so are certificates with critical options
+    /// other than `source-address`, which has to include `addr`, the client's
+    /// address.
+    pub fn identity(
+        &self,
+        login: &str,
+        certificate: &Certificate,
+        addr: Option<IpAddr>,
+        now: u64,
+    ) -> Result<Identity> {
+        if certificate.cert_type() != CertType::User {
+            return Err(eyre!("{} isn't a user certificate", certificate.key_id()));
+        }
+
+        for (name, value) in certificate.critical_options().iter() {
+            if name.as_str() != SOURCE_ADDRESS {
+                return Err(eyre!(
+                    "{} has the critical option {name}, which isn't supported",
+                    certificate.key_id()
+                ));
+            }
+
+            if !addr.is_some_and(|addr| source_allowed(value, addr)) {
+                return Err(eyre!(
+                    "{} can't be used from this address",
+                    certificate.key_id()
+                ));
+            }
+        }
+
+        certificate
+            .validate_at(now, &self.fingerprints)
+            .map_err(|_| {
+                eyre!(
+                    "{} isn't signed by a trusted authority or isn't valid right now",
+                    certificate.key_id()
+                )
+            })?;
+
+        let (groups, users): (Vec<_>, Vec<_>) = certificate
+            .valid_principals()
+            .iter()
+            .partition(|principal| principal.starts_with(GROUP_PREFIX));
+
+        let user = match users.as_slice() {
+            [] => return Err(eyre!("{} wasn't issued to a user", certificate.key_id())),
+            [user] => user,
+            _ => users
+                .iter()
+                .find(|principal| **principal == login)
+                .ok_or_else(|| {
+                    eyre!(
+                        "{} was issued to more than one user, log in as one of them",
+                        certificate.key_id()
+                    )
+                })?,
+        };
+
+        let groups = groups
+            .into_iter()
+            .filter_map(|principal| principal.strip_prefix(GROUP_PREFIX))
+            .map(ToString::to_string)
+            .collect();
+
+        Ok(Identity::new((*user).clone(), groups).method("certificate".to_string()))
+    }
+}
+
+// Whether `addr` is in one of the comma separated addresses or CIDR ranges in
+// `allowed`. Anything that can't be parsed doesn't match.
+fn source_allowed(allowed: &str, addr: IpAddr) -> bool {
+    let addr = addr.to_canonical();
+
+    allowed.split(',').any(|entry| {
+        let (network, bits) = match entry.trim().split_once('/') {
+            Some((network, bits)) => (network, bits.parse::<u32>().ok()),
+            None => (entry.trim(), None),
+        };
+
+        match (network.parse::<IpAddr>(), addr) {
+            (Ok(IpAddr::V4(network)), IpAddr::V4(addr)) => {
+                matches(u32::from(network).into(), u32::from(addr).into(), bits, 32)
+            }
+            (Ok(IpAddr::V6(network)), IpAddr::V6(addr)) => {
+                matches(network.into(), addr.into(), bits, 128)
+            }
+            _ => false,
+        }
+    })
+}
+
+// Whether the first `bits` of `network` and `addr` are the same, they're the
+// first `width` bits of each.
+fn matches(network: u128, addr: u128, bits: Option<u32>, width: u32) -> bool {
+    let bits = bits.unwrap_or(width);
+
+    if bits > width {
+        return false;
+    }
+
+    let mask = u128::MAX.checked_shl(width - bits).unwrap_or_default();
+
+    network & mask == addr & mask
+}
+
+#[cfg(test)]
+mod tests {
+    use ssh_key::{certificate::Builder, rand_core::OsRng, Algorithm, PrivateKey};
+
+    use super::*;
+
+    static NOW: u64 = 1_700_000_000;
+
+    fn key() -> PrivateKey {
+        PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap()
+    }
+
+    fn authorities(ca: &PrivateKey) -> Authorities {
+        Authorities::parse(&ca.public_key().to_openssh().unwrap()).unwrap()
+    }
+
+    fn certificate(ca: &PrivateKey, cert_type: CertType, principals: &[&str]) -> Certificate {
+        with_options(ca, cert_type, principals, &[])
+    }
+
+    fn with_options(
+        ca: &PrivateKey,
+        cert_type: CertType,
+        principals: &[&str],
+        options: &[(&str, &str)],
+    ) -> Certificate {
+        let mut builder = Builder::new_with_random_nonce(
+            &mut OsRng,
+            key().public_key().key_data().clone(),
+            NOW - 60,
+            NOW + 60,
+        )
+        .unwrap();
+
+        builder
+            .cert_type(cert_type)
+            .unwrap()
+            .key_id("test")
+            .unwrap();
+
+        if principals.is_empty() {
+            builder.all_principals_valid().unwrap();
+        }
+
+        for principal in principals {
+            builder.valid_principal(*principal).unwrap();
+        }
+
+        for (name, value) in options {
+            builder.critical_option(*name, *value).unwrap();
+        }
+
+        builder.sign(ca).unwrap()
+    }
+
+    #[test]
+    fn maps_principals() {
+        let ca = key();
+        let cert = certificate(&ca, CertType::User, &["jane", "group:admins"]);
+
+        let identity = authorities(&ca).identity("jane", &cert, None, NOW).unwrap();
+
+        assert_eq!(identity.name, "jane");
+        assert_eq!(identity.groups, vec!["admins".to_string()]);
+        assert_eq!(identity.method.as_deref(), Some("certificate"));
+    }
+
+    #[test]
+    fn picks_the_user_by_login() {
+        let ca = key();
+        let authorities = authorities(&ca);
+
+        let single = certificate(&ca, CertType::User, &["jane", "group:admins"]);
+        assert_eq!(
+            authorities
+                .identity("pods", &single, None, NOW)
+                .unwrap()
+                .name,
+            "jane"
+        );
+
+        let multiple = certificate(&ca, CertType::User, &["jane", "john"]);
+        assert_eq!(
+            authorities
+                .identity("john", &multiple, None, NOW)
+                .unwrap()
+                .name,
+            "john"
+        );
+        assert!(authorities.identity("pods", &multiple, None, NOW).is_err());
+    }
+
+    #[test]
+    fn requires_a_user_principal() {
+        let ca = key();
+        let cert = certificate(&ca, CertType::User, &["group:admins"]);
+
+        assert!(authorities(&ca)
+            .identity("group:admins", &cert, None, NOW)
+            .is_err());
+    }
+
+    #[test]
+    fn rejects_without_principals() {
+        let ca = key();
+        let cert = certificate(&ca, CertType::User, &[]);
+
+        assert!(authorities(&ca).identity("jane", &cert, None, NOW).is_err());
+    }
+
+    #[test]
+    fn rejects_untrusted_authorities() {
+        let cert = certificate(&key(), CertType::User, &["jane"]);
+
+        assert!(authorities(&key())
+            .identity("jane", &cert, None, NOW)
+            .is_err());
+    }
+
+    #[test]
+    fn enforces_validity() {
+        let ca = key();
+        let cert = certificate(&ca, CertType::User, &["jane"]);
+        let authorities = authorities(&ca);
+
+        assert!(authorities.identity("jane", &cert, None, NOW - 61).is_err());
+        assert!(authorities.identity("jane", &cert, None, NOW + 60).is_err());
+        assert!(authorities.identity("jane", &cert, None, NOW + 59).is_ok());
+    }
+
+    #[test]
+    fn rejects_host_certificates() {
+        let ca = key();
+        let cert = certificate(&ca, CertType::Host, &["jane"]);
+
+        assert!(authorities(&ca).identity("jane", &cert, None, NOW).is_err());
+    }
+
+    #[test]
+    fn enforces_source_address() {
+        let ca = key();
+        let authorities = authorities(&ca);
+        let cert = with_options(
+            &ca,
+            CertType::User,
+            &["jane"],
+            &[("source-address", "10.0.0.0/8,2001:db8::1")],
+        );
+
+        for (addr, allowed) in [
+            ("10.1.2.3", true),
+            ("::ffff:10.1.2.3", true),
+            ("2001:db8::1", true),
+            ("11.0.0.1", false),
+            ("2001:db8::2", false),
+        ] {
+            let addr = Some(addr.parse().unwrap());
+
+            assert_eq!(
+                authorities.identity("jane", &cert, addr, NOW).is_ok(),
+                allowed,
+                "{addr:?}"
+            );
+        }
+
+        assert!(authorities.identity("jane", &cert, None, NOW).is_err());
+    }
+
+    #[test]
+    fn rejects_unsupported_critical_options() {
+        let ca = key();
+        let cert = with_options(
+            &ca,
+            CertType::User,
+            &["jane"],
+            &[("force-command", "/bin/true")],
+        );
+
+        assert!(authorities(&ca).identity("jane", &cert, None, NOW).is_err());
+    }
+
+    #[test]
+    fn parses_authorities() {
+        let ca = key();
+        let keys = format!("# trusted\n\n{}\n", ca.public_key().to_openssh().unwrap());
+
+        assert!(Authorities::parse(&keys).is_ok());
+        assert!(Authorities::parse("# nothing\n").is_err());
+        assert!(Authorities::parse("not a key").is_err());
+    }
+}
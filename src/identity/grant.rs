@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use k8s_openapi::api::rbac::v1::{ClusterRoleBinding, RoleRef, Subject};
+use kube::{
+    api::{Api, DeleteParams, ListParams, ObjectMeta},
+    ResourceExt,
+};
+
+use super::Identity;
+use crate::{resources::KubeID, ssh::leader::Leader};
+
+static CREATED_BY: &str = "app.kubernetes.io/created-by";
+/// Annotation on grants with when they expire, as RFC 3339. Grants without it
+/// don't expire.
+pub static EXPIRES: &str = "kty.dev/expires";
+
+// Impersonated users are always in this group, bindings for it apply to
+// everyone.
+static AUTHENTICATED: &str = "system:authenticated";
+
+// How often the leader looks for grants that have expired.
+static REAP_EVERY: Duration = Duration::from_secs(60);
+
+fn name(id: &str) -> Result<String> {
+    Ok(format!("kty:{id}").kube_id()?)
+}
+
+/// A `ClusterRoleBinding` granting `role` to the user `id`, named `kty-<id>`.
+/// Expired grants are deleted by the server, see [`reaper`].
+pub fn binding(role: &str, id: &str, expires: Option<DateTime<Utc>>) -> Result<ClusterRoleBinding> {
+    Ok(ClusterRoleBinding {
+        metadata: ObjectMeta {
+            name: Some(name(id)?),
+            labels: Some([(CREATED_BY.to_string(), "kty".to_string())].into()),
+            annotations: expires.map(|at| [(EXPIRES.to_string(), at.to_rfc3339())].into()),
+            ..Default::default()
+        },
+        role_ref: RoleRef {
+            kind: "ClusterRole".to_string(),
+            name: role.to_string(),
+            ..Default::default()
+        },
+        subjects: Some(vec![Subject {
+            kind: "User".to_string(),
+            name: id.to_string(),
+            ..Default::default()
+        }]),
+    })
+}
+
+pub fn expiration(binding: &ClusterRoleBinding) -> Option<DateTime<Utc>> {
+    binding
+        .annotations()
+        .get(EXPIRES)
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        .map(|at| at.with_timezone(&Utc))
+}
+
+fn applies(binding: &ClusterRoleBinding, identity: &Identity) -> bool {
+    binding
+        .subjects
+        .iter()
+        .flatten()
+        .any(|s| match s.kind.as_str() {
+            "User" => s.name == identity.name,
+            "Group" => s.name == AUTHENTICATED || identity.groups.contains(&s.name),
+            _ => false,
+        })
+}
+
+async fn grants(client: kube::Client) -> Result<Vec<ClusterRoleBinding>> {
+    Ok(Api::<ClusterRoleBinding>::all(client)
+        .list(&ListParams::default().labels(&format!("{CREATED_BY}=kty")))
+        .await?
+        .items)
+}
+
+/// When the grants `identity` has run out. `None` when none of them expire,
+/// access can come from elsewhere in RBAC as well so this only says when to
+/// check again.
+pub async fn expiration_for(
+    client: kube::Client,
+    identity: &Identity,
+) -> Result<Option<DateTime<Utc>>> {
+    let expirations: Option<Vec<_>> = grants(client)
+        .await?
+        .iter()
+        .filter(|binding| applies(binding, identity))
+        .map(expiration)
+        .collect();
+
+    Ok(expirations.and_then(|all| all.into_iter().max()))
+}
+
+fn has_expired(binding: &ClusterRoleBinding, now: DateTime<Utc>) -> bool {
+    expiration(binding).is_some_and(|at| at <= now)
+}
+
+/// Whether the grant for `identity`, the one `kty users grant` created for the
+/// user, has expired. It is looked up by name, bindings for groups or from
+/// anywhere else are left to RBAC. Bindings are only deleted once [`reaper`]
+/// gets to them, until then RBAC still honors them.
+pub async fn expired(client: kube::Client, identity: &Identity) -> Result<bool> {
+    let binding = Api::<ClusterRoleBinding>::all(client)
+        .get_opt(&name(&identity.name)?)
+        .await?;
+
+    Ok(binding.is_some_and(|binding| {
+        binding
+            .labels()
+            .get(CREATED_BY)
+            .is_some_and(|by| by == "kty")
+            && has_expired(&binding, Utc::now())
+    }))
+}
+
+/// Delete expired grants every minute, while `leader` holds the lease. Without
+/// permission to delete, expired grants are left for an administrator and
+/// [`expired`] keeps them from being used to log in.
+pub async fn reaper(client: kube::Client, leader: Leader) {
+    let mut interval = tokio::time::interval(REAP_EVERY);
+
+    loop {
+        interval.tick().await;
+
+        if !leader.is_leading() {
+            continue;
+        }
+
+        match reap(client.clone()).await {
+            Ok(0) => {}
+            Ok(reaped) => tracing::info!(reaped, "removed expired grants"),
+            Err(err) => tracing::warn!(?err, "unable to remove expired grants"),
+        }
+    }
+}
+
+/// Delete every grant that has expired, returning how many there were.
+pub async fn reap(client: kube::Client) -> Result<usize> {
+    let api = Api::<ClusterRoleBinding>::all(client.clone());
+    let now = Utc::now();
+
+    let mut reaped = 0;
+
+    for binding in grants(client).await? {
+        if !has_expired(&binding, now) {
+            continue;
+        }
+
+        api.delete(&binding.name_any(), &DeleteParams::default())
+            .await?;
+
+        reaped += 1;
+    }
+
+    Ok(reaped)
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use eyre::Result;
+use kube::{
+    api::{Api, DeleteParams, Patch, PatchParams, PostParams},
+    core::ErrorResponse,
+    CustomResource, ResourceExt,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::resources::KubeID;
+
+/// Number of commands, and entries for each kind of input, kept for each user.
+static LIMIT: usize = 100;
+// Sessions for the same user update the same resource, conflicting updates are
+// tried again with what the other session wrote.
+static MAX_CONFLICTS: usize = 5;
+
+// TODO: make it possible for kube-derive to consume a variable for
+// group/version
+#[allow(clippy::module_name_repetitions)]
+#[derive(CustomResource, Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[kube(group = "kty.dev", version = "v1alpha1", kind = "History", namespaced)]
+pub struct HistorySpec {
+    pub user: String,
+    /// Commands, most recent first.
+    pub commands: Vec<String>,
+    /// Entries for inputs such as filters, by the kind of input. Oldest first.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub inputs: HashMap<String, Vec<String>>,
+}
+
+/// Command history for a user, stored alongside keys with the server's own
+/// permissions. A `History` resource existing for the user is what opts them
+/// in.
+#[allow(clippy::module_name_repetitions)]
+pub struct UserHistory {
+    client: Api<History>,
+    user: String,
+}
+
+impl UserHistory {
+    pub fn new(client: kube::Client, user: String) -> Self {
+        Self {
+            client: Api::default_namespaced(client),
+            user,
+        }
+    }
+
+    async fn get(&self) -> Result<Option<History>> {
+        let history = self.client.get_opt(&self.user.kube_id()?).await?;
+
+        // IDs are lossy, make sure this is actually for the current user.
+        Ok(history.filter(|h| h.spec.user == self.user))
+    }
+
+    // Patches `spec` with what `change` returns for the current history. The
+    // patch carries the `resourceVersion` it was based on, so that another
+    // session pushing at the same time doesn't get overwritten.
+    async fn update<F>(&self, change: F) -> Result<()>
+    where
+        F: Fn(&HistorySpec) -> serde_json::Value + Send + Sync,
+    {
+        let mut conflicts = 0;
+
+        loop {
+            let Some(history) = self.get().await? else {
+                return Ok(());
+            };
+
+            let patch = json!({
+                "metadata": { "resourceVersion": history.resource_version() },
+                "spec": change(&history.spec),
+            });
+
+            match self
+                .client
+                .patch(
+                    &self.user.kube_id()?,
+                    &PatchParams::default(),
+                    &Patch::Merge(patch),
+                )
+                .await
+            {
+                Ok(_) => return Ok(()),
+                Err(kube::Error::Api(ErrorResponse { code: 409, .. }))
+                    if conflicts < MAX_CONFLICTS =>
+                {
+                    conflicts += 1;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::history::History for UserHistory {
+    #[tracing::instrument(skip(self))]
+    async fn load(&self) -> Result<Option<Vec<String>>> {
+        Ok(self.get().await?.map(|h| h.spec.commands))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn enable(&self) -> Result<()> {
+        if self.get().await?.is_some() {
+            return Ok(());
+        }
+
+        self.client
+            .create(
+                &PostParams::default(),
+                &History::new(
+                    &self.user.kube_id()?,
+                    HistorySpec {
+                        user: self.user.clone(),
+                        commands: Vec::new(),
+                        inputs: HashMap::new(),
+                    },
+                ),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn disable(&self) -> Result<()> {
+        if self.get().await?.is_none() {
+            return Ok(());
+        }
+
+        self.client
+            .delete(&self.user.kube_id()?, &DeleteParams::default())
+            .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, command))]
+    async fn push(&self, command: String) -> Result<()> {
+        self.update(|spec| {
+            let mut commands = spec.commands.clone();
+            commands.retain(|c| *c != command);
+            commands.insert(0, command.clone());
+            commands.truncate(LIMIT);
+
+            json!({ "commands": commands })
+        })
+        .await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn inputs(&self) -> Result<HashMap<String, Vec<String>>> {
+        Ok(self.get().await?.map(|h| h.spec.inputs).unwrap_or_default())
+    }
+
+    #[tracing::instrument(skip(self, entry))]
+    async fn push_input(&self, kind: String, entry: String) -> Result<()> {
+        self.update(|spec| {
+            let mut entries = spec.inputs.get(&kind).cloned().unwrap_or_default();
+            entries.retain(|e| *e != entry);
+            entries.push(entry.clone());
+
+            if entries.len() > LIMIT {
+                entries.drain(..entries.len() - LIMIT);
+            }
+
+            json!({ "inputs": { &kind: entries } })
+        })
+        .await
+    }
+}
@@ -1,13 +1,17 @@
+use base64::prelude::*;
 use chrono::{DateTime, Utc};
 use eyre::Result;
+use k8s_openapi::api::authorization::v1::{
+    ResourceAttributes, SelfSubjectAccessReview, SelfSubjectAccessReviewSpec,
+};
 use kube::{
-    api::{Api, Patch, PatchParams},
-    CustomResource, ResourceExt,
+    api::{Api, ListParams, Patch, PatchParams, PostParams},
+    CustomResource, Resource, ResourceExt,
 };
-use russh::keys::key::PublicKey;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use ssh_key::{HashAlg, PublicKey};
 
 use super::Identity;
 use crate::{
@@ -15,6 +19,13 @@ use crate::{
     ssh::{Authenticate, Controller},
 };
 
+/// Label on keys that are waiting for an admin, see
+/// [`crate::resources::settings::KtySettingsSpec::key_approval`]. Keys are
+/// approved by setting it to `true` or removing it.
+pub static APPROVED: &str = "kty.dev/approved";
+
+static LAST_USED_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
 // TODO: make it possible for kube-derive to consume a variable for
 // group/version
 #[allow(clippy::module_name_repetitions)]
@@ -59,6 +70,74 @@ impl Key {
         self.spec.expiration < Utc::now()
     }
 
+    /// Require an admin to approve the key before it can be used.
+    pub fn pending(mut self) -> Self {
+        self.meta_mut()
+            .labels
+            .get_or_insert_with(Default::default)
+            .insert(APPROVED.to_string(), "false".to_string());
+
+        self
+    }
+
+    pub fn approved(&self) -> bool {
+        self.labels().get(APPROVED).map_or(true, |v| v != "false")
+    }
+
+    /// The stored key for `key`, if there is one.
+    pub async fn find(client: kube::Client, key: &PublicKey) -> Result<Option<Self>> {
+        Ok(Api::<Key>::default_namespaced(client)
+            .get_opt(&key.kube_id()?)
+            .await?)
+    }
+
+    /// Every key bound to `user`.
+    pub async fn for_user(client: kube::Client, user: &str) -> Result<Vec<Self>> {
+        Ok(Api::<Key>::default_namespaced(client)
+            .list(&ListParams::default())
+            .await?
+            .into_iter()
+            .filter(|key| key.spec.user == user)
+            .collect())
+    }
+
+    /// Stop the key from working at `at`, keys are checked every time they're
+    /// used.
+    pub async fn expire(client: kube::Client, name: &str, at: DateTime<Utc>) -> Result<()> {
+        Api::<Key>::default_namespaced(client)
+            .patch(
+                name,
+                &PatchParams::default(),
+                &Patch::Merge(json!({
+                    "spec": {
+                        "expiration": at,
+                    }
+                })),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Allow a pending key to be used, see [`Key::pending`].
+    pub async fn approve(client: kube::Client, name: &str) -> Result<()> {
+        Api::<Key>::default_namespaced(client)
+            .patch(
+                name,
+                &PatchParams::default(),
+                &Patch::Merge(json!({
+                    "metadata": {
+                        "labels": {
+                            APPROVED: "true",
+                        }
+                    }
+                })),
+            )
+            .await?;
+
+        Ok(())
+    }
+
     #[tracing::instrument(skip(self, client))]
     pub async fn update(&self, client: kube::Client) -> Result<()> {
         Api::<Key>::default_namespaced(client)
@@ -71,6 +150,63 @@ impl Key {
 
         Ok(())
     }
+
+    // `last_used` only needs to be roughly right, it isn't updated more often
+    // than this so that reconnecting doesn't mean a write every time.
+    fn recently_used(&self) -> bool {
+        self.status.as_ref().is_some_and(|status| {
+            (Utc::now() - status.last_used)
+                .to_std()
+                .is_ok_and(|since| since < LAST_USED_INTERVAL)
+        })
+    }
+
+    /// Verify that keys can be read with the permissions of `client`. This
+    /// fails if the CRD hasn't been installed or access to it has been
+    /// restricted.
+    pub async fn available(client: kube::Client) -> Result<()> {
+        Api::<Key>::default_namespaced(client)
+            .list_metadata(&ListParams::default().limit(1))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Whether keys can be saved with the permissions of `client`. Being able
+    /// to read them isn't enough, binding a key creates it and logging in with
+    /// one updates its status.
+    pub async fn writable(client: kube::Client) -> Result<bool> {
+        let namespace = client.default_namespace().to_string();
+        let reviews = Api::<SelfSubjectAccessReview>::all(client);
+
+        for (verb, subresource) in [("create", None), ("patch", None), ("patch", Some("status"))] {
+            let review = reviews
+                .create(
+                    &PostParams::default(),
+                    &SelfSubjectAccessReview {
+                        spec: SelfSubjectAccessReviewSpec {
+                            resource_attributes: Some(ResourceAttributes {
+                                group: Some(Key::group(&()).to_string()),
+                                resource: Some(Key::plural(&()).to_string()),
+                                subresource: subresource.map(ToString::to_string),
+                                namespace: Some(namespace.clone()),
+                                verb: Some(verb.to_string()),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                )
+                .await?;
+
+            if !review.status.is_some_and(|status| status.allowed) {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -90,21 +226,26 @@ impl Default for KeyStatus {
 impl KubeID for PublicKey {
     fn kube_id(&self) -> Result<String> {
         // TODO: This feels wrong, but fingerprints can contain invalid id characters.
-        // Is there any reason this should be something else?
-        self.fingerprint().kube_id()
+        // Is there any reason this should be something else? The digest is encoded
+        // without the `SHA256:` prefix so that existing keys keep their names.
+        BASE64_STANDARD_NO_PAD
+            .encode(self.fingerprint(HashAlg::Sha256).as_bytes())
+            .kube_id()
     }
 }
 
 mod public_key {
-    use russh::keys::key::PublicKey;
-    use russh_keys::{parse_public_key_base64, PublicKeyBase64};
+    use base64::prelude::*;
     use serde::{Deserialize, Deserializer, Serializer};
+    use ssh_key::PublicKey;
 
     pub fn serialize<S>(key: &PublicKey, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        serializer.serialize_str(key.public_key_base64().as_str())
+        let bytes = key.to_bytes().map_err(serde::ser::Error::custom)?;
+
+        serializer.serialize_str(BASE64_STANDARD.encode(bytes).as_str())
     }
 
     pub fn deserialize<'de, D>(deserializer: D) -> Result<PublicKey, D::Error>
@@ -113,7 +254,10 @@ mod public_key {
     {
         let key = String::deserialize(deserializer)?;
 
-        parse_public_key_base64(&key).map_err(serde::de::Error::custom)
+        BASE64_STANDARD
+            .decode(key)
+            .map_err(serde::de::Error::custom)
+            .and_then(|bytes| PublicKey::from_bytes(&bytes).map_err(serde::de::Error::custom))
     }
 }
 
@@ -124,13 +268,25 @@ impl Authenticate for PublicKey {
     // - should there be a status field or condition for an existing key?
     #[tracing::instrument(skip(self, ctrl))]
     async fn authenticate(&self, ctrl: &Controller) -> Result<Option<Identity>> {
+        if !ctrl.keys_enabled() {
+            return Ok(None);
+        }
+
         let keys: Api<Key> = Api::default_namespaced(ctrl.client()?);
+        let id = self.kube_id()?;
 
-        let Some(key): Option<Key> = keys.get_opt(&self.kube_id()?).await? else {
+        // Scanners try keys by the thousands, the cache keeps those from ever
+        // reaching the API server.
+        let key = match ctrl.cached_key(&id) {
+            Some(key) => key.map(|key| key.as_ref().clone()),
+            None => keys.get_opt(&id).await?,
+        };
+
+        let Some(key) = key else {
             return Ok(None);
         };
 
-        if key.expired() {
+        if key.expired() || !key.approved() {
             return Ok(None);
         }
 
@@ -138,16 +294,25 @@ impl Authenticate for PublicKey {
             return Ok(None);
         };
 
-        keys.patch_status(
-            &key.name_any(),
-            &PatchParams::apply(MANAGER).force(),
-            &Patch::Apply(&Key::patch(&json!({
-                "status": {
-                    "last_used": Some(Utc::now()),
-                }
-            }))?),
-        )
-        .await?;
+        if key.recently_used() || !ctrl.keys_writable() {
+            return Ok(Some(ident));
+        }
+
+        // The key has already been checked, `last_used` is only informational.
+        if let Err(err) = keys
+            .patch_status(
+                &key.name_any(),
+                &PatchParams::apply(MANAGER).force(),
+                &Patch::Apply(&Key::patch(&json!({
+                    "status": {
+                        "last_used": Some(Utc::now()),
+                    }
+                }))?),
+            )
+            .await
+        {
+            tracing::warn!(?err, "unable to update when the key was last used");
+        }
 
         Ok(Some(ident))
     }
@@ -0,0 +1,351 @@
+use std::{
+    collections::HashMap,
+    fs,
+    net::{IpAddr, Ipv6Addr},
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use data_encoding::BASE32_NOPAD;
+use eyre::{eyre, Result, WrapErr};
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use subtle::ConstantTimeEq;
+
+use super::Identity;
+
+// RFC 6238 defaults, these are what authenticator apps expect.
+static STEP: i64 = 30;
+static DIGITS: u32 = 6;
+
+// Failed codes allowed from a single source before codes from it stop being
+// checked for a while.
+static MAX_FAILURES: u32 = 5;
+// IPv6 clients usually get a whole /64, sources are counted by it so that
+// rotating addresses inside of it doesn't get more guesses.
+static IPV6_PREFIX: u32 = 64;
+// How long the first lockout lasts, each one after that is twice as long up to
+// `MAX_LOCKOUT_SECS`. A success resets it.
+static LOCKOUT_SECS: i64 = 60;
+static MAX_LOCKOUT_SECS: i64 = 60 * 60;
+// Sources that haven't failed for this long are forgotten, so that the
+// addresses being tracked don't grow forever.
+static FORGET_SECS: i64 = 24 * 60 * 60;
+
+/// The outcome of a break-glass code, see [`BreakGlass::verify`].
+#[derive(Debug)]
+pub enum Verdict {
+    Accepted(Identity),
+    /// `locked` is set when this failure used up the last attempt, codes are
+    /// then refused until that time.
+    Rejected {
+        locked: Option<DateTime<Utc>>,
+    },
+    /// Too many codes have failed, this one wasn't checked.
+    Locked(DateTime<Utc>),
+}
+
+#[derive(Debug, Default)]
+struct Attempts {
+    failures: u32,
+    lockouts: u32,
+    locked_until: Option<DateTime<Utc>>,
+    last_failure: Option<DateTime<Utc>>,
+}
+
+impl Attempts {
+    fn locked(&mut self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let until = self.locked_until?;
+
+        if now < until {
+            return Some(until);
+        }
+
+        self.locked_until = None;
+
+        None
+    }
+
+    fn fail(&mut self, max: u32, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.failures += 1;
+        self.last_failure = Some(now);
+
+        if self.failures < max {
+            return None;
+        }
+
+        let secs = LOCKOUT_SECS
+            .saturating_mul(1 << self.lockouts.min(16))
+            .min(MAX_LOCKOUT_SECS);
+        let until = now + TimeDelta::seconds(secs);
+
+        self.failures = 0;
+        self.lockouts += 1;
+        self.locked_until = Some(until);
+
+        Some(until)
+    }
+}
+
+// Where failures are counted, IPv4 addresses on their own and IPv6 addresses by
+// their /64. Connections without an address share one source.
+fn source(addr: Option<IpAddr>) -> Option<IpAddr> {
+    match addr?.to_canonical() {
+        IpAddr::V4(addr) => Some(IpAddr::V4(addr)),
+        IpAddr::V6(addr) => Some(IpAddr::V6(Ipv6Addr::from(
+            u128::from(addr) & (u128::MAX << (128 - IPV6_PREFIX)),
+        ))),
+    }
+}
+
+#[derive(Debug, Default)]
+struct Limits {
+    // Codes are single use, this is the last time step that was accepted.
+    used: i64,
+    // Keyed by `source`.
+    sources: HashMap<Option<IpAddr>, Attempts>,
+}
+
+impl Limits {
+    fn locked(&mut self, source: Option<IpAddr>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.sources.retain(|_, attempts| {
+            attempts
+                .last_failure
+                .is_some_and(|last| now - last < TimeDelta::seconds(FORGET_SECS))
+        });
+
+        self.sources
+            .get_mut(&source)
+            .and_then(|attempts| attempts.locked(now))
+    }
+
+    fn fail(&mut self, source: Option<IpAddr>) -> Verdict {
+        Verdict::Rejected {
+            locked: self
+                .sources
+                .entry(source)
+                .or_default()
+                .fail(MAX_FAILURES, Utc::now()),
+        }
+    }
+}
+
+/// Break-glass access for when the `OpenID` provider can't be used. A
+/// directory, normally a mounted `Secret`, contains:
+///
+/// - `secret`: the base32 encoded TOTP secret.
+/// - `user`: who codes authenticate as.
+/// - `groups`: optional, comma separated groups for the user.
+///
+/// The directory is read on every attempt so that rotating the `Secret` takes
+/// effect without a restart.
+///
+/// A six digit code is easy to guess given enough tries, so failures are
+/// counted per source and codes from it are refused for a while once there
+/// have been too many. There's no limit across sources, a flood of guesses
+/// shouldn't be able to lock out the people who need access.
+#[derive(Debug)]
+pub struct BreakGlass {
+    path: PathBuf,
+    limits: Mutex<Limits>,
+}
+
+impl BreakGlass {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            limits: Mutex::new(Limits::default()),
+        }
+    }
+
+    /// Make sure the directory has everything required, so that
+    /// misconfiguration shows up on startup instead of during an outage.
+    pub fn check(&self) -> Result<()> {
+        self.secret()?;
+        self.identity()?;
+
+        Ok(())
+    }
+
+    fn read(&self, name: &str) -> Result<Option<String>> {
+        let path = self.path.join(name);
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            fs::read_to_string(&path)
+                .wrap_err_with(|| format!("unable to read {}", path.display()))?
+                .trim()
+                .to_string(),
+        ))
+    }
+
+    fn secret(&self) -> Result<Vec<u8>> {
+        let secret = self
+            .read("secret")?
+            .ok_or_else(|| eyre!("break-glass secret is missing"))?
+            .replace([' ', '='], "")
+            .to_uppercase();
+
+        BASE32_NOPAD
+            .decode(secret.as_bytes())
+            .wrap_err("break-glass secret must be base32")
+    }
+
+    fn identity(&self) -> Result<Identity> {
+        let user = self
+            .read("user")?
+            .filter(|user| !user.is_empty())
+            .ok_or_else(|| eyre!("break-glass user is missing"))?;
+
+        let groups = self
+            .read("groups")?
+            .map(|groups| {
+                groups
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|group| !group.is_empty())
+                    .map(ToString::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Identity::new(user, groups).method("break_glass".to_string()))
+    }
+
+    /// Check `code`, returning the identity it is for when valid. Codes from
+    /// the previous and next time step are accepted to allow for clock drift,
+    /// but every code can only be used once. Failures count against `addr`,
+    /// the client's address.
+    pub fn verify(&self, code: &str, addr: Option<IpAddr>) -> Result<Verdict> {
+        let source = source(addr);
+
+        let code = {
+            let mut limits = self.limits.lock().unwrap();
+
+            if let Some(until) = limits.locked(source, Utc::now()) {
+                return Ok(Verdict::Locked(until));
+            }
+
+            let Ok(code) = code.trim().parse::<u32>() else {
+                return Ok(limits.fail(source));
+            };
+
+            code
+        };
+
+        // The files are read without holding the lock, a slow read shouldn't
+        // hold up everyone else's attempts.
+        let secret = self.secret()?;
+        let now = Utc::now().timestamp() / STEP;
+        let steps = [now - 1, now, now + 1]
+            .into_iter()
+            .map(|step| Ok((step, totp(&secret, step)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        {
+            let mut limits = self.limits.lock().unwrap();
+
+            // Another attempt could have used up the last one in the meantime.
+            if let Some(until) = limits.locked(source, Utc::now()) {
+                return Ok(Verdict::Locked(until));
+            }
+
+            let used = limits.used;
+            let Some((step, _)) = steps
+                .into_iter()
+                .find(|(step, expected)| *step > used && bool::from(expected.ct_eq(&code)))
+            else {
+                return Ok(limits.fail(source));
+            };
+
+            limits.used = step;
+            limits.sources.remove(&source);
+        }
+
+        Ok(Verdict::Accepted(self.identity()?))
+    }
+}
+
+#[allow(clippy::cast_sign_loss)]
+fn totp(secret: &[u8], step: i64) -> Result<u32> {
+    let mut mac =
+        Hmac::<Sha1>::new_from_slice(secret).map_err(|_| eyre!("invalid break-glass secret"))?;
+    mac.update(&(step as u64).to_be_bytes());
+
+    let hash = mac.finalize().into_bytes();
+    let offset = (hash[hash.len() - 1] & 0xf) as usize;
+
+    let value = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+
+    Ok(value % 10u32.pow(DIGITS))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    #[test]
+    fn locks_out_per_source() {
+        let break_glass = BreakGlass::new("/nonexistent");
+        let attacker = Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+        let other = Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)));
+
+        for _ in 0..MAX_FAILURES {
+            break_glass.verify("guess", attacker).unwrap();
+        }
+
+        assert!(matches!(
+            break_glass.verify("guess", attacker).unwrap(),
+            Verdict::Locked(_)
+        ));
+        assert!(matches!(
+            break_glass.verify("guess", other).unwrap(),
+            Verdict::Rejected { locked: None }
+        ));
+    }
+
+    #[test]
+    fn locks_out_by_prefix() {
+        let break_glass = BreakGlass::new("/nonexistent");
+        let subnet = Ipv6Addr::new(0x2001, 0xdb8, 0, 1, 0, 0, 0, 0);
+
+        for i in 0..MAX_FAILURES {
+            let addr = Ipv6Addr::from(u128::from(subnet) + u128::from(i));
+            break_glass.verify("guess", Some(IpAddr::V6(addr))).unwrap();
+        }
+
+        assert!(matches!(
+            break_glass
+                .verify(
+                    "guess",
+                    Some(IpAddr::V6(Ipv6Addr::from(u128::from(subnet) + 100)))
+                )
+                .unwrap(),
+            Verdict::Locked(_)
+        ));
+        assert!(matches!(
+            break_glass
+                .verify(
+                    "guess",
+                    Some(IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 2, 0, 0, 0, 1)))
+                )
+                .unwrap(),
+            Verdict::Rejected { locked: None }
+        ));
+        assert!(matches!(
+            break_glass.verify("guess", None).unwrap(),
+            Verdict::Rejected { locked: None }
+        ));
+    }
+}
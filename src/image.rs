@@ -0,0 +1,122 @@
+//! Details about container images that the cluster doesn't have, shown in the
+//! `images` tab. Both are optional, the tab works from pods alone.
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use eyre::Result;
+
+/// A parsed image reference, with the defaults a container runtime would fill
+/// in. `nginx` and `docker.io/library/nginx:latest` are the same image.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Reference {
+    pub registry: String,
+    pub repository: String,
+    pub tag: String,
+    pub digest: Option<String>,
+}
+
+impl Reference {
+    pub fn parse(image: &str) -> Self {
+        let (name, digest) = match image.split_once('@') {
+            Some((name, digest)) => (name, Some(digest.to_string())),
+            None => (image, None),
+        };
+
+        // A tag comes after the last `:`, unless that is part of a registry's
+        // port such as `localhost:5000/app`.
+        let (name, tag) = match name.rsplit_once(':') {
+            Some((name, tag)) if !tag.contains('/') => (name, tag),
+            _ => (name, "latest"),
+        };
+
+        // The first component is only a registry when it looks like a host.
+        let (registry, repository) = match name.split_once('/') {
+            Some((host, rest)) if host.contains(['.', ':']) || host == "localhost" => {
+                (host, rest.to_string())
+            }
+            _ => ("docker.io", name.to_string()),
+        };
+
+        let registry = match registry {
+            "index.docker.io" | "registry-1.docker.io" => "docker.io",
+            registry => registry,
+        };
+
+        let repository = if registry == "docker.io" && !repository.contains('/') {
+            format!("library/{repository}")
+        } else {
+            repository
+        };
+
+        Self {
+            registry: registry.to_string(),
+            repository,
+            tag: tag.to_string(),
+            digest,
+        }
+    }
+}
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}:{}", self.registry, self.repository, self.tag)?;
+
+        if let Some(digest) = &self.digest {
+            write!(f, "@{digest}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// What a registry knows about a tag.
+#[derive(Clone, Debug, Default)]
+pub struct Manifest {
+    /// What the tag currently points at, which can differ from what pods are
+    /// running when it has been pushed to since.
+    pub digest: Option<String>,
+    /// When the image was built, from its config.
+    pub created: Option<DateTime<Utc>>,
+}
+
+/// Looks up images in the registry they come from.
+#[async_trait::async_trait]
+pub trait Registry: Send + Sync {
+    async fn manifest(&self, image: &Reference) -> Result<Manifest>;
+}
+
+/// Known vulnerabilities for an image, by severity.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Vulnerabilities {
+    pub critical: u32,
+    pub high: u32,
+}
+
+impl fmt::Display for Vulnerabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.critical, self.high)
+    }
+}
+
+/// Reports vulnerabilities for images, such as from a Trivy server. Requests
+/// are made with the user's client so that a scanner inside the cluster only
+/// shows what the user is allowed to see.
+#[async_trait::async_trait]
+pub trait Scanner: Send + Sync {
+    /// Vulnerabilities for any of `images` the scanner knows about, keyed by
+    /// the image as it was passed in. Images that haven't been scanned are
+    /// left out.
+    async fn scan(
+        &self,
+        client: kube::Client,
+        images: &[String],
+    ) -> Result<HashMap<String, Vulnerabilities>>;
+}
+
+/// Where the `images` tab gets details from, it only lists what pods are
+/// running without either.
+#[derive(Clone, Default)]
+pub struct Enrichment {
+    pub registry: Option<Arc<dyn Registry>>,
+    pub scanner: Option<Arc<dyn Scanner>>,
+}
@@ -1,11 +1,13 @@
 use std::{
-    io,
+    io::{self, Write},
     sync::{Arc, Mutex},
 };
 
+use base64::{engine::general_purpose::STANDARD, Engine as _};
 use ratatui::{
     backend::{Backend as BackendTrait, ClearType, CrosstermBackend, WindowSize},
     buffer::Cell,
+    crossterm::terminal::SetTitle,
     layout::{Position, Size},
 };
 
@@ -20,6 +22,13 @@ where
     crossterm: CrosstermBackend<W>,
 
     size: Arc<Mutex<WindowSize>>,
+
+    // Only widgets that take text show the cursor.
+    cursor: bool,
+
+    // Frames are drawn to the buffer as usual but nothing goes out, only what is
+    // written with `say`.
+    linear: bool,
 }
 
 impl<W> Backend<W>
@@ -36,10 +45,51 @@ where
             Self {
                 crossterm: CrosstermBackend::new(writer),
                 size: size.clone(),
+                cursor: false,
+                linear: false,
             },
             size,
         )
     }
+
+    /// Stop drawing to the client's terminal, for screen readers. See
+    /// `Capabilities::linear`.
+    #[must_use]
+    pub fn linear(mut self, linear: bool) -> Self {
+        self.linear = linear;
+        self
+    }
+
+    /// Write `lines` out as plain text, one after the other.
+    pub fn say(&mut self, lines: &[String]) -> io::Result<()> {
+        for line in lines {
+            write!(self.crossterm, "{line}\r\n")?;
+        }
+
+        io::Write::flush(&mut self.crossterm)
+    }
+
+    /// Set the title of the client's terminal window (or tab).
+    pub fn set_title(&mut self, title: &str) -> io::Result<()> {
+        if self.linear {
+            return Ok(());
+        }
+
+        ratatui::crossterm::execute!(self.crossterm, SetTitle(title))
+    }
+
+    /// Whether the last frame showed the cursor, which only happens while a
+    /// text input has focus.
+    pub fn typing(&self) -> bool {
+        self.cursor
+    }
+
+    /// Put `text` on the client's clipboard with OSC 52. Terminals that don't
+    /// support it ignore the sequence.
+    pub fn set_clipboard(&mut self, text: &str) -> io::Result<()> {
+        write!(self.crossterm, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+        io::Write::flush(&mut self.crossterm)
+    }
 }
 
 impl<W> BackendTrait for Backend<W>
@@ -50,14 +100,30 @@ where
     where
         I: Iterator<Item = (u16, u16, &'a Cell)>,
     {
+        if self.linear {
+            return Ok(());
+        }
+
         self.crossterm.draw(items)
     }
 
     fn hide_cursor(&mut self) -> io::Result<()> {
+        self.cursor = false;
+
+        if self.linear {
+            return Ok(());
+        }
+
         self.crossterm.hide_cursor()
     }
 
     fn show_cursor(&mut self) -> io::Result<()> {
+        self.cursor = true;
+
+        if self.linear {
+            return Ok(());
+        }
+
         self.crossterm.show_cursor()
     }
 
@@ -68,7 +134,7 @@ where
     }
 
     fn set_cursor(&mut self, x: u16, y: u16) -> io::Result<()> {
-        self.crossterm.set_cursor_position(Position::new(x, y))
+        self.set_cursor_position(Position::new(x, y))
     }
 
     fn get_cursor_position(&mut self) -> io::Result<Position> {
@@ -76,18 +142,34 @@ where
     }
 
     fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
+        if self.linear {
+            return Ok(());
+        }
+
         self.crossterm.set_cursor_position(position)
     }
 
     fn clear(&mut self) -> io::Result<()> {
+        if self.linear {
+            return Ok(());
+        }
+
         self.crossterm.clear()
     }
 
     fn clear_region(&mut self, clear_type: ClearType) -> io::Result<()> {
+        if self.linear {
+            return Ok(());
+        }
+
         self.crossterm.clear_region(clear_type)
     }
 
     fn append_lines(&mut self, count: u16) -> io::Result<()> {
+        if self.linear {
+            return Ok(());
+        }
+
         self.crossterm.append_lines(count)
     }
 
@@ -107,6 +189,6 @@ where
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        self.crossterm.flush()
+        io::Write::flush(&mut self.crossterm)
     }
 }
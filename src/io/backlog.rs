@@ -0,0 +1,102 @@
+use std::sync::{Arc, Mutex};
+
+/// Bytes written to an SSH channel that the client doesn't have room for yet.
+/// The session holds on to anything past the client's window until the client
+/// adjusts it, which it only does once it has read what was already sent. A
+/// backlog means the client isn't keeping up, no matter how quickly writes
+/// return.
+///
+/// The client's initial window isn't known, until it first adjusts the window
+/// everything is assumed to fit.
+#[derive(Clone, Debug, Default)]
+pub struct Backlog(Arc<Mutex<Window>>);
+
+#[derive(Debug, Default)]
+struct Window {
+    room: Option<usize>,
+    queued: usize,
+}
+
+impl Backlog {
+    /// `len` bytes were handed to the session.
+    pub fn sent(&self, len: usize) {
+        let mut window = self.0.lock().unwrap();
+
+        let Some(room) = window.room else {
+            return;
+        };
+
+        // Once anything is queued, everything after it queues up behind it.
+        let fits = if window.queued == 0 { room.min(len) } else { 0 };
+
+        window.room = Some(room - fits);
+        window.queued += len - fits;
+    }
+
+    /// The client adjusted the window to `size`, what was queued goes out
+    /// first.
+    pub fn adjusted(&self, size: u32) {
+        let mut window = self.0.lock().unwrap();
+
+        let size = size as usize;
+        let flushed = window.queued.min(size);
+
+        window.queued -= flushed;
+        window.room = Some(size - flushed);
+    }
+
+    /// Bytes waiting on the client.
+    pub fn bytes(&self) -> usize {
+        self.0.lock().unwrap().queued
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Backlog;
+
+    #[test]
+    fn fits_until_the_window_is_known() {
+        let backlog = Backlog::default();
+
+        backlog.sent(10 * 1024 * 1024);
+
+        assert_eq!(backlog.bytes(), 0);
+    }
+
+    #[test]
+    fn queues_past_the_window() {
+        let backlog = Backlog::default();
+        backlog.adjusted(100);
+
+        backlog.sent(60);
+        assert_eq!(backlog.bytes(), 0);
+
+        backlog.sent(60);
+        assert_eq!(backlog.bytes(), 20);
+
+        backlog.sent(10);
+        assert_eq!(backlog.bytes(), 30);
+    }
+
+    #[test]
+    fn adjusting_flushes_the_queue() {
+        let backlog = Backlog::default();
+        backlog.adjusted(0);
+
+        backlog.sent(50);
+        assert_eq!(backlog.bytes(), 50);
+
+        backlog.adjusted(30);
+        assert_eq!(backlog.bytes(), 20);
+
+        backlog.adjusted(100);
+        assert_eq!(backlog.bytes(), 0);
+
+        // 80 bytes of room are left after the flush.
+        backlog.sent(80);
+        assert_eq!(backlog.bytes(), 0);
+        backlog.sent(1);
+        assert_eq!(backlog.bytes(), 1);
+    }
+}
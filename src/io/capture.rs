@@ -0,0 +1,79 @@
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use tokio::io::AsyncWrite;
+
+// Enough for a few thousand lines, older output is dropped first.
+static LIMIT: usize = 1024 * 1024;
+
+/// The most recent output of a raw mode widget, kept so that it can be scrolled
+/// through once the widget has exited.
+#[derive(Clone, Default)]
+pub struct Captured {
+    bytes: Arc<Mutex<VecDeque<u8>>>,
+}
+
+impl Captured {
+    /// Write to `inner`, keeping a copy of everything that was written.
+    pub fn tee<W>(&self, inner: W) -> Tee<W> {
+        Tee {
+            inner,
+            captured: self.clone(),
+        }
+    }
+
+    /// Everything captured so far, leaving nothing behind.
+    pub fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.bytes.lock().unwrap()).into()
+    }
+
+    fn push(&self, buf: &[u8]) {
+        let mut bytes = self.bytes.lock().unwrap();
+
+        bytes.extend(buf);
+
+        if bytes.len() > LIMIT {
+            let excess = bytes.len() - LIMIT;
+            bytes.drain(..excess);
+        }
+    }
+}
+
+/// See [`Captured::tee`].
+pub struct Tee<W> {
+    inner: W,
+    captured: Captured,
+}
+
+impl<W> AsyncWrite for Tee<W>
+where
+    W: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+
+        let result = Pin::new(&mut this.inner).poll_write(cx, buf);
+
+        if let Poll::Ready(Ok(n)) = result {
+            this.captured.push(&buf[..n]);
+        }
+
+        result
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
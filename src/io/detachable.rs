@@ -0,0 +1,109 @@
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use eyre::Result;
+use tokio::io::AsyncWrite;
+use tokio_util::either::Either;
+
+use super::{Channel, Writer};
+
+/// Output that can be moved from one SSH channel to another. While detached,
+/// everything written is dropped. A failed write detaches, the connection is
+/// gone at that point and the dashboard should keep running until someone
+/// attaches again.
+#[derive(Clone, Default)]
+pub struct Detachable {
+    current: Arc<Mutex<Option<Channel>>>,
+}
+
+impl Detachable {
+    pub fn new(channel: Channel) -> Self {
+        Self {
+            current: Arc::new(Mutex::new(Some(channel))),
+        }
+    }
+
+    pub fn attach(&self, channel: Channel) {
+        *self.current.lock().unwrap() = Some(channel);
+    }
+
+    pub fn detach(&self) {
+        self.current.lock().unwrap().take();
+    }
+
+    /// See [`Channel::adjusted`].
+    pub fn adjusted(&self, size: u32) {
+        if let Some(channel) = self.channel() {
+            channel.adjusted(size);
+        }
+    }
+
+    fn channel(&self) -> Option<Channel> {
+        self.current.lock().unwrap().clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl Writer for Detachable {
+    fn blocking(&self) -> impl Write + Send {
+        Buffered {
+            output: self.clone(),
+            buf: Vec::new(),
+        }
+    }
+
+    // Raw mode widgets keep the channel they started with. When it goes away,
+    // writes fail and the widget exits back to the UI.
+    fn non_blocking(&self) -> impl AsyncWrite + Send + Unpin + 'static {
+        match self.channel() {
+            Some(channel) => Either::Left(channel.writer()),
+            None => Either::Right(tokio::io::sink()),
+        }
+    }
+
+    fn backlog(&self) -> usize {
+        self.channel().map_or(0, |channel| channel.backlog())
+    }
+
+    async fn shutdown(&self, msg: String) -> Result<()> {
+        match self.channel() {
+            Some(channel) => channel.shutdown(msg).await,
+            None => Ok(()),
+        }
+    }
+}
+
+struct Buffered {
+    output: Detachable,
+    buf: Vec<u8>,
+}
+
+impl Write for Buffered {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        let buf = std::mem::take(&mut self.buf);
+
+        let Some(channel) = self.output.channel() else {
+            return Ok(());
+        };
+
+        let mut writer = channel.blocking();
+
+        if writer
+            .write_all(&buf)
+            .and_then(|()| writer.flush())
+            .is_err()
+        {
+            self.output.detach();
+        }
+
+        Ok(())
+    }
+}
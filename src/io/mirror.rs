@@ -0,0 +1,113 @@
+use std::{
+    io::Write,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use eyre::Result;
+use tokio::{io::AsyncWrite, sync::broadcast};
+use tokio_util::bytes::Bytes;
+
+use super::Writer;
+
+/// Copies everything written to `inner` to any subscribers. Nothing is
+/// buffered, subscribers only get what is written after they've subscribed.
+pub struct Mirror<W> {
+    inner: W,
+    tx: broadcast::Sender<Bytes>,
+}
+
+impl<W> Mirror<W>
+where
+    W: Writer,
+{
+    pub fn new(inner: W, tx: broadcast::Sender<Bytes>) -> Self {
+        Self { inner, tx }
+    }
+}
+
+#[async_trait::async_trait]
+impl<W> Writer for Mirror<W>
+where
+    W: Writer,
+{
+    fn blocking(&self) -> impl Write + Send {
+        Tee {
+            inner: self.inner.blocking(),
+            tx: self.tx.clone(),
+        }
+    }
+
+    fn non_blocking(&self) -> impl AsyncWrite + Send + Unpin + 'static {
+        Tee {
+            inner: self.inner.non_blocking(),
+            tx: self.tx.clone(),
+        }
+    }
+
+    fn backlog(&self) -> usize {
+        self.inner.backlog()
+    }
+
+    async fn shutdown(&self, msg: String) -> Result<()> {
+        self.inner.shutdown(msg).await
+    }
+}
+
+struct Tee<W> {
+    inner: W,
+    tx: broadcast::Sender<Bytes>,
+}
+
+impl<W> Tee<W> {
+    // Sending only fails when there are no subscribers, which is the common case.
+    fn copy(&self, buf: &[u8]) {
+        if self.tx.receiver_count() > 0 {
+            let _ = self.tx.send(Bytes::copy_from_slice(buf));
+        }
+    }
+}
+
+impl<W> Write for Tee<W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+
+        self.copy(&buf[..n]);
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W> AsyncWrite for Tee<W>
+where
+    W: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let result = Pin::new(&mut self.inner).poll_write(cx, buf);
+
+        if let Poll::Ready(Ok(n)) = result {
+            self.copy(&buf[..n]);
+        }
+
+        result
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,15 @@
+use eyre::Result;
+use tokio::process::Child;
+
+/// Starts external programs for the user of a dashboard, see
+/// `KtySettings.commands`. Programs get access to the cluster as the user and
+/// nothing else.
+pub trait Launch: Send + Sync {
+    /// Start `argv` with stdin, stdout and stderr piped. Fails when the program
+    /// isn't allowed.
+    fn launch(&self, argv: &[String]) -> Result<Child>;
+
+    /// The child with `pid` from [`Launch::launch`] has exited, anything set
+    /// up for it can go away.
+    fn exited(&self, pid: u32);
+}
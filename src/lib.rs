@@ -0,0 +1,74 @@
+//! # kty
+//!
+//! The pieces that make up `kty` can be embedded in other tools. The dashboard
+//! is available by default and runs over anything that implements [`Writer`]:
+//!
+//! ```no_run
+//! # async fn run(stdin: tokio::io::Stdin, stdout: impl kty::Writer) -> eyre::Result<()> {
+//! let tx = kty::Dashboard::builder()
+//!     .client(kube::Client::try_default().await?)
+//!     .build()
+//!     .start(stdin, stdout)?;
+//!
+//! // Input, resize and shutdown are all communicated as events.
+//! tx.send(kty::Event::Shutdown)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The SSH server plumbing is behind the `ssh` feature and the `OpenID`
+//! device code flow is behind the `openid` feature. Both are enabled by
+//! default.
+
+#[cfg(feature = "ssh")]
+mod admin;
+pub mod audit;
+#[cfg(feature = "ssh")]
+mod broadcast;
+// Only public so that the binary can use it, this isn't part of the API.
+#[cfg(feature = "ssh")]
+#[doc(hidden)]
+pub mod cli;
+mod dashboard;
+mod events;
+mod fx;
+#[cfg(feature = "ssh")]
+mod health;
+pub mod history;
+#[cfg(feature = "openid")]
+mod identity;
+pub mod image;
+mod io;
+pub mod launch;
+#[cfg(feature = "openid")]
+pub mod openid;
+pub mod plugin;
+mod resources;
+pub mod share;
+#[cfg(feature = "ssh")]
+pub mod ssh;
+mod widget;
+
+use std::sync::OnceLock;
+
+pub use dashboard::Dashboard;
+pub use events::Event;
+pub use io::Writer;
+use tracing::metadata::LevelFilter;
+
+/// Verbosity the dashboard should use. Setting this to debug or higher shows
+/// debug information in the UI.
+///
+/// While tracing allows for you to get the global log filter
+/// (`tracing::metadata::LevelFilter::current()`), the
+/// `tracing_subscriber::registry::Registry` doesn't actually set it. The
+/// `Subscriber` interface exposes an interface to check for `enabled()` but
+/// that doesn't look at the individual layers of the registry. This
+/// effectively copies how the global `LevelFilter` is set and allows other
+/// things to check against it in a similar fashion.
+pub static LEVEL: OnceLock<LevelFilter> = OnceLock::new();
+
+/// Set for air-gapped clusters. Telemetry is disabled and HTTP requests, such
+/// as to the `OpenID` provider, are only made to hosts inside the cluster or
+/// network.
+pub static OFFLINE: OnceLock<bool> = OnceLock::new();
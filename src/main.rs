@@ -1,26 +1,13 @@
 //! # kty
-
-mod broadcast;
-#[warn(dead_code)]
-mod cli;
-mod dashboard;
-mod events;
-mod fx;
-mod health;
-mod identity;
-mod io;
-mod openid;
-mod resources;
-mod ssh;
-mod widget;
+// Dependencies are consumed by the library, the binary only wires up the CLI.
+#![allow(unused_crate_dependencies)]
 
 use cata::execute;
 use clap::Parser;
 use eyre::Result;
+use kty::cli::Root;
 use tokio::signal::unix::{signal, SignalKind};
 
-use crate::cli::Root;
-
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::config::HookBuilder::default()
@@ -31,8 +18,12 @@ async fn main() -> Result<()> {
     let root = Root::parse();
     let mut sigterm = signal(SignalKind::terminate())?;
 
-    tokio::select! {
+    let result = tokio::select! {
         _ = sigterm.recv() => Ok(()),
         result = execute(&root) => result,
-    }
+    };
+
+    kty::cli::shutdown().await;
+
+    result
 }
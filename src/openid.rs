@@ -1,25 +1,123 @@
+//! `OpenID` device code flow. Users are shown a link (or QR code) to verify
+//! their identity and the resulting `id_token` is mapped to an identity via. a
+//! claim.
+
+pub mod jwks;
+
+use std::{
+    fmt::Display,
+    net::IpAddr,
+    sync::{Arc, RwLock},
+};
+
 use chrono::{DateTime, Duration, Utc};
 use color_eyre::{Section, SectionExt};
 use derive_builder::Builder;
-use eyre::Result;
-use itertools::Itertools;
+use eyre::{eyre, Result};
 use jsonwebtoken::{jwk, jwk::JwkSet};
 use serde::{de::Deserializer, Deserialize};
 
-use crate::identity::Identity;
+use self::jwks::Jwks;
+use crate::identity::{Identity, Rejection};
+
+// Domains that can't be resolved from the internet.
+static LOCAL_SUFFIXES: [&str; 5] = [".svc", ".cluster.local", ".local", ".internal", ".lan"];
+
+/// Whether requests should stay inside the cluster or network, see
+/// [`crate::OFFLINE`].
+pub fn offline() -> bool {
+    crate::OFFLINE.get().copied().unwrap_or_default()
+}
+
+/// Refuse requests to anything that isn't local while offline.
+pub fn check(url: &str) -> Result<()> {
+    if !offline() || is_local(url)? {
+        return Ok(());
+    }
+
+    Err(eyre!(
+        "offline mode only allows requests to local hosts, refusing: {url}"
+    ))
+}
+
+fn is_local(url: &str) -> Result<bool> {
+    let url = reqwest::Url::parse(url)?;
+
+    let Some(host) = url.host_str() else {
+        return Ok(false);
+    };
+
+    // IPv6 hosts are bracketed in URLs.
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+
+    Ok(match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => ip.is_private() || ip.is_loopback() || ip.is_link_local(),
+        // Unique local addresses, fc00::/7.
+        Ok(IpAddr::V6(ip)) => ip.is_loopback() || (ip.segments()[0] & 0xfe00) == 0xfc00,
+        Err(_) => {
+            host == "localhost"
+                || !host.contains('.')
+                || LOCAL_SUFFIXES.iter().any(|suffix| host.ends_with(suffix))
+        }
+    })
+}
 
+/// The provider couldn't be reached or failed to answer, as opposed to it
+/// answering that the user can't log in. Only these offer break-glass access.
+#[derive(Debug)]
+pub struct Unavailable(String);
+
+impl Unavailable {
+    /// Used when there's no provider yet, it hasn't been reachable since
+    /// startup.
+    pub fn missing() -> Self {
+        Self("it hasn't been reachable since startup".to_string())
+    }
+
+    // Connection failures, timeouts and server errors are the provider's
+    // problem. Everything else is kept as is so callers can inspect it.
+    fn check(err: reqwest::Error) -> eyre::Report {
+        if err.is_connect()
+            || err.is_timeout()
+            || err.status().is_some_and(|status| status.is_server_error())
+        {
+            return Self(err.to_string()).into();
+        }
+
+        err.into()
+    }
+}
+
+impl Display for Unavailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for Unavailable {}
+
+/// Response from the device authorization endpoint.
 #[allow(dead_code)]
 #[derive(Clone, Deserialize, Debug)]
 pub struct DeviceCode {
     #[allow(clippy::struct_field_names)]
     device_code: String,
+    /// Code the user enters at `verification_uri`.
     pub user_code: String,
+    /// Where the user goes to verify their identity.
     pub verification_uri: String,
     expires_in: u64,
     interval: u64,
+    /// `verification_uri` with the `user_code` already filled in.
     pub verification_uri_complete: String,
 }
 
+// Error responses from the token endpoint, see RFC 8628 section 3.5.
+#[derive(Deserialize, Debug)]
+struct TokenError {
+    error: String,
+}
+
 #[derive(Deserialize, Debug)]
 struct OauthToken {
     id_token: String,
@@ -36,6 +134,8 @@ where
     Ok(Duration::seconds(seconds))
 }
 
+/// The subset of the provider's `.well-known/openid-configuration` that is
+/// used.
 #[derive(Clone, Debug, Deserialize)]
 pub struct Config {
     token_endpoint: String,
@@ -48,11 +148,16 @@ impl Fetch for Config {
     type Output = Self;
 }
 
+/// Fetch and deserialize JSON from a URL.
 #[async_trait::async_trait]
 pub trait Fetch {
+    /// What the response is deserialized into.
     type Output: for<'de> Deserialize<'de>;
 
+    /// Fetch `url`, including the response in any deserialization errors.
     async fn fetch(url: &str) -> Result<Self::Output> {
+        check(url)?;
+
         let data = reqwest::Client::new()
             .get(url)
             .send()
@@ -75,22 +180,67 @@ impl Fetch for JwkSet {
 }
 
 impl Config {
+    /// Fetch the keys used to validate tokens.
     pub async fn jwks(&self) -> Result<JwkSet> {
         JwkSet::fetch(&self.jwks_uri).await
     }
+
+    /// Fetch the keys and keep them up to date, see [`Jwks`].
+    pub async fn keys(&self) -> Result<Jwks> {
+        Jwks::new(self.jwks_uri.clone()).await
+    }
+
+    /// Make sure every endpoint can be used, discovery can point somewhere
+    /// other than where the configuration came from.
+    pub fn check(&self) -> Result<()> {
+        for url in [
+            &self.token_endpoint,
+            &self.device_authorization_endpoint,
+            &self.jwks_uri,
+        ] {
+            check(url)?;
+        }
+
+        Ok(())
+    }
 }
 
+/// Everything required to authenticate users with an `OpenID` provider.
 #[derive(Clone, Debug, Builder)]
 pub struct Provider {
+    /// Client ID registered with the provider.
     client_id: String,
+    /// Claim of the `id_token` to use as the user's name.
     claim: String,
 
+    /// Provider configuration, see [`Config`].
     config: Config,
-    jwks: JwkSet,
+    /// Keys used to validate tokens, see [`Config::keys`].
+    jwks: Jwks,
+}
+
+/// The provider once it could be used. Servers with break-glass access start
+/// without one when it is unavailable and set it once it can be reached.
+#[derive(Clone, Debug, Default)]
+pub struct Shared(Arc<RwLock<Option<Arc<Provider>>>>);
+
+impl Shared {
+    /// The provider, unset until it has been reachable.
+    pub fn get(&self) -> Option<Arc<Provider>> {
+        self.0.read().unwrap().clone()
+    }
+
+    /// Start using `provider`, break-glass is no longer offered.
+    pub fn set(&self, provider: Provider) {
+        *self.0.write().unwrap() = Some(Arc::new(provider));
+    }
 }
 
 impl Provider {
+    /// Start the device code flow.
     pub async fn code(&self) -> Result<DeviceCode> {
+        check(&self.config.device_authorization_endpoint)?;
+
         let code = reqwest::Client::new()
             .post(self.config.device_authorization_endpoint.clone())
             .form(&[
@@ -98,8 +248,10 @@ impl Provider {
                 ("scope", "openid email".to_string()),
             ])
             .send()
-            .await?
-            .error_for_status()?
+            .await
+            .map_err(Unavailable::check)?
+            .error_for_status()
+            .map_err(Unavailable::check)?
             .json::<DeviceCode>()
             .await?;
 
@@ -107,7 +259,9 @@ impl Provider {
     }
 
     async fn oauth_token(&self, code: &DeviceCode) -> Result<OauthToken> {
-        let data = reqwest::Client::new()
+        check(&self.config.token_endpoint)?;
+
+        let response = reqwest::Client::new()
             .post(&self.config.token_endpoint)
             .form(&[
                 ("client_id", &self.client_id),
@@ -118,10 +272,24 @@ impl Provider {
                 ),
             ])
             .send()
-            .await?
-            .error_for_status()?
-            .text()
-            .await?;
+            .await
+            .map_err(Unavailable::check)?;
+
+        // Anything other than the code being dead stays an HTTP error, waiting for
+        // the user to finish is a 403.
+        if let Some(err) = response.error_for_status_ref().err() {
+            let rejection = response.json::<TokenError>().await.ok().and_then(|body| {
+                match body.error.as_str() {
+                    "expired_token" => Some(Rejection::Expired),
+                    "access_denied" => Some(Rejection::Denied),
+                    _ => None,
+                }
+            });
+
+            return Err(rejection.map_or_else(|| Unavailable::check(err), Into::into));
+        }
+
+        let data = response.text().await?;
 
         let content: OauthToken =
             serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(&data))
@@ -130,7 +298,7 @@ impl Provider {
         Ok(content)
     }
 
-    fn id_token(&self, token: &OauthToken) -> Result<serde_json::Value> {
+    async fn id_token(&self, token: &OauthToken) -> Result<serde_json::Value> {
         let header = jsonwebtoken::decode_header(&token.id_token)?;
 
         let Some(kid) = header.kid else {
@@ -138,16 +306,7 @@ impl Provider {
                 .with_section(move || format!("{header:#?}").header("Token Header"));
         };
 
-        let Some(jwk) = self.jwks.find(&kid) else {
-            return Err(eyre::eyre!("JWK not found for {}", kid)).with_section(move || {
-                self.jwks
-                    .keys
-                    .iter()
-                    .map(|jwk| jwk.common.key_id.as_ref().unwrap())
-                    .join("\n")
-                    .header("Available Key IDs")
-            });
-        };
+        let jwk = self.jwks.find(&kid).await?;
 
         let key = match &jwk.algorithm {
             jwk::AlgorithmParameters::RSA(rsa) => {
@@ -169,15 +328,29 @@ impl Provider {
         Ok(token_data.claims)
     }
 
+    /// Exchange a verified code for the user's identity and when it expires.
     pub async fn identity(&self, code: &DeviceCode) -> Result<(Identity, DateTime<Utc>)> {
         let oauth_token = self.oauth_token(code).await?;
-        let id_token = self.id_token(&oauth_token)?;
+        let id_token = self.id_token(&oauth_token).await?;
 
         let Some(name) = id_token.get(&self.claim) else {
-            return Err(eyre::eyre!("Claim {} not found in token", self.claim))
-                .section(format!("{id_token:#?}").header("Token Claims"));
+            return Err(eyre::Report::new(Rejection::MissingClaim(
+                self.claim.clone(),
+            )))
+            .section(format!("{id_token:#?}").header("Token Claims"));
         };
 
+        // Providers that let users sign up with any address mark whether they've
+        // proven it's theirs.
+        if self.claim == "email"
+            && id_token
+                .get("email_verified")
+                .and_then(serde_json::Value::as_bool)
+                == Some(false)
+        {
+            return Err(Rejection::EmailUnverified.into());
+        }
+
         // TODO: add groups via claim to the identity.
         Ok((
             Identity::new(name.as_str().unwrap().into(), Vec::new()).method("openid".into()),
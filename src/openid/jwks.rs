@@ -0,0 +1,148 @@
+use std::{
+    sync::{Arc, RwLock, Weak},
+    time::{Duration, Instant},
+};
+
+use eyre::{eyre, Result};
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use lazy_static::lazy_static;
+use prometheus::{opts, register_int_counter_vec, IntCounterVec};
+use tokio::sync::Mutex;
+
+use super::{Fetch, Unavailable};
+
+// Providers rotate keys rarely and publish new ones ahead of time, this only
+// needs to catch up eventually. Unknown key IDs trigger a refresh right away.
+static REFRESH_EVERY: Duration = Duration::from_secs(60 * 60);
+// Tokens with a bogus key ID shouldn't be able to hammer the provider.
+static MIN_REFRESH: Duration = Duration::from_secs(30);
+
+lazy_static! {
+    static ref REFRESHES: IntCounterVec = register_int_counter_vec!(
+        opts!(
+            "openid_jwks_refresh_total",
+            "Number of times the provider's signing keys were fetched by reason (interval, miss) \
+             and result (ok, error)",
+        ),
+        &["reason", "result"],
+    )
+    .unwrap();
+}
+
+#[derive(Debug)]
+struct Inner {
+    uri: String,
+    keys: RwLock<JwkSet>,
+    fetched: RwLock<Instant>,
+    // Held while fetching. Anyone else that needs fresh keys waits here and uses
+    // the result instead of fetching again.
+    fetching: Mutex<()>,
+}
+
+/// The provider's signing keys, shared by every login. Keys are refreshed in
+/// the background and whenever a token is signed with a key that isn't known
+/// yet, so that rotation at the provider doesn't require a restart.
+#[derive(Clone, Debug)]
+pub struct Jwks {
+    inner: Arc<Inner>,
+}
+
+impl Jwks {
+    /// Fetch the keys from `uri` and keep them up to date until every clone
+    /// has been dropped.
+    pub async fn new(uri: String) -> Result<Self> {
+        let keys = JwkSet::fetch(&uri).await?;
+
+        let jwks = Self {
+            inner: Arc::new(Inner {
+                uri,
+                keys: RwLock::new(keys),
+                fetched: RwLock::new(Instant::now()),
+                fetching: Mutex::new(()),
+            }),
+        };
+
+        tokio::spawn(refresh(Arc::downgrade(&jwks.inner)));
+
+        Ok(jwks)
+    }
+
+    /// The key `kid` signed with. Unknown keys are fetched at most every
+    /// `MIN_REFRESH`, concurrent lookups share one request.
+    pub async fn find(&self, kid: &str) -> Result<Jwk> {
+        if let Some(jwk) = self.cached(kid) {
+            return Ok(jwk);
+        }
+
+        let requested = Instant::now();
+        let _fetching = self.inner.fetching.lock().await;
+
+        // Someone else fetched while this was waiting.
+        let fetched = self.fetched();
+        if fetched < requested && fetched.elapsed() >= MIN_REFRESH {
+            self.fetch("miss")
+                .await
+                .map_err(|err| Unavailable(format!("unable to fetch keys: {err}")))?;
+        }
+
+        self.cached(kid)
+            .ok_or_else(|| eyre!("JWK not found for {kid}, known: {}", self.kids().join(", ")))
+    }
+
+    fn kids(&self) -> Vec<String> {
+        self.inner
+            .keys
+            .read()
+            .unwrap()
+            .keys
+            .iter()
+            .filter_map(|jwk| jwk.common.key_id.clone())
+            .collect()
+    }
+
+    fn cached(&self, kid: &str) -> Option<Jwk> {
+        self.inner.keys.read().unwrap().find(kid).cloned()
+    }
+
+    fn fetched(&self) -> Instant {
+        *self.inner.fetched.read().unwrap()
+    }
+
+    // Replaces every key, providers keep keys that are still in use published.
+    async fn fetch(&self, reason: &str) -> Result<()> {
+        let result = JwkSet::fetch(&self.inner.uri).await;
+
+        REFRESHES
+            .with_label_values(&[reason, if result.is_ok() { "ok" } else { "error" }])
+            .inc();
+
+        let keys = result?;
+
+        *self.inner.keys.write().unwrap() = keys;
+        *self.inner.fetched.write().unwrap() = Instant::now();
+
+        Ok(())
+    }
+}
+
+// Failures keep the keys that are already known, the provider being down
+// shouldn't stop logins that don't need a new key.
+async fn refresh(inner: Weak<Inner>) {
+    let mut interval = tokio::time::interval(REFRESH_EVERY);
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        let Some(inner) = inner.upgrade() else {
+            return;
+        };
+
+        let jwks = Jwks { inner };
+        let _fetching = jwks.inner.fetching.lock().await;
+
+        if let Err(err) = jwks.fetch("interval").await {
+            tracing::warn!(?err, "unable to refresh JWKS");
+        }
+    }
+}
@@ -0,0 +1,95 @@
+#[cfg(feature = "plugins")]
+pub mod wasm;
+
+#[cfg(feature = "plugins")]
+use std::{path::Path, sync::Arc};
+
+use eyre::Result;
+use kube::api::{ApiResource, DynamicObject, GroupVersionKind};
+use serde::{Deserialize, Serialize};
+
+/// A tab that isn't built into kty, such as one for an internal CRD. Plugins
+/// only decide how resources are shown, kty lists them as the user so RBAC and
+/// `AccessPolicy` still apply.
+pub trait Plugin: Send + Sync {
+    /// What the tab lists and how, read when the dashboard starts.
+    fn schema(&self) -> &Schema;
+
+    /// A row for each of `objects`, in the same order.
+    fn rows(&self, objects: &[DynamicObject]) -> Result<Vec<Row>>;
+
+    /// What is shown once `object` has been selected. ANSI escapes can be used
+    /// for color.
+    fn detail(&self, object: &DynamicObject) -> Result<String>;
+}
+
+/// Every plugin in `dir`, see [`wasm::Module`].
+#[cfg(feature = "plugins")]
+pub fn load(dir: &Path) -> Result<Vec<Arc<dyn Plugin>>> {
+    Ok(wasm::Module::load_dir(dir)?
+        .into_iter()
+        .map(|module| Arc::new(module) as Arc<dyn Plugin>)
+        .collect())
+}
+
+/// What a plugin's tab shows.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Schema {
+    /// Refers to the tab in `tabs`, such as `certificates`.
+    pub name: String,
+    /// Shown on the tab.
+    pub title: String,
+    /// What is listed.
+    pub resource: Resource,
+    /// In the order cells are returned in.
+    pub columns: Vec<Column>,
+}
+
+/// A kind of resource, by group, version and kind.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Resource {
+    /// Empty for the core group.
+    #[serde(default)]
+    pub group: String,
+    pub version: String,
+    pub kind: String,
+    pub plural: String,
+}
+
+impl Resource {
+    /// Used to list it with the dynamic API.
+    pub fn api_resource(&self) -> ApiResource {
+        ApiResource::from_gvk_with_plural(
+            &GroupVersionKind::gvk(&self.group, &self.version, &self.kind),
+            &self.plural,
+        )
+    }
+}
+
+/// A column in the tab's table.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Column {
+    pub name: String,
+    /// Columns without a width share whatever space is left.
+    #[serde(default)]
+    pub width: Option<u16>,
+}
+
+/// How a row is colored.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Health {
+    Healthy,
+    Unhealthy,
+    #[default]
+    Normal,
+}
+
+/// A resource, as shown in the tab's table.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Row {
+    /// One for each of [`Schema::columns`].
+    pub cells: Vec<String>,
+    #[serde(default)]
+    pub health: Health,
+}
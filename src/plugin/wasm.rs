@@ -0,0 +1,160 @@
+use std::path::Path;
+
+use eyre::{eyre, Result};
+use kube::api::DynamicObject;
+use wasmtime::{Config, Engine, Linker, StoreLimits, StoreLimitsBuilder};
+
+use super::{Plugin, Row, Schema};
+
+// Instructions a single call can run before it is stopped.
+static FUEL: u64 = 1_000_000_000;
+// How large a plugin's memory can grow.
+static MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+
+/// A plugin compiled to WebAssembly. Modules can't import anything, so all they
+/// can do is turn the JSON they're given into JSON or text. Every call gets a
+/// fresh instance with limited fuel and memory. Modules export:
+///
+/// - `memory`
+/// - `kty_alloc(len: u32) -> u32`, where kty writes the input of a call.
+/// - `kty_schema() -> u64`, a [`Schema`] as JSON.
+/// - `kty_rows(ptr: u32, len: u32) -> u64`, a list of objects in, a list of
+///   [`Row`] out.
+/// - `kty_detail(ptr: u32, len: u32) -> u64`, an object in, text out.
+///
+/// Output is returned as the pointer in the upper 32 bits and the length in the
+/// lower 32 bits.
+pub struct Module {
+    engine: Engine,
+    module: wasmtime::Module,
+    schema: Schema,
+}
+
+impl Module {
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+
+        let engine = Engine::new(&config).map_err(|err| eyre!("{err:#}"))?;
+        let module = wasmtime::Module::from_file(&engine, path).map_err(|err| eyre!("{err:#}"))?;
+
+        if let Some(import) = module.imports().next() {
+            return Err(eyre!(
+                "plugins can't import anything, found {}::{}",
+                import.module(),
+                import.name()
+            ));
+        }
+
+        let schema = call(&engine, &module, "kty_schema", None)?;
+
+        Ok(Self {
+            schema: serde_json::from_slice(&schema)?,
+            engine,
+            module,
+        })
+    }
+
+    /// Every `.wasm` file in `dir`, by name.
+    pub fn load_dir(dir: &Path) -> Result<Vec<Self>> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<_, _>>()?;
+
+        paths.retain(|path| path.extension().is_some_and(|ext| ext == "wasm"));
+        paths.sort();
+
+        paths
+            .iter()
+            .map(|path| {
+                Self::load(path).map_err(|err| eyre!("unable to load {}: {err}", path.display()))
+            })
+            .collect()
+    }
+
+    fn call(&self, export: &str, input: &[u8]) -> Result<Vec<u8>> {
+        call(&self.engine, &self.module, export, Some(input))
+    }
+}
+
+impl Plugin for Module {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    fn rows(&self, objects: &[DynamicObject]) -> Result<Vec<Row>> {
+        let rows: Vec<Row> =
+            serde_json::from_slice(&self.call("kty_rows", &serde_json::to_vec(objects)?)?)?;
+
+        if rows.len() != objects.len() {
+            return Err(eyre!(
+                "{} returned {} rows for {} objects",
+                self.schema.name,
+                rows.len(),
+                objects.len()
+            ));
+        }
+
+        Ok(rows)
+    }
+
+    fn detail(&self, object: &DynamicObject) -> Result<String> {
+        let output = self.call("kty_detail", &serde_json::to_vec(object)?)?;
+
+        Ok(String::from_utf8_lossy(&output).into_owned())
+    }
+}
+
+fn call(
+    engine: &Engine,
+    module: &wasmtime::Module,
+    export: &str,
+    input: Option<&[u8]>,
+) -> Result<Vec<u8>> {
+    try_call(engine, module, export, input).map_err(|err| eyre!("{export} failed: {err:#}"))
+}
+
+fn try_call(
+    engine: &Engine,
+    module: &wasmtime::Module,
+    export: &str,
+    input: Option<&[u8]>,
+) -> wasmtime::Result<Vec<u8>> {
+    let mut store = wasmtime::Store::new(
+        engine,
+        StoreLimitsBuilder::new().memory_size(MEMORY_LIMIT).build(),
+    );
+    store.limiter(|limits: &mut StoreLimits| limits);
+    store.set_fuel(FUEL)?;
+
+    let instance = Linker::<StoreLimits>::new(engine).instantiate(&mut store, module)?;
+    let memory = instance
+        .get_memory(&mut store, "memory")
+        .ok_or_else(|| wasmtime::Error::msg("memory isn't exported"))?;
+
+    let packed = match input {
+        Some(input) => {
+            let len = u32::try_from(input.len())?;
+            let ptr = instance
+                .get_typed_func::<u32, u32>(&mut store, "kty_alloc")?
+                .call(&mut store, len)?;
+
+            memory.write(&mut store, usize::try_from(ptr)?, input)?;
+
+            instance
+                .get_typed_func::<(u32, u32), u64>(&mut store, export)?
+                .call(&mut store, (ptr, len))?
+        }
+        None => instance
+            .get_typed_func::<(), u64>(&mut store, export)?
+            .call(&mut store, ())?,
+    };
+
+    let ptr = usize::try_from(packed >> 32)?;
+    let len = usize::try_from(packed & 0xffff_ffff)?;
+
+    let mut output = vec![0; len];
+    memory.read(&store, ptr, &mut output)?;
+
+    Ok(output)
+}
@@ -1,25 +1,63 @@
+// Managing the CRDs and generic helpers for other resources are only used by
+// the server and CLI.
+#![cfg_attr(not(feature = "ssh"), allow(dead_code, unused_imports))]
+
+pub mod action;
 pub mod age;
+pub mod alerts;
+pub mod bulk;
+pub mod columns;
+pub mod command;
 pub mod container;
+pub mod deployment;
+pub mod ephemeral;
+#[cfg(feature = "ssh")]
 pub mod file;
+pub mod gateway;
+pub mod gitops;
+pub mod hpa;
+pub mod idle;
+pub mod image;
+#[cfg(feature = "ssh")]
 pub mod install;
+pub mod metadata;
+#[cfg(feature = "ssh")]
+pub mod migrate;
+pub mod netpol;
 pub mod node;
+pub mod notes;
+pub mod orphan;
+pub mod overview;
+pub mod pdb;
 pub mod pod;
+pub mod policy;
+pub mod preview;
+pub mod probe;
+pub mod pv;
+pub mod pvc;
+pub mod redaction;
+pub mod requirements;
+pub mod rollout;
+pub mod saved;
+pub mod selector;
+#[cfg(feature = "ssh")]
+pub mod settings;
+pub mod snapshot;
 pub mod status;
 pub mod store;
+pub mod target;
+pub mod template;
+pub mod timeline;
 pub mod tunnel;
+pub mod workload;
 
-use color_eyre::Section;
 use eyre::{eyre, Result};
+#[cfg(feature = "ssh")]
 pub use file::File;
-use futures::StreamExt;
-use itertools::Itertools;
 use json_value_merge::Merge;
 use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
 use kube::{
-    api::{
-        Api, DynamicObject, GroupVersionKind, ObjectMeta, PartialObjectMetaExt, PatchParams,
-        PostParams, ResourceExt,
-    },
+    api::{Api, DynamicObject, GroupVersionKind, ObjectMeta, PartialObjectMetaExt, ResourceExt},
     core::discovery::Scope,
     discovery::pinned_kind,
     CustomResourceExt, Resource,
@@ -28,52 +66,20 @@ use regex::Regex;
 use serde::Serialize;
 pub use tunnel::Tunnel;
 
+#[cfg(feature = "ssh")]
 use crate::identity;
 
 pub static MANAGER: &str = "kkty.dev";
 
+#[cfg(feature = "ssh")]
 pub(crate) fn all() -> Vec<CustomResourceDefinition> {
-    vec![identity::key::Key::crd()]
-}
-
-pub(crate) async fn create(
-    client: &Api<CustomResourceDefinition>,
-    update: bool,
-) -> Result<Vec<CustomResourceDefinition>> {
-    tracing::info!(update = update, "updating CRD definitions...");
-
-    let results: Vec<_> = futures::stream::iter(all())
-        .map(|resource| async move {
-            tracing::info!("creating/updating CRD: {}", resource.name_any());
-
-            if update {
-                client
-                    .patch(
-                        &resource.name_any(),
-                        &PatchParams::apply("kty").force(),
-                        &kube::api::Patch::Apply(&resource),
-                    )
-                    .await
-            } else {
-                client.create(&PostParams::default(), &resource).await
-            }
-        })
-        .buffered(100)
-        .collect()
-        .await;
-
-    let (success, failure): (Vec<CustomResourceDefinition>, Vec<_>) =
-        results.into_iter().partition_result();
-
-    if !failure.is_empty() {
-        return Err(failure
-            .into_iter()
-            .fold(eyre!("unable to create resources"), |acc, err| {
-                acc.error(err)
-            }));
-    }
-
-    Ok(success)
+    vec![
+        identity::key::Key::crd(),
+        identity::history::History::crd(),
+        saved::SavedView::crd(),
+        settings::KtySettings::crd(),
+        policy::AccessPolicy::crd(),
+    ]
 }
 
 pub(crate) trait KubeID {
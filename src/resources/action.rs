@@ -0,0 +1,91 @@
+use eyre::{eyre, Result};
+use kube::{
+    api::{Api, ApiResource, DynamicObject, Patch, PatchParams},
+    Resource, ResourceExt,
+};
+use serde::Deserialize;
+
+/// Annotation containing a JSON list of actions, for example:
+///
+/// ```json
+/// [{"key": "p", "name": "Pause", "patch": {"spec": {"paused": true}}}]
+/// ```
+pub static ANNOTATION: &str = "kty.dev/actions";
+
+// Keys used for navigation can't be bound to an action.
+pub(crate) static RESERVED: &[char] = &['h', 'j', 'k', 'l', 'H', 'L', ' ', 'f', 'b', '^', '$'];
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Action {
+    pub key: char,
+    pub name: String,
+    /// JSON merge patch applied to the resource when the action is triggered.
+    pub patch: serde_json::Value,
+}
+
+impl Action {
+    /// Apply the patch, returning the updated resource. With `dry_run`, the API
+    /// server only checks what would happen.
+    pub async fn apply(
+        &self,
+        client: &Api<DynamicObject>,
+        name: &str,
+        dry_run: bool,
+    ) -> Result<DynamicObject> {
+        let params = PatchParams {
+            dry_run,
+            ..PatchParams::default()
+        };
+
+        Ok(client
+            .patch(name, &params, &Patch::Merge(&self.patch))
+            .await?)
+    }
+}
+
+/// Actions declared on `obj` via. [`ANNOTATION`]. This works for any resource,
+/// including custom resources that are only known at runtime.
+pub fn declared<K>(obj: &K) -> Result<Vec<Action>>
+where
+    K: ResourceExt,
+{
+    let Some(raw) = obj.annotations().get(ANNOTATION) else {
+        return Ok(Vec::new());
+    };
+
+    let actions: Vec<Action> =
+        serde_json::from_str(raw).map_err(|err| eyre!("invalid {ANNOTATION} annotation: {err}"))?;
+
+    if let Some(action) = actions.iter().find(|a| RESERVED.contains(&a.key)) {
+        return Err(eyre!(
+            "{} uses '{}', which is reserved for navigation",
+            action.name,
+            action.key
+        ));
+    }
+
+    Ok(actions)
+}
+
+pub trait Actions {
+    fn actions(&self) -> Result<Vec<Action>>;
+    fn dynamic_api(&self, client: kube::Client) -> Api<DynamicObject>;
+}
+
+impl<K> Actions for K
+where
+    K: Resource<DynamicType = ()>,
+{
+    fn actions(&self) -> Result<Vec<Action>> {
+        declared(self)
+    }
+
+    fn dynamic_api(&self, client: kube::Client) -> Api<DynamicObject> {
+        let ar = ApiResource::erase::<K>(&());
+
+        match self.namespace() {
+            Some(ns) => Api::namespaced_with(client, ns.as_str(), &ar),
+            None => Api::all_with(client, &ar),
+        }
+    }
+}
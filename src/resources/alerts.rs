@@ -0,0 +1,239 @@
+use std::{cell::RefCell, cmp::Ordering, collections::BTreeMap, rc::Rc, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use eyre::{eyre, Result};
+use ratatui::{
+    layout::Constraint,
+    widgets::{Cell, Row},
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{age::Age, policy::Policy, selector::Selector, Compare};
+use crate::widget::{
+    link::{Kind, Link},
+    table::{self, Health},
+};
+
+// Labels that point at a resource, most specific first. These are the ones
+// kube-state-metrics and the usual mixins attach.
+static TARGETS: [(&str, Kind); 5] = [
+    ("pod", Kind::Pods),
+    ("deployment", Kind::Deployments),
+    ("persistentvolumeclaim", Kind::Pvcs),
+    ("persistentvolume", Kind::Pvs),
+    ("node", Kind::Nodes),
+];
+
+/// Where firing alerts are read from, a service as
+/// `<namespace>/<name>:<port>`. Requests go through the API server's service
+/// proxy as the user, so they need `services/proxy` on it.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum AlertSource {
+    /// Alertmanager, silenced and inhibited alerts are left out.
+    Alertmanager(String),
+    /// The Prometheus rules API, alerts that are still pending are left out.
+    Prometheus(String),
+}
+
+impl AlertSource {
+    fn path(&self) -> Result<String> {
+        let (service, api) = match self {
+            Self::Alertmanager(service) => (
+                service,
+                "api/v2/alerts?active=true&silenced=false&inhibited=false",
+            ),
+            Self::Prometheus(service) => (service, "api/v1/alerts"),
+        };
+
+        let Some((namespace, name)) = service.split_once('/') else {
+            return Err(eyre!(
+                "invalid service, expected <namespace>/<name>:<port>: {service}"
+            ));
+        };
+
+        Ok(format!(
+            "/api/v1/namespaces/{namespace}/services/{name}/proxy/{api}"
+        ))
+    }
+
+    /// Every alert that is currently firing.
+    pub async fn fetch(&self, client: kube::Client) -> Result<Vec<Arc<Alert>>> {
+        let request = http::Request::get(self.path()?).body(Vec::new())?;
+
+        let alerts = match self {
+            Self::Alertmanager(_) => client.request::<Vec<Alert>>(request).await?,
+            Self::Prometheus(_) => client.request::<Rules>(request).await?.data.alerts,
+        };
+
+        let mut alerts: Vec<_> = alerts
+            .into_iter()
+            .filter(|alert| alert.state.as_deref().map_or(true, |s| s == "firing"))
+            .map(Arc::new)
+            .collect();
+
+        alerts.sort_by(Compare::cmp);
+
+        Ok(alerts)
+    }
+}
+
+#[derive(Deserialize)]
+struct Rules {
+    data: RulesData,
+}
+
+#[derive(Deserialize)]
+struct RulesData {
+    alerts: Vec<Alert>,
+}
+
+/// An alert as returned by either Alertmanager or Prometheus, they only differ
+/// in what the start is called.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Alert {
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub annotations: BTreeMap<String, String>,
+    #[serde(alias = "activeAt")]
+    pub starts_at: Option<DateTime<Utc>>,
+    // Only Prometheus sets this, it includes alerts that are still pending.
+    state: Option<String>,
+}
+
+impl Alert {
+    pub fn name(&self) -> &str {
+        self.labels
+            .get("alertname")
+            .map_or("<unknown>", String::as_str)
+    }
+
+    pub fn namespace(&self) -> Option<&str> {
+        self.labels.get("namespace").map(String::as_str)
+    }
+
+    pub fn severity(&self) -> &str {
+        self.labels.get("severity").map_or("none", String::as_str)
+    }
+
+    pub fn summary(&self) -> &str {
+        self.annotations
+            .get("summary")
+            .or_else(|| self.annotations.get("description"))
+            .map_or("", String::as_str)
+    }
+
+    /// The resource the alert is about, if its labels point at one that kty
+    /// has a detail view for.
+    pub fn link(&self) -> Option<Link> {
+        TARGETS.iter().find_map(|(label, kind)| {
+            let name = self.labels.get(*label)?;
+
+            Some(Link {
+                kind: *kind,
+                namespace: self
+                    .namespace()
+                    .filter(|_| kind.namespaced())
+                    .map(ToString::to_string),
+                name: Some(name.clone()),
+            })
+        })
+    }
+
+    // Critical alerts first.
+    fn rank(&self) -> u8 {
+        match self.severity() {
+            "critical" => 0,
+            "error" => 1,
+            "warning" => 2,
+            _ => 3,
+        }
+    }
+}
+
+/// Alerts from the last time they were fetched, shared with the table that
+/// shows them.
+#[derive(Clone, Default)]
+pub struct Firing(pub Rc<RefCell<Vec<Arc<Alert>>>>);
+
+impl table::Items for Firing {
+    type Item = Arc<Alert>;
+
+    fn items(&self, filter: Option<String>) -> Vec<Self::Item> {
+        let selector = filter.as_deref().map(Selector::from).unwrap_or_default();
+
+        self.0
+            .borrow()
+            .iter()
+            .filter(|alert| {
+                Policy::visible(alert.namespace())
+                    && selector.matches_fields(alert.name(), |key| {
+                        alert.labels.get(key).map(String::as_str)
+                    })
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+impl table::Row for Arc<Alert> {
+    fn header<'a>() -> Option<Row<'a>> {
+        Some(Row::new(vec![
+            Cell::from("Namespace"),
+            Cell::from("Name"),
+            Cell::from("Severity"),
+            Cell::from("Resource"),
+            Cell::from("Summary"),
+            Cell::from("Age"),
+        ]))
+    }
+
+    fn constraints() -> Vec<Constraint> {
+        vec![
+            Constraint::Max(20),
+            Constraint::Max(30),
+            Constraint::Max(10),
+            Constraint::Max(40),
+            Constraint::Fill(1),
+            Constraint::Max(10),
+        ]
+    }
+
+    fn row(&self, style: &table::RowStyle) -> Row {
+        let health = table::Row::health(self);
+
+        Row::new(vec![
+            self.namespace().unwrap_or_default().to_string(),
+            self.name().to_string(),
+            style.status(health, self.severity()),
+            self.link()
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+            self.summary().to_string(),
+            self.starts_at
+                .map(|at| (Utc::now() - at).to_age())
+                .unwrap_or_default(),
+        ])
+        .style(style.get(health))
+    }
+
+    fn health(&self) -> Health {
+        match self.severity() {
+            "critical" | "error" => Health::Unhealthy,
+            _ => Health::Normal,
+        }
+    }
+}
+
+impl Compare for Arc<Alert> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank()
+            .cmp(&other.rank())
+            .then_with(|| self.namespace().cmp(&other.namespace()))
+            .then_with(|| self.name().cmp(other.name()))
+    }
+}
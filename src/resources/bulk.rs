@@ -0,0 +1,187 @@
+use std::{str::FromStr, time::Duration};
+
+use chrono::Utc;
+use eyre::{eyre, Result};
+use k8s_openapi::api::apps::v1::Deployment;
+use kube::api::{Api, DeleteParams, DynamicObject, Patch, PatchParams};
+use serde_json::json;
+
+use super::{deployment::DeploymentExt, metadata::Field, policy::Verb};
+
+// Same annotation that `kubectl rollout restart` uses.
+static RESTARTED_AT: &str = "kubectl.kubernetes.io/restartedAt";
+// How often to check on a rollout and how long to wait for it before giving up.
+static ROLLOUT_INTERVAL: Duration = Duration::from_secs(2);
+static ROLLOUT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Operations that can be applied to many resources at once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Delete,
+    Cordon,
+    Uncordon,
+    Label,
+    Restart,
+}
+
+impl Operation {
+    pub fn key(self) -> char {
+        match self {
+            Self::Delete => 'd',
+            Self::Cordon => 'c',
+            Self::Uncordon => 'u',
+            Self::Label => 'l',
+            Self::Restart => 'r',
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Delete => "Delete",
+            Self::Cordon => "Cordon",
+            Self::Uncordon => "Uncordon",
+            Self::Label => "Label",
+            Self::Restart => "Restart",
+        }
+    }
+
+    /// Number of resources the operation runs against at once. Restarts wait
+    /// for the rollout to finish, so a low limit keeps from taking too much
+    /// capacity out of the cluster at the same time.
+    pub fn concurrency(self) -> usize {
+        match self {
+            Self::Restart => 2,
+            _ => 10,
+        }
+    }
+
+    pub fn verb(self) -> Verb {
+        match self {
+            Self::Delete => Verb::Delete,
+            _ => Verb::Patch,
+        }
+    }
+
+    /// Labels need a key and value before they can be applied.
+    pub fn needs_label(self) -> bool {
+        matches!(self, Self::Label)
+    }
+
+    /// Apply the operation, returning the updated resource or `None` when it
+    /// has been deleted. With `dry_run`, the API server only checks what would
+    /// happen and restarts don't wait for a rollout.
+    pub async fn apply(
+        self,
+        client: &Api<DynamicObject>,
+        name: &str,
+        label: Option<&Label>,
+        dry_run: bool,
+    ) -> Result<Option<DynamicObject>> {
+        let patch = match self {
+            // The default for some resources, such as jobs, is to leave what
+            // they own behind.
+            Self::Delete => {
+                let params = DeleteParams {
+                    dry_run,
+                    ..DeleteParams::background()
+                };
+
+                client.delete(name, &params).await?;
+
+                return Ok(None);
+            }
+            Self::Cordon => json!({"spec": {"unschedulable": true}}),
+            Self::Uncordon => json!({"spec": {"unschedulable": null}}),
+            Self::Label => {
+                let label = label.ok_or_else(|| eyre!("no label provided"))?;
+
+                json!({"metadata": {"labels": {label.key.as_str(): label.value}}})
+            }
+            Self::Restart => json!({"spec": {"template": {"metadata": {"annotations": {
+                RESTARTED_AT: Utc::now().to_rfc3339(),
+            }}}}}),
+        };
+
+        let params = PatchParams {
+            dry_run,
+            ..PatchParams::default()
+        };
+
+        let obj = client.patch(name, &params, &Patch::Merge(&patch)).await?;
+
+        if self == Self::Restart && !dry_run {
+            tokio::time::timeout(ROLLOUT_TIMEOUT, rollout(client, name))
+                .await
+                .map_err(|_| eyre!("timed out waiting for the rollout to finish"))??;
+        }
+
+        Ok(Some(obj))
+    }
+}
+
+async fn rollout(client: &Api<DynamicObject>, name: &str) -> Result<()> {
+    loop {
+        let obj = client.get(name).await?;
+        let deployment: Deployment = serde_json::from_value(serde_json::to_value(obj)?)?;
+
+        if deployment.is_rolled_out() {
+            return Ok(());
+        }
+
+        if let Some(reason) = deployment
+            .status
+            .as_ref()
+            .and_then(|s| s.conditions.as_ref())
+            .and_then(|conditions| conditions.iter().find(|c| c.type_ == "Progressing"))
+            .and_then(|c| c.reason.as_deref())
+            .filter(|reason| *reason == "ProgressDeadlineExceeded")
+        {
+            return Err(eyre!("rollout failed: {reason}"));
+        }
+
+        tokio::time::sleep(ROLLOUT_INTERVAL).await;
+    }
+}
+
+/// A label to set, `key=value`, or remove, `key-`. This follows `kubectl
+/// label`.
+#[derive(Clone, Debug)]
+pub struct Label {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+impl FromStr for Label {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(key) = s.strip_suffix('-') {
+            Field::Label.validate(key, "")?;
+
+            return Ok(Self {
+                key: key.to_string(),
+                value: None,
+            });
+        }
+
+        let Some((key, value)) = s.split_once('=') else {
+            return Err(eyre!("expected key=value or key-"));
+        };
+
+        Field::Label.validate(key, value)?;
+
+        Ok(Self {
+            key: key.to_string(),
+            value: Some(value.to_string()),
+        })
+    }
+}
+
+impl std::fmt::Display for Label {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{}={value}", self.key),
+            None => write!(f, "{}-", self.key),
+        }
+    }
+}
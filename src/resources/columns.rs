@@ -0,0 +1,253 @@
+use std::str::FromStr;
+
+use eyre::{eyre, Report, Result};
+use kube::Resource;
+use ratatui::{
+    layout::Constraint,
+    widgets::{Cell, Row},
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::dashboard::context::Context;
+
+/// A column admins have added to a table, for fields that matter to their
+/// organization such as the team a pod belongs to.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Column {
+    /// Shown in the header.
+    pub name: String,
+    /// Kind of the table it's added to. One of `Pod`, `Node`,
+    /// `PersistentVolume`, `NetworkPolicy` or `PodDisruptionBudget`.
+    pub kind: String,
+    /// Where the value is, the same as `kubectl get -o jsonpath`. For example
+    /// `.spec.nodeName`, `.metadata.labels.team` or
+    /// `.spec.containers[*].image`. Dots in keys are escaped, as in
+    /// `.metadata.labels.app\.kubernetes\.io/name`.
+    pub path: String,
+    /// Columns without a width share whatever space is left.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u16>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Field(String),
+    Index(usize),
+    All,
+}
+
+/// A jsonpath expression, limited to what's useful for picking a field out of
+/// a resource: keys, indexes and `[*]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Path(Vec<Segment>);
+
+impl FromStr for Path {
+    type Err = Report;
+
+    fn from_str(path: &str) -> Result<Self> {
+        // `{.spec.nodeName}` and `$.spec.nodeName` are both what `kubectl` takes.
+        let path = path.trim();
+        let path = path
+            .strip_prefix('{')
+            .and_then(|p| p.strip_suffix('}'))
+            .unwrap_or(path);
+        let path = path.strip_prefix('$').unwrap_or(path);
+
+        let mut segments = Vec::new();
+        let mut chars = path.chars().peekable();
+
+        while let Some(c) = chars.peek().copied() {
+            match c {
+                '[' => {
+                    chars.next();
+
+                    let inner: String = chars.by_ref().take_while(|c| *c != ']').collect();
+                    let inner = inner.trim();
+
+                    segments.push(if inner == "*" {
+                        Segment::All
+                    } else if let Ok(idx) = inner.parse() {
+                        Segment::Index(idx)
+                    } else if let Some(key) = inner
+                        .strip_prefix('\'')
+                        .and_then(|k| k.strip_suffix('\''))
+                        .or_else(|| inner.strip_prefix('"').and_then(|k| k.strip_suffix('"')))
+                    {
+                        Segment::Field(key.to_string())
+                    } else {
+                        return Err(eyre!("invalid index in {path}: [{inner}]"));
+                    });
+                }
+                _ => {
+                    if c == '.' {
+                        chars.next();
+                    }
+
+                    let mut key = String::new();
+
+                    while let Some(c) = chars.peek().copied() {
+                        match c {
+                            '.' | '[' => break,
+                            '\\' => {
+                                chars.next();
+                                key.extend(chars.next());
+                            }
+                            _ => {
+                                chars.next();
+                                key.push(c);
+                            }
+                        }
+                    }
+
+                    match key.as_str() {
+                        "" if chars.peek().is_some_and(|c| *c == '[') => {}
+                        "" => return Err(eyre!("empty key in {path}")),
+                        "*" => segments.push(Segment::All),
+                        _ => segments.push(Segment::Field(key)),
+                    }
+                }
+            }
+        }
+
+        if segments.is_empty() {
+            return Err(eyre!("empty path"));
+        }
+
+        Ok(Self(segments))
+    }
+}
+
+impl Path {
+    /// Everything the path matches in `value`, `[*]` can match more than one.
+    fn find<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        self.0.iter().fold(vec![value], |values, segment| {
+            values
+                .into_iter()
+                .flat_map(|value| match (segment, value) {
+                    (Segment::Field(key), Value::Object(map)) => map.get(key).into_iter().collect(),
+                    (Segment::Index(idx), Value::Array(items)) => {
+                        items.get(*idx).into_iter().collect()
+                    }
+                    (Segment::All, Value::Array(items)) => items.iter().collect(),
+                    (Segment::All, Value::Object(map)) => map.values().collect(),
+                    _ => Vec::new(),
+                })
+                .collect()
+        })
+    }
+
+    /// What is shown in the cell, multiple matches are separated by commas.
+    pub fn render(&self, value: &Value) -> String {
+        self.find(value)
+            .into_iter()
+            .filter_map(|value| match value {
+                Value::Null => None,
+                Value::String(s) => Some(s.clone()),
+                _ => Some(value.to_string()),
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// The extra columns of every table, with their paths parsed up front so that
+/// drawing a row only has to look the values up.
+#[derive(Clone, Debug, Default)]
+pub struct Columns {
+    columns: Vec<(Column, Path)>,
+}
+
+impl Columns {
+    /// Columns with a path that doesn't parse are skipped.
+    pub fn new(columns: Vec<Column>) -> Self {
+        let columns = columns
+            .into_iter()
+            .filter_map(|column| {
+                let path = column
+                    .path
+                    .parse::<Path>()
+                    .inspect_err(|err| {
+                        tracing::warn!(column = %column.name, ?err, "invalid column path");
+                    })
+                    .ok()?;
+
+                Some((column, path))
+            })
+            .collect();
+
+        Self { columns }
+    }
+
+    fn with<K, T>(f: impl FnOnce(Vec<&(Column, Path)>) -> T) -> T
+    where
+        K: Resource<DynamicType = ()>,
+    {
+        let kind = K::kind(&());
+
+        Context::with(|context| {
+            f(context
+                .columns
+                .columns
+                .iter()
+                .filter(|(column, _)| column.kind.eq_ignore_ascii_case(&kind))
+                .collect())
+        })
+    }
+}
+
+/// `cells` followed by the names of the extra columns for `K`.
+pub fn header<'a, K>(cells: Vec<Cell<'a>>) -> Row<'a>
+where
+    K: Resource<DynamicType = ()>,
+{
+    Columns::with::<K, _>(|columns| {
+        Row::new(
+            cells.into_iter().chain(
+                columns
+                    .into_iter()
+                    .map(|(column, _)| Cell::from(column.name.clone())),
+            ),
+        )
+    })
+}
+
+/// `constraints` followed by the widths of the extra columns for `K`.
+pub fn constraints<K>(constraints: Vec<Constraint>) -> Vec<Constraint>
+where
+    K: Resource<DynamicType = ()>,
+{
+    Columns::with::<K, _>(|columns| {
+        constraints
+            .into_iter()
+            .chain(
+                columns
+                    .into_iter()
+                    .map(|(column, _)| column.width.map_or(Constraint::Fill(1), Constraint::Max)),
+            )
+            .collect()
+    })
+}
+
+/// `cells` followed by the values of the extra columns for `obj`. The object is
+/// only serialized when there are any.
+pub fn cells<K>(obj: &K, cells: Vec<String>) -> Vec<String>
+where
+    K: Resource<DynamicType = ()> + Serialize,
+{
+    Columns::with::<K, _>(|columns| {
+        if columns.is_empty() {
+            return cells;
+        }
+
+        let Ok(value) = serde_json::to_value(obj) else {
+            return cells;
+        };
+
+        cells
+            .into_iter()
+            .chain(columns.into_iter().map(|(_, path)| path.render(&value)))
+            .collect()
+    })
+}
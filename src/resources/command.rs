@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use eyre::{eyre, Result};
+use k8s_openapi::api::core::v1::Pod;
+use kube::ResourceExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::action::RESERVED;
+use crate::{dashboard::context::Context, launch::Launch};
+
+/// A program admins have configured for the dashboard, such as `stern`. It runs
+/// on the server with access to the cluster as the user and only when the
+/// program has been allowed with `kty serve --allow-command`.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Command {
+    /// Runs the command from the `Commands` tab of a pod.
+    pub key: char,
+    pub name: String,
+    /// The program and its arguments. `{namespace}`, `{pod}` and `{node}` are
+    /// replaced with those of the selected pod.
+    pub command: Vec<String>,
+}
+
+impl Command {
+    /// The command line to run for `pod`.
+    pub fn argv(&self, pod: &Pod) -> Vec<String> {
+        let namespace = pod.namespace().unwrap_or_default();
+        let name = pod.name_any();
+        let node = pod
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.node_name.clone())
+            .unwrap_or_default();
+
+        self.command
+            .iter()
+            .map(|arg| {
+                arg.replace("{namespace}", &namespace)
+                    .replace("{pod}", &name)
+                    .replace("{node}", &node)
+            })
+            .collect()
+    }
+}
+
+/// The commands a dashboard can run and what runs them.
+#[derive(Clone, Default)]
+pub struct Commands {
+    commands: Vec<Command>,
+    launch: Option<Arc<dyn Launch>>,
+}
+
+impl Commands {
+    /// Commands bound to navigation keys, or without a program, are an error.
+    pub fn new(commands: Vec<Command>, launch: Arc<dyn Launch>) -> Result<Self> {
+        if let Some(command) = commands.iter().find(|c| RESERVED.contains(&c.key)) {
+            return Err(eyre!(
+                "{} uses '{}', which is reserved for navigation",
+                command.name,
+                command.key
+            ));
+        }
+
+        if let Some(command) = commands.iter().find(|c| c.command.is_empty()) {
+            return Err(eyre!("{} doesn't have a command", command.name));
+        }
+
+        Ok(Self {
+            commands,
+            launch: Some(launch),
+        })
+    }
+
+    /// The commands of the current dashboard, empty when there's nothing to run
+    /// them.
+    pub fn current() -> Option<(Vec<Command>, Arc<dyn Launch>)> {
+        Context::with(|context| {
+            let current = &context.commands;
+            let launch = current.launch.clone()?;
+
+            (!current.commands.is_empty()).then(|| (current.commands.clone(), launch))
+        })
+    }
+}
@@ -1,6 +1,9 @@
+pub mod browse;
+#[cfg(feature = "ssh")]
 pub mod file;
 
 use chrono::Utc;
+#[cfg(feature = "ssh")]
 #[allow(clippy::module_name_repetitions)]
 pub use file::ContainerFiles;
 use k8s_openapi::api::core::v1::{
@@ -14,7 +17,7 @@ use ratatui::{
 };
 
 use super::{age::Age, Compare};
-use crate::widget::table;
+use crate::widget::table::{self, Health};
 
 #[allow(clippy::module_name_repetitions)]
 pub trait ContainerExt {
@@ -220,18 +223,21 @@ impl table::Row for Container {
     }
 
     fn row(&self, style: &table::RowStyle) -> Row {
+        let state = self.state();
+        let health = match state {
+            State::Running | State::Waiting(_) => Health::Normal,
+            _ => Health::Unhealthy,
+        };
+
         Row::new(vec![
             Cell::from(self.name_any()),
             Cell::from(self.image()),
             Cell::from(self.ready()),
-            Cell::from(self.state().to_string()),
+            Cell::from(style.status(health, state.to_string())),
             Cell::from(self.restarts()),
             Cell::from(self.age()),
         ])
-        .style(match self.state() {
-            State::Running | State::Waiting(_) => style.normal,
-            _ => style.unhealthy,
-        })
+        .style(style.get(health))
     }
 }
 
@@ -0,0 +1,225 @@
+//! Listing and reading files in a container for the file browser. Like the
+//! sftp server, this is all `exec`. There's no other way to get at a
+//! container's filesystem.
+use std::path::Path;
+
+use eyre::Result;
+
+use super::Container;
+use crate::resources::pod::Proc;
+
+/// Previews stop after this many bytes, anything bigger should be downloaded.
+pub static PREVIEW_BYTES: usize = 64 * 1024;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Dir,
+    File,
+    /// Where the link points, it could be to a file or a directory.
+    Link(String),
+    /// Devices, sockets and pipes.
+    Other,
+}
+
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub name: String,
+    pub kind: Kind,
+    pub mode: String,
+    pub size: u64,
+}
+
+impl Entry {
+    fn new(name: &str, mode: &str, size: u64, target: Option<&str>) -> Option<Self> {
+        let kind = match mode.chars().next()? {
+            'd' => Kind::Dir,
+            '-' => Kind::File,
+            'l' => Kind::Link(target.unwrap_or_default().to_string()),
+            'b' | 'c' | 'p' | 's' => Kind::Other,
+            _ => return None,
+        };
+
+        Some(Self {
+            name: name.to_string(),
+            kind,
+            mode: mode.to_string(),
+            size,
+        })
+    }
+
+    // The fields of one entry from `find -printf`, see `FIND_FORMAT`.
+    fn from_find(fields: &[&str]) -> Option<Self> {
+        let [mode, size, name, target] = fields else {
+            return None;
+        };
+
+        Self::new(
+            name,
+            mode,
+            size.parse().unwrap_or_default(),
+            Some(target).filter(|target| !target.is_empty()).copied(),
+        )
+    }
+
+    // A line of `ls -l`. GNU and BusyBox agree on the columns, the date is
+    // three of them in either. Devices have a major and minor number instead of
+    // a size. The name is everything after the date so that it can contain
+    // spaces.
+    fn from_ls(line: &str) -> Option<Self> {
+        let mut rest = line;
+        let mut next = || {
+            rest = rest.trim_start();
+            let end = rest.find(' ')?;
+            let field = &rest[..end];
+            rest = &rest[end..];
+
+            Some(field)
+        };
+
+        let mode = next()?;
+        let _links = next()?;
+        let _owner = next()?;
+        let _group = next()?;
+
+        let mut size = next()?;
+        // `8, 1` for devices, some versions leave out the space.
+        if size.ends_with(',') {
+            next()?;
+            size = "0";
+        }
+
+        for _ in 0..3 {
+            next()?;
+        }
+
+        let name = rest.trim_start();
+
+        let (name, target) = match name.split_once(" -> ") {
+            Some((name, target)) if mode.starts_with('l') => (name, Some(target)),
+            _ => (name, None),
+        };
+
+        Self::new(name, mode, size.parse().unwrap_or_default(), target)
+    }
+}
+
+// Mode, size, name and where links point. NUL can't be part of a name, so this
+// is unambiguous no matter what the entries are called.
+static FIND_FORMAT: &str = "%M\\0%s\\0%P\\0%l\\0";
+
+async fn find(client: kube::Client, container: &Container, dir: &str) -> Result<Vec<Entry>> {
+    let (out, _) = Proc::new(container.clone())
+        .exec(
+            client,
+            vec![
+                "find",
+                dir,
+                "-mindepth",
+                "1",
+                "-maxdepth",
+                "1",
+                "-printf",
+                FIND_FORMAT,
+            ],
+        )
+        .await?;
+
+    Ok(parse_find(std::str::from_utf8(&out)?))
+}
+
+fn parse_find(out: &str) -> Vec<Entry> {
+    let fields: Vec<_> = out.strip_suffix('\0').unwrap_or(out).split('\0').collect();
+
+    fields.chunks(4).filter_map(Entry::from_find).collect()
+}
+
+async fn ls(client: kube::Client, container: &Container, dir: &str) -> Result<Vec<Entry>> {
+    let (out, _) = Proc::new(container.clone())
+        .exec(client, vec!["ls", "-lA", dir])
+        .await?;
+
+    Ok(std::str::from_utf8(&out)?
+        .lines()
+        .filter_map(Entry::from_ls)
+        .collect())
+}
+
+/// A bounded chunk of the start of a file.
+pub struct Preview {
+    /// Not set when the file looks like it is binary.
+    pub text: Option<String>,
+    pub truncated: bool,
+}
+
+/// Contents of a directory, directories first and then alphabetical.
+pub async fn list(client: kube::Client, container: &Container, path: &Path) -> Result<Vec<Entry>> {
+    // The trailing slash lists what a symlink points to instead of the link.
+    let dir = format!("{}/", path.to_string_lossy().trim_end_matches('/'));
+
+    // GNU find describes entries in a way that doesn't need any guessing, BusyBox
+    // doesn't have `-printf` though. `ls -l` is everywhere but has to be taken
+    // apart by column.
+    let mut entries = match find(client.clone(), container, &dir).await {
+        Ok(entries) => entries,
+        Err(_) => ls(client, container, &dir).await?,
+    };
+
+    entries.sort_by(|a, b| (a.kind != Kind::Dir, &a.name).cmp(&(b.kind != Kind::Dir, &b.name)));
+
+    Ok(entries)
+}
+
+pub async fn preview(client: kube::Client, container: &Container, path: &Path) -> Result<Preview> {
+    // One extra byte is read to know whether there was more.
+    let limit = (PREVIEW_BYTES + 1).to_string();
+    let path = path.to_string_lossy();
+
+    let (mut out, _) = Proc::new(container.clone())
+        .exec(client, vec!["head", "-c", limit.as_str(), path.as_ref()])
+        .await?;
+
+    let truncated = out.len() > PREVIEW_BYTES;
+    out.truncate(PREVIEW_BYTES);
+
+    Ok(Preview {
+        text: (!out.contains(&0)).then(|| String::from_utf8_lossy(&out).into_owned()),
+        truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_find() {
+        let out = "drwxr-xr-x\x004096\x00etc\x00\x00lrwxrwxrwx\x007\x00a b\x00/usr/bin\x00";
+        let entries = parse_find(out);
+
+        assert_eq!(entries[0].name, "etc");
+        assert_eq!(entries[0].kind, Kind::Dir);
+        assert_eq!(entries[1].name, "a b");
+        assert_eq!(entries[1].kind, Kind::Link("/usr/bin".to_string()));
+    }
+
+    #[test]
+    fn parses_ls() {
+        let out = "\
+total 8
+-rw-r--r--    1 root     root          1234 Jan  1 12:00 hello world.txt
+lrwxrwxrwx    1 root     root            12 Mar 10  2023 sh -> /bin/busybox
+crw-rw-rw-    1 root     root        1,   3 Oct 16 09:21 null
+brw-rw----    1 root     disk      8,0 Oct 16 09:21 sda
+";
+        let entries: Vec<_> = out.lines().filter_map(Entry::from_ls).collect();
+
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].name, "hello world.txt");
+        assert_eq!(entries[0].size, 1234);
+        assert_eq!(entries[1].name, "sh");
+        assert_eq!(entries[1].kind, Kind::Link("/bin/busybox".to_string()));
+        assert_eq!(entries[2].name, "null");
+        assert_eq!(entries[2].kind, Kind::Other);
+        assert_eq!(entries[3].name, "sda");
+    }
+}
@@ -0,0 +1,207 @@
+use std::{cmp::Ordering, sync::Arc};
+
+use chrono::{TimeDelta, Utc};
+use k8s_openapi::api::{apps::v1::Deployment, autoscaling::v2::HorizontalPodAutoscaler};
+use kube::ResourceExt;
+use ratatui::{
+    layout::Constraint,
+    widgets::{Cell, Row},
+};
+
+use super::{age::Age, hpa::HorizontalPodAutoscalerExt, store::Store, Compare, Filter};
+use crate::widget::{
+    table::{self, Health},
+    yank::Yank,
+};
+
+#[allow(clippy::module_name_repetitions)]
+pub trait DeploymentExt {
+    fn age(&self) -> TimeDelta;
+    fn ready(&self) -> String;
+    fn up_to_date(&self) -> i32;
+    fn available(&self) -> i32;
+    fn is_rolled_out(&self) -> bool;
+}
+
+impl DeploymentExt for Deployment {
+    fn age(&self) -> TimeDelta {
+        let Some(creation) = self.creation_timestamp() else {
+            return TimeDelta::zero();
+        };
+
+        Utc::now() - creation.0
+    }
+
+    fn ready(&self) -> String {
+        format!(
+            "{}/{}",
+            self.status
+                .as_ref()
+                .and_then(|s| s.ready_replicas)
+                .unwrap_or_default(),
+            self.spec.as_ref().and_then(|s| s.replicas).unwrap_or(1)
+        )
+    }
+
+    fn up_to_date(&self) -> i32 {
+        self.status
+            .as_ref()
+            .and_then(|s| s.updated_replicas)
+            .unwrap_or_default()
+    }
+
+    fn available(&self) -> i32 {
+        self.status
+            .as_ref()
+            .and_then(|s| s.available_replicas)
+            .unwrap_or_default()
+    }
+
+    // Matches what `kubectl rollout status` waits for.
+    fn is_rolled_out(&self) -> bool {
+        let Some(status) = self.status.as_ref() else {
+            return false;
+        };
+
+        let replicas = self.spec.as_ref().and_then(|s| s.replicas).unwrap_or(1);
+
+        status.observed_generation >= self.metadata.generation
+            && status.updated_replicas.unwrap_or_default() >= replicas
+            && status.replicas.unwrap_or_default() <= replicas
+            && status.available_replicas.unwrap_or_default() >= replicas
+    }
+}
+
+/// A deployment along with the autoscaler that manages its replicas, if there
+/// is one.
+pub struct Scaled {
+    pub deployment: Arc<Deployment>,
+    pub autoscaler: Option<Arc<HorizontalPodAutoscaler>>,
+}
+
+impl table::Row for Scaled {
+    fn yank(&self) -> Option<Yank> {
+        Some(Yank::from_obj(self.deployment.as_ref()))
+    }
+
+    fn id(&self) -> Option<String> {
+        self.deployment.uid()
+    }
+
+    fn header<'a>() -> Option<Row<'a>> {
+        Some(Row::new(vec![
+            Cell::from("Namespace"),
+            Cell::from("Name"),
+            Cell::from("Ready"),
+            Cell::from("Up-to-date"),
+            Cell::from("Available"),
+            Cell::from("Autoscaling"),
+            Cell::from("Targets"),
+            Cell::from("Age"),
+        ]))
+    }
+
+    fn constraints() -> Vec<Constraint> {
+        vec![
+            Constraint::Max(20),
+            Constraint::Min(10),
+            Constraint::Max(10),
+            Constraint::Max(12),
+            Constraint::Max(10),
+            Constraint::Max(12),
+            Constraint::Min(10),
+            Constraint::Max(10),
+        ]
+    }
+
+    fn row(&self, style: &table::RowStyle) -> Row {
+        let deployment = &self.deployment;
+
+        let health = if deployment.is_rolled_out() {
+            Health::Healthy
+        } else {
+            Health::Normal
+        };
+
+        // Shown as `desired (min-max)`, like `kubectl get hpa`.
+        let (autoscaling, targets) = self.autoscaler.as_ref().map_or_else(
+            || (String::new(), String::new()),
+            |hpa| {
+                (
+                    format!("{} ({}-{})", hpa.desired(), hpa.min(), hpa.max()),
+                    hpa.targets(),
+                )
+            },
+        );
+
+        Row::new(vec![
+            deployment.namespace().unwrap_or_default(),
+            deployment.name_any(),
+            style.status(health, deployment.ready()),
+            deployment.up_to_date().to_string(),
+            deployment.available().to_string(),
+            autoscaling,
+            targets,
+            deployment.age().to_age(),
+        ])
+        .style(style.get(health))
+    }
+}
+
+impl Filter for Deployment {
+    fn matches(&self, filter: &str) -> bool {
+        self.name_any().contains(filter) || self.namespace().is_some_and(|ns| ns.contains(filter))
+    }
+}
+
+impl Compare for Arc<Deployment> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let lhs = self
+            .namespace()
+            .unwrap_or_default()
+            .cmp(&other.namespace().unwrap_or_default());
+
+        if lhs != Ordering::Equal {
+            return lhs;
+        }
+
+        self.name_any().cmp(&other.name_any())
+    }
+}
+
+/// Joins deployments with the autoscalers that scale them.
+pub struct Deployments {
+    pub deployments: Arc<Store<Deployment>>,
+    pub autoscalers: Arc<Store<HorizontalPodAutoscaler>>,
+}
+
+impl table::Items for Deployments {
+    type Item = Scaled;
+
+    fn items(&self, filter: Option<String>) -> Vec<Self::Item> {
+        let autoscalers = self.autoscalers.items(None);
+
+        self.deployments
+            .items(filter)
+            .into_iter()
+            .map(|deployment| Scaled {
+                autoscaler: autoscaler(&deployment, &autoscalers),
+                deployment,
+            })
+            .collect()
+    }
+}
+
+/// The autoscaler, out of `autoscalers`, that scales `deployment`.
+pub fn autoscaler(
+    deployment: &Deployment,
+    autoscalers: &[Arc<HorizontalPodAutoscaler>],
+) -> Option<Arc<HorizontalPodAutoscaler>> {
+    autoscalers
+        .iter()
+        .find(|hpa| {
+            hpa.namespace() == deployment.namespace()
+                && hpa.scales("Deployment", &deployment.name_any())
+        })
+        .cloned()
+}
@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use eyre::{eyre, Result};
+use k8s_openapi::api::core::v1::{self, EphemeralContainer, Pod};
+use kube::api::{Api, Patch, PatchParams};
+use serde_json::json;
+
+use super::container::State;
+
+// How often to check on the container and how long to wait for it to start.
+static START_INTERVAL: Duration = Duration::from_secs(1);
+static START_TIMEOUT: Duration = Duration::from_secs(120);
+
+// Reasons a container will never start on its own.
+static FAILED: [&str; 4] = [
+    "ErrImagePull",
+    "ImagePullBackOff",
+    "InvalidImageName",
+    "CreateContainerError",
+];
+
+/// An ephemeral container, the same as `kubectl debug --target`. It shares the
+/// process namespace of `target` so that its processes can be inspected.
+pub fn container(image: &str, target: &str) -> EphemeralContainer {
+    EphemeralContainer {
+        name: format!("debugger-{}", Utc::now().format("%H%M%S")),
+        image: Some(image.to_string()),
+        target_container_name: Some(target.to_string()),
+        stdin: Some(true),
+        tty: Some(true),
+        ..Default::default()
+    }
+}
+
+/// Add `container` to the pod and wait for it to start running. The returned
+/// pod includes the container's status.
+pub async fn inject(api: &Api<Pod>, pod: &str, container: &EphemeralContainer) -> Result<Pod> {
+    api.patch_ephemeral_containers(
+        pod,
+        &PatchParams::default(),
+        &Patch::Strategic(json!({"spec": {"ephemeralContainers": [container]}})),
+    )
+    .await?;
+
+    tokio::time::timeout(START_TIMEOUT, wait(api, pod, &container.name))
+        .await
+        .map_err(|_| eyre!("timed out waiting for {} to start", container.name))?
+}
+
+async fn wait(api: &Api<Pod>, pod: &str, name: &str) -> Result<Pod> {
+    loop {
+        let current = api.get(pod).await?;
+
+        let state = current
+            .status
+            .as_ref()
+            .and_then(|status| status.ephemeral_container_statuses.as_ref())
+            .and_then(|statuses| statuses.iter().find(|status| status.name == name))
+            .map(|status| State::from(status.state.as_ref()));
+
+        match state {
+            Some(State::Running) => return Ok(current),
+            Some(State::Terminated(reason)) => return Err(eyre!("{name} exited: {reason}")),
+            Some(State::Waiting(reason)) if FAILED.contains(&reason.as_str()) => {
+                return Err(eyre!("{name} can't start: {reason}"));
+            }
+            _ => {}
+        }
+
+        tokio::time::sleep(START_INTERVAL).await;
+    }
+}
+
+/// Ephemeral containers aren't part of the pod's containers, this is what exec
+/// needs to find one.
+pub fn as_container(container: &EphemeralContainer) -> v1::Container {
+    v1::Container {
+        name: container.name.clone(),
+        image: container.image.clone(),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,402 @@
+use std::{cmp::Ordering, sync::Arc};
+
+use chrono::{TimeDelta, Utc};
+use itertools::Itertools;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+use kube::{CustomResource, ResourceExt};
+use ratatui::{
+    layout::Constraint,
+    widgets::{Cell, Row},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{age::Age, store::Store, Compare, Filter};
+use crate::widget::table::{self, Health};
+
+// Only the parts of the Gateway API that are shown are modeled, the CRDs
+// themselves come from whichever implementation is installed.
+
+/// Routes HTTP requests from a gateway to services.
+#[derive(CustomResource, Clone, Debug, Default, Serialize, Deserialize)]
+#[kube(
+    group = "gateway.networking.k8s.io",
+    version = "v1",
+    kind = "HTTPRoute",
+    namespaced,
+    status = "RouteStatus",
+    schema = "disabled"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct HTTPRouteSpec {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parent_refs: Vec<ParentReference>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hostnames: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<HTTPRouteRule>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParentReference {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section_name: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HTTPRouteRule {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub backend_refs: Vec<BackendRef>,
+}
+
+/// Where requests are sent, a service unless `kind` says otherwise.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendRef {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<i32>,
+}
+
+impl BackendRef {
+    pub fn is_service(&self) -> bool {
+        self.kind.as_deref().unwrap_or("Service") == "Service"
+    }
+}
+
+impl std::fmt::Display for BackendRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.port {
+            Some(port) => write!(f, "{}:{port}", self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteStatus {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parents: Vec<RouteParentStatus>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteParentStatus {
+    pub parent_ref: ParentReference,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<Condition>,
+}
+
+/// Where traffic enters the cluster, routes attach to its listeners.
+#[allow(clippy::module_name_repetitions)]
+#[derive(CustomResource, Clone, Debug, Default, Serialize, Deserialize)]
+#[kube(
+    group = "gateway.networking.k8s.io",
+    version = "v1",
+    kind = "Gateway",
+    namespaced,
+    status = "GatewayStatus",
+    schema = "disabled"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewaySpec {
+    pub gateway_class_name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub listeners: Vec<Listener>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Listener {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    pub port: i32,
+    pub protocol: String,
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewayStatus {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub addresses: Vec<GatewayAddress>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<Condition>,
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewayAddress {
+    pub value: String,
+}
+
+impl HTTPRoute {
+    /// Every backend from every rule, without duplicates.
+    pub fn backends(&self) -> Vec<BackendRef> {
+        self.spec
+            .rules
+            .iter()
+            .flat_map(|rule| rule.backend_refs.iter().cloned())
+            .sorted()
+            .dedup()
+            .collect()
+    }
+
+    // Rejected by a gateway or pointing at something that doesn't exist.
+    fn health(&self) -> Health {
+        let conditions: Vec<_> = self
+            .status
+            .iter()
+            .flat_map(|status| &status.parents)
+            .flat_map(|parent| &parent.conditions)
+            .collect();
+
+        if conditions.iter().any(|c| c.status == "False") {
+            Health::Unhealthy
+        } else if conditions.is_empty() {
+            Health::Normal
+        } else {
+            Health::Healthy
+        }
+    }
+}
+
+impl Gateway {
+    fn health(&self) -> Health {
+        let programmed = self
+            .status
+            .iter()
+            .flat_map(|status| &status.conditions)
+            .find(|c| c.type_ == "Programmed");
+
+        match programmed.map(|c| c.status.as_str()) {
+            Some("True") => Health::Healthy,
+            Some(_) => Health::Unhealthy,
+            None => Health::Normal,
+        }
+    }
+}
+
+fn age<K: ResourceExt>(obj: &K) -> TimeDelta {
+    obj.creation_timestamp()
+        .map_or_else(TimeDelta::zero, |time| Utc::now() - time.0)
+}
+
+/// Either end of the Gateway API, listed together so that routes show up next
+/// to the gateways they attach to.
+#[derive(Clone, Debug)]
+pub enum Routing {
+    Gateway(Arc<Gateway>),
+    Route(Arc<HTTPRoute>),
+}
+
+impl Routing {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Gateway(_) => "Gateway",
+            Self::Route(_) => "HTTPRoute",
+        }
+    }
+
+    pub fn namespace(&self) -> Option<String> {
+        match self {
+            Self::Gateway(gateway) => gateway.namespace(),
+            Self::Route(route) => route.namespace(),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::Gateway(gateway) => gateway.name_any(),
+            Self::Route(route) => route.name_any(),
+        }
+    }
+
+    fn hosts(&self) -> String {
+        let hosts = match self {
+            Self::Gateway(gateway) => gateway
+                .spec
+                .listeners
+                .iter()
+                .filter_map(|l| l.hostname.clone())
+                .unique()
+                .join(", "),
+            Self::Route(route) => route.spec.hostnames.join(", "),
+        };
+
+        if hosts.is_empty() {
+            "*".to_string()
+        } else {
+            hosts
+        }
+    }
+
+    // Gateways: class, listeners and addresses. Routes: parents -> backends.
+    fn summary(&self) -> String {
+        match self {
+            Self::Gateway(gateway) => {
+                let listeners = gateway
+                    .spec
+                    .listeners
+                    .iter()
+                    .map(|l| format!("{}/{}", l.protocol, l.port))
+                    .join(", ");
+                let addresses = gateway
+                    .status
+                    .iter()
+                    .flat_map(|s| &s.addresses)
+                    .map(|a| a.value.as_str())
+                    .join(", ");
+
+                format!(
+                    "{} {listeners} {addresses}",
+                    gateway.spec.gateway_class_name
+                )
+                .trim_end()
+                .to_string()
+            }
+            Self::Route(route) => format!(
+                "{} -> {}",
+                route
+                    .spec
+                    .parent_refs
+                    .iter()
+                    .map(|p| p.name.as_str())
+                    .join(", "),
+                route.backends().iter().join(", "),
+            ),
+        }
+    }
+}
+
+impl table::Row for Routing {
+    fn health(&self) -> Health {
+        match self {
+            Self::Gateway(gateway) => gateway.health(),
+            Self::Route(route) => route.health(),
+        }
+    }
+
+    fn id(&self) -> Option<String> {
+        match self {
+            Self::Gateway(gateway) => gateway.uid(),
+            Self::Route(route) => route.uid(),
+        }
+    }
+
+    fn header<'a>() -> Option<Row<'a>> {
+        Some(Row::new(vec![
+            Cell::from("Kind"),
+            Cell::from("Namespace"),
+            Cell::from("Name"),
+            Cell::from("Hosts"),
+            Cell::from("Routes"),
+            Cell::from("Age"),
+        ]))
+    }
+
+    fn constraints() -> Vec<Constraint> {
+        vec![
+            Constraint::Max(10),
+            Constraint::Max(20),
+            Constraint::Min(10),
+            Constraint::Max(30),
+            Constraint::Min(20),
+            Constraint::Max(10),
+        ]
+    }
+
+    fn row(&self, style: &table::RowStyle) -> Row {
+        let health = table::Row::health(self);
+
+        let age = match self {
+            Self::Gateway(gateway) => age(gateway.as_ref()),
+            Self::Route(route) => age(route.as_ref()),
+        };
+
+        Row::new(vec![
+            style.status(health, self.kind()),
+            self.namespace().unwrap_or_default(),
+            self.name(),
+            self.hosts(),
+            self.summary(),
+            age.to_age(),
+        ])
+        .style(style.get(health))
+    }
+}
+
+impl Filter for Gateway {
+    fn matches(&self, filter: &str) -> bool {
+        self.name_any().contains(filter) || self.namespace().is_some_and(|ns| ns.contains(filter))
+    }
+}
+
+impl Compare for Arc<Gateway> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.namespace(), self.name_any()).cmp(&(other.namespace(), other.name_any()))
+    }
+}
+
+impl Filter for HTTPRoute {
+    fn matches(&self, filter: &str) -> bool {
+        self.name_any().contains(filter)
+            || self.namespace().is_some_and(|ns| ns.contains(filter))
+            || self.spec.hostnames.iter().any(|host| host.contains(filter))
+    }
+}
+
+impl Compare for Arc<HTTPRoute> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.namespace(), self.name_any()).cmp(&(other.namespace(), other.name_any()))
+    }
+}
+
+/// Gateways and routes from watching both, ordered by namespace with the
+/// gateways first.
+#[derive(Clone)]
+pub struct Routes {
+    gateways: Arc<Store<Gateway>>,
+    routes: Arc<Store<HTTPRoute>>,
+}
+
+impl Routes {
+    pub fn new(gateways: Arc<Store<Gateway>>, routes: Arc<Store<HTTPRoute>>) -> Self {
+        Self { gateways, routes }
+    }
+
+    pub fn get(&self, idx: usize, filter: Option<String>) -> Option<Routing> {
+        table::Items::items(self, filter).get(idx).cloned()
+    }
+}
+
+impl table::Items for Routes {
+    type Item = Routing;
+
+    fn items(&self, filter: Option<String>) -> Vec<Self::Item> {
+        let gateways = self
+            .gateways
+            .items(filter.clone())
+            .into_iter()
+            .map(Routing::Gateway);
+        let routes = self.routes.items(filter).into_iter().map(Routing::Route);
+
+        let mut items: Vec<_> = gateways.chain(routes).collect();
+        items.sort_by(|a, b| {
+            (a.namespace(), a.kind(), a.name()).cmp(&(b.namespace(), b.kind(), b.name()))
+        });
+
+        items
+    }
+}
@@ -0,0 +1,125 @@
+use kube::ResourceExt;
+use strum::Display;
+
+// Argo CD's annotation based tracking,
+// `<app>:<group>/<kind>:<namespace>/<name>`.
+static ARGO_TRACKING: &str = "argocd.argoproj.io/tracking-id";
+static ARGO_INSTANCE: &str = "argocd.argoproj.io/instance";
+static ARGO_PREFIX: &str = "argocd.argoproj.io/";
+
+static FLUX_KUSTOMIZE: &str = "kustomize.toolkit.fluxcd.io";
+static FLUX_HELM: &str = "helm.toolkit.fluxcd.io";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display)]
+pub enum Tool {
+    #[strum(to_string = "Argo CD")]
+    ArgoCd,
+    #[strum(to_string = "Flux")]
+    Flux,
+}
+
+/// A GitOps controller that applies a resource from a repository. Anything
+/// changed by hand is likely to be put back the next time it syncs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Manager {
+    pub tool: Tool,
+    /// The Argo CD application or Flux `Kustomization`/`HelmRelease` the
+    /// resource comes from, eg. `flux-system/apps`.
+    pub owner: String,
+    /// Annotations the controller reads to decide how to sync the resource,
+    /// such as `argocd.argoproj.io/sync-options`.
+    pub annotations: Vec<(String, String)>,
+    /// Flux can be told to leave a resource alone with a `reconcile: disabled`
+    /// annotation, changes to it then stick.
+    pub reconciled: bool,
+}
+
+impl Manager {
+    /// Shown before a change is applied to a managed resource.
+    pub fn warning(&self) -> String {
+        format!(
+            "Managed by {} ({}), changes will likely be reverted on the next sync",
+            self.tool, self.owner
+        )
+    }
+}
+
+/// The GitOps controller managing `obj`, from the labels and annotations Argo
+/// CD and Flux put on the resources they apply.
+pub fn manager<K>(obj: &K) -> Option<Manager>
+where
+    K: ResourceExt,
+{
+    argo(obj)
+        .or_else(|| flux(obj, FLUX_KUSTOMIZE))
+        .or_else(|| flux(obj, FLUX_HELM))
+}
+
+/// Whether changes to `obj` are likely to be reverted by a GitOps controller.
+pub fn reverts<K>(obj: &K) -> bool
+where
+    K: ResourceExt,
+{
+    manager(obj).is_some_and(|manager| manager.reconciled)
+}
+
+fn argo<K>(obj: &K) -> Option<Manager>
+where
+    K: ResourceExt,
+{
+    let owner = obj
+        .annotations()
+        .get(ARGO_TRACKING)
+        .and_then(|id| id.split(':').next())
+        .or_else(|| obj.labels().get(ARGO_INSTANCE).map(String::as_str))
+        .filter(|owner| !owner.is_empty())?
+        .to_string();
+
+    let annotations = obj
+        .annotations()
+        .iter()
+        .filter(|(k, _)| k.starts_with(ARGO_PREFIX) && k.as_str() != ARGO_TRACKING)
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+
+    Some(Manager {
+        tool: Tool::ArgoCd,
+        owner,
+        annotations,
+        reconciled: true,
+    })
+}
+
+// Flux labels resources with the name and namespace of what applied them,
+// eg. `kustomize.toolkit.fluxcd.io/name`.
+fn flux<K>(obj: &K, group: &str) -> Option<Manager>
+where
+    K: ResourceExt,
+{
+    let labels = obj.labels();
+
+    let name = labels.get(&format!("{group}/name"))?;
+    let owner = match labels.get(&format!("{group}/namespace")) {
+        Some(namespace) => format!("{namespace}/{name}"),
+        None => name.clone(),
+    };
+
+    let prefix = format!("{group}/");
+    let annotations: Vec<_> = obj
+        .annotations()
+        .iter()
+        .filter(|(k, _)| k.starts_with(&prefix))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+
+    let reconciled = !annotations
+        .iter()
+        .any(|(k, v)| k.ends_with("/reconcile") && v == "disabled");
+
+    Some(Manager {
+        tool: Tool::Flux,
+        owner,
+        annotations,
+        reconciled,
+    })
+}
@@ -0,0 +1,204 @@
+use std::{cmp::Ordering, sync::Arc};
+
+use k8s_openapi::{
+    api::autoscaling::v2::{
+        HorizontalPodAutoscaler, MetricSpec, MetricStatus, MetricTarget, MetricValueStatus,
+    },
+    apimachinery::pkg::api::resource::Quantity,
+};
+use kube::ResourceExt;
+
+use super::{Compare, Filter};
+
+/// A metric that is scaled on, as `kubectl get hpa` shows it. Values are
+/// `<unknown>` until the autoscaler has read the metric.
+#[derive(Clone, Debug)]
+pub struct Metric {
+    pub name: String,
+    pub current: String,
+    pub target: String,
+}
+
+fn spec_metric(spec: &MetricSpec) -> Option<(String, &MetricTarget)> {
+    if let Some(source) = &spec.resource {
+        return Some((source.name.clone(), &source.target));
+    }
+
+    if let Some(source) = &spec.container_resource {
+        return Some((
+            format!("{}/{}", source.container, source.name),
+            &source.target,
+        ));
+    }
+
+    if let Some(source) = &spec.pods {
+        return Some((source.metric.name.clone(), &source.target));
+    }
+
+    if let Some(source) = &spec.object {
+        return Some((source.metric.name.clone(), &source.target));
+    }
+
+    spec.external
+        .as_ref()
+        .map(|source| (source.metric.name.clone(), &source.target))
+}
+
+fn status_metric(status: &MetricStatus) -> Option<(String, &MetricValueStatus)> {
+    if let Some(source) = &status.resource {
+        return Some((source.name.clone(), &source.current));
+    }
+
+    if let Some(source) = &status.container_resource {
+        return Some((
+            format!("{}/{}", source.container, source.name),
+            &source.current,
+        ));
+    }
+
+    if let Some(source) = &status.pods {
+        return Some((source.metric.name.clone(), &source.current));
+    }
+
+    if let Some(source) = &status.object {
+        return Some((source.metric.name.clone(), &source.current));
+    }
+
+    status
+        .external
+        .as_ref()
+        .map(|source| (source.metric.name.clone(), &source.current))
+}
+
+fn value(
+    utilization: Option<i32>,
+    average: Option<&Quantity>,
+    value: Option<&Quantity>,
+) -> Option<String> {
+    utilization
+        .map(|u| format!("{u}%"))
+        .or_else(|| average.map(|q| q.0.clone()))
+        .or_else(|| value.map(|q| q.0.clone()))
+}
+
+#[allow(clippy::module_name_repetitions)]
+pub trait HorizontalPodAutoscalerExt {
+    /// Whether this autoscaler scales the `kind` (eg. `Deployment`) named
+    /// `name`, in its own namespace.
+    fn scales(&self, kind: &str, name: &str) -> bool;
+    fn min(&self) -> i32;
+    fn max(&self) -> i32;
+    fn current(&self) -> i32;
+    fn desired(&self) -> i32;
+    fn metrics(&self) -> Vec<Metric>;
+    /// Every metric as `current/target`, eg. `cpu: 45%/80%`.
+    fn targets(&self) -> String;
+    /// Conditions that are currently true or false, with why.
+    fn conditions(&self) -> Vec<(String, String, String)>;
+}
+
+impl HorizontalPodAutoscalerExt for HorizontalPodAutoscaler {
+    fn scales(&self, kind: &str, name: &str) -> bool {
+        self.spec.as_ref().is_some_and(|spec| {
+            spec.scale_target_ref.kind == kind && spec.scale_target_ref.name == name
+        })
+    }
+
+    fn min(&self) -> i32 {
+        self.spec
+            .as_ref()
+            .and_then(|spec| spec.min_replicas)
+            .unwrap_or(1)
+    }
+
+    fn max(&self) -> i32 {
+        self.spec.as_ref().map_or(0, |spec| spec.max_replicas)
+    }
+
+    fn current(&self) -> i32 {
+        self.status
+            .as_ref()
+            .and_then(|status| status.current_replicas)
+            .unwrap_or_default()
+    }
+
+    fn desired(&self) -> i32 {
+        self.status
+            .as_ref()
+            .map_or(0, |status| status.desired_replicas)
+    }
+
+    // Current values are matched up with the spec by name, the status can be
+    // missing entries while metrics are unavailable.
+    fn metrics(&self) -> Vec<Metric> {
+        let current: Vec<_> = self
+            .status
+            .iter()
+            .flat_map(|status| status.current_metrics.iter().flatten())
+            .filter_map(status_metric)
+            .collect();
+
+        self.spec
+            .iter()
+            .flat_map(|spec| spec.metrics.iter().flatten())
+            .filter_map(spec_metric)
+            .map(|(name, target)| Metric {
+                current: current
+                    .iter()
+                    .find(|(n, _)| *n == name)
+                    .and_then(|(_, status)| {
+                        value(
+                            status.average_utilization,
+                            status.average_value.as_ref(),
+                            status.value.as_ref(),
+                        )
+                    })
+                    .unwrap_or_else(|| "<unknown>".to_string()),
+                target: value(
+                    target.average_utilization,
+                    target.average_value.as_ref(),
+                    target.value.as_ref(),
+                )
+                .unwrap_or_else(|| "<unknown>".to_string()),
+                name,
+            })
+            .collect()
+    }
+
+    fn targets(&self) -> String {
+        self.metrics()
+            .iter()
+            .map(|m| format!("{}: {}/{}", m.name, m.current, m.target))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn conditions(&self) -> Vec<(String, String, String)> {
+        self.status
+            .iter()
+            .flat_map(|status| status.conditions.iter().flatten())
+            .map(|cond| {
+                (
+                    cond.type_.clone(),
+                    cond.status.clone(),
+                    cond.message
+                        .clone()
+                        .or_else(|| cond.reason.clone())
+                        .unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+}
+
+impl Filter for HorizontalPodAutoscaler {
+    fn matches(&self, filter: &str) -> bool {
+        self.name_any().contains(filter)
+    }
+}
+
+impl Compare for Arc<HorizontalPodAutoscaler> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.namespace(), self.name_any()).cmp(&(other.namespace(), other.name_any()))
+    }
+}
@@ -0,0 +1,398 @@
+use std::{cmp::Ordering, sync::Arc};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use k8s_openapi::api::{
+    apps::v1::Deployment,
+    batch::v1::{Job, JobCondition},
+    core::v1::{Endpoints, PersistentVolumeClaim, Service},
+};
+use kube::{api::DynamicObject, runtime::reflector::ObjectRef, Api, ResourceExt};
+use ratatui::{
+    layout::Constraint,
+    widgets::{Cell, Row},
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{action::Actions, age::Age, store::Store, Compare, Filter};
+use crate::widget::{
+    table::{self, Health},
+    yank::Yank,
+};
+
+// Endpoints show up shortly after a service is created, until then every new
+// service would look unused.
+static SETTLE_MINUTES: i64 = 10;
+
+/// How long something has to look unused before the `idle` tab lists it.
+/// Anything left out uses the default.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IdleSettings {
+    /// Days a deployment has been scaled to zero, 7 by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scaled_to_zero_days: Option<i64>,
+    /// Days since a job failed, for jobs without a `ttlSecondsAfterFinished`.
+    /// Jobs with one are listed once it has passed. 1 by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failed_job_days: Option<i64>,
+    /// Days a claim has gone without being bound, 1 by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unbound_claim_days: Option<i64>,
+    /// Namespaces that are never listed, such as `kube-system`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_namespaces: Vec<String>,
+}
+
+impl IdleSettings {
+    fn scaled_to_zero(&self) -> TimeDelta {
+        TimeDelta::days(self.scaled_to_zero_days.unwrap_or(7))
+    }
+
+    fn failed_job(&self, job: &Job) -> TimeDelta {
+        job.spec
+            .as_ref()
+            .and_then(|spec| spec.ttl_seconds_after_finished)
+            .map_or_else(
+                || TimeDelta::days(self.failed_job_days.unwrap_or(1)),
+                |ttl| TimeDelta::seconds(ttl.into()),
+            )
+    }
+
+    fn unbound_claim(&self) -> TimeDelta {
+        TimeDelta::days(self.unbound_claim_days.unwrap_or(1))
+    }
+
+    fn ignores(&self, namespace: Option<String>) -> bool {
+        namespace.is_some_and(|ns| self.ignore_namespaces.contains(&ns))
+    }
+}
+
+/// Something that looks like it isn't being used anymore. These are guesses,
+/// the reason says why it was picked so that users can decide.
+#[derive(Clone, Debug)]
+pub enum Idle {
+    /// Scaled to zero replicas.
+    Deployment(Arc<Deployment>),
+    /// Failed and left around, even past its TTL.
+    Job(Arc<Job>),
+    /// Never bound to a volume, or lost the one it had.
+    Claim(Arc<PersistentVolumeClaim>),
+    /// Selects pods, but none of them are ready.
+    Service(Arc<Service>),
+}
+
+impl Idle {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Deployment(_) => "Deployment",
+            Self::Job(_) => "Job",
+            Self::Claim(_) => "PersistentVolumeClaim",
+            Self::Service(_) => "Service",
+        }
+    }
+
+    pub fn namespace(&self) -> Option<String> {
+        match self {
+            Self::Deployment(deploy) => deploy.namespace(),
+            Self::Job(job) => job.namespace(),
+            Self::Claim(pvc) => pvc.namespace(),
+            Self::Service(svc) => svc.namespace(),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::Deployment(deploy) => deploy.name_any(),
+            Self::Job(job) => job.name_any(),
+            Self::Claim(pvc) => pvc.name_any(),
+            Self::Service(svc) => svc.name_any(),
+        }
+    }
+
+    pub fn uid(&self) -> Option<String> {
+        match self {
+            Self::Deployment(deploy) => deploy.uid(),
+            Self::Job(job) => job.uid(),
+            Self::Claim(pvc) => pvc.uid(),
+            Self::Service(svc) => svc.uid(),
+        }
+    }
+
+    pub fn reason(&self) -> String {
+        match self {
+            Self::Deployment(_) => "scaled to zero".to_string(),
+            Self::Job(job) => failed(job)
+                .and_then(|condition| condition.reason.clone())
+                .map_or_else(
+                    || "failed".to_string(),
+                    |reason| format!("failed: {reason}"),
+                ),
+            Self::Claim(pvc) => format!(
+                "unbound: {}",
+                pvc.status
+                    .as_ref()
+                    .and_then(|status| status.phase.clone())
+                    .unwrap_or_else(|| "Pending".to_string())
+            ),
+            Self::Service(_) => "no ready endpoints".to_string(),
+        }
+    }
+
+    // When it stopped being used, as far as can be told from the resource.
+    fn since(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Deployment(deploy) => scaled_down(deploy),
+            Self::Job(job) => failed(job)
+                .and_then(|condition| condition.last_transition_time.clone())
+                .or(job.creation_timestamp())
+                .map(|time| time.0),
+            Self::Claim(pvc) => pvc.creation_timestamp().map(|time| time.0),
+            Self::Service(svc) => svc.creation_timestamp().map(|time| time.0),
+        }
+    }
+
+    fn idle_for(&self) -> TimeDelta {
+        self.since()
+            .map_or_else(TimeDelta::zero, |since| Utc::now() - since)
+    }
+
+    pub fn dynamic_api(&self, client: kube::Client) -> Api<DynamicObject> {
+        match self {
+            Self::Deployment(deploy) => deploy.dynamic_api(client),
+            Self::Job(job) => job.dynamic_api(client),
+            Self::Claim(pvc) => pvc.dynamic_api(client),
+            Self::Service(svc) => svc.dynamic_api(client),
+        }
+    }
+}
+
+fn failed(job: &Job) -> Option<&JobCondition> {
+    job.status
+        .as_ref()?
+        .conditions
+        .as_ref()?
+        .iter()
+        .find(|condition| condition.type_ == "Failed" && condition.status == "True")
+}
+
+// Deployments don't record when they were scaled, the conditions change along
+// with it and are the closest there is.
+fn scaled_down(deploy: &Deployment) -> Option<DateTime<Utc>> {
+    deploy
+        .status
+        .as_ref()
+        .and_then(|status| status.conditions.as_ref())
+        .into_iter()
+        .flatten()
+        .filter_map(|condition| {
+            condition
+                .last_update_time
+                .clone()
+                .or(condition.last_transition_time.clone())
+        })
+        .map(|time| time.0)
+        .max()
+        .or(deploy.creation_timestamp().map(|time| time.0))
+}
+
+fn scaled_to_zero(deploy: &Deployment) -> bool {
+    deploy.spec.as_ref().and_then(|spec| spec.replicas) == Some(0)
+        && deploy
+            .status
+            .as_ref()
+            .and_then(|status| status.replicas)
+            .unwrap_or_default()
+            == 0
+}
+
+fn bound(pvc: &PersistentVolumeClaim) -> bool {
+    pvc.status
+        .as_ref()
+        .and_then(|status| status.phase.as_deref())
+        == Some("Bound")
+}
+
+// Services without a selector have their endpoints managed by something else,
+// and external names don't have any.
+fn selects_pods(svc: &Service) -> bool {
+    svc.spec.as_ref().is_some_and(|spec| {
+        spec.type_.as_deref() != Some("ExternalName")
+            && spec.selector.as_ref().is_some_and(|s| !s.is_empty())
+    })
+}
+
+fn ready(endpoints: &Endpoints) -> bool {
+    endpoints
+        .subsets
+        .iter()
+        .flatten()
+        .any(|subset| subset.addresses.as_ref().is_some_and(|a| !a.is_empty()))
+}
+
+impl table::Row for Idle {
+    fn yank(&self) -> Option<Yank> {
+        Some(Yank::new(self.kind(), self.namespace(), self.name()))
+    }
+
+    fn id(&self) -> Option<String> {
+        self.uid()
+    }
+
+    fn header<'a>() -> Option<Row<'a>> {
+        Some(Row::new(vec![
+            Cell::from("Kind"),
+            Cell::from("Namespace"),
+            Cell::from("Name"),
+            Cell::from("Reason"),
+            Cell::from("Idle"),
+        ]))
+    }
+
+    fn constraints() -> Vec<Constraint> {
+        vec![
+            Constraint::Max(22),
+            Constraint::Max(20),
+            Constraint::Min(10),
+            Constraint::Max(32),
+            Constraint::Max(10),
+        ]
+    }
+
+    fn row(&self, style: &table::RowStyle) -> Row {
+        let health = match self {
+            Self::Job(_) | Self::Service(_) => Health::Unhealthy,
+            Self::Deployment(_) | Self::Claim(_) => Health::Normal,
+        };
+
+        Row::new(vec![
+            self.kind().to_string(),
+            self.namespace().unwrap_or_default(),
+            self.name(),
+            style.status(health, self.reason()),
+            self.idle_for().to_age(),
+        ])
+        .style(style.get(health))
+    }
+}
+
+impl Compare for Idle {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.kind(), self.namespace(), self.name()).cmp(&(
+            other.kind(),
+            other.namespace(),
+            other.name(),
+        ))
+    }
+}
+
+impl Filter for Endpoints {
+    fn matches(&self, filter: &str) -> bool {
+        self.name_any().contains(filter)
+    }
+}
+
+impl Compare for Arc<Endpoints> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.namespace(), self.name_any()).cmp(&(other.namespace(), other.name_any()))
+    }
+}
+
+/// Everything that looks unused according to `settings`, from watching
+/// deployments, jobs, claims, services and their endpoints.
+#[derive(Clone)]
+pub struct Idles {
+    deployments: Arc<Store<Deployment>>,
+    jobs: Arc<Store<Job>>,
+    claims: Arc<Store<PersistentVolumeClaim>>,
+    services: Arc<Store<Service>>,
+    endpoints: Arc<Store<Endpoints>>,
+    settings: Arc<IdleSettings>,
+}
+
+impl Idles {
+    pub fn new(
+        deployments: Arc<Store<Deployment>>,
+        jobs: Arc<Store<Job>>,
+        claims: Arc<Store<PersistentVolumeClaim>>,
+        services: Arc<Store<Service>>,
+        endpoints: Arc<Store<Endpoints>>,
+        settings: Arc<IdleSettings>,
+    ) -> Self {
+        Self {
+            deployments,
+            jobs,
+            claims,
+            services,
+            endpoints,
+            settings,
+        }
+    }
+
+    pub fn get(&self, idx: usize, filter: Option<String>) -> Option<Idle> {
+        table::Items::items(self, filter).get(idx).cloned()
+    }
+}
+
+impl table::Items for Idles {
+    type Item = Idle;
+
+    fn items(&self, filter: Option<String>) -> Vec<Self::Item> {
+        let settings = &self.settings;
+
+        let deployments = self
+            .deployments
+            .items(filter.clone())
+            .into_iter()
+            .filter(|deploy| scaled_to_zero(deploy))
+            .map(Idle::Deployment)
+            .filter(|idle| idle.idle_for() >= settings.scaled_to_zero());
+
+        let jobs = self
+            .jobs
+            .items(filter.clone())
+            .into_iter()
+            .filter(|job| failed(job).is_some())
+            .filter_map(|job| {
+                let after = settings.failed_job(&job);
+                let idle = Idle::Job(job);
+
+                (idle.idle_for() >= after).then_some(idle)
+            });
+
+        let claims = self
+            .claims
+            .items(filter.clone())
+            .into_iter()
+            .filter(|pvc| !bound(pvc))
+            .map(Idle::Claim)
+            .filter(|idle| idle.idle_for() >= settings.unbound_claim());
+
+        let services = self
+            .services
+            .items(filter)
+            .into_iter()
+            .filter(|svc| selects_pods(svc))
+            .filter(|svc| {
+                let obj =
+                    ObjectRef::new(&svc.name_any()).within(&svc.namespace().unwrap_or_default());
+
+                !self
+                    .endpoints
+                    .find(&obj)
+                    .is_some_and(|endpoints| ready(&endpoints))
+            })
+            .map(Idle::Service)
+            .filter(|idle| idle.idle_for() >= TimeDelta::minutes(SETTLE_MINUTES));
+
+        let mut items: Vec<_> = deployments
+            .chain(jobs)
+            .chain(claims)
+            .chain(services)
+            .filter(|idle| !settings.ignores(idle.namespace()))
+            .collect();
+        items.sort_by(Compare::cmp);
+
+        items
+    }
+}
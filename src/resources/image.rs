@@ -0,0 +1,431 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use eyre::Result;
+use k8s_openapi::api::core::v1::Pod;
+use kube::{
+    api::{Api, ApiResource, DynamicObject, GroupVersionKind, ListParams},
+    ResourceExt,
+};
+use ratatui::{
+    layout::Constraint,
+    widgets::{Cell, Row},
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{age::Age, node::is_active, store::Store};
+use crate::{
+    image::{Enrichment, Manifest, Reference, Scanner, Vulnerabilities},
+    widget::table::{self, Health},
+};
+
+// Scanners aren't watched, this is how long until reports are fetched again.
+// Registries are only asked about an image once.
+static RESCAN_EVERY: Duration = Duration::from_secs(60);
+
+/// Optional details for the `images` tab, see [`Enrichment`].
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageSettings {
+    /// Look up when images were built and what their tags currently point at
+    /// in the registry they come from. Only public images can be looked up.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry: Option<bool>,
+    /// Where known vulnerabilities come from, see [`ScannerSource`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scanner: Option<ScannerSource>,
+}
+
+/// Scanners that are built in. Reports are read as the user.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ScannerSource {
+    /// `VulnerabilityReports` written by the Trivy operator, users need to be
+    /// able to list them in every namespace.
+    TrivyOperator,
+}
+
+// Only the parts of a `VulnerabilityReport` needed to match it up with an
+// image.
+#[derive(Deserialize)]
+struct Report {
+    registry: ReportRegistry,
+    artifact: Artifact,
+    summary: Summary,
+}
+
+#[derive(Deserialize)]
+struct ReportRegistry {
+    server: String,
+}
+
+#[derive(Deserialize)]
+struct Artifact {
+    repository: String,
+    #[serde(default)]
+    tag: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Summary {
+    critical_count: u32,
+    high_count: u32,
+}
+
+#[async_trait::async_trait]
+impl Scanner for ScannerSource {
+    async fn scan(
+        &self,
+        client: kube::Client,
+        images: &[String],
+    ) -> Result<HashMap<String, Vulnerabilities>> {
+        let resource = ApiResource::from_gvk(&GroupVersionKind::gvk(
+            "aquasecurity.github.io",
+            "v1alpha1",
+            "VulnerabilityReport",
+        ));
+
+        let reports = Api::<DynamicObject>::all_with(client, &resource)
+            .list(&ListParams::default())
+            .await?;
+
+        // There's a report per workload, images that are used more than once
+        // have the same findings in each.
+        let mut found: HashMap<Reference, Vulnerabilities> = HashMap::new();
+        for report in reports {
+            let Some(Ok(report)) = report
+                .data
+                .get("report")
+                .map(|report| serde_json::from_value::<Report>(report.clone()))
+            else {
+                continue;
+            };
+
+            let image = Reference::parse(&format!(
+                "{}/{}:{}",
+                report.registry.server,
+                report.artifact.repository,
+                report.artifact.tag.as_deref().unwrap_or("latest"),
+            ));
+
+            let entry = found.entry(image).or_default();
+            entry.critical = entry.critical.max(report.summary.critical_count);
+            entry.high = entry.high.max(report.summary.high_count);
+        }
+
+        Ok(images
+            .iter()
+            .filter_map(|image| {
+                let reference = Reference {
+                    digest: None,
+                    ..Reference::parse(image)
+                };
+
+                Some((image.clone(), *found.get(&reference)?))
+            })
+            .collect())
+    }
+}
+
+/// Images a pod runs, along with the digest each one resolved to when the
+/// container started. Init containers are included.
+pub fn running(pod: &Pod) -> BTreeMap<String, Option<String>> {
+    let Some(spec) = pod.spec.as_ref() else {
+        return BTreeMap::new();
+    };
+
+    let statuses: Vec<_> = pod
+        .status
+        .iter()
+        .flat_map(|status| {
+            status
+                .container_statuses
+                .iter()
+                .flatten()
+                .chain(status.init_container_statuses.iter().flatten())
+        })
+        .collect();
+
+    let mut images = BTreeMap::new();
+    for container in spec
+        .containers
+        .iter()
+        .chain(spec.init_containers.iter().flatten())
+    {
+        let Some(image) = container.image.clone() else {
+            continue;
+        };
+
+        let digest = statuses
+            .iter()
+            .find(|status| status.name == container.name)
+            .and_then(|status| status.image_id.split_once('@'))
+            .map(|(_, digest)| digest.to_string());
+
+        let entry: &mut Option<String> = images.entry(image).or_default();
+        if entry.is_none() {
+            *entry = digest;
+        }
+    }
+
+    images
+}
+
+// Digests are long, the first 12 characters are what registries and runtimes
+// show.
+fn short(digest: &str) -> String {
+    digest
+        .split_once(':')
+        .map_or(digest, |(_, hex)| hex)
+        .chars()
+        .take(12)
+        .collect()
+}
+
+/// An image that is running somewhere in the cluster.
+#[derive(Clone, Debug)]
+pub struct Image {
+    pub name: String,
+    /// What the image resolved to in each pod, more than one means that pods
+    /// are running different builds of the same tag.
+    pub digests: BTreeSet<String>,
+    pub pods: usize,
+    pub namespaces: BTreeSet<String>,
+    pub manifest: Option<Manifest>,
+    pub vulnerabilities: Option<Vulnerabilities>,
+}
+
+impl Image {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            digests: BTreeSet::new(),
+            pods: 0,
+            namespaces: BTreeSet::new(),
+            manifest: None,
+            vulnerabilities: None,
+        }
+    }
+
+    // What pods are running, marked with `*` when the registry has something
+    // newer for the tag.
+    fn digest(&self) -> String {
+        let latest = self
+            .manifest
+            .as_ref()
+            .and_then(|manifest| manifest.digest.clone());
+
+        match (self.digests.len(), latest) {
+            (0, latest) => latest.as_deref().map(short).unwrap_or_default(),
+            (1, latest) => {
+                let digest = self.digests.iter().next().map(String::as_str);
+                let outdated = latest.is_some_and(|latest| Some(latest.as_str()) != digest);
+
+                format!(
+                    "{}{}",
+                    digest.map(short).unwrap_or_default(),
+                    if outdated { "*" } else { "" }
+                )
+            }
+            (n, _) => format!("{n} digests"),
+        }
+    }
+}
+
+impl table::Row for Image {
+    fn id(&self) -> Option<String> {
+        Some(self.name.clone())
+    }
+
+    fn health(&self) -> Health {
+        match self.vulnerabilities {
+            Some(Vulnerabilities { critical, .. }) if critical > 0 => Health::Unhealthy,
+            _ => Health::Normal,
+        }
+    }
+
+    fn header<'a>() -> Option<Row<'a>> {
+        Some(Row::new(vec![
+            Cell::from("Image"),
+            Cell::from("Pods"),
+            Cell::from("Namespaces"),
+            Cell::from("Digest"),
+            Cell::from("Created"),
+            Cell::from("Critical/High"),
+        ]))
+    }
+
+    fn constraints() -> Vec<Constraint> {
+        vec![
+            Constraint::Fill(1),
+            Constraint::Max(6),
+            Constraint::Max(10),
+            Constraint::Max(13),
+            Constraint::Max(10),
+            Constraint::Max(15),
+        ]
+    }
+
+    fn row(&self, style: &table::RowStyle) -> Row {
+        let health = table::Row::health(self);
+
+        Row::new(vec![
+            self.name.clone(),
+            self.pods.to_string(),
+            self.namespaces.len().to_string(),
+            self.digest(),
+            self.manifest
+                .as_ref()
+                .and_then(|manifest| manifest.created)
+                .map(|created| (Utc::now() - created).to_age())
+                .unwrap_or_default(),
+            self.vulnerabilities
+                .map(|found| style.status(health, found.to_string()))
+                .unwrap_or_default(),
+        ])
+        .style(style.get(health))
+    }
+}
+
+#[derive(Default)]
+struct Scans {
+    found: HashMap<String, Vulnerabilities>,
+    // Unset until the first scan has been started.
+    started: Option<Instant>,
+}
+
+/// Unique images across every running pod. Registry lookups and scans happen
+/// in the background, their results show up as they come in.
+#[derive(Clone)]
+pub struct Images {
+    client: kube::Client,
+    pods: Arc<Store<Pod>>,
+    enrichment: Enrichment,
+    // Images that have been looked up, those still in flight or that failed
+    // are `None`.
+    manifests: Arc<Mutex<HashMap<String, Option<Manifest>>>>,
+    scans: Arc<Mutex<Scans>>,
+}
+
+impl Images {
+    pub fn new(client: kube::Client, pods: Arc<Store<Pod>>, enrichment: Enrichment) -> Self {
+        Self {
+            client,
+            pods,
+            enrichment,
+            manifests: Arc::default(),
+            scans: Arc::default(),
+        }
+    }
+
+    pub fn get(&self, idx: usize, filter: Option<String>) -> Option<Image> {
+        table::Items::items(self, filter).into_iter().nth(idx)
+    }
+
+    fn lookup(&self, images: &[String]) {
+        let Some(registry) = &self.enrichment.registry else {
+            return;
+        };
+
+        let mut manifests = self.manifests.lock().unwrap();
+
+        for image in images {
+            if manifests.contains_key(image) {
+                continue;
+            }
+
+            manifests.insert(image.clone(), None);
+
+            let registry = registry.clone();
+            let manifests = self.manifests.clone();
+            let image = image.clone();
+
+            tokio::spawn(async move {
+                match registry.manifest(&Reference::parse(&image)).await {
+                    Ok(manifest) => {
+                        manifests.lock().unwrap().insert(image, Some(manifest));
+                    }
+                    Err(err) => tracing::debug!("registry lookup for {image} failed: {err}"),
+                }
+            });
+        }
+    }
+
+    fn scan(&self, images: &[String]) {
+        let Some(scanner) = &self.enrichment.scanner else {
+            return;
+        };
+
+        let mut scans = self.scans.lock().unwrap();
+
+        // Nothing is running until pods have loaded.
+        if images.is_empty()
+            || scans
+                .started
+                .is_some_and(|started| started.elapsed() < RESCAN_EVERY)
+        {
+            return;
+        }
+
+        scans.started = Some(Instant::now());
+
+        let scanner = scanner.clone();
+        let client = self.client.clone();
+        let state = self.scans.clone();
+        let images = images.to_vec();
+
+        tokio::spawn(async move {
+            match scanner.scan(client, &images).await {
+                Ok(found) => state.lock().unwrap().found = found,
+                Err(err) => tracing::debug!("scanning images failed: {err}"),
+            }
+        });
+    }
+}
+
+impl table::Items for Images {
+    type Item = Image;
+
+    fn items(&self, filter: Option<String>) -> Vec<Self::Item> {
+        let mut images: BTreeMap<String, Image> = BTreeMap::new();
+
+        for pod in self.pods.items(None).iter().filter(|pod| is_active(pod)) {
+            for (name, digest) in running(pod) {
+                let image = images
+                    .entry(name.clone())
+                    .or_insert_with(|| Image::new(name));
+
+                image.pods += 1;
+                image.namespaces.extend(pod.namespace());
+                image.digests.extend(digest);
+            }
+        }
+
+        let names: Vec<_> = images.keys().cloned().collect();
+        self.lookup(&names);
+        self.scan(&names);
+
+        let manifests = self.manifests.lock().unwrap();
+        let scans = self.scans.lock().unwrap();
+
+        images
+            .into_values()
+            .filter(|image| {
+                filter
+                    .as_ref()
+                    .map_or(true, |filter| image.name.contains(filter.as_str()))
+            })
+            .map(|image| Image {
+                manifest: manifests.get(&image.name).cloned().flatten(),
+                vulnerabilities: scans.found.get(&image.name).copied(),
+                ..image
+            })
+            .collect()
+    }
+}
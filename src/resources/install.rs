@@ -1,14 +1,13 @@
 use std::{borrow::Cow, collections::BTreeMap};
 
 use base64::prelude::*;
-use eyre::{eyre, Report, Result};
+use eyre::{Report, Result};
 use itertools::Itertools;
 use json_patch::{patch, PatchOperation};
 use kube::api::{DynamicObject, ResourceExt};
-use pkcs8::EncodePrivateKey;
-use russh_keys::key::KeyPair;
 use rust_embed::Embed;
 use serde_json::{from_value, json, to_value};
+use ssh_key::{rand_core::OsRng, Algorithm, PrivateKey};
 
 #[derive(Embed)]
 #[folder = "resources"]
@@ -83,14 +82,14 @@ pub fn add_patches(
         "value": image,
     }))?);
 
-    let KeyPair::Ed25519(key) = KeyPair::generate_ed25519().expect("key was generated") else {
-        return Err(eyre!("key was wrong type"));
-    };
+    let key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519)?;
+    let mut pem = Vec::new();
+    russh_keys::encode_pkcs8_pem(&key, &mut pem)?;
 
     patches.push(from_value(json!({
         "op": "add",
         "path": "/key-yaml/data/id_ed25519",
-        "value": BASE64_STANDARD.encode(key.to_pkcs8_pem(ssh_key::LineEnding::default())?),
+        "value": BASE64_STANDARD.encode(pem),
     }))?);
 
     patch(&mut resources, &patches)?;
@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use eyre::{eyre, Result};
+use kube::{
+    api::{Api, DynamicObject, Patch, PatchParams},
+    ResourceExt,
+};
+use serde_json::json;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    Label,
+    Annotation,
+}
+
+impl Field {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Label => "label",
+            Self::Annotation => "annotation",
+        }
+    }
+
+    fn path(self) -> &'static str {
+        match self {
+            Self::Label => "/metadata/labels",
+            Self::Annotation => "/metadata/annotations",
+        }
+    }
+
+    pub fn get(self, obj: &DynamicObject) -> &BTreeMap<String, String> {
+        match self {
+            Self::Label => obj.labels(),
+            Self::Annotation => obj.annotations(),
+        }
+    }
+
+    /// Values for labels are restricted to the same characters as names,
+    /// annotations can contain anything.
+    pub fn validate(self, key: &str, value: &str) -> Result<()> {
+        validate_key(key)?;
+
+        if self == Self::Label && !value.is_empty() && !is_name(value) {
+            return Err(eyre!(
+                "label values must be 63 characters or less, start and end with an alphanumeric \
+                 character and only contain alphanumerics, '-', '_' or '.'"
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Change {
+    Set(Field, String, String),
+    Remove(Field, String),
+}
+
+impl Change {
+    fn operations(&self, current: &DynamicObject) -> serde_json::Value {
+        match self {
+            // Adding to a map that doesn't exist yet fails, so the whole map needs to
+            // be created instead.
+            Self::Set(field, key, value) if field.get(current).is_empty() => json!([
+                {"op": "add", "path": field.path(), "value": {key: value}},
+            ]),
+            Self::Set(field, key, value) => json!([
+                {"op": "add", "path": pointer(*field, key), "value": value},
+            ]),
+            Self::Remove(field, key) => json!([
+                {"op": "remove", "path": pointer(*field, key)},
+            ]),
+        }
+    }
+
+    /// Apply the change as a JSON patch, returning the updated resource. With
+    /// `dry_run`, the API server only checks what would happen.
+    pub async fn apply(
+        &self,
+        client: &Api<DynamicObject>,
+        current: &DynamicObject,
+        dry_run: bool,
+    ) -> Result<DynamicObject> {
+        let patch: json_patch::Patch = serde_json::from_value(self.operations(current))?;
+        let params = PatchParams {
+            dry_run,
+            ..PatchParams::default()
+        };
+
+        Ok(client
+            .patch(&current.name_any(), &params, &Patch::Json::<()>(patch))
+            .await?)
+    }
+}
+
+// Keys such as `app.kubernetes.io/name` contain `/`, which needs to be escaped
+// to be used in a JSON pointer.
+fn pointer(field: Field, key: &str) -> String {
+    format!(
+        "{}/{}",
+        field.path(),
+        key.replace('~', "~0").replace('/', "~1")
+    )
+}
+
+/// Keys are a name with an optional DNS subdomain prefix, for example
+/// `app.kubernetes.io/name`.
+pub fn validate_key(key: &str) -> Result<()> {
+    let (prefix, name) = match key.split_once('/') {
+        Some((prefix, name)) => (Some(prefix), name),
+        None => (None, key),
+    };
+
+    if let Some(prefix) = prefix {
+        if prefix.len() > 253 || !prefix.split('.').all(is_dns_label) {
+            return Err(eyre!(
+                "key prefix must be a DNS subdomain, for example kty.dev, got: {prefix}"
+            ));
+        }
+    }
+
+    if !is_name(name) {
+        return Err(eyre!(
+            "key names must be 63 characters or less, start and end with an alphanumeric \
+             character and only contain alphanumerics, '-', '_' or '.', got: {name}"
+        ));
+    }
+
+    Ok(())
+}
+
+fn is_name(name: &str) -> bool {
+    let bytes = name.as_bytes();
+
+    (1..=63).contains(&bytes.len())
+        && bytes[0].is_ascii_alphanumeric()
+        && bytes[bytes.len() - 1].is_ascii_alphanumeric()
+        && bytes
+            .iter()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, b'-' | b'_' | b'.'))
+}
+
+fn is_dns_label(label: &str) -> bool {
+    let bytes = label.as_bytes();
+
+    (1..=63).contains(&bytes.len())
+        && bytes[0].is_ascii_alphanumeric()
+        && bytes[bytes.len() - 1].is_ascii_alphanumeric()
+        && bytes
+            .iter()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || *c == b'-')
+}
@@ -0,0 +1,243 @@
+use color_eyre::Section;
+use eyre::{eyre, Result};
+use futures::{StreamExt, TryStreamExt};
+use itertools::Itertools;
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+use kube::{
+    api::{Api, ApiResource, DynamicObject, ListParams, Patch, PatchParams},
+    ResourceExt,
+};
+use serde::Serialize;
+use serde_json::json;
+
+use super::all;
+
+/// Set on definitions to the version of kty that applied them.
+pub static VERSION_ANNOTATION: &str = "kty.dev/version";
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// Objects are rewritten in pages of this size while migrating.
+const PAGE_SIZE: u32 = 500;
+
+/// Where a definition in the cluster is compared to the one this version of
+/// kty expects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, strum::Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum State {
+    /// Not installed yet.
+    Missing,
+    /// Applied by an older (or unknown) version of kty.
+    Outdated,
+    /// Up to date, but some objects are still stored as a previous version.
+    Unmigrated,
+    Current,
+    /// Applied by a newer version of kty, which is left alone.
+    Newer,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Status {
+    pub name: String,
+    pub state: State,
+    /// The version of kty that last applied the definition.
+    pub installed: Option<String>,
+    /// The version objects should be stored as.
+    pub storage: String,
+    /// The versions objects are stored as, according to the API server.
+    pub stored: Vec<String>,
+}
+
+impl Status {
+    fn new(
+        desired: &CustomResourceDefinition,
+        existing: Option<&CustomResourceDefinition>,
+    ) -> Self {
+        let storage = storage_version(desired).unwrap_or_default();
+        let installed = existing.and_then(|crd| crd.annotations().get(VERSION_ANNOTATION).cloned());
+        let stored = existing
+            .and_then(|crd| crd.status.as_ref()?.stored_versions.clone())
+            .unwrap_or_default();
+
+        let state = match (existing, installed.as_deref()) {
+            (None, _) => State::Missing,
+            (Some(_), Some(installed)) if newer(installed) => State::Newer,
+            (Some(_), Some(VERSION)) if stored.iter().any(|v| *v != storage) => State::Unmigrated,
+            (Some(_), Some(VERSION)) => State::Current,
+            (Some(_), _) => State::Outdated,
+        };
+
+        Self {
+            name: desired.name_any(),
+            state,
+            installed,
+            storage,
+            stored,
+        }
+    }
+}
+
+fn storage_version(crd: &CustomResourceDefinition) -> Option<String> {
+    crd.spec
+        .versions
+        .iter()
+        .find(|version| version.storage)
+        .map(|version| version.name.clone())
+}
+
+// Versions that don't parse, such as builds from a branch, are never newer.
+fn newer(installed: &str) -> bool {
+    fn parse(version: &str) -> Option<Vec<u64>> {
+        version
+            .split(['.', '-', '+'])
+            .take(3)
+            .map(|part| part.parse().ok())
+            .collect()
+    }
+
+    match (parse(installed), parse(VERSION)) {
+        (Some(installed), Some(current)) => installed > current,
+        _ => false,
+    }
+}
+
+/// How every definition kty uses compares to what is in the cluster.
+pub async fn status(client: &Api<CustomResourceDefinition>) -> Result<Vec<Status>> {
+    futures::stream::iter(all())
+        .then(|desired| async move {
+            let existing = client.get_opt(&desired.name_any()).await?;
+
+            Ok(Status::new(&desired, existing.as_ref()))
+        })
+        .try_collect()
+        .await
+}
+
+/// Bring every definition up to date. Missing and outdated definitions are
+/// applied, then objects still stored as a previous version are rewritten so
+/// that the previous version can be dropped. Definitions from a newer version
+/// of kty are left alone, so that replicas running different versions during a
+/// rollout don't fight over them.
+pub async fn migrate(client: kube::Client) -> Result<Vec<Status>> {
+    tracing::info!("migrating CRD definitions...");
+
+    let results: Vec<_> = futures::stream::iter(all())
+        .map(|desired| migrate_one(client.clone(), desired))
+        .buffered(100)
+        .collect()
+        .await;
+
+    let (success, failure): (Vec<Status>, Vec<_>) = results.into_iter().partition_result();
+
+    if !failure.is_empty() {
+        return Err(failure
+            .into_iter()
+            .fold(eyre!("unable to migrate resources"), |acc, err| {
+                acc.section(format!("{err:#}"))
+            }));
+    }
+
+    Ok(success)
+}
+
+async fn migrate_one(
+    client: kube::Client,
+    mut desired: CustomResourceDefinition,
+) -> Result<Status> {
+    let api = Api::<CustomResourceDefinition>::all(client.clone());
+    let name = desired.name_any();
+
+    let existing = api.get_opt(&name).await?;
+    let status = Status::new(&desired, existing.as_ref());
+
+    tracing::info!(crd = name, state = %status.state, "checked CRD");
+
+    let applied = match status.state {
+        State::Current | State::Newer => return Ok(status),
+        State::Unmigrated => existing.ok_or_else(|| eyre!("{name} went away"))?,
+        State::Missing | State::Outdated => {
+            desired
+                .annotations_mut()
+                .insert(VERSION_ANNOTATION.to_string(), VERSION.to_string());
+
+            api.patch(
+                &name,
+                &PatchParams::apply("kty").force(),
+                &Patch::Apply(&desired),
+            )
+            .await?
+        }
+    };
+
+    let after = Status::new(&desired, Some(&applied));
+    if after.state != State::Unmigrated {
+        return Ok(after);
+    }
+
+    tracing::info!(crd = name, from = ?after.stored, to = after.storage, "migrating storage");
+
+    rewrite(client, &desired, &after.storage).await?;
+
+    // Only now that every object has been rewritten is it safe to forget about
+    // the other versions.
+    let migrated = api
+        .patch_status(
+            &name,
+            &PatchParams::default(),
+            &Patch::Merge(json!({ "status": { "storedVersions": [after.storage] } })),
+        )
+        .await?;
+
+    Ok(Status::new(&desired, Some(&migrated)))
+}
+
+// An empty patch makes the API server read every object and write it back as
+// the storage version, the same as the storage version migrator does.
+async fn rewrite(
+    client: kube::Client,
+    crd: &CustomResourceDefinition,
+    version: &str,
+) -> Result<()> {
+    let resource = ApiResource {
+        group: crd.spec.group.clone(),
+        version: version.to_string(),
+        api_version: format!("{}/{version}", crd.spec.group),
+        kind: crd.spec.names.kind.clone(),
+        plural: crd.spec.names.plural.clone(),
+    };
+
+    let cluster = Api::<DynamicObject>::all_with(client.clone(), &resource);
+    let mut params = ListParams::default().limit(PAGE_SIZE);
+
+    loop {
+        let page = cluster.list_metadata(&params).await?;
+
+        for object in &page.items {
+            let api = match object.namespace() {
+                Some(ns) => Api::<DynamicObject>::namespaced_with(client.clone(), &ns, &resource),
+                None => cluster.clone(),
+            };
+
+            match api
+                .patch(
+                    &object.name_any(),
+                    &PatchParams::default(),
+                    &Patch::Merge(json!({})),
+                )
+                .await
+            {
+                // Deleted since it was listed, there's nothing left to migrate.
+                Err(kube::Error::Api(err)) if err.code == 404 => {}
+                result => {
+                    result?;
+                }
+            }
+        }
+
+        match page.metadata.continue_.filter(|token| !token.is_empty()) {
+            Some(token) => params = params.continue_token(&token),
+            None => return Ok(()),
+        }
+    }
+}
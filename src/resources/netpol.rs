@@ -0,0 +1,122 @@
+use std::{cmp::Ordering, sync::Arc};
+
+use chrono::{TimeDelta, Utc};
+use k8s_openapi::api::networking::v1::NetworkPolicy;
+use kube::ResourceExt;
+use ratatui::{
+    layout::Constraint,
+    widgets::{Cell, Row},
+};
+
+use super::{age::Age, columns, target, Compare, Filter};
+use crate::widget::{table, yank::Yank};
+
+#[allow(clippy::module_name_repetitions)]
+pub trait NetworkPolicyExt {
+    fn age(&self) -> TimeDelta;
+    fn selector(&self) -> String;
+    fn types(&self) -> String;
+    fn rules(&self) -> String;
+}
+
+impl NetworkPolicyExt for NetworkPolicy {
+    fn age(&self) -> TimeDelta {
+        let Some(creation) = self.creation_timestamp() else {
+            return TimeDelta::zero();
+        };
+
+        Utc::now() - creation.0
+    }
+
+    fn selector(&self) -> String {
+        self.spec
+            .as_ref()
+            .map(|spec| target::describe(&spec.pod_selector))
+            .unwrap_or_default()
+    }
+
+    // Without `policyTypes`, ingress is always restricted and egress only when
+    // there are egress rules.
+    fn types(&self) -> String {
+        let Some(spec) = self.spec.as_ref() else {
+            return String::new();
+        };
+
+        match &spec.policy_types {
+            Some(types) => types.join(", "),
+            None if spec.egress.is_some() => "Ingress, Egress".to_string(),
+            None => "Ingress".to_string(),
+        }
+    }
+
+    fn rules(&self) -> String {
+        let Some(spec) = self.spec.as_ref() else {
+            return String::new();
+        };
+
+        format!(
+            "{} in / {} out",
+            spec.ingress.as_ref().map_or(0, Vec::len),
+            spec.egress.as_ref().map_or(0, Vec::len),
+        )
+    }
+}
+
+impl table::Row for Arc<NetworkPolicy> {
+    fn yank(&self) -> Option<Yank> {
+        Some(Yank::from_obj(self.as_ref()))
+    }
+
+    fn id(&self) -> Option<String> {
+        self.uid()
+    }
+
+    fn header<'a>() -> Option<Row<'a>> {
+        Some(columns::header::<NetworkPolicy>(vec![
+            Cell::from("Namespace"),
+            Cell::from("Name"),
+            Cell::from("Pod Selector"),
+            Cell::from("Types"),
+            Cell::from("Rules"),
+            Cell::from("Age"),
+        ]))
+    }
+
+    fn constraints() -> Vec<Constraint> {
+        columns::constraints::<NetworkPolicy>(vec![
+            Constraint::Max(20),
+            Constraint::Min(10),
+            Constraint::Min(20),
+            Constraint::Max(16),
+            Constraint::Max(14),
+            Constraint::Max(10),
+        ])
+    }
+
+    fn row(&self, style: &table::RowStyle) -> Row {
+        Row::new(columns::cells(
+            self.as_ref(),
+            vec![
+                self.namespace().unwrap_or_default(),
+                self.name_any(),
+                self.selector(),
+                self.types(),
+                self.rules(),
+                self.age().to_age(),
+            ],
+        ))
+        .style(style.normal)
+    }
+}
+
+impl Filter for NetworkPolicy {
+    fn matches(&self, filter: &str) -> bool {
+        self.name_any().contains(filter) || self.namespace().is_some_and(|ns| ns.contains(filter))
+    }
+}
+
+impl Compare for Arc<NetworkPolicy> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.namespace(), self.name_any()).cmp(&(other.namespace(), other.name_any()))
+    }
+}
@@ -1,8 +1,10 @@
-use std::{cmp::Ordering, str::FromStr, sync::Arc};
+pub mod shell;
+
+use std::{cmp::Ordering, collections::HashMap, fmt, str::FromStr, sync::Arc};
 
 use chrono::{TimeDelta, Utc};
 use itertools::Itertools;
-use k8s_openapi::api::core::v1::{Node, NodeSpec};
+use k8s_openapi::api::core::v1::{Node, NodeCondition, NodeSpec, Pod, Taint, Toleration};
 use kube::ResourceExt;
 use ratatui::{
     layout::Constraint,
@@ -10,8 +12,11 @@ use ratatui::{
 };
 use strum::{Display, EnumString};
 
-use super::{age::Age, Compare, Filter};
-use crate::widget::table;
+use super::{age::Age, columns, requirements, store::Store, Compare, Filter};
+use crate::widget::{
+    table::{self, Health},
+    yank::Yank,
+};
 
 #[derive(EnumString, Display)]
 pub enum Status {
@@ -30,6 +35,8 @@ pub trait NodeExt {
     fn instance_type(&self) -> String;
     fn roles(&self) -> Vec<String>;
     fn status(&self) -> Vec<Status>;
+    fn conditions(&self) -> Vec<NodeCondition>;
+    fn taints(&self) -> Vec<Taint>;
     fn version(&self) -> String;
 }
 
@@ -112,6 +119,20 @@ impl NodeExt for Node {
         status
     }
 
+    fn conditions(&self) -> Vec<NodeCondition> {
+        self.status
+            .as_ref()
+            .and_then(|status| status.conditions.clone())
+            .unwrap_or_default()
+    }
+
+    fn taints(&self) -> Vec<Taint> {
+        self.spec
+            .as_ref()
+            .and_then(|spec| spec.taints.clone())
+            .unwrap_or_default()
+    }
+
     fn version(&self) -> String {
         self.status
             .as_ref()
@@ -120,9 +141,122 @@ impl NodeExt for Node {
     }
 }
 
+/// How worried to be about a node condition. `Ready` is the only condition
+/// that is good when true, the rest (`MemoryPressure`, `DiskPressure` ...) are
+/// problems when true.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reading {
+    Fine,
+    Problem,
+    Unknown,
+}
+
+#[allow(clippy::module_name_repetitions)]
+pub trait ConditionExt {
+    fn reading(&self) -> Reading;
+    /// A short name for the node list, eg. `Mem` for `MemoryPressure`.
+    fn badge(&self) -> String;
+    /// What the condition means for the node, in plain words.
+    fn describe(&self) -> String;
+}
+
+impl ConditionExt for NodeCondition {
+    fn reading(&self) -> Reading {
+        let fine = if self.type_ == "Ready" {
+            "True"
+        } else {
+            "False"
+        };
+
+        match self.status.as_str() {
+            status if status == fine => Reading::Fine,
+            "True" | "False" => Reading::Problem,
+            _ => Reading::Unknown,
+        }
+    }
+
+    fn badge(&self) -> String {
+        let name = match self.type_.as_str() {
+            "MemoryPressure" => "Mem",
+            "DiskPressure" => "Disk",
+            "PIDPressure" => "PID",
+            "NetworkUnavailable" => "Net",
+            other => other,
+        };
+
+        // Marked so that the badge doesn't depend on color alone.
+        match self.reading() {
+            Reading::Fine => name.to_string(),
+            Reading::Problem => format!("{name}!"),
+            Reading::Unknown => format!("{name}?"),
+        }
+    }
+
+    fn describe(&self) -> String {
+        let reading = self.reading();
+
+        match (self.type_.as_str(), reading) {
+            (_, Reading::Unknown) => "Not reported, the kubelet may be unreachable".to_string(),
+            ("Ready", Reading::Fine) => "Ready for pods".to_string(),
+            ("Ready", Reading::Problem) => "Not ready, new pods won't be scheduled".to_string(),
+            ("MemoryPressure", Reading::Fine) => "Enough memory".to_string(),
+            ("MemoryPressure", Reading::Problem) => {
+                "Low on memory, pods may be evicted".to_string()
+            }
+            ("DiskPressure", Reading::Fine) => "Enough disk space".to_string(),
+            ("DiskPressure", Reading::Problem) => {
+                "Low on disk space, pods may be evicted".to_string()
+            }
+            ("PIDPressure", Reading::Fine) => "Enough process IDs".to_string(),
+            ("PIDPressure", Reading::Problem) => "Running out of process IDs".to_string(),
+            ("NetworkUnavailable", Reading::Fine) => "Network is configured".to_string(),
+            ("NetworkUnavailable", Reading::Problem) => "Network isn't configured".to_string(),
+            (kind, _) => format!("{kind} is {}", self.status),
+        }
+    }
+}
+
+/// Whether `toleration` lets a pod onto a node with `taint`. A toleration
+/// without a key and with `Exists` tolerates everything.
+pub fn tolerates(toleration: &Toleration, taint: &Taint) -> bool {
+    let exists = toleration.operator.as_deref() == Some("Exists");
+
+    let key = match toleration.key.as_deref() {
+        None | Some("") => exists,
+        Some(key) => key == taint.key,
+    };
+
+    let value = exists
+        || toleration.value.as_deref().unwrap_or_default()
+            == taint.value.as_deref().unwrap_or_default();
+
+    let effect = match toleration.effect.as_deref() {
+        None | Some("") => true,
+        Some(effect) => effect == taint.effect,
+    };
+
+    key && value && effect
+}
+
+/// `key=value:effect`, the same as `kubectl taint` takes.
+pub fn taint_spec(taint: &Taint) -> String {
+    match taint.value.as_deref() {
+        Some(value) if !value.is_empty() => format!("{}={value}:{}", taint.key, taint.effect),
+        _ => format!("{}:{}", taint.key, taint.effect),
+    }
+}
+
 impl table::Row for Arc<Node> {
+    fn yank(&self) -> Option<Yank> {
+        Some(Yank::from_obj(self.as_ref()))
+    }
+
+    fn id(&self) -> Option<String> {
+        self.uid()
+    }
+
     fn header<'a>() -> Option<Row<'a>> {
-        Some(Row::new(vec![
+        Some(columns::header::<Node>(vec![
             Cell::from("Name"),
             Cell::from("Status"),
             Cell::from("Roles"),
@@ -133,31 +267,36 @@ impl table::Row for Arc<Node> {
     }
 
     fn constraints() -> Vec<Constraint> {
-        vec![
+        columns::constraints::<Node>(vec![
             Constraint::Max(20),
             Constraint::Max(30),
             Constraint::Fill(1),
             Constraint::Max(10),
             Constraint::Max(10),
             Constraint::Max(10),
-        ]
+        ])
     }
 
     fn row(&self, style: &table::RowStyle) -> Row {
         let status = self.status();
+        let health = if status.iter().any(|s| matches!(s, Status::Ready)) {
+            Health::Healthy
+        } else {
+            Health::Normal
+        };
 
-        Row::new(vec![
-            self.name_any(),
-            status.iter().join(", "),
-            self.roles().join(", "),
-            self.instance_type(),
-            self.version(),
-            self.age().to_age(),
-        ])
-        .style(status.iter().fold(style.normal, |acc, s| match s {
-            Status::Ready => style.healthy,
-            _ => acc,
-        }))
+        Row::new(columns::cells(
+            self.as_ref(),
+            vec![
+                self.name_any(),
+                style.status(health, status.iter().join(", ")),
+                self.roles().join(", "),
+                self.instance_type(),
+                self.version(),
+                self.age().to_age(),
+            ],
+        ))
+        .style(style.get(health))
     }
 }
 
@@ -181,3 +320,170 @@ impl Compare for Arc<Node> {
         self.name_any().cmp(&other.name_any())
     }
 }
+
+/// How much of an extended resource, such as `nvidia.com/gpu`, is taken on a
+/// node.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Usage {
+    pub name: String,
+    pub allocatable: f64,
+    /// Requested by the pods scheduled to the node.
+    pub allocated: f64,
+}
+
+impl Usage {
+    pub fn free(&self) -> f64 {
+        (self.allocatable - self.allocated).max(0.0)
+    }
+}
+
+impl fmt::Display for Usage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}/{}", self.name, self.allocated, self.allocatable)
+    }
+}
+
+/// Whether a pod is holding on to what it requested, pods that have finished
+/// don't count against their node.
+pub fn is_active(pod: &Pod) -> bool {
+    !matches!(
+        pod.status
+            .as_ref()
+            .and_then(|status| status.phase.as_deref()),
+        Some("Succeeded" | "Failed")
+    )
+}
+
+/// Extended resources on `node`, with what `pods` request. `pods` are expected
+/// to be the active ones scheduled to the node. Resources requested but not
+/// allocatable, such as after a device plugin goes away, are included too.
+pub fn usage<'a>(node: &Node, pods: impl IntoIterator<Item = &'a Pod>) -> Vec<Usage> {
+    let mut allocatable = requirements::allocatable_extended(
+        node.status
+            .as_ref()
+            .and_then(|status| status.allocatable.as_ref()),
+    );
+
+    let mut allocated = requirements::Extended::new();
+    for (name, value) in pods.into_iter().flat_map(requirements::extended) {
+        *allocated.entry(name).or_default() += value;
+    }
+
+    for name in allocated.keys() {
+        allocatable.entry(name.clone()).or_default();
+    }
+
+    allocatable
+        .into_iter()
+        .map(|(name, allocatable)| Usage {
+            allocated: allocated.get(&name).copied().unwrap_or_default(),
+            name,
+            allocatable,
+        })
+        .collect()
+}
+
+/// A node along with its extended resources, see [`usage`].
+pub struct Allocated {
+    pub node: Arc<Node>,
+    pub extended: Vec<Usage>,
+}
+
+impl table::Row for Allocated {
+    fn yank(&self) -> Option<Yank> {
+        Some(Yank::from_obj(self.node.as_ref()))
+    }
+
+    fn id(&self) -> Option<String> {
+        self.node.uid()
+    }
+
+    fn header<'a>() -> Option<Row<'a>> {
+        Some(columns::header::<Node>(vec![
+            Cell::from("Name"),
+            Cell::from("Status"),
+            Cell::from("Roles"),
+            Cell::from("Type"),
+            Cell::from("Conditions"),
+            Cell::from("Extended"),
+            Cell::from("Version"),
+            Cell::from("Age"),
+        ]))
+    }
+
+    fn constraints() -> Vec<Constraint> {
+        columns::constraints::<Node>(vec![
+            Constraint::Max(20),
+            Constraint::Max(30),
+            Constraint::Fill(1),
+            Constraint::Max(10),
+            Constraint::Max(22),
+            Constraint::Max(25),
+            Constraint::Max(10),
+            Constraint::Max(10),
+        ])
+    }
+
+    fn row(&self, style: &table::RowStyle) -> Row {
+        let node = &self.node;
+        let status = node.status();
+        let health = if status.iter().any(|s| matches!(s, Status::Ready)) {
+            Health::Healthy
+        } else {
+            Health::Normal
+        };
+
+        Row::new(columns::cells(
+            node.as_ref(),
+            vec![
+                node.name_any(),
+                style.status(health, status.iter().join(", ")),
+                node.roles().join(", "),
+                node.instance_type(),
+                node.conditions().iter().map(ConditionExt::badge).join(" "),
+                self.extended.iter().join(", "),
+                node.version(),
+                node.age().to_age(),
+            ],
+        ))
+        .style(style.get(health))
+    }
+}
+
+/// Joins nodes with the pods scheduled to them, to count up extended
+/// resources.
+pub struct Nodes {
+    pub nodes: Arc<Store<Node>>,
+    pub pods: Arc<Store<Pod>>,
+}
+
+impl table::Items for Nodes {
+    type Item = Allocated;
+
+    fn items(&self, filter: Option<String>) -> Vec<Self::Item> {
+        let pods = self.pods.items(None);
+
+        let mut scheduled: HashMap<&str, Vec<&Pod>> = HashMap::new();
+        for pod in pods.iter().filter(|pod| is_active(pod)) {
+            if let Some(node) = pod.spec.as_ref().and_then(|spec| spec.node_name.as_deref()) {
+                scheduled.entry(node).or_default().push(pod);
+            }
+        }
+
+        self.nodes
+            .items(filter)
+            .into_iter()
+            .map(|node| Allocated {
+                extended: usage(
+                    &node,
+                    scheduled
+                        .get(node.name_any().as_str())
+                        .into_iter()
+                        .flatten()
+                        .copied(),
+                ),
+                node,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,225 @@
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use eyre::{eyre, Result};
+use k8s_openapi::api::core::v1::{Container, Pod, PodSpec, SecurityContext, Toleration};
+#[cfg(feature = "ssh")]
+use kube::runtime::reflector;
+use kube::{
+    api::{Api, DeleteParams, ListParams, ObjectMeta},
+    ResourceExt,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{dashboard::context::Context, resources::pod::Phase};
+#[cfg(feature = "ssh")]
+use crate::{
+    resources::settings::{KtySettings, KtySettingsSpec},
+    ssh::leader::Leader,
+};
+
+static CREATED_BY: &str = "app.kubernetes.io/created-by";
+static COMPONENT: &str = "app.kubernetes.io/component";
+static IMAGE: &str = "busybox";
+
+/// Enters every namespace of the host's init process, this is what makes the
+/// shell a shell on the node instead of in the pod.
+pub static COMMAND: &str = "nsenter --target 1 --mount --uts --ipc --net --pid -- sh";
+
+// How often to check on the pod and how long to wait for it to start.
+static START_INTERVAL: Duration = Duration::from_secs(1);
+static START_TIMEOUT: Duration = Duration::from_secs(120);
+// Pods are stopped after this long no matter what, unless the settings say
+// otherwise. Pods are deleted when the shell exits, this is for when that
+// doesn't happen such as the server going away mid-session.
+static DEADLINE_MINUTES: i64 = 60;
+
+// How often the leader looks for pods that were left behind.
+static REAP_EVERY: Duration = Duration::from_secs(60);
+
+/// Where node shells run and for how long. Anything left out uses the
+/// default.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeShellSettings {
+    /// Namespace pods are created in, the server's own namespace by default.
+    /// Users need to be able to create pods here and see it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Image for the pod, only `sleep` and `nsenter` are used. `busybox` by
+    /// default, point this at a mirror for air-gapped clusters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// Minutes a shell can stay open, 60 by default. Pods that are left behind
+    /// are removed by the server once this has passed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline_minutes: Option<i64>,
+}
+
+impl NodeShellSettings {
+    /// The configured namespace, `default` is the namespace of whoever is
+    /// asking.
+    pub fn namespace(&self, default: &str) -> String {
+        self.namespace
+            .clone()
+            .unwrap_or_else(|| default.to_string())
+    }
+
+    fn image(&self) -> String {
+        self.image.clone().unwrap_or_else(|| IMAGE.to_string())
+    }
+
+    fn deadline(&self) -> i64 {
+        self.deadline_minutes.unwrap_or(DEADLINE_MINUTES).max(1) * 60
+    }
+
+    /// The settings for the dashboard being drawn on this thread.
+    pub fn current() -> Self {
+        Context::with(|context| context.node_shell.clone())
+    }
+
+    /// A privileged pod on `node` that shares the host's namespaces. It does
+    /// nothing on its own, the shell is exec'd into it with [`COMMAND`]. The
+    /// API server picks the name so that shells on the same node don't
+    /// collide.
+    pub fn pod(&self, node: &str, namespace: &str) -> Pod {
+        Pod {
+            metadata: ObjectMeta {
+                generate_name: Some("node-shell-".to_string()),
+                namespace: Some(namespace.to_string()),
+                labels: Some(
+                    [
+                        (CREATED_BY.to_string(), "kty".to_string()),
+                        (COMPONENT.to_string(), "node-shell".to_string()),
+                    ]
+                    .into(),
+                ),
+                ..Default::default()
+            },
+            spec: Some(PodSpec {
+                node_name: Some(node.to_string()),
+                host_pid: Some(true),
+                host_ipc: Some(true),
+                host_network: Some(true),
+                restart_policy: Some("Never".to_string()),
+                active_deadline_seconds: Some(self.deadline()),
+                termination_grace_period_seconds: Some(0),
+                // Nodes that are cordoned or tainted are usually the ones that need
+                // a shell the most.
+                tolerations: Some(vec![Toleration {
+                    operator: Some("Exists".to_string()),
+                    ..Default::default()
+                }]),
+                containers: vec![Container {
+                    name: "shell".to_string(),
+                    image: Some(self.image()),
+                    command: Some(vec!["sleep".to_string(), "infinity".to_string()]),
+                    security_context: Some(SecurityContext {
+                        privileged: Some(true),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+/// Wait for the pod to be running, the returned pod has its current status.
+pub async fn wait(api: &Api<Pod>, name: &str) -> Result<Pod> {
+    tokio::time::timeout(START_TIMEOUT, async {
+        loop {
+            let pod = api.get(name).await?;
+
+            match Phase::from(&pod.status.as_ref().and_then(|s| s.phase.clone())) {
+                Phase::Running => return Ok(pod),
+                Phase::Pending => {}
+                phase => return Err(eyre!("{name} didn't start: {phase}")),
+            }
+
+            tokio::time::sleep(START_INTERVAL).await;
+        }
+    })
+    .await
+    .map_err(|_| eyre!("timed out waiting for {name} to start"))?
+}
+
+// Pods that have stopped, usually because they hit their deadline, or have
+// been around for longer than it. Either way, nobody is using the shell.
+fn orphaned(pod: &Pod, now: DateTime<Utc>) -> bool {
+    let phase = Phase::from(&pod.status.as_ref().and_then(|s| s.phase.clone()));
+    if !matches!(phase, Phase::Pending | Phase::Running) {
+        return true;
+    }
+
+    let deadline = pod
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.active_deadline_seconds)
+        .map(TimeDelta::seconds);
+
+    match (pod.creation_timestamp(), deadline) {
+        (Some(created), Some(deadline)) => created.0 + deadline <= now,
+        _ => false,
+    }
+}
+
+/// Delete every node shell in `namespace` that has been left behind,
+/// returning how many there were.
+pub async fn reap(client: kube::Client, namespace: &str) -> Result<usize> {
+    let api = Api::<Pod>::namespaced(client, namespace);
+    let now = Utc::now();
+
+    let mut reaped = 0;
+
+    for pod in api
+        .list(&ListParams::default().labels(&format!("{CREATED_BY}=kty,{COMPONENT}=node-shell")))
+        .await?
+    {
+        if !orphaned(&pod, now) {
+            continue;
+        }
+
+        api.delete(&pod.name_any(), &DeleteParams::default())
+            .await?;
+
+        reaped += 1;
+    }
+
+    Ok(reaped)
+}
+
+/// Delete node shells that were left behind every minute, while `leader`
+/// holds the lease. The namespace comes from `settings`, falling back to
+/// `namespace`.
+#[cfg(feature = "ssh")]
+pub async fn reaper(
+    client: kube::Client,
+    leader: Leader,
+    settings: reflector::Store<KtySettings>,
+    namespace: String,
+) {
+    let mut interval = tokio::time::interval(REAP_EVERY);
+
+    loop {
+        interval.tick().await;
+
+        if !leader.is_leading() {
+            continue;
+        }
+
+        let namespace = KtySettingsSpec::merged(settings.state().iter().map(AsRef::as_ref))
+            .node_shell
+            .unwrap_or_default()
+            .namespace(&namespace);
+
+        match reap(client.clone(), &namespace).await {
+            Ok(0) => {}
+            Ok(reaped) => tracing::info!(reaped, %namespace, "removed node shells left behind"),
+            Err(err) => tracing::warn!(?err, %namespace, "unable to remove node shells"),
+        }
+    }
+}
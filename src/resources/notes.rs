@@ -0,0 +1,25 @@
+use std::collections::BTreeMap;
+
+use k8s_openapi::api::core::v1::ConfigMap;
+use kube::api::ObjectMeta;
+
+static CREATED_BY: &str = "app.kubernetes.io/created-by";
+/// Annotation with the user that wrote the notes.
+pub static USER: &str = "kty.dev/user";
+/// Key in the `ConfigMap` that the notes are stored under.
+pub static KEY: &str = "notes.md";
+
+/// A `ConfigMap` holding notes from a session. The name is generated so that
+/// every session gets its own.
+pub fn config_map(text: &str, user: Option<&str>) -> ConfigMap {
+    ConfigMap {
+        metadata: ObjectMeta {
+            generate_name: Some("kty-notes-".to_string()),
+            labels: Some([(CREATED_BY.to_string(), "kty".to_string())].into()),
+            annotations: user.map(|user| [(USER.to_string(), user.to_string())].into()),
+            ..Default::default()
+        },
+        data: Some(BTreeMap::from([(KEY.to_string(), text.to_string())])),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,254 @@
+use std::{cell::Cell, cmp::Ordering, rc::Rc, sync::Arc};
+
+use chrono::{TimeDelta, Utc};
+use k8s_openapi::{
+    api::{apps::v1::ReplicaSet, batch::v1::Job, core::v1::Pod},
+    apimachinery::pkg::apis::meta::v1::Time,
+};
+use kube::{api::DynamicObject, Api, ResourceExt};
+use ratatui::{
+    layout::Constraint,
+    widgets::{Cell as TableCell, Row},
+};
+
+use super::{action::Actions, age::Age, store::Store, Compare, Filter};
+use crate::widget::{
+    table::{self, Health},
+    yank::Yank,
+};
+
+/// Completed jobs are only listed once they're this old, see [`Orphans`].
+pub static DEFAULT_DAYS: i64 = 7;
+
+/// Something that is taking up space in the cluster without anything looking
+/// after it.
+#[derive(Clone, Debug)]
+pub enum Orphan {
+    /// Has replicas but no deployment, usually left behind by deleting the
+    /// deployment with `--cascade=orphan`.
+    ReplicaSet(Arc<ReplicaSet>),
+    /// Created directly, nothing will replace it if it goes away.
+    Pod(Arc<Pod>),
+    /// Finished and never cleaned up, there's no `ttlSecondsAfterFinished`.
+    Job(Arc<Job>),
+}
+
+impl Orphan {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::ReplicaSet(_) => "ReplicaSet",
+            Self::Pod(_) => "Pod",
+            Self::Job(_) => "Job",
+        }
+    }
+
+    pub fn namespace(&self) -> Option<String> {
+        match self {
+            Self::ReplicaSet(rs) => rs.namespace(),
+            Self::Pod(pod) => pod.namespace(),
+            Self::Job(job) => job.namespace(),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::ReplicaSet(rs) => rs.name_any(),
+            Self::Pod(pod) => pod.name_any(),
+            Self::Job(job) => job.name_any(),
+        }
+    }
+
+    pub fn uid(&self) -> Option<String> {
+        match self {
+            Self::ReplicaSet(rs) => rs.uid(),
+            Self::Pod(pod) => pod.uid(),
+            Self::Job(job) => job.uid(),
+        }
+    }
+
+    pub fn reason(&self) -> String {
+        match self {
+            Self::ReplicaSet(rs) => format!("{} replicas, no owner", replicas(rs)),
+            Self::Pod(_) => "no owner".to_string(),
+            Self::Job(_) => "completed".to_string(),
+        }
+    }
+
+    // Jobs are as old as the last time they did something useful.
+    fn age(&self) -> TimeDelta {
+        let created = match self {
+            Self::ReplicaSet(rs) => rs.creation_timestamp(),
+            Self::Pod(pod) => pod.creation_timestamp(),
+            Self::Job(job) => completed(job).or(job.creation_timestamp()),
+        };
+
+        created.map_or_else(TimeDelta::zero, |time| Utc::now() - time.0)
+    }
+
+    pub fn dynamic_api(&self, client: kube::Client) -> Api<DynamicObject> {
+        match self {
+            Self::ReplicaSet(rs) => rs.dynamic_api(client),
+            Self::Pod(pod) => pod.dynamic_api(client),
+            Self::Job(job) => job.dynamic_api(client),
+        }
+    }
+}
+
+fn completed(job: &Job) -> Option<Time> {
+    job.status.as_ref().and_then(|s| s.completion_time.clone())
+}
+
+fn replicas(rs: &ReplicaSet) -> i32 {
+    rs.spec.as_ref().and_then(|s| s.replicas).unwrap_or(1)
+}
+
+fn has_controller<K: ResourceExt>(obj: &K) -> bool {
+    obj.owner_references()
+        .iter()
+        .any(|owner| owner.controller == Some(true))
+}
+
+impl table::Row for Orphan {
+    fn yank(&self) -> Option<Yank> {
+        Some(Yank::new(self.kind(), self.namespace(), self.name()))
+    }
+
+    fn id(&self) -> Option<String> {
+        self.uid()
+    }
+
+    fn header<'a>() -> Option<Row<'a>> {
+        Some(Row::new(vec![
+            TableCell::from("Kind"),
+            TableCell::from("Namespace"),
+            TableCell::from("Name"),
+            TableCell::from("Reason"),
+            TableCell::from("Age"),
+        ]))
+    }
+
+    fn constraints() -> Vec<Constraint> {
+        vec![
+            Constraint::Max(12),
+            Constraint::Max(20),
+            Constraint::Min(10),
+            Constraint::Max(24),
+            Constraint::Max(10),
+        ]
+    }
+
+    fn row(&self, style: &table::RowStyle) -> Row {
+        let health = match self {
+            Self::ReplicaSet(_) => Health::Unhealthy,
+            Self::Pod(_) | Self::Job(_) => Health::Normal,
+        };
+
+        Row::new(vec![
+            self.kind().to_string(),
+            self.namespace().unwrap_or_default(),
+            self.name(),
+            style.status(health, self.reason()),
+            self.age().to_age(),
+        ])
+        .style(style.get(health))
+    }
+}
+
+impl Compare for Orphan {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.kind(), self.namespace(), self.name()).cmp(&(
+            other.kind(),
+            other.namespace(),
+            other.name(),
+        ))
+    }
+}
+
+impl Filter for ReplicaSet {
+    fn matches(&self, filter: &str) -> bool {
+        self.name_any().contains(filter)
+    }
+}
+
+impl Compare for Arc<ReplicaSet> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.namespace(), self.name_any()).cmp(&(other.namespace(), other.name_any()))
+    }
+}
+
+impl Filter for Job {
+    fn matches(&self, filter: &str) -> bool {
+        self.name_any().contains(filter)
+    }
+}
+
+impl Compare for Arc<Job> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.namespace(), self.name_any()).cmp(&(other.namespace(), other.name_any()))
+    }
+}
+
+/// Everything that is orphaned, from watching replica sets, pods and jobs.
+/// Completed jobs are left out until they're `days` old, the view can change
+/// that while it is open.
+#[derive(Clone)]
+pub struct Orphans {
+    replicasets: Arc<Store<ReplicaSet>>,
+    pods: Arc<Store<Pod>>,
+    jobs: Arc<Store<Job>>,
+    pub days: Rc<Cell<i64>>,
+}
+
+impl Orphans {
+    pub fn new(
+        replicasets: Arc<Store<ReplicaSet>>,
+        pods: Arc<Store<Pod>>,
+        jobs: Arc<Store<Job>>,
+    ) -> Self {
+        Self {
+            replicasets,
+            pods,
+            jobs,
+            days: Rc::new(Cell::new(DEFAULT_DAYS)),
+        }
+    }
+
+    pub fn get(&self, idx: usize, filter: Option<String>) -> Option<Orphan> {
+        table::Items::items(self, filter).get(idx).cloned()
+    }
+}
+
+impl table::Items for Orphans {
+    type Item = Orphan;
+
+    fn items(&self, filter: Option<String>) -> Vec<Self::Item> {
+        let cutoff = TimeDelta::days(self.days.get());
+
+        let replicasets = self
+            .replicasets
+            .items(filter.clone())
+            .into_iter()
+            .filter(|rs| !has_controller(rs.as_ref()) && replicas(rs) > 0)
+            .map(Orphan::ReplicaSet);
+
+        let pods = self
+            .pods
+            .items(filter.clone())
+            .into_iter()
+            .filter(|pod| !has_controller(pod.as_ref()))
+            .map(Orphan::Pod);
+
+        let jobs = self
+            .jobs
+            .items(filter)
+            .into_iter()
+            .filter(|job| !has_controller(job.as_ref()))
+            .filter(|job| completed(job).is_some_and(|time| Utc::now() - time.0 >= cutoff))
+            .map(Orphan::Job);
+
+        let mut items: Vec<_> = replicasets.chain(pods).chain(jobs).collect();
+        items.sort_by(Compare::cmp);
+
+        items
+    }
+}
@@ -0,0 +1,94 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use k8s_openapi::api::core::v1::{Node, Pod};
+use kube::ResourceExt;
+
+use super::node::{NodeExt, Status};
+
+/// Phases in the order they're shown, anything else is added after these.
+pub static PHASES: [&str; 5] = ["Running", "Pending", "Succeeded", "Failed", "Unknown"];
+
+/// A pod that has restarted, see [`Summary::restarts`].
+pub struct Restarts {
+    pub namespace: String,
+    pub name: String,
+    pub count: i32,
+}
+
+/// What the cluster looks like right now, for the overview tab.
+pub struct Summary {
+    pub nodes_ready: usize,
+    pub nodes: usize,
+    /// Number of pods in each phase, as reported by the pod's status.
+    pub phases: BTreeMap<String, u64>,
+    /// Pods with the most container restarts, most first.
+    pub restarts: Vec<Restarts>,
+}
+
+impl Summary {
+    pub fn new(nodes: &[Arc<Node>], pods: &[Arc<Pod>], top: usize) -> Self {
+        let nodes_ready = nodes
+            .iter()
+            .filter(|node| node.status().iter().any(|s| matches!(s, Status::Ready)))
+            .count();
+
+        let mut phases: BTreeMap<_, _> = PHASES.iter().map(|p| (p.to_string(), 0)).collect();
+
+        for pod in pods {
+            let phase = pod
+                .status
+                .as_ref()
+                .and_then(|s| s.phase.clone())
+                .unwrap_or_else(|| "Unknown".to_string());
+
+            *phases.entry(phase).or_default() += 1;
+        }
+
+        let mut restarts: Vec<_> = pods
+            .iter()
+            .map(|pod| Restarts {
+                namespace: pod.namespace().unwrap_or_default(),
+                name: pod.name_any(),
+                count: restarts(pod),
+            })
+            .filter(|r| r.count > 0)
+            .collect();
+        restarts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+        restarts.truncate(top);
+
+        Self {
+            nodes_ready,
+            nodes: nodes.len(),
+            phases,
+            restarts,
+        }
+    }
+
+    /// Phases with their counts, the common ones first in a fixed order so that
+    /// bars don't move around.
+    pub fn phases(&self) -> Vec<(String, u64)> {
+        let known = PHASES.iter().map(|p| {
+            (
+                p.to_string(),
+                self.phases.get(*p).copied().unwrap_or_default(),
+            )
+        });
+
+        let other = self
+            .phases
+            .iter()
+            .filter(|(p, _)| !PHASES.contains(&p.as_str()))
+            .map(|(p, c)| (p.clone(), *c));
+
+        known.chain(other).collect()
+    }
+}
+
+/// Restarts across every container in the pod.
+pub fn restarts(pod: &Pod) -> i32 {
+    pod.status
+        .as_ref()
+        .and_then(|s| s.container_statuses.as_ref())
+        .map(|containers| containers.iter().map(|c| c.restart_count).sum())
+        .unwrap_or_default()
+}
@@ -0,0 +1,390 @@
+use std::{cmp::Ordering, collections::HashMap, fmt, sync::Arc};
+
+use chrono::{TimeDelta, Utc};
+use k8s_openapi::{
+    api::{core::v1::Pod, policy::v1::PodDisruptionBudget},
+    apimachinery::pkg::util::intstr::IntOrString,
+};
+use kube::ResourceExt;
+use ratatui::{
+    layout::Constraint,
+    widgets::{Cell, Row},
+};
+
+use super::{age::Age, columns, store::Store, target, Compare, Filter};
+use crate::widget::{table, yank::Yank};
+
+// Set on pods the kubelet runs from static manifests, drains leave them alone.
+static MIRROR_ANNOTATION: &str = "kubernetes.io/config.mirror";
+
+#[allow(clippy::module_name_repetitions)]
+pub trait PodDisruptionBudgetExt {
+    fn age(&self) -> TimeDelta;
+    fn min_available(&self) -> String;
+    fn max_unavailable(&self) -> String;
+    /// Pods that can be evicted right now, according to the disruption
+    /// controller.
+    fn allowed(&self) -> i32;
+    /// Healthy pods out of the number that need to stay healthy.
+    fn healthy(&self) -> String;
+    /// Whether `pod` counts towards this budget.
+    fn covers(&self, pod: &Pod) -> bool;
+}
+
+fn describe(value: Option<&IntOrString>) -> String {
+    match value {
+        Some(IntOrString::Int(i)) => i.to_string(),
+        Some(IntOrString::String(s)) => s.clone(),
+        None => "N/A".to_string(),
+    }
+}
+
+impl PodDisruptionBudgetExt for PodDisruptionBudget {
+    fn age(&self) -> TimeDelta {
+        let Some(creation) = self.creation_timestamp() else {
+            return TimeDelta::zero();
+        };
+
+        Utc::now() - creation.0
+    }
+
+    fn min_available(&self) -> String {
+        describe(
+            self.spec
+                .as_ref()
+                .and_then(|spec| spec.min_available.as_ref()),
+        )
+    }
+
+    fn max_unavailable(&self) -> String {
+        describe(
+            self.spec
+                .as_ref()
+                .and_then(|spec| spec.max_unavailable.as_ref()),
+        )
+    }
+
+    fn allowed(&self) -> i32 {
+        self.status
+            .as_ref()
+            .map_or(0, |status| status.disruptions_allowed)
+    }
+
+    fn healthy(&self) -> String {
+        self.status.as_ref().map_or_else(String::new, |status| {
+            format!("{}/{}", status.current_healthy, status.desired_healthy)
+        })
+    }
+
+    // Unlike other selectors, a budget without one doesn't select anything.
+    fn covers(&self, pod: &Pod) -> bool {
+        let Some(selector) = self.spec.as_ref().and_then(|spec| spec.selector.as_ref()) else {
+            return false;
+        };
+
+        pod.namespace() == self.namespace() && target::selects(selector, pod.labels())
+    }
+}
+
+impl table::Row for Arc<PodDisruptionBudget> {
+    fn yank(&self) -> Option<Yank> {
+        Some(Yank::from_obj(self.as_ref()))
+    }
+
+    fn id(&self) -> Option<String> {
+        self.uid()
+    }
+
+    fn health(&self) -> table::Health {
+        if self.allowed() > 0 {
+            table::Health::Normal
+        } else {
+            table::Health::Unhealthy
+        }
+    }
+
+    fn header<'a>() -> Option<Row<'a>> {
+        Some(columns::header::<PodDisruptionBudget>(vec![
+            Cell::from("Namespace"),
+            Cell::from("Name"),
+            Cell::from("Min Available"),
+            Cell::from("Max Unavailable"),
+            Cell::from("Allowed"),
+            Cell::from("Healthy"),
+            Cell::from("Age"),
+        ]))
+    }
+
+    fn constraints() -> Vec<Constraint> {
+        columns::constraints::<PodDisruptionBudget>(vec![
+            Constraint::Max(20),
+            Constraint::Min(10),
+            Constraint::Max(15),
+            Constraint::Max(17),
+            Constraint::Max(9),
+            Constraint::Max(9),
+            Constraint::Max(10),
+        ])
+    }
+
+    fn row(&self, style: &table::RowStyle) -> Row {
+        Row::new(columns::cells(
+            self.as_ref(),
+            vec![
+                self.namespace().unwrap_or_default(),
+                self.name_any(),
+                self.min_available(),
+                self.max_unavailable(),
+                self.allowed().to_string(),
+                self.healthy(),
+                self.age().to_age(),
+            ],
+        ))
+        .style(style.get(table::Row::health(self)))
+    }
+}
+
+impl Filter for PodDisruptionBudget {
+    fn matches(&self, filter: &str) -> bool {
+        self.name_any().contains(filter) || self.namespace().is_some_and(|ns| ns.contains(filter))
+    }
+}
+
+impl Compare for Arc<PodDisruptionBudget> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.namespace(), self.name_any()).cmp(&(other.namespace(), other.name_any()))
+    }
+}
+
+/// What happens to a pod when its node is drained.
+#[derive(Clone, Debug)]
+pub enum Verdict {
+    Evictable,
+    /// Drains don't evict the pod at all, such as pods owned by a `DaemonSet`.
+    Skipped(&'static str),
+    /// Evictions are refused until the budget allows disruptions again.
+    Blocked(String),
+    /// Some pods on the node can be evicted, the drain waits on replacements
+    /// becoming healthy before it gets to the rest.
+    Stalls(String),
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Evictable => write!(f, "Evictable"),
+            Self::Skipped(reason) => write!(f, "Skipped ({reason})"),
+            Self::Blocked(reason) => write!(f, "Blocked ({reason})"),
+            Self::Stalls(reason) => write!(f, "Stalls ({reason})"),
+        }
+    }
+}
+
+/// A pod, the budget it falls under and what a drain would do to it.
+#[derive(Clone, Debug)]
+pub struct Eviction {
+    pub pod: Arc<Pod>,
+    pub budget: Option<String>,
+    pub verdict: Verdict,
+}
+
+impl table::Row for Eviction {
+    fn id(&self) -> Option<String> {
+        self.pod.uid()
+    }
+
+    fn health(&self) -> table::Health {
+        match self.verdict {
+            Verdict::Blocked(_) | Verdict::Stalls(_) => table::Health::Unhealthy,
+            Verdict::Evictable | Verdict::Skipped(_) => table::Health::Normal,
+        }
+    }
+
+    fn header<'a>() -> Option<Row<'a>> {
+        Some(Row::new(vec![
+            Cell::from("Namespace"),
+            Cell::from("Name"),
+            Cell::from("Node"),
+            Cell::from("Budget"),
+            Cell::from("Drain"),
+        ]))
+    }
+
+    fn constraints() -> Vec<Constraint> {
+        vec![
+            Constraint::Max(20),
+            Constraint::Min(10),
+            Constraint::Max(20),
+            Constraint::Max(20),
+            Constraint::Min(20),
+        ]
+    }
+
+    fn row(&self, style: &table::RowStyle) -> Row {
+        Row::new(vec![
+            self.pod.namespace().unwrap_or_default(),
+            self.pod.name_any(),
+            node(&self.pod),
+            self.budget.clone().unwrap_or_default(),
+            self.verdict.to_string(),
+        ])
+        .style(style.get(table::Row::health(self)))
+    }
+}
+
+fn node(pod: &Pod) -> String {
+    pod.spec
+        .as_ref()
+        .and_then(|spec| spec.node_name.clone())
+        .unwrap_or_default()
+}
+
+fn skipped(pod: &Pod) -> Option<&'static str> {
+    if pod.annotations().contains_key(MIRROR_ANNOTATION) {
+        return Some("static pod");
+    }
+
+    if pod
+        .owner_references()
+        .iter()
+        .any(|owner| owner.controller == Some(true) && owner.kind == "DaemonSet")
+    {
+        return Some("daemonset");
+    }
+
+    // Pods that have finished can always be evicted, there's nothing to disrupt.
+    match pod
+        .status
+        .as_ref()
+        .and_then(|status| status.phase.as_deref())
+    {
+        Some("Succeeded" | "Failed") => Some("finished"),
+        _ => None,
+    }
+}
+
+/// Simulate draining the nodes `pods` are on, the way `kubectl drain` would go
+/// about it. Pods covered by a budget are evictable while the budget allows
+/// for every one of its pods on the same node.
+pub fn evictions(pods: &[Arc<Pod>], budgets: &[Arc<PodDisruptionBudget>]) -> Vec<Eviction> {
+    let matched: Vec<_> = pods
+        .iter()
+        .map(|pod| {
+            let covering: Vec<_> = budgets
+                .iter()
+                .filter(|budget| skipped(pod).is_none() && budget.covers(pod))
+                .collect();
+
+            (pod, covering)
+        })
+        .collect();
+
+    let mut per_node: HashMap<(String, String), i32> = HashMap::new();
+    for (pod, covering) in &matched {
+        if let [budget] = covering.as_slice() {
+            *per_node
+                .entry((budget.uid().unwrap_or_default(), node(pod)))
+                .or_default() += 1;
+        }
+    }
+
+    matched
+        .into_iter()
+        .map(|(pod, covering)| {
+            let budget = covering.first().map(|budget| budget.name_any());
+
+            let verdict = if let Some(reason) = skipped(pod) {
+                Verdict::Skipped(reason)
+            } else {
+                match covering.as_slice() {
+                    [] => Verdict::Evictable,
+                    [budget] => {
+                        let allowed = budget.allowed();
+                        let on_node = per_node
+                            .get(&(budget.uid().unwrap_or_default(), node(pod)))
+                            .copied()
+                            .unwrap_or_default();
+
+                        if allowed <= 0 {
+                            Verdict::Blocked("no disruptions allowed".to_string())
+                        } else if on_node > allowed {
+                            Verdict::Stalls(format!("{on_node} on the node, {allowed} allowed"))
+                        } else {
+                            Verdict::Evictable
+                        }
+                    }
+                    // The API server refuses to evict pods that more than one budget covers.
+                    _ => Verdict::Blocked("more than one budget".to_string()),
+                }
+            };
+
+            Eviction {
+                pod: pod.clone(),
+                budget,
+                verdict,
+            }
+        })
+        .collect()
+}
+
+/// Which pods are being simulated.
+#[derive(Clone)]
+pub enum Scope {
+    /// Every pod on a node, the store is expected to only have those.
+    Node,
+    /// The pods a budget covers, wherever they are.
+    Budget(Arc<PodDisruptionBudget>),
+}
+
+/// Live evictions, see [`evictions`]. Pods and budgets are watched, so this
+/// keeps up as pods are rescheduled and budgets change.
+#[derive(Clone)]
+pub struct Evictions {
+    scope: Scope,
+    pods: Arc<Store<Pod>>,
+    budgets: Arc<Store<PodDisruptionBudget>>,
+}
+
+impl Evictions {
+    pub fn new(
+        scope: Scope,
+        pods: Arc<Store<Pod>>,
+        budgets: Arc<Store<PodDisruptionBudget>>,
+    ) -> Self {
+        Self {
+            scope,
+            pods,
+            budgets,
+        }
+    }
+
+    pub fn get(&self, idx: usize, filter: Option<String>) -> Option<Arc<Pod>> {
+        table::Items::items(self, filter)
+            .get(idx)
+            .map(|eviction| eviction.pod.clone())
+    }
+}
+
+impl table::Items for Evictions {
+    type Item = Eviction;
+
+    fn items(&self, filter: Option<String>) -> Vec<Self::Item> {
+        let budgets = self.budgets.items(None);
+        let pods = self.pods.items(filter);
+
+        match &self.scope {
+            Scope::Node => evictions(&pods, &budgets),
+            Scope::Budget(budget) => {
+                // The copy in the store is more recent, unless it has gone away.
+                let budget = budgets
+                    .into_iter()
+                    .find(|b| b.uid() == budget.uid())
+                    .unwrap_or_else(|| budget.clone());
+
+                let pods: Vec<_> = pods.into_iter().filter(|pod| budget.covers(pod)).collect();
+
+                evictions(&pods, &[budget])
+            }
+        }
+    }
+}
@@ -16,10 +16,15 @@ use ratatui::{
 
 use super::{
     age::Age,
+    columns,
     container::{Container, ContainerExt},
+    requirements::{self, Requirements},
     Compare, Filter,
 };
-use crate::widget::table;
+use crate::widget::{
+    table::{self, Health},
+    yank::Yank,
+};
 
 pub enum Phase {
     Pending,
@@ -61,6 +66,9 @@ pub trait PodExt {
     fn status(&self) -> Phase;
     fn containers(&self, filter: Option<String>) -> Vec<Container>;
     fn ip(&self) -> Option<IpAddr>;
+    fn requirements(&self) -> Requirements;
+    fn qos(&self) -> String;
+    fn owner(&self) -> Option<String>;
 }
 
 impl PodExt for Pod {
@@ -222,45 +230,146 @@ impl PodExt for Pod {
 
         pod_ip.parse().ok()
     }
+
+    fn requirements(&self) -> Requirements {
+        self.into()
+    }
+
+    fn qos(&self) -> String {
+        requirements::qos(self)
+    }
+
+    // Replica sets are an implementation detail of deployments, the hash they
+    // add to their name is dropped to get back to the deployment.
+    fn owner(&self) -> Option<String> {
+        let owner = self
+            .owner_references()
+            .iter()
+            .find(|o| o.controller == Some(true))?;
+
+        let deployment = (owner.kind == "ReplicaSet")
+            .then(|| self.labels().get("pod-template-hash"))
+            .flatten()
+            .and_then(|hash| owner.name.strip_suffix(&format!("-{hash}")));
+
+        Some(match deployment {
+            Some(name) => format!("Deployment/{name}"),
+            None => format!("{}/{}", owner.kind, owner.name),
+        })
+    }
+}
+
+fn restart_count(pod: &Pod) -> i32 {
+    pod.status
+        .as_ref()
+        .and_then(|status| status.container_statuses.as_ref())
+        .map_or(0, |containers| {
+            containers.iter().map(|c| c.restart_count).sum()
+        })
+}
+
+// Lower is worse. Waiting reasons that are part of starting up count as
+// pending, anything else (`CrashLoopBackOff`, `Error`, ...) is a problem.
+fn severity(pod: &Pod) -> u8 {
+    match pod.status() {
+        Phase::Unknown(reason) if reason == "ContainerCreating" || reason == "PodInitializing" => 1,
+        Phase::Unknown(_) => 0,
+        Phase::Pending => 1,
+        Phase::Running => 2,
+        Phase::Succeeded => 3,
+    }
 }
 
 impl table::Row for Arc<Pod> {
+    fn yank(&self) -> Option<Yank> {
+        Some(Yank::from_obj(self.as_ref()))
+    }
+
+    fn group(&self, group: table::Group) -> Option<String> {
+        match group {
+            table::Group::Node => self.spec.as_ref().and_then(|spec| spec.node_name.clone()),
+            table::Group::Namespace => self.namespace(),
+            table::Group::Owner => self.owner(),
+        }
+    }
+
+    fn health(&self) -> Health {
+        match self.status() {
+            Phase::Pending | Phase::Running => Health::Normal,
+            Phase::Succeeded => Health::Healthy,
+            Phase::Unknown(_) => Health::Unhealthy,
+        }
+    }
+
+    fn id(&self) -> Option<String> {
+        self.uid()
+    }
+
+    fn compare(&self, other: &Self, sort: table::Sort) -> Ordering {
+        let age = other
+            .creation_timestamp()
+            .map(|time| time.0)
+            .cmp(&self.creation_timestamp().map(|time| time.0));
+        let restarts = restart_count(other).cmp(&restart_count(self));
+
+        match sort {
+            table::Sort::Severity => severity(self)
+                .cmp(&severity(other))
+                .then(restarts)
+                .then(age),
+            table::Sort::Restarts => restarts.then(age),
+            table::Sort::Age => age,
+        }
+    }
+
     fn header<'a>() -> Option<Row<'a>> {
-        Some(Row::new(vec![
+        Some(columns::header::<Pod>(vec![
             Cell::from("Namespace"),
             Cell::from("Name"),
             Cell::from("Ready"),
             Cell::from("Status"),
             Cell::from("Restarts"),
+            Cell::from("CPU"),
+            Cell::from("Memory"),
+            Cell::from("QoS"),
             Cell::from("Age"),
         ]))
     }
 
     fn constraints() -> Vec<Constraint> {
-        vec![
+        columns::constraints::<Pod>(vec![
             Constraint::Max(20),
             Constraint::Min(10),
             Constraint::Max(10),
             Constraint::Max(10),
             Constraint::Max(10),
+            Constraint::Max(12),
+            Constraint::Max(16),
             Constraint::Max(10),
-        ]
+            Constraint::Max(10),
+        ])
     }
 
     fn row(&self, style: &table::RowStyle) -> Row {
-        Row::new(vec![
-            self.namespace().unwrap_or_default(),
-            self.name_any(),
-            self.ready(),
-            self.status().to_string(),
-            self.restarts(),
-            self.age().to_age(),
-        ])
-        .style(match self.status() {
-            Phase::Pending | Phase::Running => style.normal,
-            Phase::Succeeded => style.healthy,
-            Phase::Unknown(_) => style.unhealthy,
-        })
+        let requirements = self.requirements();
+        let status = self.status();
+        let health = table::Row::health(self);
+
+        Row::new(columns::cells(
+            self.as_ref(),
+            vec![
+                self.namespace().unwrap_or_default(),
+                self.name_any(),
+                self.ready(),
+                style.status(health, status.to_string()),
+                self.restarts(),
+                requirements.cpu(),
+                requirements.memory(),
+                self.qos(),
+                self.age().to_age(),
+            ],
+        ))
+        .style(style.get(health))
     }
 }
 
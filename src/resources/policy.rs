@@ -0,0 +1,299 @@
+use std::{
+    collections::BTreeSet,
+    future::ready,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use clap::ValueEnum;
+use eyre::{eyre, Result};
+use futures::{FutureExt, StreamExt};
+use kube::{
+    api::Api,
+    runtime::{reflector, watcher, WatchStreamExt},
+    CustomResource,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::dashboard::context::Context;
+
+/// Narrows down what users and groups see in the dashboard. This is evaluated
+/// in addition to RBAC and can only take away, what RBAC denies stays denied.
+/// Identities that no policy applies to see everything RBAC allows.
+#[allow(clippy::module_name_repetitions)]
+#[derive(CustomResource, Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[kube(group = "kty.dev", version = "v1alpha1", kind = "AccessPolicy")]
+#[serde(rename_all = "camelCase")]
+pub struct AccessPolicySpec {
+    /// Users the policy applies to.
+    #[serde(default)]
+    pub users: Vec<String>,
+    /// Groups the policy applies to.
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Namespaces that can be seen, a trailing `*` matches any suffix such as
+    /// `team-a-*`. All of them when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespaces: Option<Vec<String>>,
+    /// What can be done from the dashboard: `logs`, `exec`, `create`, `patch`
+    /// and `delete`. All of them when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verbs: Option<Vec<String>>,
+    /// Disconnect sessions that haven't had any input for this long, instead of
+    /// the server's `--inactivity-timeout`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inactivity_timeout_seconds: Option<u64>,
+    /// Changes made from the dashboard always show a server side dry run to be
+    /// confirmed first, users can't turn this off for their session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub require_preview: Option<bool>,
+}
+
+impl AccessPolicySpec {
+    fn applies(&self, user: &str, groups: &[String]) -> bool {
+        self.users.iter().any(|u| u == user) || self.groups.iter().any(|g| groups.contains(g))
+    }
+}
+
+/// Things a user can do from the dashboard that a policy can take away.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Verb {
+    Logs,
+    Exec,
+    Create,
+    Patch,
+    Delete,
+    /// Forwarding with `ssh -L` and `ssh -R`. Only enforced for verbs disabled
+    /// by an administrator, tunnels aren't part of the dashboard.
+    PortForward,
+    /// Reading secrets, which is only possible through the API proxy. Like
+    /// `PortForward`, this is only enforced when disabled.
+    Secrets,
+}
+
+impl Verb {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Logs => "logs",
+            Self::Exec => "exec",
+            Self::Create => "create",
+            Self::Patch => "patch",
+            Self::Delete => "delete",
+            Self::PortForward => "port-forward",
+            Self::Secrets => "secrets",
+        }
+    }
+
+    /// The verb called `name`, as in [`Verb::name`].
+    pub fn parse(name: &str) -> Option<Self> {
+        <Self as ValueEnum>::from_str(name, true).ok()
+    }
+
+    /// Whether the dashboard being drawn on this thread allows the verb.
+    /// Nothing is allowed off that thread, see [`Policy::visible`].
+    pub fn allowed(self) -> bool {
+        Context::with(|context| context.policy.as_ref().is_some_and(|p| p.allows(self)))
+    }
+
+    /// Whether an administrator has turned the verb off for every session, see
+    /// [`Policy::disable`]. Unlike verbs a policy doesn't grant, these are
+    /// still shown so that users know why they can't be used.
+    pub fn disabled(self) -> bool {
+        Context::with(|context| context.policy.as_ref().map_or(true, |p| p.disables(self)))
+    }
+}
+
+/// What a session is allowed, combined from every [`AccessPolicy`] that
+/// applies to it. When more than one applies, anything any of them allows is
+/// allowed, the longest inactivity timeout wins and previews are only required
+/// when all of them require it.
+#[derive(Clone, Debug, Default)]
+pub struct Policy {
+    namespaces: Option<Vec<String>>,
+    verbs: Option<BTreeSet<String>>,
+    inactivity_timeout: Option<Duration>,
+    require_preview: bool,
+    disabled: Vec<Verb>,
+}
+
+impl Policy {
+    pub fn resolve<'a>(
+        policies: impl IntoIterator<Item = &'a AccessPolicy>,
+        user: &str,
+        groups: &[String],
+    ) -> Self {
+        let applied: Vec<_> = policies
+            .into_iter()
+            .filter(|policy| policy.spec.applies(user, groups))
+            .collect();
+
+        if applied.is_empty() {
+            return Self::default();
+        }
+
+        Self {
+            namespaces: applied
+                .iter()
+                .map(|policy| policy.spec.namespaces.clone())
+                .collect::<Option<Vec<_>>>()
+                .map(|all| all.into_iter().flatten().collect()),
+            verbs: applied
+                .iter()
+                .map(|policy| policy.spec.verbs.clone())
+                .collect::<Option<Vec<_>>>()
+                .map(|all| all.into_iter().flatten().collect()),
+            inactivity_timeout: applied
+                .iter()
+                .filter_map(|policy| policy.spec.inactivity_timeout_seconds)
+                .max()
+                .map(Duration::from_secs),
+            require_preview: applied
+                .iter()
+                .all(|policy| policy.spec.require_preview.unwrap_or_default()),
+            disabled: Vec::new(),
+        }
+    }
+
+    /// Take `verbs` away from everyone, no matter what any policy allows.
+    pub fn disable(mut self, verbs: impl IntoIterator<Item = Verb>) -> Self {
+        self.disabled.extend(verbs);
+        self
+    }
+
+    /// Overrides the server's inactivity timeout when set.
+    pub fn inactivity_timeout(&self) -> Option<Duration> {
+        self.inactivity_timeout
+    }
+
+    pub fn allows(&self, verb: Verb) -> bool {
+        if self.disabled.contains(&verb) {
+            return false;
+        }
+
+        // Only administrators can take these away, see `Verb::PortForward`.
+        matches!(verb, Verb::PortForward | Verb::Secrets)
+            || self
+                .verbs
+                .as_ref()
+                .map_or(true, |verbs| verbs.contains(verb.name()))
+    }
+
+    /// Whether an administrator has turned `verb` off, see [`Policy::disable`].
+    pub fn disables(&self, verb: Verb) -> bool {
+        self.disabled.contains(&verb)
+    }
+
+    /// Whether every namespace can be seen, requests across all of them would
+    /// otherwise show resources from namespaces that can't.
+    pub fn sees_all(&self) -> bool {
+        self.namespaces.is_none()
+    }
+
+    /// Whether resources in `namespace` can be seen, cluster scoped resources
+    /// always can.
+    pub fn sees(&self, namespace: Option<&str>) -> bool {
+        let (Some(patterns), Some(namespace)) = (self.namespaces.as_ref(), namespace) else {
+            return true;
+        };
+
+        patterns
+            .iter()
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => namespace.starts_with(prefix),
+                None => pattern == namespace,
+            })
+    }
+
+    /// Whether the dashboard being drawn on this thread has to preview changes,
+    /// see [`AccessPolicySpec::require_preview`].
+    pub fn requires_preview() -> bool {
+        Context::with(|context| context.policy.as_ref().map_or(true, |p| p.require_preview))
+    }
+
+    /// [`Policy::sees`] for the dashboard being drawn on this thread.
+    ///
+    /// Off that thread, such as in `spawn_blocking`, nothing is visible unless
+    /// the dashboard's [`Context`] has been entered.
+    pub fn visible(namespace: Option<&str>) -> bool {
+        Context::with(|context| context.policy.as_ref().is_some_and(|p| p.sees(namespace)))
+    }
+
+    /// The policy for the dashboard being drawn on this thread, for work that
+    /// happens elsewhere. Off that thread, this is [`Policy::denied`].
+    pub fn current() -> Self {
+        Context::with(|context| context.policy.clone().unwrap_or_else(Self::denied))
+    }
+
+    /// Sees no namespaces and allows none of the verbs.
+    pub fn denied() -> Self {
+        Self {
+            namespaces: Some(Vec::new()),
+            verbs: Some(BTreeSet::new()),
+            require_preview: true,
+            disabled: vec![Verb::PortForward, Verb::Secrets],
+            ..Self::default()
+        }
+    }
+}
+
+/// Every [`AccessPolicy`], kept up to date by a watcher. Policies only ever
+/// take away, so while they can't be watched nobody can be given a policy.
+#[derive(Clone)]
+pub struct Policies {
+    store: reflector::Store<AccessPolicy>,
+    // Unset from the first failed watch event until the next one that works.
+    healthy: Arc<AtomicBool>,
+}
+
+impl Policies {
+    /// Start watching policies across the cluster with `client`.
+    pub fn watch(client: kube::Client) -> Self {
+        let (store, writer) = reflector::store();
+        let healthy = Arc::new(AtomicBool::new(true));
+
+        tokio::spawn({
+            let healthy = healthy.clone();
+
+            watcher(Api::<AccessPolicy>::all(client), watcher::Config::default())
+                .default_backoff()
+                .reflect(writer)
+                .for_each(move |event| {
+                    if let Err(err) = &event {
+                        tracing::error!("unable to watch access policies, denying logins: {err}");
+                    }
+
+                    healthy.store(event.is_ok(), Ordering::Relaxed);
+
+                    ready(())
+                })
+        });
+
+        Self { store, healthy }
+    }
+
+    /// Wait for the first list of policies to finish.
+    pub async fn ready(&self) -> Result<()> {
+        Ok(self.store.wait_until_ready().await?)
+    }
+
+    /// The policy for `user` and `groups`, see [`Policy::resolve`]. Until the
+    /// first list has finished or while policies can't be watched, this is an
+    /// error.
+    pub fn resolve(&self, user: &str, groups: &[String]) -> Result<Policy> {
+        let ready = matches!(self.store.wait_until_ready().now_or_never(), Some(Ok(())));
+
+        if !ready || !self.healthy.load(Ordering::Relaxed) {
+            return Err(eyre!("access policies are unavailable"));
+        }
+
+        Ok(Policy::resolve(
+            self.store.state().iter().map(AsRef::as_ref),
+            user,
+            groups,
+        ))
+    }
+}
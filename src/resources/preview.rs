@@ -0,0 +1,123 @@
+use kube::api::DynamicObject;
+
+// Lines around each change that are kept in a diff, the rest are elided.
+const CONTEXT: usize = 2;
+
+/// What a change would do to a resource, from a server side dry run. The API
+/// server runs admission (defaulting, validation and webhooks) as if it were
+/// real, so this is also where permissions and policies are checked.
+#[derive(Clone, Debug)]
+pub struct Outcome {
+    /// The resource, such as `default/nginx`.
+    pub target: String,
+    /// `None` when the resource is being created.
+    pub before: Option<DynamicObject>,
+    /// `None` when the resource is being deleted.
+    pub after: Option<DynamicObject>,
+}
+
+impl Outcome {
+    /// Changed lines of the resource as YAML, with a little context around each
+    /// change. `None` stands in for lines that were left out.
+    pub fn diff(&self) -> Vec<Option<Line>> {
+        elide(diff(
+            &yaml(self.before.as_ref()),
+            &yaml(self.after.as_ref()),
+        ))
+    }
+}
+
+/// Only the changed lines of `lines`, with a little context around each.
+/// `None` stands in for lines that were left out.
+pub fn elide(lines: Vec<Line>) -> Vec<Option<Line>> {
+    let changed: Vec<_> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Line::Same(_)))
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut out = Vec::new();
+    let mut last = None;
+
+    for (i, line) in lines.into_iter().enumerate() {
+        let near = changed
+            .iter()
+            .any(|c| i + CONTEXT >= *c && i <= c + CONTEXT);
+
+        if !near {
+            continue;
+        }
+
+        if last.is_some_and(|last| last + 1 != i) {
+            out.push(None);
+        }
+
+        out.push(Some(line));
+        last = Some(i);
+    }
+
+    out
+}
+
+/// A line of a diff.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Line {
+    Same(String),
+    Added(String),
+    Removed(String),
+}
+
+// Fields that change on every write and only get in the way of what the change
+// actually does.
+fn yaml(obj: Option<&DynamicObject>) -> String {
+    let Some(obj) = obj else {
+        return String::new();
+    };
+
+    let mut obj = obj.clone();
+    obj.metadata.managed_fields = None;
+    obj.metadata.resource_version = None;
+    obj.metadata.generation = None;
+
+    serde_yaml::to_string(&obj).unwrap_or_default()
+}
+
+/// Lines of `before` and `after` as a diff, from the longest common
+/// subsequence. Resources are small enough that the quadratic table is fine.
+pub fn diff(before: &str, after: &str) -> Vec<Line> {
+    let before: Vec<_> = before.lines().collect();
+    let after: Vec<_> = after.lines().collect();
+
+    // `table[i][j]` is the length of the longest common subsequence of
+    // `before[i..]` and `after[j..]`.
+    let mut table = vec![vec![0usize; after.len() + 1]; before.len() + 1];
+    for i in (0..before.len()).rev() {
+        for j in (0..after.len()).rev() {
+            table[i][j] = if before[i] == after[j] {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+
+    while i < before.len() || j < after.len() {
+        if i < before.len() && j < after.len() && before[i] == after[j] {
+            lines.push(Line::Same(before[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if j < after.len() && (i == before.len() || table[i][j + 1] >= table[i + 1][j]) {
+            lines.push(Line::Added(after[j].to_string()));
+            j += 1;
+        } else {
+            lines.push(Line::Removed(before[i].to_string()));
+            i += 1;
+        }
+    }
+
+    lines
+}
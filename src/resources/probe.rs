@@ -0,0 +1,166 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use k8s_openapi::{
+    api::core::v1::{Container, Pod, Probe},
+    apimachinery::pkg::util::intstr::IntOrString,
+};
+
+use super::age::Age;
+
+/// Event reasons the kubelet uses when a probe doesn't pass.
+pub static FAILURE_REASONS: [&str; 2] = ["Unhealthy", "ProbeWarning"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Startup,
+    Liveness,
+    Readiness,
+}
+
+impl Kind {
+    pub fn all() -> &'static [Kind] {
+        &[Self::Startup, Self::Liveness, Self::Readiness]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Startup => "startup",
+            Self::Liveness => "liveness",
+            Self::Readiness => "readiness",
+        }
+    }
+
+    pub fn get(self, container: &Container) -> Option<&Probe> {
+        match self {
+            Self::Startup => container.startup_probe.as_ref(),
+            Self::Liveness => container.liveness_probe.as_ref(),
+            Self::Readiness => container.readiness_probe.as_ref(),
+        }
+    }
+}
+
+pub trait ProbeExt {
+    fn handler(&self) -> String;
+    fn timing(&self) -> String;
+    fn next(&self, started: DateTime<Utc>) -> TimeDelta;
+}
+
+// Defaults from the API reference, these are what the kubelet uses when unset.
+fn initial_delay(probe: &Probe) -> i64 {
+    probe.initial_delay_seconds.unwrap_or(0).into()
+}
+
+fn period(probe: &Probe) -> i64 {
+    probe.period_seconds.unwrap_or(10).max(1).into()
+}
+
+fn port(port: &IntOrString) -> String {
+    match port {
+        IntOrString::Int(port) => port.to_string(),
+        IntOrString::String(name) => name.clone(),
+    }
+}
+
+impl ProbeExt for Probe {
+    /// What the probe checks, eg. `GET http://:8080/healthz`.
+    fn handler(&self) -> String {
+        if let Some(http) = &self.http_get {
+            return format!(
+                "GET {}://{}:{}{}",
+                http.scheme.as_deref().unwrap_or("HTTP").to_lowercase(),
+                http.host.as_deref().unwrap_or_default(),
+                port(&http.port),
+                http.path.as_deref().unwrap_or("/"),
+            );
+        }
+
+        if let Some(tcp) = &self.tcp_socket {
+            return format!(
+                "tcp {}:{}",
+                tcp.host.as_deref().unwrap_or_default(),
+                port(&tcp.port)
+            );
+        }
+
+        if let Some(grpc) = &self.grpc {
+            return match &grpc.service {
+                Some(service) => format!("grpc :{} {service}", grpc.port),
+                None => format!("grpc :{}", grpc.port),
+            };
+        }
+
+        if let Some(exec) = &self.exec {
+            return format!(
+                "exec {}",
+                exec.command.clone().unwrap_or_default().join(" ")
+            );
+        }
+
+        "unknown".to_string()
+    }
+
+    fn timing(&self) -> String {
+        format!(
+            "delay={}s timeout={}s period={}s #success={} #failure={}",
+            initial_delay(self),
+            self.timeout_seconds.unwrap_or(1),
+            period(self),
+            self.success_threshold.unwrap_or(1),
+            self.failure_threshold.unwrap_or(3),
+        )
+    }
+
+    /// Time until the probe runs next for a container that started at
+    /// `started`. The kubelet adds jitter, so this is an estimate.
+    fn next(&self, started: DateTime<Utc>) -> TimeDelta {
+        let since = (Utc::now() - started).num_seconds() - initial_delay(self);
+
+        if since < 0 {
+            return TimeDelta::seconds(-since);
+        }
+
+        TimeDelta::seconds(period(self) - since % period(self))
+    }
+}
+
+/// When the container last started, if it is running.
+pub fn started(pod: &Pod, container: &str) -> Option<DateTime<Utc>> {
+    pod.status
+        .as_ref()?
+        .container_statuses
+        .as_ref()?
+        .iter()
+        .find(|status| status.name == container)?
+        .state
+        .as_ref()?
+        .running
+        .as_ref()?
+        .started_at
+        .as_ref()
+        .map(|t| t.0)
+}
+
+/// Countdown to the next run of a probe, or why there won't be one.
+pub fn countdown(probe: &Probe, started: Option<DateTime<Utc>>) -> String {
+    match started {
+        Some(started) => format!("next in {}", probe.next(started).to_age()),
+        None => "not running".to_string(),
+    }
+}
+
+/// Readiness gates of the pod with the status of their condition, `Unknown`
+/// until something reports on it.
+pub fn gates(pod: &Pod) -> Vec<(String, String)> {
+    let conditions = pod.status.as_ref().and_then(|s| s.conditions.as_ref());
+
+    pod.spec
+        .iter()
+        .flat_map(|spec| spec.readiness_gates.iter().flatten())
+        .map(|gate| {
+            let status = conditions
+                .and_then(|conditions| conditions.iter().find(|c| c.type_ == gate.condition_type))
+                .map_or("Unknown".to_string(), |c| c.status.clone());
+
+            (gate.condition_type.clone(), status)
+        })
+        .collect()
+}
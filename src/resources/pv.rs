@@ -0,0 +1,166 @@
+use std::{cmp::Ordering, sync::Arc};
+
+use chrono::{TimeDelta, Utc};
+use k8s_openapi::api::core::v1::PersistentVolume;
+use kube::ResourceExt;
+use ratatui::{
+    layout::Constraint,
+    widgets::{Cell, Row},
+};
+
+use super::{age::Age, columns, pvc::short_mode, Compare, Filter};
+use crate::widget::{
+    table::{self, Health},
+    yank::Yank,
+};
+
+#[allow(clippy::module_name_repetitions)]
+pub trait PvExt {
+    fn age(&self) -> TimeDelta;
+    fn phase(&self) -> String;
+    fn capacity(&self) -> String;
+    fn access_modes(&self) -> String;
+    fn reclaim_policy(&self) -> String;
+    fn storage_class(&self) -> String;
+    fn claim(&self) -> String;
+}
+
+impl PvExt for PersistentVolume {
+    fn age(&self) -> TimeDelta {
+        let Some(creation) = self.creation_timestamp() else {
+            return TimeDelta::zero();
+        };
+
+        Utc::now() - creation.0
+    }
+
+    fn phase(&self) -> String {
+        self.status
+            .as_ref()
+            .and_then(|s| s.phase.clone())
+            .unwrap_or("Unknown".to_string())
+    }
+
+    fn capacity(&self) -> String {
+        self.spec
+            .as_ref()
+            .and_then(|s| s.capacity.as_ref())
+            .and_then(|c| c.get("storage"))
+            .map(|q| q.0.clone())
+            .unwrap_or_default()
+    }
+
+    fn access_modes(&self) -> String {
+        self.spec
+            .as_ref()
+            .and_then(|s| s.access_modes.as_ref())
+            .map(|modes| {
+                modes
+                    .iter()
+                    .map(|m| short_mode(m))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .unwrap_or_default()
+    }
+
+    fn reclaim_policy(&self) -> String {
+        self.spec
+            .as_ref()
+            .and_then(|s| s.persistent_volume_reclaim_policy.clone())
+            .unwrap_or_default()
+    }
+
+    fn storage_class(&self) -> String {
+        self.spec
+            .as_ref()
+            .and_then(|s| s.storage_class_name.clone())
+            .unwrap_or_default()
+    }
+
+    fn claim(&self) -> String {
+        self.spec
+            .as_ref()
+            .and_then(|s| s.claim_ref.as_ref())
+            .map(|c| {
+                format!(
+                    "{}/{}",
+                    c.namespace.clone().unwrap_or_default(),
+                    c.name.clone().unwrap_or_default()
+                )
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl table::Row for Arc<PersistentVolume> {
+    fn yank(&self) -> Option<Yank> {
+        Some(Yank::from_obj(self.as_ref()))
+    }
+
+    fn id(&self) -> Option<String> {
+        self.uid()
+    }
+
+    fn header<'a>() -> Option<Row<'a>> {
+        Some(columns::header::<PersistentVolume>(vec![
+            Cell::from("Name"),
+            Cell::from("Capacity"),
+            Cell::from("Access"),
+            Cell::from("Reclaim"),
+            Cell::from("Status"),
+            Cell::from("Claim"),
+            Cell::from("Class"),
+            Cell::from("Age"),
+        ]))
+    }
+
+    fn constraints() -> Vec<Constraint> {
+        columns::constraints::<PersistentVolume>(vec![
+            Constraint::Min(20),
+            Constraint::Max(10),
+            Constraint::Max(10),
+            Constraint::Max(10),
+            Constraint::Max(10),
+            Constraint::Fill(1),
+            Constraint::Max(15),
+            Constraint::Max(10),
+        ])
+    }
+
+    fn row(&self, style: &table::RowStyle) -> Row {
+        let phase = self.phase();
+        let health = match phase.as_str() {
+            "Bound" => Health::Healthy,
+            "Available" | "Pending" => Health::Normal,
+            _ => Health::Unhealthy,
+        };
+
+        Row::new(columns::cells(
+            self.as_ref(),
+            vec![
+                self.name_any(),
+                self.capacity(),
+                self.access_modes(),
+                self.reclaim_policy(),
+                style.status(health, phase),
+                self.claim(),
+                self.storage_class(),
+                self.age().to_age(),
+            ],
+        ))
+        .style(style.get(health))
+    }
+}
+
+impl Filter for PersistentVolume {
+    fn matches(&self, filter: &str) -> bool {
+        self.name_any().contains(filter)
+    }
+}
+
+impl Compare for Arc<PersistentVolume> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.name_any().cmp(&other.name_any())
+    }
+}
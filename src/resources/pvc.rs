@@ -0,0 +1,220 @@
+use std::{cmp::Ordering, sync::Arc};
+
+use chrono::{TimeDelta, Utc};
+use k8s_openapi::api::core::v1::{PersistentVolumeClaim, Pod};
+use kube::ResourceExt;
+use ratatui::{
+    layout::Constraint,
+    widgets::{Cell, Row},
+};
+
+use super::{age::Age, store::Store, Compare, Filter};
+use crate::widget::{
+    table::{self, Health},
+    yank::Yank,
+};
+
+#[allow(clippy::module_name_repetitions)]
+pub trait PvcExt {
+    fn age(&self) -> TimeDelta;
+    fn phase(&self) -> String;
+    fn capacity(&self) -> String;
+    fn access_modes(&self) -> String;
+    fn storage_class(&self) -> String;
+    fn volume(&self) -> String;
+    fn consumers(&self, pods: &[Arc<Pod>]) -> Vec<String>;
+}
+
+impl PvcExt for PersistentVolumeClaim {
+    fn age(&self) -> TimeDelta {
+        let Some(creation) = self.creation_timestamp() else {
+            return TimeDelta::zero();
+        };
+
+        Utc::now() - creation.0
+    }
+
+    fn phase(&self) -> String {
+        self.status
+            .as_ref()
+            .and_then(|s| s.phase.clone())
+            .unwrap_or("Unknown".to_string())
+    }
+
+    fn capacity(&self) -> String {
+        self.status
+            .as_ref()
+            .and_then(|s| s.capacity.as_ref())
+            .and_then(|c| c.get("storage"))
+            .map(|q| q.0.clone())
+            .unwrap_or_default()
+    }
+
+    fn access_modes(&self) -> String {
+        self.status
+            .as_ref()
+            .and_then(|s| s.access_modes.as_ref())
+            .map(|modes| {
+                modes
+                    .iter()
+                    .map(|m| short_mode(m))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .unwrap_or_default()
+    }
+
+    fn storage_class(&self) -> String {
+        self.spec
+            .as_ref()
+            .and_then(|s| s.storage_class_name.clone())
+            .unwrap_or_default()
+    }
+
+    fn volume(&self) -> String {
+        self.spec
+            .as_ref()
+            .and_then(|s| s.volume_name.clone())
+            .unwrap_or_default()
+    }
+
+    fn consumers(&self, pods: &[Arc<Pod>]) -> Vec<String> {
+        let name = self.name_any();
+        let namespace = self.namespace();
+
+        pods.iter()
+            .filter(|pod| pod.namespace() == namespace)
+            .filter(|pod| {
+                pod.spec
+                    .iter()
+                    .flat_map(|s| s.volumes.iter().flatten())
+                    .any(|v| {
+                        v.persistent_volume_claim
+                            .as_ref()
+                            .is_some_and(|c| c.claim_name == name)
+                    })
+            })
+            .map(|pod| pod.name_any())
+            .collect()
+    }
+}
+
+pub fn short_mode(mode: &str) -> &str {
+    match mode {
+        "ReadWriteOnce" => "RWO",
+        "ReadOnlyMany" => "ROX",
+        "ReadWriteMany" => "RWX",
+        "ReadWriteOncePod" => "RWOP",
+        _ => mode,
+    }
+}
+
+/// A claim along with the pods that are currently using it.
+pub struct Claim {
+    pub pvc: Arc<PersistentVolumeClaim>,
+    pub pods: Vec<String>,
+}
+
+/// Joins claims with the pods that mount them.
+pub struct Claims {
+    pub claims: Arc<Store<PersistentVolumeClaim>>,
+    pub pods: Arc<Store<Pod>>,
+}
+
+impl table::Items for Claims {
+    type Item = Claim;
+
+    fn items(&self, filter: Option<String>) -> Vec<Self::Item> {
+        let pods = self.pods.items(None);
+
+        self.claims
+            .items(filter)
+            .into_iter()
+            .map(|pvc| Claim {
+                pods: pvc.consumers(&pods),
+                pvc,
+            })
+            .collect()
+    }
+}
+
+impl table::Row for Claim {
+    fn yank(&self) -> Option<Yank> {
+        Some(Yank::from_obj(self.pvc.as_ref()))
+    }
+
+    fn id(&self) -> Option<String> {
+        self.pvc.uid()
+    }
+
+    fn header<'a>() -> Option<Row<'a>> {
+        Some(Row::new(vec![
+            Cell::from("Namespace"),
+            Cell::from("Name"),
+            Cell::from("Status"),
+            Cell::from("Volume"),
+            Cell::from("Capacity"),
+            Cell::from("Access"),
+            Cell::from("Class"),
+            Cell::from("Used By"),
+            Cell::from("Age"),
+        ]))
+    }
+
+    fn constraints() -> Vec<Constraint> {
+        vec![
+            Constraint::Max(20),
+            Constraint::Min(10),
+            Constraint::Max(10),
+            Constraint::Max(20),
+            Constraint::Max(10),
+            Constraint::Max(10),
+            Constraint::Max(15),
+            Constraint::Fill(1),
+            Constraint::Max(10),
+        ]
+    }
+
+    fn row(&self, style: &table::RowStyle) -> Row {
+        let phase = self.pvc.phase();
+        let health = match phase.as_str() {
+            "Bound" => Health::Healthy,
+            "Pending" => Health::Normal,
+            _ => Health::Unhealthy,
+        };
+
+        Row::new(vec![
+            self.pvc.namespace().unwrap_or_default(),
+            self.pvc.name_any(),
+            style.status(health, phase),
+            self.pvc.volume(),
+            self.pvc.capacity(),
+            self.pvc.access_modes(),
+            self.pvc.storage_class(),
+            self.pods.join(", "),
+            self.pvc.age().to_age(),
+        ])
+        .style(style.get(health))
+    }
+}
+
+impl Filter for PersistentVolumeClaim {
+    fn matches(&self, filter: &str) -> bool {
+        self.name_any().contains(filter)
+    }
+}
+
+impl Compare for Arc<PersistentVolumeClaim> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let lhs = self
+            .namespace()
+            .unwrap_or_default()
+            .cmp(&other.namespace().unwrap_or_default());
+
+        if lhs != Ordering::Equal {
+            return lhs;
+        }
+
+        self.name_any().cmp(&other.name_any())
+    }
+}
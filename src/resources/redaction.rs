@@ -0,0 +1,87 @@
+use std::{borrow::Cow, sync::LazyLock};
+
+use regex::Regex;
+
+use crate::dashboard::context::Context;
+
+static MASK: &str = "********";
+
+// Patterns that can be referred to by name instead of spelling them out.
+static BUILTIN: LazyLock<Vec<(&str, Regex)>> = LazyLock::new(|| {
+    [
+        ("aws", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+        ("bearer", r"(?i)\bbearer\s+([A-Za-z0-9\-._~+/]+=*)"),
+        (
+            "password",
+            r#"(?i)\b(?:password|passwd|pwd|secret)["']?\s*[:=]\s*["']?([^\s"',;]+)"#,
+        ),
+    ]
+    .into_iter()
+    .map(|(name, pattern)| (name, Regex::new(pattern).expect("valid regex")))
+    .collect()
+});
+
+/// Masks anything in log lines that looks like a credential, so that it
+/// doesn't end up on a shared screen or in a recording. Patterns with a
+/// capture group only mask what the first group matched, for example the
+/// value in `password=hunter2`.
+#[derive(Clone, Debug, Default)]
+pub struct Redaction {
+    patterns: Vec<Regex>,
+}
+
+impl Redaction {
+    /// `aws`, `bearer` and `password` are built in, anything else is a regular
+    /// expression. Patterns that don't compile are skipped.
+    pub fn new(patterns: &[String]) -> Self {
+        let patterns = patterns
+            .iter()
+            .filter_map(|pattern| {
+                if let Some((_, re)) = BUILTIN.iter().find(|(name, _)| name == pattern) {
+                    return Some(re.clone());
+                }
+
+                Regex::new(pattern)
+                    .inspect_err(|err| tracing::warn!(pattern, ?err, "invalid redaction pattern"))
+                    .ok()
+            })
+            .collect();
+
+        Self { patterns }
+    }
+
+    /// `line` with everything matching the current dashboard's patterns
+    /// masked.
+    pub fn apply(line: &str) -> Cow<'_, str> {
+        Context::with(|context| context.redaction.redact(line))
+    }
+
+    fn redact<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        let mut line = Cow::Borrowed(line);
+
+        for re in &self.patterns {
+            if !re.is_match(&line) {
+                continue;
+            }
+
+            let haystack: &str = &line;
+            let replaced = re
+                .replace_all(haystack, |caps: &regex::Captures| {
+                    let (Some(all), Some(group)) = (caps.get(0), caps.get(1)) else {
+                        return MASK.to_string();
+                    };
+
+                    format!(
+                        "{}{MASK}{}",
+                        &haystack[all.start()..group.start()],
+                        &haystack[group.end()..all.end()]
+                    )
+                })
+                .into_owned();
+
+            line = Cow::Owned(replaced);
+        }
+
+        line
+    }
+}
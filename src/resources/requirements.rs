@@ -0,0 +1,290 @@
+use std::{collections::BTreeMap, iter::Sum, ops::Add};
+
+use k8s_openapi::{
+    api::core::v1::{Container, Pod, ResourceRequirements},
+    apimachinery::pkg::api::resource::Quantity,
+};
+
+static CPU: &str = "cpu";
+static MEMORY: &str = "memory";
+
+// Convert a quantity (`100m`, `1.5`, `128Mi`, `1e3`) into its base unit.
+fn parse(quantity: &Quantity) -> Option<f64> {
+    let raw = quantity.0.trim();
+    let idx = raw
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | '+')))
+        .unwrap_or(raw.len());
+
+    let (num, suffix) = raw.split_at(idx);
+    let num: f64 = num.parse().ok()?;
+
+    let multiplier = match suffix {
+        "" => 1.0,
+        "n" => 1e-9,
+        "u" => 1e-6,
+        "m" => 1e-3,
+        "k" => 1e3,
+        "M" => 1e6,
+        "G" => 1e9,
+        "T" => 1e12,
+        "P" => 1e15,
+        "E" => 1e18,
+        "Ki" => 1024f64,
+        "Mi" => 1024f64.powi(2),
+        "Gi" => 1024f64.powi(3),
+        "Ti" => 1024f64.powi(4),
+        "Pi" => 1024f64.powi(5),
+        "Ei" => 1024f64.powi(6),
+        exp if exp.starts_with(['e', 'E']) => 10f64.powi(exp[1..].parse().ok()?),
+        _ => return None,
+    };
+
+    Some(num * multiplier)
+}
+
+fn get(values: Option<&BTreeMap<String, Quantity>>, key: &str) -> Option<f64> {
+    values.and_then(|v| v.get(key)).and_then(parse)
+}
+
+#[allow(clippy::cast_possible_truncation)]
+pub fn cpu(cores: f64) -> String {
+    if cores < 1.0 {
+        return format!("{}m", (cores * 1000.0).round() as i64);
+    }
+
+    format!("{cores:.2}")
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+pub fn memory(bytes: f64) -> String {
+    let mut value = bytes;
+
+    for unit in ["", "Ki", "Mi", "Gi", "Ti"] {
+        if value < 1024.0 {
+            return format!("{value:.1}").trim_end_matches(".0").to_string() + unit;
+        }
+
+        value /= 1024.0;
+    }
+
+    format!("{value:.1}Pi")
+}
+
+/// Aggregated CPU (cores) and memory (bytes) requests and limits. A `None`
+/// limit is unbounded.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Requirements {
+    pub cpu_request: f64,
+    pub cpu_limit: Option<f64>,
+    pub memory_request: f64,
+    pub memory_limit: Option<f64>,
+}
+
+impl Requirements {
+    fn max(self, other: Self) -> Self {
+        Self {
+            cpu_request: self.cpu_request.max(other.cpu_request),
+            cpu_limit: self.cpu_limit.zip(other.cpu_limit).map(|(l, r)| l.max(r)),
+            memory_request: self.memory_request.max(other.memory_request),
+            memory_limit: self
+                .memory_limit
+                .zip(other.memory_limit)
+                .map(|(l, r)| l.max(r)),
+        }
+    }
+
+    pub fn cpu(&self) -> String {
+        format!(
+            "{}/{}",
+            cpu(self.cpu_request),
+            self.cpu_limit.map_or("-".to_string(), cpu)
+        )
+    }
+
+    pub fn memory(&self) -> String {
+        format!(
+            "{}/{}",
+            memory(self.memory_request),
+            self.memory_limit.map_or("-".to_string(), memory)
+        )
+    }
+}
+
+impl From<Option<&ResourceRequirements>> for Requirements {
+    fn from(resources: Option<&ResourceRequirements>) -> Self {
+        let requests = resources.and_then(|r| r.requests.as_ref());
+        let limits = resources.and_then(|r| r.limits.as_ref());
+
+        // Requests default to limits when they're not set.
+        let cpu_limit = get(limits, CPU);
+        let memory_limit = get(limits, MEMORY);
+
+        Self {
+            cpu_request: get(requests, CPU).or(cpu_limit).unwrap_or_default(),
+            cpu_limit,
+            memory_request: get(requests, MEMORY).or(memory_limit).unwrap_or_default(),
+            memory_limit,
+        }
+    }
+}
+
+impl From<&Container> for Requirements {
+    fn from(container: &Container) -> Self {
+        container.resources.as_ref().into()
+    }
+}
+
+impl From<&Pod> for Requirements {
+    // Init containers run one at a time before the rest, the effective value is
+    // whichever is higher.
+    fn from(pod: &Pod) -> Self {
+        let Some(spec) = pod.spec.as_ref() else {
+            return Self::default();
+        };
+
+        let containers: Self = spec.containers.iter().map(Self::from).sum();
+
+        spec.init_containers
+            .iter()
+            .flatten()
+            .map(Self::from)
+            .fold(containers, Self::max)
+    }
+}
+
+impl Add for Requirements {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            cpu_request: self.cpu_request + other.cpu_request,
+            cpu_limit: self.cpu_limit.zip(other.cpu_limit).map(|(l, r)| l + r),
+            memory_request: self.memory_request + other.memory_request,
+            memory_limit: self
+                .memory_limit
+                .zip(other.memory_limit)
+                .map(|(l, r)| l + r),
+        }
+    }
+}
+
+impl Sum for Requirements {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.reduce(Add::add).unwrap_or_default()
+    }
+}
+
+/// Extended resources, such as `nvidia.com/gpu`, by name. They're whole
+/// numbers and can't be overcommitted, so only requests matter.
+pub type Extended = BTreeMap<String, f64>;
+
+// Anything with a domain outside of `kubernetes.io` is an extended resource.
+fn is_extended(name: &str) -> bool {
+    name.split_once('/')
+        .is_some_and(|(domain, _)| !domain.ends_with("kubernetes.io"))
+}
+
+fn extended_values(values: Option<&BTreeMap<String, Quantity>>) -> Extended {
+    values
+        .into_iter()
+        .flatten()
+        .filter(|(name, _)| is_extended(name))
+        .filter_map(|(name, quantity)| Some((name.clone(), parse(quantity)?)))
+        .collect()
+}
+
+// Requests default to limits, the same as CPU and memory.
+fn extended_container(container: &Container) -> Extended {
+    let resources = container.resources.as_ref();
+
+    let mut values = extended_values(resources.and_then(|r| r.limits.as_ref()));
+    values.extend(extended_values(resources.and_then(|r| r.requests.as_ref())));
+
+    values
+}
+
+/// Extended resources requested by a pod. Like [`Requirements`], init
+/// containers count for whichever is higher.
+pub fn extended(pod: &Pod) -> Extended {
+    let Some(spec) = pod.spec.as_ref() else {
+        return Extended::new();
+    };
+
+    let mut total = Extended::new();
+
+    for (name, value) in spec.containers.iter().flat_map(extended_container) {
+        *total.entry(name).or_default() += value;
+    }
+
+    for (name, value) in spec
+        .init_containers
+        .iter()
+        .flatten()
+        .flat_map(extended_container)
+    {
+        let current = total.entry(name).or_default();
+        *current = current.max(value);
+    }
+
+    total
+}
+
+/// Extended resources a node can allocate, see [`Extended`].
+pub fn allocatable_extended(values: Option<&BTreeMap<String, Quantity>>) -> Extended {
+    extended_values(values)
+}
+
+/// Allocatable CPU (cores) and memory (bytes) for a node.
+pub fn allocatable(values: Option<&BTreeMap<String, Quantity>>) -> (f64, f64) {
+    (
+        get(values, CPU).unwrap_or_default(),
+        get(values, MEMORY).unwrap_or_default(),
+    )
+}
+
+pub fn qos(pod: &Pod) -> String {
+    if let Some(class) = pod.status.as_ref().and_then(|s| s.qos_class.as_ref()) {
+        return class.clone();
+    }
+
+    let Some(spec) = pod.spec.as_ref() else {
+        return "BestEffort".to_string();
+    };
+
+    let all: Vec<_> = spec
+        .containers
+        .iter()
+        .chain(spec.init_containers.iter().flatten())
+        .map(|c| c.resources.as_ref())
+        .collect();
+
+    let is_set = |r: &Option<&ResourceRequirements>| {
+        r.is_some_and(|r| {
+            r.requests.as_ref().is_some_and(|v| !v.is_empty())
+                || r.limits.as_ref().is_some_and(|v| !v.is_empty())
+        })
+    };
+
+    if !all.iter().any(is_set) {
+        return "BestEffort".to_string();
+    }
+
+    let guaranteed = all.iter().all(|r| {
+        let limits = r.and_then(|r| r.limits.as_ref());
+        let requests = r.and_then(|r| r.requests.as_ref());
+
+        [CPU, MEMORY].iter().all(|key| {
+            get(limits, key).is_some_and(|limit| {
+                get(requests, key).map_or(true, |req| (req - limit).abs() < f64::EPSILON)
+            })
+        })
+    });
+
+    if guaranteed {
+        "Guaranteed".to_string()
+    } else {
+        "Burstable".to_string()
+    }
+}
@@ -0,0 +1,169 @@
+use std::{cmp::Ordering, sync::Arc};
+
+use chrono::{TimeDelta, Utc};
+use eyre::{eyre, Result};
+use k8s_openapi::api::{
+    apps::v1::{Deployment, ReplicaSet},
+    core::v1::{Event, PodTemplateSpec},
+};
+use kube::{
+    api::{Patch, PatchParams},
+    Api, ResourceExt,
+};
+use serde_json::json;
+
+use super::{store::Store, Compare, Filter};
+use crate::widget::events::last_seen;
+
+/// Set by the deployment controller on deployments and the replica sets they
+/// own, every change to the pod template is a new revision.
+pub static REVISION: &str = "deployment.kubernetes.io/revision";
+/// Why a revision was made, when whoever made it said so.
+pub static CHANGE_CAUSE: &str = "kubernetes.io/change-cause";
+// Added to the template of every replica set so that their pods don't overlap,
+// it isn't part of the deployment's template.
+static TEMPLATE_HASH: &str = "pod-template-hash";
+
+/// A replica set a deployment has made, which is what
+/// `kubectl rollout history` lists.
+#[derive(Clone, Debug)]
+pub struct Revision {
+    pub number: i64,
+    pub replica_set: Arc<ReplicaSet>,
+}
+
+impl Revision {
+    pub fn cause(&self) -> Option<String> {
+        self.replica_set.annotations().get(CHANGE_CAUSE).cloned()
+    }
+
+    pub fn replicas(&self) -> i32 {
+        self.replica_set
+            .status
+            .as_ref()
+            .map(|status| status.replicas)
+            .unwrap_or_default()
+    }
+
+    pub fn age(&self) -> TimeDelta {
+        let Some(creation) = self.replica_set.creation_timestamp() else {
+            return TimeDelta::zero();
+        };
+
+        Utc::now() - creation.0
+    }
+
+    pub fn images(&self) -> Vec<String> {
+        self.template()
+            .and_then(|template| template.spec)
+            .map(|spec| {
+                spec.containers
+                    .into_iter()
+                    .filter_map(|container| container.image)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The pod template as the deployment had it, without the label the
+    /// controller adds.
+    pub fn template(&self) -> Option<PodTemplateSpec> {
+        let mut template = self.replica_set.spec.as_ref()?.template.clone()?;
+
+        if let Some(labels) = template.metadata.as_mut().and_then(|m| m.labels.as_mut()) {
+            labels.remove(TEMPLATE_HASH);
+        }
+
+        Some(template)
+    }
+}
+
+fn number<K: ResourceExt>(obj: &K) -> Option<i64> {
+    obj.annotations().get(REVISION)?.parse().ok()
+}
+
+/// The revision a deployment is currently on.
+pub fn current(deployment: &Deployment) -> Option<i64> {
+    number(deployment)
+}
+
+/// Every revision of `deployment` that is still around, newest first. How many
+/// are kept is up to `spec.revisionHistoryLimit`.
+pub fn revisions(deployment: &Deployment, replicasets: &Store<ReplicaSet>) -> Vec<Revision> {
+    let Some(uid) = deployment.uid() else {
+        return Vec::new();
+    };
+
+    let mut revisions: Vec<_> = replicasets
+        .owned(None)
+        .remove(&uid)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|replica_set| {
+            Some(Revision {
+                number: number(replica_set.as_ref())?,
+                replica_set,
+            })
+        })
+        .collect();
+
+    revisions.sort_by_key(|revision| std::cmp::Reverse(revision.number));
+
+    revisions
+}
+
+/// Roll `deployment` back to `revision`, the same way `kubectl rollout undo`
+/// does it: the template is copied from the revision and the controller takes
+/// it from there, making it the newest revision. With `dry_run`, the API
+/// server only checks what would happen.
+pub async fn rollback(
+    client: &Api<Deployment>,
+    deployment: &Deployment,
+    revision: &Revision,
+    dry_run: bool,
+) -> Result<Deployment> {
+    let template = revision
+        .template()
+        .ok_or_else(|| eyre!("revision {} has no pod template", revision.number))?;
+
+    let mut operations =
+        vec![json!({"op": "replace", "path": "/spec/template", "value": template})];
+
+    // Adding to a map that doesn't exist yet fails, so the whole map needs to be
+    // created instead.
+    if let Some(cause) = revision.cause() {
+        operations.push(if deployment.annotations().is_empty() {
+            json!({"op": "add", "path": "/metadata/annotations", "value": {CHANGE_CAUSE: cause}})
+        } else {
+            json!({
+                "op": "add",
+                "path": "/metadata/annotations/kubernetes.io~1change-cause",
+                "value": cause,
+            })
+        });
+    }
+
+    let patch: json_patch::Patch = serde_json::from_value(serde_json::Value::Array(operations))?;
+    let params = PatchParams {
+        dry_run,
+        ..PatchParams::default()
+    };
+
+    Ok(client
+        .patch(&deployment.name_any(), &params, &Patch::Json::<()>(patch))
+        .await?)
+}
+
+impl Filter for Event {
+    fn matches(&self, filter: &str) -> bool {
+        self.reason.as_ref().is_some_and(|r| r.contains(filter))
+            || self.message.as_ref().is_some_and(|m| m.contains(filter))
+    }
+}
+
+// Newest first, the same as the events tab.
+impl Compare for Arc<Event> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        last_seen(other).cmp(&last_seen(self))
+    }
+}
@@ -0,0 +1,127 @@
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    sync::{Arc, LazyLock},
+};
+
+use kube::{CustomResource, ResourceExt};
+use ratatui::{
+    layout::Constraint,
+    widgets::{Cell, Row},
+};
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{Compare, Filter};
+use crate::widget::{table, yank::Yank};
+
+static VARIABLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$\{([A-Za-z0-9_-]+)\}").expect("valid regex"));
+
+/// A list with a filter that is shared between users. The filter can contain
+/// variables, such as `${team}`, so that one definition works for everyone.
+#[allow(clippy::module_name_repetitions)]
+#[derive(CustomResource, Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[kube(
+    group = "kty.dev",
+    version = "v1alpha1",
+    kind = "SavedView",
+    namespaced
+)]
+pub struct SavedViewSpec {
+    /// The list to open: pods, nodes, pvcs, pvs or deployments.
+    pub resource: String,
+    /// Filter for the list. `${user}` and `${group}` are filled in from the
+    /// user's identity, any other variable is asked for when the view is
+    /// opened.
+    #[serde(default)]
+    pub filter: String,
+}
+
+/// Names of the variables used in `filter`, in order and without duplicates.
+pub fn variables(filter: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+
+    for capture in VARIABLE.captures_iter(filter) {
+        let name = capture[1].to_string();
+
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    names
+}
+
+/// Replace every variable in `filter` with its value. Variables without a value
+/// are left as is.
+pub fn expand(filter: &str, values: &BTreeMap<String, String>) -> String {
+    VARIABLE
+        .replace_all(filter, |capture: &Captures| {
+            values
+                .get(&capture[1])
+                .cloned()
+                .unwrap_or_else(|| capture[0].to_string())
+        })
+        .to_string()
+}
+
+impl table::Row for Arc<SavedView> {
+    fn yank(&self) -> Option<Yank> {
+        Some(Yank::from_obj(self.as_ref()))
+    }
+
+    fn id(&self) -> Option<String> {
+        self.uid()
+    }
+
+    fn header<'a>() -> Option<Row<'a>> {
+        Some(Row::new(vec![
+            Cell::from("Namespace"),
+            Cell::from("Name"),
+            Cell::from("Resource"),
+            Cell::from("Filter"),
+        ]))
+    }
+
+    fn constraints() -> Vec<Constraint> {
+        vec![
+            Constraint::Max(20),
+            Constraint::Min(20),
+            Constraint::Max(15),
+            Constraint::Fill(1),
+        ]
+    }
+
+    fn row(&self, style: &table::RowStyle) -> Row {
+        Row::new(vec![
+            self.namespace().unwrap_or_default(),
+            self.name_any(),
+            self.spec.resource.clone(),
+            self.spec.filter.clone(),
+        ])
+        .style(style.normal)
+    }
+}
+
+impl Filter for SavedView {
+    fn matches(&self, filter: &str) -> bool {
+        self.name_any().contains(filter)
+    }
+}
+
+impl Compare for Arc<SavedView> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let lhs = self
+            .namespace()
+            .unwrap_or_default()
+            .cmp(&other.namespace().unwrap_or_default());
+
+        if lhs != Ordering::Equal {
+            return lhs;
+        }
+
+        self.name_any().cmp(&other.name_any())
+    }
+}
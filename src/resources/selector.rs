@@ -0,0 +1,161 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    sync::Arc,
+};
+
+use kube::ResourceExt;
+
+use super::Filter;
+
+/// Field that matches against the namespace instead of a label.
+pub static NAMESPACE: &str = "namespace";
+
+/// Values that have been seen for each field, used to offer choices when
+/// building a filter.
+pub type Fields = BTreeMap<String, BTreeSet<String>>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operator {
+    Equal,
+    NotEqual,
+}
+
+impl Operator {
+    pub fn all() -> &'static [Operator] {
+        &[Self::Equal, Self::NotEqual]
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Equal => "=",
+            Self::NotEqual => "!=",
+        }
+    }
+
+    fn matches(self, actual: Option<&str>, value: &str) -> bool {
+        match self {
+            Self::Equal => actual == Some(value),
+            Self::NotEqual => actual != Some(value),
+        }
+    }
+}
+
+/// Part of a filter, see [`Selector`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Term {
+    Text(String),
+    Field {
+        key: String,
+        op: Operator,
+        value: String,
+    },
+}
+
+impl Term {
+    fn parse(term: &str) -> Self {
+        let parts = term
+            .split_once("!=")
+            .map(|(key, value)| (key, Operator::NotEqual, value))
+            .or_else(|| {
+                term.split_once('=')
+                    .map(|(key, value)| (key, Operator::Equal, value))
+            });
+
+        match parts {
+            Some((key, op, value)) if !key.is_empty() => Self::Field {
+                key: key.to_string(),
+                op,
+                value: value.to_string(),
+            },
+            _ => Self::Text(term.to_string()),
+        }
+    }
+
+    fn matches<K>(&self, obj: &K) -> bool
+    where
+        K: Filter + ResourceExt,
+    {
+        match self {
+            Self::Text(text) => obj.matches(text),
+            Self::Field { key, op, value } if key == NAMESPACE => {
+                op.matches(obj.namespace().as_deref(), value)
+            }
+            Self::Field { key, op, value } => {
+                op.matches(obj.labels().get(key).map(String::as_str), value)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text(text) => write!(f, "{text}"),
+            Self::Field { key, op, value } => write!(f, "{key}{}{value}", op.symbol()),
+        }
+    }
+}
+
+/// A filter for lists. Terms are separated by whitespace and all of them must
+/// match. `key=value` and `key!=value` compare against labels (or the
+/// namespace for `namespace`), anything else is matched against the name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Selector(Vec<Term>);
+
+impl Selector {
+    pub fn matches<K>(&self, obj: &K) -> bool
+    where
+        K: Filter + ResourceExt,
+    {
+        self.0.iter().all(|term| term.matches(obj))
+    }
+
+    /// Like [`Selector::matches`], for things that aren't resources such as
+    /// alerts. Plain terms are matched against `text` and `field` looks up the
+    /// value of a key, `namespace` included.
+    pub fn matches_fields<'a>(&self, text: &str, field: impl Fn(&str) -> Option<&'a str>) -> bool {
+        self.0.iter().all(|term| match term {
+            Term::Text(term) => text.contains(term.as_str()),
+            Term::Field { key, op, value } => op.matches(field(key), value),
+        })
+    }
+
+    /// The namespace the filter narrows down to, if it is a single one.
+    pub fn namespace(&self) -> Option<&str> {
+        self.0.iter().find_map(|term| match term {
+            Term::Field {
+                key,
+                op: Operator::Equal,
+                value,
+            } if key == NAMESPACE => Some(value.as_str()),
+            _ => None,
+        })
+    }
+}
+
+impl From<&str> for Selector {
+    fn from(filter: &str) -> Self {
+        Self(filter.split_whitespace().map(Term::parse).collect())
+    }
+}
+
+/// Namespaces and labels across `items`, with every value they have.
+pub fn observed<K>(items: &[Arc<K>]) -> Fields
+where
+    K: ResourceExt,
+{
+    let mut fields = Fields::new();
+
+    for item in items {
+        if let Some(ns) = item.namespace() {
+            fields.entry(NAMESPACE.to_string()).or_default().insert(ns);
+        }
+
+        for (key, value) in item.labels() {
+            fields.entry(key.clone()).or_default().insert(value.clone());
+        }
+    }
+
+    fields
+}
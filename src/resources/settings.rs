@@ -0,0 +1,128 @@
+use kube::{CustomResource, ResourceExt};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    alerts::AlertSource, columns::Column, command::Command, idle::IdleSettings,
+    image::ImageSettings, node::shell::NodeShellSettings, policy::Verb,
+};
+
+/// Defaults for every session, set by admins. There can be more than one,
+/// they're merged in order of their names with later ones taking precedence.
+/// Anything a user asks for, such as a link, overrides these.
+#[allow(clippy::module_name_repetitions)]
+#[derive(CustomResource, Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[kube(group = "kty.dev", version = "v1alpha1", kind = "KtySettings")]
+#[serde(rename_all = "camelCase")]
+pub struct KtySettingsSpec {
+    /// Shown to users when their session starts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub banner: Option<String>,
+    /// Tabs to show in the dashboard, in order. One of `overview`, `alerts`,
+    /// `pods`, `nodes`, `pvcs`, `pvs`, `deployments`, `workloads`, `gateways`,
+    /// `netpols`, `pdbs`, `images`, `views`, `housekeeping` or `idle`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tabs: Option<Vec<String>>,
+    /// The tab selected when a session starts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab: Option<String>,
+    /// Features sessions are allowed to use, this can only narrow down what the
+    /// server has been started with. See `kty serve --features`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub features: Option<Vec<String>>,
+    /// Keys bound while onboarding can't be used until an admin approves them,
+    /// users verify with the `OpenID` provider every time until then.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_approval: Option<bool>,
+    /// Values masked in logs before they're shown, so that they don't end up on
+    /// shared screens or in recordings. `aws`, `bearer` and `password` are
+    /// built in, anything else is a regular expression. Only what the first
+    /// capture group matched is masked when there is one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redact_logs: Option<Vec<String>>,
+    /// Where the `alerts` tab gets firing alerts from, see [`AlertSource`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alerts: Option<AlertSource>,
+    /// Where the `images` tab gets details about images from, see
+    /// [`ImageSettings`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub images: Option<ImageSettings>,
+    /// Programs users can run from a pod's `Commands` tab, such as `stern`.
+    /// Only those allowed with `kty serve --allow-command` can be started.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commands: Option<Vec<Command>>,
+    /// Language of the dashboard, such as `es`, for sessions that don't ask for
+    /// one with `LANG` or `KTY_LANG`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// Columns added to tables, such as a pod's team label. See [`Column`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Vec<Column>>,
+    /// Turned off for every session, in addition to `kty serve --disable`. Any
+    /// of `logs`, `exec`, `create`, `patch` or `delete`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disable: Option<Vec<String>>,
+    /// What the `idle` tab considers unused, see [`IdleSettings`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle: Option<IdleSettings>,
+    /// Where node shells run and for how long, see [`NodeShellSettings`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_shell: Option<NodeShellSettings>,
+}
+
+impl KtySettingsSpec {
+    fn merge(self, other: &Self) -> Self {
+        Self {
+            banner: other.banner.clone().or(self.banner),
+            tabs: other.tabs.clone().or(self.tabs),
+            tab: other.tab.clone().or(self.tab),
+            features: other.features.clone().or(self.features),
+            key_approval: other.key_approval.or(self.key_approval),
+            redact_logs: other.redact_logs.clone().or(self.redact_logs),
+            alerts: other.alerts.clone().or(self.alerts),
+            images: other.images.clone().or(self.images),
+            commands: other.commands.clone().or(self.commands),
+            locale: other.locale.clone().or(self.locale),
+            columns: other.columns.clone().or(self.columns),
+            disable: other.disable.clone().or(self.disable),
+            idle: other.idle.clone().or(self.idle),
+            node_shell: other.node_shell.clone().or(self.node_shell),
+        }
+    }
+
+    /// Combine every settings object into one, see [`KtySettingsSpec`] for the
+    /// order.
+    pub fn merged<'a>(settings: impl IntoIterator<Item = &'a KtySettings>) -> Self {
+        let mut settings: Vec<_> = settings.into_iter().collect();
+        settings.sort_by_key(|s| s.name_any());
+
+        settings
+            .into_iter()
+            .fold(Self::default(), |acc, s| acc.merge(&s.spec))
+    }
+
+    /// What [`KtySettingsSpec::disable`] turns off, names that aren't a verb
+    /// are skipped.
+    pub fn disabled(&self) -> Vec<Verb> {
+        self.disable
+            .iter()
+            .flatten()
+            .filter_map(|name| {
+                let verb = Verb::parse(name);
+
+                if verb.is_none() {
+                    tracing::warn!(%name, "unknown verb in settings");
+                }
+
+                verb
+            })
+            .collect()
+    }
+
+    /// Whether `feature` is allowed, everything is when no list has been set.
+    pub fn allows(&self, feature: &str) -> bool {
+        self.features
+            .as_ref()
+            .map_or(true, |features| features.iter().any(|f| f == feature))
+    }
+}
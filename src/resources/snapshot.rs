@@ -0,0 +1,154 @@
+use std::{collections::BTreeMap, path::Path, sync::LazyLock};
+
+use eyre::{eyre, Result};
+use k8s_openapi::api::core::v1::Secret;
+use kube::{
+    api::{ObjectMeta, PostParams},
+    Api, ResourceExt,
+};
+use ratatui::{buffer::Buffer, text::Span};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::policy::Verb;
+
+static CREATED_BY: &str = "app.kubernetes.io/created-by";
+/// Annotation with the user that took the snapshot.
+pub static USER: &str = "kty.dev/user";
+/// Key in the `Secret` that the snapshot is stored under.
+pub static KEY: &str = "snapshot.yaml";
+static REDACTED: &str = "[redacted]";
+
+// Values following something that looks like a credential, eg. `password: foo`.
+static ASSIGNMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(password|passwd|secret|token|api[_-]?key|auth\w*)(\s*[:=]\s*)\S+")
+        .expect("valid regex")
+});
+// Long runs without spaces are most likely tokens, certificates or base64
+// encoded data. Resource names are limited to 63 characters per segment but
+// rarely get anywhere near this.
+static OPAQUE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[A-Za-z0-9+/=_\-\.]{40,}").expect("valid regex"));
+
+/// What was on screen along with enough state to make sense of it, for bug
+/// reports. Anything that looks like a credential is redacted before it is
+/// saved, typed text is never kept.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: String,
+    pub taken: String,
+    pub width: u16,
+    pub height: u16,
+    pub title: String,
+    /// The widget tree, see [`crate::widget::Widget::tree`].
+    pub widgets: Vec<String>,
+    /// Most recent events, oldest first.
+    pub events: Vec<String>,
+    pub frame: Vec<String>,
+}
+
+impl Snapshot {
+    pub fn new(buffer: &Buffer, title: &str, widgets: Vec<String>, events: Vec<String>) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            taken: chrono::Utc::now().to_rfc3339(),
+            width: buffer.area.width,
+            height: buffer.area.height,
+            title: redact(title),
+            widgets,
+            events,
+            frame: lines(buffer).iter().map(|line| redact(line)).collect(),
+        }
+    }
+
+    pub fn secret(&self, user: Option<&str>) -> Result<Secret> {
+        Ok(Secret {
+            metadata: ObjectMeta {
+                generate_name: Some("kty-snapshot-".to_string()),
+                labels: Some([(CREATED_BY.to_string(), "kty".to_string())].into()),
+                annotations: user.map(|user| [(USER.to_string(), user.to_string())].into()),
+                ..Default::default()
+            },
+            string_data: Some(BTreeMap::from([(
+                KEY.to_string(),
+                serde_yaml::to_string(self)?,
+            )])),
+            ..Default::default()
+        })
+    }
+
+    /// Write the snapshot to a file in `dir`, or to a `Secret` in the client's
+    /// default namespace when there is no directory. Returns where it ended up.
+    pub async fn save(
+        &self,
+        client: kube::Client,
+        dir: Option<&Path>,
+        user: Option<&str>,
+    ) -> Result<String> {
+        if let Some(dir) = dir {
+            let path = dir.join(format!(
+                "kty-snapshot-{}.yaml",
+                chrono::Utc::now().format("%Y%m%dT%H%M%S")
+            ));
+
+            tokio::fs::write(&path, serde_yaml::to_string(self)?).await?;
+
+            return Ok(path.display().to_string());
+        }
+
+        if !Verb::Create.allowed() {
+            return Err(eyre!(
+                "snapshots are saved as a secret, which the policy doesn't allow creating"
+            ));
+        }
+
+        let secret = Api::<Secret>::default_namespaced(client)
+            .create(&PostParams::default(), &self.secret(user)?)
+            .await?;
+
+        Ok(format!(
+            "secret {}/{}",
+            secret.namespace().unwrap_or_default(),
+            secret.name_any()
+        ))
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        Ok(serde_yaml::from_str(&std::fs::read_to_string(path)?)?)
+    }
+}
+
+// Wide characters take up more than one cell, the cells they cover are skipped
+// so that lines keep their width.
+fn lines(buffer: &Buffer) -> Vec<String> {
+    let area = buffer.area;
+
+    (area.top()..area.bottom())
+        .map(|y| {
+            let mut line = String::new();
+            let mut skip = 0;
+
+            for x in area.left()..area.right() {
+                if skip > 0 {
+                    skip -= 1;
+
+                    continue;
+                }
+
+                let symbol = buffer[(x, y)].symbol();
+                skip = Span::raw(symbol).width().saturating_sub(1);
+
+                line.push_str(symbol);
+            }
+
+            line.trim_end().to_string()
+        })
+        .collect()
+}
+
+/// Mask anything in `text` that looks like a credential.
+pub fn redact(text: &str) -> String {
+    let text = ASSIGNMENT.replace_all(text, format!("$1$2{REDACTED}"));
+
+    OPAQUE.replace_all(&text, REDACTED).into_owned()
+}
@@ -6,6 +6,7 @@ use k8s_openapi::apimachinery::pkg::apis::meta::v1;
 #[allow(clippy::module_name_repetitions)]
 pub trait StatusExt {
     fn is_success(&self) -> bool;
+    fn exit_code(&self) -> Option<i32>;
     fn into_report(self) -> Report;
 }
 
@@ -14,6 +15,22 @@ impl StatusExt for v1::Status {
         self.status == Some("Success".to_string())
     }
 
+    // Exec reports a non-zero exit as a failure, with the code as the message
+    // of an `ExitCode` cause.
+    fn exit_code(&self) -> Option<i32> {
+        if self.is_success() {
+            return Some(0);
+        }
+
+        self.details
+            .as_ref()?
+            .causes
+            .as_ref()?
+            .iter()
+            .find(|cause| cause.reason.as_deref() == Some("ExitCode"))
+            .and_then(|cause| cause.message.as_ref()?.parse().ok())
+    }
+
     // Because this is a golang error that's being returned, there's really no good
     // way to convert this into something that is moderately usable. The rest of the
     // `Status` struct is empty of anything useful. The decision is to be naive here
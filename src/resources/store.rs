@@ -1,16 +1,59 @@
-use std::{future::ready, iter::Iterator, sync::Arc};
+use std::{
+    any::TypeId,
+    borrow::Borrow,
+    collections::{HashMap, VecDeque},
+    future::ready,
+    iter::Iterator,
+    sync::{Arc, Mutex, Weak},
+};
 
 use eyre::{eyre, Result};
 use futures::StreamExt;
 use kube::{
-    runtime::{self, reflector, watcher::Config, WatchStreamExt},
+    runtime::{
+        self, reflector,
+        reflector::ObjectRef,
+        watcher::{self, Config},
+        WatchStreamExt,
+    },
     Api, ResourceExt,
 };
-use serde::de::DeserializeOwned;
-use tokio::{sync::oneshot, task::JoinSet};
+use lazy_static::lazy_static;
+use prometheus::{opts, register_int_counter_vec, IntCounterVec};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::{
+    sync::{
+        broadcast::{self, error::TryRecvError},
+        oneshot,
+    },
+    task::JoinSet,
+};
+
+use super::{
+    policy::Policy,
+    selector::Selector,
+    timeline::{self, Update},
+    Compare, Filter,
+};
+use crate::{dashboard::context::Context, widget::table};
 
-use super::{Compare, Filter};
-use crate::widget::table;
+// The kind and field selector of a shared store, see [`Store::scoped`].
+pub(crate) type Key = (TypeId, Option<String>);
+
+// Changes that can be waiting for a subscriber before it falls behind and has
+// to start over from the store's state.
+static CHANGES: usize = 1024;
+
+lazy_static! {
+    static ref STORES: IntCounterVec = register_int_counter_vec!(
+        opts!(
+            "stores_total",
+            "Number of stores requested by widgets, shared when one was already watching",
+        ),
+        &["kind", "shared"],
+    )
+    .unwrap();
+}
 
 async fn is_ready<K>(reader: reflector::Store<K>, tx: oneshot::Sender<()>) -> Result<()>
 where
@@ -29,6 +72,140 @@ where
     Ok(())
 }
 
+/// Something that happened to a store, see [`Store::subscribe`]. Objects are
+/// referred to, the store already has their latest version by the time a
+/// change is received.
+#[derive(Clone, Debug)]
+pub enum Change<K>
+where
+    K: kube::Resource<DynamicType = ()>,
+{
+    Applied(ObjectRef<K>),
+    Deleted(ObjectRef<K>),
+    /// The watch started over, anything could have changed. This is also sent
+    /// once the store is first ready.
+    Reset,
+}
+
+// Recent updates to each object, see [`Store::updates`].
+type Updates<K> = Arc<Mutex<HashMap<ObjectRef<K>, VecDeque<Update>>>>;
+
+// Runs before the store is updated, so that what changed can be worked out
+// from the version it still has.
+fn record<K>(
+    reader: &reflector::Store<K>,
+    updates: &Updates<K>,
+    event: &Result<watcher::Event<K>, watcher::Error>,
+) where
+    K: kube::Resource<DynamicType = ()> + Clone + Serialize,
+{
+    match event {
+        Ok(watcher::Event::Apply(obj)) => {
+            let key = ObjectRef::from_obj(obj);
+
+            let Some(update) = reader
+                .get(&key)
+                .and_then(|before| Update::new(before.as_ref(), obj))
+            else {
+                return;
+            };
+
+            let mut updates = updates.lock().unwrap();
+            let list = updates.entry(key).or_default();
+
+            if list.len() == timeline::UPDATES {
+                list.pop_front();
+            }
+
+            list.push_back(update);
+        }
+        Ok(watcher::Event::Delete(obj)) => {
+            updates.lock().unwrap().remove(&ObjectRef::from_obj(obj));
+        }
+        _ => {}
+    }
+}
+
+// Every item in the store, kept sorted as changes come in so that drawing a
+// table doesn't have to sort the whole thing again.
+struct Sorted<K>
+where
+    K: kube::Resource<DynamicType = ()>,
+{
+    items: Vec<Arc<K>>,
+    changes: broadcast::Receiver<Change<K>>,
+    stale: bool,
+    version: u64,
+}
+
+impl<K> Sorted<K>
+where
+    K: kube::Resource<DynamicType = ()>
+        + Clone
+        + std::fmt::Debug
+        + Send
+        + Sync
+        + DeserializeOwned
+        + 'static,
+    Arc<K>: Compare,
+{
+    // Applies whatever changes have come in since the last time and returns the
+    // version, which goes up whenever the items do change.
+    fn sync(&mut self, reader: &reflector::Store<K>) -> u64 {
+        loop {
+            match self.changes.try_recv() {
+                Ok(Change::Applied(obj)) => {
+                    self.remove(&obj);
+
+                    if let Some(obj) = reader.get(&obj) {
+                        let idx = self
+                            .items
+                            .binary_search_by(|item| Compare::cmp(item, &obj))
+                            .unwrap_or_else(|idx| idx);
+
+                        self.items.insert(idx, obj);
+                    }
+
+                    self.version += 1;
+                }
+                Ok(Change::Deleted(obj)) => {
+                    self.remove(&obj);
+                    self.version += 1;
+                }
+                Ok(Change::Reset) | Err(TryRecvError::Lagged(_)) => self.stale = true,
+                Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+            }
+        }
+
+        if self.stale {
+            self.items = reader.state();
+            self.items.sort_by(Compare::cmp);
+            self.stale = false;
+            self.version += 1;
+        }
+
+        self.version
+    }
+
+    fn remove(&mut self, obj: &ObjectRef<K>) {
+        if let Some(idx) = self
+            .items
+            .iter()
+            .position(|item| ObjectRef::from_obj(item.as_ref()) == *obj)
+        {
+            self.items.remove(idx);
+        }
+    }
+}
+
+/// The uid of whatever is managing `obj`, eg. the replica set of a pod.
+pub fn controller<K: ResourceExt>(obj: &K) -> Option<String> {
+    obj.owner_references()
+        .iter()
+        .find(|owner| owner.controller == Some(true))
+        .map(|owner| owner.uid.clone())
+}
+
 pub struct Store<K>
 where
     K: kube::Resource<DynamicType = ()>
@@ -41,6 +218,9 @@ where
 {
     tasks: JoinSet<Result<()>>,
     reader: reflector::Store<K>,
+    changes: broadcast::Sender<Change<K>>,
+    sorted: Mutex<Sorted<K>>,
+    updates: Updates<K>,
 }
 
 impl<K> Store<K>
@@ -52,55 +232,232 @@ where
         + Send
         + Sync
         + DeserializeOwned
+        + Serialize
         + 'static,
     Arc<K>: Compare,
 {
+    /// A store of every `K` in the cluster. Widgets on the same dashboard share
+    /// one watch per kind, so opening pods in several places only lists and
+    /// watches them once. The receiver fires once the store is ready, straight
+    /// away when it already was.
+    pub fn new(client: kube::Client) -> (Arc<Self>, oneshot::Receiver<()>) {
+        Self::open(client, None)
+    }
+
+    /// Like [`Store::new`], but only with what matches the field selector
+    /// `fields`, eg. `spec.nodeName=node-1`. The API server does the filtering,
+    /// so a tab showing a slice of the cluster doesn't have to hold all of it.
+    /// Stores are shared per selector.
+    pub fn scoped(client: kube::Client, fields: &str) -> (Arc<Self>, oneshot::Receiver<()>) {
+        Self::open(client, Some(fields.to_string()))
+    }
+
+    fn open(client: kube::Client, fields: Option<String>) -> (Arc<Self>, oneshot::Receiver<()>) {
+        let kind = K::kind(&());
+        let key = (TypeId::of::<K>(), fields);
+
+        if let Some(store) = lookup(&key) {
+            STORES.with_label_values(&[kind.borrow(), "true"]).inc();
+
+            let (tx, rx) = oneshot::channel();
+            tokio::spawn(is_ready(store.reader.clone(), tx));
+
+            return (store, rx);
+        }
+
+        STORES.with_label_values(&[kind.borrow(), "false"]).inc();
+
+        let config = match key.1.as_deref() {
+            Some(fields) => Config::default().fields(fields),
+            None => Config::default(),
+        };
+
+        let (store, rx) = Self::watch(client, config);
+
+        Context::with(|context| {
+            context
+                .stores
+                .lock()
+                .unwrap()
+                .insert(key, Box::new(Arc::downgrade(&store)));
+        });
+
+        (store, rx)
+    }
+
     // TODO: need to have a way to filter stuff out (with some defaults) to keep
     // from memory going nuts.
-    pub fn new(client: kube::Client) -> (Arc<Self>, oneshot::Receiver<()>) {
+    fn watch(client: kube::Client, config: Config) -> (Arc<Self>, oneshot::Receiver<()>) {
         let (reader, writer) = reflector::store();
-        let stream = runtime::watcher(Api::<K>::all(client), Config::default())
+        let updates = Updates::default();
+
+        let previous = reader.clone();
+        let recorded = updates.clone();
+        let stream = runtime::watcher(Api::<K>::all(client), config)
             .default_backoff()
             .modify(|obj| {
                 ResourceExt::managed_fields_mut(obj).clear();
             })
+            .inspect(move |event| record(&previous, &recorded, event))
             .reflect(writer)
-            .applied_objects()
             .boxed();
 
+        let (changes, rx) = broadcast::channel(CHANGES);
+        let tx = changes.clone();
+
         let mut tasks = JoinSet::new();
 
         tasks.spawn(async move {
-            stream.for_each(|_| ready(())).await;
+            stream
+                .for_each(|event| {
+                    let change = match event {
+                        Ok(watcher::Event::Apply(obj)) => {
+                            Some(Change::Applied(ObjectRef::from_obj(&obj)))
+                        }
+                        Ok(watcher::Event::Delete(obj)) => {
+                            Some(Change::Deleted(ObjectRef::from_obj(&obj)))
+                        }
+                        Ok(watcher::Event::InitDone) => Some(Change::Reset),
+                        _ => None,
+                    };
+
+                    // Nobody listening is fine, subscribers come and go.
+                    if let Some(change) = change {
+                        let _ = tx.send(change);
+                    }
+
+                    ready(())
+                })
+                .await;
 
             Ok(())
         });
 
-        let (tx, rx) = oneshot::channel();
-        tasks.spawn(is_ready(reader.clone(), tx));
+        let (ready_tx, ready_rx) = oneshot::channel();
+        tasks.spawn(is_ready(reader.clone(), ready_tx));
+
+        let sorted = Mutex::new(Sorted {
+            items: Vec::new(),
+            changes: rx,
+            stale: true,
+            version: 0,
+        });
 
-        (Arc::new(Self { tasks, reader }), rx)
+        (
+            Arc::new(Self {
+                tasks,
+                reader,
+                changes,
+                sorted,
+                updates,
+            }),
+            ready_rx,
+        )
     }
 
+    /// Changes to the store from now on, as they happen. Receivers that fall
+    /// behind get [`broadcast::error::RecvError::Lagged`] and should start over
+    /// from [`Store::items`].
+    pub fn subscribe(&self) -> broadcast::Receiver<Change<K>> {
+        self.changes.subscribe()
+    }
+
+    /// What changed about `obj` since the store started watching, oldest first.
+    /// Only the last [`timeline::UPDATES`] are kept.
+    pub fn updates(&self, obj: &ObjectRef<K>) -> Vec<Update> {
+        self.updates
+            .lock()
+            .unwrap()
+            .get(obj)
+            .map(|list| list.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Goes up whenever the items in the store change, see
+    /// [`table::Items::version`].
+    pub fn version(&self) -> u64 {
+        self.sorted.lock().unwrap().sync(&self.reader)
+    }
+
+    // Anything in a namespace hidden by the session's policy is left out, as if
+    // it didn't exist.
     pub fn items(&self, filter: Option<String>) -> Vec<Arc<K>> {
-        let mut items = filter
-            .map(|filter| {
-                self.reader
-                    .state()
-                    .into_iter()
-                    .filter(|obj| obj.matches(filter.as_str()))
-                    .collect()
-            })
-            .unwrap_or(self.reader.state());
+        let selector = filter.as_deref().map(Selector::from).unwrap_or_default();
 
-        items.sort_by(Compare::cmp);
+        let mut sorted = self.sorted.lock().unwrap();
+        sorted.sync(&self.reader);
 
-        items
+        sorted
+            .items
+            .iter()
+            .filter(|&obj| {
+                Policy::visible(obj.namespace().as_deref()) && selector.matches(obj.as_ref())
+            })
+            .cloned()
+            .collect()
     }
 
     pub fn get(&self, idx: usize, filter: Option<String>) -> Option<Arc<K>> {
         self.items(filter).get(idx).cloned()
     }
+
+    /// Items indexed by the uid of the controller that owns them, following
+    /// `ownerReferences`. Items without a controller are left out. Each list
+    /// is in the same order as [`Store::items`].
+    pub fn owned(&self, filter: Option<String>) -> HashMap<String, Vec<Arc<K>>> {
+        let mut owned: HashMap<_, Vec<_>> = HashMap::new();
+
+        for obj in self.items(filter) {
+            if let Some(uid) = controller(obj.as_ref()) {
+                owned.entry(uid).or_default().push(obj);
+            }
+        }
+
+        owned
+    }
+}
+
+impl<K> Store<K>
+where
+    K: kube::Resource<DynamicType = ()>
+        + Clone
+        + std::fmt::Debug
+        + Send
+        + Sync
+        + DeserializeOwned
+        + 'static,
+{
+    /// The store a widget on this dashboard is already watching every `K` with,
+    /// if there is one. Unlike [`Store::new`], this never starts a watch.
+    pub fn shared() -> Option<Arc<Self>> {
+        lookup(&(TypeId::of::<K>(), None))
+    }
+
+    /// The latest version of `obj`, `None` once it has been deleted.
+    pub fn find(&self, obj: &ObjectRef<K>) -> Option<Arc<K>> {
+        self.reader.get(obj)
+    }
+}
+
+fn lookup<K>(key: &Key) -> Option<Arc<Store<K>>>
+where
+    K: kube::Resource<DynamicType = ()>
+        + Clone
+        + std::fmt::Debug
+        + Send
+        + Sync
+        + DeserializeOwned
+        + 'static,
+{
+    Context::with(|context| {
+        context
+            .stores
+            .lock()
+            .unwrap()
+            .get(key)
+            .and_then(|store| store.downcast_ref::<Weak<Store<K>>>())
+            .and_then(Weak::upgrade)
+    })
 }
 
 impl<K> Drop for Store<K>
@@ -127,6 +484,7 @@ where
         + Send
         + Sync
         + DeserializeOwned
+        + Serialize
         + 'static,
     Arc<K>: table::Row + Compare,
 {
@@ -135,4 +493,8 @@ where
     fn items(&self, filter: Option<String>) -> Vec<Self::Item> {
         Store::items(self, filter)
     }
+
+    fn version(&self) -> Option<u64> {
+        Some(Store::version(self))
+    }
 }
@@ -0,0 +1,270 @@
+use std::{cmp::Ordering, collections::BTreeMap, sync::Arc};
+
+use itertools::Itertools;
+use k8s_openapi::{
+    api::{
+        core::v1::{Pod, Service},
+        networking::v1::NetworkPolicy,
+    },
+    apimachinery::pkg::apis::meta::v1::LabelSelector,
+};
+use kube::ResourceExt;
+use ratatui::{
+    layout::Constraint,
+    widgets::{Cell, Row},
+};
+
+use super::{age::Age, gateway::HTTPRoute, pod::PodExt, store::Store, Compare, Filter};
+use crate::widget::{table, yank::Yank};
+
+/// Whether `labels` are matched by `selector`. Like the API server, an empty
+/// selector matches everything.
+pub fn selects(selector: &LabelSelector, labels: &BTreeMap<String, String>) -> bool {
+    let matches_labels = selector
+        .match_labels
+        .iter()
+        .flatten()
+        .all(|(key, value)| labels.get(key) == Some(value));
+
+    let matches_expressions = selector.match_expressions.iter().flatten().all(|expr| {
+        let value = labels.get(&expr.key);
+        let values = expr.values.as_deref().unwrap_or_default();
+
+        match expr.operator.as_str() {
+            "In" => value.is_some_and(|v| values.contains(v)),
+            "NotIn" => value.map_or(true, |v| !values.contains(v)),
+            "Exists" => value.is_some(),
+            "DoesNotExist" => value.is_none(),
+            _ => false,
+        }
+    });
+
+    matches_labels && matches_expressions
+}
+
+/// A selector the way `kubectl` would write it, eg. `app=nginx,tier in (web)`.
+pub fn describe(selector: &LabelSelector) -> String {
+    let labels = selector
+        .match_labels
+        .iter()
+        .flatten()
+        .map(|(key, value)| format!("{key}={value}"));
+
+    let expressions = selector.match_expressions.iter().flatten().map(|expr| {
+        let values = expr.values.as_deref().unwrap_or_default().join(",");
+
+        match expr.operator.as_str() {
+            "Exists" => expr.key.clone(),
+            "DoesNotExist" => format!("!{}", expr.key),
+            op => format!("{} {} ({values})", expr.key, op.to_lowercase()),
+        }
+    });
+
+    let description = labels.chain(expressions).join(",");
+
+    if description.is_empty() {
+        "<all pods>".to_string()
+    } else {
+        description
+    }
+}
+
+/// What the pods are being resolved for.
+#[derive(Clone)]
+pub enum Source {
+    /// Every service in `backendRefs`, and then the pods those services select.
+    Route(Arc<HTTPRoute>),
+    /// The pods the policy applies to, from `podSelector`.
+    Policy(Arc<NetworkPolicy>),
+    /// Pods running an image, see [`super::image::Image`].
+    Image(String),
+}
+
+/// A pod that is targeted, along with how it was reached (a backend, selector
+/// or the containers running an image).
+#[derive(Clone, Debug)]
+pub struct Target {
+    pub via: String,
+    pub pod: Arc<Pod>,
+}
+
+impl table::Row for Target {
+    fn yank(&self) -> Option<Yank> {
+        Some(Yank::from_obj(self.pod.as_ref()))
+    }
+
+    fn health(&self) -> table::Health {
+        table::Row::health(&self.pod)
+    }
+
+    fn id(&self) -> Option<String> {
+        self.pod.uid().map(|uid| format!("{}/{uid}", self.via))
+    }
+
+    fn header<'a>() -> Option<Row<'a>> {
+        Some(Row::new(vec![
+            Cell::from("Via"),
+            Cell::from("Namespace"),
+            Cell::from("Name"),
+            Cell::from("Ready"),
+            Cell::from("Status"),
+            Cell::from("Node"),
+            Cell::from("Age"),
+        ]))
+    }
+
+    fn constraints() -> Vec<Constraint> {
+        vec![
+            Constraint::Max(24),
+            Constraint::Max(20),
+            Constraint::Min(10),
+            Constraint::Max(10),
+            Constraint::Max(20),
+            Constraint::Max(20),
+            Constraint::Max(10),
+        ]
+    }
+
+    fn row(&self, style: &table::RowStyle) -> Row {
+        let health = table::Row::health(self);
+
+        Row::new(vec![
+            self.via.clone(),
+            self.pod.namespace().unwrap_or_default(),
+            self.pod.name_any(),
+            self.pod.ready(),
+            style.status(health, self.pod.status().to_string()),
+            self.pod
+                .spec
+                .as_ref()
+                .and_then(|spec| spec.node_name.clone())
+                .unwrap_or_default(),
+            self.pod.age().to_age(),
+        ])
+        .style(style.get(health))
+    }
+}
+
+impl Filter for Service {
+    fn matches(&self, filter: &str) -> bool {
+        self.name_any().contains(filter)
+    }
+}
+
+impl Compare for Arc<Service> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.namespace(), self.name_any()).cmp(&(other.namespace(), other.name_any()))
+    }
+}
+
+/// The live pods behind a route, network policy or image. Pods and services are
+/// watched, so this keeps up as pods come and go or selectors change.
+#[derive(Clone)]
+pub struct Targets {
+    source: Source,
+    pods: Arc<Store<Pod>>,
+    services: Arc<Store<Service>>,
+}
+
+impl Targets {
+    pub fn new(source: Source, pods: Arc<Store<Pod>>, services: Arc<Store<Service>>) -> Self {
+        Self {
+            source,
+            pods,
+            services,
+        }
+    }
+
+    pub fn get(&self, idx: usize, filter: Option<String>) -> Option<Arc<Pod>> {
+        table::Items::items(self, filter)
+            .get(idx)
+            .map(|target| target.pod.clone())
+    }
+
+    fn policy(policy: &NetworkPolicy, pods: Vec<Arc<Pod>>) -> Vec<Target> {
+        let selector = policy
+            .spec
+            .as_ref()
+            .map(|spec| spec.pod_selector.clone())
+            .unwrap_or_default();
+        let via = describe(&selector);
+
+        pods.into_iter()
+            .filter(|pod| pod.namespace() == policy.namespace() && selects(&selector, pod.labels()))
+            .map(|pod| Target {
+                via: via.clone(),
+                pod,
+            })
+            .collect()
+    }
+
+    fn image(image: &str, pods: Vec<Arc<Pod>>) -> Vec<Target> {
+        pods.into_iter()
+            .filter_map(|pod| {
+                let spec = pod.spec.as_ref()?;
+                let via = spec
+                    .containers
+                    .iter()
+                    .chain(spec.init_containers.iter().flatten())
+                    .filter(|container| container.image.as_deref() == Some(image))
+                    .map(|container| container.name.as_str())
+                    .join(", ");
+
+                if via.is_empty() {
+                    return None;
+                }
+
+                Some(Target { via, pod })
+            })
+            .collect()
+    }
+
+    // Backends that aren't services, or services without a selector, don't
+    // resolve to any pods.
+    fn route(&self, route: &HTTPRoute, pods: &[Arc<Pod>]) -> Vec<Target> {
+        let services = self.services.items(None);
+
+        route
+            .backends()
+            .into_iter()
+            .filter(|backend| backend.is_service())
+            .flat_map(|backend| {
+                let namespace = backend.namespace.clone().or_else(|| route.namespace());
+
+                let selector = services
+                    .iter()
+                    .find(|svc| svc.namespace() == namespace && svc.name_any() == backend.name)
+                    .and_then(|svc| svc.spec.as_ref()?.selector.clone())
+                    .unwrap_or_default();
+
+                pods.iter()
+                    .filter(|pod| {
+                        !selector.is_empty()
+                            && pod.namespace() == namespace
+                            && selector
+                                .iter()
+                                .all(|(key, value)| pod.labels().get(key) == Some(value))
+                    })
+                    .map(|pod| Target {
+                        via: backend.to_string(),
+                        pod: pod.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+impl table::Items for Targets {
+    type Item = Target;
+
+    fn items(&self, filter: Option<String>) -> Vec<Self::Item> {
+        let pods = self.pods.items(filter);
+
+        match &self.source {
+            Source::Policy(policy) => Self::policy(policy, pods),
+            Source::Route(route) => self.route(route, &pods),
+            Source::Image(image) => Self::image(image, pods),
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use chrono::Utc;
+use eyre::{eyre, Result};
+use k8s_openapi::api::{
+    batch::v1::{Job, JobSpec},
+    core::v1::{Container, Pod, PodSpec, PodTemplateSpec},
+};
+use kube::api::{ApiResource, DynamicObject, ObjectMeta};
+
+static CREATED_BY: &str = "app.kubernetes.io/created-by";
+
+/// Starting points for creating new resources from the dashboard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Template {
+    Debug,
+    Netshoot,
+    Job,
+}
+
+impl Template {
+    pub fn all() -> &'static [Template] {
+        &[Self::Debug, Self::Netshoot, Self::Job]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Debug => "Debug pod",
+            Self::Netshoot => "Netshoot pod",
+            Self::Job => "Job from image",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Debug => "A pod that sleeps forever, exec into it from the Shell tab.",
+            Self::Netshoot => "A pod with networking tools (dig, curl, tcpdump, ...).",
+            Self::Job => "Run an image to completion once.",
+        }
+    }
+
+    pub fn image(self) -> &'static str {
+        match self {
+            Self::Debug => "busybox",
+            Self::Netshoot => "nicolaka/netshoot",
+            Self::Job => "",
+        }
+    }
+
+    pub fn kind(self) -> &'static str {
+        match self {
+            Self::Debug | Self::Netshoot => "pod",
+            Self::Job => "job",
+        }
+    }
+
+    /// Default name for the resource, a timestamp keeps it from colliding with
+    /// anything that has already been created.
+    pub fn default_name(self) -> String {
+        let prefix = match self {
+            Self::Debug => "debug",
+            Self::Netshoot => "netshoot",
+            Self::Job => "job",
+        };
+
+        format!("{prefix}-{}", Utc::now().format("%H%M%S"))
+    }
+
+    pub fn api_resource(self) -> ApiResource {
+        match self {
+            Self::Debug | Self::Netshoot => ApiResource::erase::<Pod>(&()),
+            Self::Job => ApiResource::erase::<Job>(&()),
+        }
+    }
+
+    pub fn render(self, name: &str, namespace: &str, image: &str) -> Result<DynamicObject> {
+        if !is_subdomain(name) {
+            return Err(eyre!(
+                "names must be lowercase alphanumerics, '-' or '.' and start and end with an \
+                 alphanumeric, got: {name}"
+            ));
+        }
+
+        if namespace.is_empty() {
+            return Err(eyre!("namespace is required"));
+        }
+
+        if image.is_empty() {
+            return Err(eyre!("image is required"));
+        }
+
+        let metadata = ObjectMeta {
+            name: Some(name.to_string()),
+            namespace: Some(namespace.to_string()),
+            labels: Some([(CREATED_BY.to_string(), "kty".to_string())].into()),
+            ..Default::default()
+        };
+
+        let container = Container {
+            name: "main".to_string(),
+            image: Some(image.to_string()),
+            ..Default::default()
+        };
+
+        let value = match self {
+            Self::Debug | Self::Netshoot => serde_json::to_value(Pod {
+                metadata,
+                spec: Some(PodSpec {
+                    containers: vec![Container {
+                        command: Some(vec!["sleep".to_string(), "infinity".to_string()]),
+                        ..container
+                    }],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })?,
+            Self::Job => serde_json::to_value(Job {
+                metadata,
+                spec: Some(JobSpec {
+                    backoff_limit: Some(0),
+                    template: PodTemplateSpec {
+                        metadata: None,
+                        spec: Some(PodSpec {
+                            containers: vec![container],
+                            restart_policy: Some("Never".to_string()),
+                            ..Default::default()
+                        }),
+                    },
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })?,
+        };
+
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+fn is_subdomain(name: &str) -> bool {
+    let bytes = name.as_bytes();
+
+    (1..=253).contains(&bytes.len())
+        && bytes[0].is_ascii_alphanumeric()
+        && bytes[bytes.len() - 1].is_ascii_alphanumeric()
+        && bytes
+            .iter()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, b'-' | b'.'))
+}
@@ -0,0 +1,106 @@
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Updates kept for each object, older ones are dropped.
+pub static UPDATES: usize = 20;
+// Lines kept for each update, anything past this is summarized.
+static CHANGES: usize = 8;
+// Values longer than this are cut off, they're one line each.
+static VALUE_WIDTH: usize = 40;
+// These change with every update without saying anything about what happened.
+static IGNORED: [&str; 3] = [
+    "metadata.resourceVersion",
+    "metadata.managedFields",
+    "metadata.generation",
+];
+
+/// A change to an object that a store's watch saw, see
+/// [`super::store::Store::updates`]. Only what happened while the store was
+/// watching is known.
+#[derive(Clone, Debug)]
+pub struct Update {
+    pub at: DateTime<Utc>,
+    /// One line per field that changed, eg. `status.phase: Pending → Running`.
+    pub changes: Vec<String>,
+}
+
+impl Update {
+    /// `None` when nothing worth showing changed between `before` and `after`.
+    pub fn new<K: Serialize>(before: &K, after: &K) -> Option<Self> {
+        let (Ok(before), Ok(after)) = (serde_json::to_value(before), serde_json::to_value(after))
+        else {
+            return None;
+        };
+
+        let mut changes = Vec::new();
+        walk("", Some(&before), Some(&after), &mut changes);
+
+        if changes.is_empty() {
+            return None;
+        }
+
+        if changes.len() > CHANGES {
+            let more = changes.len() - CHANGES + 1;
+            changes.truncate(CHANGES - 1);
+            changes.push(format!("and {more} more"));
+        }
+
+        Some(Self {
+            at: Utc::now(),
+            changes,
+        })
+    }
+}
+
+fn walk(path: &str, before: Option<&Value>, after: Option<&Value>, changes: &mut Vec<String>) {
+    if before == after || IGNORED.contains(&path) {
+        return;
+    }
+
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+
+    match (before, after) {
+        (Some(Value::Object(before)), Some(Value::Object(after))) => {
+            for key in before.keys().chain(after.keys()).collect::<BTreeSet<_>>() {
+                walk(&join(key), before.get(key), after.get(key), changes);
+            }
+        }
+        (Some(Value::Array(before)), Some(Value::Array(after))) if before.len() == after.len() => {
+            for (idx, (before, after)) in before.iter().zip(after).enumerate() {
+                walk(
+                    &format!("{path}[{idx}]"),
+                    Some(before),
+                    Some(after),
+                    changes,
+                );
+            }
+        }
+        _ => changes.push(format!("{path}: {} → {}", short(before), short(after))),
+    }
+}
+
+fn short(value: Option<&Value>) -> String {
+    let value = match value {
+        None | Some(Value::Null) => return "<none>".to_string(),
+        Some(Value::String(s)) => s.clone(),
+        Some(value) => value.to_string(),
+    };
+
+    if value.chars().count() > VALUE_WIDTH {
+        format!(
+            "{}…",
+            value.chars().take(VALUE_WIDTH - 1).collect::<String>()
+        )
+    } else {
+        value
+    }
+}
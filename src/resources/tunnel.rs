@@ -1,12 +1,20 @@
+// Streaming is only used by the SSH server, the rest of this is used to
+// display tunnels in the dashboard.
+#![cfg_attr(not(feature = "ssh"), allow(dead_code, unused_imports))]
+
+#[cfg(feature = "ssh")]
 mod egress;
+#[cfg(feature = "ssh")]
 mod ingress;
 
 use std::hash::{Hash, Hasher};
 
 use chrono::Utc;
 use derive_builder::Builder;
+#[cfg(feature = "ssh")]
 pub use egress::EgressBuilder;
 use eyre::{Report, Result};
+#[cfg(feature = "ssh")]
 pub use ingress::Ingress;
 use lazy_static::lazy_static;
 use prometheus::{
@@ -17,7 +25,7 @@ use prometheus_static_metric::make_static_metric;
 use ratatui::{layout::Constraint, widgets::Row};
 use tokio::io::{AsyncRead, AsyncWrite};
 
-use crate::widget::table;
+use crate::widget::table::{self, Health};
 
 make_static_metric! {
     pub struct ResourceVec: IntCounter {
@@ -163,16 +171,18 @@ impl table::Row for Tunnel {
     }
 
     fn row(&self, style: &table::RowStyle) -> Row {
+        let health = match self.lifecycle {
+            Lifecycle::Active => Health::Healthy,
+            Lifecycle::Inactive | Lifecycle::Listening => Health::Normal,
+            Lifecycle::Error => Health::Unhealthy,
+        };
+
         Row::new(vec![
             self.kind.to_string().to_lowercase(),
             format!("{}:{}", self.host, self.port),
-            self.lifecycle.to_string(),
+            style.status(health, self.lifecycle.to_string()),
         ])
-        .style(match self.lifecycle {
-            Lifecycle::Active => style.healthy,
-            Lifecycle::Inactive | Lifecycle::Listening => style.normal,
-            Lifecycle::Error => style.unhealthy,
-        })
+        .style(style.get(health))
     }
 }
 
@@ -0,0 +1,361 @@
+use std::{cmp::Ordering, collections::HashMap, sync::Arc};
+
+use chrono::{TimeDelta, Utc};
+use k8s_openapi::api::{
+    apps::v1::{DaemonSet, Deployment, ReplicaSet, StatefulSet},
+    batch::v1::Job,
+    core::v1::Pod,
+};
+use kube::ResourceExt;
+use ratatui::{
+    layout::Constraint,
+    widgets::{Cell, Row},
+};
+
+use super::{age::Age, store::Store, Compare, Filter};
+use crate::widget::{
+    table::{self, Health},
+    yank::Yank,
+};
+
+/// Something that creates and looks after pods.
+#[derive(Clone, Debug)]
+pub enum Controller {
+    Deployment(Arc<Deployment>),
+    StatefulSet(Arc<StatefulSet>),
+    DaemonSet(Arc<DaemonSet>),
+    Job(Arc<Job>),
+}
+
+impl Controller {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Deployment(_) => "Deployment",
+            Self::StatefulSet(_) => "StatefulSet",
+            Self::DaemonSet(_) => "DaemonSet",
+            Self::Job(_) => "Job",
+        }
+    }
+
+    pub fn namespace(&self) -> Option<String> {
+        match self {
+            Self::Deployment(d) => d.namespace(),
+            Self::StatefulSet(s) => s.namespace(),
+            Self::DaemonSet(d) => d.namespace(),
+            Self::Job(j) => j.namespace(),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::Deployment(d) => d.name_any(),
+            Self::StatefulSet(s) => s.name_any(),
+            Self::DaemonSet(d) => d.name_any(),
+            Self::Job(j) => j.name_any(),
+        }
+    }
+
+    pub fn uid(&self) -> Option<String> {
+        match self {
+            Self::Deployment(d) => d.uid(),
+            Self::StatefulSet(s) => s.uid(),
+            Self::DaemonSet(d) => d.uid(),
+            Self::Job(j) => j.uid(),
+        }
+    }
+
+    /// Pods that are ready and the number of pods there should be. Daemon sets
+    /// want one for every node they're scheduled on, jobs count the pods that
+    /// have succeeded against how many need to.
+    pub fn replicas(&self) -> (i32, i32) {
+        match self {
+            Self::Deployment(d) => (
+                d.status
+                    .as_ref()
+                    .and_then(|s| s.ready_replicas)
+                    .unwrap_or_default(),
+                d.spec.as_ref().and_then(|s| s.replicas).unwrap_or(1),
+            ),
+            Self::StatefulSet(s) => (
+                s.status
+                    .as_ref()
+                    .and_then(|s| s.ready_replicas)
+                    .unwrap_or_default(),
+                s.spec.as_ref().and_then(|s| s.replicas).unwrap_or(1),
+            ),
+            Self::DaemonSet(d) => d
+                .status
+                .as_ref()
+                .map_or((0, 0), |s| (s.number_ready, s.desired_number_scheduled)),
+            Self::Job(j) => (
+                j.status
+                    .as_ref()
+                    .and_then(|s| s.succeeded)
+                    .unwrap_or_default(),
+                j.spec.as_ref().and_then(|s| s.completions).unwrap_or(1),
+            ),
+        }
+    }
+
+    fn age(&self) -> TimeDelta {
+        let created = match self {
+            Self::Deployment(d) => d.creation_timestamp(),
+            Self::StatefulSet(s) => s.creation_timestamp(),
+            Self::DaemonSet(d) => d.creation_timestamp(),
+            Self::Job(j) => j.creation_timestamp(),
+        };
+
+        created.map_or_else(TimeDelta::zero, |time| Utc::now() - time.0)
+    }
+}
+
+/// A controller along with the pods it currently owns.
+#[derive(Clone, Debug)]
+pub struct Workload {
+    pub controller: Controller,
+    pub pods: Vec<Arc<Pod>>,
+}
+
+impl table::Row for Workload {
+    fn yank(&self) -> Option<Yank> {
+        Some(Yank::new(
+            self.controller.kind(),
+            self.controller.namespace(),
+            self.controller.name(),
+        ))
+    }
+
+    fn id(&self) -> Option<String> {
+        self.controller.uid()
+    }
+
+    fn group(&self, group: table::Group) -> Option<String> {
+        match group {
+            table::Group::Namespace => self.controller.namespace(),
+            table::Group::Owner => Some(self.controller.kind().to_string()),
+            table::Group::Node => None,
+        }
+    }
+
+    fn health(&self) -> Health {
+        let (ready, desired) = self.controller.replicas();
+
+        if ready >= desired {
+            Health::Healthy
+        } else {
+            Health::Normal
+        }
+    }
+
+    fn header<'a>() -> Option<Row<'a>> {
+        Some(Row::new(vec![
+            Cell::from("Kind"),
+            Cell::from("Namespace"),
+            Cell::from("Name"),
+            Cell::from("Ready"),
+            Cell::from("Pods"),
+            Cell::from("Age"),
+        ]))
+    }
+
+    fn constraints() -> Vec<Constraint> {
+        vec![
+            Constraint::Max(12),
+            Constraint::Max(20),
+            Constraint::Min(10),
+            Constraint::Max(10),
+            Constraint::Max(6),
+            Constraint::Max(10),
+        ]
+    }
+
+    fn row(&self, style: &table::RowStyle) -> Row {
+        let health = self.health();
+        let (ready, desired) = self.controller.replicas();
+
+        Row::new(vec![
+            self.controller.kind().to_string(),
+            self.controller.namespace().unwrap_or_default(),
+            self.controller.name(),
+            style.status(health, format!("{ready}/{desired}")),
+            self.pods.len().to_string(),
+            self.controller.age().to_age(),
+        ])
+        .style(style.get(health))
+    }
+}
+
+impl Compare for Workload {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (
+            self.controller.namespace(),
+            self.controller.name(),
+            self.controller.kind(),
+        )
+            .cmp(&(
+                other.controller.namespace(),
+                other.controller.name(),
+                other.controller.kind(),
+            ))
+    }
+}
+
+impl Filter for StatefulSet {
+    fn matches(&self, filter: &str) -> bool {
+        self.name_any().contains(filter)
+    }
+}
+
+impl Compare for Arc<StatefulSet> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.namespace(), self.name_any()).cmp(&(other.namespace(), other.name_any()))
+    }
+}
+
+impl Filter for DaemonSet {
+    fn matches(&self, filter: &str) -> bool {
+        self.name_any().contains(filter)
+    }
+}
+
+impl Compare for Arc<DaemonSet> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.namespace(), self.name_any()).cmp(&(other.namespace(), other.name_any()))
+    }
+}
+
+// Deployments own replica sets, which own the pods. Everything else owns its
+// pods directly.
+fn resolve(
+    uid: &str,
+    replicasets: &HashMap<String, Vec<Arc<ReplicaSet>>>,
+    pods: &HashMap<String, Vec<Arc<Pod>>>,
+) -> Vec<Arc<Pod>> {
+    let owners = std::iter::once(uid.to_string()).chain(
+        replicasets
+            .get(uid)
+            .into_iter()
+            .flatten()
+            .filter_map(|rs| rs.uid()),
+    );
+
+    let mut resolved: Vec<_> = owners
+        .filter_map(|owner| pods.get(&owner))
+        .flatten()
+        .cloned()
+        .collect();
+    resolved.sort_by(Compare::cmp);
+
+    resolved
+}
+
+/// Deployments, stateful sets, daemon sets and jobs with the pods they own,
+/// resolved through `ownerReferences` as the stores change.
+#[derive(Clone)]
+pub struct Workloads {
+    deployments: Arc<Store<Deployment>>,
+    statefulsets: Arc<Store<StatefulSet>>,
+    daemonsets: Arc<Store<DaemonSet>>,
+    jobs: Arc<Store<Job>>,
+    replicasets: Arc<Store<ReplicaSet>>,
+    pods: Arc<Store<Pod>>,
+}
+
+impl Workloads {
+    pub fn new(
+        deployments: Arc<Store<Deployment>>,
+        statefulsets: Arc<Store<StatefulSet>>,
+        daemonsets: Arc<Store<DaemonSet>>,
+        jobs: Arc<Store<Job>>,
+        replicasets: Arc<Store<ReplicaSet>>,
+        pods: Arc<Store<Pod>>,
+    ) -> Self {
+        Self {
+            deployments,
+            statefulsets,
+            daemonsets,
+            jobs,
+            replicasets,
+            pods,
+        }
+    }
+
+    pub fn get(&self, idx: usize, filter: Option<String>) -> Option<Workload> {
+        table::Items::items(self, filter).get(idx).cloned()
+    }
+
+    /// The pods currently owned by the workload with `uid`, matching `filter`.
+    pub fn pods(&self, uid: &str, filter: Option<String>) -> Vec<Arc<Pod>> {
+        resolve(uid, &self.replicasets.owned(None), &self.pods.owned(filter))
+    }
+}
+
+impl table::Items for Workloads {
+    type Item = Workload;
+
+    fn items(&self, filter: Option<String>) -> Vec<Self::Item> {
+        let replicasets = self.replicasets.owned(None);
+        let pods = self.pods.owned(None);
+
+        let deployments = self
+            .deployments
+            .items(filter.clone())
+            .into_iter()
+            .map(Controller::Deployment);
+
+        let statefulsets = self
+            .statefulsets
+            .items(filter.clone())
+            .into_iter()
+            .map(Controller::StatefulSet);
+
+        let daemonsets = self
+            .daemonsets
+            .items(filter.clone())
+            .into_iter()
+            .map(Controller::DaemonSet);
+
+        let jobs = self.jobs.items(filter).into_iter().map(Controller::Job);
+
+        let mut items: Vec<_> = deployments
+            .chain(statefulsets)
+            .chain(daemonsets)
+            .chain(jobs)
+            .map(|controller| Workload {
+                pods: controller
+                    .uid()
+                    .map(|uid| resolve(&uid, &replicasets, &pods))
+                    .unwrap_or_default(),
+                controller,
+            })
+            .collect();
+        items.sort_by(Compare::cmp);
+
+        items
+    }
+}
+
+/// The pods of a single workload, see [`Workloads::pods`].
+#[derive(Clone)]
+pub struct Owned {
+    workloads: Workloads,
+    uid: String,
+}
+
+impl Owned {
+    pub fn new(workloads: Workloads, uid: String) -> Self {
+        Self { workloads, uid }
+    }
+
+    pub fn get(&self, idx: usize, filter: Option<String>) -> Option<Arc<Pod>> {
+        self.workloads.pods(&self.uid, filter).get(idx).cloned()
+    }
+}
+
+impl table::Items for Owned {
+    type Item = Arc<Pod>;
+
+    fn items(&self, filter: Option<String>) -> Vec<Self::Item> {
+        self.workloads.pods(&self.uid, filter)
+    }
+}
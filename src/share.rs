@@ -0,0 +1,11 @@
+use eyre::Result;
+
+/// Lets other users watch a dashboard. They see everything that is rendered
+/// but can't interact with it.
+pub trait Share: Send + Sync {
+    /// Start sharing, returning the code observers use to attach.
+    fn start(&self) -> Result<String>;
+
+    /// Stop sharing and disconnect any observers.
+    fn stop(&self);
+}
@@ -1,25 +1,57 @@
+//! SSH server plumbing. Connections are authenticated via. public keys, user
+//! certificates or `OpenID` and then impersonate the resulting identity for
+//! everything they do.
+
+pub(crate) mod client;
+pub(crate) mod detached;
+pub mod export;
+pub(crate) mod launch;
+pub(crate) mod leader;
+pub(crate) mod oci;
+pub(crate) mod proxy_protocol;
+pub(crate) mod registry;
 pub(crate) mod session;
 
 use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
     net::{IpAddr, SocketAddr},
     sync::Arc,
+    time::Duration,
 };
 
 use clap::ValueEnum;
 use derive_builder::Builder;
-use eyre::Result;
+use detached::Detached;
+use eyre::{eyre, Result};
+use futures::FutureExt;
 use k8s_openapi::{
     api::core::v1::{ObjectReference, Pod, PodStatus},
     apimachinery::pkg::apis::meta::v1,
 };
-use kube::runtime::events::{Event, Recorder, Reporter};
+use kube::runtime::{
+    events::{Event, Recorder, Reporter},
+    reflector::{self, ObjectRef},
+};
+use launch::RunAs;
 use lazy_static::lazy_static;
 use prometheus::{register_int_counter, IntCounter};
+use registry::Registry;
 use russh::server::{Config, Handler, Server};
 use session::{Session, SessionBuilder};
+use tokio::net::TcpListener;
 use tracing::error;
 
-use crate::{identity::Identity, openid};
+use crate::{
+    identity::{certificate::Authorities, otp::BreakGlass, Identity, Key},
+    openid,
+    plugin::Plugin,
+    resources::{
+        policy::{Policies, Policy, Verb},
+        settings::{KtySettings, KtySettingsSpec},
+    },
+    widget::statusbar::Slow,
+};
 
 lazy_static! {
     static ref CLIENT_COUNTER: IntCounter = register_int_counter!(
@@ -32,13 +64,28 @@ lazy_static! {
         "Number of errors encountered by sessions. Note that this does not include IO errors",
     )
     .unwrap();
+    static ref PROXY_REJECTED: IntCounter = register_int_counter!(
+        "ssh_proxy_rejected_total",
+        "Number of connections closed because they didn't start with a valid PROXY protocol header",
+    )
+    .unwrap();
 }
 
+// Load balancers send the PROXY protocol header as soon as they connect,
+// anything that takes longer than this isn't one.
+static PROXY_HEADER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where the server is running. This is used as the owner of any resources
+/// created on behalf of users, such as for egress tunnels.
 #[derive(Clone, Debug, Builder)]
 pub struct CurrentPod {
+    /// Namespace of the pod.
     pub namespace: String,
+    /// Name of the pod.
     pub name: String,
+    /// UID of the pod.
     pub uid: String,
+    /// IP that can be used to reach the server from inside the cluster.
     pub addr: IpAddr,
 }
 
@@ -71,33 +118,123 @@ impl From<CurrentPod> for Pod {
     }
 }
 
+/// Shared state for the server and how it talks to the cluster.
 #[derive(Builder)]
 pub struct Controller {
+    /// Config for the server's own client. Users are impersonated from this.
     config: kube::Config,
+    /// Reporter used when publishing events.
     #[allow(dead_code)]
     #[builder(default)]
     reporter: Option<Reporter>,
+    /// Where the server is running.
     #[builder(default)]
     server: CurrentPod,
+    /// Whether keys can be used. Keys are stored as CRDs and in locked down
+    /// clusters, it is possible that the server doesn't have access to them.
+    /// When disabled, keys are neither checked nor saved and users go through
+    /// `OpenID` every time.
+    #[builder(default = "true")]
+    keys: bool,
+    /// Whether new keys can be saved, existing keys can still be used without
+    /// it. Users are asked to log in every time instead of binding their key.
+    #[builder(default = "true")]
+    keys_writable: bool,
+    /// `Key` objects in the server's namespace, kept up to date by a watcher so
+    /// that authentication doesn't go to the API server for every attempt.
+    #[builder(default)]
+    key_cache: Option<reflector::Store<Key>>,
+    /// Active sessions and what they've been doing.
+    #[builder(default)]
+    sessions: Arc<Registry>,
+    /// Name of the cluster the server is running in, shown to users.
+    #[builder(default)]
+    cluster: Option<String>,
+    /// Defaults set by admins via. `KtySettings`, kept up to date by a watcher.
+    #[builder(default)]
+    settings: Option<reflector::Store<KtySettings>>,
+    /// `AccessPolicy` objects, kept up to date by a watcher.
+    #[builder(default)]
+    policies: Option<Policies>,
+    /// Frames per second for dashboards, sessions can ask for something else.
+    #[builder(default = "crate::dashboard::FPS")]
+    fps: u16,
+    /// Sessions without input for this long are disconnected, `AccessPolicy`
+    /// can change it per user. Sessions are never disconnected when unset.
+    #[builder(default)]
+    inactivity_timeout: Option<std::time::Duration>,
+    /// Offered when the `OpenID` provider can't be used, see [`BreakGlass`].
+    #[builder(default)]
+    break_glass: Option<Arc<BreakGlass>>,
+    /// Authorities whose user certificates can be used to log in. Certificates
+    /// are rejected when unset.
+    #[builder(default)]
+    authorities: Option<Authorities>,
+    /// Dashboards are kept running for this long after their connection goes
+    /// away, reconnecting picks them back up. Dashboards exit with their
+    /// connection when unset.
+    #[builder(default)]
+    detach_timeout: Option<std::time::Duration>,
+    /// Dashboards waiting for their user to reconnect.
+    #[builder(default)]
+    detached: Arc<Detached>,
+    /// Keep what shells write to the terminal in the audit log, not only the
+    /// command line and how they exited.
+    #[builder(default)]
+    exec_transcripts: bool,
+    /// Extra tabs for every dashboard, see [`Plugin`].
+    #[builder(default)]
+    plugins: Vec<Arc<dyn Plugin>>,
+    /// Programs that `KtySettings.commands` can run, by name or path. Commands
+    /// are disabled when empty.
+    #[builder(default)]
+    allowed_commands: Vec<String>,
+    /// Who commands run as, the server's own OS user when unset.
+    #[builder(default)]
+    command_user: Option<RunAs>,
+    /// Verbs nobody can use, whatever RBAC and `AccessPolicy` allow.
+    /// `KtySettings` can add to these.
+    #[builder(default)]
+    disabled: Vec<Verb>,
 }
 
 impl Controller {
+    /// Client with the server's own permissions.
     pub fn client(&self) -> Result<kube::Client, kube::Error> {
-        kube::Client::try_from(self.config.clone())
+        client::build(self.config.clone(), Slow::default())
     }
 
+    /// Client that impersonates the provided user and groups.
     pub fn impersonate(
         &self,
         user: String,
         groups: Vec<String>,
     ) -> Result<kube::Client, kube::Error> {
+        self.impersonate_with(user, groups, Slow::default())
+    }
+
+    /// [`Controller::impersonate`] for a dashboard, calls that are slow show up
+    /// in its status bar.
+    pub fn impersonate_with(
+        &self,
+        user: String,
+        groups: Vec<String>,
+        slow: Slow,
+    ) -> Result<kube::Client, kube::Error> {
+        client::build(self.impersonated(user, groups), slow)
+    }
+
+    /// Config that impersonates the provided user and groups, for when
+    /// something other than the server talks to the cluster as them.
+    pub(crate) fn impersonated(&self, user: String, groups: Vec<String>) -> kube::Config {
         let mut cfg = self.config.clone();
         cfg.auth_info.impersonate = Some(user);
         cfg.auth_info.impersonate_groups = (!groups.is_empty()).then_some(groups);
 
-        kube::Client::try_from(cfg)
+        cfg
     }
 
+    /// Publish an event for `obj_ref` if a reporter has been configured.
     #[allow(dead_code)]
     pub async fn publish(&self, obj_ref: ObjectReference, ev: Event) -> Result<()> {
         if let Some(reporter) = &self.reporter {
@@ -109,44 +246,262 @@ impl Controller {
         Ok(())
     }
 
+    /// The pod the server is running in.
     pub fn server(&self) -> Pod {
         self.server.clone().into()
     }
+
+    /// Whether keys can be checked and saved.
+    pub fn keys_enabled(&self) -> bool {
+        self.keys
+    }
+
+    /// Whether keys can be bound, see [`Key::writable`].
+    pub fn keys_writable(&self) -> bool {
+        self.keys && self.keys_writable
+    }
+
+    /// The key named `name` from the cache, which is never more than a watch
+    /// event behind. `None` when there's no cache or it hasn't finished its
+    /// first list yet, the API server needs to be asked instead.
+    pub(crate) fn cached_key(&self, name: &str) -> Option<Option<Arc<Key>>> {
+        let store = self.key_cache.as_ref()?;
+
+        store.wait_until_ready().now_or_never()?.ok()?;
+
+        Some(store.get(&ObjectRef::new(name).within(&self.config.default_namespace)))
+    }
+
+    pub(crate) fn sessions(&self) -> &Registry {
+        &self.sessions
+    }
+
+    pub fn cluster(&self) -> Option<&str> {
+        self.cluster.as_deref()
+    }
+
+    pub fn fps(&self) -> u16 {
+        self.fps
+    }
+
+    /// The current defaults for sessions. These are read when a session starts,
+    /// changes only apply to new sessions.
+    pub fn settings(&self) -> KtySettingsSpec {
+        self.settings
+            .as_ref()
+            .map(|store| KtySettingsSpec::merged(store.state().iter().map(AsRef::as_ref)))
+            .unwrap_or_default()
+    }
+
+    pub fn break_glass(&self) -> Option<&BreakGlass> {
+        self.break_glass.as_deref()
+    }
+
+    pub fn authorities(&self) -> Option<&Authorities> {
+        self.authorities.as_ref()
+    }
+
+    pub fn exec_transcripts(&self) -> bool {
+        self.exec_transcripts
+    }
+
+    pub fn plugins(&self) -> Vec<Arc<dyn Plugin>> {
+        self.plugins.clone()
+    }
+
+    pub fn allowed_commands(&self) -> &[String] {
+        &self.allowed_commands
+    }
+
+    pub(crate) fn command_user(&self) -> Option<RunAs> {
+        self.command_user
+    }
+
+    pub(crate) fn detached(&self) -> &Detached {
+        &self.detached
+    }
+
+    /// How long a dashboard waits for its user to reconnect, if at all.
+    pub fn detach_timeout(&self) -> Option<std::time::Duration> {
+        self.detach_timeout.filter(|timeout| !timeout.is_zero())
+    }
+
+    /// How long the identity can go without input before being disconnected.
+    pub fn inactivity_timeout(&self, user: &str, groups: &[String]) -> Option<std::time::Duration> {
+        self.policy(user, groups)
+            .ok()
+            .and_then(|policy| policy.inactivity_timeout())
+            .or(self.inactivity_timeout)
+    }
+
+    /// What the identity is allowed to see in the dashboard. Like settings,
+    /// this is read when a session starts. Nobody gets a policy while they
+    /// can't be watched, see [`Policies::resolve`].
+    pub fn policy(&self, user: &str, groups: &[String]) -> Result<Policy> {
+        Ok(self
+            .policies
+            .as_ref()
+            .map(|policies| policies.resolve(user, groups))
+            .transpose()?
+            .unwrap_or_default()
+            .disable(self.disabled()))
+    }
+
+    /// Verbs turned off for everyone, from the server's flags and
+    /// `KtySettings`.
+    pub fn disabled(&self) -> Vec<Verb> {
+        self.disabled
+            .iter()
+            .copied()
+            .chain(self.settings().disabled())
+            .collect()
+    }
 }
 
-#[derive(Clone, Debug, PartialEq, ValueEnum, strum::VariantArray)]
+/// Functionality that can be enabled for connections.
+#[derive(Clone, Debug, PartialEq, ValueEnum)]
 pub enum Features {
+    /// The dashboard.
     Pty,
+    /// Forwarding from the client into the cluster (`ssh -L`).
     IngressTunnel,
+    /// Forwarding from the cluster to the client (`ssh -R`).
     EgressTunnel,
+    /// File transfer via. `sftp` and `scp`.
     Sftp,
+    /// The Kubernetes API as the user, like `kubectl proxy` (`ssh -L
+    /// 8001:kube-api:80`). Off by default.
+    ApiProxy,
 }
 
+impl Features {
+    /// The verb that turns the feature off when disabled, see
+    /// [`Controller::disabled`]. File transfers exec into containers, so they
+    /// go with `exec`. The API proxy checks every request instead.
+    pub fn verb(&self) -> Option<Verb> {
+        match self {
+            Self::IngressTunnel | Self::EgressTunnel => Some(Verb::PortForward),
+            Self::Sftp => Some(Verb::Exec),
+            Self::Pty | Self::ApiProxy => None,
+        }
+    }
+}
+
+/// What happens when a client asks for something kty never provides, such as
+/// agent or X11 forwarding. Every attempt is logged either way.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Forwarding {
+    /// Refuse the request, the rest of the session carries on.
+    #[default]
+    Deny,
+    /// Refuse the request and disconnect the client.
+    Disconnect,
+}
+
+/// The SSH server.
 #[derive(Clone, Builder)]
 pub struct UIServer {
+    /// Shared state for all sessions.
     controller: Arc<Controller>,
-    identity_provider: Arc<openid::Provider>,
+    /// Provider used to authenticate users without a key. Unset until it can
+    /// be reached when break-glass access is enabled.
+    identity_provider: openid::Shared,
+    /// Features enabled for sessions.
     features: Vec<Features>,
+    /// How to handle agent and X11 forwarding requests.
+    forwarding: Forwarding,
+    /// Whether connections start with a PROXY protocol header, see
+    /// [`proxy_protocol`].
+    proxy_protocol: bool,
 }
 
 impl UIServer {
-    pub async fn run(&mut self, cfg: Config, addr: (String, u16)) -> Result<()> {
-        self.run_on_address(Arc::new(cfg), addr).await?;
+    /// Handle incoming connections on `listener` until an error occurs.
+    pub async fn run(&mut self, cfg: Config, listener: &TcpListener) -> Result<()> {
+        if self.proxy_protocol {
+            return self.run_proxied(Arc::new(cfg), listener).await;
+        }
+
+        self.run_on_socket(Arc::new(cfg), listener).await?;
 
         Ok(())
     }
+
+    // Like `Server::run_on_socket`, except that sessions get the client address
+    // from the PROXY protocol header instead of the load balancer's. The header
+    // is required, otherwise anyone who can reach the listener directly could
+    // claim to be somewhere else.
+    async fn run_proxied(&mut self, cfg: Arc<Config>, listener: &TcpListener) -> Result<()> {
+        loop {
+            let (mut stream, peer) = listener.accept().await?;
+
+            let mut server = self.clone();
+            let cfg = cfg.clone();
+
+            tokio::spawn(async move {
+                let header =
+                    tokio::time::timeout(PROXY_HEADER_TIMEOUT, proxy_protocol::read(&mut stream))
+                        .await
+                        .unwrap_or_else(|_| Err(eyre!("timed out waiting for the header")));
+
+                // Health checks from the load balancer itself don't have a client
+                // address, they get the load balancer's.
+                let addr = match header {
+                    Ok(addr) => addr.unwrap_or(peer),
+                    Err(err) => {
+                        PROXY_REJECTED.inc();
+                        tracing::warn!("rejected connection from {peer}: {err}");
+
+                        return;
+                    }
+                };
+
+                let handler = server.new_client(Some(addr));
+
+                let session = match russh::server::run_stream(cfg, stream, handler).await {
+                    Ok(session) => session,
+                    Err(err) => {
+                        server.handle_session_error(err);
+
+                        return;
+                    }
+                };
+
+                if let Err(err) = session.await {
+                    server.handle_session_error(err);
+                }
+            });
+        }
+    }
+}
+
+// There's no need for anything cryptographically secure here, `RandomState` is
+// seeded randomly and is enough to tell sessions apart in the logs.
+fn session_id() -> String {
+    format!("{:016x}", RandomState::new().build_hasher().finish())
 }
 
 impl Server for UIServer {
     type Handler = Session;
 
-    fn new_client(&mut self, _: Option<SocketAddr>) -> Self::Handler {
+    fn new_client(&mut self, addr: Option<SocketAddr>) -> Self::Handler {
         CLIENT_COUNTER.inc();
 
+        let id = session_id();
+        self.controller.sessions().connected(id.clone(), addr);
+
         SessionBuilder::default()
             .controller(self.controller.clone())
             .identity_provider(self.identity_provider.clone())
             .features(self.features.clone())
+            .forwarding(self.forwarding)
+            .span(tracing::info_span!(
+                "session",
+                session_id = %id,
+                addr = ?addr,
+            ))
+            .id(id)
+            .addr(addr)
             .build()
             .expect("is valid session")
     }
@@ -162,7 +517,9 @@ impl Server for UIServer {
     }
 }
 
+/// Map something, such as a public key, to the identity it belongs to.
 #[async_trait::async_trait]
 pub trait Authenticate {
+    /// Returns the identity if it exists and is allowed to access the cluster.
     async fn authenticate(&self, ctrl: &Controller) -> Result<Option<Identity>>;
 }
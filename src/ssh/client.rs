@@ -0,0 +1,170 @@
+//! Clients for the API server. Every request is recorded by verb and resource,
+//! so that a slow or failing cluster can be told apart from kty itself.
+use std::{
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use futures::future::BoxFuture;
+use http::{Method, Request, Response};
+use kube::client::{Body, ClientBuilder};
+use lazy_static::lazy_static;
+use prometheus::{
+    histogram_opts, opts, register_histogram_vec, register_int_counter_vec, HistogramVec,
+    IntCounterVec,
+};
+use tower::{Layer, Service};
+
+use crate::widget::statusbar::Slow;
+
+// Calls that take longer than this are shown in the status bar of the
+// dashboard that made them.
+static SLOW_CALL: Duration = Duration::from_secs(2);
+
+lazy_static! {
+    static ref KUBE_REQUESTS: IntCounterVec = register_int_counter_vec!(
+        opts!(
+            "kube_requests_total",
+            "Number of requests made to the API server, by response code. `error` is for requests \
+             that never got a response."
+        ),
+        &["verb", "resource", "code"],
+    )
+    .unwrap();
+    static ref KUBE_REQUEST_DURATION: HistogramVec = register_histogram_vec!(
+        histogram_opts!(
+            "kube_request_duration_seconds",
+            "Time until the API server responded. Watches and streams only count until the \
+             response starts.",
+            vec![0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0],
+        ),
+        &["verb", "resource"],
+    )
+    .unwrap();
+}
+
+/// A client for `cfg` that records [`Metrics`], slow calls end up in `slow`.
+pub fn build(cfg: kube::Config, slow: Slow) -> Result<kube::Client, kube::Error> {
+    Ok(ClientBuilder::try_from(cfg)?
+        .with_layer(&Metrics { slow })
+        .build())
+}
+
+/// Records requests in prometheus and slow ones in [`Slow`].
+#[derive(Clone)]
+pub struct Metrics {
+    slow: Slow,
+}
+
+impl<S> Layer<S> for Metrics {
+    type Service = Recorded<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Recorded {
+            inner,
+            slow: self.slow.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Recorded<S> {
+    inner: S,
+    slow: Slow,
+}
+
+impl<S, B> Service<Request<Body>> for Recorded<S>
+where
+    S: Service<Request<Body>, Response = Response<B>>,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let (verb, resource) = describe(req.method(), req.uri().path(), req.uri().query());
+        let slow = self.slow.clone();
+        let start = Instant::now();
+
+        let response = self.inner.call(req);
+
+        Box::pin(async move {
+            let result = response.await;
+            let elapsed = start.elapsed();
+
+            let code = result.as_ref().map_or_else(
+                |_| "error".to_string(),
+                |resp| resp.status().as_u16().to_string(),
+            );
+
+            KUBE_REQUESTS
+                .with_label_values(&[verb, resource.as_str(), code.as_str()])
+                .inc();
+            KUBE_REQUEST_DURATION
+                .with_label_values(&[verb, resource.as_str()])
+                .observe(elapsed.as_secs_f64());
+
+            if elapsed > SLOW_CALL {
+                tracing::debug!("slow API call: {verb} {resource} took {elapsed:?}");
+
+                slow.record(format!("{verb} {resource}"), elapsed);
+            }
+
+            result
+        })
+    }
+}
+
+// The verb, as RBAC would call it, and resource of a request. Paths look like
+// `/api/v1/namespaces/default/pods/nginx/log` or
+// `/apis/apps/v1/deployments`, the namespace and name are left out to keep the
+// number of series down.
+fn describe(method: &Method, path: &str, query: Option<&str>) -> (&'static str, String) {
+    let segments: Vec<_> = path.trim_matches('/').split('/').collect();
+
+    let rest = match segments.as_slice() {
+        ["api", _, rest @ ..] | ["apis", _, _, rest @ ..] => rest,
+        _ => return (verb(method, query, false), "discovery".to_string()),
+    };
+
+    let rest = match rest {
+        ["namespaces", _, rest @ ..] if !rest.is_empty() => rest,
+        rest => rest,
+    };
+
+    let (resource, named) = match rest {
+        [] => ("discovery".to_string(), false),
+        [resource] => ((*resource).to_string(), false),
+        [resource, _] => ((*resource).to_string(), true),
+        [resource, _, subresource, ..] => (format!("{resource}/{subresource}"), true),
+    };
+
+    (verb(method, query, named), resource)
+}
+
+fn verb(method: &Method, query: Option<&str>, named: bool) -> &'static str {
+    let watch = query.is_some_and(|query| {
+        query
+            .split('&')
+            .any(|param| param == "watch=true" || param == "watch=1")
+    });
+
+    match method.as_str() {
+        "GET" if watch => "watch",
+        "GET" if named => "get",
+        "GET" => "list",
+        "POST" => "create",
+        "PUT" => "update",
+        "PATCH" => "patch",
+        "DELETE" if named => "delete",
+        "DELETE" => "deletecollection",
+        _ => "other",
+    }
+}
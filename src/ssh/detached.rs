@@ -0,0 +1,124 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::registry::Attachment;
+use crate::{events::Event, identity::Identity, io::Detachable};
+
+/// A dashboard that can outlive the connection it was started from.
+#[derive(Clone)]
+pub struct Running {
+    pub tx: UnboundedSender<Event>,
+    pub output: Detachable,
+    pub(crate) attachment: Attachment,
+}
+
+struct Parked {
+    id: u64,
+    running: Running,
+}
+
+// Dashboards are scoped to the groups they were started with, so the user only
+// gets one back when their groups are still the same.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Owner {
+    name: String,
+    groups: Vec<String>,
+}
+
+impl From<&Identity> for Owner {
+    fn from(identity: &Identity) -> Self {
+        let mut groups = identity.groups.clone();
+        groups.sort();
+        groups.dedup();
+
+        Self {
+            name: identity.name.clone(),
+            groups,
+        }
+    }
+}
+
+/// Dashboards whose connection went away, by identity. They keep running for a
+/// grace period so that the user can reconnect and pick up where they were.
+#[derive(Default)]
+pub struct Detached {
+    next: AtomicU64,
+    parked: Arc<Mutex<HashMap<Owner, Vec<Parked>>>>,
+}
+
+impl Detached {
+    /// Keep `running` around for `grace`, after which it is shut down unless
+    /// it has been taken. Dashboards that have already exited aren't kept.
+    pub fn park(&self, identity: &Identity, running: Running, grace: Duration) -> bool {
+        if running.tx.is_closed() {
+            return false;
+        }
+
+        running.output.detach();
+
+        let id = self.next.fetch_add(1, Ordering::Relaxed);
+        let owner = Owner::from(identity);
+
+        self.parked
+            .lock()
+            .unwrap()
+            .entry(owner.clone())
+            .or_default()
+            .push(Parked { id, running });
+
+        let parked = self.parked.clone();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(grace).await;
+
+            let mut parked = parked.lock().unwrap();
+
+            let Some(dashboards) = parked.get_mut(&owner) else {
+                return;
+            };
+
+            if let Some(idx) = dashboards.iter().position(|p| p.id == id) {
+                let _ = dashboards.remove(idx).running.tx.send(Event::Shutdown);
+            }
+
+            if dashboards.is_empty() {
+                parked.remove(&owner);
+            }
+        });
+
+        true
+    }
+
+    /// Number of dashboards waiting to be picked back up.
+    pub fn count(&self) -> usize {
+        self.parked.lock().unwrap().values().map(Vec::len).sum()
+    }
+
+    /// The most recently detached dashboard for `identity`, if there is one
+    /// that is still running. Only dashboards started with the same user and
+    /// groups are returned.
+    pub fn take(&self, identity: &Identity) -> Option<Running> {
+        let owner = Owner::from(identity);
+
+        let mut parked = self.parked.lock().unwrap();
+        let dashboards = parked.get_mut(&owner)?;
+
+        let running = std::iter::from_fn(|| dashboards.pop())
+            .map(|p| p.running)
+            .find(|running| !running.tx.is_closed());
+
+        if dashboards.is_empty() {
+            parked.remove(&owner);
+        }
+
+        running
+    }
+}
@@ -0,0 +1,74 @@
+//! Records written somewhere outside the server, as newline-delimited JSON, so
+//! that they can be collected by a SIEM.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    os::unix::net::UnixDatagram,
+    path::PathBuf,
+    str::FromStr,
+    sync::Mutex,
+};
+
+use eyre::Result;
+
+use super::registry::Record;
+
+// `authpriv.info`, where syslog expects security related messages.
+static SYSLOG_PRIORITY: u8 = 10 * 8 + 6;
+
+/// Where records are exported to. Parsed from either a path to a file, which
+/// is appended to, or `syslog:<socket>` such as `syslog:/dev/log`.
+pub enum Sink {
+    File(Mutex<File>),
+    Syslog(UnixDatagram),
+}
+
+impl FromStr for Sink {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(socket) = s.strip_prefix("syslog:") {
+            let sock = UnixDatagram::unbound()?;
+            sock.connect(socket)?;
+
+            return Ok(Self::Syslog(sock));
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(PathBuf::from(s))?;
+
+        Ok(Self::File(Mutex::new(file)))
+    }
+}
+
+impl Sink {
+    /// Export `record`. Failures are logged instead of returned, what
+    /// happened on the server shouldn't depend on the export.
+    pub fn write(&self, record: &Record) {
+        if let Err(err) = self.try_write(record) {
+            tracing::warn!("unable to export record: {err}");
+        }
+    }
+
+    fn try_write(&self, record: &Record) -> Result<()> {
+        let line = serde_json::to_string(record)?;
+
+        match self {
+            Self::File(file) => {
+                let mut file = file.lock().unwrap();
+
+                file.write_all(line.as_bytes())?;
+                file.write_all(b"\n")?;
+                file.flush()?;
+            }
+            Self::Syslog(sock) => {
+                sock.send(format!("<{SYSLOG_PRIORITY}>kty: {line}").as_bytes())?;
+            }
+        }
+
+        Ok(())
+    }
+}
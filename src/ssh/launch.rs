@@ -0,0 +1,340 @@
+use std::{
+    collections::HashMap,
+    fs::DirBuilder,
+    io::Write,
+    os::unix::fs::{chown, DirBuilderExt, MetadataExt, OpenOptionsExt},
+    path::PathBuf,
+    process::Stdio,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use data_encoding::HEXLOWER;
+use eyre::{eyre, Result};
+use kube::config::{
+    AuthInfo, Cluster, Context, Kubeconfig, NamedAuthInfo, NamedCluster, NamedContext,
+};
+use rand::{rngs::OsRng, RngCore};
+use secrecy::SecretString;
+use tokio::{
+    net::TcpListener,
+    process::{Child, Command},
+    runtime::Handle,
+    task::AbortHandle,
+};
+use tracing::Instrument;
+
+use super::{session::proxy, Controller};
+use crate::{identity::Identity, launch::Launch};
+
+// Every launch gets its own home directory, kubeconfig and endpoint.
+static ENDPOINTS: AtomicU64 = AtomicU64::new(0);
+// Name of the cluster, user and context in the kubeconfig given to commands.
+static CONTEXT: &str = "kty";
+static TOKEN_BYTES: usize = 16;
+// Where Kubernetes mounts the server's service account token.
+static SERVICE_ACCOUNT_TOKEN: &str = "/var/run/secrets/kubernetes.io/serviceaccount/token";
+
+/// The user, and group, that commands run as instead of the server's own, see
+/// `kty serve --command-user`. Written as `uid` or `uid:gid`, the group is the
+/// same as the user when left out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RunAs {
+    pub uid: u32,
+    pub gid: u32,
+}
+
+impl FromStr for RunAs {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (uid, gid) = s.split_once(':').unwrap_or((s, s));
+
+        Ok(Self {
+            uid: uid.parse()?,
+            gid: gid.parse()?,
+        })
+    }
+}
+
+impl RunAs {
+    /// Whether the server's service account token can be read as this user,
+    /// going by the file's owner and mode. Supplementary groups are dropped
+    /// when commands start, so only `gid` counts.
+    pub fn reads_token(self) -> Result<bool> {
+        let metadata = std::fs::metadata(SERVICE_ACCOUNT_TOKEN)?;
+        let mode = metadata.mode();
+
+        Ok(mode & 0o004 != 0
+            || (metadata.uid() == self.uid && mode & 0o400 != 0)
+            || (metadata.gid() == self.gid && mode & 0o040 != 0))
+    }
+}
+
+/// Runs commands from `KtySettings.commands` on the server for a session. Only
+/// programs in `kty serve --allow-command` can be started. They get no
+/// environment from the server besides `PATH`, each command talks to the API
+/// server through its own local [`proxy`] endpoint, which impersonates the
+/// user.
+///
+/// Commands run as the server's OS user unless `kty serve --command-user` is
+/// set. That user can read the server's service account token, which is far
+/// more powerful than the session's user.
+pub(crate) struct Launcher {
+    controller: Arc<Controller>,
+    session_id: String,
+    identity: Identity,
+    runtime: Handle,
+    // Keyed by the pid of the command each is for, see [`Launch::exited`].
+    endpoints: Mutex<HashMap<u32, Endpoint>>,
+}
+
+impl Launcher {
+    // The dashboard runs on a runtime of its own, endpoints are served from the
+    // one this is created on so that they don't hold up rendering.
+    pub fn new(
+        controller: Arc<Controller>,
+        session_id: String,
+        identity: &Identity,
+    ) -> Result<Self> {
+        Ok(Self {
+            controller,
+            session_id,
+            identity: identity.clone(),
+            runtime: Handle::try_current()?,
+            endpoints: Mutex::default(),
+        })
+    }
+
+    fn endpoint(&self) -> Result<Endpoint> {
+        let home = std::env::temp_dir().join(format!(
+            "kty-{}-{}",
+            self.session_id,
+            ENDPOINTS.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        // Commands can leave anything in their home directory, such as a cache with
+        // responses from the API server, so nobody else gets to look.
+        DirBuilder::new().mode(0o700).create(&home)?;
+
+        let mut endpoint = Endpoint { home, server: None };
+
+        if let Some(user) = self.controller.command_user() {
+            chown(&endpoint.home, Some(user.uid), Some(user.gid))?;
+        }
+
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 0))?;
+        listener.set_nonblocking(true)?;
+        let port = listener.local_addr()?.port();
+
+        let mut token = [0u8; TOKEN_BYTES];
+        OsRng.fill_bytes(&mut token);
+        let token = HEXLOWER.encode(&token);
+
+        let client = self
+            .controller
+            .impersonate(self.identity.name.clone(), self.identity.groups.clone())?;
+
+        endpoint.server = Some(
+            self.runtime
+                .spawn(
+                    accept(
+                        {
+                            let _guard = self.runtime.enter();
+                            TcpListener::from_std(listener)?
+                        },
+                        client,
+                        self.controller.clone(),
+                        self.session_id.clone(),
+                        self.identity.clone(),
+                        token.clone(),
+                    )
+                    .in_current_span(),
+                )
+                .abort_handle(),
+        );
+
+        let config = kubeconfig_for(
+            format!("http://127.0.0.1:{port}"),
+            token,
+            self.controller.config.default_namespace.clone(),
+        );
+
+        // The token is only good for this endpoint, but nobody else needs it.
+        std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(endpoint.kubeconfig())?
+            .write_all(serde_yaml::to_string(&config)?.as_bytes())?;
+
+        if let Some(user) = self.controller.command_user() {
+            chown(endpoint.kubeconfig(), Some(user.uid), Some(user.gid))?;
+        }
+
+        Ok(endpoint)
+    }
+}
+
+impl Launch for Launcher {
+    fn launch(&self, argv: &[String]) -> Result<Child> {
+        let (program, args) = argv.split_first().ok_or(eyre!("empty command"))?;
+
+        if !self
+            .controller
+            .allowed_commands()
+            .iter()
+            .any(|allowed| allowed == program)
+        {
+            return Err(eyre!("{program} isn't allowed on this server"));
+        }
+
+        let endpoint = self.endpoint()?;
+
+        self.controller
+            .sessions()
+            .command(&self.session_id, format!("command: {}", argv.join(" ")));
+
+        let mut cmd = Command::new(program);
+        cmd.args(args)
+            .env_clear()
+            .env("KUBECONFIG", endpoint.kubeconfig())
+            .env("HOME", &endpoint.home)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        if let Some(path) = std::env::var_os("PATH") {
+            cmd.env("PATH", path);
+        }
+
+        if let Some(user) = self.controller.command_user() {
+            cmd.uid(user.uid).gid(user.gid);
+        }
+
+        let child = cmd.spawn()?;
+
+        let pid = child.id().ok_or(eyre!("command exited immediately"))?;
+
+        self.endpoints
+            .lock()
+            .map_err(|_| eyre!("launcher lock poisoned"))?
+            .insert(pid, endpoint);
+
+        Ok(child)
+    }
+
+    fn exited(&self, pid: u32) {
+        let endpoint = self
+            .endpoints
+            .lock()
+            .ok()
+            .and_then(|mut endpoints| endpoints.remove(&pid));
+
+        // Dropped outside of the lock, removing the directory can take a moment.
+        drop(endpoint);
+    }
+}
+
+// The home directory, kubeconfig and server for a single launch. They all go
+// away when it is dropped, including when setting it up fails part way
+// through.
+struct Endpoint {
+    home: PathBuf,
+    server: Option<AbortHandle>,
+}
+
+impl Endpoint {
+    fn kubeconfig(&self) -> PathBuf {
+        self.home.join("kubeconfig")
+    }
+}
+
+impl Drop for Endpoint {
+    fn drop(&mut self) {
+        if let Some(server) = self.server.take() {
+            server.abort();
+        }
+
+        match std::fs::remove_dir_all(&self.home) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => {
+                tracing::warn!(?err, path = ?self.home, "unable to remove home directory");
+            }
+        }
+    }
+}
+
+async fn accept(
+    listener: TcpListener,
+    client: kube::Client,
+    controller: Arc<Controller>,
+    id: String,
+    identity: Identity,
+    token: String,
+) {
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                tracing::warn!(?err, "unable to accept command connection");
+
+                continue;
+            }
+        };
+
+        let serve = proxy::serve(
+            client.clone(),
+            controller.clone(),
+            id.clone(),
+            identity.clone(),
+            stream,
+            Some(token.clone()),
+        );
+
+        tokio::spawn(
+            async move {
+                if let Err(err) = serve.await {
+                    tracing::debug!(?err, "command connection closed");
+                }
+            }
+            .in_current_span(),
+        );
+    }
+}
+
+fn kubeconfig_for(server: String, token: String, namespace: String) -> Kubeconfig {
+    Kubeconfig {
+        clusters: vec![NamedCluster {
+            name: CONTEXT.to_string(),
+            cluster: Some(Cluster {
+                server: Some(server),
+                ..Default::default()
+            }),
+        }],
+        auth_infos: vec![NamedAuthInfo {
+            name: CONTEXT.to_string(),
+            auth_info: Some(AuthInfo {
+                token: Some(SecretString::new(token)),
+                ..Default::default()
+            }),
+        }],
+        contexts: vec![NamedContext {
+            name: CONTEXT.to_string(),
+            context: Some(Context {
+                cluster: CONTEXT.to_string(),
+                user: CONTEXT.to_string(),
+                namespace: Some(namespace),
+                extensions: None,
+            }),
+        }],
+        current_context: Some(CONTEXT.to_string()),
+        ..Default::default()
+    }
+}
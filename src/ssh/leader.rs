@@ -0,0 +1,203 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use eyre::Result;
+use k8s_openapi::{
+    api::coordination::v1::{Lease, LeaseSpec},
+    apimachinery::pkg::apis::meta::v1::MicroTime,
+};
+use kube::{
+    api::{Api, ObjectMeta, PostParams},
+    ResourceExt,
+};
+use lazy_static::lazy_static;
+use prometheus::{register_int_gauge, IntGauge};
+use tokio::task::JoinHandle;
+
+lazy_static! {
+    static ref LEADER: IntGauge = register_int_gauge!(
+        "leader",
+        "Whether this replica holds the lease, see `--leader-election`"
+    )
+    .unwrap();
+}
+
+// How long the lease is held for without being renewed.
+static LEASE_DURATION: Duration = Duration::from_secs(15);
+// How often the lease is renewed or, while another replica holds it, checked.
+static RETRY_EVERY: Duration = Duration::from_secs(5);
+
+/// Whether this replica holds the lease, for work that runs on a schedule and
+/// only needs doing once, see [`Election::leader`].
+#[derive(Clone, Debug)]
+pub struct Leader(Arc<AtomicBool>);
+
+impl Leader {
+    /// For servers that run without an election, there's only the one replica.
+    pub fn always() -> Self {
+        Self(Arc::new(AtomicBool::new(true)))
+    }
+
+    pub fn is_leading(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Coordinates replicas via. a `Lease` so that only one of them does the work
+/// that shouldn't be duplicated, such as creating CRDs. Every replica accepts
+/// sessions either way.
+pub struct Election {
+    api: Api<Lease>,
+    name: String,
+    identity: String,
+    leading: bool,
+    leader: Leader,
+}
+
+impl Election {
+    /// The lease is `name` in `namespace` and this replica holds it as
+    /// `identity`, normally the name of its pod.
+    pub fn new(client: kube::Client, namespace: &str, name: &str, identity: String) -> Self {
+        Self {
+            api: Api::namespaced(client, namespace),
+            name: name.to_string(),
+            identity,
+            leading: false,
+            leader: Leader(Arc::new(AtomicBool::new(false))),
+        }
+    }
+
+    /// Follows whether this replica holds the lease, as of the last attempt.
+    pub fn leader(&self) -> Leader {
+        self.leader.clone()
+    }
+
+    /// Try to acquire or renew the lease. True only when this replica has just
+    /// become the leader.
+    pub async fn step(&mut self) -> bool {
+        let leading = match self.acquire().await {
+            Ok(leading) => leading,
+            Err(err) => {
+                tracing::warn!(?err, lease = self.name, "unable to acquire lease");
+
+                false
+            }
+        };
+
+        let became = leading && !self.leading;
+
+        if leading != self.leading {
+            tracing::info!(lease = self.name, leading, "leadership changed");
+            LEADER.set(i64::from(leading));
+        }
+
+        self.leading = leading;
+        self.leader.0.store(leading, Ordering::Relaxed);
+
+        became
+    }
+
+    /// Keep the lease, or keep trying for it, forever. `duties` runs every time
+    /// this replica becomes the leader. They run in the background so that the
+    /// lease keeps being renewed while they do, and are stopped if it is lost.
+    pub async fn run<F, Fut>(mut self, duties: F)
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut running: Option<JoinHandle<()>> = None;
+
+        loop {
+            tokio::time::sleep(RETRY_EVERY).await;
+
+            if self.step().await {
+                running = Some(tokio::spawn(duties()));
+            } else if !self.leading {
+                if let Some(handle) = running.take() {
+                    handle.abort();
+                }
+            }
+        }
+    }
+
+    async fn acquire(&self) -> Result<bool> {
+        let now = Utc::now();
+
+        let Some(mut lease) = self.api.get_opt(&self.name).await? else {
+            return self
+                .write(
+                    Lease {
+                        metadata: ObjectMeta {
+                            name: Some(self.name.clone()),
+                            ..Default::default()
+                        },
+                        spec: None,
+                    },
+                    now,
+                )
+                .await;
+        };
+
+        let spec = lease.spec.get_or_insert_with(LeaseSpec::default);
+        let held = spec.holder_identity.as_deref() == Some(self.identity.as_str());
+
+        if !held && !expired(spec, now) {
+            return Ok(false);
+        }
+
+        self.write(lease, now).await
+    }
+
+    // The resource version of `lease` makes sure that only one replica wins when
+    // several try at once, the others get a conflict.
+    async fn write(&self, mut lease: Lease, now: DateTime<Utc>) -> Result<bool> {
+        let previous = lease.spec.take().unwrap_or_default();
+        let held = previous.holder_identity.as_deref() == Some(self.identity.as_str());
+
+        lease.spec = Some(LeaseSpec {
+            holder_identity: Some(self.identity.clone()),
+            lease_duration_seconds: Some(i32::try_from(LEASE_DURATION.as_secs())?),
+            acquire_time: if held {
+                previous.acquire_time
+            } else {
+                Some(MicroTime(now))
+            },
+            renew_time: Some(MicroTime(now)),
+            lease_transitions: Some(
+                previous.lease_transitions.unwrap_or_default() + i32::from(!held),
+            ),
+            ..Default::default()
+        });
+
+        let result = if lease.resource_version().is_some() {
+            self.api
+                .replace(&self.name, &PostParams::default(), &lease)
+                .await
+        } else {
+            self.api.create(&PostParams::default(), &lease).await
+        };
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(kube::Error::Api(err)) if err.code == 409 => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+fn expired(spec: &LeaseSpec, now: DateTime<Utc>) -> bool {
+    let Some(MicroTime(renewed)) = spec.renew_time else {
+        return true;
+    };
+
+    let duration = TimeDelta::seconds(i64::from(spec.lease_duration_seconds.unwrap_or_default()));
+
+    renewed + duration < now
+}
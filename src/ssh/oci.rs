@@ -0,0 +1,202 @@
+use std::{collections::HashMap, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use eyre::{eyre, Result};
+use reqwest::{header, StatusCode};
+use serde::Deserialize;
+
+use crate::{
+    image::{Enrichment, Manifest, Reference, Registry, Scanner},
+    openid,
+    resources::image::ImageSettings,
+};
+
+// Everything a tag can point at. Indexes are for images built for more than
+// one platform.
+static ACCEPT: &str = "application/vnd.oci.image.index.v1+json, \
+                       application/vnd.docker.distribution.manifest.list.v2+json, \
+                       application/vnd.oci.image.manifest.v1+json, \
+                       application/vnd.docker.distribution.manifest.v2+json";
+
+/// What the `images` tab gets details from, see [`ImageSettings`].
+pub fn enrichment(settings: ImageSettings) -> Enrichment {
+    Enrichment {
+        registry: settings
+            .registry
+            .unwrap_or_default()
+            .then(|| Arc::new(Oci::default()) as Arc<dyn Registry>),
+        scanner: settings
+            .scanner
+            .map(|scanner| Arc::new(scanner) as Arc<dyn Scanner>),
+    }
+}
+
+#[derive(Deserialize)]
+struct Token {
+    token: Option<String>,
+    access_token: Option<String>,
+}
+
+// Either an index or a manifest, only one of the two fields is set.
+#[derive(Deserialize)]
+struct Document {
+    #[serde(default)]
+    manifests: Vec<Descriptor>,
+    config: Option<Descriptor>,
+}
+
+#[derive(Deserialize)]
+struct Descriptor {
+    digest: String,
+    platform: Option<Platform>,
+}
+
+#[derive(Deserialize)]
+struct Platform {
+    os: String,
+    architecture: String,
+}
+
+#[derive(Deserialize)]
+struct Config {
+    created: Option<DateTime<Utc>>,
+}
+
+/// Looks up images with the OCI distribution API, anonymously. Registries that
+/// require credentials to pull fail the lookup and the image is shown without
+/// details.
+#[derive(Default)]
+pub struct Oci {
+    client: reqwest::Client,
+}
+
+impl Oci {
+    async fn get(&self, url: &str, token: &mut Option<String>) -> Result<reqwest::Response> {
+        openid::check(url)?;
+
+        let mut response = self.send(url, token.as_deref()).await?;
+
+        if response.status() == StatusCode::UNAUTHORIZED && token.is_none() {
+            let challenge = response
+                .headers()
+                .get(header::WWW_AUTHENTICATE)
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(|| eyre!("unauthorized without a challenge: {url}"))?
+                .to_string();
+
+            *token = Some(self.token(&challenge).await?);
+            response = self.send(url, token.as_deref()).await?;
+        }
+
+        Ok(response.error_for_status()?)
+    }
+
+    async fn send(&self, url: &str, token: Option<&str>) -> Result<reqwest::Response> {
+        let mut request = self.client.get(url).header(header::ACCEPT, ACCEPT);
+
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+
+        Ok(request.send().await?)
+    }
+
+    // Exchange a challenge, such as `Bearer realm="...",service="...",scope="..."`,
+    // for an anonymous token.
+    async fn token(&self, challenge: &str) -> Result<String> {
+        let params: HashMap<&str, &str> = challenge
+            .strip_prefix("Bearer ")
+            .ok_or_else(|| eyre!("unsupported challenge: {challenge}"))?
+            // Scopes can have commas in them, values are always quoted.
+            .split("\",")
+            .filter_map(|param| {
+                let (key, value) = param.split_once('=')?;
+
+                Some((key.trim(), value.trim().trim_matches('"')))
+            })
+            .collect();
+
+        let realm = params
+            .get("realm")
+            .ok_or_else(|| eyre!("challenge without a realm: {challenge}"))?;
+
+        openid::check(realm)?;
+
+        let query: Vec<_> = params.iter().filter(|(key, _)| **key != "realm").collect();
+
+        let token: Token = self
+            .client
+            .get(*realm)
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        token
+            .token
+            .or(token.access_token)
+            .ok_or_else(|| eyre!("no token returned by {realm}"))
+    }
+}
+
+#[async_trait::async_trait]
+impl Registry for Oci {
+    async fn manifest(&self, image: &Reference) -> Result<Manifest> {
+        let host = match image.registry.as_str() {
+            "docker.io" => "registry-1.docker.io",
+            host => host,
+        };
+        let base = format!("https://{host}/v2/{}", image.repository);
+        let mut token = None;
+
+        let response = self
+            .get(
+                &format!(
+                    "{base}/manifests/{}",
+                    image.digest.as_deref().unwrap_or(&image.tag)
+                ),
+                &mut token,
+            )
+            .await?;
+
+        let digest = response
+            .headers()
+            .get("docker-content-digest")
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string);
+
+        let mut document: Document = response.json().await?;
+
+        // The config, which has when the image was built, is in the manifest
+        // for a platform. Nodes are most likely to be `linux/amd64`.
+        let platform = document
+            .manifests
+            .iter()
+            .find(|manifest| {
+                manifest.platform.as_ref().is_some_and(|platform| {
+                    platform.os == "linux" && platform.architecture == "amd64"
+                })
+            })
+            .or_else(|| document.manifests.first())
+            .map(|manifest| format!("{base}/manifests/{}", manifest.digest));
+
+        if let Some(url) = platform {
+            document = self.get(&url, &mut token).await?.json().await?;
+        }
+
+        let created = match document.config {
+            Some(config) => {
+                self.get(&format!("{base}/blobs/{}", config.digest), &mut token)
+                    .await?
+                    .json::<Config>()
+                    .await?
+                    .created
+            }
+            None => None,
+        };
+
+        Ok(Manifest { digest, created })
+    }
+}
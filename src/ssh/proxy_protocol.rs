@@ -0,0 +1,99 @@
+//! The PROXY protocol, which load balancers use to pass along where a
+//! connection really came from. Both the text (v1) and binary (v2) versions are
+//! supported, see <https://www.haproxy.org/download/2.9/doc/proxy-protocol.txt>.
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use eyre::{eyre, Result};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+static SIGNATURE: &[u8; 12] = b"\r\n\r\n\0\r\nQUIT\n";
+// The longest a v1 header can be, including the trailing CRLF.
+static V1_MAX: usize = 107;
+
+/// Read the header from the start of `stream`, returning the address of the
+/// client on the other side of the load balancer. Connections the load
+/// balancer made itself, such as health checks, are `None`. Nothing past the
+/// header is read, the rest of the stream is left for SSH.
+pub async fn read<S: AsyncRead + Unpin>(stream: &mut S) -> Result<Option<SocketAddr>> {
+    // The shortest v1 header, `PROXY UNKNOWN\r\n`, is longer than this so it is
+    // safe to read either way.
+    let mut prefix = [0u8; 12];
+    stream.read_exact(&mut prefix).await?;
+
+    if &prefix == SIGNATURE {
+        v2(stream).await
+    } else if prefix.starts_with(b"PROXY ") {
+        v1(stream, &prefix).await
+    } else {
+        Err(eyre!("missing PROXY protocol header"))
+    }
+}
+
+async fn v1<S: AsyncRead + Unpin>(stream: &mut S, prefix: &[u8]) -> Result<Option<SocketAddr>> {
+    let mut line = prefix.to_vec();
+
+    // There's no length up front, so this goes a byte at a time to avoid reading
+    // into the SSH handshake.
+    while !line.ends_with(b"\r\n") {
+        if line.len() >= V1_MAX {
+            return Err(eyre!("PROXY header is too long"));
+        }
+
+        line.push(stream.read_u8().await?);
+    }
+
+    let line = std::str::from_utf8(&line[..line.len() - 2])?;
+    let fields: Vec<_> = line.split(' ').collect();
+
+    match fields.as_slice() {
+        ["PROXY", "UNKNOWN", ..] => Ok(None),
+        ["PROXY", "TCP4" | "TCP6", src, _, port, _] => {
+            Ok(Some(SocketAddr::new(src.parse::<IpAddr>()?, port.parse()?)))
+        }
+        _ => Err(eyre!("invalid PROXY header: {line}")),
+    }
+}
+
+async fn v2<S: AsyncRead + Unpin>(stream: &mut S) -> Result<Option<SocketAddr>> {
+    let mut header = [0u8; 4];
+    stream.read_exact(&mut header).await?;
+
+    let [version, family, len @ ..] = header;
+
+    if version >> 4 != 2 {
+        return Err(eyre!(
+            "unsupported PROXY protocol version: {}",
+            version >> 4
+        ));
+    }
+
+    // Everything after the addresses (TLVs) is read and ignored.
+    let mut body = vec![0u8; usize::from(u16::from_be_bytes(len))];
+    stream.read_exact(&mut body).await?;
+
+    match version & 0x0f {
+        // LOCAL
+        0x0 => return Ok(None),
+        // PROXY
+        0x1 => {}
+        command => return Err(eyre!("unsupported PROXY command: {command}")),
+    }
+
+    // Only the source address and port are of interest, they come first.
+    let addr = match family >> 4 {
+        // AF_INET
+        0x1 if body.len() >= 12 => SocketAddr::new(
+            IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(&body[0..4])?)),
+            u16::from_be_bytes([body[8], body[9]]),
+        ),
+        // AF_INET6
+        0x2 if body.len() >= 36 => SocketAddr::new(
+            IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(&body[0..16])?)),
+            u16::from_be_bytes([body[32], body[33]]),
+        ),
+        // AF_UNSPEC and AF_UNIX don't have anything useful.
+        _ => return Ok(None),
+    };
+
+    Ok(Some(addr))
+}
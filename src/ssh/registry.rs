@@ -0,0 +1,648 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use data_encoding::HEXLOWER;
+use eyre::Result;
+use rand::{rngs::OsRng, RngCore};
+use ringbuffer::{AllocRingBuffer, RingBuffer};
+use russh::ChannelId;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tokio_util::{bytes::Bytes, sync::CancellationToken};
+
+use super::{export::Sink, Controller};
+use crate::{
+    audit::{Audit, ExecSession},
+    broadcast::Broadcast,
+    events::Event,
+    share::Share,
+};
+
+static AUDIT_SIZE: usize = 1000;
+// Share codes are 128 bits, anyone who has one can watch the dashboard.
+static SHARE_CODE_BYTES: usize = 16;
+// Share codes are secrets, the audit log only gets enough of them to tell
+// shares apart.
+static SHARE_CODE_PREFIX: usize = 8;
+
+fn share_code() -> String {
+    let mut bytes = [0; SHARE_CODE_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+
+    HEXLOWER.encode(&bytes)
+}
+
+fn redacted(code: &str) -> String {
+    format!(
+        "{}...",
+        code.chars().take(SHARE_CODE_PREFIX).collect::<String>()
+    )
+}
+
+/// A connection to the server.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SessionInfo {
+    /// ID used in logs for everything done on behalf of this session.
+    pub id: String,
+    /// Authenticated user, unset until authentication has succeeded.
+    pub user: Option<String>,
+    /// Address of the client.
+    pub addr: Option<SocketAddr>,
+    /// When the connection was made.
+    pub started: DateTime<Utc>,
+    /// Last time the client sent anything.
+    pub last_activity: DateTime<Utc>,
+}
+
+/// The type of a [`Record`], stable so that it can be matched on.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    Connected,
+    Authenticated,
+    AuthenticationFailed,
+    Disconnected,
+    SharingStarted,
+    SharingStopped,
+    Watching,
+    Message,
+    KeyBound,
+    KeyApproved,
+    KeyRevoked,
+    Terminated,
+    BreakGlass,
+    ApiRequest,
+    Exec,
+    Command,
+}
+
+/// Something that happened on the server. This is also the exported schema,
+/// fields can be added but not changed.
+#[derive(Clone, Debug, Serialize)]
+pub struct Record {
+    /// When it happened.
+    pub time: DateTime<Utc>,
+    /// What kind of thing happened.
+    pub kind: Kind,
+    /// Session it happened in, if any.
+    pub session_id: Option<String>,
+    /// User that did it, if known.
+    pub user: Option<String>,
+    /// Address of the client, if it happened in a session.
+    pub addr: Option<SocketAddr>,
+    /// What happened, for people.
+    pub action: String,
+    /// The shell, for `exec` records.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exec: Option<ExecSession>,
+}
+
+struct Entry {
+    info: SessionInfo,
+    // Name of the `Key` the session authenticated with, if it used one.
+    key: Option<String>,
+    broadcast: Option<Broadcast>,
+    terminate: CancellationToken,
+}
+
+/// A dashboard that others can watch.
+#[derive(Clone)]
+pub(crate) struct Shared {
+    /// Everything written to the dashboard's terminal.
+    pub output: broadcast::Sender<Bytes>,
+    /// Cancelled once the owner stops sharing.
+    pub stopped: CancellationToken,
+    // The dashboard's channel, used to ask it to redraw.
+    attachment: Attachment,
+}
+
+impl Shared {
+    /// Ask the dashboard to write the entire screen out again. Output only
+    /// contains changes, so this is required for observers to get in sync.
+    pub async fn redraw(&self) -> Result<()> {
+        let Attached {
+            broadcast, channel, ..
+        } = self.attachment.get();
+
+        broadcast.send(&channel, Event::Redraw).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+struct Attached {
+    session_id: String,
+    broadcast: Broadcast,
+    channel: ChannelId,
+}
+
+/// The session and channel a dashboard is attached to. A detached dashboard is
+/// picked back up by a new session, everything recorded for it from then on
+/// belongs to that one, see [`Attachment::rebind`].
+#[derive(Clone)]
+pub(crate) struct Attachment(Arc<Mutex<Attached>>);
+
+impl Attachment {
+    pub fn new(session_id: String, broadcast: Broadcast, channel: ChannelId) -> Self {
+        Self(Arc::new(Mutex::new(Attached {
+            session_id,
+            broadcast,
+            channel,
+        })))
+    }
+
+    /// The dashboard was reattached to `channel` of the session with
+    /// `session_id`.
+    pub fn rebind(&self, session_id: String, broadcast: Broadcast, channel: ChannelId) {
+        *self.0.lock().unwrap() = Attached {
+            session_id,
+            broadcast,
+            channel,
+        };
+    }
+
+    fn get(&self) -> Attached {
+        self.0.lock().unwrap().clone()
+    }
+
+    fn session_id(&self) -> String {
+        self.0.lock().unwrap().session_id.clone()
+    }
+}
+
+/// Active sessions and a bounded log of what they've done. This is in-memory
+/// only and is local to the server it is running on.
+pub struct Registry {
+    sessions: Mutex<HashMap<String, Entry>>,
+    shares: Mutex<HashMap<String, Shared>>,
+    audit: Mutex<AllocRingBuffer<Record>>,
+    export: Option<Sink>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self {
+            sessions: Mutex::default(),
+            shares: Mutex::default(),
+            audit: Mutex::new(AllocRingBuffer::new(AUDIT_SIZE)),
+            export: None,
+        }
+    }
+}
+
+impl Registry {
+    /// Every record is also written to `export`, see [`Sink`].
+    pub fn with_export(export: Sink) -> Self {
+        Self {
+            export: Some(export),
+            ..Self::default()
+        }
+    }
+
+    fn record(&self, info: Option<&SessionInfo>, kind: Kind, action: String) {
+        self.push(Record {
+            time: Utc::now(),
+            kind,
+            session_id: info.map(|i| i.id.clone()),
+            user: info.and_then(|i| i.user.clone()),
+            addr: info.and_then(|i| i.addr),
+            action,
+            exec: None,
+        });
+    }
+
+    fn push(&self, record: Record) {
+        if let Some(export) = &self.export {
+            export.write(&record);
+        }
+
+        self.audit.lock().unwrap().push(record);
+    }
+
+    pub(crate) fn connected(&self, id: String, addr: Option<SocketAddr>) {
+        let now = Utc::now();
+        let info = SessionInfo {
+            id: id.clone(),
+            user: None,
+            addr,
+            started: now,
+            last_activity: now,
+        };
+
+        self.record(Some(&info), Kind::Connected, "connected".to_string());
+
+        self.sessions.lock().unwrap().insert(
+            id,
+            Entry {
+                info,
+                key: None,
+                broadcast: None,
+                terminate: CancellationToken::new(),
+            },
+        );
+    }
+
+    pub(crate) fn authenticated(
+        &self,
+        id: &str,
+        user: String,
+        method: &str,
+        key: Option<String>,
+        broadcast: Broadcast,
+    ) {
+        let mut sessions = self.sessions.lock().unwrap();
+
+        let Some(entry) = sessions.get_mut(id) else {
+            return;
+        };
+
+        entry.info.user = Some(user);
+        entry.key = key;
+        entry.broadcast = Some(broadcast);
+
+        self.record(
+            Some(&entry.info),
+            Kind::Authenticated,
+            format!("authenticated via {method}"),
+        );
+    }
+
+    pub(crate) fn disconnected(&self, id: &str) {
+        let entry = self.sessions.lock().unwrap().remove(id);
+
+        if let Some(entry) = entry {
+            self.record(
+                Some(&entry.info),
+                Kind::Disconnected,
+                "disconnected".to_string(),
+            );
+        }
+    }
+
+    /// The client sent something, used to show how long sessions have been
+    /// idle.
+    pub(crate) fn active(&self, id: &str) {
+        if let Some(entry) = self.sessions.lock().unwrap().get_mut(id) {
+            entry.info.last_activity = Utc::now();
+        }
+    }
+
+    /// How long it has been since the client sent anything.
+    pub(crate) fn idle(&self, id: &str) -> Option<std::time::Duration> {
+        self.sessions.lock().unwrap().get(id).map(|e| {
+            (Utc::now() - e.info.last_activity)
+                .to_std()
+                .unwrap_or_default()
+        })
+    }
+
+    /// Cancelled once an admin asks for the session to be terminated, see
+    /// [`Registry::terminate`].
+    pub(crate) fn terminated(&self, id: &str) -> Option<CancellationToken> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(id)
+            .map(|e| e.terminate.clone())
+    }
+
+    /// A user proved who they are but isn't allowed in.
+    pub(crate) fn rejected(&self, id: &str, user: &str) {
+        self.record_for(
+            id,
+            Kind::AuthenticationFailed,
+            format!("authentication failed: {user} is not allowed"),
+        );
+    }
+
+    /// Break-glass access was offered or used, see
+    /// [`crate::identity::otp::BreakGlass`]. These are also logged as warnings.
+    pub(crate) fn break_glass(&self, id: &str, action: String) {
+        tracing::warn!(action, "break-glass");
+
+        self.record_for(id, Kind::BreakGlass, action);
+    }
+
+    fn record_for(&self, id: &str, kind: Kind, action: String) {
+        let info = self
+            .sessions
+            .lock()
+            .unwrap()
+            .get(id)
+            .map(|e| e.info.clone());
+
+        self.record(info.as_ref(), kind, action);
+    }
+
+    fn share(&self, id: &str, shared: Shared) -> String {
+        let code = share_code();
+
+        self.shares.lock().unwrap().insert(code.clone(), shared);
+        self.record_for(
+            id,
+            Kind::SharingStarted,
+            format!("started sharing: {}", redacted(&code)),
+        );
+
+        code
+    }
+
+    fn unshare(&self, id: &str, code: &str) {
+        self.shares.lock().unwrap().remove(code);
+        self.record_for(
+            id,
+            Kind::SharingStopped,
+            format!("stopped sharing: {}", redacted(code)),
+        );
+    }
+
+    /// Start watching the dashboard shared with `code`.
+    pub(crate) fn watch(&self, id: &str, code: &str) -> Option<Shared> {
+        let shared = self.shares.lock().unwrap().get(code).cloned();
+
+        if shared.is_some() {
+            self.record_for(id, Kind::Watching, format!("watching: {}", redacted(code)));
+        }
+
+        shared
+    }
+
+    /// A key was bound to a user while onboarding.
+    pub(crate) fn key_bound(&self, id: &str, action: String) {
+        self.record_for(id, Kind::KeyBound, action);
+    }
+
+    /// A request made to the API server through the proxy, see
+    /// `session::proxy`.
+    pub(crate) fn api_request(&self, id: &str, request: String) {
+        self.record_for(id, Kind::ApiRequest, request);
+    }
+
+    /// A command from `KtySettings.commands` was started, see
+    /// `launch::Launcher`.
+    pub(crate) fn command(&self, id: &str, action: String) {
+        self.record_for(id, Kind::Command, action);
+    }
+
+    /// A shell opened from the dashboard has exited, see [`ExecAudit`].
+    pub(crate) fn exec(&self, id: &str, session: ExecSession) {
+        let info = self
+            .sessions
+            .lock()
+            .unwrap()
+            .get(id)
+            .map(|e| e.info.clone());
+
+        let exit = session
+            .exit_code
+            .map_or_else(|| "unknown".to_string(), |code| code.to_string());
+
+        self.push(Record {
+            time: Utc::now(),
+            kind: Kind::Exec,
+            session_id: info.as_ref().map(|i| i.id.clone()),
+            user: info.as_ref().and_then(|i| i.user.clone()),
+            addr: info.as_ref().and_then(|i| i.addr),
+            action: format!(
+                "exec {}/{}/{}: {} (exit {exit})",
+                session.namespace, session.pod, session.container, session.command,
+            ),
+            exec: Some(session),
+        });
+    }
+
+    /// Add a record that isn't associated with a specific session.
+    pub fn audit(&self, kind: Kind, action: String) {
+        self.record(None, kind, action);
+    }
+
+    /// All active sessions, oldest first.
+    pub fn sessions(&self) -> Vec<SessionInfo> {
+        let mut sessions: Vec<_> = self
+            .sessions
+            .lock()
+            .unwrap()
+            .values()
+            .map(|e| e.info.clone())
+            .collect();
+
+        sessions.sort_by_key(|s| s.started);
+
+        sessions
+    }
+
+    /// Disconnect the session with `id`. Returns false if there is no such
+    /// session on this server.
+    pub fn terminate(&self, id: &str) -> bool {
+        let info = {
+            let sessions = self.sessions.lock().unwrap();
+
+            let Some(entry) = sessions.get(id) else {
+                return false;
+            };
+
+            entry.terminate.cancel();
+            entry.info.clone()
+        };
+
+        self.record(
+            Some(&info),
+            Kind::Terminated,
+            "terminated by an admin".to_string(),
+        );
+
+        true
+    }
+
+    /// Disconnect every session `user` has on this server, recording `reason`
+    /// for each of them. Returns how many there were.
+    pub fn terminate_user(&self, user: &str, reason: &str) -> usize {
+        let terminated: Vec<_> = self
+            .sessions
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|e| e.info.user.as_deref() == Some(user))
+            .map(|e| {
+                e.terminate.cancel();
+
+                e.info.clone()
+            })
+            .collect();
+
+        for info in &terminated {
+            self.record(Some(info), Kind::Terminated, reason.to_string());
+        }
+
+        terminated.len()
+    }
+
+    /// Disconnect every session on this server that authenticated with the
+    /// key named `key`. Returns how many there were.
+    pub fn terminate_key(&self, key: &str) -> usize {
+        let terminated: Vec<_> = self
+            .sessions
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|e| e.key.as_deref() == Some(key))
+            .map(|e| {
+                e.terminate.cancel();
+
+                e.info.clone()
+            })
+            .collect();
+
+        for info in &terminated {
+            self.record(
+                Some(info),
+                Kind::Terminated,
+                format!("key {key} revoked by an admin"),
+            );
+        }
+
+        terminated.len()
+    }
+
+    /// Recent records, oldest first.
+    pub fn records(&self) -> Vec<Record> {
+        self.audit.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Show `message` on the dashboard of every authenticated session. Sessions
+    /// that are going away as it is sent don't stop the rest from getting it.
+    pub async fn message(&self, message: String) -> Delivered {
+        let targets: Vec<_> = self
+            .sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(id, e)| Some((id.clone(), e.broadcast.clone()?)))
+            .collect();
+
+        let mut delivered = Delivered::default();
+
+        for (id, broadcast) in &targets {
+            match broadcast.all(Event::Message(message.clone())).await {
+                Ok(()) => delivered.sessions += 1,
+                Err(err) => {
+                    tracing::warn!(session_id = id, ?err, "unable to deliver message");
+
+                    delivered.failed += 1;
+                }
+            }
+        }
+
+        self.audit(Kind::Message, format!("message: {message}"));
+
+        delivered
+    }
+}
+
+/// How many sessions a message from [`Registry::message`] reached.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct Delivered {
+    pub sessions: usize,
+    /// Sessions that were there but couldn't be sent the message, normally
+    /// because they were disconnecting.
+    pub failed: usize,
+}
+
+/// Lets the user of a dashboard share it with others, see [`Registry::watch`].
+pub(crate) struct Sharing {
+    controller: Arc<Controller>,
+    output: broadcast::Sender<Bytes>,
+    attachment: Attachment,
+
+    current: Mutex<Option<(String, CancellationToken)>>,
+}
+
+impl Sharing {
+    pub fn new(
+        controller: Arc<Controller>,
+        output: broadcast::Sender<Bytes>,
+        attachment: Attachment,
+    ) -> Self {
+        Self {
+            controller,
+            output,
+            attachment,
+            current: Mutex::default(),
+        }
+    }
+}
+
+impl Share for Sharing {
+    fn start(&self) -> Result<String> {
+        let mut current = self.current.lock().unwrap();
+
+        if let Some((code, _)) = current.as_ref() {
+            return Ok(code.clone());
+        }
+
+        let stopped = CancellationToken::new();
+
+        let code = self.controller.sessions().share(
+            &self.attachment.session_id(),
+            Shared {
+                output: self.output.clone(),
+                stopped: stopped.clone(),
+                attachment: self.attachment.clone(),
+            },
+        );
+
+        *current = Some((code.clone(), stopped));
+
+        Ok(code)
+    }
+
+    fn stop(&self) {
+        let current = self.current.lock().unwrap().take();
+
+        if let Some((code, stopped)) = current {
+            stopped.cancel();
+
+            self.controller
+                .sessions()
+                .unshare(&self.attachment.session_id(), &code);
+        }
+    }
+}
+
+impl Drop for Sharing {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Records the shells a session opens, see [`Registry::exec`].
+pub(crate) struct ExecAudit {
+    controller: Arc<Controller>,
+    attachment: Attachment,
+}
+
+impl ExecAudit {
+    pub fn new(controller: Arc<Controller>, attachment: Attachment) -> Self {
+        Self {
+            controller,
+            attachment,
+        }
+    }
+}
+
+impl Audit for ExecAudit {
+    fn exec(&self, session: ExecSession) {
+        self.controller
+            .sessions()
+            .exec(&self.attachment.session_id(), session);
+    }
+
+    fn transcripts(&self) -> bool {
+        self.controller.exec_transcripts()
+    }
+}
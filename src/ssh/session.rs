@@ -1,39 +1,130 @@
 mod metrics;
+pub(super) mod proxy;
 mod sftp;
 mod state;
 
-use std::{borrow::Cow, collections::HashMap, str, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+    str,
+    sync::Arc,
+    time::Duration,
+};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset, Utc};
+use clap::ValueEnum;
 use derive_builder::Builder;
 use eyre::{eyre, Report, Result};
 use fast_qr::QRBuilder;
+use futures::StreamExt;
+use kube::ResourceExt;
+pub(crate) use metrics::succeeded as authenticated;
 use metrics::{
-    ACTIVE_SESSIONS, AUTH_ATTEMPTS, AUTH_RESULTS, AUTH_SUCEEDED, CHANNELS, CODE_CHECKED,
-    CODE_GENERATED, REQUESTS, SESSION_DURATION, TOTAL_BYTES, TOTAL_SESSIONS, UNEXPECTED_STATE,
+    ACTIVE_SESSIONS, AUTH_ATTEMPTS, AUTH_REJECTIONS, AUTH_RESULTS, AUTH_SUCEEDED, CHANNELS,
+    CODE_CHECKED, CODE_GENERATED, DETACHED, REATTACHED, REQUESTS, SESSION_DURATION, TOTAL_BYTES,
+    TOTAL_SESSIONS, UNEXPECTED_STATE,
 };
 use ratatui::{backend::WindowSize, layout::Size};
 use russh::{
-    keys::key::PublicKey,
     server::{self, Auth, Response},
-    ChannelId, Disconnect, MethodSet,
+    ChannelId, CryptoVec, Disconnect, MethodSet,
 };
+use ssh_key::{Certificate, HashAlg, PublicKey};
 use state::State;
-use tokio::task::JoinSet;
-use tracing::debug;
+use tokio::{sync::broadcast::error::RecvError, task::JoinSet};
+use tokio_util::{io::ReaderStream, sync::CancellationToken};
+use tracing::{debug, Instrument};
 
-use super::Features;
+use super::{Features, Forwarding};
 use crate::{
+    audit::Audit,
     broadcast::Broadcast,
-    dashboard::Dashboard,
-    events::Event,
-    identity::Key,
-    io::Channel,
+    dashboard::{self, Dashboard},
+    events::{Event, Keypress},
+    history::History,
+    identity::{grant, history::UserHistory, otp::Verdict, Identity, Key, Rejection},
+    io::{Channel, Detachable, Mirror},
     openid,
-    resources::tunnel::{self, EgressBuilder, Ingress, Tunnel, TunnelBuilder},
-    ssh::{Authenticate, Controller},
+    resources::{
+        columns::Columns,
+        command::{Command, Commands},
+        redaction::Redaction,
+        tunnel::{self, EgressBuilder, Ingress, Tunnel, TunnelBuilder},
+        KubeID,
+    },
+    share::Share,
+    ssh::{
+        detached::Running,
+        launch::Launcher,
+        oci,
+        registry::{Attachment, ExecAudit, Shared, Sharing},
+        Authenticate, Controller,
+    },
+    widget::{
+        caps::Capabilities,
+        clock::{self, Clock},
+        link::Link,
+        locale::Locale,
+        statusbar::Slow,
+    },
 };
 
+// Number of writes that can be waiting to go out to observers before they fall
+// behind and need a full redraw.
+static SHARE_BUFFER: usize = 4096;
+// How long before an inactivity timeout users are warned.
+static IDLE_WARNING: Duration = Duration::from_secs(60);
+// Held before answering a rejected break-glass code. The server doesn't delay
+// rejections in general, this slows down guessing on a single connection.
+static BREAK_GLASS_DELAY: Duration = Duration::from_secs(3);
+
+/// Mirror a shared dashboard onto `channel`. Observers are read-only, their
+/// input is only used to stop watching.
+async fn watch(
+    shared: Shared,
+    channel: russh::Channel<server::Msg>,
+    handle: server::Handle,
+) -> Result<()> {
+    let id = channel.id();
+    let mut output = shared.output.subscribe();
+    let mut input = ReaderStream::new(channel.into_stream());
+
+    shared.redraw().await?;
+
+    loop {
+        tokio::select! {
+            () = shared.stopped.cancelled() => break,
+            msg = output.recv() => match msg {
+                Ok(data) => {
+                    if handle.data(id, CryptoVec::from_slice(&data)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(_)) => shared.redraw().await?,
+                Err(RecvError::Closed) => break,
+            },
+            msg = input.next() => match msg {
+                Some(Ok(data)) if !matches!(
+                    Keypress::from(data.as_ref()),
+                    Keypress::Control('c') | Keypress::Printable('q')
+                ) => {}
+                _ => break,
+            },
+        }
+    }
+
+    handle
+        .disconnect(
+            Disconnect::ByApplication,
+            "stopped watching".to_string(),
+            String::new(),
+        )
+        .await?;
+
+    Ok(())
+}
+
 fn token_response(error: Report) -> Result<Auth> {
     let http_error = match error.downcast::<reqwest::Error>() {
         Err(err) => return Err(err),
@@ -66,13 +157,60 @@ fn token_response(error: Report) -> Result<Auth> {
 #[builder(pattern = "owned")]
 pub struct Session {
     controller: Arc<Controller>,
-    identity_provider: Arc<openid::Provider>,
+    identity_provider: openid::Shared,
     features: Vec<Features>,
+    forwarding: Forwarding,
+    id: String,
+    // Where the client connected from, break-glass failures are counted per
+    // address.
+    addr: Option<SocketAddr>,
+    // Parent of everything logged on behalf of this connection, it carries the
+    // `session_id` field used to correlate logs.
+    span: tracing::Span,
 
     #[builder(default)]
     start: DateTime<Utc>,
     #[builder(default)]
     state: State,
+    // Requested via. the username, see [`Link`].
+    #[builder(default)]
+    link: Option<Link>,
+    // Share code for a dashboard this session would like to watch, requested via.
+    // the username as `share/<code>`.
+    #[builder(default)]
+    watching: Option<String>,
+    // Frame rate requested by the client via. `KTY_FPS`, see `env_request`.
+    #[builder(default)]
+    fps: Option<u16>,
+    // Requested by the client via. `KTY_ASCII` and `KTY_SYMBOLS`, these win over
+    // what is guessed from the terminal type in `pty_request`.
+    #[builder(default)]
+    ascii: Option<bool>,
+    #[builder(default)]
+    symbols: Option<bool>,
+    // Requested by the client via. `KTY_LINEAR`, for screen readers.
+    #[builder(default)]
+    linear: Option<bool>,
+    // Requested by the client via. `KTY_BASIC_COLORS`, `COLORTERM` and
+    // `KTY_ANIMATIONS`.
+    #[builder(default)]
+    basic_colors: Option<bool>,
+    #[builder(default)]
+    still: Option<bool>,
+    // Requested by the client via. `TZ`, used to show local timestamps.
+    #[builder(default)]
+    offset: Option<FixedOffset>,
+    // Requested by the client via. one of `locale::VARIABLES`, along with where
+    // that variable ranks so that `KTY_LANG` wins over `LANG`.
+    #[builder(default)]
+    locale: Option<(usize, Locale)>,
+    // Name of the `Key` used to authenticate, so that revoking it can disconnect
+    // the session.
+    #[builder(default)]
+    key: Option<String>,
+    // When access from an expiring grant runs out, see `expire_grant`.
+    #[builder(default)]
+    expires: Option<DateTime<Utc>>,
     // TODO: there's nothing that actually removes tasks from this set. For anything that is
     // especially long running, probably makes sense to remove them periodically with
     // `try_join_next`.
@@ -91,6 +229,16 @@ pub struct Session {
     #[builder(default)]
     broadcast: Broadcast,
 
+    // Dashboards started (or picked back up) by this session, by the channel
+    // they're attached to.
+    #[builder(default)]
+    dashboards: HashMap<ChannelId, Running>,
+    // Cancelled when the server ends the connection, such as for an admin or an
+    // inactivity timeout. Dashboards are then shut down instead of waiting for
+    // a reconnect, see `detach`.
+    #[builder(default)]
+    ending: CancellationToken,
+
     // This is a somewhat special state. With my OpenSSH client, the
     // `tcpip_forward` connection comes in before the `pty` request. This makes
     // it difficult to show that there's an open, listening egress tunnel via.
@@ -102,25 +250,235 @@ pub struct Session {
 }
 
 impl Session {
+    // Settings and disabled verbs can only narrow down what the server allows.
     fn enabled(&self, feature: &Features) -> bool {
         self.features.contains(feature)
+            && feature
+                .to_possible_value()
+                .is_some_and(|value| self.controller.settings().allows(value.get_name()))
+            && !feature
+                .verb()
+                .is_some_and(|verb| self.controller.disabled().contains(&verb))
+    }
+
+    // Nothing is ever forwarded back to the client, so these are refused
+    // explicitly instead of leaving it up to russh's defaults.
+    fn refuse(&self, request: &str, session: &mut server::Session) {
+        let user = match &self.state {
+            State::Authenticated(identity) => identity.name.as_str(),
+            _ => "unauthenticated",
+        };
+
+        tracing::warn!("refused {request} for {user}");
+
+        if self.forwarding == Forwarding::Disconnect {
+            self.ending.cancel();
+            session.disconnect(
+                Disconnect::ByApplication,
+                format!("{request} is not allowed").as_str(),
+                "",
+            );
+        }
+    }
+
+    // The username isn't used for identity, instead it can request what the
+    // session should show.
+    fn requested(&mut self, user: &str) {
+        match user.strip_prefix("share/") {
+            Some(code) => self.watching = Some(code.to_string()),
+            None => self.link = user.parse().ok(),
+        }
+    }
+
+    // With a detach timeout, a dashboard outlives its channel so that the user
+    // can reconnect to it. Otherwise, or when the server is the one ending the
+    // connection, it is shut down.
+    fn detach(&mut self, id: &ChannelId) {
+        let Some(running) = self.dashboards.remove(id) else {
+            return;
+        };
+
+        let parked = match (self.controller.detach_timeout(), &self.state) {
+            (Some(timeout), State::Authenticated(identity)) if !self.ending.is_cancelled() => self
+                .controller
+                .detached()
+                .park(identity, running.clone(), timeout),
+            _ => false,
+        };
+
+        if parked {
+            DETACHED.inc();
+            debug!("detached");
+        } else {
+            let _ = running.tx.send(Event::Shutdown);
+        }
+    }
+
+    fn open_proxy(
+        &mut self,
+        channel: russh::Channel<server::Msg>,
+        session: &mut server::Session,
+    ) -> Result<bool> {
+        tracing::debug!("api-proxy");
+
+        if !self.enabled(&Features::ApiProxy) {
+            session.channel_failure(channel.id());
+
+            return Ok(false);
+        }
+
+        let State::Authenticated(identity) = &self.state else {
+            UNEXPECTED_STATE
+                .with_label_values(&["Authenticated", self.state.as_ref()])
+                .inc();
+            return Err(eyre!("Unexpected state: {:?}", self.state));
+        };
+
+        self.tasks.spawn(
+            proxy::serve(
+                identity.client(&self.controller)?,
+                self.controller.clone(),
+                self.id.clone(),
+                identity.clone(),
+                channel.into_stream(),
+                None,
+            )
+            .in_current_span(),
+        );
+
+        Ok(true)
+    }
+
+    // Commands are left out of the dashboard, rather than failing the session,
+    // when they can't be set up.
+    fn commands(&self, identity: &Identity, commands: Vec<Command>) -> Commands {
+        if commands.is_empty() || self.controller.allowed_commands().is_empty() {
+            return Commands::default();
+        }
+
+        let result = Launcher::new(self.controller.clone(), self.id.clone(), identity)
+            .and_then(|launcher| Commands::new(commands, Arc::new(launcher)));
+
+        result.unwrap_or_else(|err| {
+            tracing::warn!(?err, "unable to set up commands");
+
+            Commands::default()
+        })
+    }
+
+    fn provider(&self) -> Result<Arc<openid::Provider>> {
+        self.identity_provider
+            .get()
+            .ok_or_else(|| openid::Unavailable::missing().into())
+    }
+
+    // Only an `OpenID` provider that can't be used offers break-glass access,
+    // everything else is returned as is.
+    fn break_glass(&mut self, result: Result<Auth>) -> Result<Auth> {
+        let err = match result {
+            Ok(auth) => return Ok(auth),
+            Err(err) if self.controller.break_glass().is_none() => return Err(err),
+            Err(err) => match err.downcast::<openid::Unavailable>() {
+                Ok(err) => err,
+                Err(err) => return Err(err),
+            },
+        };
+
+        self.controller
+            .sessions()
+            .break_glass(&self.id, format!("break-glass offered: {err}"));
+
+        self.state.code_requested();
+
+        AUTH_RESULTS.interactive.partial.inc();
+
+        Ok(Auth::Partial {
+            name: Cow::Borrowed("kty break-glass"),
+            instructions: Cow::Owned(format!(
+                "\nThe identity provider is unavailable: {err}\n\nEnter the break-glass code to \
+                 continue. This is audited.\n"
+            )),
+            prompts: Cow::Owned(vec![(Cow::Borrowed("Code: "), false)]),
+        })
+    }
+
+    #[tracing::instrument(parent = &self.span, skip(self, response))]
+    async fn authenticate_break_glass(&mut self, response: Option<Response<'_>>) -> Result<Auth> {
+        let Some(break_glass) = self.controller.break_glass() else {
+            return Err(eyre!("break-glass access is disabled"));
+        };
+
+        let code = response
+            .and_then(|mut response| response.next())
+            .and_then(|code| String::from_utf8(code.into()).ok())
+            .unwrap_or_default();
+
+        let action = match break_glass.verify(&code, self.addr.map(|addr| addr.ip()))? {
+            Verdict::Accepted(ident) => Ok(ident),
+            Verdict::Rejected { locked: None } => Err("break-glass code rejected".to_string()),
+            Verdict::Rejected {
+                locked: Some(until),
+            } => Err(format!(
+                "break-glass code rejected, too many failures so codes are refused until {until}"
+            )),
+            Verdict::Locked(until) => Err(format!(
+                "break-glass code refused, locked out until {until}"
+            )),
+        };
+
+        let ident = match action {
+            Ok(ident) => ident,
+            Err(action) => {
+                AUTH_RESULTS.interactive.reject.inc();
+
+                self.controller.sessions().break_glass(&self.id, action);
+
+                self.state = State::Unauthenticated;
+
+                tokio::time::sleep(BREAK_GLASS_DELAY).await;
+
+                return Ok(Auth::Reject {
+                    proceed_with_methods: None,
+                });
+            }
+        };
+
+        self.controller
+            .sessions()
+            .break_glass(&self.id, format!("break-glass used by {ident}"));
+
+        // RBAC still applies, break-glass only replaces the identity provider.
+        let Some(ident) = ident.authenticate(&self.controller).await? else {
+            AUTH_RESULTS.interactive.reject.inc();
+            self.controller.sessions().rejected(&self.id, &ident.name);
+
+            self.state = State::Unauthenticated;
+
+            return Ok(Auth::Reject {
+                proceed_with_methods: None,
+            });
+        };
+
+        AUTH_RESULTS.interactive.accept.inc();
+
+        self.state.authenticated(ident);
+
+        Ok(Auth::Accept)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(parent = &self.span, skip(self))]
     async fn send_code(&mut self) -> Result<Auth> {
         CODE_GENERATED.inc();
 
-        let preface = if let State::InvalidIdentity(id, _) = &self.state {
-            format!(
-                "\nAuthenticated ID is invalid:\n- name: {}\n- groups: {}\n--------------------\n",
-                id.name,
-                id.groups.join(", ")
-            )
-        } else {
-            String::new()
+        let preface = match &self.state {
+            State::Rejected(rejection, _) => format!("\n{rejection}\n--------------------\n"),
+            State::KeyOffered(key) if self.controller.keys_writable() => {
+                self.onboarding(key).await?
+            }
+            _ => String::new(),
         };
 
-        let code = self.identity_provider.code().await?;
+        let code = self.provider()?.code().await?;
 
         self.state.code_sent(&code);
 
@@ -128,9 +486,16 @@ impl Session {
 
         let login_url = QRBuilder::new(uri.clone()).build().unwrap().to_str();
 
-        let instructions =
+        let mut instructions =
             format!("{preface}\nLogin or scan the QRCode below to validate your identity:\n");
 
+        if !self.controller.keys_writable() {
+            instructions.push_str(
+                "\nNote: this server is unable to store keys, you will need to login again for \
+                 every session.\n",
+            );
+        }
+
         let prompt = format!("\n{login_url}\n\n{uri}\n\nPress Enter to continue");
 
         AUTH_RESULTS.interactive.partial.inc();
@@ -142,8 +507,146 @@ impl Session {
         })
     }
 
+    // Unknown keys go through onboarding: verify with the provider, then decide
+    // whether to bind the key to that identity.
+    async fn onboarding(&self, key: &PublicKey) -> Result<String> {
+        let fingerprint = key.fingerprint(HashAlg::Sha256);
+
+        // Not being able to tell only changes the wording.
+        let pending = Key::find(self.controller.client()?, key)
+            .await
+            .ok()
+            .flatten()
+            .is_some_and(|key| !key.approved() && !key.expired());
+
+        let rejection = if pending {
+            Rejection::KeyPending
+        } else {
+            Rejection::UnknownKey
+        };
+        AUTH_REJECTIONS
+            .with_label_values(&[rejection.reason()])
+            .inc();
+
+        Ok(if pending {
+            format!(
+                "\nYour key ({fingerprint}) is waiting for an admin to approve it. Until then, \
+                 verify your identity for every session.\n"
+            )
+        } else {
+            format!(
+                "\nThis key ({fingerprint}) isn't bound to anyone yet.\n\nStep 1 of 2: verify \
+                 your identity.\nStep 2 of 2: choose whether to bind the key to it, so that \
+                 future sessions skip this.\n"
+            )
+        })
+    }
+
+    // Verified identities with a key are asked to confirm binding the key before
+    // being let in.
+    fn confirm(&mut self, ident: Identity, key: Key) -> Auth {
+        let approval = if self.controller.settings().key_approval.unwrap_or_default() {
+            "\nAn admin has to approve the key before it can be used, until then you will be asked \
+             to verify every time.\n"
+        } else {
+            ""
+        };
+
+        let instructions = format!(
+            "\nStep 2 of 2: you have been verified as:\n- name: {}\n- groups: {}\n\nBinding this \
+             key ({}) lets future sessions skip verification until {}.\n{approval}",
+            ident.name,
+            ident.groups.join(", "),
+            key.spec.key.fingerprint(HashAlg::Sha256),
+            key.spec.expiration.to_rfc3339(),
+        );
+
+        self.state.confirm(ident, key);
+
+        AUTH_RESULTS.interactive.partial.inc();
+
+        Auth::Partial {
+            name: Cow::Borrowed("Bind your key"),
+            instructions: Cow::Owned(instructions),
+            prompts: Cow::Owned(vec![(Cow::Borrowed("Bind this key? [y/N] "), true)]),
+        }
+    }
+
+    #[tracing::instrument(parent = &self.span, skip(self, response))]
+    async fn authenticate_confirm(&mut self, response: Option<Response<'_>>) -> Result<Auth> {
+        let (ident, key) = match std::mem::take(&mut self.state) {
+            State::Confirm(ident, key) => (ident, key),
+            state => {
+                UNEXPECTED_STATE
+                    .with_label_values(&["Confirm", state.as_ref()])
+                    .inc();
+                let err = eyre!("Unexpected state: {state:?}");
+                self.state = state;
+
+                return Err(err);
+            }
+        };
+
+        let answer = response
+            .and_then(|mut response| response.next())
+            .and_then(|answer| String::from_utf8(answer.into()).ok())
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase();
+
+        if matches!(answer.as_str(), "y" | "yes") {
+            let key = if self.controller.settings().key_approval.unwrap_or_default() {
+                key.pending()
+            } else {
+                *key
+            };
+
+            // The user has been verified, a key that couldn't be saved only means
+            // they'll be asked again next time.
+            match key.update(self.controller.client()?).await {
+                Err(err) => tracing::warn!(?err, "unable to bind key"),
+                Ok(()) => self.controller.sessions().key_bound(
+                    &self.id,
+                    format!(
+                        "key {} bound to {} until {}{}",
+                        key.name_any(),
+                        key.spec.user,
+                        key.spec.expiration.to_rfc3339(),
+                        if key.approved() {
+                            ""
+                        } else {
+                            ", pending approval"
+                        }
+                    ),
+                ),
+            }
+        }
+
+        AUTH_RESULTS.interactive.accept.inc();
+
+        self.state.authenticated(ident);
+
+        Ok(Auth::Accept)
+    }
+
+    // Clients go back to keyboard-interactive on their own, which is where the
+    // reason is shown.
+    fn reject(&mut self, rejection: Rejection) -> Auth {
+        AUTH_RESULTS.interactive.reject.inc();
+        AUTH_REJECTIONS
+            .with_label_values(&[rejection.reason()])
+            .inc();
+        debug!("rejected: {rejection}");
+
+        self.state.rejected(rejection);
+
+        Auth::Reject {
+            proceed_with_methods: None,
+        }
+    }
+
     // TODO: need to handle 429 responses and backoff.
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(parent = &self.span, skip(self))]
     async fn authenticate_code(&mut self) -> Result<Auth> {
         let (code, key) = {
             let State::CodeSent(code, key) = &self.state else {
@@ -156,9 +659,12 @@ impl Session {
             (code.clone(), key.clone())
         };
 
-        let (id, expiration) = match self.identity_provider.identity(&code).await {
+        let (id, expiration) = match self.provider()?.identity(&code).await {
             Ok(id) => id,
-            Err(e) => return token_response(e),
+            Err(err) => match err.downcast::<Rejection>() {
+                Ok(rejection) => return Ok(self.reject(rejection)),
+                Err(err) => return token_response(err),
+            },
         };
 
         CODE_CHECKED.valid.inc();
@@ -169,23 +675,17 @@ impl Session {
         self.state.code_used();
 
         let Some(ident) = id.authenticate(&self.controller).await? else {
-            AUTH_RESULTS.interactive.reject.inc();
+            self.controller.sessions().rejected(&self.id, &id.name);
 
-            self.state.invalid_identity(id);
-
-            return Ok(Auth::Reject {
-                proceed_with_methods: None,
-            });
+            return Ok(self.reject(Rejection::NoAccess(id)));
         };
 
-        self.state.authenticated(ident);
-
-        if let Some(user_key) = key {
-            Key::from_identity(user_key, &id, expiration)?
-                .update(self.controller.client()?)
-                .await?;
+        if let Some(user_key) = key.filter(|_| self.controller.keys_writable()) {
+            return Ok(self.confirm(ident, Key::from_identity(user_key, &id, expiration)?));
         }
 
+        self.state.authenticated(ident);
+
         AUTH_RESULTS.publickey.accept.inc();
 
         Ok(Auth::Accept)
@@ -197,16 +697,20 @@ impl Session {
 impl server::Handler for Session {
     type Error = eyre::Error;
 
-    #[tracing::instrument(skip(self, key))]
+    // The username can carry a share code, which is a secret, so it isn't logged.
+    #[tracing::instrument(parent = &self.span, skip(self, user, key))]
     async fn auth_publickey(&mut self, user: &str, key: &PublicKey) -> Result<Auth> {
         AUTH_ATTEMPTS.publickey.inc();
         tracing::debug!("publickey");
 
+        self.requested(user);
+
         self.state.key_offered(key);
 
         if let Some(ident) = key.authenticate(&self.controller).await? {
             AUTH_RESULTS.publickey.accept.inc();
 
+            self.key = Some(key.kube_id()?);
             self.state.authenticated(ident);
 
             return Ok(Auth::Accept);
@@ -219,21 +723,87 @@ impl server::Handler for Session {
         })
     }
 
-    #[tracing::instrument(skip(self))]
+    // Certificates from a trusted authority stand in for a key bound to the
+    // identity, nothing has to be registered ahead of time.
+    #[tracing::instrument(parent = &self.span, skip(self, user, certificate))]
+    async fn auth_openssh_certificate(
+        &mut self,
+        user: &str,
+        certificate: &Certificate,
+    ) -> Result<Auth> {
+        AUTH_ATTEMPTS.certificate.inc();
+        tracing::debug!("certificate");
+
+        self.requested(user);
+
+        let Some(authorities) = self.controller.authorities() else {
+            AUTH_RESULTS.certificate.reject.inc();
+
+            return Ok(Auth::Reject {
+                proceed_with_methods: Some(MethodSet::KEYBOARD_INTERACTIVE),
+            });
+        };
+
+        let now = u64::try_from(Utc::now().timestamp())?;
+        let addr = self.addr.map(|addr| addr.ip());
+        let rejection = match authorities.identity(user, certificate, addr, now) {
+            Ok(id) => {
+                if let Some(ident) = id.authenticate(&self.controller).await? {
+                    AUTH_RESULTS.certificate.accept.inc();
+
+                    self.state.authenticated(ident);
+
+                    return Ok(Auth::Accept);
+                }
+
+                self.controller.sessions().rejected(&self.id, &id.name);
+
+                Rejection::NoAccess(id)
+            }
+            Err(err) => {
+                debug!(?err, "certificate rejected");
+
+                Rejection::Certificate(err.to_string())
+            }
+        };
+
+        // Clients go on to keyboard-interactive, which shows the reason.
+        AUTH_RESULTS.certificate.reject.inc();
+        AUTH_REJECTIONS
+            .with_label_values(&[rejection.reason()])
+            .inc();
+        self.state.rejected(rejection);
+
+        Ok(Auth::Reject {
+            proceed_with_methods: Some(MethodSet::KEYBOARD_INTERACTIVE),
+        })
+    }
+
+    #[tracing::instrument(parent = &self.span, skip(self, user, response))]
     async fn auth_keyboard_interactive(
         &mut self,
         user: &str,
         _: &str,
-        _: Option<Response<'async_trait>>,
+        response: Option<Response<'async_trait>>,
     ) -> Result<Auth> {
         AUTH_ATTEMPTS.interactive.inc();
         tracing::debug!("keyboard-interactive");
 
+        self.requested(user);
+
         match self.state {
-            State::Unauthenticated | State::KeyOffered(_) | State::InvalidIdentity(_, _) => {
-                self.send_code().await
+            State::Unauthenticated | State::KeyOffered(_) | State::Rejected(..) => {
+                let result = self.send_code().await;
+
+                self.break_glass(result)
             }
-            State::CodeSent(..) => self.authenticate_code().await,
+            State::CodeSent(..) => {
+                let result = self.authenticate_code().await;
+
+                self.break_glass(result)
+            }
+            State::CodeRequested => self.authenticate_break_glass(response).await,
+            State::Confirm(..) => self.authenticate_confirm(response).await,
             State::Authenticated(..) => {
                 UNEXPECTED_STATE
                     .with_label_values(&[
@@ -247,8 +817,8 @@ impl server::Handler for Session {
     }
 
     // TODO: add some kind of event to log successful authentication.
-    #[tracing::instrument(skip(self, _session))]
-    async fn auth_succeeded(&mut self, _session: &mut server::Session) -> Result<()> {
+    #[tracing::instrument(parent = &self.span, skip(self, session))]
+    async fn auth_succeeded(&mut self, session: &mut server::Session) -> Result<()> {
         let State::Authenticated(identity) = &self.state else {
             UNEXPECTED_STATE
                 .with_label_values(&["Authenticated", self.state.as_ref()])
@@ -262,12 +832,75 @@ impl server::Handler for Session {
 
         AUTH_SUCEEDED.with_label_values(&[method.as_str()]).inc();
 
+        self.controller.sessions().authenticated(
+            &self.id,
+            identity.name.clone(),
+            method,
+            self.key.clone(),
+            self.broadcast.clone(),
+        );
+
+        if let Some(terminated) = self.controller.sessions().terminated(&self.id) {
+            let handle = session.handle();
+            let ending = self.ending.clone();
+
+            self.tasks.spawn(
+                async move {
+                    terminated.cancelled().await;
+
+                    debug!("terminated by an admin");
+
+                    end(&handle, &ending, "terminated by an admin").await
+                }
+                .in_current_span(),
+            );
+        }
+
+        if let Some(timeout) = self
+            .controller
+            .inactivity_timeout(&identity.name, &identity.groups)
+        {
+            self.tasks.spawn(
+                disconnect_idle(
+                    self.controller.clone(),
+                    self.id.clone(),
+                    timeout,
+                    self.broadcast.clone(),
+                    session.handle(),
+                    self.ending.clone(),
+                )
+                .in_current_span(),
+            );
+        }
+
+        let identity = identity.clone();
+
+        match grant::expiration_for(self.controller.client()?, &identity).await {
+            Ok(Some(at)) => {
+                self.expires = Some(at);
+
+                self.tasks.spawn(
+                    expire_grant(
+                        self.controller.clone(),
+                        identity,
+                        at,
+                        self.broadcast.clone(),
+                        session.handle(),
+                        self.ending.clone(),
+                    )
+                    .in_current_span(),
+                );
+            }
+            Ok(None) => {}
+            Err(err) => tracing::warn!(?err, "unable to check for expiring grants"),
+        }
+
         debug!(method, "authenticated");
 
         Ok(())
     }
 
-    #[tracing::instrument(skip(self, channel))]
+    #[tracing::instrument(parent = &self.span, skip(self, channel))]
     async fn channel_open_session(
         &mut self,
         channel: russh::Channel<server::Msg>,
@@ -283,20 +916,19 @@ impl server::Handler for Session {
         Ok(true)
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(parent = &self.span, skip(self))]
     async fn channel_close(&mut self, id: ChannelId, _: &mut server::Session) -> Result<()> {
         ACTIVE_SESSIONS.dec();
         CHANNELS.close.inc();
         tracing::debug!("channel-close");
 
-        if let Some(writer) = self.broadcast.remove(&id).await {
-            writer.send(Event::Shutdown)?;
-        }
+        self.broadcast.remove(&id).await;
+        self.detach(&id);
 
         Ok(())
     }
 
-    #[tracing::instrument(skip(self, session))]
+    #[tracing::instrument(parent = &self.span, skip(self, session))]
     async fn channel_eof(&mut self, id: ChannelId, session: &mut server::Session) -> Result<()> {
         CHANNELS.eof.inc();
         tracing::debug!("channel-eof");
@@ -316,10 +948,42 @@ impl server::Handler for Session {
         Ok(())
     }
 
+    // Clients only open these when the server asked for forwarding, which kty
+    // never does.
+    #[tracing::instrument(parent = &self.span, skip(self, _channel, session))]
+    async fn channel_open_x11(
+        &mut self,
+        _channel: russh::Channel<server::Msg>,
+        _originator_address: &str,
+        _originator_port: u32,
+        session: &mut server::Session,
+    ) -> Result<bool, Self::Error> {
+        CHANNELS.x11.inc();
+        self.refuse("X11 channel", session);
+
+        Ok(false)
+    }
+
+    #[tracing::instrument(parent = &self.span, skip(self, _channel, session))]
+    async fn channel_open_forwarded_tcpip(
+        &mut self,
+        _channel: russh::Channel<server::Msg>,
+        _host_to_connect: &str,
+        _port_to_connect: u32,
+        _originator_address: &str,
+        _originator_port: u32,
+        session: &mut server::Session,
+    ) -> Result<bool, Self::Error> {
+        CHANNELS.forwarded_tcpip.inc();
+        self.refuse("forwarded-tcpip channel", session);
+
+        Ok(false)
+    }
+
     // There is some funkiness here around showing status in the dashboard. If two
     // requests are made in parallel and one finishes first, the `Inactive` event
     // will be sent, even though one is still active.
-    #[tracing::instrument(skip(self, channel, session))]
+    #[tracing::instrument(parent = &self.span, skip(self, channel, session))]
     async fn channel_open_direct_tcpip(
         &mut self,
         channel: russh::Channel<server::Msg>,
@@ -330,6 +994,11 @@ impl server::Handler for Session {
         session: &mut server::Session,
     ) -> Result<bool, Self::Error> {
         CHANNELS.direct_tcpip.inc();
+
+        if host_to_connect == proxy::HOST {
+            return self.open_proxy(channel, session);
+        }
+
         tracing::debug!("ingress-tunnel");
 
         if !self.enabled(&Features::IngressTunnel) {
@@ -362,45 +1031,65 @@ impl server::Handler for Session {
         #[allow(clippy::cast_possible_truncation)]
         let ingress = Ingress::new(host_to_connect, port_to_connect as u16)?;
 
-        self.tasks.spawn(async move {
-            let meta = meta.into_inactive();
-
-            #[allow(clippy::cast_possible_truncation)]
-            match ingress.run(client, channel).await {
-                Ok(()) => {
-                    broadcast.all(Event::Tunnel(Ok(meta))).await?;
-                    Ok(())
-                }
-                Err(e) => {
-                    let e = e
-                        .wrap_err(format!("failed to open connection to {}", ingress.host()))
-                        .wrap_err("unable to forward connection");
-
-                    broadcast
-                        .all(Event::Tunnel(Err(tunnel::Error::new(&e, meta))))
-                        .await?;
-
-                    handle
-                        .close(id)
-                        .await
-                        .map_err(|()| eyre!("failed closing channel"))?;
-
-                    Err(e)
+        self.tasks.spawn(
+            async move {
+                let meta = meta.into_inactive();
+
+                #[allow(clippy::cast_possible_truncation)]
+                match ingress.run(client, channel).await {
+                    Ok(()) => {
+                        broadcast.all(Event::Tunnel(Ok(meta))).await?;
+                        Ok(())
+                    }
+                    Err(e) => {
+                        let e = e
+                            .wrap_err(format!("failed to open connection to {}", ingress.host()))
+                            .wrap_err("unable to forward connection");
+
+                        broadcast
+                            .all(Event::Tunnel(Err(tunnel::Error::new(&e, meta))))
+                            .await?;
+
+                        handle
+                            .close(id)
+                            .await
+                            .map_err(|()| eyre!("failed closing channel"))?;
+
+                        Err(e)
+                    }
                 }
             }
-        });
+            .in_current_span(),
+        );
 
         Ok(true)
     }
 
-    #[tracing::instrument(skip(self, data))]
+    // Dashboards draw less often while the client is behind, see `Backlog`.
+    #[tracing::instrument(parent = &self.span, skip(self, _session))]
+    async fn window_adjusted(
+        &mut self,
+        id: ChannelId,
+        new_size: u32,
+        _session: &mut server::Session,
+    ) -> Result<()> {
+        if let Some(running) = self.dashboards.get(&id) {
+            running.output.adjusted(new_size);
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(parent = &self.span, skip(self, data))]
     async fn data(&mut self, _: ChannelId, data: &[u8], _: &mut server::Session) -> Result<()> {
         TOTAL_BYTES.inc_by(data.len() as u64);
 
+        self.controller.sessions().active(&self.id);
+
         Ok(())
     }
 
-    #[tracing::instrument(skip(self, session))]
+    #[tracing::instrument(parent = &self.span, skip(self, session))]
     async fn window_change_request(
         &mut self,
         id: ChannelId,
@@ -435,18 +1124,80 @@ impl server::Handler for Session {
         Ok(())
     }
 
-    #[tracing::instrument(skip(self, _session))]
-    async fn agent_request(
+    // `KTY_FPS`, `KTY_ASCII`, `KTY_SYMBOLS`, `KTY_LINEAR`, `KTY_BASIC_COLORS`,
+    // `KTY_ANIMATIONS`, `COLORTERM`, `TZ` and the locale variables are supported,
+    // they can be set with `ssh -o SetEnv=KTY_FPS=5`.
+    #[tracing::instrument(parent = &self.span, skip(self, session))]
+    async fn env_request(
         &mut self,
-        _: ChannelId,
-        _session: &mut server::Session,
-    ) -> Result<bool> {
-        tracing::debug!("agent");
+        id: ChannelId,
+        name: &str,
+        value: &str,
+        session: &mut server::Session,
+    ) -> Result<()> {
+        tracing::debug!("env");
+
+        let requested = Locale::from_env(name, value);
+
+        match (name, value.parse::<u16>(), Capabilities::toggle(value)) {
+            ("KTY_FPS", Ok(fps), _) => self.fps = Some(fps),
+            ("KTY_ASCII", _, Some(ascii)) => self.ascii = Some(ascii),
+            ("KTY_SYMBOLS", _, Some(symbols)) => self.symbols = Some(symbols),
+            ("KTY_LINEAR", _, Some(linear)) => self.linear = Some(linear),
+            ("KTY_BASIC_COLORS", _, Some(basic)) => self.basic_colors = Some(basic),
+            ("KTY_ANIMATIONS", _, Some(animations)) => self.still = Some(!animations),
+            // Set by terminals that draw 24-bit color, `KTY_BASIC_COLORS` still wins.
+            ("COLORTERM", _, _) if matches!(value, "truecolor" | "24bit") => {
+                self.basic_colors = self.basic_colors.or(Some(false));
+            }
+            ("TZ", _, _) if clock::offset(value).is_some() => self.offset = clock::offset(value),
+            // Clients send these in any order, only a higher precedence one replaces
+            // what was already requested.
+            _ if requested.is_some() => {
+                self.locale = requested
+                    .into_iter()
+                    .chain(self.locale)
+                    .min_by_key(|(rank, _)| *rank);
+            }
+            _ => {
+                session.channel_failure(id);
+
+                return Ok(());
+            }
+        }
+
+        session.channel_success(id);
+
+        Ok(())
+    }
+
+    #[tracing::instrument(parent = &self.span, skip(self, session))]
+    async fn agent_request(&mut self, _: ChannelId, session: &mut server::Session) -> Result<bool> {
+        REQUESTS.agent.inc();
+        self.refuse("agent forwarding", session);
 
         Ok(false)
     }
 
-    #[tracing::instrument(skip(self, _modes, session))]
+    #[tracing::instrument(parent = &self.span, skip(self, _cookie, session))]
+    async fn x11_request(
+        &mut self,
+        id: ChannelId,
+        _single_connection: bool,
+        _protocol: &str,
+        _cookie: &str,
+        _screen: u32,
+        session: &mut server::Session,
+    ) -> Result<()> {
+        REQUESTS.x11.inc();
+        self.refuse("X11 forwarding", session);
+
+        session.channel_failure(id);
+
+        Ok(())
+    }
+
+    #[tracing::instrument(parent = &self.span, skip(self, _modes, session))]
     async fn pty_request(
         &mut self,
         id: ChannelId,
@@ -481,16 +1232,23 @@ impl server::Handler for Session {
             return Err(eyre!("channel {id} already consumed"));
         };
 
-        let writer = Dashboard::builder()
-            .client(identity.client(&self.controller)?)
-            .build()
-            .start(
-                channel.into_stream(),
-                Channel::new(id, session.handle().clone()),
-            )?;
+        if let Some(code) = &self.watching {
+            let Some(shared) = self.controller.sessions().watch(&self.id, code) else {
+                session.channel_failure(id);
+                session.disconnect(Disconnect::ByApplication, "unknown share code", "");
+
+                return Ok(());
+            };
+
+            self.tasks
+                .spawn(watch(shared, channel, session.handle()).in_current_span());
+            session.channel_success(id);
+
+            return Ok(());
+        }
 
         #[allow(clippy::cast_possible_truncation)]
-        writer.send(Event::Resize(WindowSize {
+        let size = WindowSize {
             columns_rows: Size {
                 width: cx as u16,
                 height: cy as u16,
@@ -499,19 +1257,155 @@ impl server::Handler for Session {
                 width: px as u16,
                 height: py as u16,
             },
-        }))?;
+        };
+
+        // Reconnecting picks up where the user left off, unless they asked for
+        // something specific.
+        let detached = self
+            .link
+            .is_none()
+            .then(|| self.controller.detached().take(&identity))
+            .flatten();
+
+        if let Some(running) = detached {
+            REATTACHED.inc();
+            debug!("reattached");
+
+            running
+                .output
+                .attach(Channel::new(id, session.handle().clone()));
+            running
+                .attachment
+                .rebind(self.id.clone(), self.broadcast.clone(), id);
+            dashboard::read(channel.into_stream(), running.tx.clone());
+
+            running.tx.send(Event::Resize(size))?;
+            running.tx.send(Event::Redraw)?;
+
+            self.broadcast.add(id, running.tx.clone()).await?;
+            self.dashboards.insert(id, running);
+            session.channel_success(id);
+
+            return Ok(());
+        }
+
+        // History is stored next to keys, when those are unavailable there's nowhere
+        // to put it.
+        let history: Option<Arc<dyn History>> = if self.controller.keys_enabled() {
+            Some(Arc::new(UserHistory::new(
+                self.controller.client()?,
+                identity.name.clone(),
+            )))
+        } else {
+            None
+        };
+
+        let mut variables = BTreeMap::from([("user".to_string(), identity.name.clone())]);
+        if let Some(group) = identity.groups.first() {
+            variables.insert("group".to_string(), group.clone());
+        }
+
+        let attachment = Attachment::new(self.id.clone(), self.broadcast.clone(), id);
+
+        let (shared, _) = tokio::sync::broadcast::channel(SHARE_BUFFER);
+        let sharing: Arc<dyn Share> = Arc::new(Sharing::new(
+            self.controller.clone(),
+            shared.clone(),
+            attachment.clone(),
+        ));
+
+        let audit: Arc<dyn Audit> =
+            Arc::new(ExecAudit::new(self.controller.clone(), attachment.clone()));
+
+        let settings = self.controller.settings();
+
+        let commands = self.commands(&identity, settings.commands.clone().unwrap_or_default());
+
+        let locale = self
+            .locale
+            .map(|(_, locale)| locale)
+            .or_else(|| settings.locale.as_deref().and_then(Locale::from_posix))
+            .unwrap_or_default();
+
+        let default = Capabilities::from_term(term);
+        let caps = Capabilities {
+            ascii: self.ascii.unwrap_or(default.ascii),
+            symbols: self.symbols.unwrap_or(default.symbols),
+            linear: self.linear.unwrap_or(default.linear),
+            basic_colors: self.basic_colors.unwrap_or(default.basic_colors),
+            still: self.still.unwrap_or(default.still),
+        };
+
+        let output = Detachable::new(Channel::new(id, session.handle().clone()));
+
+        let slow = Slow::default();
+
+        let writer = Dashboard::builder()
+            .client(self.controller.impersonate_with(
+                identity.name.clone(),
+                identity.groups.clone(),
+                slow.clone(),
+            )?)
+            .slow(slow)
+            .maybe_tabs(settings.tabs)
+            .maybe_tab(settings.tab)
+            .fps(self.fps.unwrap_or(self.controller.fps()))
+            .caps(caps)
+            .locale(locale)
+            .clock(Clock {
+                offset: self.offset.unwrap_or(Clock::default().offset),
+                ..Clock::default()
+            })
+            .maybe_history(history)
+            .share(sharing)
+            .audit(audit)
+            .maybe_link(self.link.clone())
+            .maybe_cluster(self.controller.cluster().map(ToString::to_string))
+            .keys_read_only(!self.controller.keys_writable())
+            .variables(Arc::new(variables))
+            .policy(self.controller.policy(&identity.name, &identity.groups)?)
+            .redaction(Redaction::new(
+                settings.redact_logs.as_deref().unwrap_or_default(),
+            ))
+            .columns(Columns::new(settings.columns.unwrap_or_default()))
+            .maybe_alerts(settings.alerts)
+            .images(oci::enrichment(settings.images.unwrap_or_default()))
+            .idle(settings.idle.unwrap_or_default())
+            .node_shell(settings.node_shell.unwrap_or_default())
+            .plugins(self.controller.plugins())
+            .commands(commands)
+            .build()
+            .start(channel.into_stream(), Mirror::new(output.clone(), shared))?;
+
+        writer.send(Event::Resize(size))?;
 
         if let Some(tunnel) = self.tunnel.take() {
             writer.send(Event::Tunnel(Ok(tunnel.clone())))?;
         }
 
-        self.broadcast.add(id, writer).await?;
+        if let Some(banner) = settings.banner {
+            writer.send(Event::Message(banner))?;
+        }
+
+        if let Some(expires) = self.expires {
+            writer.send(Event::Expires(expires))?;
+        }
+
+        self.broadcast.add(id, writer.clone()).await?;
+        self.dashboards.insert(
+            id,
+            Running {
+                tx: writer,
+                output,
+                attachment,
+            },
+        );
         session.channel_success(id);
 
         Ok(())
     }
 
-    #[tracing::instrument(skip(self, session), fields(activity = "sftp"))]
+    #[tracing::instrument(parent = &self.span, skip(self, session), fields(activity = "sftp"))]
     async fn subsystem_request(
         &mut self,
         id: ChannelId,
@@ -530,6 +1424,7 @@ impl server::Handler for Session {
         if name != "sftp" {
             session.channel_failure(id);
 
+            self.ending.cancel();
             session.disconnect(
                 Disconnect::ByApplication,
                 format!("unsupported subsystem: {name}").as_str(),
@@ -556,7 +1451,7 @@ impl server::Handler for Session {
 
         self.channels.insert(id, None);
 
-        let handler = sftp::Handler::new(identity.client(&self.controller)?);
+        let handler = sftp::Handler::new(identity.client(&self.controller)?, self.span.clone());
         russh_sftp::server::run(channel.into_stream(), handler).await;
 
         session.channel_success(id);
@@ -564,7 +1459,7 @@ impl server::Handler for Session {
         Ok(())
     }
 
-    #[tracing::instrument(skip(self, session))]
+    #[tracing::instrument(parent = &self.span, skip(self, session))]
     async fn tcpip_forward(
         &mut self,
         address: &str,
@@ -607,6 +1502,7 @@ impl server::Handler for Session {
 
         let handle = session.handle();
         let broadcast = self.broadcast.clone();
+        let ending = self.ending.clone();
         #[allow(clippy::cast_possible_truncation)]
         let mut egress = EgressBuilder::default()
             .host(address)?
@@ -618,33 +1514,130 @@ impl server::Handler for Session {
             .build()?;
         let client = identity.client(&self.controller)?;
 
-        self.tasks.spawn(async move {
-            match egress.run(client, handle.clone()).await {
-                Ok(()) => Ok(()),
-                Err(e) => {
-                    tracing::error!("egress-tunnel: {:?}", e);
-
-                    handle
-                        .disconnect(
-                            Disconnect::ByApplication,
-                            format!("unrecoverable error, reconnect and try again: {e}"),
-                            String::new(),
+        self.tasks.spawn(
+            async move {
+                match egress.run(client, handle.clone()).await {
+                    Ok(()) => Ok(()),
+                    Err(e) => {
+                        tracing::error!("egress-tunnel: {:?}", e);
+
+                        end(
+                            &handle,
+                            &ending,
+                            &format!("unrecoverable error, reconnect and try again: {e}"),
                         )
                         .await?;
 
-                    Err(e)
+                        Err(e)
+                    }
                 }
             }
-        });
+            .in_current_span(),
+        );
 
         Ok(true)
     }
 }
 
+// The server ending the connection, as opposed to the client going away. The
+// session's dashboards are shut down rather than kept for a reconnect.
+async fn end(handle: &server::Handle, ending: &CancellationToken, reason: &str) -> Result<()> {
+    ending.cancel();
+
+    handle
+        .disconnect(Disconnect::ByApplication, reason.to_string(), String::new())
+        .await?;
+
+    Ok(())
+}
+
+// Users are warned for `IDLE_WARNING` before being disconnected, anything they
+// send resets the timer.
+async fn disconnect_idle(
+    controller: Arc<Controller>,
+    id: String,
+    timeout: Duration,
+    broadcast: Broadcast,
+    handle: server::Handle,
+    ending: CancellationToken,
+) -> Result<()> {
+    let mut interval = tokio::time::interval(Duration::from_secs(1));
+
+    loop {
+        interval.tick().await;
+
+        let Some(idle) = controller.sessions().idle(&id) else {
+            return Ok(());
+        };
+
+        let remaining = timeout.saturating_sub(idle);
+
+        if remaining.is_zero() {
+            debug!("inactivity timeout");
+
+            return end(&handle, &ending, "disconnected for inactivity").await;
+        }
+
+        if remaining <= IDLE_WARNING {
+            broadcast.all(Event::Idle(remaining)).await?;
+        }
+    }
+}
+
+// Once a grant expires, the session is disconnected unless access comes from
+// somewhere else too. Grants that were extended in the meantime push this back.
+async fn expire_grant(
+    controller: Arc<Controller>,
+    identity: Identity,
+    mut at: DateTime<Utc>,
+    broadcast: Broadcast,
+    handle: server::Handle,
+    ending: CancellationToken,
+) -> Result<()> {
+    loop {
+        tokio::time::sleep((at - Utc::now()).to_std().unwrap_or_default()).await;
+
+        // Access that can't be checked is treated as expired.
+        let allowed = match identity.authenticate(&controller).await {
+            Ok(ident) => ident.is_some(),
+            Err(err) => {
+                tracing::warn!(?err, "unable to check access");
+
+                false
+            }
+        };
+
+        if !allowed {
+            debug!("grant expired");
+
+            return end(&handle, &ending, "access expired").await;
+        }
+
+        // Another grant that applies, such as one for a group, may run out later.
+        let Some(next) = grant::expiration_for(controller.client()?, &identity)
+            .await?
+            .filter(|next| *next > at)
+        else {
+            return Ok(());
+        };
+
+        at = next;
+        broadcast.all(Event::Expires(at)).await?;
+    }
+}
+
 impl Drop for Session {
     fn drop(&mut self) {
         ACTIVE_SESSIONS.dec();
 
+        self.controller.sessions().disconnected(&self.id);
+
+        // Connections that drop without closing their channels first.
+        let ids: Vec<_> = self.dashboards.keys().copied().collect();
+        for id in ids {
+            self.detach(&id);
+        }
+
         SESSION_DURATION.observe(
             (Utc::now() - self.start)
                 .to_std()
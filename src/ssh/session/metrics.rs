@@ -1,7 +1,9 @@
+use std::collections::BTreeMap;
+
 use lazy_static::lazy_static;
 use prometheus::{
-    histogram_opts, opts, register_histogram, register_int_counter, register_int_counter_vec,
-    register_int_gauge, Histogram, IntCounter, IntCounterVec, IntGauge,
+    core::Collector, histogram_opts, opts, register_histogram, register_int_counter,
+    register_int_counter_vec, register_int_gauge, Histogram, IntCounter, IntCounterVec, IntGauge,
 };
 use prometheus_static_metric::make_static_metric;
 
@@ -9,12 +11,14 @@ make_static_metric! {
     pub struct MethodVec: IntCounter {
         "method" => {
             publickey,
+            certificate,
             interactive,
         }
     }
     pub struct ResultVec: IntCounter {
         "method" => {
             publickey,
+            certificate,
             interactive,
         },
         "result" => {
@@ -35,6 +39,8 @@ make_static_metric! {
             sftp,
             window_resize,
             tcpip_forward,
+            agent,
+            x11,
         }
     }
     pub struct ChannelVec: IntCounter {
@@ -43,6 +49,8 @@ make_static_metric! {
             close,
             eof,
             direct_tcpip,
+            forwarded_tcpip,
+            x11,
         }
     }
 }
@@ -57,9 +65,19 @@ lazy_static! {
     pub static ref SESSION_DURATION: Histogram = register_histogram!(histogram_opts!(
         "session_duration_minutes",
         "Session duration",
-        vec!(0.5, 1.0, 2.0, 5.0, 10.0, 30.0, 60.0),
+        vec!(0.5, 1.0, 2.0, 5.0, 10.0, 30.0, 60.0, 120.0, 240.0, 480.0),
     ))
     .unwrap();
+    pub static ref DETACHED: IntCounter = register_int_counter!(
+        "dashboards_detached_total",
+        "Number of dashboards kept running after their connection went away"
+    )
+    .unwrap();
+    pub static ref REATTACHED: IntCounter = register_int_counter!(
+        "dashboards_reattached_total",
+        "Number of times a detached dashboard was picked back up by a new connection"
+    )
+    .unwrap();
     pub static ref UNEXPECTED_STATE: IntCounterVec = register_int_counter_vec!(
         opts!(
             "unexpected_state_total",
@@ -112,6 +130,14 @@ lazy_static! {
     )
     .unwrap();
     pub static ref CODE_CHECKED: CodeVec = CodeVec::from(&CODE_CHECKED_VEC);
+    pub static ref AUTH_REJECTIONS: IntCounterVec = register_int_counter_vec!(
+        opts!(
+            "auth_rejections_total",
+            "Number of logins that didn't work, by the reason shown to the user",
+        ),
+        &["reason"],
+    )
+    .unwrap();
 }
 
 lazy_static! {
@@ -129,3 +155,19 @@ lazy_static! {
     .unwrap();
     pub static ref CHANNELS: ChannelVec = ChannelVec::from(&CHANNELS_VEC);
 }
+
+/// Sessions that have authenticated since the server started, by method.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn succeeded() -> BTreeMap<String, u64> {
+    AUTH_SUCEEDED
+        .collect()
+        .iter()
+        .flat_map(|family| family.get_metric())
+        .filter_map(|metric| {
+            Some((
+                metric.get_label().first()?.get_value().to_string(),
+                metric.get_counter().get_value() as u64,
+            ))
+        })
+        .collect()
+}
@@ -0,0 +1,437 @@
+use std::{convert::Infallible, sync::Arc};
+
+use bytes::Bytes;
+use eyre::{eyre, Result};
+use http::{header, HeaderMap, Method, Request, Response, StatusCode};
+use http_body_util::{combinators::UnsyncBoxBody, BodyExt, Full, LengthLimitError, Limited};
+use hyper::{body::Incoming, server::conn::http1, service::service_fn};
+use hyper_util::rt::TokioIo;
+use lazy_static::lazy_static;
+use percent_encoding::percent_decode_str;
+use prometheus::{opts, register_int_counter_vec, IntCounterVec};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::{
+    health,
+    identity::Identity,
+    resources::policy::{Policy, Verb},
+    ssh::Controller,
+};
+
+/// Forwarding to this host (`ssh -L 8001:kube-api:80`) serves the Kubernetes
+/// API as the user, the same way `kubectl proxy` does.
+pub static HOST: &str = "kube-api";
+
+// Requests are read into memory before being sent on, anything larger than
+// what the API server accepts by default is refused.
+static MAX_BODY: usize = 3 * 1024 * 1024;
+
+lazy_static! {
+    static ref API_REQUESTS: IntCounterVec = register_int_counter_vec!(
+        opts!(
+            "api_proxy_requests_total",
+            "Number of requests made to the API server via. the proxy",
+        ),
+        &["method", "code"],
+    )
+    .unwrap();
+}
+
+type Body = UnsyncBoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>;
+
+// Credentials and impersonation come from the server, anything the client
+// sends would let it escalate past the user's own permissions.
+fn strip(headers: &mut HeaderMap) {
+    let names: Vec<_> = headers
+        .keys()
+        .filter(|name| {
+            *name == header::AUTHORIZATION
+                || *name == header::HOST
+                || *name == header::CONNECTION
+                || *name == header::UPGRADE
+                || name.as_str().starts_with("impersonate-")
+        })
+        .cloned()
+        .collect();
+
+    for name in names {
+        headers.remove(name);
+    }
+}
+
+// Where a request points, split up the same way the API server does it:
+// `/api/v1/namespaces/{namespace}/{resource}/{name}/{subresource}`, or with
+// `/apis/{group}/{version}` in place of `/api/v1`.
+#[derive(Debug, Default, PartialEq)]
+struct Target {
+    // Empty for discovery, `/version` and anything else that isn't a resource.
+    group_version: String,
+    namespace: Option<String>,
+    resource: Option<String>,
+    name: Option<String>,
+    subresource: Option<String>,
+}
+
+// Namespaces have subresources of their own, `/namespaces/{name}/status` is
+// about the namespace rather than a list of `status` in it.
+static NAMESPACE_SUBRESOURCES: &[&str] = &["status", "finalize"];
+
+impl Target {
+    // The API server percent-decodes the path before routing it, so this has to
+    // as well. Anything that could be read more than one way is refused.
+    fn parse(path: &str) -> Option<Self> {
+        let path = path.split('?').next().unwrap_or_default();
+
+        if path == "/" {
+            return Some(Self::default());
+        }
+
+        let segments = path
+            .strip_prefix('/')?
+            .split('/')
+            .map(|segment| {
+                let segment = percent_decode_str(segment).decode_utf8().ok()?;
+
+                (!segment.is_empty() && segment != "." && segment != ".." && !segment.contains('/'))
+                    .then(|| segment.into_owned())
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let segments: Vec<_> = segments.iter().map(String::as_str).collect();
+
+        let (group_version, rest) = match segments.as_slice() {
+            ["api", version, rest @ ..] => ((*version).to_string(), rest),
+            ["apis", group, version, rest @ ..] => (format!("{group}/{version}"), rest),
+            _ => return Some(Self::default()),
+        };
+
+        // Watches can still be requested the deprecated way, `/watch/namespaces/...`.
+        let rest = match rest {
+            ["watch", rest @ ..] => rest,
+            rest => rest,
+        };
+
+        let (namespace, rest) = match rest {
+            ["namespaces", namespace, resource, ..]
+                if !NAMESPACE_SUBRESOURCES.contains(resource) =>
+            {
+                (Some((*namespace).to_string()), &rest[2..])
+            }
+            // The namespace itself is in the namespace as far as policies go.
+            ["namespaces", namespace, ..] => (Some((*namespace).to_string()), rest),
+            rest => (None, rest),
+        };
+
+        let [resource, name, subresource] =
+            [0, 1, 2].map(|idx| rest.get(idx).map(ToString::to_string));
+
+        Some(Self {
+            group_version,
+            namespace,
+            resource,
+            name,
+            subresource,
+        })
+    }
+
+    fn is(&self, group_version: &str, resource: &str) -> bool {
+        self.group_version == group_version && self.resource.as_deref() == Some(resource)
+    }
+
+    // What the request does, so that verbs disabled for everyone can't be used
+    // through the proxy either.
+    fn verbs(&self, method: &Method) -> Vec<Verb> {
+        let mut verbs: Vec<_> = match *method {
+            Method::DELETE => Some(Verb::Delete),
+            Method::POST => Some(Verb::Create),
+            Method::PUT | Method::PATCH => Some(Verb::Patch),
+            _ => None,
+        }
+        .into_iter()
+        .collect();
+
+        if self.is("v1", "pods") {
+            match self.subresource.as_deref() {
+                Some("log") => verbs.push(Verb::Logs),
+                Some("exec" | "attach") => verbs.push(Verb::Exec),
+                Some("portforward") => verbs.push(Verb::PortForward),
+                _ => {}
+            }
+        }
+
+        if self.is("v1", "secrets") {
+            verbs.push(Verb::Secrets);
+        }
+
+        verbs
+    }
+}
+
+// Cluster scoped resources are always visible, only namespaced ones that span
+// every namespace need to be kept from users who can't see all of them.
+async fn namespaced(client: &kube::Client, group_version: &str, resource: &str) -> Result<bool> {
+    let resources = if group_version.contains('/') {
+        client.list_api_group_resources(group_version).await?
+    } else {
+        client.list_core_api_resources(group_version).await?
+    };
+
+    Ok(resources
+        .resources
+        .iter()
+        .any(|r| r.name == resource && r.namespaced))
+}
+
+// Why `policy` doesn't allow the request, if it doesn't.
+async fn refused(
+    client: &kube::Client,
+    policy: &Policy,
+    method: &Method,
+    target: &Target,
+) -> Result<Option<String>> {
+    for verb in target.verbs(method) {
+        if policy.disables(verb) {
+            return Ok(Some(format!(
+                "{} has been disabled by an administrator\n",
+                verb.name()
+            )));
+        }
+
+        if !policy.allows(verb) {
+            return Ok(Some(format!(
+                "{} isn't allowed by your access policy\n",
+                verb.name()
+            )));
+        }
+    }
+
+    let hidden = match (&target.namespace, &target.resource) {
+        (Some(namespace), _) => !policy.sees(Some(namespace)),
+        // A resource across every namespace, or one that is cluster scoped.
+        (None, Some(resource)) => {
+            !policy.sees_all() && namespaced(client, &target.group_version, resource).await?
+        }
+        (None, None) => false,
+    };
+
+    Ok(hidden.then(|| {
+        "your access policy doesn't allow this namespace, or requests across every namespace\n"
+            .to_string()
+    }))
+}
+
+// `kube::Client` can't hand over the raw connection after an upgrade, so SPDY
+// and WebSocket requests can't be relayed.
+fn upgrade(headers: &HeaderMap) -> bool {
+    headers.contains_key(header::UPGRADE)
+        || headers
+            .get_all(header::CONNECTION)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(|value| value.trim().eq_ignore_ascii_case("upgrade"))
+}
+
+fn error(code: StatusCode, msg: String) -> Response<Body> {
+    let mut response = Response::new(
+        Full::new(Bytes::from(msg))
+            .map_err(|never| match never {})
+            .boxed_unsync(),
+    );
+    *response.status_mut() = code;
+
+    response
+}
+
+async fn forward(client: kube::Client, req: Request<Incoming>) -> Result<Response<Body>> {
+    let (mut parts, body) = req.into_parts();
+
+    let body = match Limited::new(body, MAX_BODY).collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(err) if err.is::<LengthLimitError>() => {
+            return Ok(error(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("request bodies are limited to {MAX_BODY} bytes\n"),
+            ));
+        }
+        Err(err) => return Err(eyre!(err)),
+    };
+
+    // The client adds the API server's address, only the path is kept.
+    parts.uri = parts
+        .uri
+        .path_and_query()
+        .map_or("/", |p| p.as_str())
+        .parse()?;
+    strip(&mut parts.headers);
+
+    let response = client
+        .send(Request::from_parts(parts, kube::client::Body::from(body)))
+        .await?;
+
+    Ok(response.map(|body| body.map_err(Into::into).boxed_unsync()))
+}
+
+/// Serve HTTP on `io`, sending every request on to the API server with
+/// `client`. Each request is added to the audit log for session `id`. When
+/// `token` is set, requests must carry it as a bearer token. It only
+/// authenticates the connection to the proxy and isn't sent on.
+///
+/// Access policies for `identity` apply to every request, along with verbs
+/// disabled for everyone. Requests across every namespace are refused when a
+/// policy limits namespaces.
+///
+/// Upgrades aren't supported, so `exec`, `attach` and `port-forward` don't
+/// work through the proxy and are refused with `501 Not Implemented`. Use the
+/// dashboard or `ssh -L` for those.
+pub async fn serve<S>(
+    client: kube::Client,
+    controller: Arc<Controller>,
+    id: String,
+    identity: Identity,
+    io: S,
+    token: Option<String>,
+) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    let service = service_fn(move |req: Request<Incoming>| {
+        let client = client.clone();
+        let controller = controller.clone();
+        let id = id.clone();
+        let identity = identity.clone();
+        let token = token.clone();
+
+        async move {
+            let method = req.method().clone();
+            let path = req
+                .uri()
+                .path_and_query()
+                .map_or_else(|| "/".to_string(), ToString::to_string);
+
+            // Policies can change during a session, they're looked up for every request.
+            let policy = controller.policy(&identity.name, &identity.groups);
+
+            let authorized = token.as_deref().map_or(true, |token| {
+                health::bearer(
+                    req.headers()
+                        .get(header::AUTHORIZATION)
+                        .and_then(|value| value.to_str().ok()),
+                    token,
+                )
+            });
+
+            let target = Target::parse(&path);
+
+            let refused = match (&policy, target.as_ref()) {
+                (Ok(policy), Some(target)) if authorized => {
+                    refused(&client, policy, &method, target).await
+                }
+                _ => Ok(None),
+            };
+
+            let response = if !authorized {
+                error(StatusCode::UNAUTHORIZED, "invalid token\n".to_string())
+            } else if let Err(err) = &policy {
+                tracing::warn!(?err, "refusing api proxy request");
+
+                error(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "access policies are unavailable, try again later\n".to_string(),
+                )
+            } else if target.is_none() {
+                error(StatusCode::BAD_REQUEST, "invalid path\n".to_string())
+            } else if let Err(err) = &refused {
+                tracing::warn!(?err, "unable to check api proxy request against policies");
+
+                error(
+                    StatusCode::BAD_GATEWAY,
+                    "unable to check the request against access policies\n".to_string(),
+                )
+            } else if let Ok(Some(msg)) = refused {
+                error(StatusCode::FORBIDDEN, msg)
+            } else if upgrade(req.headers()) {
+                error(
+                    StatusCode::NOT_IMPLEMENTED,
+                    "upgrading the connection isn't supported by the kty proxy, exec, attach and \
+                     port-forward are only available from the dashboard\n"
+                        .to_string(),
+                )
+            } else {
+                match forward(client, req).await {
+                    Ok(response) => response,
+                    Err(err) => {
+                        tracing::warn!(?err, "api proxy request failed");
+
+                        error(
+                            StatusCode::BAD_GATEWAY,
+                            "unable to send the request to the API server\n".to_string(),
+                        )
+                    }
+                }
+            };
+
+            let code = response.status();
+
+            API_REQUESTS
+                .with_label_values(&[method.as_str(), code.as_str()])
+                .inc();
+            controller
+                .sessions()
+                .api_request(&id, format!("{method} {path} {}", code.as_u16()));
+
+            Ok::<_, Infallible>(response)
+        }
+    });
+
+    http1::Builder::new()
+        .serve_connection(TokioIo::new(io), service)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_by_position() {
+        let target = Target::parse("/api/v1/namespaces/secrets/pods/p/log?follow=true").unwrap();
+
+        assert_eq!(target.namespace.as_deref(), Some("secrets"));
+        assert_eq!(target.verbs(&Method::GET), vec![Verb::Logs]);
+
+        let target = Target::parse("/apis/apps/v1/watch/deployments").unwrap();
+
+        assert_eq!(target.group_version, "apps/v1");
+        assert_eq!(target.namespace, None);
+        assert_eq!(target.resource.as_deref(), Some("deployments"));
+
+        let target = Target::parse("/api/v1/namespaces/default/status").unwrap();
+
+        assert_eq!(target.resource.as_deref(), Some("namespaces"));
+        assert_eq!(target.name.as_deref(), Some("default"));
+    }
+
+    #[test]
+    fn decodes_before_matching() {
+        let target = Target::parse("/api/v1/namespaces/x/secret%73").unwrap();
+        assert_eq!(target.verbs(&Method::GET), vec![Verb::Secrets]);
+
+        let target = Target::parse("/api/v1/namespaces/x/pods/p/lo%67").unwrap();
+        assert_eq!(target.verbs(&Method::GET), vec![Verb::Logs]);
+    }
+
+    #[test]
+    fn refuses_ambiguous_paths() {
+        for path in [
+            "/api/v1/namespaces/x/../y/secrets",
+            "/api/v1/namespaces/x/./secrets",
+            "/api/v1//secrets",
+            "/api/v1/namespaces/x%2Fy/pods",
+            "api/v1/pods",
+        ] {
+            assert_eq!(Target::parse(path), None, "{path}");
+        }
+    }
+}
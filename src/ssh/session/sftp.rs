@@ -67,16 +67,18 @@ impl Default for State {
 pub struct Handler {
     client: kube::Client,
     state: State,
+    span: tracing::Span,
 }
 
 // TODO: would it be better to add a `Store<Pod>` to this?
 impl Handler {
-    pub fn new(client: kube::Client) -> Self {
+    pub fn new(client: kube::Client, span: tracing::Span) -> Self {
         SFTP_ACTIVE.inc();
 
         Self {
             client,
             state: State::default(),
+            span,
         }
     }
 }
@@ -104,7 +106,7 @@ impl server::Handler for Handler {
         })
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(parent = &self.span, skip(self))]
     async fn read(
         &mut self,
         id: u32,
@@ -144,7 +146,7 @@ impl server::Handler for Handler {
         })
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(parent = &self.span, skip(self))]
     async fn write(
         &mut self,
         _id: u32,
@@ -163,7 +165,7 @@ impl server::Handler for Handler {
         Ok(Handle { id, handle: path })
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(parent = &self.span, skip(self))]
     async fn readdir(&mut self, id: u32, handle: String) -> Result<Name, Self::Error> {
         SFTP_LIST.inc();
         tracing::debug!("readdir");
@@ -197,7 +199,7 @@ impl server::Handler for Handler {
         })
     }
 
-    #[tracing::instrument(skip(self))]
+    #[tracing::instrument(parent = &self.span, skip(self))]
     async fn stat(&mut self, id: u32, path: String) -> Result<Attrs, Self::Error> {
         SFTP_STAT.inc();
         tracing::debug!("stat");
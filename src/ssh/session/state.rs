@@ -1,15 +1,25 @@
 use std::str;
 
-use russh::keys::key::PublicKey;
+use ssh_key::PublicKey;
 
-use crate::{identity::Identity, openid};
+use crate::{
+    identity::{Identity, Key, Rejection},
+    openid,
+};
 
 #[derive(Debug, strum_macros::AsRefStr)]
 pub enum State {
     Unauthenticated,
     KeyOffered(PublicKey),
     CodeSent(openid::DeviceCode, Option<PublicKey>),
-    InvalidIdentity(Identity, Option<PublicKey>),
+    // Login didn't work, the reason is shown along with the next code.
+    Rejected(Rejection, Option<PublicKey>),
+    // The `OpenID` provider couldn't be used and a break-glass code has been
+    // asked for instead.
+    CodeRequested,
+    // Verified with the `OpenID` provider, the user is asked whether the offered
+    // key should be bound to the identity before continuing.
+    Confirm(Identity, Box<Key>),
     // Once an authenticated state is reached, the user can really go do
     // whatever they want. For example, a dashboard and port-forwarding can
     // happen. This is intended to be the final state.
@@ -30,7 +40,7 @@ impl State {
     pub fn code_sent(&mut self, code: &openid::DeviceCode) {
         let key = match self {
             State::KeyOffered(key) => Some(key.clone()),
-            State::InvalidIdentity(_, key) => key.clone(),
+            State::Rejected(_, key) => key.clone(),
             _ => None,
         };
 
@@ -54,13 +64,22 @@ impl State {
         }
     }
 
-    pub fn invalid_identity(&mut self, identity: Identity) {
+    pub fn rejected(&mut self, rejection: Rejection) {
         let key = match self {
             State::KeyOffered(key) => Some(key.clone()),
+            State::CodeSent(_, key) | State::Rejected(_, key) => key.clone(),
             _ => None,
         };
 
-        *self = State::InvalidIdentity(identity, key);
+        *self = State::Rejected(rejection, key);
+    }
+
+    pub fn code_requested(&mut self) {
+        *self = State::CodeRequested;
+    }
+
+    pub fn confirm(&mut self, identity: Identity, key: Key) {
+        *self = State::Confirm(identity, Box::new(key));
     }
 
     pub fn authenticated(&mut self, identity: Identity) {
@@ -1,18 +1,56 @@
+pub mod actions;
+pub mod alerts;
+pub mod allocation;
 pub mod apex;
+pub mod bulk;
+pub mod caps;
+pub mod clock;
+pub mod create;
 pub mod debug;
+pub mod deployment;
 pub mod error;
+pub mod events;
+pub mod filter;
+pub mod gateway;
+pub mod gitops;
+pub mod help;
+pub mod hpa;
+pub mod idle;
+pub mod image;
 pub mod input;
+pub mod keys;
+pub mod link;
 pub mod loading;
+pub mod locale;
 pub mod log;
+pub mod metadata;
+pub mod narration;
 pub mod nav;
+pub mod netpol;
 pub mod node;
+pub mod orphan;
+pub mod overview;
+pub mod pdb;
+pub mod plugin;
 pub mod pod;
+pub mod preview;
+pub mod pv;
+pub mod pvc;
+pub mod rollout;
+pub mod saved;
+pub mod scratchpad;
+pub mod scrollback;
+pub mod statusbar;
 pub mod table;
 pub mod tabs;
+pub mod target;
+pub mod timeline;
 pub mod tunnel;
 pub mod view;
 pub mod viewport;
+pub mod workload;
 pub mod yaml;
+pub mod yank;
 
 use std::pin::Pin;
 
@@ -33,16 +71,48 @@ use crate::events::{Broadcast, Event};
 make_static_metric! {
     pub struct WidgetVec: IntCounter {
         "resource" => {
+            alert,
             container,
+            deployment,
+            gateway,
+            idle,
+            image,
+            netpol,
             pod,
             node,
+            orphan,
+            overview,
+            pdb,
+            plugin,
+            pv,
+            pvc,
+            view,
+            workload,
         },
         "type" => {
+            actions,
+            allocation,
+            autoscaler,
+            bulk,
             cmd,
+            commands,
+            create,
+            debug,
             detail,
+            events,
+            evictions,
             exec,
+            files,
+            filter,
+            help,
+            launch,
             list,
             log,
+            metadata,
+            probes,
+            scratchpad,
+            scrollback,
+            targets,
             yaml,
         },
     }
@@ -96,6 +166,30 @@ pub trait Widget {
         0
     }
 
+    /// Where the user currently is, for example `["pods", "default", "nginx"]`.
+    /// Used for the terminal's title.
+    fn title(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Filter of the table being shown, copied over when a tab is duplicated.
+    fn filter(&self) -> Option<String> {
+        None
+    }
+
+    /// Whether something changed without any input, such as new log lines or a
+    /// running animation, and the next frame needs to be drawn. Idle sessions
+    /// skip frames otherwise.
+    fn dirty(&self) -> bool {
+        false
+    }
+
+    /// The widget and everything it contains, one per line and indented by
+    /// depth. Included in snapshots, see [`crate::resources::snapshot`].
+    fn tree(&self) -> Vec<String> {
+        vec![self._name().to_string()]
+    }
+
     fn boxed(self) -> BoxWidget
     where
         Self: Sized + 'static,
@@ -104,6 +198,18 @@ pub trait Widget {
     }
 }
 
+/// `name` followed by the trees of `children`, indented a level.
+pub fn nested<'a>(name: &str, children: impl IntoIterator<Item = &'a dyn Widget>) -> Vec<String> {
+    std::iter::once(name.to_string())
+        .chain(
+            children
+                .into_iter()
+                .flat_map(|child| child.tree())
+                .map(|line| format!("  {line}")),
+        )
+        .collect()
+}
+
 impl std::fmt::Debug for Box<dyn Widget> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct(format!("Box<dyn Widget<{}>>", self._name()).as_str())
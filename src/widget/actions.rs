@@ -0,0 +1,296 @@
+use std::{borrow::Borrow, sync::Arc};
+
+use eyre::Result;
+use kube::{
+    api::{ApiResource, DynamicObject},
+    Api, Resource, ResourceExt,
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{palette::tailwind, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use tokio::task::JoinHandle;
+
+use super::{preview::Preview, tabs::Tab, Widget, WIDGET_VIEWS_VEC};
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    resources::{
+        action::{self, Action, Actions as _},
+        gitops,
+        policy::Verb,
+        preview::Outcome,
+    },
+};
+
+enum Status {
+    Idle,
+    Running(String, JoinHandle<Result<()>>),
+    Done(Result<String>),
+}
+
+/// Actions declared on a resource via. annotations. Each action is bound to a
+/// key and applies a patch to the resource as the current user.
+pub struct Actions {
+    client: Api<DynamicObject>,
+    name: String,
+    actions: Result<Vec<Action>>,
+    // Managed by a GitOps controller, see `gitops::reverts`.
+    reverts: bool,
+
+    idx: usize,
+    status: Status,
+    preview: Option<(Preview, usize)>,
+}
+
+impl Actions {
+    pub fn new<K>(client: kube::Client, resource: &Arc<K>) -> Self
+    where
+        K: Resource<DynamicType = ()> + Send + Sync + 'static,
+    {
+        Self::with_api(
+            K::kind(&()).borrow(),
+            resource.dynamic_api(client),
+            resource.as_ref(),
+        )
+    }
+
+    /// For resources that are only known at runtime, such as the custom
+    /// resources a plugin lists.
+    pub fn dynamic(client: kube::Client, resource: &ApiResource, object: &DynamicObject) -> Self {
+        let api = match object.namespace() {
+            Some(ns) => Api::namespaced_with(client, ns.as_str(), resource),
+            None => Api::all_with(client, resource),
+        };
+
+        Self::with_api(&resource.kind, api, object)
+    }
+
+    fn with_api(kind: &str, client: Api<DynamicObject>, resource: &impl ResourceExt) -> Self {
+        WIDGET_VIEWS_VEC.with_label_values(&[kind, "actions"]).inc();
+
+        Self {
+            client,
+            name: resource.name_any(),
+            actions: action::declared(resource),
+            reverts: gitops::reverts(resource),
+
+            idx: 0,
+            status: Status::Idle,
+            preview: None,
+        }
+    }
+
+    pub fn tab<K>(name: String, client: kube::Client, resource: Arc<K>) -> Tab
+    where
+        K: Resource<DynamicType = ()> + Send + Sync + 'static,
+    {
+        Tab::builder()
+            .name(name)
+            .verb(Verb::Patch)
+            .constructor(Box::new(move || {
+                Self::new(client.clone(), &resource).boxed().into()
+            }))
+            .build()
+    }
+
+    /// See [`Actions::dynamic`].
+    pub fn dynamic_tab(
+        name: String,
+        client: kube::Client,
+        resource: ApiResource,
+        object: Arc<DynamicObject>,
+    ) -> Tab {
+        Tab::builder()
+            .name(name)
+            .verb(Verb::Patch)
+            .constructor(Box::new(move || {
+                Self::dynamic(client.clone(), &resource, &object)
+                    .boxed()
+                    .into()
+            }))
+            .build()
+    }
+
+    fn run(&mut self, idx: usize) {
+        if matches!(self.status, Status::Running(..)) {
+            return;
+        }
+
+        let Ok(actions) = self.actions.as_ref() else {
+            return;
+        };
+
+        let action = actions[idx].clone();
+        let client = self.client.clone();
+        let name = self.name.clone();
+
+        self.idx = idx;
+
+        if super::preview::enabled() || self.reverts {
+            let preview = Preview::new(action.name.clone(), async move {
+                let before = client.get(&name).await?;
+                let after = action.apply(&client, &name, true).await?;
+
+                Ok(vec![Outcome {
+                    target: name,
+                    before: Some(before),
+                    after: Some(after),
+                }])
+            });
+
+            self.preview = Some((preview, idx));
+
+            return;
+        }
+
+        self.apply(idx);
+    }
+
+    fn apply(&mut self, idx: usize) {
+        let Ok(actions) = self.actions.as_ref() else {
+            return;
+        };
+
+        let action = actions[idx].clone();
+        let client = self.client.clone();
+        let name = self.name.clone();
+
+        self.status = Status::Running(
+            action.name.clone(),
+            tokio::spawn(async move { action.apply(&client, &name, false).await.map(|_| ()) }),
+        );
+    }
+
+    fn update(&mut self) {
+        let Status::Running(name, task) = &mut self.status else {
+            return;
+        };
+
+        if !task.is_finished() {
+            return;
+        }
+
+        let name = name.clone();
+        let result = futures::executor::block_on(async move { task.await? });
+
+        self.status = Status::Done(result.map(|()| name));
+    }
+}
+
+impl Widget for Actions {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        if let Some((preview, idx)) = self.preview.as_mut() {
+            match preview.dispatch(event, buffer, area)? {
+                Broadcast::Selected(_) => {
+                    let idx = *idx;
+                    self.preview = None;
+                    self.apply(idx);
+                }
+                Broadcast::Exited => self.preview = None,
+                _ => {}
+            }
+
+            return Ok(Broadcast::Consumed);
+        }
+
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        let Ok(actions) = self.actions.as_ref() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        match key {
+            Keypress::CursorUp | Keypress::Printable('k') => {
+                self.idx = self.idx.saturating_sub(1);
+            }
+            Keypress::CursorDown | Keypress::Printable('j') => {
+                self.idx = (self.idx + 1).min(actions.len().saturating_sub(1));
+            }
+            Keypress::Enter if !actions.is_empty() => self.run(self.idx),
+            Keypress::Printable(c) => {
+                let Some(idx) = actions.iter().position(|a| a.key == *c) else {
+                    return Ok(Broadcast::Ignored);
+                };
+
+                self.run(idx);
+            }
+            _ => return Ok(Broadcast::Ignored),
+        }
+
+        Ok(Broadcast::Consumed)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.update();
+
+        let block = Block::default().borders(Borders::ALL);
+
+        let actions = match self.actions.as_ref() {
+            Ok(actions) => actions,
+            Err(err) => {
+                frame.render_widget(
+                    Paragraph::new(err.to_string())
+                        .style(Style::default().fg(tailwind::RED.c300))
+                        .block(block),
+                    area,
+                );
+
+                return Ok(());
+            }
+        };
+
+        let [list, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(block.inner(area));
+
+        let lines: Vec<Line> = actions
+            .iter()
+            .enumerate()
+            .map(|(i, action)| {
+                let line = Line::from(format!(
+                    "[{}] {}  {}",
+                    action.key, action.name, action.patch
+                ));
+
+                if i == self.idx {
+                    line.style(Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    line
+                }
+            })
+            .collect();
+
+        frame.render_widget(Paragraph::new(lines), list);
+
+        let status_line = match &self.status {
+            Status::Idle => Paragraph::new("Press a key or enter to run an action"),
+            Status::Running(name, _) => Paragraph::new(format!("Running {name}...")),
+            Status::Done(Ok(name)) => Paragraph::new(format!("Applied {name}"))
+                .style(Style::default().fg(tailwind::GREEN.c300)),
+            Status::Done(Err(err)) => Paragraph::new(format!("Failed: {err}"))
+                .style(Style::default().fg(tailwind::RED.c300)),
+        };
+
+        frame.render_widget(status_line, status);
+        frame.render_widget(block, area);
+
+        if let Some((preview, _)) = self.preview.as_mut() {
+            preview.draw(frame, area)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Actions {
+    fn drop(&mut self) {
+        if let Status::Running(_, task) = &self.status {
+            task.abort();
+        }
+    }
+}
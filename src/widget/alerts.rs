@@ -0,0 +1,292 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use eyre::{eyre, Result};
+use ratatui::{buffer::Buffer, layout::Rect, Frame};
+use tokio::task::JoinHandle;
+
+use super::{
+    error::Error,
+    link::{Fetched, Link},
+    loading::Loading,
+    nested, propagate, table,
+    tabs::Tab,
+    view::{Element, View},
+    BoxWidget, Widget, WIDGET_VIEWS,
+};
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    history::History,
+    resources::alerts::{Alert, AlertSource, Firing},
+};
+
+// Alerts aren't watched, this is how long until they're fetched again.
+static REFRESH_EVERY: Duration = Duration::from_secs(30);
+
+/// Alerts that are firing, from Alertmanager or Prometheus. Selecting one opens
+/// the detail of the resource it is about.
+pub struct List {
+    view: View,
+    client: kube::Client,
+    source: AlertSource,
+    firing: Firing,
+    task: Option<JoinHandle<Result<Vec<Arc<Alert>>>>>,
+    // Unset until the first fetch has finished.
+    fetched: Option<Instant>,
+}
+
+impl List {
+    pub fn new(client: kube::Client, history: Arc<dyn History>, source: AlertSource) -> Self {
+        WIDGET_VIEWS.alert.list.inc();
+
+        let firing = Firing::default();
+
+        let table = table::Filtered::builder()
+            .table(
+                table::Table::builder()
+                    .items(firing.clone())
+                    .filter(Rc::new(RefCell::new(None)))
+                    .build(),
+            )
+            .constructor(Affected::from_firing(
+                client.clone(),
+                history,
+                firing.clone(),
+            ))
+            .build();
+
+        let widgets = vec![
+            table.boxed().into(),
+            Element::builder()
+                .widget(Loading.boxed())
+                .ignore(true)
+                .build(),
+        ];
+
+        Self {
+            view: View::builder().widgets(widgets).build(),
+            task: Some(fetch(client.clone(), source.clone())),
+            client,
+            source,
+            firing,
+            fetched: None,
+        }
+    }
+
+    pub fn tab(
+        name: String,
+        client: kube::Client,
+        history: Arc<dyn History>,
+        source: AlertSource,
+        terminal: bool,
+    ) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Element::builder()
+                    .widget(Self::new(client.clone(), history.clone(), source.clone()).boxed())
+                    .terminal(terminal)
+                    .build()
+            }))
+            .build()
+    }
+
+    // Pick up the last fetch without blocking the frame, and start the next one
+    // once it is due.
+    fn poll(&mut self) {
+        let Some(task) = self.task.as_mut() else {
+            if self.fetched.is_some_and(|at| at.elapsed() > REFRESH_EVERY) {
+                self.task = Some(fetch(self.client.clone(), self.source.clone()));
+            }
+
+            return;
+        };
+
+        if !task.is_finished() {
+            return;
+        }
+
+        let result = futures::executor::block_on(async move { task.await? });
+        self.task = None;
+
+        if self.fetched.replace(Instant::now()).is_none() {
+            self.view.pop();
+        }
+
+        match result {
+            Ok(alerts) => *self.firing.0.borrow_mut() = alerts,
+            Err(err) => self.view.error(Error::from(err)),
+        }
+    }
+}
+
+fn fetch(client: kube::Client, source: AlertSource) -> JoinHandle<Result<Vec<Arc<Alert>>>> {
+    tokio::spawn(async move { source.fetch(client).await })
+}
+
+impl Drop for List {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.as_ref() {
+            task.abort();
+        }
+    }
+}
+
+impl Widget for List {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.poll();
+
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        self.view.zindex()
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        let title = self.view.title();
+
+        if title.is_empty() {
+            vec!["alerts".to_string()]
+        } else {
+            title
+        }
+    }
+
+    fn filter(&self) -> Option<String> {
+        self.view.filter()
+    }
+
+    fn dirty(&self) -> bool {
+        self.task.as_ref().is_some_and(JoinHandle::is_finished) || self.view.dirty()
+    }
+}
+
+/// The detail view of the resource an alert is about. It is fetched in the
+/// background, the same way a [`Link`] is when the dashboard starts.
+struct Affected {
+    link: Link,
+    client: kube::Client,
+    history: Arc<dyn History>,
+    task: JoinHandle<Result<Option<Fetched>>>,
+    detail: Option<BoxWidget>,
+}
+
+impl Affected {
+    fn new(client: kube::Client, history: Arc<dyn History>, link: Link) -> Self {
+        let task = {
+            let client = client.clone();
+            let link = link.clone();
+
+            tokio::spawn(async move { link.fetch(client).await })
+        };
+
+        Self {
+            link,
+            client,
+            history,
+            task,
+            detail: None,
+        }
+    }
+
+    fn from_firing(
+        client: kube::Client,
+        history: Arc<dyn History>,
+        firing: Firing,
+    ) -> table::DetailFn {
+        Box::new(move |idx, filter| {
+            let alert = table::Items::items(&firing, filter)
+                .get(idx)
+                .cloned()
+                .ok_or_else(|| eyre!("alert not found"))?;
+
+            let link = alert
+                .link()
+                .ok_or_else(|| eyre!("{} isn't about a resource kty can show", alert.name()))?;
+
+            Ok(Self::new(client.clone(), history.clone(), link).boxed())
+        })
+    }
+}
+
+impl Drop for Affected {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl Widget for Affected {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        if let Some(detail) = self.detail.as_mut() {
+            return detail.dispatch(event, buffer, area);
+        }
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if self.detail.is_none() && self.task.is_finished() {
+            let task = &mut self.task;
+
+            self.detail = Some(
+                match futures::executor::block_on(async move { task.await? }) {
+                    Ok(Some(fetched)) => fetched.widget(self.client.clone(), self.history.clone()),
+                    Ok(None) => Error::from(eyre!("{} can't be shown", self.link)).boxed(),
+                    Err(err) => Error::from(err).boxed(),
+                },
+            );
+        }
+
+        match self.detail.as_mut() {
+            Some(detail) => detail.draw(frame, area),
+            None => Loading.draw(frame, area),
+        }
+    }
+
+    fn zindex(&self) -> u16 {
+        self.detail.as_ref().map_or(0, |detail| detail.zindex())
+    }
+
+    fn tree(&self) -> Vec<String> {
+        match self.detail.as_ref() {
+            Some(detail) => nested(self._name(), [detail.as_ref()]),
+            None => vec![self._name().to_string()],
+        }
+    }
+
+    fn title(&self) -> Vec<String> {
+        match self.detail.as_ref() {
+            Some(detail) => detail.title(),
+            None => vec![self.link.to_string()],
+        }
+    }
+
+    fn dirty(&self) -> bool {
+        self.detail
+            .as_ref()
+            .map_or_else(|| self.task.is_finished(), |detail| detail.dirty())
+    }
+}
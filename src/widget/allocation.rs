@@ -0,0 +1,327 @@
+use std::sync::Arc;
+
+use eyre::Result;
+use k8s_openapi::api::core::v1::{Node, Pod};
+use kube::{api::ListParams, Api, ResourceExt};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{palette::tailwind, Style, Stylize},
+    text::Line,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    Frame,
+};
+use tokio::task::JoinHandle;
+
+use super::{
+    nav::{move_cursor, Movement},
+    tabs::Tab,
+    Widget, WIDGET_VIEWS,
+};
+use crate::{
+    events::{Broadcast, Event},
+    resources::{
+        node::{self, Usage},
+        pod::PodExt,
+        policy::Policy,
+        requirements::{self, Requirements},
+    },
+};
+
+struct Summary {
+    title: String,
+    items: Vec<(String, Requirements)>,
+    // Allocatable CPU (cores) and memory (bytes), only available for nodes.
+    allocatable: Option<(f64, f64)>,
+    // Extended resources such as GPUs, only for nodes that have any.
+    extended: Vec<Usage>,
+}
+
+impl Summary {
+    fn pod(pod: &Pod) -> Self {
+        let items = pod
+            .spec
+            .iter()
+            .flat_map(|spec| {
+                spec.init_containers
+                    .iter()
+                    .flatten()
+                    .chain(&spec.containers)
+            })
+            .map(|c| (c.name.clone(), Requirements::from(c)))
+            .collect();
+
+        Self {
+            title: format!("QoS: {}", pod.qos()),
+            items,
+            allocatable: None,
+            extended: Vec::new(),
+        }
+    }
+
+    // Runs in a task, so `policy` is passed in instead of being read from the
+    // dashboard's thread. Pods in namespaces it hides still count towards the
+    // node's totals, they're only shown as one row without their names.
+    async fn node(client: kube::Client, node: Arc<Node>, policy: Policy) -> Result<Self> {
+        let pods = Api::<Pod>::all(client)
+            .list(&ListParams::default().fields(&format!(
+                "spec.nodeName={},status.phase!=Succeeded,status.phase!=Failed",
+                node.name_any()
+            )))
+            .await?;
+
+        let (visible, hidden): (Vec<_>, Vec<_>) = pods
+            .iter()
+            .partition(|pod| policy.sees(pod.namespace().as_deref()));
+
+        let mut items = visible
+            .iter()
+            .map(|pod| {
+                (
+                    format!("{}/{}", pod.namespace().unwrap_or_default(), pod.name_any()),
+                    pod.requirements(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if !hidden.is_empty() {
+            items.push((
+                format!("{} pods in other namespaces", hidden.len()),
+                hidden.iter().map(|pod| pod.requirements()).sum(),
+            ));
+        }
+
+        Ok(Self {
+            title: format!("{} pods", pods.items.len()),
+            items,
+            allocatable: Some(requirements::allocatable(
+                node.status.as_ref().and_then(|s| s.allocatable.as_ref()),
+            )),
+            extended: node::usage(&node, &pods.items),
+        })
+    }
+
+    fn rows(&self) -> Vec<Row> {
+        let total: Requirements = self.items.iter().map(|(_, r)| *r).sum();
+
+        let mut rows: Vec<Row> = self
+            .items
+            .iter()
+            .map(|(name, r)| Row::new(vec![name.clone(), r.cpu(), r.memory()]))
+            .collect();
+
+        rows.push(
+            Row::new(vec!["Total".to_string(), total.cpu(), total.memory()])
+                .style(Style::default().bold()),
+        );
+
+        if let Some((cpu, memory)) = self.allocatable {
+            rows.push(
+                Row::new(vec![
+                    "Allocatable".to_string(),
+                    requirements::cpu(cpu),
+                    requirements::memory(memory),
+                ])
+                .style(Style::default().bold()),
+            );
+            rows.push(
+                Row::new(vec![
+                    "Requested".to_string(),
+                    percent(total.cpu_request, cpu),
+                    percent(total.memory_request, memory),
+                ])
+                .style(Style::default().bold()),
+            );
+        }
+
+        rows
+    }
+}
+
+fn percent(value: f64, total: f64) -> String {
+    if total <= 0.0 {
+        return "-".to_string();
+    }
+
+    format!("{:.0}%", value / total * 100.0)
+}
+
+enum State {
+    Loading(JoinHandle<Result<Summary>>),
+    Ready(Summary),
+    Failed(String),
+}
+
+/// Requests and limits, aggregated across containers for pods and across the
+/// scheduled pods for nodes.
+pub struct Allocation {
+    state: State,
+    offset: usize,
+}
+
+impl Allocation {
+    pub fn pod_tab(name: String, pod: Arc<Pod>) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                WIDGET_VIEWS.pod.allocation.inc();
+
+                Self {
+                    state: State::Ready(Summary::pod(&pod)),
+                    offset: 0,
+                }
+                .boxed()
+                .into()
+            }))
+            .build()
+    }
+
+    pub fn node_tab(name: String, client: kube::Client, node: Arc<Node>) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                WIDGET_VIEWS.node.allocation.inc();
+
+                Self {
+                    state: State::Loading(tokio::spawn(Summary::node(
+                        client.clone(),
+                        node.clone(),
+                        Policy::current(),
+                    ))),
+                    offset: 0,
+                }
+                .boxed()
+                .into()
+            }))
+            .build()
+    }
+}
+
+impl Widget for Allocation {
+    fn dispatch(&mut self, event: &Event, _: &Buffer, area: Rect) -> Result<Broadcast> {
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        if let Some(Movement::Y(y)) = move_cursor(key, area) {
+            self.offset = self.offset.saturating_add_signed(y.into());
+
+            return Ok(Broadcast::Consumed);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let block = Block::default().borders(Borders::ALL);
+
+        if let State::Loading(task) = &mut self.state {
+            if !task.is_finished() {
+                frame.render_widget(Line::from("Loading...").centered(), block.inner(area));
+
+                return Ok(());
+            }
+
+            self.state = match futures::executor::block_on(async move { task.await? }) {
+                Ok(summary) => State::Ready(summary),
+                Err(err) => State::Failed(err.to_string()),
+            };
+        }
+
+        let summary = match &self.state {
+            State::Ready(summary) => summary,
+            State::Failed(err) => {
+                frame.render_widget(
+                    Paragraph::new(format!("Unable to fetch pods: {err}"))
+                        .style(Style::default().fg(tailwind::RED.c300))
+                        .block(block),
+                    area,
+                );
+
+                return Ok(());
+            }
+            State::Loading(_) => return Ok(()),
+        };
+
+        let rows = summary.rows();
+        self.offset = self.offset.min(rows.len().saturating_sub(1));
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Max(20),
+                Constraint::Max(20),
+            ],
+        )
+        .header(
+            Row::new(vec![
+                Cell::from("Name"),
+                Cell::from("CPU (req/lim)"),
+                Cell::from("Memory (req/lim)"),
+            ])
+            .style(Style::default().bold()),
+        )
+        .block(block.clone().title(summary.title.as_str()));
+
+        // Extended resources stay in view below the pods, however many there are.
+        #[allow(clippy::cast_possible_truncation)]
+        let [area, extended] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(if summary.extended.is_empty() {
+                0
+            } else {
+                summary.extended.len() as u16 + 3
+            }),
+        ])
+        .areas(area);
+
+        frame.render_stateful_widget(
+            table,
+            area,
+            &mut TableState::default().with_offset(self.offset),
+        );
+
+        if !summary.extended.is_empty() {
+            frame.render_widget(
+                Table::new(
+                    summary.extended.iter().map(|usage| {
+                        Row::new(vec![
+                            usage.name.clone(),
+                            usage.allocatable.to_string(),
+                            usage.allocated.to_string(),
+                            usage.free().to_string(),
+                        ])
+                    }),
+                    [
+                        Constraint::Fill(1),
+                        Constraint::Max(12),
+                        Constraint::Max(12),
+                        Constraint::Max(12),
+                    ],
+                )
+                .header(
+                    Row::new(vec![
+                        Cell::from("Extended"),
+                        Cell::from("Allocatable"),
+                        Cell::from("Allocated"),
+                        Cell::from("Free"),
+                    ])
+                    .style(Style::default().bold()),
+                )
+                .block(block),
+                extended,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Allocation {
+    fn drop(&mut self) {
+        if let State::Loading(task) = &self.state {
+            task.abort();
+        }
+    }
+}
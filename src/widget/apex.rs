@@ -1,38 +1,166 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, rc::Rc, sync::Arc, time::Instant};
 
 use eyre::Result;
-use ratatui::{buffer::Buffer, layout::Rect, Frame};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
 use tachyonfx::{fx, EffectTimer, Interpolation};
 use tracing::{metadata::LevelFilter, Level};
 
 use super::{
+    alerts,
     debug::Debug,
+    deployment,
     error::Error,
-    node, pod,
-    tabs::TabbedView,
+    gateway,
+    help::Help,
+    idle, image,
+    keys::Action,
+    link::Kind,
+    locale, nested, netpol, node, orphan, overview, pdb, plugin, pod, pv, pvc,
+    saved::{self, Variables},
+    scratchpad::{Notes, Scratchpad},
+    statusbar::StatusBar,
+    tabs::{Editable, Tab, TabbedView},
     tunnel::Tunnel,
     view::{Element, View},
-    Widget,
+    workload, BoxWidget, Widget,
 };
 use crate::{
     events::{Broadcast, Event},
     fx::Animated,
+    history::History,
+    image::Enrichment,
+    plugin::Plugin,
+    resources::{alerts::AlertSource, idle::IdleSettings},
+    share::Share,
 };
 
+/// Every tab, in the default order.
+pub static TABS: [&str; 15] = [
+    "overview",
+    "alerts",
+    "pods",
+    "nodes",
+    "pvcs",
+    "pvs",
+    "deployments",
+    "workloads",
+    "gateways",
+    "netpols",
+    "pdbs",
+    "images",
+    "views",
+    "housekeeping",
+    "idle",
+];
+
 pub struct Apex {
     view: View,
     tunnel_idx: Rc<RefCell<u16>>,
+    share: Option<Arc<dyn Share>>,
+    sharing: bool,
+    notes: Notes,
+    // Created the first time it is shown, hidden when `None`.
+    scratchpad: Option<Scratchpad>,
+    help: Option<Help>,
+    // When the session will be disconnected for inactivity, see `Event::Idle`.
+    disconnect: Option<Instant>,
+    status: StatusBar,
 }
 
+#[bon::bon]
 impl Apex {
-    pub fn new(client: kube::Client) -> Self {
+    #[builder]
+    pub fn new(
+        client: kube::Client,
+        history: Arc<dyn History>,
+        share: Option<Arc<dyn Share>>,
+        link: Option<Kind>,
+        // Preloaded view for `link`, see [`super::link::Link::detail`].
+        detail: Option<BoxWidget>,
+        // Values for variables in saved views, see [`crate::resources::saved`].
+        #[builder(default)] variables: Variables,
+        // Names from [`TABS`] to show, in order. Unknown names are skipped.
+        tabs: Option<Vec<String>>,
+        // Selected on start, `link` takes precedence.
+        tab: Option<String>,
+        // What has been written in the scratchpad, see [`Scratchpad`].
+        #[builder(default)] notes: Notes,
+        // Shown in the status bar.
+        cluster: Option<String>,
+        // Where the alerts tab gets them from, it is left out without one.
+        alerts: Option<AlertSource>,
+        // Details for the images tab beyond what pods have.
+        #[builder(default)] images: Enrichment,
+        // What the idle tab considers unused.
+        #[builder(default)] idle: IdleSettings,
+        // Extra tabs, shown after the built in ones. See [`Plugin`].
+        #[builder(default)] plugins: Vec<Arc<dyn Plugin>>,
+    ) -> Self {
+        let status = StatusBar::new(cluster, variables.get("user").cloned());
+
         let tunnel_idx = Rc::new(RefCell::new(0));
 
+        let sources = Sources {
+            alerts,
+            images,
+            idle: Arc::new(idle),
+        };
+
+        let available: Vec<_> = TABS
+            .iter()
+            .filter(|name| **name != "alerts" || sources.alerts.is_some())
+            .map(ToString::to_string)
+            .chain(plugins.iter().map(|plugin| plugin.schema().name.clone()))
+            .collect();
+
+        let mut names: Vec<_> = tabs
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|name| available.contains(name))
+            .collect();
+
+        if names.is_empty() {
+            names.clone_from(&available);
+        }
+
+        let selected = link
+            .map(|kind| kind.name().to_string())
+            .or(tab)
+            .and_then(|selected| names.iter().position(|name| *name == selected))
+            .unwrap_or_default();
+
         let tabs = TabbedView::builder()
-            .tabs(vec![
-                pod::List::tab("Pods".to_string(), client.clone(), true),
-                node::List::tab("Nodes".to_string(), client, true),
-            ])
+            .tabs(
+                names
+                    .iter()
+                    .map(|name| {
+                        build_tab(
+                            &client, &history, &variables, &sources, &plugins, name, None,
+                        )
+                    })
+                    .collect(),
+            )
+            .selected(selected)
+            .maybe_initial(detail)
+            .editable(Editable {
+                kinds: available,
+                tab: {
+                    let client = client.clone();
+                    let history = history.clone();
+
+                    Box::new(move |name, filter| {
+                        build_tab(
+                            &client, &history, &variables, &sources, &plugins, name, filter,
+                        )
+                    })
+                },
+            })
             .build();
 
         let mut widgets = vec![
@@ -56,9 +184,9 @@ impl Apex {
         ];
 
         // TODO: This dependency on the crate is unfortunate, it should probably be
-        // moved into something like `cata`. See `crate::cli::LEVEL` for an explanation
+        // moved into something like `cata`. See `crate::LEVEL` for an explanation
         // of why this is required instead of using `tracing::enabled!()`.
-        if crate::cli::LEVEL.get().unwrap_or(&LevelFilter::ERROR) >= &Level::DEBUG {
+        if crate::LEVEL.get().unwrap_or(&LevelFilter::ERROR) >= &Level::DEBUG {
             widgets.push(
                 Element::builder()
                     .widget(Debug::default().boxed())
@@ -70,22 +198,284 @@ impl Apex {
         Self {
             view: View::builder().widgets(widgets).show_all(true).build(),
             tunnel_idx,
+            share,
+            sharing: false,
+            notes,
+            scratchpad: None,
+            help: None,
+            disconnect: None,
+            status,
+        }
+    }
+
+    fn toggle_scratchpad(&mut self) {
+        self.scratchpad = match self.scratchpad {
+            Some(_) => None,
+            None => Some(Scratchpad::new(self.notes.clone())),
+        };
+    }
+
+    fn toggle_share(&mut self) {
+        let Some(share) = &self.share else {
+            return;
+        };
+
+        if self.sharing {
+            share.stop();
+            self.sharing = false;
+
+            self.view.error(Error::info("Stopped sharing"));
+
+            return;
+        }
+
+        match share.start() {
+            Ok(code) => {
+                self.sharing = true;
+
+                self.view.error(Error::info(format!(
+                    "Sharing read-only, others can watch with `ssh -l share/{code} <server>`. \
+                     Press ctrl-x to stop."
+                )));
+            }
+            Err(err) => self.view.error(err),
         }
     }
 }
 
 impl Widget for Apex {
     fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
-        if let Event::Tunnel(Err(err)) = event {
-            self.view.push(Error::from(err.message()).boxed().into());
+        match event {
+            Event::Tunnel(Err(err)) => self.view.error(Error::from(err.message())),
+            Event::Message(msg) => self.view.error(Error::notice(msg)),
+            Event::Idle(remaining) => self.disconnect = Some(Instant::now() + *remaining),
+            Event::Expires(at) => self.status.expires(*at),
+            Event::Snapshot(Ok(location)) => self.view.error(Error::info(format!(
+                "Snapshot saved to {location}, attach it to bug reports. Open it with `kty dev \
+                 replay`."
+            ))),
+            Event::Snapshot(Err(err)) => {
+                self.view
+                    .error(Error::from(format!(" unable to save snapshot: {err}")));
+            }
+            _ => {}
+        }
+
+        // The key only needs to reach the server to reset the timer, it shouldn't
+        // also do something.
+        if event.key().is_some() && self.disconnect.take().is_some() {
+            return Ok(Broadcast::Consumed);
+        }
+
+        if let Some(help) = self.help.as_mut() {
+            if matches!(help.dispatch(event, buffer, area)?, Broadcast::Exited) {
+                self.help = None;
+            }
+
+            return Ok(Broadcast::Consumed);
+        }
+
+        if event.key().is_some_and(|key| Action::Share.matches(key)) && self.share.is_some() {
+            self.toggle_share();
+
+            return Ok(Broadcast::Consumed);
         }
 
-        self.view.dispatch(event, buffer, area)
+        if event
+            .key()
+            .is_some_and(|key| Action::Scratchpad.matches(key))
+        {
+            self.toggle_scratchpad();
+
+            return Ok(Broadcast::Consumed);
+        }
+
+        // The scratchpad has focus while it is open, anything it doesn't use still
+        // goes to the view so that tables can be scrolled with the arrow keys.
+        if let Some(scratchpad) = self.scratchpad.as_mut() {
+            match scratchpad.dispatch(event, buffer, area)? {
+                Broadcast::Ignored => {}
+                Broadcast::Exited => {
+                    self.scratchpad = None;
+
+                    return Ok(Broadcast::Consumed);
+                }
+                result => return Ok(result),
+            }
+        }
+
+        let [main, _] = split(area);
+
+        // Text inputs, such as the filter, get `?` first.
+        match self.view.dispatch(event, buffer, main)? {
+            Broadcast::Ignored if event.key().is_some_and(|key| Action::Help.matches(key)) => {
+                self.help = Some(Help::new());
+
+                Ok(Broadcast::Consumed)
+            }
+            result => Ok(result),
+        }
     }
 
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
         *self.tunnel_idx.borrow_mut() = self.view.zindex();
 
-        self.view.draw(frame, area)
+        let [area, bar] = split(area);
+
+        self.status.update(self.view.filter());
+        self.status.draw(frame, bar)?;
+
+        if let Some(scratchpad) = self.scratchpad.as_mut() {
+            let [main, notes] =
+                Layout::horizontal([Constraint::Fill(1), Constraint::Percentage(35)]).areas(area);
+
+            self.view.draw(frame, main)?;
+            scratchpad.draw(frame, notes)?;
+        } else {
+            self.view.draw(frame, area)?;
+        }
+
+        if let Some(help) = self.help.as_mut() {
+            help.draw(frame, area)?;
+        }
+
+        if let Some(disconnect) = self.disconnect {
+            draw_idle(frame, area, disconnect);
+        }
+
+        Ok(())
     }
+
+    fn title(&self) -> Vec<String> {
+        self.view.title()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(
+            self._name(),
+            std::iter::once(&self.view as &dyn Widget)
+                .chain(self.scratchpad.iter().map(|s| s as &dyn Widget))
+                .chain(self.help.iter().map(|h| h as &dyn Widget)),
+        )
+    }
+}
+
+// Builds a top level tab from its name in `TABS` or the name of a plugin. Lists
+// that can be linked to start with `filter` applied, see `Kind::list`.
+// Where tabs that need more than the cluster get it from.
+struct Sources {
+    alerts: Option<AlertSource>,
+    images: Enrichment,
+    idle: Arc<IdleSettings>,
+}
+
+fn build_tab(
+    client: &kube::Client,
+    history: &Arc<dyn History>,
+    variables: &Variables,
+    sources: &Sources,
+    plugins: &[Arc<dyn Plugin>],
+    name: &str,
+    filter: Option<String>,
+) -> Tab {
+    if let Some(plugin) = plugins.iter().find(|plugin| plugin.schema().name == name) {
+        return plugin::List::tab(client.clone(), plugin.clone(), true).with_kind(name);
+    }
+
+    let title = locale::t(&format!(
+        "tabs.{}",
+        match name {
+            "overview" | "alerts" | "nodes" | "pvcs" | "pvs" | "deployments" | "workloads"
+            | "gateways" | "netpols" | "pdbs" | "images" | "housekeeping" | "idle" | "views" => {
+                name
+            }
+            _ => "pods",
+        }
+    ));
+
+    if let (Some(filter), Ok(kind)) = (filter, name.parse::<Kind>()) {
+        let client = client.clone();
+        let history = history.clone();
+
+        return Tab::builder()
+            .name(title)
+            .constructor(Box::new(move || {
+                Element::builder()
+                    .widget(kind.list(client.clone(), history.clone(), Some(filter.clone())))
+                    .terminal(true)
+                    .build()
+            }))
+            .build()
+            .with_kind(name);
+    }
+
+    if let ("alerts", Some(source)) = (name, &sources.alerts) {
+        return alerts::List::tab(title, client.clone(), history.clone(), source.clone(), true)
+            .with_kind(name);
+    }
+
+    match name {
+        "overview" => overview::Overview::tab(title, client.clone(), true),
+        "nodes" => node::List::tab(title, client.clone(), history.clone(), true),
+        "pvcs" => pvc::List::tab(title, client.clone(), true),
+        "pvs" => pv::List::tab(title, client.clone(), true),
+        "deployments" => deployment::List::tab(title, client.clone(), true),
+        "workloads" => workload::List::tab(title, client.clone(), history.clone(), true),
+        "gateways" => gateway::List::tab(title, client.clone(), history.clone(), true),
+        "netpols" => netpol::List::tab(title, client.clone(), history.clone(), true),
+        "pdbs" => pdb::List::tab(title, client.clone(), history.clone(), true),
+        "images" => image::List::tab(
+            title,
+            client.clone(),
+            history.clone(),
+            sources.images.clone(),
+            true,
+        ),
+        "housekeeping" => orphan::List::tab(title, client.clone(), true),
+        "idle" => idle::List::tab(title, client.clone(), sources.idle.clone(), true),
+        "views" => saved::List::tab(
+            title,
+            client.clone(),
+            history.clone(),
+            variables.clone(),
+            true,
+        ),
+        _ => pod::List::tab(title, client.clone(), history.clone(), true),
+    }
+    .with_kind(name)
+}
+
+// The status bar takes up the last line.
+fn split(area: Rect) -> [Rect; 2] {
+    Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area)
+}
+
+fn draw_idle(frame: &mut Frame, area: Rect, disconnect: Instant) {
+    let remaining = disconnect.saturating_duration_since(Instant::now());
+
+    let [area] = Layout::horizontal([Constraint::Length(44)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(4)])
+        .flex(Flex::Center)
+        .areas(area);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(format!(
+            "Disconnecting in {}s for inactivity.\nPress any key to stay connected.",
+            remaining.as_secs()
+        ))
+        .centered()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow)),
+        ),
+        area,
+    );
 }
@@ -0,0 +1,513 @@
+use std::{borrow::Borrow, sync::Arc};
+
+use eyre::{eyre, Result};
+use futures::{stream, StreamExt};
+use kube::{api::DynamicObject, Api, Resource, ResourceExt};
+use lazy_static::lazy_static;
+use prometheus::{opts, register_int_counter_vec, IntCounterVec};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{palette::tailwind, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::{sync::mpsc, task::JoinHandle};
+
+use super::{
+    input, locale::t_with, nav::exit_keys, preview::Preview, statusbar::Pending, table, Widget,
+    WIDGET_VIEWS_VEC,
+};
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    resources::{
+        action::Actions as _,
+        bulk::{Label, Operation},
+        preview::Outcome,
+        store::Store,
+        Compare, Filter,
+    },
+};
+
+lazy_static! {
+    static ref BULK_OPERATIONS: IntCounterVec = register_int_counter_vec!(
+        opts!(
+            "bulk_operations_total",
+            "Number of times a bulk operation has been run"
+        ),
+        &["operation"],
+    )
+    .unwrap();
+}
+
+/// Something an operation is applied to.
+pub struct Target {
+    name: String,
+    client: Api<DynamicObject>,
+    display: String,
+}
+
+impl Target {
+    pub fn new(client: Api<DynamicObject>, namespace: Option<String>, name: String) -> Self {
+        Self {
+            display: match namespace {
+                Some(ns) => format!("{ns}/{name}"),
+                None => name.clone(),
+            },
+            name,
+            client,
+        }
+    }
+}
+
+enum Progress {
+    Queued,
+    Running,
+    Done(Result<()>),
+}
+
+struct Run {
+    description: String,
+    progress: Vec<Progress>,
+    rx: mpsc::UnboundedReceiver<(usize, Progress)>,
+    task: JoinHandle<()>,
+}
+
+/// Applies an operation to every marked resource, tracking the result of each
+/// one separately so that partial failures can be seen.
+pub struct Bulk {
+    kind: String,
+    targets: Vec<Target>,
+    operations: Vec<Operation>,
+
+    idx: usize,
+    input: Option<input::Text>,
+    invalid: Option<String>,
+    running: Option<Run>,
+    preview: Option<(Preview, Operation, Option<Label>)>,
+}
+
+impl Bulk {
+    pub fn new<K>(client: &kube::Client, resources: &[Arc<K>], operations: &[Operation]) -> Self
+    where
+        K: Resource<DynamicType = ()> + Send + Sync + 'static,
+    {
+        WIDGET_VIEWS_VEC
+            .with_label_values(&[K::kind(&()).borrow(), "bulk"])
+            .inc();
+
+        Self::with_targets(
+            K::plural(&()).to_string(),
+            resources
+                .iter()
+                .map(|resource| {
+                    Target::new(
+                        resource.dynamic_api(client.clone()),
+                        resource.namespace(),
+                        resource.name_any(),
+                    )
+                })
+                .collect(),
+            operations,
+        )
+    }
+
+    /// For targets that aren't all the same kind, `kind` describes them as a
+    /// whole in the title.
+    pub fn with_targets(kind: String, targets: Vec<Target>, operations: &[Operation]) -> Self {
+        Self {
+            kind,
+            targets,
+            operations: operations
+                .iter()
+                .filter(|operation| operation.verb().allowed() || operation.verb().disabled())
+                .copied()
+                .collect(),
+
+            idx: 0,
+            input: None,
+            invalid: None,
+            running: None,
+            preview: None,
+        }
+    }
+
+    pub fn from_store<K>(
+        client: kube::Client,
+        store: Arc<Store<K>>,
+        operations: &'static [Operation],
+    ) -> table::BulkFn
+    where
+        K: Filter
+            + Resource<DynamicType = ()>
+            + Clone
+            + std::fmt::Debug
+            + Send
+            + Sync
+            + DeserializeOwned
+            + Serialize
+            + 'static,
+        Arc<K>: Compare,
+    {
+        Box::new(move |marked| {
+            let resources: Vec<_> = store
+                .items(None)
+                .into_iter()
+                .filter(|resource| resource.uid().is_some_and(|uid| marked.contains(&uid)))
+                .collect();
+
+            if resources.is_empty() {
+                return Err(eyre!("marked resources no longer exist"));
+            }
+
+            Ok(Self::new(&client, &resources, operations).boxed())
+        })
+    }
+
+    fn is_running(&self) -> bool {
+        self.running
+            .as_ref()
+            .is_some_and(|run| run.progress.iter().any(|p| !matches!(p, Progress::Done(_))))
+    }
+
+    fn start(&mut self, operation: Operation) {
+        if self.is_running() {
+            return;
+        }
+
+        if operation.verb().disabled() {
+            self.invalid = Some(t_with("disabled", &[("verb", &operation.verb().name())]));
+
+            return;
+        }
+
+        if operation.needs_label() {
+            self.input = Some(
+                input::Text::builder()
+                    .title(format!(
+                        "Label {} {} (key=value or key-)",
+                        self.targets.len(),
+                        self.kind
+                    ))
+                    .history("label")
+                    .build(),
+            );
+
+            return;
+        }
+
+        self.run(operation, None);
+    }
+
+    fn submit(&mut self) {
+        let Some(text) = self.input.as_ref() else {
+            return;
+        };
+
+        match text.value().parse::<Label>() {
+            Ok(label) => {
+                self.input = None;
+                self.run(Operation::Label, Some(label));
+            }
+            Err(err) => self.invalid = Some(err.to_string()),
+        }
+    }
+
+    fn run(&mut self, operation: Operation, label: Option<Label>) {
+        if !super::preview::enabled() {
+            self.apply(operation, label);
+
+            return;
+        }
+
+        let targets: Vec<_> = self
+            .targets
+            .iter()
+            .map(|target| {
+                (
+                    target.client.clone(),
+                    target.name.clone(),
+                    target.display.clone(),
+                )
+            })
+            .collect();
+
+        let dry_label = label.clone();
+
+        let preview = Preview::new(
+            format!("{} {} {}", operation.name(), targets.len(), self.kind),
+            async move {
+                let label = dry_label.as_ref();
+
+                stream::iter(targets)
+                    .then(|(client, name, display)| async move {
+                        let before = client.get(&name).await?;
+                        let after = operation.apply(&client, &name, label, true).await?;
+
+                        Ok(Outcome {
+                            target: display,
+                            before: Some(before),
+                            after,
+                        })
+                    })
+                    .collect::<Vec<Result<_>>>()
+                    .await
+                    .into_iter()
+                    .collect()
+            },
+        );
+
+        self.preview = Some((preview, operation, label));
+    }
+
+    // Targets are started in order, with at most `operation.concurrency()` of
+    // them in flight at once.
+    fn apply(&mut self, operation: Operation, label: Option<Label>) {
+        BULK_OPERATIONS
+            .with_label_values(&[operation.name().to_lowercase().as_str()])
+            .inc();
+
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let description = match label.as_ref() {
+            Some(label) => format!("{} {label}", operation.name()),
+            None => operation.name().to_string(),
+        };
+
+        let targets: Vec<_> = self
+            .targets
+            .iter()
+            .map(|target| (target.client.clone(), target.name.clone()))
+            .collect();
+
+        let pending = Pending::start();
+
+        let task = tokio::spawn(async move {
+            let _pending = pending;
+            let label = label.as_ref();
+            let tx = &tx;
+
+            stream::iter(targets.into_iter().enumerate())
+                .for_each_concurrent(operation.concurrency(), |(i, (client, name))| async move {
+                    let _ = tx.send((i, Progress::Running));
+
+                    let result = operation
+                        .apply(&client, &name, label, false)
+                        .await
+                        .map(|_| ());
+
+                    let _ = tx.send((i, Progress::Done(result)));
+                })
+                .await;
+        });
+
+        self.invalid = None;
+        self.running = Some(Run {
+            description,
+            progress: self.targets.iter().map(|_| Progress::Queued).collect(),
+            rx,
+            task,
+        });
+    }
+
+    fn update(&mut self) {
+        let Some(run) = self.running.as_mut() else {
+            return;
+        };
+
+        while let Ok((i, progress)) = run.rx.try_recv() {
+            if let Some(item) = run.progress.get_mut(i) {
+                *item = progress;
+            }
+        }
+    }
+
+    fn summary(&self) -> Paragraph {
+        let Some(Run {
+            description,
+            progress,
+            ..
+        }) = self.running.as_ref()
+        else {
+            return match &self.invalid {
+                Some(err) => {
+                    Paragraph::new(err.as_str()).style(Style::default().fg(tailwind::RED.c300))
+                }
+                None => Paragraph::new("Press a key or enter to run, esc to go back"),
+            };
+        };
+
+        let done = progress
+            .iter()
+            .filter(|p| matches!(p, Progress::Done(_)))
+            .count();
+        let failed = progress
+            .iter()
+            .filter(|p| matches!(p, Progress::Done(Err(_))))
+            .count();
+
+        let line = Paragraph::new(format!(
+            "{description}: {done}/{} done, {failed} failed",
+            progress.len()
+        ));
+
+        match (done == progress.len(), failed) {
+            (false, _) => line,
+            (true, 0) => line.style(Style::default().fg(tailwind::GREEN.c300)),
+            (true, _) => line.style(Style::default().fg(tailwind::RED.c300)),
+        }
+    }
+}
+
+impl Widget for Bulk {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        if let Some((preview, ..)) = self.preview.as_mut() {
+            match preview.dispatch(event, buffer, area)? {
+                Broadcast::Selected(_) => {
+                    if let Some((_, operation, label)) = self.preview.take() {
+                        self.apply(operation, label);
+                    }
+                }
+                Broadcast::Exited => self.preview = None,
+                _ => {}
+            }
+
+            return Ok(Broadcast::Consumed);
+        }
+
+        if let Some(text) = self.input.as_mut() {
+            if text.submit(key) {
+                self.submit();
+            } else if matches!(text.dispatch(event, buffer, area)?, Broadcast::Exited) {
+                self.input = None;
+                self.invalid = None;
+            }
+
+            return Ok(Broadcast::Consumed);
+        }
+
+        // Everything is consumed so that the table underneath doesn't move while
+        // this is open.
+        match key {
+            exit_keys!() => return Ok(Broadcast::Exited),
+            Keypress::CursorUp | Keypress::Printable('k') => {
+                self.idx = self.idx.saturating_sub(1);
+            }
+            Keypress::CursorDown | Keypress::Printable('j') => {
+                self.idx = (self.idx + 1).min(self.operations.len().saturating_sub(1));
+            }
+            Keypress::Enter => {
+                if let Some(operation) = self.operations.get(self.idx).copied() {
+                    self.start(operation);
+                }
+            }
+            Keypress::Printable(c) => {
+                if let Some(operation) = self.operations.iter().find(|o| o.key() == *c).copied() {
+                    self.start(operation);
+                }
+            }
+            _ => {}
+        }
+
+        Ok(Broadcast::Consumed)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.update();
+
+        let block = Block::default().borders(Borders::ALL).title(format!(
+            " {} {} marked ",
+            self.targets.len(),
+            self.kind
+        ));
+
+        #[allow(clippy::cast_possible_truncation)]
+        let [menu, editor, targets, status] = Layout::vertical([
+            Constraint::Length(self.operations.len() as u16 + 1),
+            Constraint::Length(if self.input.is_some() { 3 } else { 0 }),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(block.inner(area));
+
+        let lines: Vec<Line> = self
+            .operations
+            .iter()
+            .enumerate()
+            .map(|(i, operation)| {
+                let line = Line::from(format!("[{}] {}", operation.key(), operation.name()));
+                let line = if operation.verb().disabled() {
+                    line.style(Style::default().fg(tailwind::GRAY.c500))
+                } else {
+                    line
+                };
+
+                if i == self.idx {
+                    line.style(Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    line
+                }
+            })
+            .collect();
+
+        frame.render_widget(Paragraph::new(lines), menu);
+
+        if let Some(text) = self.input.as_mut() {
+            text.draw(frame, editor)?;
+        }
+
+        let lines: Vec<Line> = self
+            .targets
+            .iter()
+            .enumerate()
+            .map(|(i, target)| {
+                let progress = self.running.as_ref().and_then(|run| run.progress.get(i));
+
+                match progress {
+                    None => Line::from(format!("  {}", target.display)),
+                    Some(Progress::Queued) => Line::from(format!("  {}", target.display))
+                        .style(Style::default().fg(tailwind::GRAY.c500)),
+                    Some(Progress::Running) => Line::from(format!("… {}", target.display)),
+                    Some(Progress::Done(Ok(()))) => Line::from(format!("✓ {}", target.display))
+                        .style(Style::default().fg(tailwind::GREEN.c300)),
+                    Some(Progress::Done(Err(err))) => {
+                        Line::from(format!("✗ {}: {err}", target.display))
+                            .style(Style::default().fg(tailwind::RED.c300))
+                    }
+                }
+            })
+            .collect();
+
+        frame.render_widget(Paragraph::new(lines), targets);
+        frame.render_widget(self.summary(), status);
+        frame.render_widget(block, area);
+
+        if let Some((preview, ..)) = self.preview.as_mut() {
+            preview.draw(frame, area)?;
+        }
+
+        Ok(())
+    }
+
+    fn zindex(&self) -> u16 {
+        1
+    }
+
+    fn dirty(&self) -> bool {
+        self.running.as_ref().is_some_and(|run| !run.rx.is_empty())
+    }
+}
+
+impl Drop for Bulk {
+    fn drop(&mut self) {
+        if let Some(run) = self.running.as_ref() {
+            run.task.abort();
+        }
+    }
+}
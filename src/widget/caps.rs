@@ -0,0 +1,173 @@
+use ratatui::{buffer::Buffer, layout::Rect, style::Color};
+
+use crate::dashboard::context::Context;
+
+// Terminals that are known to mangle box-drawing characters.
+static ASCII_TERMS: [&str; 5] = ["dumb", "ansi", "vt52", "vt100", "vt102"];
+// Anything with one of these in its terminal type draws more than the 16 ANSI
+// colors. Plain `xterm`, `screen` and `linux` don't promise anything more.
+static RICH_TERMS: [&str; 9] = [
+    "256",
+    "truecolor",
+    "direct",
+    "24bit",
+    "kitty",
+    "alacritty",
+    "wezterm",
+    "ghostty",
+    "foot",
+];
+// Below this, animations are more distracting than they are helpful.
+static ANIMATED_MIN: (u16, u16) = (80, 24);
+
+/// How the client's terminal should be drawn to. These are negotiated when the
+/// session starts, from the terminal type and `KTY_ASCII`/`KTY_SYMBOLS`/
+/// `KTY_LINEAR`/`KTY_BASIC_COLORS`/`KTY_ANIMATIONS`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Only draw ASCII, borders and symbols are replaced with lookalikes.
+    pub ascii: bool,
+    /// Mark the status of rows with a symbol in addition to its color.
+    pub symbols: bool,
+    /// Write what changed as lines of text instead of drawing the screen, see
+    /// [`super::narration`].
+    pub linear: bool,
+    /// Only use the 16 ANSI colors, everything else is swapped for the closest
+    /// one.
+    pub basic_colors: bool,
+    /// Don't animate, such as when switching tabs. Also set once the client
+    /// can't keep up with the frame rate.
+    pub still: bool,
+}
+
+impl Capabilities {
+    /// Defaults for a terminal type, such as `xterm-256color`.
+    pub fn from_term(term: &str) -> Self {
+        Self {
+            ascii: ASCII_TERMS.contains(&term),
+            symbols: false,
+            linear: false,
+            basic_colors: !RICH_TERMS.iter().any(|rich| term.contains(rich)),
+            still: false,
+        }
+    }
+
+    /// Parse an environment variable's value as a toggle.
+    pub fn toggle(value: &str) -> Option<bool> {
+        match value.to_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => Some(true),
+            "0" | "false" | "no" | "off" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Capabilities of the dashboard being drawn on this thread.
+    pub fn current() -> Self {
+        Context::with(|context| *context.caps.lock().unwrap())
+    }
+
+    /// Stop drawing effects for the rest of the session, see `still`.
+    pub(crate) fn settle() {
+        Context::with(|context| context.caps.lock().unwrap().still = true);
+    }
+
+    /// Whether effects should be drawn in `area`, which is the whole terminal.
+    pub fn animate(self, area: Rect) -> bool {
+        !self.still && !self.linear && area.width >= ANIMATED_MIN.0 && area.height >= ANIMATED_MIN.1
+    }
+
+    /// Replace everything in `buffer` that the terminal can't draw. This runs
+    /// after the widgets have drawn so that none of them need to know about it.
+    pub fn apply(self, buffer: &mut Buffer) {
+        if !self.ascii && !self.basic_colors {
+            return;
+        }
+
+        for cell in &mut buffer.content {
+            if self.basic_colors {
+                cell.fg = basic(cell.fg);
+                cell.bg = basic(cell.bg);
+            }
+
+            let symbol = cell.symbol();
+
+            if !self.ascii || symbol.is_ascii() {
+                continue;
+            }
+
+            let replacement = symbol.chars().next().map_or(' ', ascii);
+            cell.set_char(replacement);
+        }
+    }
+}
+
+// What xterm draws the 16 ANSI colors as by default, other terminals are close
+// enough.
+static PALETTE: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+fn basic(color: Color) -> Color {
+    let (r, g, b) = match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Indexed(idx) if idx >= 16 => indexed(idx),
+        _ => return color,
+    };
+
+    PALETTE
+        .iter()
+        .min_by_key(|(_, (pr, pg, pb))| {
+            [(r, *pr), (g, *pg), (b, *pb)]
+                .iter()
+                .map(|(x, y)| (i32::from(*x) - i32::from(*y)).pow(2))
+                .sum::<i32>()
+        })
+        .map_or(color, |(basic, _)| *basic)
+}
+
+// The 256 color palette is the 16 ANSI colors, a 6x6x6 cube and then 24 grays.
+fn indexed(idx: u8) -> (u8, u8, u8) {
+    static LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    if idx >= 232 {
+        let gray = 8 + (idx - 232) * 10;
+
+        return (gray, gray, gray);
+    }
+
+    let idx = usize::from(idx - 16);
+
+    (LEVELS[idx / 36], LEVELS[(idx / 6) % 6], LEVELS[idx % 6])
+}
+
+fn ascii(c: char) -> char {
+    match c {
+        '─' | '━' | '═' | '┄' | '┈' | '╌' => '-',
+        '│' | '┃' | '║' | '┆' | '┊' | '╎' => '|',
+        '\u{2500}'..='\u{257f}' => '+',
+        '\u{2580}'..='\u{259f}' | '●' => '#',
+        '←' => '<',
+        '→' | '▸' => '>',
+        '↑' => '^',
+        '↓' | '▾' => 'v',
+        '✓' => '+',
+        '✗' => 'x',
+        '…' | '·' => '.',
+        _ => '?',
+    }
+}
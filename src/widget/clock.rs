@@ -0,0 +1,143 @@
+use chrono::{DateTime, FixedOffset, Utc};
+
+use crate::{dashboard::context::Context, resources::age::Age};
+
+/// How timestamps are shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// Time since, such as `5m3s`.
+    #[default]
+    Relative,
+    Utc,
+    /// In the client's timezone, see [`offset`].
+    Local,
+}
+
+impl Format {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Relative => "relative",
+            Self::Utc => "utc",
+            Self::Local => "local",
+        }
+    }
+
+    /// Widest a timestamp in this format gets, for sizing columns.
+    pub fn width(self) -> u16 {
+        match self {
+            Self::Relative => 10,
+            Self::Utc => 20,
+            Self::Local => 25,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Self::Relative => Self::Utc,
+            Self::Utc => Self::Local,
+            Self::Local => Self::Relative,
+        }
+    }
+}
+
+/// Formats timestamps the same way for every widget on a dashboard. The format
+/// is switched from the log viewer and the offset comes from the client's `TZ`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Clock {
+    pub format: Format,
+    pub offset: FixedOffset,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self {
+            format: Format::default(),
+            offset: FixedOffset::east_opt(0).expect("valid offset"),
+        }
+    }
+}
+
+impl Clock {
+    /// Clock of the dashboard being drawn on this thread.
+    pub fn current() -> Self {
+        Context::with(|context| *context.clock.lock().unwrap())
+    }
+
+    /// Move the current dashboard on to the next format, returning it.
+    pub fn cycle() -> Format {
+        Context::with(|context| {
+            let mut clock = context.clock.lock().unwrap();
+            clock.format = clock.format.next();
+
+            clock.format
+        })
+    }
+
+    pub fn format(self, time: DateTime<Utc>) -> String {
+        match self.format {
+            Format::Relative => (Utc::now() - time).to_age(),
+            Format::Utc => time.format("%Y-%m-%d %H:%M:%SZ").to_string(),
+            Format::Local => time
+                .with_timezone(&self.offset)
+                .format("%Y-%m-%d %H:%M:%S%:z")
+                .to_string(),
+        }
+    }
+}
+
+/// Parse a `TZ` value into an offset. Only fixed offsets are supported as
+/// there's no timezone database, so `+02:00`, `-0530` and POSIX values such as
+/// `CET-1` work but daylight saving rules (`EST5EDT`) are ignored.
+pub fn offset(tz: &str) -> Option<FixedOffset> {
+    let tz = tz.trim().trim_start_matches(':');
+
+    if matches!(tz, "UTC" | "GMT" | "Z") {
+        return FixedOffset::east_opt(0);
+    }
+
+    // ISO 8601 offsets are east of UTC, POSIX ones are west of it.
+    let (east, rest) = if tz.starts_with(['+', '-']) {
+        (true, tz)
+    } else {
+        let name = tz.chars().take_while(char::is_ascii_alphabetic).count();
+
+        if name < 3 {
+            return None;
+        }
+
+        (false, &tz[name..])
+    };
+
+    let end = rest
+        .char_indices()
+        .skip(1)
+        .find(|(_, c)| !c.is_ascii_digit() && *c != ':')
+        .map_or(rest.len(), |(i, _)| i);
+
+    let seconds = seconds(&rest[..end])?;
+
+    FixedOffset::east_opt(if east { seconds } else { -seconds })
+}
+
+// `[+-]hh[[:]mm]`
+fn seconds(value: &str) -> Option<i32> {
+    let (sign, value) = match value.strip_prefix('-') {
+        Some(value) => (-1, value),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+
+    let (hours, minutes) = match value.split_once(':') {
+        Some((hours, minutes)) => (hours, minutes),
+        None if value.len() == 4 => value.split_at(2),
+        None => (value, "0"),
+    };
+
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+
+    if hours > 24 || minutes > 59 {
+        return None;
+    }
+
+    Some(sign * (hours * 3600 + minutes * 60))
+}
@@ -0,0 +1,424 @@
+use eyre::Result;
+use kube::{
+    api::{DynamicObject, PostParams},
+    Api, ResourceExt,
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{palette::tailwind, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use tokio::task::JoinHandle;
+
+use super::{
+    input::{self, ContentExt},
+    nav::exit_keys,
+    preview::{self, Preview},
+    table::Focus,
+    yaml::to_lines,
+    Widget, WIDGET_VIEWS,
+};
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    resources::{policy::Policy, preview::Outcome, template::Template},
+};
+
+static FIELDS: [&str; 3] = ["Name", "Namespace", "Image"];
+
+enum Step {
+    Template,
+    Form,
+    Preview(DynamicObject, Vec<Line<'static>>),
+    Creating(JoinHandle<Result<DynamicObject>>),
+    Done(Result<String>),
+}
+
+/// Creates a new resource from a [`Template`]. A template is picked, a short
+/// form filled out and the generated YAML previewed before anything is sent to
+/// the API server.
+pub struct Create {
+    client: kube::Client,
+    focus: Focus,
+
+    step: Step,
+    idx: usize,
+    fields: Vec<input::Text>,
+    field: usize,
+    error: Option<String>,
+    scroll: u16,
+    dry_run: Option<Preview>,
+}
+
+impl Create {
+    pub fn new(client: kube::Client, focus: Focus) -> Self {
+        WIDGET_VIEWS.pod.create.inc();
+
+        Self {
+            client,
+            focus,
+
+            step: Step::Template,
+            idx: 0,
+            fields: Vec::new(),
+            field: 0,
+            error: None,
+            scroll: 0,
+            dry_run: None,
+        }
+    }
+
+    fn template(&self) -> Template {
+        Template::all()[self.idx]
+    }
+
+    fn form(&mut self) {
+        let template = self.template();
+
+        self.fields = FIELDS
+            .iter()
+            .zip([
+                template.default_name(),
+                "default".to_string(),
+                template.image().to_string(),
+            ])
+            .map(|(title, value)| {
+                input::Text::builder()
+                    .title(*title)
+                    .content(input::Content::from_string(value))
+                    .build()
+            })
+            .collect();
+        self.field = 0;
+        self.error = None;
+        self.step = Step::Form;
+    }
+
+    fn values(&self) -> Vec<String> {
+        self.fields
+            .iter()
+            .map(|field| {
+                field
+                    .content()
+                    .try_borrow()
+                    .map(|content| content.clone().unwrap_or_default())
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    fn preview(&mut self) {
+        let values = self.values();
+
+        if !Policy::visible(Some(&values[1])) {
+            self.error = Some(format!("namespace {} is not available", values[1]));
+
+            return;
+        }
+
+        let obj = match self.template().render(&values[0], &values[1], &values[2]) {
+            Ok(obj) => obj,
+            Err(err) => {
+                self.error = Some(err.to_string());
+
+                return;
+            }
+        };
+
+        let lines = serde_yaml::to_string(&obj)
+            .map(|txt| to_lines(txt.as_str()))
+            .unwrap_or_default();
+
+        self.error = None;
+        self.scroll = 0;
+        self.step = Step::Preview(obj, lines);
+    }
+
+    fn api(&self, obj: &DynamicObject) -> Api<DynamicObject> {
+        Api::namespaced_with(
+            self.client.clone(),
+            obj.namespace().unwrap_or_default().as_str(),
+            &self.template().api_resource(),
+        )
+    }
+
+    // The generated YAML has already been shown, the dry run adds what admission
+    // would change or reject.
+    fn create(&mut self) {
+        let Step::Preview(obj, _) = &self.step else {
+            return;
+        };
+
+        if !preview::enabled() {
+            self.submit();
+
+            return;
+        }
+
+        let obj = obj.clone();
+        let api = self.api(&obj);
+
+        self.dry_run = Some(Preview::new(
+            format!("create {}", self.template().kind()),
+            async move {
+                let params = PostParams {
+                    dry_run: true,
+                    ..PostParams::default()
+                };
+
+                let after = api.create(&params, &obj).await?;
+
+                Ok(vec![Outcome {
+                    target: format!(
+                        "{}/{}",
+                        after.namespace().unwrap_or_default(),
+                        after.name_any()
+                    ),
+                    before: None,
+                    after: Some(after),
+                }])
+            },
+        ));
+    }
+
+    fn submit(&mut self) {
+        let Step::Preview(obj, _) = &self.step else {
+            return;
+        };
+
+        let obj = obj.clone();
+        let api = self.api(&obj);
+
+        self.step = Step::Creating(tokio::spawn(async move {
+            Ok(api.create(&PostParams::default(), &obj).await?)
+        }));
+    }
+
+    fn update(&mut self) {
+        let template = self.template();
+
+        let Step::Creating(task) = &mut self.step else {
+            return;
+        };
+
+        if !task.is_finished() {
+            return;
+        }
+
+        let result = futures::executor::block_on(async move { task.await? });
+
+        self.step = Step::Done(result.map(|obj| {
+            let name = format!("{}/{}", obj.namespace().unwrap_or_default(), obj.name_any());
+
+            // Only pods are in the table this was opened from, jobs show up via.
+            // their pods.
+            if template.kind() == "pod" {
+                *self.focus.borrow_mut() = obj.uid();
+
+                format!("Created pod {name}")
+            } else {
+                format!(
+                    "Created {} {name}, its pod will show up shortly",
+                    template.kind()
+                )
+            }
+        }));
+    }
+
+    fn dispatch_form(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<()> {
+        let Some(key) = event.key() else {
+            return Ok(());
+        };
+
+        match key {
+            Keypress::Enter if self.field + 1 == self.fields.len() => self.preview(),
+            Keypress::Enter | Keypress::HorizontalTab | Keypress::CursorDown => {
+                self.field = (self.field + 1).min(self.fields.len().saturating_sub(1));
+            }
+            Keypress::CursorUp => self.field = self.field.saturating_sub(1),
+            _ => {
+                if matches!(
+                    self.fields[self.field].dispatch(event, buffer, area)?,
+                    Broadcast::Exited
+                ) {
+                    self.step = Step::Template;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Widget for Create {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        if let Some(dry_run) = self.dry_run.as_mut() {
+            match dry_run.dispatch(event, buffer, area)? {
+                Broadcast::Selected(_) => {
+                    self.dry_run = None;
+                    self.submit();
+                }
+                Broadcast::Exited => self.dry_run = None,
+                _ => {}
+            }
+
+            return Ok(Broadcast::Consumed);
+        }
+
+        // Everything is consumed so that the table underneath doesn't move while
+        // this is open.
+        match self.step {
+            Step::Template => match key {
+                exit_keys!() => return Ok(Broadcast::Exited),
+                Keypress::CursorUp | Keypress::Printable('k') => {
+                    self.idx = self.idx.saturating_sub(1);
+                }
+                Keypress::CursorDown | Keypress::Printable('j') => {
+                    self.idx = (self.idx + 1).min(Template::all().len() - 1);
+                }
+                Keypress::Enter => self.form(),
+                _ => {}
+            },
+            Step::Form => self.dispatch_form(event, buffer, area)?,
+            Step::Preview(..) => match key {
+                exit_keys!() => self.step = Step::Form,
+                Keypress::Enter => self.create(),
+                Keypress::CursorUp | Keypress::Printable('k') => {
+                    self.scroll = self.scroll.saturating_sub(1);
+                }
+                Keypress::CursorDown | Keypress::Printable('j') => {
+                    self.scroll = self.scroll.saturating_add(1);
+                }
+                _ => {}
+            },
+            Step::Creating(_) => {}
+            Step::Done(Ok(_)) => return Ok(Broadcast::Exited),
+            Step::Done(Err(_)) => self.step = Step::Form,
+        }
+
+        Ok(Broadcast::Consumed)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.update();
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" New resource ");
+
+        let [content, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(block.inner(area));
+
+        let help = match &self.step {
+            Step::Template => {
+                let lines: Vec<Line> = Template::all()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, template)| {
+                        let line = Line::from(format!(
+                            "{:<16}{}",
+                            template.name(),
+                            template.description()
+                        ));
+
+                        if i == self.idx {
+                            line.style(Style::default().add_modifier(Modifier::REVERSED))
+                        } else {
+                            line
+                        }
+                    })
+                    .collect();
+
+                frame.render_widget(Paragraph::new(lines), content);
+
+                "enter: select a template, esc: cancel".to_string()
+            }
+            Step::Form => {
+                let areas = Layout::vertical(self.fields.iter().map(|_| Constraint::Length(3)))
+                    .split(content);
+
+                // The active field is drawn last so that it gets the cursor.
+                let mut order: Vec<_> = (0..self.fields.len()).collect();
+                order.retain(|i| *i != self.field);
+                order.push(self.field);
+
+                for i in order {
+                    self.fields[i].draw(frame, areas[i])?;
+                }
+
+                format!(
+                    "{}: enter for next, ↑/↓ to move between fields, esc to go back",
+                    self.template().name()
+                )
+            }
+            Step::Preview(_, lines) => {
+                frame.render_widget(
+                    Paragraph::new(lines.clone()).scroll((self.scroll, 0)),
+                    content,
+                );
+
+                "enter: create, esc: edit".to_string()
+            }
+            Step::Creating(_) => {
+                frame.render_widget(Line::from("Creating...").centered(), content);
+
+                String::new()
+            }
+            Step::Done(Ok(msg)) => {
+                frame.render_widget(
+                    Line::from(msg.as_str())
+                        .centered()
+                        .style(Style::default().fg(tailwind::GREEN.c300)),
+                    content,
+                );
+
+                "press any key to close".to_string()
+            }
+            Step::Done(Err(err)) => {
+                frame.render_widget(
+                    Paragraph::new(format!("Rejected: {err}"))
+                        .style(Style::default().fg(tailwind::RED.c300)),
+                    content,
+                );
+
+                "press any key to edit".to_string()
+            }
+        };
+
+        let status_line = match self.error.as_ref() {
+            Some(err) => {
+                Paragraph::new(err.as_str()).style(Style::default().fg(tailwind::RED.c300))
+            }
+            None => Paragraph::new(help),
+        };
+
+        frame.render_widget(status_line, status);
+        frame.render_widget(block, area);
+
+        if let Some(dry_run) = self.dry_run.as_mut() {
+            dry_run.draw(frame, area)?;
+        }
+
+        Ok(())
+    }
+
+    fn zindex(&self) -> u16 {
+        1
+    }
+}
+
+impl Drop for Create {
+    fn drop(&mut self) {
+        if let Step::Creating(task) = &self.step {
+            task.abort();
+        }
+    }
+}
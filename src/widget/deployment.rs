@@ -0,0 +1,259 @@
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use eyre::{eyre, Result};
+use k8s_openapi::api::{apps::v1::Deployment, autoscaling::v2::HorizontalPodAutoscaler};
+use kube::ResourceExt;
+use ratatui::{buffer::Buffer, layout::Rect, Frame};
+use tokio::sync::oneshot;
+
+use super::{
+    actions::Actions,
+    bulk::Bulk,
+    events::Events,
+    filter::FilterBuilder,
+    gitops::GitOps,
+    hpa::Autoscaler,
+    loading::Loading,
+    metadata::Metadata,
+    nested, propagate,
+    rollout::History,
+    table,
+    tabs::{Tab, TabbedView},
+    timeline::Timeline,
+    view::{Element, View},
+    yaml::Yaml,
+    yank::Yank,
+    Widget, WIDGET_VIEWS,
+};
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    resources::{
+        action,
+        bulk::Operation,
+        deployment::{autoscaler, Deployments},
+        gitops,
+        store::Store,
+    },
+};
+
+pub struct List {
+    view: View,
+    is_ready: oneshot::Receiver<()>,
+}
+
+#[bon::bon]
+impl List {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(skip(client), fields(activity = "deployment.list"))]
+    #[builder]
+    pub fn new(client: kube::Client, filter: Option<String>) -> Self {
+        WIDGET_VIEWS.deployment.list.inc();
+
+        let (deployments, is_ready) = Store::<Deployment>::new(client.clone());
+        // Not waited on, autoscalers are extra information and the user might
+        // not have access to them.
+        let (autoscalers, _) = Store::<HorizontalPodAutoscaler>::new(client.clone());
+
+        let table = table::Filtered::builder()
+            .table(
+                table::Table::builder()
+                    .items(Deployments {
+                        deployments: deployments.clone(),
+                        autoscalers: autoscalers.clone(),
+                    })
+                    .filter(Rc::new(RefCell::new(filter)))
+                    .build(),
+            )
+            .constructor(Detail::from_store(
+                client.clone(),
+                deployments.clone(),
+                autoscalers,
+            ))
+            .bulk(Bulk::from_store(
+                client,
+                deployments.clone(),
+                &[Operation::Restart, Operation::Label],
+            ))
+            .fields(FilterBuilder::from_store(deployments))
+            .build();
+
+        let widgets = vec![
+            table.boxed().into(),
+            Element::builder()
+                .widget(Loading.boxed())
+                .ignore(true)
+                .build(),
+        ];
+
+        Self {
+            view: View::builder().widgets(widgets).build(),
+            is_ready,
+        }
+    }
+
+    pub fn tab(name: String, client: kube::Client, terminal: bool) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Element::builder()
+                    .widget(Self::builder().client(client.clone()).build().boxed())
+                    .terminal(terminal)
+                    .build()
+            }))
+            .build()
+    }
+}
+
+impl Widget for List {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if let Ok(()) = self.is_ready.try_recv() {
+            self.view.pop();
+        }
+
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        self.view.zindex()
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        let title = self.view.title();
+
+        if title.is_empty() {
+            vec!["deployments".to_string()]
+        } else {
+            title
+        }
+    }
+
+    fn filter(&self) -> Option<String> {
+        self.view.filter()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
+
+pub struct Detail {
+    view: TabbedView,
+}
+
+#[bon::bon]
+impl Detail {
+    #[builder]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn new(
+        client: kube::Client,
+        deployment: Arc<Deployment>,
+        autoscaler: Option<Arc<HorizontalPodAutoscaler>>,
+    ) -> Self {
+        WIDGET_VIEWS.deployment.detail.inc();
+
+        let mut tabs = vec![
+            Yaml::tab("YAML".to_string(), deployment.clone()),
+            Events::tab("Events".to_string(), client.clone(), deployment.clone()),
+            Metadata::tab("Metadata".to_string(), client.clone(), deployment.clone()),
+            History::tab("History".to_string(), client.clone(), deployment.clone()),
+            Timeline::tab("Timeline".to_string(), client.clone(), deployment.clone()),
+        ];
+
+        if let Some(hpa) = autoscaler {
+            tabs.push(Autoscaler::tab(
+                "Autoscaler".to_string(),
+                client.clone(),
+                hpa,
+            ));
+        }
+
+        if gitops::manager(deployment.as_ref()).is_some() {
+            tabs.push(GitOps::tab("GitOps".to_string(), deployment.clone()));
+        }
+
+        if deployment.annotations().contains_key(action::ANNOTATION) {
+            tabs.push(Actions::tab(
+                "Actions".to_string(),
+                client,
+                deployment.clone(),
+            ));
+        }
+
+        let view = TabbedView::builder()
+            .tabs(tabs)
+            .yank(Yank::from_obj(deployment.as_ref()))
+            .title(vec![
+                "deployments".to_string(),
+                deployment.namespace().unwrap_or_default(),
+                deployment.name_any(),
+            ])
+            .build();
+
+        Self { view }
+    }
+
+    pub fn from_store(
+        client: kube::Client,
+        store: Arc<Store<Deployment>>,
+        autoscalers: Arc<Store<HorizontalPodAutoscaler>>,
+    ) -> table::DetailFn {
+        Box::new(move |idx, filter| {
+            let deployment = store
+                .get(idx, filter)
+                .ok_or_else(|| eyre!("deployment not found"))?;
+
+            Ok(Detail::builder()
+                .client(client.clone())
+                .maybe_autoscaler(autoscaler(&deployment, &autoscalers.items(None)))
+                .deployment(deployment)
+                .build()
+                .boxed())
+        })
+    }
+}
+
+impl Widget for Detail {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        1
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        self.view.title()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
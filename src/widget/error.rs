@@ -1,3 +1,5 @@
+use std::time::{Duration, Instant};
+
 use ansi_to_tui::IntoText;
 use eyre::{Report, Result};
 use ratatui::{
@@ -10,34 +12,249 @@ use ratatui::{
 };
 
 use super::{
+    keys::Action,
+    locale::{t, t_with},
+    narration,
     nav::{move_cursor, Movement},
     Placement, Widget,
 };
 use crate::events::{Broadcast, Event, StringError};
 
-#[derive(Default)]
+// How long a resolved error stays up, so that it is clear the retry worked.
+static RESOLVED_FOR: Duration = Duration::from_secs(3);
+
+/// Tries whatever failed again. Returning `Ok` resolves the error.
+pub type Retry = Box<dyn FnMut() -> Result<()>>;
+
+struct Entry {
+    summary: String,
+    // The full report, with the cause chain and any sections. Only shown once
+    // the entry is expanded.
+    detail: Option<String>,
+    causes: usize,
+    border: Style,
+    retry: Option<Retry>,
+    resolved: Option<Instant>,
+    // Identical errors are counted instead of stacked, draw errors in
+    // particular happen on every frame.
+    count: usize,
+}
+
+impl Entry {
+    fn error(summary: String, detail: Option<String>, causes: usize) -> Self {
+        Self {
+            summary,
+            detail,
+            causes,
+            border: Style::default().fg(Color::Red),
+            retry: None,
+            resolved: None,
+            count: 1,
+        }
+    }
+
+    fn same(&self, other: &Self) -> bool {
+        self.summary == other.summary && self.detail == other.detail
+    }
+
+    fn retry(&mut self) {
+        let Some(retry) = self.retry.as_mut() else {
+            return;
+        };
+
+        match retry() {
+            Ok(()) => self.resolved = Some(Instant::now()),
+            Err(err) => {
+                let Entry {
+                    summary,
+                    detail,
+                    causes,
+                    ..
+                } = Entry::from(err);
+
+                self.summary = summary;
+                self.detail = detail;
+                self.causes = causes;
+                self.count += 1;
+            }
+        }
+    }
+
+    fn text(&self, expanded: bool) -> String {
+        let mut text = self.summary.clone();
+
+        if self.count > 1 {
+            text.push_str(&format!(" (x{})", self.count));
+        }
+
+        match (&self.detail, expanded) {
+            (Some(detail), true) => text = format!("{text}\n\n{detail}"),
+            (Some(_), false) if self.causes > 0 => {
+                let key = if self.causes == 1 {
+                    "error.caused_by.one"
+                } else {
+                    "error.caused_by.other"
+                };
+
+                text.push_str(&format!("\n  {}", t_with(key, &[("count", &self.causes)])));
+            }
+            _ => {}
+        }
+
+        text
+    }
+}
+
+impl From<Report> for Entry {
+    fn from(err: Report) -> Self {
+        if let Some(err) = err.downcast_ref::<StringError>() {
+            return Self::error(err.to_string(), None, 0);
+        }
+
+        Self::error(
+            err.to_string(),
+            Some(format!("{err:?}")),
+            err.chain().skip(1).count(),
+        )
+    }
+}
+
+/// Errors, and anything else important enough to interrupt the user, stacked
+/// newest last. Each starts out as a one line summary, `enter` shows the full
+/// report with its causes. Errors that can be retried are resolved by a
+/// successful retry and then go away on their own.
 pub struct Error {
-    msg: String,
+    entries: Vec<Entry>,
+    selected: usize,
+    expanded: bool,
 
     position: Position,
 }
 
+impl Error {
+    // Not actually an error, but it is important enough to interrupt the user
+    // in the same way.
+    pub fn notice(msg: &str) -> Self {
+        Self::info(t_with("error.notice", &[("message", &msg)]))
+    }
+
+    pub fn info(msg: impl Into<String>) -> Self {
+        Self::new(Entry {
+            border: Style::default().fg(Color::Yellow),
+            ..Entry::error(msg.into(), None, 0)
+        })
+    }
+
+    fn new(entry: Entry) -> Self {
+        Self {
+            entries: vec![entry],
+            selected: 0,
+            expanded: false,
+            position: Position::default(),
+        }
+    }
+
+    /// Offer to retry the most recent error with `r`.
+    pub fn retry(mut self, retry: Retry) -> Self {
+        if let Some(entry) = self.entries.last_mut() {
+            entry.retry = Some(retry);
+        }
+
+        self
+    }
+
+    /// Add the errors from `other` on top, the newest is selected.
+    pub fn extend(&mut self, other: Error) {
+        for entry in other.entries {
+            if let Some(existing) = self.entries.iter_mut().find(|e| e.same(&entry)) {
+                existing.count += entry.count;
+                existing.resolved = None;
+                existing.retry = entry.retry.or(existing.retry.take());
+
+                continue;
+            }
+
+            self.entries.push(entry);
+        }
+
+        self.selected = self.entries.len().saturating_sub(1);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether a resolved error is waiting to age out, it needs frames to do
+    /// so.
+    pub fn aging(&self) -> bool {
+        self.entries.iter().any(|e| e.resolved.is_some())
+    }
+
+    fn age(&mut self) {
+        self.entries
+            .retain(|e| e.resolved.map_or(true, |at| at.elapsed() < RESOLVED_FOR));
+        self.selected = self.selected.min(self.entries.len().saturating_sub(1));
+    }
+
+    fn select(&mut self, y: i16) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(y.into())
+            .min(self.entries.len().saturating_sub(1));
+        self.expanded = false;
+        self.position = Position::default();
+    }
+
+    fn hints(&self) -> String {
+        let mut hints = Vec::new();
+
+        if let Some(entry) = self.entries.get(self.selected) {
+            if entry.detail.is_some() {
+                hints.push(format!(
+                    "{}: {}",
+                    Action::ShowCauses.label(),
+                    t(if self.expanded {
+                        "error.less"
+                    } else {
+                        "error.more"
+                    })
+                ));
+            }
+
+            if entry.retry.is_some() && entry.resolved.is_none() {
+                hints.push(format!("{}: {}", Action::Retry.label(), t("error.retry")));
+            }
+        }
+
+        if self.entries.len() > 1 {
+            hints.push(format!(
+                "{}: {}",
+                Action::Dismiss.label(),
+                t("error.dismiss")
+            ));
+        }
+
+        if hints.is_empty() {
+            String::new()
+        } else {
+            format!(" {} ", hints.join(", "))
+        }
+    }
+}
+
 impl From<Report> for Error {
     fn from(err: Report) -> Self {
-        let Some(err) = err.downcast_ref::<StringError>() else {
-            return format!("{err:?}").into();
-        };
-
-        err.to_string().into()
+        Self::new(err.into())
     }
 }
 
 impl From<String> for Error {
     fn from(msg: String) -> Self {
-        Self {
-            msg: format!("Error:{msg}"),
-            ..Default::default()
-        }
+        Self::new(Entry::error(
+            t_with("error.error", &[("message", &msg)]),
+            None,
+            0,
+        ))
     }
 }
 
@@ -47,10 +264,29 @@ impl Widget for Error {
             return Ok(Broadcast::Ignored);
         };
 
-        match move_cursor(key, area) {
-            Some(Movement::X(x)) => self.position.x = self.position.x.saturating_add_signed(x),
-            Some(Movement::Y(y)) => self.position.y = self.position.y.saturating_add_signed(y),
-            None => return Ok(Broadcast::Exited),
+        let multiple = self.entries.len() > 1;
+
+        match key {
+            key if Action::ShowCauses.matches(key) => self.expanded = !self.expanded,
+            key if Action::Retry.matches(key) => {
+                if let Some(entry) = self.entries.get_mut(self.selected) {
+                    entry.retry();
+                }
+            }
+            key if Action::Dismiss.matches(key) && multiple => {
+                self.entries.remove(self.selected);
+                self.select(0);
+            }
+            key => match move_cursor(key, area) {
+                Some(Movement::Y(y)) if multiple && !self.expanded => self.select(y),
+                Some(Movement::X(x)) => self.position.x = self.position.x.saturating_add_signed(x),
+                Some(Movement::Y(y)) => self.position.y = self.position.y.saturating_add_signed(y),
+                None => return Ok(Broadcast::Exited),
+            },
+        }
+
+        if self.entries.is_empty() {
+            return Ok(Broadcast::Exited);
         }
 
         Ok(Broadcast::Consumed)
@@ -58,11 +294,74 @@ impl Widget for Error {
 
     #[allow(clippy::cast_possible_truncation)]
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
-        let block = Block::default()
+        self.age();
+
+        let mut text = Text::default();
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            if i > 0 {
+                text.push_line(Line::default());
+            }
+
+            let selected = i == self.selected;
+
+            let style = if entry.resolved.is_some() {
+                Style::default().add_modifier(Modifier::DIM)
+            } else if selected && self.entries.len() > 1 {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+
+            text.extend(
+                entry
+                    .text(selected && self.expanded)
+                    .as_bytes()
+                    .into_text()?
+                    .into_iter()
+                    .map(|line| line.patch_style(style)),
+            );
+
+            if entry.resolved.is_some() {
+                text.push_line(Line::from(format!("  {}", t("error.resolved"))).green());
+            }
+        }
+
+        narration::focus(
+            || Some(t("narration.alert")),
+            || {
+                let text = self
+                    .entries
+                    .get(self.selected)
+                    .map(|entry| entry.text(self.expanded))
+                    .unwrap_or_default();
+
+                [text, self.hints().trim().to_string()]
+                    .into_iter()
+                    .filter(|part| !part.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            },
+        );
+
+        let border = self
+            .entries
+            .get(self.selected)
+            .map_or_else(Style::default, |e| e.border);
+
+        let mut block = Block::default()
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Red));
+            .border_style(border)
+            .title_bottom(Line::from(self.hints()).right_aligned());
+
+        if self.entries.len() > 1 {
+            block = block.title(format!(
+                " {} ",
+                t_with("error.count", &[("count", &self.entries.len())])
+            ));
+        }
 
-        let pg = Paragraph::new(self.msg.as_bytes().into_text()?)
+        let pg = Paragraph::new(text)
             .block(block)
             .scroll((self.position.y, self.position.x));
 
@@ -100,4 +399,8 @@ impl Widget for Error {
     fn zindex(&self) -> u16 {
         1
     }
+
+    fn dirty(&self) -> bool {
+        self.aging()
+    }
 }
@@ -0,0 +1,199 @@
+use std::{borrow::Borrow, sync::Arc};
+
+use chrono::Utc;
+use eyre::Result;
+use k8s_openapi::api::core::v1::Event as KubeEvent;
+use kube::{api::ListParams, Api, Resource, ResourceExt};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{palette::tailwind, Style, Stylize},
+    text::Line,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    Frame,
+};
+use tokio::task::JoinHandle;
+
+use super::{
+    clock::Clock,
+    nav::{move_cursor, Movement},
+    tabs::Tab,
+    Widget, WIDGET_VIEWS_VEC,
+};
+use crate::events::{Broadcast, Event};
+
+enum State {
+    Loading(JoinHandle<Result<Vec<KubeEvent>>>),
+    Ready(Vec<KubeEvent>),
+    Failed(String),
+}
+
+/// Kubernetes events that reference a specific resource, newest first.
+pub struct Events {
+    state: State,
+    offset: usize,
+}
+
+impl Events {
+    pub fn new<K>(client: kube::Client, resource: &Arc<K>) -> Self
+    where
+        K: Resource<DynamicType = ()> + Send + Sync + 'static,
+    {
+        WIDGET_VIEWS_VEC
+            .with_label_values(&[K::kind(&()).borrow(), "events"])
+            .inc();
+
+        let api = match resource.namespace() {
+            Some(ns) => Api::<KubeEvent>::namespaced(client, ns.as_str()),
+            None => Api::<KubeEvent>::all(client),
+        };
+
+        let params = ListParams::default().fields(&format!(
+            "involvedObject.uid={}",
+            resource.uid().unwrap_or_default()
+        ));
+
+        Self {
+            state: State::Loading(tokio::spawn(async move {
+                let mut events = api.list(&params).await?.items;
+
+                events.sort_by_key(|ev| std::cmp::Reverse(last_seen(ev)));
+
+                Ok(events)
+            })),
+            offset: 0,
+        }
+    }
+
+    pub fn tab<K>(name: String, client: kube::Client, resource: Arc<K>) -> Tab
+    where
+        K: Resource<DynamicType = ()> + Send + Sync + 'static,
+    {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Self::new(client.clone(), &resource).boxed().into()
+            }))
+            .build()
+    }
+}
+
+pub fn last_seen(ev: &KubeEvent) -> Option<chrono::DateTime<Utc>> {
+    ev.last_timestamp
+        .as_ref()
+        .map(|t| t.0)
+        .or(ev.event_time.as_ref().map(|t| t.0))
+}
+
+impl Widget for Events {
+    fn dispatch(&mut self, event: &Event, _: &Buffer, area: Rect) -> Result<Broadcast> {
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        if let Some(Movement::Y(y)) = move_cursor(key, area) {
+            self.offset = self.offset.saturating_add_signed(y.into());
+
+            return Ok(Broadcast::Consumed);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let block = Block::default().borders(Borders::ALL);
+
+        if let State::Loading(task) = &mut self.state {
+            if !task.is_finished() {
+                frame.render_widget(Line::from("Loading...").centered(), block.inner(area));
+
+                return Ok(());
+            }
+
+            self.state = match futures::executor::block_on(async move { task.await? }) {
+                Ok(events) => State::Ready(events),
+                Err(err) => State::Failed(err.to_string()),
+            };
+        }
+
+        let events = match &self.state {
+            State::Ready(events) => events,
+            State::Failed(err) => {
+                frame.render_widget(
+                    Paragraph::new(format!("Unable to fetch events: {err}"))
+                        .style(Style::default().fg(tailwind::RED.c300))
+                        .block(block),
+                    area,
+                );
+
+                return Ok(());
+            }
+            State::Loading(_) => return Ok(()),
+        };
+
+        if events.is_empty() {
+            frame.render_widget(Line::from("No events found").centered(), block.inner(area));
+            frame.render_widget(block, area);
+
+            return Ok(());
+        }
+
+        self.offset = self.offset.min(events.len().saturating_sub(1));
+
+        let clock = Clock::current();
+
+        let rows = events.iter().map(|ev| {
+            let row = Row::new(vec![
+                ev.type_.clone().unwrap_or_default(),
+                ev.reason.clone().unwrap_or_default(),
+                last_seen(ev).map_or(String::new(), |t| clock.format(t)),
+                ev.count.unwrap_or(1).to_string(),
+                ev.message.clone().unwrap_or_default(),
+            ]);
+
+            if ev.type_.as_deref() == Some("Warning") {
+                row.style(Style::default().fg(tailwind::RED.c300))
+            } else {
+                row
+            }
+        });
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Max(10),
+                Constraint::Max(20),
+                Constraint::Max(clock.format.width()),
+                Constraint::Max(6),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(
+            Row::new(vec![
+                Cell::from("Type"),
+                Cell::from("Reason"),
+                Cell::from("Age"),
+                Cell::from("Count"),
+                Cell::from("Message"),
+            ])
+            .style(Style::default().bold()),
+        )
+        .block(block);
+
+        frame.render_stateful_widget(
+            table,
+            area,
+            &mut TableState::default().with_offset(self.offset),
+        );
+
+        Ok(())
+    }
+}
+
+impl Drop for Events {
+    fn drop(&mut self) {
+        if let State::Loading(task) = &self.state {
+            task.abort();
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use std::{borrow::Borrow, cell::RefCell, rc::Rc, sync::Arc};
+
+use eyre::Result;
+use kube::Resource;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Position, Rect},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, List, ListState, Paragraph},
+    Frame,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{nav::exit_keys, table, Widget, WIDGET_VIEWS_VEC};
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    resources::{
+        selector::{self, Fields, Operator, Term},
+        store::Store,
+        Compare, Filter,
+    },
+};
+
+enum Step {
+    Field,
+    Operator(String),
+    Value(String, Operator),
+}
+
+/// Guides building a filter one term at a time: pick a field, then an operator
+/// and finally a value. Fields and values are the ones seen in the list, typing
+/// narrows them down. The term is added to the list's current filter.
+pub struct FilterBuilder {
+    fields: Fields,
+    filter: Rc<RefCell<Option<String>>>,
+
+    step: Step,
+    query: String,
+    state: ListState,
+}
+
+impl FilterBuilder {
+    pub fn new(fields: Fields, filter: Rc<RefCell<Option<String>>>) -> Self {
+        Self {
+            fields,
+            filter,
+
+            step: Step::Field,
+            query: String::new(),
+            state: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub fn from_store<K>(store: Arc<Store<K>>) -> table::FieldsFn
+    where
+        K: Filter
+            + Resource<DynamicType = ()>
+            + Clone
+            + std::fmt::Debug
+            + Send
+            + Sync
+            + DeserializeOwned
+            + Serialize
+            + 'static,
+        Arc<K>: Compare,
+    {
+        Box::new(move || {
+            WIDGET_VIEWS_VEC
+                .with_label_values(&[K::kind(&()).borrow(), "filter"])
+                .inc();
+
+            selector::observed(&store.items(None))
+        })
+    }
+
+    fn options(&self) -> Vec<String> {
+        let all: Vec<String> = match &self.step {
+            Step::Field => self.fields.keys().cloned().collect(),
+            Step::Operator(_) => Operator::all()
+                .iter()
+                .map(|op| op.symbol().to_string())
+                .collect(),
+            Step::Value(key, _) => self
+                .fields
+                .get(key)
+                .map(|values| values.iter().cloned().collect())
+                .unwrap_or_default(),
+        };
+
+        all.into_iter()
+            .filter(|option| option.contains(self.query.as_str()))
+            .collect()
+    }
+
+    fn next(&mut self, step: Step) {
+        self.step = step;
+        self.query.clear();
+        self.state.select(Some(0));
+    }
+
+    fn back(&mut self) -> bool {
+        let step = match &self.step {
+            Step::Field => return false,
+            Step::Operator(_) => Step::Field,
+            Step::Value(key, _) => Step::Operator(key.clone()),
+        };
+
+        self.next(step);
+
+        true
+    }
+
+    // Values that weren't seen can still be used by typing them out in full.
+    fn select(&mut self) -> Result<Broadcast> {
+        let options = self.options();
+        let choice = self
+            .state
+            .selected()
+            .and_then(|i| options.get(i).cloned())
+            .or_else(|| (!self.query.is_empty()).then(|| self.query.clone()));
+
+        let Some(choice) = choice else {
+            return Ok(Broadcast::Consumed);
+        };
+
+        match &self.step {
+            Step::Field => self.next(Step::Operator(choice)),
+            Step::Operator(key) => {
+                let op = Operator::all()
+                    .iter()
+                    .find(|op| op.symbol() == choice)
+                    .copied()
+                    .unwrap_or(Operator::Equal);
+
+                self.next(Step::Value(key.clone(), op));
+            }
+            Step::Value(key, op) => {
+                let term = Term::Field {
+                    key: key.clone(),
+                    op: *op,
+                    value: choice,
+                };
+
+                let mut filter = self.filter.try_borrow_mut()?;
+                let current = filter.take().unwrap_or_default();
+
+                *filter = Some(format!("{} {term}", current.trim()).trim().to_string());
+
+                return Ok(Broadcast::Exited);
+            }
+        }
+
+        Ok(Broadcast::Consumed)
+    }
+
+    fn prompt(&self) -> String {
+        match &self.step {
+            Step::Field => "field".to_string(),
+            Step::Operator(key) => format!("{key} ?"),
+            Step::Value(key, op) => format!("{key}{} ?", op.symbol()),
+        }
+    }
+}
+
+impl Widget for FilterBuilder {
+    fn dispatch(&mut self, event: &Event, _: &Buffer, _: Rect) -> Result<Broadcast> {
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        let len = self.options().len();
+
+        // Everything is consumed so that the table underneath doesn't move while
+        // this is open.
+        match key {
+            exit_keys!() => {
+                if !self.back() {
+                    return Ok(Broadcast::Exited);
+                }
+            }
+            Keypress::Enter => return self.select(),
+            Keypress::CursorUp => {
+                let idx = self.state.selected().unwrap_or_default();
+                self.state.select(Some(idx.saturating_sub(1)));
+            }
+            Keypress::CursorDown => {
+                let idx = self.state.selected().unwrap_or_default();
+                self.state
+                    .select(Some((idx + 1).min(len.saturating_sub(1))));
+            }
+            Keypress::Backspace | Keypress::Delete => {
+                self.query.pop();
+                self.state.select(Some(0));
+            }
+            Keypress::Printable(c) => {
+                self.query.push(*c);
+                self.state.select(Some(0));
+            }
+            _ => {}
+        }
+
+        Ok(Broadcast::Consumed)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let current = RefCell::borrow(&self.filter).clone().unwrap_or_default();
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Filter: {current} "));
+
+        let [query, options, help] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(block.inner(area));
+
+        let line = Line::from(format!("{}: {}", self.prompt(), self.query));
+
+        // The cursor shows that typing goes into the query, rather than moving
+        // around the table underneath.
+        #[allow(clippy::cast_possible_truncation)]
+        frame.set_cursor_position(Position::new(
+            query.x + (line.width() as u16).min(query.width.saturating_sub(1)),
+            query.y,
+        ));
+        frame.render_widget(line, query);
+
+        frame.render_stateful_widget(
+            List::new(self.options())
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+            options,
+            &mut self.state,
+        );
+
+        frame.render_widget(
+            Paragraph::new("type to narrow, ↑/↓: move, enter: select, esc: back"),
+            help,
+        );
+        frame.render_widget(block, area);
+
+        Ok(())
+    }
+
+    fn zindex(&self) -> u16 {
+        1
+    }
+}
@@ -0,0 +1,250 @@
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use eyre::{eyre, Result};
+use kube::{Resource, ResourceExt};
+use ratatui::{buffer::Buffer, layout::Rect, Frame};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::oneshot;
+
+use super::{
+    events::Events,
+    loading::Loading,
+    metadata::Metadata,
+    nested, propagate, table,
+    tabs::{Tab, TabbedView},
+    target::Targets,
+    view::{Element, View},
+    yaml::Yaml,
+    yank::Yank,
+    Widget, WIDGET_VIEWS,
+};
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    history::History,
+    resources::{
+        gateway::{Routes, Routing},
+        store::Store,
+        target::Source,
+    },
+};
+
+/// Gateways and the HTTP routes attached to them, from the Gateway API. The
+/// CRDs need to be installed for anything to show up.
+pub struct List {
+    view: View,
+    is_ready: Vec<oneshot::Receiver<()>>,
+}
+
+#[bon::bon]
+impl List {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(skip(client, history), fields(activity = "gateway.list"))]
+    #[builder]
+    pub fn new(client: kube::Client, history: Arc<dyn History>, filter: Option<String>) -> Self {
+        WIDGET_VIEWS.gateway.list.inc();
+
+        let (gateways, gateways_ready) = Store::new(client.clone());
+        let (routes, routes_ready) = Store::new(client.clone());
+
+        let routes = Routes::new(gateways, routes);
+
+        let table = table::Filtered::builder()
+            .table(
+                table::Table::builder()
+                    .items(routes.clone())
+                    .filter(Rc::new(RefCell::new(filter)))
+                    .build(),
+            )
+            .constructor(Detail::from_routes(client, routes, history))
+            .build();
+
+        let widgets = vec![
+            table.boxed().into(),
+            Element::builder()
+                .widget(Loading.boxed())
+                .ignore(true)
+                .build(),
+        ];
+
+        Self {
+            view: View::builder().widgets(widgets).build(),
+            is_ready: vec![gateways_ready, routes_ready],
+        }
+    }
+
+    pub fn tab(
+        name: String,
+        client: kube::Client,
+        history: Arc<dyn History>,
+        terminal: bool,
+    ) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Element::builder()
+                    .widget(
+                        Self::builder()
+                            .client(client.clone())
+                            .history(history.clone())
+                            .build()
+                            .boxed(),
+                    )
+                    .terminal(terminal)
+                    .build()
+            }))
+            .build()
+    }
+}
+
+impl Widget for List {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        // The loading indicator is removed once both stores are ready.
+        let waiting = !self.is_ready.is_empty();
+        self.is_ready.retain_mut(|rx| rx.try_recv().is_err());
+
+        if waiting && self.is_ready.is_empty() {
+            self.view.pop();
+        }
+
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        self.view.zindex()
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        let title = self.view.title();
+
+        if title.is_empty() {
+            vec!["gateways".to_string()]
+        } else {
+            title
+        }
+    }
+
+    fn filter(&self) -> Option<String> {
+        self.view.filter()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
+
+pub struct Detail {
+    view: TabbedView,
+}
+
+impl Detail {
+    fn new<K>(client: kube::Client, resource: Arc<K>, extra: Option<Tab>) -> Self
+    where
+        K: Resource<DynamicType = ()>
+            + Serialize
+            + Clone
+            + std::fmt::Debug
+            + Send
+            + Sync
+            + DeserializeOwned
+            + 'static,
+    {
+        WIDGET_VIEWS.gateway.detail.inc();
+
+        let title = vec![
+            K::plural(&()).to_string(),
+            resource.namespace().unwrap_or_default(),
+            resource.name_any(),
+        ];
+        let yank = Yank::from_obj(resource.as_ref());
+
+        let mut tabs = vec![Yaml::tab("YAML".to_string(), resource.clone())];
+        tabs.extend(extra);
+        tabs.extend([
+            Events::tab("Events".to_string(), client.clone(), resource.clone()),
+            Metadata::tab("Metadata".to_string(), client, resource),
+        ]);
+
+        let view = TabbedView::builder()
+            .tabs(tabs)
+            .title(title)
+            .yank(yank)
+            .build();
+
+        Self { view }
+    }
+
+    pub fn from_routes(
+        client: kube::Client,
+        routes: Routes,
+        history: Arc<dyn History>,
+    ) -> table::DetailFn {
+        Box::new(move |idx, filter| {
+            let routing = routes
+                .get(idx, filter)
+                .ok_or_else(|| eyre!("resource not found"))?;
+
+            let client = client.clone();
+
+            Ok(match routing {
+                Routing::Gateway(gateway) => Self::new(client, gateway, None),
+                Routing::Route(route) => {
+                    let targets = Targets::tab(
+                        "Targets".to_string(),
+                        client.clone(),
+                        history.clone(),
+                        Source::Route(route.clone()),
+                    );
+
+                    Self::new(client, route, Some(targets))
+                }
+            }
+            .boxed())
+        })
+    }
+}
+
+impl Widget for Detail {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        1
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        self.view.title()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
@@ -0,0 +1,107 @@
+use std::{borrow::Borrow, sync::Arc};
+
+use eyre::Result;
+use kube::Resource;
+use ratatui::{
+    layout::Rect,
+    style::{palette::tailwind, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+use super::{tabs::Tab, Widget, WIDGET_VIEWS_VEC};
+use crate::resources::gitops::{self, Manager, Tool};
+
+/// Which GitOps controller applies a resource and the annotations it syncs the
+/// resource with. Only added to detail views for resources that have one.
+pub struct GitOps {
+    manager: Option<Manager>,
+}
+
+impl GitOps {
+    pub fn new<K>(resource: &Arc<K>) -> Self
+    where
+        K: Resource<DynamicType = ()>,
+    {
+        WIDGET_VIEWS_VEC
+            .with_label_values(&[K::kind(&()).borrow(), "gitops"])
+            .inc();
+
+        Self {
+            manager: gitops::manager(resource.as_ref()),
+        }
+    }
+
+    pub fn tab<K>(name: String, resource: Arc<K>) -> Tab
+    where
+        K: Resource<DynamicType = ()> + Send + Sync + 'static,
+    {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || Self::new(&resource).boxed().into()))
+            .build()
+    }
+}
+
+impl Widget for GitOps {
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let block = Block::default().borders(Borders::ALL);
+
+        let Some(manager) = self.manager.as_ref() else {
+            frame.render_widget(
+                Paragraph::new("Not managed by Argo CD or Flux").block(block),
+                area,
+            );
+
+            return Ok(());
+        };
+
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let owner = match manager.tool {
+            Tool::ArgoCd => "Application",
+            Tool::Flux => "Source",
+        };
+
+        let mut lines = vec![
+            Line::styled(format!("Managed by {}", manager.tool), bold),
+            Line::from(vec![
+                Span::from(format!("  {owner:<14}")),
+                Span::from(manager.owner.clone()),
+            ]),
+            Line::default(),
+            if manager.reconciled {
+                Line::styled(
+                    "Changes made here will likely be reverted on the next sync, make them in the \
+                     repository instead.",
+                    Style::default().fg(tailwind::YELLOW.c300),
+                )
+            } else {
+                Line::styled(
+                    "Reconciling is disabled for this resource, changes made here stick.",
+                    Style::default().fg(tailwind::GRAY.c400),
+                )
+            },
+        ];
+
+        if !manager.annotations.is_empty() {
+            lines.push(Line::default());
+            lines.push(Line::styled("Sync annotations", bold));
+            lines.extend(manager.annotations.iter().map(|(k, v)| {
+                Line::from(vec![
+                    Span::styled(format!("  {k}: "), Style::default().fg(tailwind::GRAY.c400)),
+                    Span::from(v.clone()),
+                ])
+            }));
+        }
+
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .wrap(Wrap { trim: false }),
+            area,
+        );
+
+        Ok(())
+    }
+}
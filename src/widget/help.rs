@@ -0,0 +1,142 @@
+use eyre::Result;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{palette::tailwind, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use super::{keys::Action, locale::t, narration, nav::exit_keys, Widget, WIDGET_VIEWS};
+use crate::events::{Broadcast, Event, Keypress};
+
+/// Every key binding, grouped by where it applies. Built from [`Action`], so
+/// what's shown here is always what the keys do.
+pub struct Help {
+    lines: Vec<Line<'static>>,
+    width: u16,
+    scroll: u16,
+}
+
+impl Help {
+    pub fn new() -> Self {
+        WIDGET_VIEWS.view.help.inc();
+
+        let groups = Action::by_context();
+
+        let width = groups
+            .iter()
+            .flat_map(|(_, actions)| actions.iter())
+            .map(|action| action.label().chars().count())
+            .max()
+            .unwrap_or_default()
+            + 2;
+
+        let mut lines = Vec::new();
+
+        for (context, actions) in groups {
+            if !lines.is_empty() {
+                lines.push(Line::default());
+            }
+
+            lines.push(Line::from(context.title()).bold());
+
+            for action in actions {
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        format!("  {:<width$}", action.label()),
+                        Style::default().fg(tailwind::INDIGO.c300),
+                    ),
+                    Span::from(action.description()),
+                ]));
+            }
+        }
+
+        let width = lines.iter().map(Line::width).max().unwrap_or_default();
+
+        Self {
+            lines,
+            width: u16::try_from(width).unwrap_or(u16::MAX).saturating_add(2),
+            scroll: 0,
+        }
+    }
+}
+
+impl Default for Help {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for Help {
+    fn dispatch(&mut self, event: &Event, _: &Buffer, _: Rect) -> Result<Broadcast> {
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        match key {
+            exit_keys!() => return Ok(Broadcast::Exited),
+            key if Action::Help.matches(key) => return Ok(Broadcast::Exited),
+            key if Action::Down.matches(key) => self.scroll = self.scroll.saturating_add(1),
+            key if Action::Up.matches(key) => self.scroll = self.scroll.saturating_sub(1),
+            _ => {}
+        }
+
+        // Everything underneath waits until the help has been closed.
+        Ok(Broadcast::Consumed)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let height = u16::try_from(self.lines.len())
+            .unwrap_or(u16::MAX)
+            .saturating_add(2);
+
+        let [area] = Layout::horizontal([Constraint::Length(self.width)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::vertical([Constraint::Length(height)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        // Lines are read out one at a time in linear mode, every one of them can be
+        // scrolled to.
+        let last = if narration::enabled() {
+            height.saturating_sub(3)
+        } else {
+            height.saturating_sub(area.height)
+        };
+
+        self.scroll = self.scroll.min(last);
+
+        narration::focus(
+            || Some(t("help.title")),
+            || {
+                self.lines
+                    .get(usize::from(self.scroll))
+                    .map(ToString::to_string)
+                    .unwrap_or_default()
+            },
+        );
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(self.lines.clone())
+                .wrap(Wrap { trim: false })
+                .scroll((self.scroll, 0))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(t("help.title"))
+                        .title_bottom(Line::from(t("help.close")).right_aligned()),
+                ),
+            area,
+        );
+
+        Ok(())
+    }
+
+    fn zindex(&self) -> u16 {
+        2
+    }
+}
@@ -0,0 +1,125 @@
+use std::sync::Arc;
+
+use eyre::Result;
+use k8s_openapi::api::autoscaling::v2::HorizontalPodAutoscaler;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{palette::tailwind, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use super::{clock::Clock, events::Events, tabs::Tab, Widget, WIDGET_VIEWS};
+use crate::{
+    events::{Broadcast, Event},
+    resources::hpa::HorizontalPodAutoscalerExt,
+};
+
+/// What an autoscaler is currently doing and why, above the events it has
+/// emitted. Scaling decisions show up as `SuccessfulRescale` events.
+pub struct Autoscaler {
+    hpa: Arc<HorizontalPodAutoscaler>,
+    events: Events,
+}
+
+impl Autoscaler {
+    pub fn new(client: kube::Client, hpa: Arc<HorizontalPodAutoscaler>) -> Self {
+        WIDGET_VIEWS.deployment.autoscaler.inc();
+
+        Self {
+            events: Events::new(client, &hpa),
+            hpa,
+        }
+    }
+
+    pub fn tab(name: String, client: kube::Client, hpa: Arc<HorizontalPodAutoscaler>) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Self::new(client.clone(), hpa.clone()).boxed().into()
+            }))
+            .build()
+    }
+
+    fn summary(&self) -> Vec<Line<'static>> {
+        let hpa = &self.hpa;
+
+        let last_scaled = hpa
+            .status
+            .as_ref()
+            .and_then(|status| status.last_scale_time.as_ref())
+            .map_or_else(|| "never".to_string(), |t| Clock::current().format(t.0));
+
+        let mut lines = vec![
+            Line::from(vec![
+                Span::from("Replicas: ").bold(),
+                Span::from(format!(
+                    "{} current, {} desired ({}-{})",
+                    hpa.current(),
+                    hpa.desired(),
+                    hpa.min(),
+                    hpa.max()
+                )),
+            ]),
+            Line::from(vec![
+                Span::from("Last scaled: ").bold(),
+                Span::from(last_scaled),
+            ]),
+        ];
+
+        lines.extend(hpa.metrics().into_iter().map(|metric| {
+            Line::from(vec![
+                Span::from(format!("{}: ", metric.name)).bold(),
+                Span::from(format!("{} / {}", metric.current, metric.target)),
+            ])
+        }));
+
+        lines.extend(
+            hpa.conditions()
+                .into_iter()
+                .map(|(type_, status, message)| {
+                    let line = Line::from(vec![
+                        Span::from(format!("{type_}: ")).bold(),
+                        Span::from(format!("{status} {message}")),
+                    ]);
+
+                    // These explain a replica count that isn't moving. Every
+                    // condition is bad when false, except for `ScalingLimited`
+                    // which is bad when true.
+                    if (status == "False") != (type_ == "ScalingLimited") {
+                        line.style(Style::default().fg(tailwind::RED.c300))
+                    } else {
+                        line
+                    }
+                }),
+        );
+
+        lines
+    }
+}
+
+impl Widget for Autoscaler {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        self.events.dispatch(event, buffer, area)
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let lines = self.summary();
+
+        let [summary, events] = Layout::vertical([
+            Constraint::Length(lines.len() as u16 + 2),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::default().borders(Borders::ALL)),
+            summary,
+        );
+
+        self.events.draw(frame, events)
+    }
+}
@@ -0,0 +1,284 @@
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use eyre::{eyre, Result};
+use kube::{Resource, ResourceExt};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    widgets::Paragraph,
+    Frame,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::oneshot;
+
+use super::{
+    bulk::{Bulk, Target},
+    events::Events,
+    loading::Loading,
+    nested, propagate, table,
+    tabs::{Tab, TabbedView},
+    view::{Element, View},
+    yaml::Yaml,
+    yank::Yank,
+    Widget, WIDGET_VIEWS,
+};
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    resources::{
+        bulk::Operation,
+        idle::{Idle, IdleSettings, Idles},
+        store::Store,
+    },
+};
+
+/// Resources that look like they aren't used anymore, see [`Idle`]. Marked rows
+/// can be deleted all at once.
+pub struct List {
+    view: View,
+    is_ready: Vec<oneshot::Receiver<()>>,
+    ready: bool,
+}
+
+#[bon::bon]
+impl List {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(skip(client, settings), fields(activity = "idle.list"))]
+    #[builder]
+    pub fn new(client: kube::Client, settings: Arc<IdleSettings>, filter: Option<String>) -> Self {
+        WIDGET_VIEWS.idle.list.inc();
+
+        let (deployments, deployments_ready) = Store::new(client.clone());
+        let (jobs, jobs_ready) = Store::new(client.clone());
+        let (claims, claims_ready) = Store::new(client.clone());
+        let (services, services_ready) = Store::new(client.clone());
+        let (endpoints, endpoints_ready) = Store::new(client.clone());
+
+        let idles = Idles::new(deployments, jobs, claims, services, endpoints, settings);
+
+        let table = table::Filtered::builder()
+            .table(
+                table::Table::builder()
+                    .items(idles.clone())
+                    .filter(Rc::new(RefCell::new(filter)))
+                    .build(),
+            )
+            .constructor(Detail::from_idles(client.clone(), idles.clone()))
+            .bulk(bulk(client, idles))
+            .build();
+
+        let widgets = vec![
+            table.boxed().into(),
+            Element::builder()
+                .widget(Loading.boxed())
+                .ignore(true)
+                .build(),
+        ];
+
+        Self {
+            view: View::builder().widgets(widgets).build(),
+            is_ready: vec![
+                deployments_ready,
+                jobs_ready,
+                claims_ready,
+                services_ready,
+                endpoints_ready,
+            ],
+            ready: false,
+        }
+    }
+
+    pub fn tab(
+        name: String,
+        client: kube::Client,
+        settings: Arc<IdleSettings>,
+        terminal: bool,
+    ) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Element::builder()
+                    .widget(
+                        Self::builder()
+                            .client(client.clone())
+                            .settings(settings.clone())
+                            .build()
+                            .boxed(),
+                    )
+                    .terminal(terminal)
+                    .build()
+            }))
+            .build()
+    }
+}
+
+// Everything marked is deleted together, no matter what kind it is.
+fn bulk(client: kube::Client, idles: Idles) -> table::BulkFn {
+    Box::new(move |marked| {
+        let targets: Vec<_> = table::Items::items(&idles, None)
+            .into_iter()
+            .filter(|idle| idle.uid().is_some_and(|uid| marked.contains(&uid)))
+            .map(|idle| {
+                Target::new(
+                    idle.dynamic_api(client.clone()),
+                    idle.namespace(),
+                    idle.name(),
+                )
+            })
+            .collect();
+
+        if targets.is_empty() {
+            return Err(eyre!("marked resources no longer exist"));
+        }
+
+        WIDGET_VIEWS.idle.bulk.inc();
+
+        Ok(Bulk::with_targets("idle resources".to_string(), targets, &[Operation::Delete]).boxed())
+    })
+}
+
+impl Widget for List {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.is_ready.retain_mut(|rx| rx.try_recv().is_err());
+
+        // The loading indicator is removed once every store is ready.
+        if self.is_ready.is_empty() && !self.ready {
+            self.view.pop();
+            self.ready = true;
+        }
+
+        let [table, help] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+
+        frame.render_widget(
+            Paragraph::new(
+                "These are guesses, check the reason before deleting. x to mark, enter to delete.",
+            ),
+            help,
+        );
+
+        self.view.draw(frame, table)
+    }
+
+    fn zindex(&self) -> u16 {
+        self.view.zindex()
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        let title = self.view.title();
+
+        if title.is_empty() {
+            vec!["idle".to_string()]
+        } else {
+            title
+        }
+    }
+
+    fn filter(&self) -> Option<String> {
+        self.view.filter()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
+
+pub struct Detail {
+    view: TabbedView,
+}
+
+impl Detail {
+    fn new<K>(client: kube::Client, resource: Arc<K>) -> Self
+    where
+        K: Resource<DynamicType = ()>
+            + Serialize
+            + Clone
+            + std::fmt::Debug
+            + Send
+            + Sync
+            + DeserializeOwned
+            + 'static,
+    {
+        WIDGET_VIEWS.idle.detail.inc();
+
+        let title = vec![
+            K::plural(&()).to_string(),
+            resource.namespace().unwrap_or_default(),
+            resource.name_any(),
+        ];
+
+        let view = TabbedView::builder()
+            .tabs(vec![
+                Yaml::tab("YAML".to_string(), resource.clone()),
+                Events::tab("Events".to_string(), client, resource.clone()),
+            ])
+            .yank(Yank::from_obj(resource.as_ref()))
+            .title(title)
+            .build();
+
+        Self { view }
+    }
+
+    pub fn from_idles(client: kube::Client, idles: Idles) -> table::DetailFn {
+        Box::new(move |idx, filter| {
+            let idle = idles
+                .get(idx, filter)
+                .ok_or_else(|| eyre!("resource not found"))?;
+
+            let client = client.clone();
+
+            Ok(match idle {
+                Idle::Deployment(deploy) => Self::new(client, deploy),
+                Idle::Job(job) => Self::new(client, job),
+                Idle::Claim(pvc) => Self::new(client, pvc),
+                Idle::Service(svc) => Self::new(client, svc),
+            }
+            .boxed())
+        })
+    }
+}
+
+impl Widget for Detail {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        1
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        self.view.title()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
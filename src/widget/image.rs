@@ -0,0 +1,216 @@
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use eyre::{eyre, Result};
+use k8s_openapi::api::core::v1::Pod;
+use ratatui::{buffer::Buffer, layout::Rect, Frame};
+use tokio::sync::oneshot;
+
+use super::{
+    loading::Loading,
+    nested, propagate, table,
+    tabs::{Tab, TabbedView},
+    target::Targets,
+    view::{Element, View},
+    Widget, WIDGET_VIEWS,
+};
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    history::History,
+    image::Enrichment,
+    resources::{image::Images as Items, store::Store, target::Source},
+};
+
+/// Every image running in the cluster, along with whatever the registry and
+/// scanner have to say about it. See [`Enrichment`].
+pub struct List {
+    view: View,
+    is_ready: oneshot::Receiver<()>,
+}
+
+#[bon::bon]
+impl List {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(skip_all, fields(activity = "image.list"))]
+    #[builder]
+    pub fn new(
+        client: kube::Client,
+        history: Arc<dyn History>,
+        #[builder(default)] enrichment: Enrichment,
+    ) -> Self {
+        WIDGET_VIEWS.image.list.inc();
+
+        let (pods, is_ready) = Store::<Pod>::new(client.clone());
+        let items = Items::new(client.clone(), pods, enrichment);
+
+        let table = table::Filtered::builder()
+            .table(
+                table::Table::builder()
+                    .items(items.clone())
+                    .filter(Rc::new(RefCell::new(None)))
+                    .build(),
+            )
+            .constructor(Detail::from_items(client, items, history))
+            .build();
+
+        let widgets = vec![
+            table.boxed().into(),
+            Element::builder()
+                .widget(Loading.boxed())
+                .ignore(true)
+                .build(),
+        ];
+
+        Self {
+            view: View::builder().widgets(widgets).build(),
+            is_ready,
+        }
+    }
+
+    pub fn tab(
+        name: String,
+        client: kube::Client,
+        history: Arc<dyn History>,
+        enrichment: Enrichment,
+        terminal: bool,
+    ) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Element::builder()
+                    .widget(
+                        Self::builder()
+                            .client(client.clone())
+                            .history(history.clone())
+                            .enrichment(enrichment.clone())
+                            .build()
+                            .boxed(),
+                    )
+                    .terminal(terminal)
+                    .build()
+            }))
+            .build()
+    }
+}
+
+impl Widget for List {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if let Ok(()) = self.is_ready.try_recv() {
+            self.view.pop();
+        }
+
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        self.view.zindex()
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        let title = self.view.title();
+
+        if title.is_empty() {
+            vec!["images".to_string()]
+        } else {
+            title
+        }
+    }
+
+    fn filter(&self) -> Option<String> {
+        self.view.filter()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
+
+pub struct Detail {
+    view: TabbedView,
+}
+
+#[bon::bon]
+impl Detail {
+    #[builder]
+    pub fn new(client: kube::Client, image: String, history: Arc<dyn History>) -> Self {
+        WIDGET_VIEWS.image.detail.inc();
+
+        let tabs = vec![Targets::tab(
+            "Pods".to_string(),
+            client,
+            history,
+            Source::Image(image.clone()),
+        )];
+
+        let view = TabbedView::builder()
+            .tabs(tabs)
+            .title(vec!["images".to_string(), image])
+            .build();
+
+        Self { view }
+    }
+
+    pub fn from_items(
+        client: kube::Client,
+        items: Items,
+        history: Arc<dyn History>,
+    ) -> table::DetailFn {
+        Box::new(move |idx, filter| {
+            let image = items
+                .get(idx, filter)
+                .ok_or_else(|| eyre!("image not found"))?;
+
+            Ok(Detail::builder()
+                .client(client.clone())
+                .image(image.name)
+                .history(history.clone())
+                .build()
+                .boxed())
+        })
+    }
+}
+
+impl Widget for Detail {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        1
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        self.view.title()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
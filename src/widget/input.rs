@@ -1,18 +1,86 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, ops::Range, rc::Rc};
 
-use eyre::{eyre, Result};
+use eyre::Result;
+use itertools::Itertools;
 use ratatui::{
     buffer::Buffer,
     layout::{Position, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
     Frame,
 };
 
-use super::{
-    nav::{exit_keys, move_cursor, Movement},
-    Widget,
+use super::{nav::exit_keys, Widget};
+use crate::{
+    dashboard::context::Context,
+    events::{Broadcast, Event, Keypress},
 };
-use crate::events::{Broadcast, Event, Keypress};
+
+// How many entries are kept for each kind of input.
+const HISTORY_LEN: usize = 100;
+// Multi-line inputs grow with their content up to this many lines.
+const MAX_LINES: u16 = 8;
+
+// History isn't shared with other dashboards. It starts out with what was
+// saved for the user, see `History::inputs`, and entries are saved as they're
+// submitted.
+fn remember(kind: &'static str, entry: &str) {
+    if entry.trim().is_empty() {
+        return;
+    }
+
+    let (added, store) = Context::with(|context| {
+        let mut history = context.inputs.lock().unwrap();
+        let entries = history.entry(kind.to_string()).or_default();
+
+        if entries.last().is_some_and(|last| last == entry) {
+            return (false, None);
+        }
+
+        entries.retain(|existing| existing != entry);
+        entries.push(entry.to_string());
+
+        if entries.len() > HISTORY_LEN {
+            entries.remove(0);
+        }
+
+        (true, context.history.clone())
+    });
+
+    if let (true, Some(store)) = (added, store) {
+        let entry = entry.to_string();
+
+        tokio::spawn(async move {
+            if let Err(err) = store.push_input(kind.to_string(), entry).await {
+                tracing::warn!(?err, "failed to save input history");
+            }
+        });
+    }
+}
+
+fn recorded(kind: &'static str) -> Vec<String> {
+    Context::with(|context| {
+        context
+            .inputs
+            .lock()
+            .unwrap()
+            .get(kind)
+            .cloned()
+            .unwrap_or_default()
+    })
+}
+
+// Whether every character of `query` shows up in `entry`, in order. Case is
+// ignored so that `ctrl-r` finds things without being exact.
+fn fuzzy(entry: &str, query: &str) -> bool {
+    let mut chars = entry.chars().flat_map(char::to_lowercase);
+
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|q| chars.any(|c| c == q))
+}
 
 pub type Content = Rc<RefCell<Option<String>>>;
 
@@ -24,90 +92,542 @@ pub trait ContentExt {
 
 impl ContentExt for Content {}
 
+/// Takes everything before the cursor and returns what it could be replaced
+/// with, `tab` cycles through them.
+pub type CompleteFn = Box<dyn Fn(&str) -> Vec<String>>;
+
+/// Complete the word before the cursor from a fixed set of words.
+pub fn complete_words(words: Vec<String>) -> CompleteFn {
+    Box::new(move |before| {
+        let start = before
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let (head, word) = before.split_at(start);
+
+        words
+            .iter()
+            .filter(|w| w.starts_with(word) && w.as_str() != word)
+            .map(|w| format!("{head}{w}"))
+            .collect()
+    })
+}
+
+fn byte_offset(text: &str, pos: usize) -> usize {
+    text.char_indices().nth(pos).map_or(text.len(), |(i, _)| i)
+}
+
+// The (row, column) of a position, in characters.
+fn locate(text: &str, pos: usize) -> (usize, usize) {
+    let before: Vec<char> = text.chars().take(pos).collect();
+    let row = before.iter().filter(|c| **c == '\n').count();
+    let col = before.iter().rev().take_while(|c| **c != '\n').count();
+
+    (row, col)
+}
+
+// The position of a (row, column), the column is clamped to the row's length.
+fn offset(text: &str, row: usize, col: usize) -> usize {
+    let mut pos = 0;
+
+    for (i, line) in text.split('\n').enumerate() {
+        let len = line.chars().count();
+
+        if i == row {
+            return pos + col.min(len);
+        }
+
+        pos += len + 1;
+    }
+
+    pos.saturating_sub(1)
+}
+
+/// Text input shared by filters, forms and prompts. Supports the usual readline
+/// style editing keys, selecting with `ctrl-space`, going back through what
+/// was entered previously with the arrow keys, searching it with `ctrl-r` and
+/// completing with `tab`.
+///
+/// Multi-line inputs insert a newline on `enter` and are submitted with
+/// `ctrl-s` instead.
 pub struct Text {
     title: String,
     content: Content,
-    pos: u16,
+    multiline: bool,
+    history: Option<&'static str>,
+    complete: Option<CompleteFn>,
+
+    pos: usize,
+    mark: Option<usize>,
+    // The history entry being shown and what had been typed before going
+    // through history.
+    recall: Option<(usize, String)>,
+    // Candidates from the last `tab`, the one being shown and the text after
+    // the cursor when completion started.
+    completions: Option<(Vec<String>, usize, String)>,
+    search: Option<Search>,
+}
+
+// An in progress `ctrl-r`: what has been typed to find an entry, the entry
+// found and what had been typed before searching.
+struct Search {
+    query: String,
+    found: Option<usize>,
+    draft: String,
 }
 
 #[bon::bon]
 impl Text {
     #[builder]
-    pub fn new(#[builder(into)] title: String, #[builder(default)] content: Content) -> Self {
-        #[allow(clippy::cast_possible_truncation)]
-        let pos = content.borrow().as_ref().map_or(0, String::len) as u16;
+    pub fn new(
+        #[builder(into)] title: String,
+        #[builder(default)] content: Content,
+        #[builder(default)] multiline: bool,
+        history: Option<&'static str>,
+        complete: Option<CompleteFn>,
+    ) -> Self {
+        let pos = content
+            .borrow()
+            .as_ref()
+            .map_or(0, |content| content.chars().count());
 
         Self {
             title,
             content,
+            multiline,
+            history,
+            complete,
+
             pos,
+            mark: None,
+            recall: None,
+            completions: None,
+            search: None,
         }
     }
 
     pub fn content(&self) -> Content {
         self.content.clone()
     }
+
+    pub fn value(&self) -> String {
+        self.content
+            .try_borrow()
+            .map(|content| content.clone().unwrap_or_default())
+            .unwrap_or_default()
+    }
+
+    /// Whether `key` submits the input, what's been entered is added to history
+    /// when it does. Widgets that act on submission check this before passing
+    /// the key along.
+    pub fn submit(&mut self, key: &Keypress) -> bool {
+        let submitted = if self.multiline {
+            matches!(key, Keypress::Control('s'))
+        } else {
+            matches!(key, Keypress::Enter)
+        };
+
+        if submitted {
+            if let Some(kind) = self.history {
+                remember(kind, &self.value());
+            }
+
+            self.recall = None;
+        }
+
+        submitted
+    }
+
+    /// Rows taken up when drawn, including the border.
+    pub fn height(&self) -> u16 {
+        if !self.multiline {
+            return 3;
+        }
+
+        let lines = self.value().split('\n').count();
+
+        u16::try_from(lines).unwrap_or(u16::MAX).min(MAX_LINES) + 2
+    }
+
+    fn selection(&self) -> Option<Range<usize>> {
+        let mark = self.mark?;
+
+        (mark != self.pos).then(|| mark.min(self.pos)..mark.max(self.pos))
+    }
+
+    fn remove(&mut self, text: &mut String, Range { start, end }: Range<usize>) {
+        text.replace_range(byte_offset(text, start)..byte_offset(text, end), "");
+
+        self.pos = start;
+        self.mark = None;
+    }
+
+    fn insert(&mut self, text: &mut String, c: char) {
+        if let Some(range) = self.selection() {
+            self.remove(text, range);
+        }
+
+        text.insert(byte_offset(text, self.pos), c);
+
+        self.pos += 1;
+        self.mark = None;
+    }
+
+    fn line_start(text: &str, pos: usize) -> usize {
+        pos - locate(text, pos).1
+    }
+
+    fn line_end(text: &str, pos: usize) -> usize {
+        pos + text.chars().skip(pos).take_while(|c| *c != '\n').count()
+    }
+
+    fn word_start(text: &str, pos: usize) -> usize {
+        let before: Vec<char> = text.chars().take(pos).collect();
+        let spaces = before
+            .iter()
+            .rev()
+            .take_while(|c| c.is_whitespace())
+            .count();
+        let word = before
+            .iter()
+            .rev()
+            .skip(spaces)
+            .take_while(|c| !c.is_whitespace())
+            .count();
+
+        pos - spaces - word
+    }
+
+    // Multi-line inputs move between lines first, history is only used from
+    // the first or last line.
+    fn vertical(&mut self, text: &mut String, up: bool) -> bool {
+        if self.multiline {
+            let (row, col) = locate(text, self.pos);
+            let rows = text.split('\n').count();
+
+            if up && row > 0 {
+                self.pos = offset(text, row - 1, col);
+
+                return true;
+            }
+
+            if !up && row + 1 < rows {
+                self.pos = offset(text, row + 1, col);
+
+                return true;
+            }
+        }
+
+        self.recall(text, up)
+    }
+
+    fn recall(&mut self, text: &mut String, older: bool) -> bool {
+        let Some(kind) = self.history else {
+            return false;
+        };
+
+        let entries = recorded(kind);
+
+        let idx = match (&self.recall, older) {
+            (None, true) => entries.len().checked_sub(1),
+            (None, false) => None,
+            (Some((idx, _)), true) => Some(idx.saturating_sub(1)),
+            (Some((idx, _)), false) => Some(idx + 1),
+        };
+
+        let Some(idx) = idx else {
+            return false;
+        };
+
+        match (entries.get(idx), self.recall.as_mut()) {
+            (Some(entry), Some(recall)) => {
+                recall.0 = idx;
+                text.clone_from(entry);
+            }
+            (Some(entry), None) => {
+                self.recall = Some((idx, text.clone()));
+                text.clone_from(entry);
+            }
+            // Past the newest entry, back to what was being typed.
+            (None, _) => {
+                if let Some((_, draft)) = self.recall.take() {
+                    *text = draft;
+                }
+            }
+        }
+
+        self.pos = text.chars().count();
+        self.mark = None;
+
+        true
+    }
+
+    // Whole entries from history that start with what's before the cursor are
+    // offered after everything else.
+    fn complete(&mut self, text: &mut String) -> bool {
+        if self.complete.is_none() && self.history.is_none() {
+            return false;
+        }
+
+        if let Some((candidates, idx, _)) = self.completions.as_mut() {
+            *idx = (*idx + 1) % candidates.len();
+        } else {
+            let split = byte_offset(text, self.pos);
+            let before = &text[..split];
+
+            let mut candidates = self
+                .complete
+                .as_ref()
+                .map(|complete| complete(before))
+                .unwrap_or_default();
+
+            let recalled: Vec<_> = self
+                .history
+                .map(recorded)
+                .unwrap_or_default()
+                .into_iter()
+                .rev()
+                .filter(|entry| {
+                    entry.starts_with(before) && entry != before && !candidates.contains(entry)
+                })
+                .collect();
+            candidates.extend(recalled);
+
+            // Inputs without completion of their own leave `tab` to whatever
+            // they're in, such as a form.
+            if candidates.is_empty() {
+                return self.complete.is_some();
+            }
+
+            self.completions = Some((candidates, 0, text[split..].to_string()));
+        }
+
+        let Some((candidates, idx, after)) = self.completions.as_ref() else {
+            return true;
+        };
+
+        let candidate = &candidates[*idx];
+
+        *text = format!("{candidate}{after}");
+
+        self.pos = candidate.chars().count();
+        self.mark = None;
+
+        true
+    }
+
+    // The newest entry before `before` that matches what's being searched for.
+    // Nothing changes when there isn't one.
+    fn find(&mut self, text: &mut String, before: usize) {
+        let (Some(kind), Some(search)) = (self.history, self.search.as_mut()) else {
+            return;
+        };
+
+        let entries = recorded(kind);
+
+        let Some(idx) = entries[..before.min(entries.len())]
+            .iter()
+            .rposition(|entry| fuzzy(entry, &search.query))
+        else {
+            return;
+        };
+
+        search.found = Some(idx);
+        text.clone_from(&entries[idx]);
+
+        self.pos = text.chars().count();
+        self.mark = None;
+    }
+
+    // Keys while searching history. Anything that isn't part of searching keeps
+    // the entry that was found and is handled as usual, so `enter` submits it.
+    fn searching(&mut self, key: &Keypress) -> Result<bool> {
+        let mut text = self.value();
+
+        match key {
+            Keypress::Control('r') => {
+                let before = self
+                    .search
+                    .as_ref()
+                    .and_then(|search| search.found)
+                    .unwrap_or(usize::MAX);
+
+                self.find(&mut text, before);
+            }
+            Keypress::Printable(c) => {
+                if let Some(search) = self.search.as_mut() {
+                    search.query.push(*c);
+                }
+
+                self.find(&mut text, usize::MAX);
+            }
+            Keypress::Backspace | Keypress::Delete => {
+                if let Some(search) = self.search.as_mut() {
+                    search.query.pop();
+                }
+
+                self.find(&mut text, usize::MAX);
+            }
+            exit_keys!() => {
+                if let Some(search) = self.search.take() {
+                    text = search.draft;
+                    self.pos = text.chars().count();
+                }
+            }
+            _ => {
+                self.search = None;
+
+                return Ok(false);
+            }
+        }
+
+        *self.content.try_borrow_mut()? = Some(text);
+
+        Ok(true)
+    }
+
+    fn lines(&self, text: &str) -> Vec<Line<'static>> {
+        let selection = self.selection().unwrap_or_default();
+        let selected = Style::default().add_modifier(Modifier::REVERSED);
+
+        let mut lines = Vec::new();
+        let mut pos = 0;
+
+        for line in text.split('\n') {
+            let spans: Vec<Span> = line
+                .chars()
+                .enumerate()
+                .chunk_by(|(i, _)| selection.contains(&(pos + i)))
+                .into_iter()
+                .map(|(is_selected, chunk)| {
+                    let chunk: String = chunk.map(|(_, c)| c).collect();
+
+                    if is_selected {
+                        Span::styled(chunk, selected)
+                    } else {
+                        Span::raw(chunk)
+                    }
+                })
+                .collect();
+
+            pos += line.chars().count() + 1;
+            lines.push(Line::from(spans));
+        }
+
+        lines
+    }
 }
 
 impl Widget for Text {
-    // TODO: implement ctrl + a, ctrl + e, ctrl + k, ctrl + u
-    fn dispatch(&mut self, event: &Event, _: &Buffer, area: Rect) -> Result<Broadcast> {
+    fn dispatch(&mut self, event: &Event, _: &Buffer, _: Rect) -> Result<Broadcast> {
         let Some(key) = event.key() else {
             return Ok(Broadcast::Ignored);
         };
 
+        if self.search.is_some() && self.searching(key)? {
+            return Ok(Broadcast::Consumed);
+        }
+
+        // The widget using this decides what submitting does.
+        if self.submit(key) {
+            return Ok(Broadcast::Ignored);
+        }
+
+        if !matches!(key, Keypress::HorizontalTab) {
+            self.completions = None;
+        }
+
+        if !matches!(key, Keypress::CursorUp | Keypress::CursorDown) {
+            self.recall = None;
+        }
+
+        let before = self.value();
+        let mut text = before.clone();
+
+        self.pos = self.pos.min(text.chars().count());
+
         match key {
+            Keypress::Escape if self.mark.is_some() => self.mark = None,
             exit_keys!() => {
                 self.content.try_borrow_mut()?.take();
 
                 return Ok(Broadcast::Exited);
             }
-            Keypress::Printable(x) => {
-                self.content
-                    .try_borrow_mut()?
-                    .get_or_insert_with(String::new)
-                    .insert(self.pos as usize, *x);
-                self.pos = self.pos.saturating_add(1);
+            Keypress::Null => {
+                self.mark = if self.mark.is_some() {
+                    None
+                } else {
+                    Some(self.pos)
+                };
+            }
+            Keypress::Control('y') => {
+                let selected = match self.selection() {
+                    Some(range) => text.chars().skip(range.start).take(range.len()).collect(),
+                    None => text,
+                };
 
-                return Ok(Broadcast::Consumed);
+                return Ok(Broadcast::Clipboard(selected));
+            }
+            Keypress::Control('r') if self.history.is_some() => {
+                self.search = Some(Search {
+                    query: String::new(),
+                    found: None,
+                    draft: text.clone(),
+                });
             }
-            Keypress::Backspace | Keypress::Delete => 'outer: {
-                if self.pos == 0 {
-                    break 'outer;
+            Keypress::Printable(c) => self.insert(&mut text, *c),
+            Keypress::Enter => self.insert(&mut text, '\n'),
+            Keypress::Backspace | Keypress::Delete => {
+                if let Some(range) = self.selection() {
+                    self.remove(&mut text, range);
+                } else if self.pos > 0 {
+                    self.remove(&mut text, self.pos - 1..self.pos);
                 }
-
-                self.content
-                    .try_borrow_mut()?
-                    .as_mut()
-                    .ok_or(eyre!("no content"))?
-                    .remove(self.pos as usize - 1);
-                self.pos = self.pos.saturating_sub(1);
-
-                return Ok(Broadcast::Consumed);
+            }
+            Keypress::ETB => {
+                let start = Self::word_start(&text, self.pos);
+                self.remove(&mut text, start..self.pos);
+            }
+            Keypress::Control('u') => {
+                let start = Self::line_start(&text, self.pos);
+                self.remove(&mut text, start..self.pos);
             }
             Keypress::Control('k') => {
-                let mut opt = self.content.try_borrow_mut()?;
-
-                let content = opt.get_or_insert_with(String::new);
-
-                *content = String::new();
-
-                self.pos = 0;
+                let pos = self.pos;
+                let end = Self::line_end(&text, pos);
+                self.remove(&mut text, pos..end);
             }
-            _ => {}
-        };
-
-        #[allow(clippy::cast_possible_truncation)]
-        if let Some(Movement::X(x)) = move_cursor(key, area) {
-            self.pos = self.pos.saturating_add_signed(x);
+            Keypress::Control('a') | Keypress::CursorHome => {
+                self.pos = Self::line_start(&text, self.pos);
+            }
+            Keypress::Control('e') => self.pos = Self::line_end(&text, self.pos),
+            Keypress::CursorLeft | Keypress::Control('b') => {
+                self.pos = self.pos.saturating_sub(1);
+            }
+            Keypress::CursorRight | Keypress::Control('f') => {
+                self.pos = (self.pos + 1).min(text.chars().count());
+            }
+            Keypress::CursorUp | Keypress::CursorDown => {
+                if !self.vertical(&mut text, matches!(key, Keypress::CursorUp)) {
+                    return Ok(Broadcast::Ignored);
+                }
+            }
+            Keypress::HorizontalTab => {
+                if !self.complete(&mut text) {
+                    return Ok(Broadcast::Ignored);
+                }
+            }
+            _ => return Ok(Broadcast::Ignored),
+        }
 
-            return Ok(Broadcast::Consumed);
+        if text != before {
+            *self.content.try_borrow_mut()? = Some(text);
         }
 
-        Ok(Broadcast::Ignored)
+        Ok(Broadcast::Consumed)
     }
 
-    #[allow(clippy::cast_possible_truncation)]
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
         let mut block = Block::default().borders(Borders::ALL);
 
@@ -115,27 +635,45 @@ impl Widget for Text {
             block = block.title(self.title.as_ref());
         }
 
-        let cmd_pos = block.inner(area);
-        let content = self
-            .content
-            .try_borrow()?
-            .as_ref()
-            .map_or(String::new(), String::clone);
+        if let Some(search) = self.search.as_ref() {
+            block = block.title_bottom(format!(" search: {} ", search.query));
+        } else if let Some((candidates, idx, _)) = self.completions.as_ref() {
+            if candidates.len() > 1 {
+                block = block.title_bottom(format!(" {}/{} ", idx + 1, candidates.len()));
+            }
+        }
+
+        let inner = block.inner(area);
+        let text = self.value();
+
+        self.pos = self.pos.min(text.chars().count());
 
-        self.pos = self.pos.clamp(0, content.len() as u16);
+        // Scroll so that the cursor is always visible.
+        let (row, col) = locate(&text, self.pos);
+        let row = u16::try_from(row).unwrap_or(u16::MAX);
+        let col = u16::try_from(col).unwrap_or(u16::MAX);
+        let scroll = (
+            row.saturating_sub(inner.height.saturating_sub(1)),
+            col.saturating_sub(inner.width.saturating_sub(1)),
+        );
 
-        let pg = Paragraph::new(content).block(block);
+        let pg = Paragraph::new(self.lines(&text))
+            .scroll(scroll)
+            .block(block);
 
         frame.render_widget(pg, area);
 
-        frame.set_cursor_position(Position::new(cmd_pos.x + self.pos, cmd_pos.y));
+        frame.set_cursor_position(Position::new(
+            inner.x + col - scroll.1,
+            inner.y + row - scroll.0,
+        ));
 
         Ok(())
     }
 
     fn placement(&self) -> super::Placement {
         super::Placement {
-            vertical: super::Constraint::Length(3),
+            vertical: super::Constraint::Length(self.height()),
             ..Default::default()
         }
     }
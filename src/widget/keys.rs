@@ -0,0 +1,237 @@
+use itertools::Itertools;
+use strum::{EnumIter, IntoEnumIterator, IntoStaticStr};
+
+use super::locale;
+use crate::events::Keypress;
+
+/// Where a key does something, the help overlay groups bindings by this.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, EnumIter, IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum Context {
+    Global,
+    Navigation,
+    Tabs,
+    Tables,
+    Copy,
+    Logs,
+    Errors,
+}
+
+impl Context {
+    /// Heading in the help overlay, in the dashboard's language.
+    pub fn title(self) -> String {
+        let key: &'static str = self.into();
+
+        locale::t(&format!("keys.context.{key}"))
+    }
+}
+
+/// Everything a key can do. Widgets match keys with [`Action::matches`] and
+/// the help overlay (`?`) is built from the same definitions, so it can't
+/// drift from what the keys actually do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum Action {
+    Help,
+    Back,
+    Scratchpad,
+    Share,
+    Snapshot,
+
+    Up,
+    Down,
+    Left,
+    Right,
+    PageDown,
+    PageUp,
+    HalfPageDown,
+    HalfPageUp,
+    Top,
+    Bottom,
+    LineStart,
+    LineEnd,
+
+    NewTab,
+    CloseTab,
+    MoveTabLeft,
+    MoveTabRight,
+
+    Open,
+    Filter,
+    BuildFilter,
+    Mark,
+    MarkAll,
+    ClearMarks,
+    Group,
+    Sort,
+    Fold,
+    FoldAll,
+
+    YankName,
+    YankPath,
+    YankCommand,
+
+    CycleTimestamps,
+    ToggleTimestamps,
+
+    ShowCauses,
+    Retry,
+    Dismiss,
+}
+
+impl Action {
+    // Some control keys arrive as their ASCII names, both are listed so that
+    // either matches.
+    pub fn keys(self) -> &'static [Keypress] {
+        match self {
+            Self::Help => &[Keypress::Printable('?')],
+            Self::Back => &[Keypress::Escape],
+            Self::Scratchpad => &[Keypress::Control('n'), Keypress::ShiftOut],
+            Self::Share => &[Keypress::Control('x')],
+            Self::Snapshot => &[Keypress::FS],
+
+            Self::Up => &[Keypress::CursorUp, Keypress::Printable('k')],
+            Self::Down => &[Keypress::CursorDown, Keypress::Printable('j')],
+            Self::Left => &[Keypress::CursorLeft, Keypress::Printable('h')],
+            Self::Right => &[Keypress::CursorRight, Keypress::Printable('l')],
+            Self::PageDown => &[
+                Keypress::Printable(' '),
+                Keypress::Printable('f'),
+                Keypress::Control('f'),
+            ],
+            Self::PageUp => &[Keypress::Printable('b'), Keypress::Control('b')],
+            Self::HalfPageDown => &[Keypress::Control('d')],
+            Self::HalfPageUp => &[Keypress::Control('u')],
+            // `gg` is handled by `Nav`, as a sequence it can't be listed here.
+            Self::Top => &[Keypress::Printable('H')],
+            Self::Bottom => &[Keypress::Printable('G'), Keypress::Printable('L')],
+            Self::LineStart => &[Keypress::Printable('^'), Keypress::Control('a')],
+            Self::LineEnd => &[Keypress::Printable('$'), Keypress::Control('e')],
+
+            Self::NewTab => &[Keypress::Control('t'), Keypress::DC4],
+            Self::CloseTab => &[Keypress::Control('w'), Keypress::ETB],
+            Self::MoveTabLeft => &[Keypress::Printable('<')],
+            Self::MoveTabRight => &[Keypress::Printable('>')],
+
+            Self::Open => &[Keypress::Enter],
+            Self::Filter => &[Keypress::Printable('/')],
+            Self::BuildFilter => &[Keypress::Printable('F')],
+            // Space marks rows in tables, `x` is kept as an alias. Everywhere else,
+            // and in tables whose rows can't be marked, space pages down.
+            Self::Mark => &[Keypress::Printable('x'), Keypress::Printable(' ')],
+            Self::MarkAll => &[Keypress::Printable('A')],
+            Self::ClearMarks => &[Keypress::Printable('X')],
+            Self::Group => &[Keypress::Printable('o')],
+            Self::Sort => &[Keypress::Printable('s')],
+            Self::Fold => &[Keypress::Printable('z')],
+            Self::FoldAll => &[Keypress::Printable('Z')],
+
+            Self::YankName => &[Keypress::Printable('y')],
+            Self::YankPath => &[Keypress::Printable('Y')],
+            Self::YankCommand => &[Keypress::Control('k')],
+
+            Self::CycleTimestamps => &[Keypress::Printable('t')],
+            Self::ToggleTimestamps => &[Keypress::Printable('T')],
+
+            Self::ShowCauses => &[Keypress::Enter],
+            Self::Retry => &[Keypress::Printable('r')],
+            Self::Dismiss => &[Keypress::Printable('d')],
+        }
+    }
+
+    pub fn context(self) -> Context {
+        match self {
+            Self::Help | Self::Back | Self::Scratchpad | Self::Share | Self::Snapshot => {
+                Context::Global
+            }
+            Self::Up
+            | Self::Down
+            | Self::Left
+            | Self::Right
+            | Self::PageDown
+            | Self::PageUp
+            | Self::HalfPageDown
+            | Self::HalfPageUp
+            | Self::Top
+            | Self::Bottom
+            | Self::LineStart
+            | Self::LineEnd => Context::Navigation,
+            Self::NewTab | Self::CloseTab | Self::MoveTabLeft | Self::MoveTabRight => Context::Tabs,
+            Self::Open
+            | Self::Filter
+            | Self::BuildFilter
+            | Self::Mark
+            | Self::MarkAll
+            | Self::ClearMarks
+            | Self::Group
+            | Self::Sort
+            | Self::Fold
+            | Self::FoldAll => Context::Tables,
+            Self::YankName | Self::YankPath | Self::YankCommand => Context::Copy,
+            Self::CycleTimestamps | Self::ToggleTimestamps => Context::Logs,
+            Self::ShowCauses | Self::Retry | Self::Dismiss => Context::Errors,
+        }
+    }
+
+    /// What the action does, in the dashboard's language.
+    pub fn description(self) -> String {
+        let key: &'static str = self.into();
+
+        locale::t(&format!("keys.action.{key}"))
+    }
+
+    pub fn matches(self, key: &Keypress) -> bool {
+        self.keys().contains(key)
+    }
+
+    /// The keys as they're written in the help, eg. `ctrl-n`.
+    pub fn label(self) -> String {
+        let keys = self.keys().iter().map(name);
+
+        match self {
+            Self::Top => std::iter::once("gg".to_string())
+                .chain(keys)
+                .unique()
+                .join(", "),
+            _ => keys.unique().join(", "),
+        }
+    }
+
+    /// Every binding, grouped by context in display order.
+    pub fn by_context() -> Vec<(Context, Vec<Action>)> {
+        Context::iter()
+            .map(|context| {
+                (
+                    context,
+                    Self::iter()
+                        .filter(|action| action.context() == context)
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// How a key is written for people.
+pub fn name(key: &Keypress) -> String {
+    match key {
+        Keypress::Printable(' ') => "space".to_string(),
+        Keypress::Printable(c) => c.to_string(),
+        Keypress::Control(c) => format!("ctrl-{c}"),
+        Keypress::Null => "ctrl-space".to_string(),
+        Keypress::ShiftOut => "ctrl-n".to_string(),
+        Keypress::DC4 => "ctrl-t".to_string(),
+        Keypress::ETB => "ctrl-w".to_string(),
+        Keypress::FS => "ctrl-\\".to_string(),
+        Keypress::Enter => "enter".to_string(),
+        Keypress::Escape => "esc".to_string(),
+        Keypress::HorizontalTab => "tab".to_string(),
+        Keypress::Backspace | Keypress::Delete => "backspace".to_string(),
+        Keypress::CursorUp => "↑".to_string(),
+        Keypress::CursorDown => "↓".to_string(),
+        Keypress::CursorLeft => "←".to_string(),
+        Keypress::CursorRight => "→".to_string(),
+        Keypress::CursorHome => "home".to_string(),
+        key => format!("{key:?}").to_lowercase(),
+    }
+}
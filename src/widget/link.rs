@@ -0,0 +1,239 @@
+use std::{fmt, str::FromStr, sync::Arc};
+
+use eyre::{eyre, Result};
+use k8s_openapi::api::{
+    apps::v1::Deployment,
+    autoscaling::v2::HorizontalPodAutoscaler,
+    core::v1::{Node, PersistentVolume, PersistentVolumeClaim, Pod},
+};
+use kube::{api::ListParams, Api};
+
+use super::{deployment, node, pod, pv, pvc, BoxWidget, Widget};
+use crate::{
+    history::History,
+    resources::{deployment::autoscaler, policy::Policy},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Pods,
+    Nodes,
+    Pvcs,
+    Pvs,
+    Deployments,
+}
+
+impl Kind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Pods => "pods",
+            Self::Nodes => "nodes",
+            Self::Pvcs => "pvcs",
+            Self::Pvs => "pvs",
+            Self::Deployments => "deployments",
+        }
+    }
+
+    pub fn namespaced(self) -> bool {
+        matches!(self, Self::Pods | Self::Pvcs | Self::Deployments)
+    }
+
+    /// Construct the list for this kind with `filter` already applied.
+    pub fn list(
+        self,
+        client: kube::Client,
+        history: Arc<dyn History>,
+        filter: Option<String>,
+    ) -> BoxWidget {
+        match self {
+            Self::Pods => pod::List::new(client, history, filter).boxed(),
+            Self::Nodes => node::List::builder()
+                .client(client)
+                .history(history)
+                .maybe_filter(filter)
+                .build()
+                .boxed(),
+            Self::Pvcs => pvc::List::builder()
+                .client(client)
+                .maybe_filter(filter)
+                .build()
+                .boxed(),
+            Self::Pvs => pv::List::builder()
+                .client(client)
+                .maybe_filter(filter)
+                .build()
+                .boxed(),
+            Self::Deployments => deployment::List::builder()
+                .client(client)
+                .maybe_filter(filter)
+                .build()
+                .boxed(),
+        }
+    }
+}
+
+impl FromStr for Kind {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "pods" => Ok(Self::Pods),
+            "nodes" => Ok(Self::Nodes),
+            "pvcs" => Ok(Self::Pvcs),
+            "pvs" => Ok(Self::Pvs),
+            "deployments" => Ok(Self::Deployments),
+            _ => Err(eyre!("unknown resource: {s}")),
+        }
+    }
+}
+
+/// A view to open as soon as the dashboard starts, for example
+/// `pods/default/nginx` or `nodes`. These are passed in as the SSH username so
+/// that alerts can link straight to the resource in question.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Link {
+    pub kind: Kind,
+    pub namespace: Option<String>,
+    pub name: Option<String>,
+}
+
+impl fmt::Display for Link {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind.name())?;
+
+        for part in [&self.namespace, &self.name].into_iter().flatten() {
+            write!(f, "/{part}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Link {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split('/');
+
+        let kind: Kind = parts.next().unwrap_or_default().parse()?;
+
+        let (namespace, name) = match (kind.namespaced(), parts.next(), parts.next()) {
+            (_, None, None) => (None, None),
+            (true, Some(ns), Some(name)) => (Some(ns.to_string()), Some(name.to_string())),
+            (false, Some(name), None) => (None, Some(name.to_string())),
+            _ => return Err(eyre!("invalid link: {s}")),
+        };
+
+        if parts.next().is_some() || name.as_deref() == Some("") {
+            return Err(eyre!("invalid link: {s}"));
+        }
+
+        Ok(Self {
+            kind,
+            namespace,
+            name,
+        })
+    }
+}
+
+/// A linked resource, fetched so that its detail view can be constructed. This
+/// is `Send` so that it can be fetched in the background, widgets aren't.
+pub enum Fetched {
+    Pod(Pod),
+    Node(Node),
+    Pvc(PersistentVolumeClaim),
+    Pv(PersistentVolume),
+    Deployment(Deployment, Option<Arc<HorizontalPodAutoscaler>>),
+}
+
+impl Fetched {
+    pub fn widget(self, client: kube::Client, history: Arc<dyn History>) -> BoxWidget {
+        match self {
+            Self::Pod(pod) => pod::Detail::builder()
+                .client(&client)
+                .pod(Arc::new(pod))
+                .history(history)
+                .build()
+                .boxed(),
+            Self::Node(node) => node::Detail::builder()
+                .client(client)
+                .node(Arc::new(node))
+                .history(history)
+                .build()
+                .boxed(),
+            Self::Pvc(pvc) => pvc::Detail::builder()
+                .client(client)
+                .pvc(Arc::new(pvc))
+                .build()
+                .boxed(),
+            Self::Pv(pv) => pv::Detail::builder()
+                .client(client)
+                .pv(Arc::new(pv))
+                .build()
+                .boxed(),
+            Self::Deployment(deployment, autoscaler) => deployment::Detail::builder()
+                .client(client)
+                .maybe_autoscaler(autoscaler)
+                .deployment(Arc::new(deployment))
+                .build()
+                .boxed(),
+        }
+    }
+}
+
+impl Link {
+    /// Fetch only the linked resource and construct its detail view. Links to a
+    /// list have nothing to preload.
+    pub async fn detail(
+        &self,
+        client: kube::Client,
+        history: Arc<dyn History>,
+    ) -> Result<Option<BoxWidget>> {
+        Ok(self
+            .fetch(client.clone())
+            .await?
+            .map(|fetched| fetched.widget(client, history)))
+    }
+
+    /// Fetch the linked resource, see [`Link::detail`].
+    pub async fn fetch(&self, client: kube::Client) -> Result<Option<Fetched>> {
+        let Some(name) = self.name.as_deref() else {
+            return Ok(None);
+        };
+
+        let ns = self.namespace.as_deref().unwrap_or_default();
+
+        if !Policy::visible(self.namespace.as_deref()) {
+            return Err(eyre!("namespace {ns} is not available"));
+        }
+
+        let fetched = match self.kind {
+            Kind::Pods => Fetched::Pod(Api::<Pod>::namespaced(client, ns).get(name).await?),
+            Kind::Nodes => Fetched::Node(Api::<Node>::all(client).get(name).await?),
+            Kind::Pvcs => Fetched::Pvc(
+                Api::<PersistentVolumeClaim>::namespaced(client, ns)
+                    .get(name)
+                    .await?,
+            ),
+            Kind::Pvs => Fetched::Pv(Api::<PersistentVolume>::all(client).get(name).await?),
+            Kind::Deployments => {
+                let deployment = Api::<Deployment>::namespaced(client.clone(), ns)
+                    .get(name)
+                    .await?;
+
+                // Missing access to autoscalers only means there's no tab for it.
+                let autoscalers = Api::<HorizontalPodAutoscaler>::namespaced(client, ns)
+                    .list(&ListParams::default())
+                    .await
+                    .map(|list| list.items.into_iter().map(Arc::new).collect::<Vec<_>>())
+                    .unwrap_or_default();
+
+                let autoscaler = autoscaler(&deployment, &autoscalers);
+
+                Fetched::Deployment(deployment, autoscaler)
+            }
+        };
+
+        Ok(Some(fetched))
+    }
+}
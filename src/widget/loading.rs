@@ -5,14 +5,14 @@ use ratatui::{
     Frame,
 };
 
-use super::Widget;
+use super::{locale::t, narration, Widget};
 
 pub struct Loading;
 
 impl Widget for Loading {
     #[allow(clippy::cast_possible_truncation)]
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
-        let pg = Paragraph::new("Loading...");
+        let pg = Paragraph::new(t("loading"));
 
         let y = Layout::horizontal([pg.line_width() as u16]).flex(Flex::Center);
         let x = Layout::vertical([pg.line_count(pg.line_width() as u16) as u16]).flex(Flex::Center);
@@ -21,6 +21,8 @@ impl Widget for Loading {
 
         frame.render_widget(pg, area);
 
+        narration::focus(|| None, || t("loading"));
+
         Ok(())
     }
 
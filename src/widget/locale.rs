@@ -0,0 +1,83 @@
+use std::{collections::HashMap, fmt::Display};
+
+use lazy_static::lazy_static;
+use strum::{EnumIter, EnumString, IntoEnumIterator};
+
+use crate::dashboard::context::Context;
+
+/// Environment variables a client can send to pick the language, highest
+/// precedence first. `KTY_LANG` allows picking one for kty alone.
+pub static VARIABLES: &[&str] = &["KTY_LANG", "LC_ALL", "LC_MESSAGES", "LANG"];
+
+/// Languages with a catalog in `locales/`.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, strum::Display, EnumIter, EnumString,
+)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+}
+
+impl Locale {
+    /// Language of the dashboard being drawn on this thread.
+    pub fn current() -> Self {
+        Context::with(|context| context.locale)
+    }
+
+    /// Parse a POSIX locale, such as `es_ES.UTF-8`. Only the language is used,
+    /// `C` and `POSIX` are English.
+    pub fn from_posix(value: &str) -> Option<Self> {
+        let language = value.split(['_', '.', '@', '-']).next().unwrap_or_default();
+
+        match language {
+            "C" | "POSIX" => Some(Self::En),
+            language => language.parse().ok(),
+        }
+    }
+
+    /// The language requested by environment variable `name`, along with its
+    /// position in [`VARIABLES`] so that the highest precedence one can win.
+    pub fn from_env(name: &str, value: &str) -> Option<(usize, Self)> {
+        let rank = VARIABLES.iter().position(|variable| *variable == name)?;
+
+        Some((rank, Self::from_posix(value)?))
+    }
+
+    fn catalog(self) -> &'static str {
+        match self {
+            Self::En => include_str!("../../locales/en.yaml"),
+            Self::Es => include_str!("../../locales/es.yaml"),
+        }
+    }
+}
+
+lazy_static! {
+    static ref CATALOGS: HashMap<Locale, HashMap<String, String>> = Locale::iter()
+        .map(|locale| {
+            let catalog = serde_yaml::from_str(locale.catalog())
+                .unwrap_or_else(|err| panic!("{locale} catalog is invalid: {err}"));
+
+            (locale, catalog)
+        })
+        .collect();
+}
+
+/// The string for `key` in the current language. Keys missing from a
+/// catalog fall back to English and then to the key itself, so that a partial
+/// translation is still usable.
+pub fn t(key: &str) -> String {
+    [Locale::current(), Locale::En]
+        .iter()
+        .find_map(|locale| CATALOGS.get(locale)?.get(key))
+        .cloned()
+        .unwrap_or_else(|| key.to_string())
+}
+
+/// Like [`t`], replacing `{name}` placeholders with their values.
+pub fn t_with(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter().fold(t(key), |text, (name, value)| {
+        text.replace(&format!("{{{name}}}"), &value.to_string())
+    })
+}
@@ -1,5 +1,6 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
+use chrono::{DateTime, Utc};
 use color_eyre::{Section, SectionExt};
 use eyre::{eyre, Report, Result};
 use futures::{
@@ -13,6 +14,7 @@ use ratatui::{
     buffer::Buffer,
     layout::{Position, Rect},
     style::{palette::tailwind, Style},
+    text::Line,
     widgets::{Block, Borders, Paragraph},
     Frame,
 };
@@ -22,7 +24,9 @@ use tokio::{
 };
 
 use super::{
-    nav::{move_cursor, Movement},
+    clock::Clock,
+    keys::Action,
+    nav::{Motion, Movement, Nav},
     tabs::Tab,
     viewport::Viewport,
     Widget, WIDGET_VIEWS,
@@ -32,21 +36,25 @@ use crate::{
     resources::{
         container::{Container, ContainerExt},
         pod::PodExt,
+        policy::Verb,
+        redaction::Redaction,
     },
 };
 
+// Lines fetched per container when following every pod of a workload, the
+// full history of all of them is rarely what anyone is after.
+static AGGREGATED_TAIL: i64 = 100;
+// Pods are told apart by the color of their prefix, as ANSI foreground codes.
+static PALETTE: [u8; 6] = [36, 33, 35, 32, 34, 31];
+
 pub struct Log {
     task: Option<JoinHandle<Result<()>>>,
 
     rx: mpsc::UnboundedReceiver<String>,
-    buffer: Vec<String>,
-
-    position: Position,
+    lines: Lines,
 }
 
 // TODO:
-// - Make this work with with anything that has pods (e.g. deployments,
-//   stateful).
 // - Allow for searching within the logs. Feels like it should be ala fzf and
 //   jump to the text + highlight it.
 // - Only fetch the most recent X lines, on scroll-back, fetch more.
@@ -70,6 +78,7 @@ impl Log {
                 follow: true,
                 pretty: true,
                 previous: true,
+                timestamps: true,
                 ..Default::default()
             },
         ));
@@ -77,9 +86,7 @@ impl Log {
         Self {
             task: Some(task),
             rx,
-            buffer: Vec::new(),
-
-            position: Position::default(),
+            lines: Lines::default(),
         }
     }
 
@@ -88,6 +95,7 @@ impl Log {
     pub fn tab(name: String, client: kube::Client, pod: Arc<Pod>) -> Tab {
         Tab::builder()
             .name(name)
+            .verb(Verb::Logs)
             .constructor(Box::new(move || {
                 Log::new(client.clone(), pod.clone()).boxed().into()
             }))
@@ -98,7 +106,9 @@ impl Log {
         let mut i = 0;
 
         while let Ok(line) = self.rx.try_recv() {
-            self.buffer.push(line);
+            self.lines
+                .buffer
+                .push(split_timestamp(Redaction::apply(&line).into_owned()));
             i += 1;
         }
 
@@ -108,33 +118,12 @@ impl Log {
 
 impl Widget for Log {
     fn dispatch(&mut self, event: &Event, _: &Buffer, area: Rect) -> Result<Broadcast> {
-        let Some(key) = event.key() else {
-            return Ok(Broadcast::Ignored);
-        };
-
-        if let Some(Movement::Y(y)) = move_cursor(key, area) {
-            self.position.y = self.position.y.saturating_add_signed(y);
-
-            return Ok(Broadcast::Consumed);
-        }
-
-        Ok(Broadcast::Ignored)
+        self.lines.dispatch(event, area)
     }
 
-    #[allow(clippy::cast_possible_truncation)]
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
         let lines = self.update();
 
-        if self
-            .position
-            .y
-            .saturating_add(lines)
-            .saturating_add(area.height)
-            >= self.buffer.len() as u16
-        {
-            self.position.y = u16::MAX;
-        }
-
         if self.task.as_ref().map_or(false, JoinHandle::is_finished) {
             let task = self.task.take().expect("task is finished");
 
@@ -152,19 +141,293 @@ impl Widget for Log {
             }
         }
 
-        let block = Block::default().borders(Borders::ALL);
+        self.lines.draw(
+            frame,
+            area,
+            lines,
+            self.task
+                .is_none()
+                .then_some("Log stream ended, come back to restart"),
+        )
+    }
+
+    fn dirty(&self) -> bool {
+        !self.rx.is_empty()
+    }
+}
+
+impl Drop for Log {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.as_ref() {
+            task.abort();
+        }
+    }
+}
+
+/// The logs of every pod of a workload, merged into one stream. Each line is
+/// prefixed with the pod it came from, pods that show up later (a rollout or a
+/// retried job) are followed as they appear.
+pub struct Aggregated {
+    client: kube::Client,
+    pods: Box<dyn Fn() -> Vec<Arc<Pod>>>,
+    // By the pod's uid, pods that went away are kept so they aren't followed
+    // again.
+    streams: HashMap<String, Stream>,
+    lines: Lines,
+}
+
+struct Stream {
+    prefix: String,
+    task: Option<JoinHandle<Result<()>>>,
+    rx: mpsc::UnboundedReceiver<String>,
+}
+
+impl Drop for Stream {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.as_ref() {
+            task.abort();
+        }
+    }
+}
+
+impl Aggregated {
+    pub fn new(client: kube::Client, pods: impl Fn() -> Vec<Arc<Pod>> + 'static) -> Self {
+        WIDGET_VIEWS.workload.log.inc();
+
+        Self {
+            client,
+            pods: Box::new(pods),
+            streams: HashMap::new(),
+            lines: Lines::default(),
+        }
+    }
+
+    pub fn tab(
+        name: String,
+        client: kube::Client,
+        pods: impl Fn() -> Vec<Arc<Pod>> + Clone + Send + 'static,
+    ) -> Tab {
+        Tab::builder()
+            .name(name)
+            .verb(Verb::Logs)
+            .constructor(Box::new(move || {
+                Aggregated::new(client.clone(), pods.clone()).boxed().into()
+            }))
+            .build()
+    }
+
+    fn follow(&mut self, pod: Arc<Pod>) {
+        let color = PALETTE[self.streams.len() % PALETTE.len()];
+        let prefix = format!("\x1b[{color}m{}\x1b[0m", pod.name_any());
+        let uid = pod.uid().unwrap_or_default();
+
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let task = tokio::spawn(log_stream(
+            self.client.clone(),
+            pod,
+            tx,
+            LogParams {
+                follow: true,
+                pretty: true,
+                tail_lines: Some(AGGREGATED_TAIL),
+                timestamps: true,
+                ..Default::default()
+            },
+        ));
+
+        self.streams.insert(
+            uid,
+            Stream {
+                prefix,
+                task: Some(task),
+                rx,
+            },
+        );
+    }
+
+    // New lines are sorted by their timestamp before they're added, so that
+    // what arrived from several pods since the last frame is in order.
+    #[allow(clippy::cast_possible_truncation)]
+    fn update(&mut self) -> u16 {
+        for pod in (self.pods)() {
+            if pod
+                .uid()
+                .is_some_and(|uid| !self.streams.contains_key(&uid))
+            {
+                self.follow(pod);
+            }
+        }
+
+        let mut added = Vec::new();
+
+        for stream in self.streams.values_mut() {
+            while let Ok(line) = stream.rx.try_recv() {
+                let (time, line) = split_timestamp(Redaction::apply(&line).into_owned());
+
+                added.push((time, format!("{} {line}", stream.prefix)));
+            }
+
+            // A pod's stream ends when it is deleted, only failures are worth showing.
+            if stream.task.as_ref().is_some_and(JoinHandle::is_finished) {
+                let task = stream.task.take().expect("task is finished");
+
+                if let Err(err) = futures::executor::block_on(async move { task.await? }) {
+                    added.push((
+                        Some(Utc::now()),
+                        format!("{} \x1b[31mlog stream failed: {err}\x1b[0m", stream.prefix),
+                    ));
+                }
+            }
+        }
+
+        added.sort_by_key(|(time, _)| *time);
+
+        let count = added.len();
+        self.lines.buffer.extend(added);
+
+        count as u16
+    }
+}
+
+impl Widget for Aggregated {
+    fn dispatch(&mut self, event: &Event, _: &Buffer, area: Rect) -> Result<Broadcast> {
+        self.lines.dispatch(event, area)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let lines = self.update();
+
+        self.lines.draw(
+            frame,
+            area,
+            lines,
+            self.streams.is_empty().then_some("No pods to follow yet"),
+        )
+    }
+
+    fn dirty(&self) -> bool {
+        self.streams.values().any(|stream| !stream.rx.is_empty())
+    }
+}
+
+// What has been read so far and where the user is looking, shared by the
+// single pod and the aggregated views.
+struct Lines {
+    buffer: Vec<(Option<DateTime<Utc>>, String)>,
+    timestamps: bool,
+
+    position: Position,
+    nav: Nav,
+}
+
+impl Default for Lines {
+    fn default() -> Self {
+        Self {
+            buffer: Vec::new(),
+            timestamps: true,
+
+            position: Position::default(),
+            nav: Nav::default(),
+        }
+    }
+}
+
+impl Lines {
+    fn dispatch(&mut self, event: &Event, area: Rect) -> Result<Broadcast> {
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        match key {
+            key if Action::CycleTimestamps.matches(key) => {
+                Clock::cycle();
+                self.timestamps = true;
+
+                return Ok(Broadcast::Consumed);
+            }
+            key if Action::ToggleTimestamps.matches(key) => {
+                self.timestamps = !self.timestamps;
+
+                return Ok(Broadcast::Consumed);
+            }
+            _ => {}
+        }
+
+        match self.nav.motion(key, area) {
+            Motion::Move(Movement::Y(y)) => {
+                self.position.y = self.position.y.saturating_add_signed(y);
+
+                return Ok(Broadcast::Consumed);
+            }
+            Motion::Pending => return Ok(Broadcast::Consumed),
+            _ => {}
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    // `added` is how many lines were added since the last frame, the view
+    // follows along when it was at the bottom. `notice` is shown on top.
+    #[allow(clippy::cast_possible_truncation)]
+    fn draw(
+        &mut self,
+        frame: &mut Frame,
+        area: Rect,
+        added: u16,
+        notice: Option<&str>,
+    ) -> Result<()> {
+        if self
+            .position
+            .y
+            .saturating_add(added)
+            .saturating_add(area.height)
+            >= self.buffer.len() as u16
+        {
+            self.position.y = u16::MAX;
+        }
+
+        let clock = Clock::current();
+
+        let block = Block::default().borders(Borders::ALL).title(
+            Line::from(if self.timestamps {
+                format!(" {} time (t: switch, T: hide) ", clock.format.name())
+            } else {
+                " no timestamps (T: show) ".to_string()
+            })
+            .right_aligned(),
+        );
 
         let inner = block.inner(area);
 
+        // Only the visible lines are formatted, relative timestamps change on every
+        // draw.
+        self.position.y = self
+            .position
+            .y
+            .min((self.buffer.len() as u16).saturating_sub(inner.height));
+        let start = self.position.y as usize;
+        let end = (start + inner.height as usize).min(self.buffer.len());
+
+        let width = clock.format.width() as usize;
+        let visible: Vec<String> = self.buffer[start..end]
+            .iter()
+            .map(|(time, line)| match time {
+                Some(time) if self.timestamps => {
+                    format!("{:<width$} {line}", clock.format(*time))
+                }
+                _ => line.clone(),
+            })
+            .collect();
+
         let result = Viewport::builder()
-            .buffer(&self.buffer)
-            .view(self.position)
+            .buffer(&visible)
             .build()
             .draw(frame, inner);
 
-        if self.task.is_none() {
+        if let Some(notice) = notice {
             frame.render_widget(
-                Paragraph::new("Log stream ended, come back to restart")
+                Paragraph::new(notice)
                     .style(Style::default().fg(tailwind::RED.c300))
                     .centered(),
                 inner,
@@ -177,11 +440,16 @@ impl Widget for Log {
     }
 }
 
-impl Drop for Log {
-    fn drop(&mut self) {
-        if let Some(task) = self.task.as_ref() {
-            task.abort();
-        }
+// Lines are prefixed with an RFC 3339 timestamp when `LogParams.timestamps` is
+// set.
+fn split_timestamp(line: String) -> (Option<DateTime<Utc>>, String) {
+    let split = line
+        .split_once(' ')
+        .and_then(|(ts, rest)| Some((DateTime::parse_from_rfc3339(ts).ok()?, rest.to_string())));
+
+    match split {
+        Some((time, rest)) => (Some(time.to_utc()), rest),
+        None => (None, line),
     }
 }
 
@@ -0,0 +1,375 @@
+use std::{borrow::Borrow, sync::Arc};
+
+use eyre::Result;
+use kube::{api::DynamicObject, Api, Resource, ResourceExt};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{palette::tailwind, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use serde::Serialize;
+use tokio::task::JoinHandle;
+
+use super::{input, preview::Preview, tabs::Tab, Widget, WIDGET_VIEWS_VEC};
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    resources::{
+        action::Actions as _,
+        gitops,
+        metadata::{Change, Field},
+        policy::Verb,
+        preview::Outcome,
+    },
+};
+
+enum Status {
+    Idle,
+    Invalid(String),
+    Running(String, JoinHandle<Result<DynamicObject>>),
+    Done(Result<String>),
+}
+
+/// Labels and annotations for a resource. These can be added and removed,
+/// changes are applied as the current user.
+pub struct Metadata {
+    client: Api<DynamicObject>,
+    object: Result<DynamicObject>,
+
+    idx: usize,
+    input: Option<(Field, input::Text)>,
+    status: Status,
+    preview: Option<(Preview, Change, String)>,
+}
+
+impl Metadata {
+    pub fn new<K>(client: kube::Client, resource: &Arc<K>) -> Self
+    where
+        K: Resource<DynamicType = ()> + Serialize + Send + Sync + 'static,
+    {
+        WIDGET_VIEWS_VEC
+            .with_label_values(&[K::kind(&()).borrow(), "metadata"])
+            .inc();
+
+        Self {
+            client: resource.dynamic_api(client),
+            object: serde_json::to_value(resource.as_ref())
+                .and_then(serde_json::from_value)
+                .map_err(Into::into),
+
+            idx: 0,
+            input: None,
+            status: Status::Idle,
+            preview: None,
+        }
+    }
+
+    pub fn tab<K>(name: String, client: kube::Client, resource: Arc<K>) -> Tab
+    where
+        K: Resource<DynamicType = ()> + Serialize + Send + Sync + 'static,
+    {
+        Tab::builder()
+            .name(name)
+            .verb(Verb::Patch)
+            .constructor(Box::new(move || {
+                Self::new(client.clone(), &resource).boxed().into()
+            }))
+            .build()
+    }
+
+    fn entries(&self) -> Vec<(Field, String, String)> {
+        let Ok(object) = self.object.as_ref() else {
+            return Vec::new();
+        };
+
+        [Field::Label, Field::Annotation]
+            .into_iter()
+            .flat_map(|field| {
+                field
+                    .get(object)
+                    .iter()
+                    .map(move |(k, v)| (field, k.clone(), v.clone()))
+            })
+            .collect()
+    }
+
+    fn apply(&mut self, change: Change, description: String) {
+        if matches!(self.status, Status::Running(..)) {
+            return;
+        }
+
+        // Resources managed by GitOps are always previewed, so that the warning
+        // about changes being reverted is seen.
+        if !super::preview::enabled() && !self.object.as_ref().is_ok_and(gitops::reverts) {
+            self.run(change, description);
+
+            return;
+        }
+
+        let Ok(object) = self.object.as_ref() else {
+            return;
+        };
+
+        let client = self.client.clone();
+        let before = object.clone();
+        let dry_change = change.clone();
+
+        let preview = Preview::new(description.clone(), async move {
+            let after = dry_change.apply(&client, &before, true).await?;
+
+            Ok(vec![Outcome {
+                target: before.name_any(),
+                before: Some(before),
+                after: Some(after),
+            }])
+        });
+
+        self.preview = Some((preview, change, description));
+    }
+
+    fn run(&mut self, change: Change, description: String) {
+        let Ok(object) = self.object.as_ref() else {
+            return;
+        };
+
+        let client = self.client.clone();
+        let object = object.clone();
+
+        self.status = Status::Running(
+            description,
+            tokio::spawn(async move { change.apply(&client, &object, false).await }),
+        );
+    }
+
+    fn submit(&mut self) {
+        let Some((field, text)) = self.input.as_ref() else {
+            return;
+        };
+
+        let field = *field;
+        let content = text.value();
+
+        let Some((key, value)) = content.split_once('=') else {
+            self.status = Status::Invalid("expected key=value".to_string());
+
+            return;
+        };
+
+        if let Err(err) = field.validate(key, value) {
+            self.status = Status::Invalid(err.to_string());
+
+            return;
+        }
+
+        self.input = None;
+        self.apply(
+            Change::Set(field, key.to_string(), value.to_string()),
+            format!("set {} {key}", field.name()),
+        );
+    }
+
+    fn update(&mut self) {
+        let Status::Running(description, task) = &mut self.status else {
+            return;
+        };
+
+        if !task.is_finished() {
+            return;
+        }
+
+        let description = description.clone();
+        let result = futures::executor::block_on(async move { task.await? });
+
+        self.status = Status::Done(result.map(|object| {
+            self.object = Ok(object);
+
+            description
+        }));
+    }
+}
+
+impl Widget for Metadata {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        if let Some((preview, ..)) = self.preview.as_mut() {
+            match preview.dispatch(event, buffer, area)? {
+                Broadcast::Selected(_) => {
+                    if let Some((_, change, description)) = self.preview.take() {
+                        self.run(change, description);
+                    }
+                }
+                Broadcast::Exited => self.preview = None,
+                _ => {}
+            }
+
+            return Ok(Broadcast::Consumed);
+        }
+
+        // While editing, every key belongs to the input so that navigation doesn't
+        // change tabs out from under it.
+        if let Some((_, text)) = self.input.as_mut() {
+            if text.submit(key) {
+                self.submit();
+
+                return Ok(Broadcast::Consumed);
+            }
+
+            match text.dispatch(event, buffer, area)? {
+                Broadcast::Exited => {
+                    self.input = None;
+                    self.status = Status::Idle;
+                }
+                Broadcast::Clipboard(content) => return Ok(Broadcast::Clipboard(content)),
+                _ => {}
+            }
+
+            return Ok(Broadcast::Consumed);
+        }
+
+        let entries = self.entries();
+
+        match key {
+            Keypress::CursorUp | Keypress::Printable('k') => {
+                self.idx = self.idx.saturating_sub(1);
+            }
+            Keypress::CursorDown | Keypress::Printable('j') => {
+                self.idx = (self.idx + 1).min(entries.len().saturating_sub(1));
+            }
+            Keypress::Printable('a') => {
+                self.input = Some((Field::Label, new_input(Field::Label, &entries)));
+            }
+            Keypress::Printable('A') => {
+                self.input = Some((Field::Annotation, new_input(Field::Annotation, &entries)));
+            }
+            Keypress::Printable('d') => {
+                let Some((field, key, _)) = entries.get(self.idx) else {
+                    return Ok(Broadcast::Ignored);
+                };
+
+                self.apply(
+                    Change::Remove(*field, key.clone()),
+                    format!("removed {} {key}", field.name()),
+                );
+            }
+            _ => return Ok(Broadcast::Ignored),
+        }
+
+        Ok(Broadcast::Consumed)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.update();
+
+        let block = Block::default().borders(Borders::ALL);
+
+        if let Err(err) = self.object.as_ref() {
+            frame.render_widget(
+                Paragraph::new(format!("Unable to read metadata: {err}"))
+                    .style(Style::default().fg(tailwind::RED.c300))
+                    .block(block),
+                area,
+            );
+
+            return Ok(());
+        }
+
+        let entries = self.entries();
+        self.idx = self.idx.min(entries.len().saturating_sub(1));
+
+        let [list, editor, status] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(self.input.as_ref().map_or(0, |(_, text)| text.height())),
+            Constraint::Length(1),
+        ])
+        .areas(block.inner(area));
+
+        let lines: Vec<Line> = entries
+            .iter()
+            .enumerate()
+            .map(|(i, (field, key, value))| {
+                let line = Line::from(format!("{:<12}{key}={value}", field.name()));
+
+                if i == self.idx {
+                    line.style(Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    line
+                }
+            })
+            .collect();
+
+        frame.render_widget(
+            Paragraph::new(lines).scroll((
+                u16::try_from(
+                    self.idx
+                        .saturating_sub(list.height.saturating_sub(1).into()),
+                )
+                .unwrap_or_default(),
+                0,
+            )),
+            list,
+        );
+
+        if let Some((_, text)) = self.input.as_mut() {
+            text.draw(frame, editor)?;
+        }
+
+        let status_line = match &self.status {
+            Status::Idle => match &self.input {
+                Some((Field::Label, _)) => {
+                    Paragraph::new("enter: apply, tab: complete, esc: cancel")
+                }
+                Some((Field::Annotation, _)) => {
+                    Paragraph::new("ctrl-s: apply, enter: new line, tab: complete, esc: cancel")
+                }
+                None => Paragraph::new("a: add label, A: add annotation, d: remove selected"),
+            },
+            Status::Invalid(err) => {
+                Paragraph::new(err.as_str()).style(Style::default().fg(tailwind::RED.c300))
+            }
+            Status::Running(description, _) => Paragraph::new(format!("{description}...")),
+            Status::Done(Ok(description)) => Paragraph::new(description.as_str())
+                .style(Style::default().fg(tailwind::GREEN.c300)),
+            Status::Done(Err(err)) => Paragraph::new(format!("Rejected: {err}"))
+                .style(Style::default().fg(tailwind::RED.c300)),
+        };
+
+        frame.render_widget(status_line, status);
+        frame.render_widget(block, area);
+
+        if let Some((preview, ..)) = self.preview.as_mut() {
+            preview.draw(frame, area)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Annotation values are commonly multi-line, such as JSON. Existing keys are
+// completed so that they can be overwritten.
+fn new_input(field: Field, entries: &[(Field, String, String)]) -> input::Text {
+    let keys = entries
+        .iter()
+        .filter(|(f, ..)| *f == field)
+        .map(|(_, key, _)| format!("{key}="))
+        .collect();
+
+    input::Text::builder()
+        .title(format!("Add {} (key=value)", field.name()))
+        .multiline(matches!(field, Field::Annotation))
+        .history(field.name())
+        .complete(input::complete_words(keys))
+        .build()
+}
+
+impl Drop for Metadata {
+    fn drop(&mut self) {
+        if let Status::Running(_, task) = &self.status {
+            task.abort();
+        }
+    }
+}
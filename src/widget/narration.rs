@@ -0,0 +1,104 @@
+use itertools::Itertools;
+use ratatui::{buffer::Buffer, layout::Rect};
+
+use super::caps::Capabilities;
+use crate::dashboard::context::Context;
+
+/// What a frame looks like to someone who can't see it, for the linear output
+/// mode (`KTY_LINEAR`). Widgets fill this in as they draw, those drawn last
+/// are on top and win. The dashboard writes out what changed as plain lines
+/// instead of redrawing the screen, which works with screen readers and
+/// braille displays.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Narration {
+    /// The selected tab of the innermost tabs.
+    pub tab: Option<String>,
+    /// What is being looked at, such as the header of a table.
+    pub heading: Option<String>,
+    /// Where the cursor is, such as the selected row.
+    pub focus: Option<String>,
+}
+
+impl Narration {
+    /// Lines for what changed since `previous`. Unless `listening`, only a new
+    /// tab or heading is worth saying, so that background updates such as a
+    /// row's age ticking over aren't read out constantly.
+    pub fn changes(&self, previous: &Self, listening: bool) -> Vec<String> {
+        let moved = self.tab != previous.tab || self.heading != previous.heading;
+
+        if !listening && !moved {
+            return Vec::new();
+        }
+
+        let mut lines = Vec::new();
+
+        if self.tab != previous.tab {
+            lines.extend(self.tab.clone());
+        }
+
+        if self.heading != previous.heading {
+            lines.extend(self.heading.clone());
+        }
+
+        if moved || self.focus != previous.focus {
+            lines.extend(
+                self.focus
+                    .iter()
+                    .flat_map(|focus| focus.lines())
+                    .map(String::from),
+            );
+        }
+
+        lines
+    }
+}
+
+/// Whether the session is in linear mode.
+pub fn enabled() -> bool {
+    Capabilities::current().linear
+}
+
+/// Set the tab being shown, see [`Narration::tab`].
+pub fn tab(name: impl FnOnce() -> String) {
+    if enabled() {
+        let name = name();
+
+        Context::with(|context| context.narration.lock().unwrap().tab = Some(name));
+    }
+}
+
+/// Set what has focus, see [`Narration::focus`]. Both are only built in linear
+/// mode.
+pub fn focus(heading: impl FnOnce() -> Option<String>, focus: impl FnOnce() -> String) {
+    if enabled() {
+        let (heading, focus) = (heading(), focus());
+
+        Context::with(|context| {
+            let mut narration = context.narration.lock().unwrap();
+            narration.heading = heading;
+            narration.focus = Some(focus);
+        });
+    }
+}
+
+/// Everything widgets set while drawing the last frame, starting over for the
+/// next one.
+pub(crate) fn take() -> Narration {
+    Context::with(|context| std::mem::take(&mut *context.narration.lock().unwrap()))
+}
+
+/// The text of a line in `buffer` with the padding between columns collapsed,
+/// borders and symbols are dropped.
+pub fn line(buffer: &Buffer, area: Rect, y: u16) -> String {
+    (area.left()..area.right())
+        .filter_map(|x| buffer.cell((x, y)))
+        .map(|cell| match cell.symbol() {
+            symbol if symbol.chars().all(|c| c.is_alphanumeric() || c.is_ascii()) => symbol,
+            _ => " ",
+        })
+        .collect::<String>()
+        .split("  ")
+        .map(str::trim)
+        .filter(|column| !column.is_empty())
+        .join(", ")
+}
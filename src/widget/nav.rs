@@ -1,5 +1,7 @@
 use ratatui::layout::Rect;
+use strum::IntoEnumIterator;
 
+use super::keys::Action;
 use crate::events::Keypress;
 
 pub enum Movement {
@@ -12,25 +14,96 @@ pub enum Movement {
 // avoid overflow.
 #[allow(clippy::cast_possible_wrap)]
 pub fn move_cursor(key: &Keypress, area: Rect) -> Option<Movement> {
-    match key {
-        Keypress::CursorLeft | Keypress::Printable('h') => Some(Movement::X(-1)),
-        Keypress::CursorRight | Keypress::Printable('l') => Some(Movement::X(1)),
-        Keypress::CursorUp | Keypress::Printable('k') => Some(Movement::Y(-1)),
-        Keypress::CursorDown | Keypress::Printable('j') => Some(Movement::Y(1)),
-        Keypress::Printable('H') => Some(Movement::Y(-i16::MAX)),
-        Keypress::Printable('L') => Some(Movement::Y(i16::MAX)),
-        Keypress::Printable(' ' | 'f') | Keypress::Control('f') => {
-            Some(Movement::Y(area.height as i16))
-        }
-        Keypress::Printable('b') | Keypress::Control('b') => {
-            Some(Movement::Y(-(area.height as i16)))
-        }
-        Keypress::Printable('^') | Keypress::Control('a') => Some(Movement::X(-i16::MAX)),
-        Keypress::Printable('$') | Keypress::Control('e') => Some(Movement::X(i16::MAX)),
+    let page = area.height as i16;
+
+    let action = Action::iter().find(|action| action.matches(key))?;
+
+    match action {
+        Action::Left => Some(Movement::X(-1)),
+        Action::Right => Some(Movement::X(1)),
+        Action::Up => Some(Movement::Y(-1)),
+        Action::Down => Some(Movement::Y(1)),
+        Action::Top => Some(Movement::Y(-i16::MAX)),
+        Action::Bottom => Some(Movement::Y(i16::MAX)),
+        Action::PageDown => Some(Movement::Y(page)),
+        Action::PageUp => Some(Movement::Y(-page)),
+        Action::LineStart => Some(Movement::X(-i16::MAX)),
+        Action::LineEnd => Some(Movement::X(i16::MAX)),
         _ => None,
     }
 }
 
+/// Result of passing a key to [`Nav`].
+pub enum Motion {
+    Move(Movement),
+    // Part of a longer sequence, such as a count or the first `g` of `gg`.
+    Pending,
+    Ignored,
+}
+
+/// Vim style navigation on top of [`move_cursor`]. Supports counts (`10j`),
+/// `gg`/`G` to jump to the top and bottom and `ctrl-d`/`ctrl-u` to move by half
+/// a page. State is kept between keys, so each widget needs its own.
+#[derive(Default)]
+pub struct Nav {
+    count: Option<u16>,
+    g: bool,
+}
+
+impl Nav {
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn motion(&mut self, key: &Keypress, area: Rect) -> Motion {
+        let g = std::mem::take(&mut self.g);
+
+        if let Keypress::Printable(c @ '0'..='9') = key {
+            // A leading zero isn't a count.
+            if *c != '0' || self.count.is_some() {
+                let digit = c
+                    .to_digit(10)
+                    .and_then(|d| u16::try_from(d).ok())
+                    .unwrap_or_default();
+
+                self.count = Some(
+                    self.count
+                        .unwrap_or_default()
+                        .saturating_mul(10)
+                        .saturating_add(digit),
+                );
+
+                return Motion::Pending;
+            }
+        }
+
+        let movement = match key {
+            Keypress::Printable('g') if !g => {
+                self.g = true;
+
+                return Motion::Pending;
+            }
+            Keypress::Printable('g') => Movement::Y(-i16::MAX),
+            // `ctrl-d` is also an exit key, so only `Nav` moves with it.
+            key if Action::HalfPageDown.matches(key) => Movement::Y((area.height / 2) as i16),
+            key if Action::HalfPageUp.matches(key) => Movement::Y(-((area.height / 2) as i16)),
+            key => {
+                let Some(movement) = move_cursor(key, area) else {
+                    self.count = None;
+
+                    return Motion::Ignored;
+                };
+
+                movement
+            }
+        };
+
+        let count = i16::try_from(self.count.take().unwrap_or(1)).unwrap_or(i16::MAX);
+
+        Motion::Move(match movement {
+            Movement::X(x) => Movement::X(x.saturating_mul(count)),
+            Movement::Y(y) => Movement::Y(y.saturating_mul(count)),
+        })
+    }
+}
+
 /// Add to match key {} to handle exiting the widget.
 #[macro_export]
 macro_rules! exit_keys {
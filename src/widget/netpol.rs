@@ -0,0 +1,227 @@
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use eyre::{eyre, Result};
+use k8s_openapi::api::networking::v1::NetworkPolicy;
+use kube::ResourceExt;
+use ratatui::{buffer::Buffer, layout::Rect, Frame};
+use tokio::sync::oneshot;
+
+use super::{
+    events::Events,
+    filter::FilterBuilder,
+    loading::Loading,
+    metadata::Metadata,
+    nested, propagate, table,
+    tabs::{Tab, TabbedView},
+    target::Targets,
+    view::{Element, View},
+    yaml::Yaml,
+    yank::Yank,
+    Widget, WIDGET_VIEWS,
+};
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    history::History,
+    resources::{store::Store, target::Source},
+};
+
+pub struct List {
+    view: View,
+    is_ready: oneshot::Receiver<()>,
+}
+
+#[bon::bon]
+impl List {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(skip(client, history), fields(activity = "netpol.list"))]
+    #[builder]
+    pub fn new(client: kube::Client, history: Arc<dyn History>, filter: Option<String>) -> Self {
+        WIDGET_VIEWS.netpol.list.inc();
+
+        let (policies, is_ready) = Store::<NetworkPolicy>::new(client.clone());
+        let table = table::Filtered::builder()
+            .table(
+                table::Table::builder()
+                    .items(policies.clone())
+                    .filter(Rc::new(RefCell::new(filter)))
+                    .build(),
+            )
+            .constructor(Detail::from_store(client, policies.clone(), history))
+            .fields(FilterBuilder::from_store(policies))
+            .build();
+
+        let widgets = vec![
+            table.boxed().into(),
+            Element::builder()
+                .widget(Loading.boxed())
+                .ignore(true)
+                .build(),
+        ];
+
+        Self {
+            view: View::builder().widgets(widgets).build(),
+            is_ready,
+        }
+    }
+
+    pub fn tab(
+        name: String,
+        client: kube::Client,
+        history: Arc<dyn History>,
+        terminal: bool,
+    ) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Element::builder()
+                    .widget(
+                        Self::builder()
+                            .client(client.clone())
+                            .history(history.clone())
+                            .build()
+                            .boxed(),
+                    )
+                    .terminal(terminal)
+                    .build()
+            }))
+            .build()
+    }
+}
+
+impl Widget for List {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if let Ok(()) = self.is_ready.try_recv() {
+            self.view.pop();
+        }
+
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        self.view.zindex()
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        let title = self.view.title();
+
+        if title.is_empty() {
+            vec!["netpols".to_string()]
+        } else {
+            title
+        }
+    }
+
+    fn filter(&self) -> Option<String> {
+        self.view.filter()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
+
+pub struct Detail {
+    view: TabbedView,
+}
+
+#[bon::bon]
+impl Detail {
+    #[builder]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn new(
+        client: kube::Client,
+        policy: Arc<NetworkPolicy>,
+        history: Arc<dyn History>,
+    ) -> Self {
+        WIDGET_VIEWS.netpol.detail.inc();
+
+        let tabs = vec![
+            Yaml::tab("YAML".to_string(), policy.clone()),
+            Targets::tab(
+                "Pods".to_string(),
+                client.clone(),
+                history,
+                Source::Policy(policy.clone()),
+            ),
+            Events::tab("Events".to_string(), client.clone(), policy.clone()),
+            Metadata::tab("Metadata".to_string(), client, policy.clone()),
+        ];
+
+        let view = TabbedView::builder()
+            .tabs(tabs)
+            .yank(Yank::from_obj(policy.as_ref()))
+            .title(vec![
+                "netpols".to_string(),
+                policy.namespace().unwrap_or_default(),
+                policy.name_any(),
+            ])
+            .build();
+
+        Self { view }
+    }
+
+    pub fn from_store(
+        client: kube::Client,
+        store: Arc<Store<NetworkPolicy>>,
+        history: Arc<dyn History>,
+    ) -> table::DetailFn {
+        Box::new(move |idx, filter| {
+            let policy = store
+                .get(idx, filter)
+                .ok_or_else(|| eyre!("network policy not found"))?;
+
+            Ok(Detail::builder()
+                .client(client.clone())
+                .policy(policy)
+                .history(history.clone())
+                .build()
+                .boxed())
+        })
+    }
+}
+
+impl Widget for Detail {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        1
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        self.view.title()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
@@ -1,22 +1,35 @@
-use std::sync::Arc;
+pub mod conditions;
+pub mod shell;
+
+use std::{cell::RefCell, rc::Rc, sync::Arc};
 
 use eyre::{eyre, Result};
-use k8s_openapi::api::core::v1::Node;
+use k8s_openapi::api::core::v1::{Node, Pod};
 use kube::ResourceExt;
 use ratatui::{buffer::Buffer, layout::Rect, Frame};
 use tokio::sync::oneshot;
 
 use super::{
+    actions::Actions,
+    allocation::Allocation,
+    bulk::Bulk,
+    filter::FilterBuilder,
     loading::Loading,
-    propagate, table,
+    metadata::Metadata,
+    nested,
+    pdb::Evictions,
+    pod, propagate, table,
     tabs::{Tab, TabbedView},
     view::{Element, View},
     yaml::Yaml,
+    yank::Yank,
     Widget, WIDGET_VIEWS,
 };
 use crate::{
     events::{Broadcast, Event, Keypress},
-    resources::store::Store,
+    history::History,
+    resources::{action, bulk::Operation, node::Nodes, store::Store},
+    widget::node::{conditions::Conditions, shell::Shell},
 };
 
 pub struct List {
@@ -27,15 +40,33 @@ pub struct List {
 #[bon::bon]
 impl List {
     #[allow(clippy::blocks_in_conditions)]
-    #[tracing::instrument(skip(client), fields(activity = "node.list"))]
+    #[tracing::instrument(skip(client, history), fields(activity = "node.list"))]
     #[builder]
-    pub fn new(client: kube::Client) -> Self {
+    pub fn new(client: kube::Client, history: Arc<dyn History>, filter: Option<String>) -> Self {
         WIDGET_VIEWS.node.list.inc();
 
         let (nodes, is_ready) = Store::<Node>::new(client.clone());
+        // Not waited on, pods are only needed to count up extended resources
+        // such as GPUs.
+        let (pods, _) = Store::<Pod>::new(client.clone());
+
         let table = table::Filtered::builder()
-            .table(table::Table::builder().items(nodes.clone()).build())
-            .constructor(Detail::from_store(client, nodes))
+            .table(
+                table::Table::builder()
+                    .items(Nodes {
+                        nodes: nodes.clone(),
+                        pods,
+                    })
+                    .filter(Rc::new(RefCell::new(filter)))
+                    .build(),
+            )
+            .constructor(Detail::from_store(client.clone(), nodes.clone(), history))
+            .bulk(Bulk::from_store(
+                client,
+                nodes.clone(),
+                &[Operation::Cordon, Operation::Uncordon, Operation::Label],
+            ))
+            .fields(FilterBuilder::from_store(nodes))
             .build();
 
         let widgets = vec![
@@ -52,12 +83,23 @@ impl List {
         }
     }
 
-    pub fn tab(name: String, client: kube::Client, terminal: bool) -> Tab {
+    pub fn tab(
+        name: String,
+        client: kube::Client,
+        history: Arc<dyn History>,
+        terminal: bool,
+    ) -> Tab {
         Tab::builder()
             .name(name)
             .constructor(Box::new(move || {
                 Element::builder()
-                    .widget(Self::builder().client(client.clone()).build().boxed())
+                    .widget(
+                        Self::builder()
+                            .client(client.clone())
+                            .history(history.clone())
+                            .build()
+                            .boxed(),
+                    )
                     .terminal(terminal)
                     .build()
             }))
@@ -87,6 +129,28 @@ impl Widget for List {
     fn zindex(&self) -> u16 {
         self.view.zindex()
     }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        let title = self.view.title();
+
+        if title.is_empty() {
+            vec!["nodes".to_string()]
+        } else {
+            title
+        }
+    }
+
+    fn filter(&self) -> Option<String> {
+        self.view.filter()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
 }
 
 pub struct Detail {
@@ -96,19 +160,43 @@ pub struct Detail {
 #[bon::bon]
 impl Detail {
     #[builder]
-    #[allow(unused_variables, clippy::needless_pass_by_value)]
-    pub fn new(client: kube::Client, node: Arc<Node>) -> Self {
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn new(client: kube::Client, node: Arc<Node>, history: Arc<dyn History>) -> Self {
         WIDGET_VIEWS.node.detail.inc();
 
+        let mut tabs = vec![
+            Yaml::tab("YAML".to_string(), node.clone()),
+            pod::List::node_tab(
+                "Pods".to_string(),
+                client.clone(),
+                history.clone(),
+                node.clone(),
+            ),
+            Allocation::node_tab("Resources".to_string(), client.clone(), node.clone()),
+            Conditions::tab("Conditions".to_string(), client.clone(), node.clone()),
+            Metadata::tab("Metadata".to_string(), client.clone(), node.clone()),
+            Shell::tab("Shell".to_string(), client.clone(), node.clone()),
+            Evictions::node_tab("Drain".to_string(), client.clone(), history, node.clone()),
+        ];
+
+        if node.annotations().contains_key(action::ANNOTATION) {
+            tabs.push(Actions::tab("Actions".to_string(), client, node.clone()));
+        }
+
         let view = TabbedView::builder()
-            .tabs(vec![Yaml::tab("YAML".to_string(), node.clone())])
+            .tabs(tabs)
+            .yank(Yank::from_obj(node.as_ref()))
             .title(vec!["nodes".to_string(), node.name_any()])
             .build();
 
         Self { view }
     }
 
-    pub fn from_store(client: kube::Client, store: Arc<Store<Node>>) -> table::DetailFn {
+    pub fn from_store(
+        client: kube::Client,
+        store: Arc<Store<Node>>,
+        history: Arc<dyn History>,
+    ) -> table::DetailFn {
         Box::new(move |idx, filter| {
             let node = store
                 .get(idx, filter)
@@ -117,6 +205,7 @@ impl Detail {
             Ok(Detail::builder()
                 .client(client.clone())
                 .node(node)
+                .history(history.clone())
                 .build()
                 .boxed())
         })
@@ -141,4 +230,16 @@ impl Widget for Detail {
     fn zindex(&self) -> u16 {
         1
     }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        self.view.title()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
 }
@@ -0,0 +1,291 @@
+use std::sync::Arc;
+
+use eyre::Result;
+use k8s_openapi::api::core::v1::{Event as KubeEvent, Node, Pod};
+use kube::{runtime::reflector::ObjectRef, ResourceExt};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{palette::tailwind, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::{
+    events::{Broadcast, Event},
+    resources::{
+        node::{is_active, taint_spec, tolerates, ConditionExt, NodeExt, Reading},
+        store::Store,
+    },
+    widget::{
+        clock::Clock,
+        events::last_seen,
+        nav::{move_cursor, Movement},
+        tabs::Tab,
+        Widget, WIDGET_VIEWS_VEC,
+    },
+};
+
+// Reasons the kubelet and node controller use for events when a condition
+// flips, along with how to read them.
+static TRANSITIONS: &[(&str, &str)] = &[
+    ("NodeReady", "Became ready"),
+    ("NodeNotReady", "Stopped being ready"),
+    ("NodeHasInsufficientMemory", "Ran low on memory"),
+    ("NodeHasSufficientMemory", "Has enough memory"),
+    ("NodeHasDiskPressure", "Ran low on disk space"),
+    ("NodeHasNoDiskPressure", "Has enough disk space"),
+    ("NodeHasInsufficientPID", "Ran low on process IDs"),
+    ("NodeHasSufficientPID", "Has enough process IDs"),
+    ("NodeNotSchedulable", "Cordoned"),
+    ("NodeSchedulable", "Uncordoned"),
+];
+
+/// What the node's conditions currently are and when they last changed, along
+/// with its taints and how many of the pods on it tolerate each one. The node
+/// is watched, so this stays current while open.
+pub struct Conditions {
+    obj: ObjectRef<Node>,
+    node: Arc<Node>,
+    store: Arc<Store<Node>>,
+    pods: Arc<Store<Pod>>,
+    events: Arc<Store<KubeEvent>>,
+    offset: usize,
+}
+
+impl Conditions {
+    pub fn new(client: kube::Client, node: Arc<Node>) -> Self {
+        WIDGET_VIEWS_VEC
+            .with_label_values(&["node", "conditions"])
+            .inc();
+
+        let (store, _) = Store::<Node>::new(client.clone());
+        let (pods, _) = Store::<Pod>::scoped(
+            client.clone(),
+            &format!("spec.nodeName={}", node.name_any()),
+        );
+        // The kubelet reports events against the node's name instead of its uid.
+        let (events, _) = Store::<KubeEvent>::scoped(
+            client,
+            &format!(
+                "involvedObject.kind=Node,involvedObject.name={}",
+                node.name_any()
+            ),
+        );
+
+        Self {
+            obj: ObjectRef::from_obj(node.as_ref()),
+            node,
+            store,
+            pods,
+            events,
+            offset: 0,
+        }
+    }
+
+    pub fn tab(name: String, client: kube::Client, node: Arc<Node>) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Self::new(client.clone(), node.clone()).boxed().into()
+            }))
+            .build()
+    }
+
+    fn conditions(node: &Node, clock: Clock) -> Vec<Line<'static>> {
+        let conditions = node.conditions();
+
+        if conditions.is_empty() {
+            return vec![Line::from("  No conditions have been reported")];
+        }
+
+        conditions
+            .iter()
+            .flat_map(|condition| {
+                let style = match condition.reading() {
+                    Reading::Fine => Style::default().fg(tailwind::GREEN.c300),
+                    Reading::Problem => Style::default().fg(tailwind::RED.c300),
+                    Reading::Unknown => Style::default().fg(tailwind::YELLOW.c300),
+                };
+
+                let since = condition
+                    .last_transition_time
+                    .as_ref()
+                    .map(|t| format!("since {}", clock.format(t.0)))
+                    .unwrap_or_default();
+
+                let mut lines = vec![Line::from(vec![
+                    Span::from(format!("  {:<20}", condition.type_)),
+                    Span::styled(format!("{:<45}", condition.describe()), style),
+                    Span::from(since),
+                ])];
+
+                let detail = [condition.reason.as_deref(), condition.message.as_deref()]
+                    .into_iter()
+                    .flatten()
+                    .filter(|s| !s.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(": ");
+
+                if !detail.is_empty() {
+                    lines.push(Line::from(format!("  {:<20}{detail}", "")).fg(tailwind::GRAY.c400));
+                }
+
+                if let Some(heartbeat) = &condition.last_heartbeat_time {
+                    lines.push(
+                        Line::from(format!(
+                            "  {:<20}last heard from {}",
+                            "",
+                            clock.format(heartbeat.0)
+                        ))
+                        .fg(tailwind::GRAY.c500),
+                    );
+                }
+
+                lines
+            })
+            .collect()
+    }
+
+    fn transitions(&self, clock: Clock) -> Vec<Line<'static>> {
+        let mut events: Vec<_> = self
+            .events
+            .items(None)
+            .into_iter()
+            .filter_map(|ev| {
+                let reason = ev.reason.as_deref()?;
+                let (_, text) = TRANSITIONS.iter().find(|(r, _)| *r == reason)?;
+
+                Some((
+                    last_seen(&ev)?,
+                    *text,
+                    ev.type_.as_deref() == Some("Warning"),
+                ))
+            })
+            .collect();
+
+        if events.is_empty() {
+            return vec![Line::from(
+                "  None seen, events are only kept for a short while",
+            )];
+        }
+
+        events.sort_by_key(|(at, ..)| std::cmp::Reverse(*at));
+
+        events
+            .into_iter()
+            .map(|(at, text, warning)| {
+                let style = if warning {
+                    Style::default().fg(tailwind::RED.c300)
+                } else {
+                    Style::default()
+                };
+
+                Line::from(vec![
+                    Span::from(format!(
+                        "  {:<width$}  ",
+                        clock.format(at),
+                        width = usize::from(clock.format.width())
+                    )),
+                    Span::styled(text, style),
+                ])
+            })
+            .collect()
+    }
+
+    fn taints(&self, node: &Node) -> Vec<Line<'static>> {
+        let taints = node.taints();
+
+        if taints.is_empty() {
+            return vec![Line::from("  None, any pod can be scheduled here")];
+        }
+
+        let pods: Vec<_> = self
+            .pods
+            .items(None)
+            .into_iter()
+            .filter(|pod| is_active(pod))
+            .collect();
+
+        taints
+            .iter()
+            .map(|taint| {
+                let effect = match taint.effect.as_str() {
+                    "NoSchedule" => "new pods need a toleration",
+                    "PreferNoSchedule" => "avoided by pods without a toleration",
+                    "NoExecute" => "pods without a toleration are evicted",
+                    _ => "",
+                };
+
+                let tolerated = pods
+                    .iter()
+                    .filter(|pod| {
+                        pod.spec
+                            .as_ref()
+                            .and_then(|spec| spec.tolerations.as_ref())
+                            .is_some_and(|tolerations| {
+                                tolerations.iter().any(|t| tolerates(t, taint))
+                            })
+                    })
+                    .count();
+
+                Line::from(vec![
+                    Span::from(format!("  {:<45}", taint_spec(taint))),
+                    Span::from(format!("{effect:<40}")),
+                    Span::from(format!("{tolerated} of {} pods tolerate", pods.len()))
+                        .fg(tailwind::GRAY.c400),
+                ])
+            })
+            .collect()
+    }
+}
+
+impl Widget for Conditions {
+    fn dispatch(&mut self, event: &Event, _: &Buffer, area: Rect) -> Result<Broadcast> {
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        if let Some(Movement::Y(y)) = move_cursor(key, area) {
+            self.offset = self.offset.saturating_add_signed(y.into());
+
+            return Ok(Broadcast::Consumed);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let block = Block::default().borders(Borders::ALL);
+        let clock = Clock::current();
+
+        // Falls back to what the detail view was opened with until the store has
+        // caught up.
+        let node = self
+            .store
+            .find(&self.obj)
+            .unwrap_or_else(|| self.node.clone());
+
+        let heading = |text: &'static str| Line::from(text).bold();
+
+        let lines: Vec<Line> = std::iter::once(heading("Conditions"))
+            .chain(Self::conditions(&node, clock))
+            .chain([Line::default(), heading("Transitions")])
+            .chain(self.transitions(clock))
+            .chain([Line::default(), heading("Taints")])
+            .chain(self.taints(&node))
+            .collect();
+
+        self.offset = self.offset.min(lines.len().saturating_sub(1));
+
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .scroll((u16::try_from(self.offset).unwrap_or(u16::MAX), 0)),
+            area,
+        );
+
+        Ok(())
+    }
+}
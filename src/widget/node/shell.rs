@@ -0,0 +1,248 @@
+use std::{pin::Pin, sync::Arc};
+
+use chrono::Utc;
+use eyre::Result;
+use k8s_openapi::api::core::v1::{self, Node, Pod};
+use kube::{
+    api::{Api, DeleteParams, PostParams},
+    ResourceExt,
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{palette::tailwind, Style},
+    text::Line,
+    widgets::{Paragraph, Wrap},
+    Frame,
+};
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt},
+    sync::mpsc::UnboundedReceiver,
+};
+use tracing::Instrument;
+
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    resources::{
+        container::Container,
+        node::shell::{self, NodeShellSettings},
+        policy::{Policy, Verb},
+    },
+    widget::{pod::shell::ExecBuilder, tabs::Tab, Raw, Widget, WIDGET_VIEWS},
+};
+
+/// A shell on the node itself. A privileged pod that shares the host's
+/// namespaces is created on the node for as long as the shell is open. Where
+/// the pod goes comes from [`NodeShellSettings`].
+pub struct Shell {
+    client: kube::Client,
+    node: Arc<Node>,
+    settings: NodeShellSettings,
+    namespace: String,
+    error: Option<String>,
+}
+
+impl Shell {
+    pub fn new(client: kube::Client, node: Arc<Node>) -> Self {
+        let settings = NodeShellSettings::current();
+        let namespace = settings.namespace(client.default_namespace());
+
+        Self {
+            client,
+            node,
+            settings,
+            namespace,
+            error: None,
+        }
+    }
+
+    // Creating the pod is as much a part of this as exec, policies that take
+    // either away or hide the namespace keep users from opening a shell.
+    fn denied(&self) -> Option<String> {
+        if !Verb::Create.allowed() {
+            return Some("creating pods isn't allowed for this session".to_string());
+        }
+
+        if !Policy::visible(Some(&self.namespace)) {
+            return Some(format!("{} isn't visible to this session", self.namespace));
+        }
+
+        None
+    }
+
+    pub fn tab(name: String, client: kube::Client, node: Arc<Node>) -> Tab {
+        Tab::builder()
+            .name(name)
+            .verb(Verb::Exec)
+            .constructor(Box::new(move || {
+                Self::new(client.clone(), node.clone()).boxed().into()
+            }))
+            .build()
+    }
+}
+
+impl Widget for Shell {
+    fn dispatch(&mut self, event: &Event, _: &Buffer, _: Rect) -> Result<Broadcast> {
+        if matches!(event.key(), Some(Keypress::Enter)) {
+            self.error = self.denied();
+
+            if self.error.is_some() {
+                return Ok(Broadcast::Consumed);
+            }
+
+            return Ok(Broadcast::Raw(Box::new(NodeShell {
+                client: self.client.clone(),
+                node: self.node.name_any(),
+                settings: self.settings.clone(),
+                namespace: self.namespace.clone(),
+            })));
+        }
+
+        // Clusters commonly reject privileged pods, that is shown here instead of
+        // ending the session.
+        if let Event::Finished(Err(err)) = event {
+            self.error = Some(err.to_string());
+
+            return Ok(Broadcast::Consumed);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let mut lines = vec![
+            Line::from(format!(
+                "Press enter to open a shell on {}.",
+                self.node.name_any()
+            )),
+            Line::default(),
+            Line::from(format!(
+                "A privileged pod that shares the node's namespaces is created in {} and deleted \
+                 when the shell exits.",
+                self.namespace
+            )),
+        ];
+
+        if let Some(err) = &self.error {
+            lines.push(Line::default());
+            lines.push(
+                Line::from(format!("Unable to open a shell: {err}"))
+                    .style(Style::default().fg(tailwind::RED.c300)),
+            );
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let [area] = Layout::vertical([Constraint::Length(lines.len() as u16 + 2)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Max(80)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+
+        Ok(())
+    }
+}
+
+// Deletes the pod however the shell ends. The shell exiting waits for the
+// delete, anything else such as the session going away falls back to deleting
+// in the background. Should that never run, the pod's deadline stops it and the
+// server's reaper removes it, see `shell::reaper`.
+struct Cleanup {
+    api: Api<Pod>,
+    name: Option<String>,
+}
+
+impl Cleanup {
+    async fn delete(&mut self) {
+        let Some(name) = self.name.take() else {
+            return;
+        };
+
+        if let Err(err) = self.api.delete(&name, &DeleteParams::default()).await {
+            tracing::warn!("unable to delete node shell {name}: {err}");
+        }
+    }
+}
+
+impl Drop for Cleanup {
+    fn drop(&mut self) {
+        let Some(name) = self.name.take() else {
+            return;
+        };
+        let api = self.api.clone();
+
+        tokio::spawn(
+            async move {
+                if let Err(err) = api.delete(&name, &DeleteParams::default()).await {
+                    tracing::warn!("unable to delete node shell {name}: {err}");
+                }
+            }
+            .in_current_span(),
+        );
+    }
+}
+
+struct NodeShell {
+    client: kube::Client,
+    node: String,
+    settings: NodeShellSettings,
+    namespace: String,
+}
+
+#[async_trait::async_trait]
+impl Raw for NodeShell {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(skip(self, stdin, stdout), fields(activity = "node.shell"))]
+    async fn start(
+        &mut self,
+        stdin: &mut UnboundedReceiver<Event>,
+        mut stdout: Pin<Box<dyn AsyncWrite + Send + Unpin>>,
+    ) -> Result<()> {
+        WIDGET_VIEWS.node.exec.inc();
+
+        let api = Api::<Pod>::namespaced(self.client.clone(), &self.namespace);
+
+        let pod = api
+            .create(
+                &PostParams::default(),
+                &self.settings.pod(&self.node, &self.namespace),
+            )
+            .await?;
+        let name = pod.name_any();
+        let mut cleanup = Cleanup {
+            api: api.clone(),
+            name: Some(name.clone()),
+        };
+
+        let result = async {
+            stdout
+                .write_all(format!("Starting {name} on {}...\r\n", self.node).as_bytes())
+                .await?;
+            stdout.flush().await?;
+
+            let pod = shell::wait(&api, &name).await?;
+            let spec: v1::Container = pod
+                .spec
+                .as_ref()
+                .and_then(|spec| spec.containers.first().cloned())
+                .unwrap_or_default();
+
+            ExecBuilder::default()
+                .start(Utc::now())
+                .client(self.client.clone())
+                .pod(Arc::new(pod.clone()))
+                .container(Container::new(pod, spec))
+                .cmd(shell::COMMAND.to_string())
+                .build()?
+                .start(stdin, stdout)
+                .await
+        }
+        .await;
+
+        cleanup.delete().await;
+
+        result
+    }
+}
@@ -0,0 +1,286 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    sync::Arc,
+};
+
+use eyre::{eyre, Result};
+use kube::{Resource, ResourceExt};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    widgets::Paragraph,
+    Frame,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::oneshot;
+
+use super::{
+    bulk::{Bulk, Target},
+    events::Events,
+    loading::Loading,
+    nested, propagate, table,
+    tabs::{Tab, TabbedView},
+    view::{Element, View},
+    yaml::Yaml,
+    yank::Yank,
+    Widget, WIDGET_VIEWS,
+};
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    resources::{
+        bulk::Operation,
+        orphan::{Orphan, Orphans},
+        store::Store,
+    },
+};
+
+/// Housekeeping, everything that has been left behind in the cluster. Marked
+/// rows can be deleted all at once.
+pub struct List {
+    days: Rc<Cell<i64>>,
+
+    view: View,
+    is_ready: Vec<oneshot::Receiver<()>>,
+    ready: bool,
+}
+
+#[bon::bon]
+impl List {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(skip(client), fields(activity = "orphan.list"))]
+    #[builder]
+    pub fn new(client: kube::Client, filter: Option<String>) -> Self {
+        WIDGET_VIEWS.orphan.list.inc();
+
+        let (replicasets, replicasets_ready) = Store::new(client.clone());
+        let (pods, pods_ready) = Store::new(client.clone());
+        let (jobs, jobs_ready) = Store::new(client.clone());
+
+        let orphans = Orphans::new(replicasets, pods, jobs);
+        let days = orphans.days.clone();
+
+        let table = table::Filtered::builder()
+            .table(
+                table::Table::builder()
+                    .items(orphans.clone())
+                    .filter(Rc::new(RefCell::new(filter)))
+                    .build(),
+            )
+            .constructor(Detail::from_orphans(client.clone(), orphans.clone()))
+            .bulk(bulk(client, orphans))
+            .build();
+
+        let widgets = vec![
+            table.boxed().into(),
+            Element::builder()
+                .widget(Loading.boxed())
+                .ignore(true)
+                .build(),
+        ];
+
+        Self {
+            days,
+
+            view: View::builder().widgets(widgets).build(),
+            is_ready: vec![replicasets_ready, pods_ready, jobs_ready],
+            ready: false,
+        }
+    }
+
+    pub fn tab(name: String, client: kube::Client, terminal: bool) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Element::builder()
+                    .widget(Self::builder().client(client.clone()).build().boxed())
+                    .terminal(terminal)
+                    .build()
+            }))
+            .build()
+    }
+}
+
+// Everything marked is deleted together, no matter what kind it is.
+fn bulk(client: kube::Client, orphans: Orphans) -> table::BulkFn {
+    Box::new(move |marked| {
+        let targets: Vec<_> = table::Items::items(&orphans, None)
+            .into_iter()
+            .filter(|orphan| orphan.uid().is_some_and(|uid| marked.contains(&uid)))
+            .map(|orphan| {
+                Target::new(
+                    orphan.dynamic_api(client.clone()),
+                    orphan.namespace(),
+                    orphan.name(),
+                )
+            })
+            .collect();
+
+        if targets.is_empty() {
+            return Err(eyre!("marked resources no longer exist"));
+        }
+
+        WIDGET_VIEWS.orphan.bulk.inc();
+
+        Ok(Bulk::with_targets("orphans".to_string(), targets, &[Operation::Delete]).boxed())
+    })
+}
+
+impl Widget for List {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        // Only from the table itself, not while something is open on top of it.
+        match event.key() {
+            Some(Keypress::Escape) => return Ok(Broadcast::Exited),
+            Some(Keypress::Printable('+')) if self.view.zindex() == 0 => {
+                self.days.set(self.days.get() + 1);
+
+                return Ok(Broadcast::Consumed);
+            }
+            Some(Keypress::Printable('-')) if self.view.zindex() == 0 => {
+                self.days.set((self.days.get() - 1).max(0));
+
+                return Ok(Broadcast::Consumed);
+            }
+            _ => {}
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.is_ready.retain_mut(|rx| rx.try_recv().is_err());
+
+        // The loading indicator is removed once every store is ready.
+        if self.is_ready.is_empty() && !self.ready {
+            self.view.pop();
+            self.ready = true;
+        }
+
+        let [table, help] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+
+        frame.render_widget(
+            Paragraph::new(format!(
+                "Completed jobs older than {} days, +/- to change. x to mark, enter to delete.",
+                self.days.get()
+            )),
+            help,
+        );
+
+        self.view.draw(frame, table)
+    }
+
+    fn zindex(&self) -> u16 {
+        self.view.zindex()
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        let title = self.view.title();
+
+        if title.is_empty() {
+            vec!["housekeeping".to_string()]
+        } else {
+            title
+        }
+    }
+
+    fn filter(&self) -> Option<String> {
+        self.view.filter()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
+
+pub struct Detail {
+    view: TabbedView,
+}
+
+impl Detail {
+    fn new<K>(client: kube::Client, resource: Arc<K>) -> Self
+    where
+        K: Resource<DynamicType = ()>
+            + Serialize
+            + Clone
+            + std::fmt::Debug
+            + Send
+            + Sync
+            + DeserializeOwned
+            + 'static,
+    {
+        WIDGET_VIEWS.orphan.detail.inc();
+
+        let title = vec![
+            K::plural(&()).to_string(),
+            resource.namespace().unwrap_or_default(),
+            resource.name_any(),
+        ];
+
+        let view = TabbedView::builder()
+            .tabs(vec![
+                Yaml::tab("YAML".to_string(), resource.clone()),
+                Events::tab("Events".to_string(), client, resource.clone()),
+            ])
+            .yank(Yank::from_obj(resource.as_ref()))
+            .title(title)
+            .build();
+
+        Self { view }
+    }
+
+    pub fn from_orphans(client: kube::Client, orphans: Orphans) -> table::DetailFn {
+        Box::new(move |idx, filter| {
+            let orphan = orphans
+                .get(idx, filter)
+                .ok_or_else(|| eyre!("resource not found"))?;
+
+            let client = client.clone();
+
+            Ok(match orphan {
+                Orphan::ReplicaSet(rs) => Self::new(client, rs),
+                Orphan::Pod(pod) => Self::new(client, pod),
+                Orphan::Job(job) => Self::new(client, job),
+            }
+            .boxed())
+        })
+    }
+}
+
+impl Widget for Detail {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        1
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        self.view.title()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
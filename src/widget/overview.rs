@@ -0,0 +1,400 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use eyre::Result;
+use k8s_openapi::api::core::v1::{Event as KubeEvent, Node, Pod};
+use kube::{api::ListParams, Api, ResourceExt};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{palette::tailwind, Style, Stylize},
+    text::Line,
+    widgets::{
+        Bar, BarChart, BarGroup, Block, Borders, Cell, Gauge, Paragraph, Row, Sparkline, Table,
+    },
+    Frame,
+};
+use tokio::{sync::oneshot, task::JoinHandle};
+
+use super::{
+    clock::Clock, events::last_seen, loading::Loading, tabs::Tab, view::Element, Widget,
+    WIDGET_VIEWS,
+};
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    resources::{overview::Summary, policy::Policy, store::Store},
+};
+
+// How often the running pod count is added to the sparkline.
+static SAMPLE_EVERY: Duration = Duration::from_secs(10);
+// Warnings are listed, not watched, this is how long until they're fetched
+// again.
+static WARNINGS_EVERY: Duration = Duration::from_secs(30);
+static TOP: usize = 5;
+
+enum Fetch<T> {
+    Loading(JoinHandle<Result<T>>),
+    Ready(T),
+    Failed(String),
+}
+
+impl<T> Fetch<T> {
+    // Move on from `Loading` once the task is done, without blocking the frame.
+    fn poll(&mut self) {
+        let Self::Loading(task) = self else {
+            return;
+        };
+
+        if !task.is_finished() {
+            return;
+        }
+
+        *self = match futures::executor::block_on(async move { task.await? }) {
+            Ok(value) => Self::Ready(value),
+            Err(err) => Self::Failed(err.to_string()),
+        };
+    }
+}
+
+impl<T> Drop for Fetch<T> {
+    fn drop(&mut self) {
+        if let Self::Loading(task) = self {
+            task.abort();
+        }
+    }
+}
+
+/// What the cluster looks like at a glance: its version, how many nodes are
+/// ready, pods by phase and over time, the pods restarting the most and recent
+/// warnings.
+pub struct Overview {
+    client: kube::Client,
+    nodes: Arc<Store<Node>>,
+    pods: Arc<Store<Pod>>,
+    is_ready: Vec<oneshot::Receiver<()>>,
+
+    version: Fetch<String>,
+    warnings: Fetch<Vec<KubeEvent>>,
+    fetched: Instant,
+
+    // Running pods, sampled every `SAMPLE_EVERY` while the tab is open.
+    running: Vec<u64>,
+    sampled: Option<Instant>,
+}
+
+impl Overview {
+    pub fn new(client: kube::Client) -> Self {
+        WIDGET_VIEWS.overview.list.inc();
+
+        let (nodes, nodes_ready) = Store::new(client.clone());
+        let (pods, pods_ready) = Store::new(client.clone());
+
+        let version = {
+            let client = client.clone();
+
+            Fetch::Loading(tokio::spawn(async move {
+                Ok(client.apiserver_version().await?.git_version)
+            }))
+        };
+
+        Self {
+            warnings: Fetch::Loading(warnings(client.clone())),
+            client,
+            nodes,
+            pods,
+            is_ready: vec![nodes_ready, pods_ready],
+
+            version,
+            fetched: Instant::now(),
+
+            running: Vec::new(),
+            sampled: None,
+        }
+    }
+
+    pub fn tab(name: String, client: kube::Client, terminal: bool) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Element::builder()
+                    .widget(Self::new(client.clone()).boxed())
+                    .terminal(terminal)
+                    .build()
+            }))
+            .build()
+    }
+
+    fn refresh(&mut self) {
+        self.version.poll();
+        self.warnings.poll();
+
+        if !matches!(self.warnings, Fetch::Loading(_)) && self.fetched.elapsed() > WARNINGS_EVERY {
+            self.warnings = Fetch::Loading(warnings(self.client.clone()));
+            self.fetched = Instant::now();
+        }
+    }
+
+    fn sample(&mut self, summary: &Summary, width: u16) {
+        if self.sampled.is_some_and(|at| at.elapsed() < SAMPLE_EVERY) {
+            return;
+        }
+
+        self.running
+            .push(summary.phases.get("Running").copied().unwrap_or_default());
+        self.sampled = Some(Instant::now());
+
+        let keep = usize::from(width.max(1));
+
+        if self.running.len() > keep {
+            self.running.drain(..self.running.len() - keep);
+        }
+    }
+}
+
+fn warnings(client: kube::Client) -> JoinHandle<Result<Vec<KubeEvent>>> {
+    tokio::spawn(async move {
+        let mut events = Api::<KubeEvent>::all(client)
+            .list(&ListParams::default().fields("type=Warning"))
+            .await?
+            .items;
+
+        events.sort_by_key(|ev| std::cmp::Reverse(last_seen(ev)));
+
+        Ok(events)
+    })
+}
+
+impl Widget for Overview {
+    fn dispatch(&mut self, event: &Event, _: &Buffer, _: Rect) -> Result<Broadcast> {
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.is_ready.retain_mut(|rx| rx.try_recv().is_err());
+
+        // Counts are wrong until both stores have everything.
+        if !self.is_ready.is_empty() {
+            return Loading.draw(frame, area);
+        }
+
+        self.refresh();
+
+        let summary = Summary::new(&self.nodes.items(None), &self.pods.items(None), TOP);
+
+        let [top, running, middle, bottom] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(6),
+            Constraint::Length(10),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+
+        let [version, nodes] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Fill(1)]).areas(top);
+        let [phases, restarts] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Fill(1)]).areas(middle);
+
+        self.sample(&summary, running.width.saturating_sub(2));
+
+        draw_version(frame, version, &self.version);
+        draw_nodes(frame, nodes, &summary);
+        draw_running(frame, running, &self.running);
+        draw_phases(frame, phases, &summary);
+        draw_restarts(frame, restarts, &summary);
+        draw_warnings(frame, bottom, &self.warnings);
+
+        Ok(())
+    }
+
+    fn title(&self) -> Vec<String> {
+        vec!["overview".to_string()]
+    }
+
+    fn dirty(&self) -> bool {
+        !self.is_ready.is_empty()
+            || self.sampled.is_some_and(|at| at.elapsed() >= SAMPLE_EVERY)
+            || self.fetched.elapsed() > WARNINGS_EVERY
+            || matches!(&self.version, Fetch::Loading(task) if task.is_finished())
+            || matches!(&self.warnings, Fetch::Loading(task) if task.is_finished())
+    }
+}
+
+fn draw_version(frame: &mut Frame, area: Rect, version: &Fetch<String>) {
+    let text = match version {
+        Fetch::Loading(_) => "Loading...".to_string(),
+        Fetch::Ready(version) => version.clone(),
+        Fetch::Failed(err) => format!("Unknown: {err}"),
+    };
+
+    frame.render_widget(
+        Paragraph::new(text).block(Block::default().borders(Borders::ALL).title("Version")),
+        area,
+    );
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn draw_nodes(frame: &mut Frame, area: Rect, summary: &Summary) {
+    let ratio = if summary.nodes == 0 {
+        0.0
+    } else {
+        summary.nodes_ready as f64 / summary.nodes as f64
+    };
+
+    let color = if summary.nodes_ready == summary.nodes {
+        tailwind::GREEN.c300
+    } else {
+        tailwind::RED.c300
+    };
+
+    frame.render_widget(
+        Gauge::default()
+            .block(Block::default().borders(Borders::ALL).title("Nodes"))
+            .gauge_style(Style::default().fg(color))
+            .ratio(ratio)
+            .label(format!("{}/{} ready", summary.nodes_ready, summary.nodes)),
+        area,
+    );
+}
+
+fn draw_running(frame: &mut Frame, area: Rect, running: &[u64]) {
+    let current = running.last().copied().unwrap_or_default();
+
+    frame.render_widget(
+        Sparkline::default()
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("Running pods ({current})")),
+            )
+            .style(Style::default().fg(tailwind::INDIGO.c300))
+            .data(running),
+        area,
+    );
+}
+
+fn draw_phases(frame: &mut Frame, area: Rect, summary: &Summary) {
+    let bars: Vec<_> = summary
+        .phases()
+        .into_iter()
+        .map(|(phase, count)| {
+            let color = match phase.as_str() {
+                "Running" | "Succeeded" => tailwind::GREEN.c300,
+                "Pending" => tailwind::INDIGO.c300,
+                _ => tailwind::RED.c300,
+            };
+
+            Bar::default()
+                .value(count)
+                .label(Line::from(phase))
+                .style(Style::default().fg(color))
+        })
+        .collect();
+
+    frame.render_widget(
+        BarChart::default()
+            .block(Block::default().borders(Borders::ALL).title("Pods"))
+            .bar_width(9)
+            .bar_gap(1)
+            .data(BarGroup::default().bars(&bars)),
+        area,
+    );
+}
+
+fn draw_restarts(frame: &mut Frame, area: Rect, summary: &Summary) {
+    let rows = summary.restarts.iter().map(|r| {
+        Row::new(vec![
+            r.namespace.clone(),
+            r.name.clone(),
+            r.count.to_string(),
+        ])
+    });
+
+    frame.render_widget(
+        Table::new(
+            rows,
+            [Constraint::Max(20), Constraint::Fill(1), Constraint::Max(8)],
+        )
+        .header(
+            Row::new(vec![
+                Cell::from("Namespace"),
+                Cell::from("Name"),
+                Cell::from("Restarts"),
+            ])
+            .style(Style::default().bold()),
+        )
+        .block(Block::default().borders(Borders::ALL).title("Top restarts")),
+        area,
+    );
+}
+
+fn draw_warnings(frame: &mut Frame, area: Rect, warnings: &Fetch<Vec<KubeEvent>>) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Recent warnings");
+
+    let events = match warnings {
+        Fetch::Ready(events) => events,
+        Fetch::Loading(_) => {
+            frame.render_widget(Line::from("Loading...").centered(), block.inner(area));
+            frame.render_widget(block, area);
+
+            return;
+        }
+        Fetch::Failed(err) => {
+            frame.render_widget(
+                Line::from(format!("Unable to fetch events: {err}")).fg(tailwind::RED.c300),
+                block.inner(area),
+            );
+            frame.render_widget(block, area);
+
+            return;
+        }
+    };
+
+    let clock = Clock::current();
+
+    let rows = events
+        .iter()
+        .filter(|ev| Policy::visible(ev.namespace().as_deref()))
+        .map(|ev| {
+            Row::new(vec![
+                last_seen(ev).map_or(String::new(), |t| clock.format(t)),
+                format!(
+                    "{}/{}",
+                    ev.involved_object.kind.clone().unwrap_or_default(),
+                    ev.involved_object.name.clone().unwrap_or_default()
+                ),
+                ev.reason.clone().unwrap_or_default(),
+                ev.message.clone().unwrap_or_default(),
+            ])
+        });
+
+    frame.render_widget(
+        Table::new(
+            rows,
+            [
+                Constraint::Max(clock.format.width()),
+                Constraint::Max(40),
+                Constraint::Max(20),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(
+            Row::new(vec![
+                Cell::from("Age"),
+                Cell::from("Object"),
+                Cell::from("Reason"),
+                Cell::from("Message"),
+            ])
+            .style(Style::default().bold()),
+        )
+        .block(block),
+        area,
+    );
+}
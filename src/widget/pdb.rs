@@ -0,0 +1,371 @@
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use eyre::{eyre, Result};
+use k8s_openapi::api::{
+    core::v1::{Node, Pod},
+    policy::v1::PodDisruptionBudget,
+};
+use kube::ResourceExt;
+use ratatui::{buffer::Buffer, layout::Rect, Frame};
+use tokio::sync::oneshot;
+
+use super::{
+    events::Events,
+    filter::FilterBuilder,
+    loading::Loading,
+    metadata::Metadata,
+    nested, pod, propagate, table,
+    tabs::{Tab, TabbedView},
+    view::{Element, View},
+    yaml::Yaml,
+    yank::Yank,
+    Widget, WIDGET_VIEWS,
+};
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    history::History,
+    resources::{
+        pdb::{Evictions as Items, Scope},
+        store::Store,
+    },
+};
+
+pub struct List {
+    view: View,
+    is_ready: oneshot::Receiver<()>,
+}
+
+#[bon::bon]
+impl List {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(skip(client, history), fields(activity = "pdb.list"))]
+    #[builder]
+    pub fn new(client: kube::Client, history: Arc<dyn History>, filter: Option<String>) -> Self {
+        WIDGET_VIEWS.pdb.list.inc();
+
+        let (budgets, is_ready) = Store::<PodDisruptionBudget>::new(client.clone());
+        let table = table::Filtered::builder()
+            .table(
+                table::Table::builder()
+                    .items(budgets.clone())
+                    .filter(Rc::new(RefCell::new(filter)))
+                    .build(),
+            )
+            .constructor(Detail::from_store(client, budgets.clone(), history))
+            .fields(FilterBuilder::from_store(budgets))
+            .build();
+
+        let widgets = vec![
+            table.boxed().into(),
+            Element::builder()
+                .widget(Loading.boxed())
+                .ignore(true)
+                .build(),
+        ];
+
+        Self {
+            view: View::builder().widgets(widgets).build(),
+            is_ready,
+        }
+    }
+
+    pub fn tab(
+        name: String,
+        client: kube::Client,
+        history: Arc<dyn History>,
+        terminal: bool,
+    ) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Element::builder()
+                    .widget(
+                        Self::builder()
+                            .client(client.clone())
+                            .history(history.clone())
+                            .build()
+                            .boxed(),
+                    )
+                    .terminal(terminal)
+                    .build()
+            }))
+            .build()
+    }
+}
+
+impl Widget for List {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if let Ok(()) = self.is_ready.try_recv() {
+            self.view.pop();
+        }
+
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        self.view.zindex()
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        let title = self.view.title();
+
+        if title.is_empty() {
+            vec!["pdbs".to_string()]
+        } else {
+            title
+        }
+    }
+
+    fn filter(&self) -> Option<String> {
+        self.view.filter()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
+
+pub struct Detail {
+    view: TabbedView,
+}
+
+#[bon::bon]
+impl Detail {
+    #[builder]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn new(
+        client: kube::Client,
+        budget: Arc<PodDisruptionBudget>,
+        history: Arc<dyn History>,
+    ) -> Self {
+        WIDGET_VIEWS.pdb.detail.inc();
+
+        let tabs = vec![
+            Yaml::tab("YAML".to_string(), budget.clone()),
+            Evictions::budget_tab(
+                "Disruptions".to_string(),
+                client.clone(),
+                history,
+                budget.clone(),
+            ),
+            Events::tab("Events".to_string(), client.clone(), budget.clone()),
+            Metadata::tab("Metadata".to_string(), client, budget.clone()),
+        ];
+
+        let view = TabbedView::builder()
+            .tabs(tabs)
+            .yank(Yank::from_obj(budget.as_ref()))
+            .title(vec![
+                "pdbs".to_string(),
+                budget.namespace().unwrap_or_default(),
+                budget.name_any(),
+            ])
+            .build();
+
+        Self { view }
+    }
+
+    pub fn from_store(
+        client: kube::Client,
+        store: Arc<Store<PodDisruptionBudget>>,
+        history: Arc<dyn History>,
+    ) -> table::DetailFn {
+        Box::new(move |idx, filter| {
+            let budget = store
+                .get(idx, filter)
+                .ok_or_else(|| eyre!("disruption budget not found"))?;
+
+            Ok(Detail::builder()
+                .client(client.clone())
+                .budget(budget)
+                .history(history.clone())
+                .build()
+                .boxed())
+        })
+    }
+}
+
+impl Widget for Detail {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        1
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        self.view.title()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
+
+/// What draining would do to pods, either those on a node or those a budget
+/// covers. Pods that would block or stall the drain are highlighted. Selecting
+/// one opens the pod.
+pub struct Evictions {
+    view: View,
+    is_ready: Vec<oneshot::Receiver<()>>,
+}
+
+impl Evictions {
+    fn new(
+        client: kube::Client,
+        history: Arc<dyn History>,
+        scope: Scope,
+        (pods, pods_ready): (Arc<Store<Pod>>, oneshot::Receiver<()>),
+    ) -> Self {
+        let (budgets, budgets_ready) = Store::new(client.clone());
+
+        let items = Items::new(scope, pods, budgets);
+
+        let table = table::Filtered::builder()
+            .table(table::Table::builder().items(items.clone()).build())
+            .constructor(detail(client, items, history))
+            .build();
+
+        let widgets = vec![
+            table.boxed().into(),
+            Element::builder()
+                .widget(Loading.boxed())
+                .ignore(true)
+                .build(),
+        ];
+
+        Self {
+            view: View::builder().widgets(widgets).build(),
+            is_ready: vec![pods_ready, budgets_ready],
+        }
+    }
+
+    /// Draining `node`, only the pods on it are watched.
+    pub fn node_tab(
+        name: String,
+        client: kube::Client,
+        history: Arc<dyn History>,
+        node: Arc<Node>,
+    ) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                WIDGET_VIEWS.node.evictions.inc();
+
+                let pods = Store::scoped(
+                    client.clone(),
+                    &format!("spec.nodeName={}", node.name_any()),
+                );
+
+                Self::new(client.clone(), history.clone(), Scope::Node, pods)
+                    .boxed()
+                    .into()
+            }))
+            .build()
+    }
+
+    /// Draining the nodes the pods `budget` covers are on.
+    pub fn budget_tab(
+        name: String,
+        client: kube::Client,
+        history: Arc<dyn History>,
+        budget: Arc<PodDisruptionBudget>,
+    ) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                WIDGET_VIEWS.pdb.evictions.inc();
+
+                let pods = Store::new(client.clone());
+
+                Self::new(
+                    client.clone(),
+                    history.clone(),
+                    Scope::Budget(budget.clone()),
+                    pods,
+                )
+                .boxed()
+                .into()
+            }))
+            .build()
+    }
+}
+
+fn detail(client: kube::Client, items: Items, history: Arc<dyn History>) -> table::DetailFn {
+    Box::new(move |idx, filter| {
+        let pod = items
+            .get(idx, filter)
+            .ok_or_else(|| eyre!("pod not found"))?;
+
+        Ok(pod::Detail::builder()
+            .client(&client)
+            .pod(pod)
+            .history(history.clone())
+            .build()
+            .boxed())
+    })
+}
+
+impl Widget for Evictions {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        self.view.dispatch(event, buffer, area)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        // Both stores are needed before a drain can be simulated, the loading
+        // indicator stays until then.
+        let waiting = !self.is_ready.is_empty();
+        self.is_ready.retain_mut(|rx| rx.try_recv().is_err());
+
+        if waiting && self.is_ready.is_empty() {
+            self.view.pop();
+        }
+
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        self.view.zindex()
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        self.view.title()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
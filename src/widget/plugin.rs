@@ -0,0 +1,476 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use eyre::{eyre, Result};
+use kube::{
+    api::{Api, DynamicObject, ListParams},
+    ResourceExt,
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Position, Rect},
+    widgets::{Block, Borders, Cell, Row},
+    Frame,
+};
+use tokio::task::JoinHandle;
+
+use super::{
+    actions::Actions,
+    error::Error,
+    loading::Loading,
+    nav::{Motion, Movement, Nav},
+    nested, propagate, table,
+    tabs::{Tab, TabbedView},
+    view::{Element, View},
+    viewport::Viewport,
+    BoxWidget, Widget, WIDGET_VIEWS,
+};
+use crate::{
+    dashboard::context::Context,
+    events::{Broadcast, Event, Keypress},
+    plugin::{self, Plugin},
+    resources::{action, policy::Policy, selector::Selector},
+};
+
+// Plugin resources aren't watched, this is how long until they're listed again.
+static REFRESH_EVERY: Duration = Duration::from_secs(10);
+
+/// A resource along with the row the plugin made for it.
+#[derive(Clone)]
+struct Entry {
+    object: Arc<DynamicObject>,
+    row: plugin::Row,
+}
+
+impl table::Row for Entry {
+    // Columns come from the plugin, see `Listed::widths`.
+    fn constraints() -> Vec<Constraint> {
+        Vec::new()
+    }
+
+    fn row(&self, style: &table::RowStyle) -> Row {
+        Row::new(self.row.cells.clone()).style(style.get(table::Row::health(self)))
+    }
+
+    fn health(&self) -> table::Health {
+        match self.row.health {
+            plugin::Health::Healthy => table::Health::Healthy,
+            plugin::Health::Unhealthy => table::Health::Unhealthy,
+            plugin::Health::Normal => table::Health::Normal,
+        }
+    }
+}
+
+/// Resources from the last time they were listed, shared with the table that
+/// shows them.
+#[derive(Clone)]
+struct Listed {
+    plugin: Arc<dyn Plugin>,
+    entries: Rc<RefCell<Vec<Entry>>>,
+}
+
+impl table::Items for Listed {
+    type Item = Entry;
+
+    fn items(&self, filter: Option<String>) -> Vec<Self::Item> {
+        let selector = filter.as_deref().map(Selector::from).unwrap_or_default();
+
+        self.entries
+            .borrow()
+            .iter()
+            .filter(|entry| {
+                Policy::visible(entry.object.metadata.namespace.as_deref())
+                    && selector.matches_fields(&entry.object.name_any(), |key| {
+                        entry.object.labels().get(key).map(String::as_str)
+                    })
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn widths(&self) -> Vec<Constraint> {
+        self.plugin
+            .schema()
+            .columns
+            .iter()
+            .map(|column| column.width.map_or(Constraint::Fill(1), Constraint::Max))
+            .collect()
+    }
+
+    fn heading(&self) -> Option<Row<'static>> {
+        Some(Row::new(
+            self.plugin
+                .schema()
+                .columns
+                .iter()
+                .map(|column| Cell::from(column.name.clone())),
+        ))
+    }
+}
+
+/// A tab from a [`Plugin`]. The resource it is for is listed as the user, the
+/// plugin decides what each row looks like and what selecting one shows.
+pub struct List {
+    view: View,
+    client: kube::Client,
+    listed: Listed,
+    task: Option<JoinHandle<Result<Vec<Entry>>>>,
+    // Unset until the first list has finished.
+    fetched: Option<Instant>,
+}
+
+impl List {
+    pub fn new(client: kube::Client, plugin: Arc<dyn Plugin>) -> Self {
+        WIDGET_VIEWS.plugin.list.inc();
+
+        let listed = Listed {
+            plugin: plugin.clone(),
+            entries: Rc::default(),
+        };
+
+        let table = table::Filtered::builder()
+            .table(
+                table::Table::builder()
+                    .items(listed.clone())
+                    .filter(Rc::new(RefCell::new(None)))
+                    .build(),
+            )
+            .constructor(Detail::from_listed(client.clone(), listed.clone()))
+            .build();
+
+        let widgets = vec![
+            table.boxed().into(),
+            Element::builder()
+                .widget(Loading.boxed())
+                .ignore(true)
+                .build(),
+        ];
+
+        Self {
+            view: View::builder().widgets(widgets).build(),
+            task: Some(fetch(client.clone(), plugin)),
+            client,
+            listed,
+            fetched: None,
+        }
+    }
+
+    pub fn tab(client: kube::Client, plugin: Arc<dyn Plugin>, terminal: bool) -> Tab {
+        Tab::builder()
+            .name(plugin.schema().title.clone())
+            .constructor(Box::new(move || {
+                Element::builder()
+                    .widget(Self::new(client.clone(), plugin.clone()).boxed())
+                    .terminal(terminal)
+                    .build()
+            }))
+            .build()
+    }
+
+    // Pick up the last list without blocking the frame, and start the next one
+    // once it is due.
+    fn poll(&mut self) {
+        let Some(task) = self.task.as_mut() else {
+            if self.fetched.is_some_and(|at| at.elapsed() > REFRESH_EVERY) {
+                self.task = Some(fetch(self.client.clone(), self.listed.plugin.clone()));
+            }
+
+            return;
+        };
+
+        if !task.is_finished() {
+            return;
+        }
+
+        let result = futures::executor::block_on(async move { task.await? });
+        self.task = None;
+
+        if self.fetched.replace(Instant::now()).is_none() {
+            self.view.pop();
+        }
+
+        match result {
+            Ok(entries) => *self.listed.entries.borrow_mut() = entries,
+            Err(err) => self.view.error(Error::from(err)),
+        }
+    }
+}
+
+// Plugins run off the dashboard's thread, they're limited in how long they can
+// take but that is still too long for a frame.
+fn fetch(client: kube::Client, plugin: Arc<dyn Plugin>) -> JoinHandle<Result<Vec<Entry>>> {
+    tokio::spawn(async move {
+        let resource = plugin.schema().resource.api_resource();
+
+        let mut objects = Api::<DynamicObject>::all_with(client, &resource)
+            .list(&ListParams::default())
+            .await?
+            .items;
+
+        for object in &mut objects {
+            object.managed_fields_mut().clear();
+        }
+
+        objects.sort_by(|a, b| {
+            (&a.metadata.namespace, &a.metadata.name)
+                .cmp(&(&b.metadata.namespace, &b.metadata.name))
+        });
+
+        // Plugins can draw on the dashboard's locale, clock and such.
+        let context = Context::current();
+        let (objects, rows) = tokio::task::spawn_blocking(move || {
+            let rows = context.enter(|| plugin.rows(&objects));
+
+            (objects, rows)
+        })
+        .await?;
+
+        Ok(objects
+            .into_iter()
+            .zip(rows?)
+            .map(|(object, row)| Entry {
+                object: Arc::new(object),
+                row,
+            })
+            .collect())
+    })
+}
+
+impl Drop for List {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.as_ref() {
+            task.abort();
+        }
+    }
+}
+
+impl Widget for List {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.poll();
+
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        self.view.zindex()
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        let title = self.view.title();
+
+        if title.is_empty() {
+            vec![self.listed.plugin.schema().name.clone()]
+        } else {
+            title
+        }
+    }
+
+    fn filter(&self) -> Option<String> {
+        self.view.filter()
+    }
+
+    fn dirty(&self) -> bool {
+        self.task.as_ref().is_some_and(JoinHandle::is_finished) || self.view.dirty()
+    }
+}
+
+/// A resource listed by a plugin. Whatever the plugin renders for it comes
+/// first, followed by the actions declared on it, see [`action::ANNOTATION`].
+struct Detail {
+    view: TabbedView,
+}
+
+impl Detail {
+    fn new(client: kube::Client, plugin: Arc<dyn Plugin>, object: Arc<DynamicObject>) -> Self {
+        WIDGET_VIEWS.plugin.detail.inc();
+
+        let title = vec![
+            plugin.schema().name.clone(),
+            object.namespace().unwrap_or_default(),
+            object.name_any(),
+        ];
+
+        let resource = plugin.schema().resource.api_resource();
+        let mut tabs = vec![Rendered::tab(
+            "Overview".to_string(),
+            plugin,
+            object.clone(),
+        )];
+
+        if object.annotations().contains_key(action::ANNOTATION) {
+            tabs.push(Actions::dynamic_tab(
+                "Actions".to_string(),
+                client,
+                resource,
+                object,
+            ));
+        }
+
+        Self {
+            view: TabbedView::builder().tabs(tabs).title(title).build(),
+        }
+    }
+
+    fn from_listed(client: kube::Client, listed: Listed) -> table::DetailFn {
+        Box::new(move |idx, filter| {
+            let entry = table::Items::items(&listed, filter)
+                .get(idx)
+                .cloned()
+                .ok_or_else(|| eyre!("resource not found"))?;
+
+            Ok(Self::new(client.clone(), listed.plugin.clone(), entry.object).boxed())
+        })
+    }
+}
+
+impl Widget for Detail {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        1
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        self.view.title()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
+
+/// What the plugin shows for a resource, rendered in the background.
+struct Rendered {
+    task: JoinHandle<Result<String>>,
+    lines: Option<Vec<String>>,
+    failed: Option<BoxWidget>,
+
+    position: Position,
+    nav: Nav,
+}
+
+impl Rendered {
+    fn new(plugin: Arc<dyn Plugin>, object: Arc<DynamicObject>) -> Self {
+        Self {
+            task: tokio::task::spawn_blocking({
+                let context = Context::current();
+
+                move || context.enter(|| plugin.detail(&object))
+            }),
+            lines: None,
+            failed: None,
+
+            position: Position::default(),
+            nav: Nav::default(),
+        }
+    }
+
+    fn tab(name: String, plugin: Arc<dyn Plugin>, object: Arc<DynamicObject>) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Self::new(plugin.clone(), object.clone()).boxed().into()
+            }))
+            .build()
+    }
+}
+
+impl Drop for Rendered {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl Widget for Rendered {
+    fn dispatch(&mut self, event: &Event, _: &Buffer, area: Rect) -> Result<Broadcast> {
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        if matches!(key, Keypress::Escape) {
+            return Ok(Broadcast::Exited);
+        }
+
+        match self.nav.motion(key, area) {
+            Motion::Move(Movement::Y(y)) => {
+                self.position.y = self.position.y.saturating_add_signed(y);
+
+                Ok(Broadcast::Consumed)
+            }
+            Motion::Pending => Ok(Broadcast::Consumed),
+            _ => Ok(Broadcast::Ignored),
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if self.lines.is_none() && self.failed.is_none() && self.task.is_finished() {
+            let task = &mut self.task;
+
+            match futures::executor::block_on(async move { task.await? }) {
+                Ok(text) => self.lines = Some(text.lines().map(ToString::to_string).collect()),
+                Err(err) => self.failed = Some(Error::from(err).boxed()),
+            }
+        }
+
+        if let Some(failed) = self.failed.as_mut() {
+            return failed.draw(frame, area);
+        }
+
+        let Some(lines) = self.lines.as_ref() else {
+            return Loading.draw(frame, area);
+        };
+
+        let block = Block::default().borders(Borders::ALL);
+        let inner = block.inner(area);
+
+        frame.render_widget(block, area);
+
+        #[allow(clippy::cast_possible_truncation)]
+        let bottom = (lines.len() as u16).saturating_sub(inner.height);
+        self.position.y = self.position.y.min(bottom);
+
+        Viewport::builder()
+            .buffer(lines)
+            .view(self.position)
+            .build()
+            .draw(frame, inner)
+    }
+
+    fn dirty(&self) -> bool {
+        self.lines.is_none() && self.failed.is_none() && self.task.is_finished()
+    }
+}
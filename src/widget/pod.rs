@@ -1,42 +1,106 @@
+pub mod commands;
+pub mod ephemeral;
+pub mod files;
+pub mod probes;
 pub mod shell;
 
-use std::sync::Arc;
+use std::{cell::RefCell, rc::Rc, sync::Arc};
 
 use eyre::{eyre, Result};
-use k8s_openapi::api::core::v1::Pod;
+use k8s_openapi::api::core::v1::{Node, Pod};
 use kube::ResourceExt;
 use ratatui::{layout::Rect, prelude::*};
 use tokio::sync::oneshot;
 
 use super::{
+    actions::Actions,
+    allocation::Allocation,
+    bulk::Bulk,
+    create::Create,
+    filter::FilterBuilder,
     loading::Loading,
     log::Log,
-    propagate, table,
+    metadata::Metadata,
+    nested, propagate, table,
     tabs::{Tab, TabbedView},
+    timeline::Timeline,
     view::{Element, View},
+    yank::Yank,
     Placement, Widget, WIDGET_VIEWS,
 };
 use crate::{
     events::{Broadcast, Event, Keypress},
-    resources::store::Store,
-    widget::{pod::shell::Shell, yaml::Yaml},
+    history::History,
+    resources::{action, bulk::Operation, policy::Verb, store::Store},
+    widget::{
+        pod::{
+            commands::Commands, ephemeral::Ephemeral, files::Files, probes::Probes, shell::Shell,
+        },
+        yaml::Yaml,
+    },
 };
 
 pub struct List {
+    client: kube::Client,
+    focus: table::Focus,
+
     view: View,
     is_ready: oneshot::Receiver<()>,
+    ready: bool,
 }
 
 impl List {
     #[allow(clippy::blocks_in_conditions)]
-    #[tracing::instrument(skip(client), fields(activity = "pod.list"))]
-    pub fn new(client: kube::Client) -> Self {
+    #[tracing::instrument(skip(client, history), fields(activity = "pod.list"))]
+    pub fn new(client: kube::Client, history: Arc<dyn History>, filter: Option<String>) -> Self {
+        let (pods, is_ready) = Store::new(client.clone());
+
+        Self::with_store(client, history, filter, pods, is_ready)
+    }
+
+    /// Pods scheduled on `node`. Only those are watched, instead of filtering
+    /// every pod in the cluster.
+    pub fn on_node(client: kube::Client, history: Arc<dyn History>, node: &str) -> Self {
+        let (pods, is_ready) = Store::scoped(client.clone(), &format!("spec.nodeName={node}"));
+
+        Self::with_store(client, history, None, pods, is_ready)
+    }
+
+    fn with_store(
+        client: kube::Client,
+        history: Arc<dyn History>,
+        filter: Option<String>,
+        pods: Arc<Store<Pod>>,
+        is_ready: oneshot::Receiver<()>,
+    ) -> Self {
         WIDGET_VIEWS.pod.list.inc();
 
-        let (pods, is_ready) = Store::new(client.clone());
+        let focus = table::Focus::default();
         let table = table::Filtered::builder()
-            .table(table::Table::builder().items(pods.clone()).build())
-            .constructor(Detail::from_store(client, pods))
+            .table(
+                table::Table::builder()
+                    .items(pods.clone())
+                    .filter(Rc::new(RefCell::new(filter)))
+                    .focus(focus.clone())
+                    .groups(&[
+                        table::Group::Node,
+                        table::Group::Namespace,
+                        table::Group::Owner,
+                    ])
+                    .sorts(&[
+                        table::Sort::Severity,
+                        table::Sort::Restarts,
+                        table::Sort::Age,
+                    ])
+                    .build(),
+            )
+            .constructor(Detail::from_store(client.clone(), pods.clone(), history))
+            .bulk(Bulk::from_store(
+                client.clone(),
+                pods.clone(),
+                &[Operation::Delete, Operation::Label],
+            ))
+            .fields(FilterBuilder::from_store(pods))
             .build();
 
         let widgets = vec![
@@ -48,30 +112,70 @@ impl List {
         ];
 
         Self {
+            client,
+            focus,
+
             view: View::builder().widgets(widgets).build(),
             is_ready,
+            ready: false,
         }
     }
 
-    pub fn tab(name: String, client: kube::Client, terminal: bool) -> Tab {
+    pub fn tab(
+        name: String,
+        client: kube::Client,
+        history: Arc<dyn History>,
+        terminal: bool,
+    ) -> Tab {
         Tab::builder()
             .name(name)
             .constructor(Box::new(move || {
                 Element::builder()
-                    .widget(Self::new(client.clone()).boxed())
+                    .widget(Self::new(client.clone(), history.clone(), None).boxed())
                     .terminal(terminal)
                     .build()
             }))
             .build()
     }
+
+    /// The pods on `node`, for its detail view.
+    pub fn node_tab(
+        name: String,
+        client: kube::Client,
+        history: Arc<dyn History>,
+        node: Arc<Node>,
+    ) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Self::on_node(client.clone(), history.clone(), &node.name_any())
+                    .boxed()
+                    .into()
+            }))
+            .build()
+    }
 }
 
 impl Widget for List {
     fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
         propagate!(self.view.dispatch(event, buffer, area));
 
-        if matches!(event.key(), Some(Keypress::Escape)) {
-            return Ok(Broadcast::Exited);
+        match event.key() {
+            Some(Keypress::Escape) => return Ok(Broadcast::Exited),
+            // Only from the table itself, not while a pod is open. The loading
+            // indicator is popped off the top once ready, so wait for that first.
+            Some(Keypress::Printable('n'))
+                if self.ready && self.view.zindex() == 0 && Verb::Create.allowed() =>
+            {
+                self.view.push(
+                    Create::new(self.client.clone(), self.focus.clone())
+                        .boxed()
+                        .into(),
+                );
+
+                return Ok(Broadcast::Consumed);
+            }
+            _ => {}
         }
 
         Ok(Broadcast::Ignored)
@@ -81,6 +185,7 @@ impl Widget for List {
         // TODO: add an error screen here if Err(TryRecvError::Closed)
         if let Ok(()) = self.is_ready.try_recv() {
             self.view.pop();
+            self.ready = true;
         }
 
         self.view.draw(frame, area)
@@ -96,9 +201,31 @@ impl Widget for List {
     fn zindex(&self) -> u16 {
         self.view.zindex()
     }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        let title = self.view.title();
+
+        if title.is_empty() {
+            vec!["pods".to_string()]
+        } else {
+            title
+        }
+    }
+
+    fn filter(&self) -> Option<String> {
+        self.view.filter()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
 }
 
-struct Detail {
+pub struct Detail {
     view: TabbedView,
 }
 
@@ -106,15 +233,34 @@ struct Detail {
 impl Detail {
     #[builder]
     #[allow(clippy::needless_pass_by_value)]
-    fn new(client: &kube::Client, pod: Arc<Pod>) -> Self {
+    pub fn new(client: &kube::Client, pod: Arc<Pod>, history: Arc<dyn History>) -> Self {
         WIDGET_VIEWS.pod.detail.inc();
 
+        let mut tabs = vec![
+            Yaml::tab("Overview".to_string(), pod.clone()),
+            Allocation::pod_tab("Resources".to_string(), pod.clone()),
+            Log::tab("Logs".to_string(), client.clone(), pod.clone()),
+            Probes::tab("Probes".to_string(), client.clone(), pod.clone()),
+            Shell::tab("Shell".to_string(), client.clone(), pod.clone(), history),
+            Files::tab("Files".to_string(), client.clone(), pod.clone()),
+            Ephemeral::tab("Debug".to_string(), client.clone(), pod.clone()),
+            Metadata::tab("Metadata".to_string(), client.clone(), pod.clone()),
+            Timeline::tab("Timeline".to_string(), client.clone(), pod.clone()),
+        ];
+
+        if pod.annotations().contains_key(action::ANNOTATION) {
+            tabs.push(Actions::tab(
+                "Actions".to_string(),
+                client.clone(),
+                pod.clone(),
+            ));
+        }
+
+        tabs.extend(Commands::tab("Commands".to_string(), pod.clone()));
+
         let view = TabbedView::builder()
-            .tabs(vec![
-                Yaml::tab("Overview".to_string(), pod.clone()),
-                Log::tab("Logs".to_string(), client.clone(), pod.clone()),
-                Shell::tab("Shell".to_string(), client.clone(), pod.clone()),
-            ])
+            .tabs(tabs)
+            .yank(Yank::from_obj(pod.as_ref()))
             .title(vec![
                 "pods".to_string(),
                 pod.namespace().unwrap_or_default(),
@@ -125,13 +271,22 @@ impl Detail {
         Self { view }
     }
 
-    pub fn from_store(client: kube::Client, pods: Arc<Store<Pod>>) -> table::DetailFn {
+    pub fn from_store(
+        client: kube::Client,
+        pods: Arc<Store<Pod>>,
+        history: Arc<dyn History>,
+    ) -> table::DetailFn {
         Box::new(move |idx, filter| {
             let pod = pods
                 .get(idx, filter)
                 .ok_or_else(|| eyre!("pod not found"))?;
 
-            Ok(Detail::builder().client(&client).pod(pod).build().boxed())
+            Ok(Detail::builder()
+                .client(&client)
+                .pod(pod)
+                .history(history.clone())
+                .build()
+                .boxed())
         })
     }
 }
@@ -154,4 +309,16 @@ impl Widget for Detail {
     fn zindex(&self) -> u16 {
         1
     }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        self.view.title()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
 }
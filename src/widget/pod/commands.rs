@@ -0,0 +1,276 @@
+use std::{pin::Pin, sync::Arc};
+
+use eyre::{eyre, Result};
+use futures::StreamExt;
+use k8s_openapi::api::core::v1::Pod;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{palette::tailwind, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt},
+    process::Child,
+    sync::mpsc::UnboundedReceiver,
+};
+use tokio_util::io::ReaderStream;
+
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    launch::Launch,
+    resources::{
+        command::{Command, Commands as Configured},
+        policy::Verb,
+    },
+    widget::{tabs::Tab, Raw, Widget, WIDGET_VIEWS},
+};
+
+/// Commands from `KtySettings.commands` for a pod. Each is bound to a key and
+/// takes over the terminal until it exits.
+pub struct Commands {
+    pod: Arc<Pod>,
+    commands: Vec<Command>,
+    launch: Arc<dyn Launch>,
+
+    idx: usize,
+    failed: Option<String>,
+}
+
+impl Commands {
+    pub fn new(pod: Arc<Pod>, commands: Vec<Command>, launch: Arc<dyn Launch>) -> Self {
+        WIDGET_VIEWS.pod.commands.inc();
+
+        Self {
+            pod,
+            commands,
+            launch,
+
+            idx: 0,
+            failed: None,
+        }
+    }
+
+    /// Only available when commands have been configured and the server can run
+    /// them. Commands run on the server as the user, so they need `exec`.
+    pub fn tab(name: String, pod: Arc<Pod>) -> Option<Tab> {
+        let (commands, launch) = Configured::current()?;
+
+        Some(
+            Tab::builder()
+                .name(name)
+                .verb(Verb::Exec)
+                .constructor(Box::new(move || {
+                    Self::new(pod.clone(), commands.clone(), launch.clone())
+                        .boxed()
+                        .into()
+                }))
+                .build(),
+        )
+    }
+
+    fn run(&mut self, idx: usize) -> Broadcast {
+        self.idx = idx;
+
+        let argv = self.commands[idx].argv(&self.pod);
+
+        match self.launch.launch(&argv) {
+            Ok(child) => {
+                self.failed = None;
+
+                Broadcast::Raw(Box::new(Launched {
+                    argv,
+                    pid: child.id(),
+                    child,
+                    launch: self.launch.clone(),
+                }))
+            }
+            Err(err) => {
+                self.failed = Some(err.to_string());
+
+                Broadcast::Consumed
+            }
+        }
+    }
+}
+
+impl Widget for Commands {
+    fn dispatch(&mut self, event: &Event, _: &Buffer, _: Rect) -> Result<Broadcast> {
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        match key {
+            Keypress::CursorUp | Keypress::Printable('k') => {
+                self.idx = self.idx.saturating_sub(1);
+            }
+            Keypress::CursorDown | Keypress::Printable('j') => {
+                self.idx = (self.idx + 1).min(self.commands.len().saturating_sub(1));
+            }
+            Keypress::Enter => return Ok(self.run(self.idx)),
+            Keypress::Printable(c) => {
+                let Some(idx) = self.commands.iter().position(|cmd| cmd.key == *c) else {
+                    return Ok(Broadcast::Ignored);
+                };
+
+                return Ok(self.run(idx));
+            }
+            _ => return Ok(Broadcast::Ignored),
+        }
+
+        Ok(Broadcast::Consumed)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let block = Block::default().borders(Borders::ALL);
+
+        let [list, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(block.inner(area));
+
+        let lines: Vec<Line> = self
+            .commands
+            .iter()
+            .enumerate()
+            .map(|(i, command)| {
+                let line = Line::from(format!(
+                    "[{}] {}  {}",
+                    command.key,
+                    command.name,
+                    command.argv(&self.pod).join(" ")
+                ));
+
+                if i == self.idx {
+                    line.style(Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    line
+                }
+            })
+            .collect();
+
+        frame.render_widget(Paragraph::new(lines), list);
+
+        let status_line = match &self.failed {
+            None => Paragraph::new("Press a key or enter to run a command, ctrl-b to return"),
+            Some(err) => Paragraph::new(format!("Failed: {err}"))
+                .style(Style::default().fg(tailwind::RED.c300)),
+        };
+
+        frame.render_widget(status_line, status);
+        frame.render_widget(block, area);
+
+        Ok(())
+    }
+}
+
+/// A running command. There's no terminal on the server side, so output is
+/// passed through line by line and ctrl-c or ctrl-b stop the command.
+struct Launched {
+    argv: Vec<String>,
+    child: Child,
+    // Taken once the child has exited, pids can be reused after that.
+    pid: Option<u32>,
+    launch: Arc<dyn Launch>,
+}
+
+impl Launched {
+    fn exited(&mut self) {
+        if let Some(pid) = self.pid.take() {
+            self.launch.exited(pid);
+        }
+    }
+
+    async fn attach(
+        &mut self,
+        stdin: &mut UnboundedReceiver<Event>,
+        stdout: &mut Pin<Box<dyn AsyncWrite + Send + Unpin>>,
+    ) -> Result<()> {
+        let mut input = self
+            .child
+            .stdin
+            .take()
+            .ok_or(eyre!("stdin not available"))?;
+        let mut output = futures::stream::select(
+            ReaderStream::new(
+                self.child
+                    .stdout
+                    .take()
+                    .ok_or(eyre!("stdout not available"))?,
+            ),
+            ReaderStream::new(
+                self.child
+                    .stderr
+                    .take()
+                    .ok_or(eyre!("stderr not available"))?,
+            ),
+        );
+
+        loop {
+            tokio::select! {
+                msg = stdin.recv() => {
+                    let Some(msg) = msg else {
+                        break;
+                    };
+
+                    let Event::Input(incoming) = &msg else {
+                        continue;
+                    };
+
+                    if matches!(msg.key(), Some(Keypress::Control('b' | 'c'))) {
+                        break;
+                    }
+
+                    // Commands that don't read their input close stdin early,
+                    // that isn't a reason to stop them.
+                    let _ = input.write_all(incoming.into()).await;
+                }
+                msg = output.next() => {
+                    let Some(msg) = msg else {
+                        break;
+                    };
+
+                    let msg = String::from_utf8_lossy(&msg?).replace('\n', "\r\n");
+
+                    stdout.write_all(msg.as_bytes()).await?;
+                    stdout.flush().await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Raw for Launched {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(skip(self, stdin, stdout), fields(activity = "pod.command"))]
+    async fn start(
+        &mut self,
+        stdin: &mut UnboundedReceiver<Event>,
+        mut stdout: Pin<Box<dyn AsyncWrite + Send + Unpin>>,
+    ) -> Result<()> {
+        WIDGET_VIEWS.pod.launch.inc();
+
+        let result = self.attach(stdin, &mut stdout).await;
+
+        // The command might have exited on its own already.
+        let _ = self.child.start_kill();
+        let status = self.child.wait().await;
+        self.exited();
+
+        tracing::info!(argv = ?self.argv, ?status, "command exited");
+
+        status?;
+
+        result
+    }
+}
+
+impl Drop for Launched {
+    fn drop(&mut self) {
+        let _ = self.child.start_kill();
+        self.exited();
+    }
+}
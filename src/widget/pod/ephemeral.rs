@@ -0,0 +1,259 @@
+use std::{pin::Pin, sync::Arc};
+
+use chrono::Utc;
+use eyre::Result;
+use k8s_openapi::api::core::v1::Pod;
+use kube::{api::Api, ResourceExt};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{palette::tailwind, Style},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt},
+    sync::mpsc::UnboundedReceiver,
+};
+
+use super::shell::ExecBuilder;
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    resources::{
+        container::{Container, ContainerExt},
+        ephemeral,
+        pod::PodExt,
+        policy::Verb,
+    },
+    widget::{input, input::ContentExt, propagate, table, tabs::Tab, Raw, Widget, WIDGET_VIEWS},
+};
+
+static IMAGE: &str = "busybox";
+static COMMAND: &str = "sh";
+
+/// Attaches an ephemeral debug container to the pod, the same as `kubectl
+/// debug`. The selected container is the target, its processes are visible from
+/// the debug container.
+pub struct Ephemeral {
+    view: table::Filtered,
+}
+
+#[bon::bon]
+impl Ephemeral {
+    #[builder]
+    pub fn new(client: kube::Client, pod: Arc<Pod>) -> Self {
+        WIDGET_VIEWS.container.list.inc();
+
+        let len = pod.as_ref().containers(None).len();
+
+        let mut view = table::Filtered::builder()
+            .table(table::Table::builder().items(pod.clone()).build())
+            .constructor(Image::from_pod(client, pod))
+            .build();
+
+        if len == 1 {
+            view.select(0).expect("can select");
+        }
+
+        Self { view }
+    }
+
+    pub fn tab(name: String, client: kube::Client, pod: Arc<Pod>) -> Tab {
+        Tab::builder()
+            .name(name)
+            .verb(Verb::Exec)
+            .constructor(Box::new(move || {
+                Self::builder()
+                    .client(client.clone())
+                    .pod(pod.clone())
+                    .build()
+                    .boxed()
+                    .into()
+            }))
+            .build()
+    }
+}
+
+impl Widget for Ephemeral {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        self.view.dispatch(event, buffer, area)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.view.draw(frame, area)
+    }
+}
+
+struct Image {
+    client: kube::Client,
+    pod: Arc<Pod>,
+    target: String,
+    content: input::Text,
+    error: Option<String>,
+}
+
+impl Image {
+    fn new(client: kube::Client, pod: Arc<Pod>, target: String) -> Self {
+        WIDGET_VIEWS.container.debug.inc();
+
+        Self {
+            client,
+            pod,
+            content: input::Text::builder()
+                .title(format!("Image to debug {target} with"))
+                .content(input::Content::from_string(IMAGE))
+                .history("image")
+                .build(),
+            target,
+            error: None,
+        }
+    }
+
+    fn from_pod(client: kube::Client, pod: Arc<Pod>) -> table::DetailFn {
+        Box::new(move |idx, filter| {
+            let containers = pod.containers(filter);
+
+            Ok(Image::new(
+                client.clone(),
+                pod.clone(),
+                containers.get(idx).unwrap().name_any(),
+            )
+            .boxed())
+        })
+    }
+}
+
+impl Widget for Image {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.content.dispatch(event, buffer, area));
+
+        let image = self
+            .content
+            .content()
+            .borrow()
+            .as_ref()
+            .map_or(String::new(), |image| image.trim().to_string());
+
+        match event.key() {
+            Some(Keypress::Enter) if !image.is_empty() => {
+                self.error = None;
+
+                return Ok(Broadcast::Raw(Box::new(Attach {
+                    client: self.client.clone(),
+                    pod: self.pod.clone(),
+                    target: self.target.clone(),
+                    image,
+                })));
+            }
+            Some(Keypress::Escape) => return Ok(Broadcast::Exited),
+            _ => {}
+        }
+
+        // Failures are shown here instead of ending the session, the API server
+        // commonly rejects this when ephemeral containers aren't allowed.
+        if let Event::Finished(result) = event {
+            return match result {
+                Ok(()) => Ok(Broadcast::Exited),
+                Err(err) => {
+                    self.error = Some(err.to_string());
+
+                    Ok(Broadcast::Consumed)
+                }
+            };
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let border = Block::default().borders(Borders::ALL);
+
+        let area = {
+            let inner = border.inner(area);
+
+            frame.render_widget(border, area);
+
+            inner
+        };
+
+        let [_, area, _] = Layout::vertical([
+            Constraint::Fill(0),
+            Constraint::Length(4),
+            Constraint::Fill(0),
+        ])
+        .areas(area);
+
+        let [_, area, _] = Layout::horizontal([
+            Constraint::Max(10),
+            Constraint::Fill(0),
+            Constraint::Max(10),
+        ])
+        .areas(area);
+
+        let [input, status] =
+            Layout::vertical([Constraint::Length(3), Constraint::Length(1)]).areas(area);
+
+        let status_line = match &self.error {
+            Some(err) => Paragraph::new(format!("Unable to debug: {err}"))
+                .style(Style::default().fg(tailwind::RED.c300)),
+            None => Paragraph::new("enter: start a debug container and open a shell in it"),
+        };
+
+        frame.render_widget(status_line, status);
+
+        self.content.draw(frame, input)
+    }
+
+    fn zindex(&self) -> u16 {
+        1
+    }
+}
+
+struct Attach {
+    client: kube::Client,
+    pod: Arc<Pod>,
+    target: String,
+    image: String,
+}
+
+#[async_trait::async_trait]
+impl Raw for Attach {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(skip(self, stdin, stdout), fields(activity = "pod.debug"))]
+    async fn start(
+        &mut self,
+        stdin: &mut UnboundedReceiver<Event>,
+        mut stdout: Pin<Box<dyn AsyncWrite + Send + Unpin>>,
+    ) -> Result<()> {
+        let api = Api::<Pod>::namespaced(
+            self.client.clone(),
+            &self.pod.namespace().unwrap_or_default(),
+        );
+        let container = ephemeral::container(&self.image, &self.target);
+
+        stdout
+            .write_all(
+                format!(
+                    "Starting {} ({}) in {}...\r\n",
+                    container.name,
+                    self.image,
+                    self.pod.name_any()
+                )
+                .as_bytes(),
+            )
+            .await?;
+        stdout.flush().await?;
+
+        let pod = ephemeral::inject(&api, &self.pod.name_any(), &container).await?;
+
+        ExecBuilder::default()
+            .start(Utc::now())
+            .client(self.client.clone())
+            .pod(Arc::new(pod.clone()))
+            .container(Container::new(pod, ephemeral::as_container(&container)))
+            .cmd(COMMAND.to_string())
+            .build()?
+            .start(stdin, stdout)
+            .await
+    }
+}
@@ -0,0 +1,394 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use eyre::Result;
+use k8s_openapi::api::core::v1::Pod;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{palette::tailwind, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, List, ListState, Paragraph},
+    Frame,
+};
+use tokio::task::JoinHandle;
+
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    resources::{
+        container::{
+            browse::{self, Entry, Kind, Preview},
+            Container, ContainerExt,
+        },
+        pod::PodExt,
+        policy::Verb,
+    },
+    widget::{
+        nav::{move_cursor, Movement},
+        table,
+        tabs::Tab,
+        Widget, WIDGET_VIEWS,
+    },
+};
+
+/// Pick a container and then browse its filesystem. Directories can be walked
+/// through and text files previewed, anything else is best downloaded via.
+/// `scp` or `sftp`.
+pub struct Files {
+    view: table::Filtered,
+}
+
+impl Files {
+    pub fn new(client: kube::Client, pod: Arc<Pod>) -> Self {
+        WIDGET_VIEWS.container.list.inc();
+
+        let len = pod.as_ref().containers(None).len();
+
+        let mut view = table::Filtered::builder()
+            .table(table::Table::builder().items(pod.clone()).build())
+            .constructor(Browser::from_pod(client, pod))
+            .build();
+
+        if len == 1 {
+            view.select(0).expect("can select");
+        }
+
+        Self { view }
+    }
+
+    pub fn tab(name: String, client: kube::Client, pod: Arc<Pod>) -> Tab {
+        Tab::builder()
+            .name(name)
+            .verb(Verb::Exec)
+            .constructor(Box::new(move || {
+                Self::new(client.clone(), pod.clone()).boxed().into()
+            }))
+            .build()
+    }
+}
+
+impl Widget for Files {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        self.view.dispatch(event, buffer, area)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.view.draw(frame, area)
+    }
+}
+
+enum Contents {
+    Dir(PathBuf, Vec<Entry>),
+    File(PathBuf, Preview),
+}
+
+struct Browser {
+    client: kube::Client,
+    container: Container,
+
+    path: PathBuf,
+    entries: Vec<Entry>,
+    state: ListState,
+    preview: Option<(PathBuf, Preview)>,
+    scroll: u16,
+
+    task: Option<JoinHandle<Result<Contents>>>,
+    error: Option<String>,
+}
+
+impl Browser {
+    fn new(client: kube::Client, container: Container) -> Self {
+        WIDGET_VIEWS.container.files.inc();
+
+        let mut browser = Self {
+            client,
+            container,
+
+            path: PathBuf::from("/"),
+            entries: Vec::new(),
+            state: ListState::default(),
+            preview: None,
+            scroll: 0,
+
+            task: None,
+            error: None,
+        };
+
+        browser.open(PathBuf::from("/"), &Kind::Dir);
+
+        browser
+    }
+
+    fn from_pod(client: kube::Client, pod: Arc<Pod>) -> table::DetailFn {
+        Box::new(move |idx, filter| {
+            let containers = pod.containers(filter);
+
+            Ok(Self::new(client.clone(), containers[idx].clone()).boxed())
+        })
+    }
+
+    fn open(&mut self, path: PathBuf, kind: &Kind) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+
+        let client = self.client.clone();
+        let container = self.container.clone();
+        let kind = kind.clone();
+
+        self.task = Some(tokio::spawn(async move {
+            match kind {
+                Kind::Dir => Ok(Contents::Dir(
+                    path.clone(),
+                    browse::list(client, &container, &path).await?,
+                )),
+                // Links are followed as directories first, if that doesn't work they're
+                // probably pointing at a file.
+                Kind::Link(_) => match browse::list(client.clone(), &container, &path).await {
+                    Ok(entries) => Ok(Contents::Dir(path, entries)),
+                    Err(_) => Ok(Contents::File(
+                        path.clone(),
+                        browse::preview(client, &container, &path).await?,
+                    )),
+                },
+                Kind::File | Kind::Other => Ok(Contents::File(
+                    path.clone(),
+                    browse::preview(client, &container, &path).await?,
+                )),
+            }
+        }));
+    }
+
+    fn update(&mut self) {
+        let Some(task) = &mut self.task else {
+            return;
+        };
+
+        if !task.is_finished() {
+            return;
+        }
+
+        let result = futures::executor::block_on(async move { task.await? });
+        self.task = None;
+
+        match result {
+            Ok(Contents::Dir(path, entries)) => {
+                self.path = path;
+                self.entries = entries;
+                self.state.select((!self.entries.is_empty()).then_some(0));
+                self.error = None;
+            }
+            Ok(Contents::File(path, preview)) => {
+                self.preview = Some((path, preview));
+                self.scroll = 0;
+                self.error = None;
+            }
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+
+    fn selected(&self) -> Option<&Entry> {
+        self.state.selected().and_then(|idx| self.entries.get(idx))
+    }
+
+    // Where `scp` and `sftp` find a file, see the SFTP section of the docs.
+    fn remote(&self, path: &Path) -> String {
+        format!(
+            "/{}/{}/{}{}",
+            self.container.namespace().unwrap_or_default(),
+            self.container.pod_name(),
+            self.container.name_any(),
+            path.to_string_lossy(),
+        )
+    }
+
+    fn up(&mut self) {
+        if let Some(parent) = self.path.parent() {
+            self.open(parent.to_path_buf(), &Kind::Dir);
+        }
+    }
+
+    fn dispatch_preview(&mut self, key: &Keypress, area: Rect) -> Broadcast {
+        match key {
+            Keypress::Escape
+            | Keypress::Backspace
+            | Keypress::Delete
+            | Keypress::CursorLeft
+            | Keypress::Printable('h') => {
+                self.preview = None;
+            }
+            Keypress::Printable('y') => {
+                if let Some((path, _)) = &self.preview {
+                    return Broadcast::Clipboard(self.remote(path));
+                }
+            }
+            key => match move_cursor(key, area) {
+                Some(Movement::Y(y)) => self.scroll = self.scroll.saturating_add_signed(y),
+                _ => return Broadcast::Ignored,
+            },
+        }
+
+        Broadcast::Consumed
+    }
+
+    fn draw_preview(&mut self, frame: &mut Frame, area: Rect) {
+        let Some((path, preview)) = &self.preview else {
+            return;
+        };
+
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", path.to_string_lossy()))
+            .title_bottom(" y to copy the scp path, esc to go back ");
+
+        if preview.truncated {
+            block = block.title_bottom(
+                Line::from(format!(
+                    " only showing the first {} ",
+                    size(browse::PREVIEW_BYTES as u64)
+                ))
+                .style(Style::default().fg(tailwind::YELLOW.c300))
+                .right_aligned(),
+            );
+        }
+
+        let paragraph = match &preview.text {
+            Some(text) => Paragraph::new(text.as_str()).scroll((self.scroll, 0)),
+            None => Paragraph::new("Binary file, download it instead.")
+                .style(Style::default().fg(tailwind::GRAY.c500)),
+        };
+
+        frame.render_widget(paragraph.block(block), area);
+    }
+}
+
+fn size(bytes: u64) -> String {
+    let mut size = bytes;
+
+    for unit in ["B", "K", "M", "G"] {
+        if size < 1024 {
+            return format!("{size}{unit}");
+        }
+
+        size /= 1024;
+    }
+
+    format!("{size}T")
+}
+
+impl Widget for Browser {
+    fn dispatch(&mut self, event: &Event, _: &Buffer, area: Rect) -> Result<Broadcast> {
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        if self.preview.is_some() {
+            return Ok(self.dispatch_preview(key, area));
+        }
+
+        match key {
+            Keypress::Escape => return Ok(Broadcast::Exited),
+            Keypress::CursorUp | Keypress::Printable('k') => self.state.select_previous(),
+            Keypress::CursorDown | Keypress::Printable('j') => self.state.select_next(),
+            Keypress::Enter | Keypress::CursorRight | Keypress::Printable('l') => {
+                let Some(entry) = self.selected().cloned() else {
+                    return Ok(Broadcast::Consumed);
+                };
+
+                self.open(self.path.join(&entry.name), &entry.kind);
+            }
+            Keypress::Backspace
+            | Keypress::Delete
+            | Keypress::CursorLeft
+            | Keypress::Printable('h') => self.up(),
+            Keypress::Printable('y') => {
+                if let Some(entry) = self.selected() {
+                    return Ok(Broadcast::Clipboard(
+                        self.remote(&self.path.join(&entry.name)),
+                    ));
+                }
+            }
+            _ => return Ok(Broadcast::Ignored),
+        }
+
+        Ok(Broadcast::Consumed)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.update();
+
+        if self.preview.is_some() {
+            self.draw_preview(frame, area);
+
+            return Ok(());
+        }
+
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", self.path.to_string_lossy()))
+            .title_bottom(" enter to open, h to go up, y to copy the scp path ");
+
+        if let Some(err) = &self.error {
+            block = block.title_bottom(
+                Line::from(format!(" {err} "))
+                    .style(Style::default().fg(tailwind::RED.c300))
+                    .right_aligned(),
+            );
+        } else if self.task.is_some() {
+            block = block.title_bottom(Line::from(" loading… ").right_aligned());
+        }
+
+        let width = block.inner(area).width as usize;
+
+        let items: Vec<_> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let (name, style) = match &entry.kind {
+                    Kind::Dir => (
+                        format!("{}/", entry.name),
+                        Style::default().fg(tailwind::BLUE.c300),
+                    ),
+                    Kind::Link(target) => (
+                        format!("{} -> {target}", entry.name),
+                        Style::default().fg(tailwind::CYAN.c300),
+                    ),
+                    Kind::File => (entry.name.clone(), Style::default()),
+                    Kind::Other => (entry.name.clone(), Style::default().fg(tailwind::GRAY.c500)),
+                };
+
+                let size = match entry.kind {
+                    Kind::File => size(entry.size),
+                    _ => String::new(),
+                };
+
+                Line::from(format!(
+                    "{} {name:<pad$}{size:>6}",
+                    entry.mode,
+                    pad = width.saturating_sub(entry.mode.len() + 7),
+                ))
+                .style(style)
+            })
+            .collect();
+
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(block)
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+            area,
+            &mut self.state,
+        );
+
+        Ok(())
+    }
+}
+
+impl Drop for Browser {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
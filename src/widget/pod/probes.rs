@@ -0,0 +1,273 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use eyre::Result;
+use k8s_openapi::api::core::v1::{Event as KubeEvent, Pod};
+use kube::{api::ListParams, Api, ResourceExt};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{palette::tailwind, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use tokio::task::JoinHandle;
+
+use crate::{
+    events::{Broadcast, Event},
+    resources::{
+        age::Age,
+        probe::{self, Kind, ProbeExt},
+    },
+    widget::{
+        clock::Clock,
+        events::last_seen,
+        nav::{move_cursor, Movement},
+        tabs::Tab,
+        Widget, WIDGET_VIEWS,
+    },
+};
+
+// Probe failures are fetched again after this long, the countdowns are updated
+// on every frame.
+static REFRESH: Duration = Duration::from_secs(10);
+
+/// Probes configured for each container of a pod, when they'll run next and
+/// the most recent times they failed. Readiness gates are shown first as they
+/// also keep a pod from being ready.
+pub struct Probes {
+    pod: Arc<Pod>,
+    api: Api<KubeEvent>,
+
+    task: Option<JoinHandle<Result<Vec<KubeEvent>>>>,
+    failures: Option<Result<Vec<KubeEvent>, String>>,
+    fetched: Instant,
+    offset: u16,
+}
+
+impl Probes {
+    pub fn new(client: kube::Client, pod: Arc<Pod>) -> Self {
+        WIDGET_VIEWS.pod.probes.inc();
+
+        let api = Api::namespaced(client, pod.namespace().unwrap_or_default().as_str());
+
+        let mut probes = Self {
+            pod,
+            api,
+
+            task: None,
+            failures: None,
+            fetched: Instant::now(),
+            offset: 0,
+        };
+
+        probes.fetch();
+
+        probes
+    }
+
+    pub fn tab(name: String, client: kube::Client, pod: Arc<Pod>) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Self::new(client.clone(), pod.clone()).boxed().into()
+            }))
+            .build()
+    }
+
+    fn fetch(&mut self) {
+        let api = self.api.clone();
+        let params = ListParams::default().fields(&format!(
+            "involvedObject.uid={}",
+            self.pod.uid().unwrap_or_default()
+        ));
+
+        self.task = Some(tokio::spawn(async move {
+            let mut events: Vec<_> = api
+                .list(&params)
+                .await?
+                .items
+                .into_iter()
+                .filter(|ev| {
+                    ev.reason
+                        .as_deref()
+                        .is_some_and(|reason| probe::FAILURE_REASONS.contains(&reason))
+                })
+                .collect();
+
+            events.sort_by_key(|ev| std::cmp::Reverse(last_seen(ev)));
+
+            Ok(events)
+        }));
+    }
+
+    fn update(&mut self) {
+        if let Some(task) = &mut self.task {
+            if !task.is_finished() {
+                return;
+            }
+
+            let result = futures::executor::block_on(async move { task.await? });
+
+            self.failures = Some(result.map_err(|err| err.to_string()));
+            self.task = None;
+            self.fetched = Instant::now();
+
+            return;
+        }
+
+        if self.fetched.elapsed() > REFRESH {
+            self.fetch();
+        }
+    }
+
+    fn gates(&self) -> Vec<Line<'static>> {
+        let gates = probe::gates(&self.pod);
+
+        if gates.is_empty() {
+            return Vec::new();
+        }
+
+        std::iter::once(Line::from("Readiness Gates").bold())
+            .chain(gates.into_iter().map(|(condition, status)| {
+                let line = Line::from(format!("  {condition:<40}{status}"));
+
+                if status == "True" {
+                    line.style(Style::default().fg(tailwind::GREEN.c300))
+                } else {
+                    line.style(Style::default().fg(tailwind::RED.c300))
+                }
+            }))
+            .chain(std::iter::once(Line::default()))
+            .collect()
+    }
+
+    fn containers(&self) -> Vec<Line<'static>> {
+        let mut lines = Vec::new();
+
+        for container in self.pod.spec.iter().flat_map(|spec| &spec.containers) {
+            let started = probe::started(&self.pod, &container.name);
+
+            lines.push(
+                Line::from(match started {
+                    Some(started) => format!(
+                        "{} (started {} ago)",
+                        container.name,
+                        (Utc::now() - started).to_age()
+                    ),
+                    None => container.name.clone(),
+                })
+                .bold(),
+            );
+
+            for kind in Kind::all() {
+                let Some(probe) = kind.get(container) else {
+                    lines.push(
+                        Line::from(format!("  {:<10}not configured", kind.name()))
+                            .style(Style::default().fg(tailwind::GRAY.c500)),
+                    );
+
+                    continue;
+                };
+
+                lines.push(Line::from(vec![
+                    Span::from(format!("  {:<10}", kind.name())),
+                    Span::from(probe.handler()),
+                    Span::from(format!("  {}", probe::countdown(probe, started)))
+                        .style(Style::default().fg(tailwind::INDIGO.c300)),
+                ]));
+                lines.push(
+                    Line::from(format!("  {:<10}{}", "", probe.timing()))
+                        .style(Style::default().fg(tailwind::GRAY.c500)),
+                );
+            }
+
+            lines.push(Line::default());
+        }
+
+        lines
+    }
+
+    fn failures(&self) -> Vec<Line<'static>> {
+        let mut lines = vec![Line::from("Recent Failures").bold()];
+
+        let clock = Clock::current();
+        let width = clock.format.width() as usize;
+
+        match &self.failures {
+            None => lines.push(Line::from("  Loading...")),
+            Some(Err(err)) => lines.push(
+                Line::from(format!("  Unable to fetch events: {err}"))
+                    .style(Style::default().fg(tailwind::RED.c300)),
+            ),
+            Some(Ok(events)) if events.is_empty() => lines.push(Line::from("  None")),
+            Some(Ok(events)) => lines.extend(events.iter().map(|ev| {
+                Line::from(format!(
+                    "  {:<width$}x{:<5}{}",
+                    last_seen(ev).map_or(String::new(), |t| clock.format(t)),
+                    ev.count.unwrap_or(1),
+                    ev.message.clone().unwrap_or_default(),
+                ))
+                .style(Style::default().fg(tailwind::RED.c300))
+            })),
+        }
+
+        lines
+    }
+}
+
+impl Widget for Probes {
+    fn dispatch(&mut self, event: &Event, _: &Buffer, area: Rect) -> Result<Broadcast> {
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        if let Some(Movement::Y(y)) = move_cursor(key, area) {
+            self.offset = self.offset.saturating_add_signed(y);
+
+            return Ok(Broadcast::Consumed);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.update();
+
+        let lines: Vec<Line> = self
+            .gates()
+            .into_iter()
+            .chain(self.containers())
+            .chain(self.failures())
+            .collect();
+
+        #[allow(clippy::cast_possible_truncation)]
+        let max = lines.len().saturating_sub(1) as u16;
+        self.offset = self.offset.min(max);
+
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(Block::default().borders(Borders::ALL))
+                .scroll((self.offset, 0)),
+            area,
+        );
+
+        Ok(())
+    }
+
+    fn dirty(&self) -> bool {
+        self.task.as_ref().is_some_and(JoinHandle::is_finished)
+    }
+}
+
+impl Drop for Probes {
+    fn drop(&mut self) {
+        if let Some(task) = &self.task {
+            task.abort();
+        }
+    }
+}
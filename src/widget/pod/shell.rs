@@ -14,20 +14,26 @@ use prometheus::{histogram_opts, register_histogram, Histogram};
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Layout, Rect},
-    widgets::{Block, Borders},
+    style::{palette::tailwind, Style},
+    widgets::{Block, Borders, Paragraph},
     Frame,
 };
 use tokio::{
     io::{AsyncWrite, AsyncWriteExt},
     sync::mpsc::UnboundedReceiver,
+    task::JoinHandle,
 };
 use tokio_util::io::ReaderStream;
+use tracing::Instrument;
 
 use crate::{
+    audit::{self, ExecSession, TRANSCRIPT_LIMIT},
     events::{Broadcast, Event, Keypress},
+    history::History,
     resources::{
         container::{Container, ContainerExt},
         pod::PodExt,
+        policy::Verb,
         status::StatusExt,
     },
     widget::{input, input::ContentExt, propagate, table, tabs::Tab, Raw, Widget, WIDGET_VIEWS},
@@ -49,14 +55,14 @@ pub struct Shell {
 #[bon::bon]
 impl Shell {
     #[builder]
-    pub fn new(client: kube::Client, pod: Arc<Pod>) -> Self {
+    pub fn new(client: kube::Client, pod: Arc<Pod>, history: Arc<dyn History>) -> Self {
         WIDGET_VIEWS.container.list.inc();
 
         let len = pod.as_ref().containers(None).len();
 
         let mut view = table::Filtered::builder()
             .table(table::Table::builder().items(pod.clone()).build())
-            .constructor(Command::from_pod(client, pod))
+            .constructor(Command::from_pod(client, pod, history))
             .build();
 
         if len == 1 {
@@ -66,13 +72,20 @@ impl Shell {
         Self { view }
     }
 
-    pub fn tab(name: String, client: kube::Client, pod: Arc<Pod>) -> Tab {
+    pub fn tab(
+        name: String,
+        client: kube::Client,
+        pod: Arc<Pod>,
+        history: Arc<dyn History>,
+    ) -> Tab {
         Tab::builder()
             .name(name)
+            .verb(Verb::Exec)
             .constructor(Box::new(move || {
                 Self::builder()
                     .client(client.clone())
                     .pod(pod.clone())
+                    .history(history.clone())
                     .build()
                     .boxed()
                     .into()
@@ -93,19 +106,38 @@ impl Widget for Shell {
 
 static COMMAND: &str = "/bin/bash";
 
+enum Recorded {
+    Loading(JoinHandle<Result<Option<Vec<String>>>>),
+    Ready(Option<Vec<String>>),
+    Failed(String),
+}
+
 struct Command {
     client: kube::Client,
     pod: Arc<Pod>,
     container: Container,
     content: input::Text,
+
+    history: Arc<dyn History>,
+    recorded: Recorded,
+    // Index into the recorded commands, this is typed into the shell once it
+    // starts.
+    selected: Option<usize>,
 }
 
 impl Command {
-    pub fn new(client: kube::Client, pod: Arc<Pod>, container: Container) -> Self {
+    pub fn new(
+        client: kube::Client,
+        pod: Arc<Pod>,
+        container: Container,
+        history: Arc<dyn History>,
+    ) -> Self {
         WIDGET_VIEWS.container.cmd.inc();
 
         let name = container.name_any();
 
+        let loader = history.clone();
+
         Self {
             client,
             pod,
@@ -114,10 +146,18 @@ impl Command {
                 .title(name)
                 .content(input::Content::from_string(COMMAND))
                 .build(),
+
+            history,
+            recorded: Recorded::Loading(tokio::spawn(async move { loader.load().await })),
+            selected: None,
         }
     }
 
-    pub fn from_pod(client: kube::Client, pod: Arc<Pod>) -> table::DetailFn {
+    pub fn from_pod(
+        client: kube::Client,
+        pod: Arc<Pod>,
+        history: Arc<dyn History>,
+    ) -> table::DetailFn {
         Box::new(move |idx, filter| {
             let containers = pod.containers(filter);
 
@@ -125,10 +165,56 @@ impl Command {
                 client.clone(),
                 pod.clone(),
                 containers.get(idx).unwrap().clone(),
+                history.clone(),
             )
             .boxed())
         })
     }
+
+    fn update(&mut self) {
+        let Recorded::Loading(task) = &mut self.recorded else {
+            return;
+        };
+
+        if !task.is_finished() {
+            return;
+        }
+
+        self.recorded = match futures::executor::block_on(async move { task.await? }) {
+            Ok(commands) => Recorded::Ready(commands),
+            Err(err) => Recorded::Failed(err.to_string()),
+        };
+    }
+
+    fn commands(&self) -> &[String] {
+        match &self.recorded {
+            Recorded::Ready(Some(commands)) => commands,
+            _ => &[],
+        }
+    }
+
+    // Opting out also forgets everything, so the local copy is updated without
+    // waiting for the server.
+    fn toggle(&mut self) {
+        let history = self.history.clone();
+
+        self.selected = None;
+        self.recorded = match &self.recorded {
+            Recorded::Ready(Some(_)) => Recorded::Loading(tokio::spawn(async move {
+                history.disable().await?;
+
+                Ok(None)
+            })),
+            Recorded::Ready(None) | Recorded::Failed(_) => {
+                Recorded::Loading(tokio::spawn(async move {
+                    history.enable().await?;
+
+                    history.load().await
+                }))
+            }
+            Recorded::Loading(_) => return,
+        };
+    }
 }
 
 impl Widget for Command {
@@ -144,6 +230,8 @@ impl Widget for Command {
 
         match event.key() {
             Some(Keypress::Enter) => {
+                let recording = matches!(self.recorded, Recorded::Ready(Some(_)));
+
                 return Ok(Broadcast::Raw(Box::new(
                     ExecBuilder::default()
                         .start(Utc::now())
@@ -151,10 +239,31 @@ impl Widget for Command {
                         .pod(self.pod.clone())
                         .container(self.container.clone())
                         .cmd(cmd)
+                        .initial(self.selected.map(|i| self.commands()[i].clone()))
+                        .history(recording.then(|| self.history.clone()))
                         .build()?,
-                )))
+                )));
             }
             Some(Keypress::Escape) => return Ok(Broadcast::Exited),
+            Some(Keypress::CursorUp) => {
+                let len = self.commands().len();
+
+                if len > 0 {
+                    self.selected = Some(self.selected.map_or(0, |i| (i + 1).min(len - 1)));
+                }
+
+                return Ok(Broadcast::Consumed);
+            }
+            Some(Keypress::CursorDown) => {
+                self.selected = self.selected.and_then(|i| i.checked_sub(1));
+
+                return Ok(Broadcast::Consumed);
+            }
+            Some(Keypress::Control('s')) => {
+                self.toggle();
+
+                return Ok(Broadcast::Consumed);
+            }
             _ => {}
         };
 
@@ -180,7 +289,7 @@ impl Widget for Command {
 
         let [_, area, _] = Layout::vertical([
             Constraint::Fill(0),
-            Constraint::Length(3),
+            Constraint::Length(4),
             Constraint::Fill(0),
         ])
         .areas(area);
@@ -192,7 +301,30 @@ impl Widget for Command {
         ])
         .areas(area);
 
-        self.content.draw(frame, area)
+        self.update();
+
+        let [input, status] =
+            Layout::vertical([Constraint::Length(3), Constraint::Length(1)]).areas(area);
+
+        let status_line = match (&self.recorded, self.selected) {
+            (Recorded::Loading(_), _) => Paragraph::new("Loading history..."),
+            (Recorded::Failed(err), _) => Paragraph::new(format!("History unavailable: {err}"))
+                .style(Style::default().fg(tailwind::RED.c300)),
+            (Recorded::Ready(None), _) => {
+                Paragraph::new("ctrl-s: remember commands across sessions")
+            }
+            (Recorded::Ready(Some(_)), None) => {
+                Paragraph::new("↑/↓: recall a command, ctrl-s: forget and stop remembering")
+            }
+            (Recorded::Ready(Some(commands)), Some(i)) => {
+                Paragraph::new(format!("run: {}", commands[i]))
+                    .style(Style::default().fg(tailwind::GREEN.c300))
+            }
+        };
+
+        frame.render_widget(status_line, status);
+
+        self.content.draw(frame, input)
     }
 
     fn zindex(&self) -> u16 {
@@ -201,29 +333,190 @@ impl Widget for Command {
 }
 
 #[derive(Builder)]
-struct Exec {
+pub(crate) struct Exec {
     start: DateTime<Utc>,
     client: kube::Client,
     pod: Arc<Pod>,
     container: Container,
     cmd: String,
+    /// Typed into the shell once it has started.
+    #[builder(default)]
+    initial: Option<String>,
+    /// Where lines typed into the shell are recorded, unset when the user
+    /// hasn't opted in.
+    #[builder(default)]
+    history: Option<Arc<dyn History>>,
 }
 
-#[async_trait::async_trait]
-impl Raw for Exec {
-    #[allow(clippy::blocks_in_conditions)]
-    #[tracing::instrument(skip(self, stdin, stdout), fields(activity = "pod.exec"))]
-    async fn start(
+impl Exec {
+    fn record(&self, line: String) {
+        let Some(history) = self.history.clone() else {
+            return;
+        };
+
+        tokio::spawn(
+            async move {
+                if let Err(err) = history.push(line).await {
+                    tracing::warn!("unable to record command: {err}");
+                }
+            }
+            .in_current_span(),
+        );
+    }
+}
+
+/// What went through a shell, for the audit log.
+#[derive(Default)]
+struct Usage {
+    bytes_in: u64,
+    bytes_out: u64,
+    exit_code: Option<i32>,
+    // Only kept when transcripts are on.
+    transcript: Option<Vec<u8>>,
+}
+
+impl Usage {
+    fn input(&mut self, data: &[u8]) {
+        self.bytes_in += data.len() as u64;
+    }
+
+    fn output(&mut self, data: &[u8]) {
+        self.bytes_out += data.len() as u64;
+
+        if let Some(transcript) = self.transcript.as_mut() {
+            let room = TRANSCRIPT_LIMIT.saturating_sub(transcript.len());
+
+            transcript.extend_from_slice(&data[..data.len().min(room)]);
+        }
+    }
+}
+
+// Prompts that ask for something secret end with one of these, followed by a
+// colon. The answer isn't echoed and mustn't be recorded.
+static SECRET_PROMPTS: [&str; 3] = ["password", "passphrase", "pin"];
+
+// Only the end of the output is kept to match prompts against.
+static MAX_PROMPT: usize = 256;
+
+/// Approximates what is being typed into the shell so that it can be recorded.
+/// Lines that involve escape sequences (arrows, tab completion, etc.) can't be
+/// reconstructed and are skipped. So are answers to prompts for passwords,
+/// which are typed while echo is off.
+#[derive(Default)]
+struct Typed {
+    line: String,
+    dirty: bool,
+    // What the shell has written since its last newline, usually a prompt.
+    prompt: String,
+    escape: bool,
+    // Set when the line was started after a prompt for something secret.
+    secret: bool,
+}
+
+impl Typed {
+    // Echo can't be seen from here, the shell's terminal is in the pod, so
+    // prompts are the best guess at whether it's off.
+    fn output(&mut self, data: &[u8]) {
+        for c in String::from_utf8_lossy(data).chars() {
+            match c {
+                '\x1b' => self.escape = true,
+                // Good enough for colors and cursor movement.
+                c if self.escape => self.escape = !(c.is_ascii_alphabetic() || c == '~'),
+                '\r' | '\n' => self.prompt.clear(),
+                c if c.is_control() => {}
+                c => self.prompt.push(c),
+            }
+        }
+
+        if self.prompt.len() > MAX_PROMPT {
+            let mut start = self.prompt.len() - MAX_PROMPT;
+            while !self.prompt.is_char_boundary(start) {
+                start += 1;
+            }
+
+            self.prompt.drain(..start);
+        }
+    }
+
+    fn secret_prompt(&self) -> bool {
+        let prompt = self.prompt.trim_end().to_lowercase();
+        let Some(prompt) = prompt.strip_suffix(':') else {
+            return false;
+        };
+
+        // `[sudo] password for jane:` and `Enter passphrase for key 'id':`.
+        let prompt = prompt
+            .rsplit_once(" for ")
+            .map_or(prompt, |(start, _)| start);
+
+        prompt
+            .split_whitespace()
+            .last()
+            .is_some_and(|word| SECRET_PROMPTS.contains(&word))
+    }
+
+    fn feed(&mut self, data: &[u8]) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        for c in String::from_utf8_lossy(data).chars() {
+            if self.line.is_empty() && !self.dirty && !c.is_control() {
+                self.secret = self.secret_prompt();
+            }
+
+            match c {
+                '\r' | '\n' => {
+                    let line = std::mem::take(&mut self.line);
+                    let secret = std::mem::take(&mut self.secret) || self.secret_prompt();
+
+                    if !std::mem::take(&mut self.dirty) && !secret && !line.trim().is_empty() {
+                        lines.push(line.trim().to_string());
+                    }
+                }
+                '\x7f' | '\x08' => {
+                    self.line.pop();
+                }
+                // ctrl-c and ctrl-u throw the line away.
+                '\x03' | '\x15' => {
+                    self.line.clear();
+                    self.dirty = false;
+                }
+                c if c.is_control() => self.dirty = true,
+                c => self.line.push(c),
+            }
+        }
+
+        lines
+    }
+}
+
+impl Exec {
+    fn session(&self, usage: Usage) -> ExecSession {
+        ExecSession {
+            namespace: self.pod.namespace().unwrap_or_default(),
+            pod: self.pod.name_any(),
+            container: self.container.name_any(),
+            command: self.cmd.clone(),
+            started: self.start,
+            ended: Utc::now(),
+            exit_code: usage.exit_code,
+            bytes_in: usage.bytes_in,
+            bytes_out: usage.bytes_out,
+            transcript: usage
+                .transcript
+                .map(|transcript| String::from_utf8_lossy(&transcript).into_owned()),
+        }
+    }
+
+    async fn attach(
         &mut self,
         stdin: &mut UnboundedReceiver<Event>,
         mut stdout: Pin<Box<dyn AsyncWrite + Send + Unpin>>,
+        usage: &mut Usage,
     ) -> Result<()> {
-        WIDGET_VIEWS.container.exec.inc();
-
         let mut proc = Api::<Pod>::namespaced(self.client.clone(), &self.pod.namespace().unwrap())
             .exec(
                 &self.pod.name_any(),
-                vec![self.cmd.as_str()],
+                self.cmd.split_whitespace().collect::<Vec<_>>(),
                 &AttachParams {
                     container: Some(self.container.name_any().to_string()),
                     stdin: true,
@@ -240,6 +533,18 @@ impl Raw for Exec {
         let mut output = ReaderStream::new(proc.stdout().ok_or(eyre!("stdout not available"))?);
         let mut input = proc.stdin().ok_or(eyre!("stdin not available"))?;
 
+        if let Some(cmd) = &self.initial {
+            let line = format!("{cmd}\r");
+
+            input.write_all(line.as_bytes()).await?;
+            input.flush().await?;
+            usage.input(line.as_bytes());
+
+            self.record(cmd.clone());
+        }
+
+        let mut typed = Typed::default();
+
         // TODO: handle resize events.
         loop {
             tokio::select! {
@@ -254,6 +559,11 @@ impl Raw for Exec {
 
                     input.write_all(incoming.into()).await?;
                     input.flush().await?;
+                    usage.input(incoming.into());
+
+                    for line in typed.feed(incoming.into()) {
+                        self.record(line);
+                    }
 
                     if matches!(msg.key(), Some(Keypress::Control('b'))) {
                         break;
@@ -264,22 +574,23 @@ impl Raw for Exec {
                         break;
                     };
 
-                    stdout.write_all(&msg?).await?;
+                    let msg = msg?;
+
+                    typed.output(&msg);
+                    usage.output(&msg);
+                    stdout.write_all(&msg).await?;
                     stdout.flush().await?;
                 }
             }
         }
 
-        status
-            .await
-            .map(|status| {
-                if status.is_success() {
-                    Ok(())
-                } else {
-                    Err(status.into_report())
-                }
-            })
-            .ok_or(eyre!("status not available"))??;
+        let status = status.await.ok_or(eyre!("status not available"))?;
+
+        usage.exit_code = status.exit_code();
+
+        if !status.is_success() {
+            return Err(status.into_report());
+        }
 
         proc.join().await?;
 
@@ -287,6 +598,38 @@ impl Raw for Exec {
     }
 }
 
+#[async_trait::async_trait]
+impl Raw for Exec {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(skip(self, stdin, stdout), fields(activity = "pod.exec"))]
+    async fn start(
+        &mut self,
+        stdin: &mut UnboundedReceiver<Event>,
+        stdout: Pin<Box<dyn AsyncWrite + Send + Unpin>>,
+    ) -> Result<()> {
+        WIDGET_VIEWS.container.exec.inc();
+
+        // Only the command line and how the session went are audited, unless
+        // transcripts have been turned on for the server.
+        let audit = audit::current();
+        let mut usage = Usage {
+            transcript: audit
+                .as_ref()
+                .filter(|audit| audit.transcripts())
+                .map(|_| Vec::new()),
+            ..Usage::default()
+        };
+
+        let result = self.attach(stdin, stdout, &mut usage).await;
+
+        if let Some(audit) = audit {
+            audit.exec(self.session(usage));
+        }
+
+        result
+    }
+}
+
 impl Drop for Exec {
     fn drop(&mut self) {
         EXEC_DURATION.observe(
@@ -298,3 +641,27 @@ impl Drop for Exec {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(prompt: &str, input: &str) -> Vec<String> {
+        let mut typed = Typed::default();
+        typed.output(prompt.as_bytes());
+        typed.feed(input.as_bytes())
+    }
+
+    #[test]
+    fn records_commands() {
+        assert_eq!(typed("\x1b[32m~\x1b[0m $ ", "ls -la\r"), vec!["ls -la"]);
+    }
+
+    #[test]
+    fn skips_answers_to_secret_prompts() {
+        assert!(typed("Password: ", "hunter2\r").is_empty());
+        assert!(typed("[sudo] password for jane: ", "hunter2\r").is_empty());
+        assert!(typed("Enter passphrase for key '/root/.ssh/id':", "hunter2\r").is_empty());
+        assert!(typed("\r\nEnter PIN:\x1b[0m ", "1234\r").is_empty());
+    }
+}
@@ -0,0 +1,221 @@
+use std::{future::Future, sync::atomic::Ordering};
+
+use eyre::Result;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Margin, Rect},
+    style::{palette::tailwind, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use tokio::task::JoinHandle;
+
+use super::{nav::exit_keys, Widget};
+use crate::{
+    dashboard::context::Context,
+    events::{Broadcast, Event, Keypress},
+    resources::{
+        gitops,
+        policy::Policy,
+        preview::{self, Outcome},
+    },
+};
+
+/// Whether changes should be previewed before they're applied. Users can stop
+/// previewing for the rest of their session, unless a policy requires it.
+pub fn enabled() -> bool {
+    Policy::requires_preview()
+        || !Context::with(|context| context.skip_preview.load(Ordering::Relaxed))
+}
+
+enum Status {
+    Running(JoinHandle<Result<Vec<Outcome>>>),
+    Done(Result<Vec<Line<'static>>>),
+}
+
+/// Runs a change as a server side dry run and shows what it would do, so that
+/// it can be confirmed before it is applied for real. Confirming is
+/// [`Broadcast::Selected`] and cancelling is [`Broadcast::Exited`], the widget
+/// that owns this applies the change.
+pub struct Preview {
+    title: String,
+    status: Status,
+    scroll: u16,
+}
+
+impl Preview {
+    pub fn new(
+        title: String,
+        dry_run: impl Future<Output = Result<Vec<Outcome>>> + Send + 'static,
+    ) -> Self {
+        Self {
+            title,
+            status: Status::Running(tokio::spawn(dry_run)),
+            scroll: 0,
+        }
+    }
+
+    fn update(&mut self) {
+        let Status::Running(task) = &mut self.status else {
+            return;
+        };
+
+        if !task.is_finished() {
+            return;
+        }
+
+        let result = futures::executor::block_on(async move { task.await? });
+
+        self.status = Status::Done(result.map(|outcomes| lines(&outcomes)));
+    }
+
+    fn ready(&self) -> bool {
+        matches!(self.status, Status::Done(Ok(_)))
+    }
+}
+
+fn lines(outcomes: &[Outcome]) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+
+    for outcome in outcomes {
+        lines.push(
+            Line::from(outcome.target.clone()).style(Style::default().add_modifier(Modifier::BOLD)),
+        );
+
+        if let Some(manager) = outcome
+            .before
+            .as_ref()
+            .and_then(gitops::manager)
+            .filter(|manager| manager.reconciled)
+        {
+            lines.push(
+                Line::from(format!("! {}", manager.warning()))
+                    .style(Style::default().fg(tailwind::YELLOW.c300)),
+            );
+        }
+
+        if outcome.after.is_none() {
+            lines.push(Line::from("- deleted").style(Style::default().fg(tailwind::RED.c300)));
+
+            continue;
+        }
+
+        let diff = outcome.diff();
+
+        if diff.is_empty() {
+            lines.push(Line::from("  no changes").style(Style::default().fg(tailwind::GRAY.c500)));
+        }
+
+        lines.extend(styled(diff));
+    }
+
+    lines
+}
+
+/// Lines of a diff, such as from [`Outcome::diff`], colored by what happened
+/// to them.
+pub fn styled(diff: Vec<Option<preview::Line>>) -> Vec<Line<'static>> {
+    diff.into_iter()
+        .map(|line| match line {
+            Some(preview::Line::Same(text)) => Line::from(format!("  {text}")),
+            Some(preview::Line::Added(text)) => {
+                Line::from(format!("+ {text}")).style(Style::default().fg(tailwind::GREEN.c300))
+            }
+            Some(preview::Line::Removed(text)) => {
+                Line::from(format!("- {text}")).style(Style::default().fg(tailwind::RED.c300))
+            }
+            None => Line::from("  …").style(Style::default().fg(tailwind::GRAY.c500)),
+        })
+        .collect()
+}
+
+impl Widget for Preview {
+    fn dispatch(&mut self, event: &Event, _: &Buffer, _: Rect) -> Result<Broadcast> {
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        // Everything is consumed so that nothing underneath changes while a change
+        // is waiting to be confirmed.
+        match key {
+            exit_keys!() => return Ok(Broadcast::Exited),
+            Keypress::Enter if self.ready() => return Ok(Broadcast::Selected(0)),
+            Keypress::Printable('s') if self.ready() && !Policy::requires_preview() => {
+                Context::with(|context| context.skip_preview.store(true, Ordering::Relaxed));
+
+                return Ok(Broadcast::Selected(0));
+            }
+            Keypress::CursorUp | Keypress::Printable('k') => {
+                self.scroll = self.scroll.saturating_sub(1);
+            }
+            Keypress::CursorDown | Keypress::Printable('j') => {
+                self.scroll = self.scroll.saturating_add(1);
+            }
+            _ => {}
+        }
+
+        Ok(Broadcast::Consumed)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.update();
+
+        let area = area.inner(Margin::new(2, 1));
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Preview: {} ", self.title));
+
+        let [content, help] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(block.inner(area));
+
+        frame.render_widget(Clear, area);
+
+        let (body, footer) = match &self.status {
+            Status::Running(_) => (
+                Paragraph::new("Running a dry run..."),
+                "esc: cancel".to_string(),
+            ),
+            Status::Done(Err(err)) => (
+                Paragraph::new(format!("Rejected: {err}"))
+                    .style(Style::default().fg(tailwind::RED.c300)),
+                "esc: back".to_string(),
+            ),
+            Status::Done(Ok(lines)) => {
+                let last = u16::try_from(lines.len())
+                    .unwrap_or(u16::MAX)
+                    .saturating_sub(content.height);
+                self.scroll = self.scroll.min(last);
+
+                let mut footer = "enter: apply, ↑/↓: scroll, esc: cancel".to_string();
+                if !Policy::requires_preview() {
+                    footer.push_str(", s: apply and stop previewing");
+                }
+
+                (
+                    Paragraph::new(lines.clone()).scroll((self.scroll, 0)),
+                    footer,
+                )
+            }
+        };
+
+        frame.render_widget(body, content);
+        frame.render_widget(Paragraph::new(footer), help);
+        frame.render_widget(block, area);
+
+        Ok(())
+    }
+
+    fn zindex(&self) -> u16 {
+        1
+    }
+}
+
+impl Drop for Preview {
+    fn drop(&mut self) {
+        if let Status::Running(task) = &self.status {
+            task.abort();
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use eyre::{eyre, Result};
+use k8s_openapi::api::core::v1::PersistentVolume;
+use kube::ResourceExt;
+use ratatui::{buffer::Buffer, layout::Rect, Frame};
+use tokio::sync::oneshot;
+
+use super::{
+    bulk::Bulk,
+    events::Events,
+    filter::FilterBuilder,
+    loading::Loading,
+    metadata::Metadata,
+    nested, propagate, table,
+    tabs::{Tab, TabbedView},
+    view::{Element, View},
+    yaml::Yaml,
+    yank::Yank,
+    Widget, WIDGET_VIEWS,
+};
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    resources::{bulk::Operation, store::Store},
+};
+
+pub struct List {
+    view: View,
+    is_ready: oneshot::Receiver<()>,
+}
+
+#[bon::bon]
+impl List {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(skip(client), fields(activity = "pv.list"))]
+    #[builder]
+    pub fn new(client: kube::Client, filter: Option<String>) -> Self {
+        WIDGET_VIEWS.pv.list.inc();
+
+        let (volumes, is_ready) = Store::<PersistentVolume>::new(client.clone());
+        let table = table::Filtered::builder()
+            .table(
+                table::Table::builder()
+                    .items(volumes.clone())
+                    .filter(Rc::new(RefCell::new(filter)))
+                    .build(),
+            )
+            .constructor(Detail::from_store(client.clone(), volumes.clone()))
+            .bulk(Bulk::from_store(
+                client,
+                volumes.clone(),
+                &[Operation::Label],
+            ))
+            .fields(FilterBuilder::from_store(volumes))
+            .build();
+
+        let widgets = vec![
+            table.boxed().into(),
+            Element::builder()
+                .widget(Loading.boxed())
+                .ignore(true)
+                .build(),
+        ];
+
+        Self {
+            view: View::builder().widgets(widgets).build(),
+            is_ready,
+        }
+    }
+
+    pub fn tab(name: String, client: kube::Client, terminal: bool) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Element::builder()
+                    .widget(Self::builder().client(client.clone()).build().boxed())
+                    .terminal(terminal)
+                    .build()
+            }))
+            .build()
+    }
+}
+
+impl Widget for List {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if let Ok(()) = self.is_ready.try_recv() {
+            self.view.pop();
+        }
+
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        self.view.zindex()
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        let title = self.view.title();
+
+        if title.is_empty() {
+            vec!["persistentvolumes".to_string()]
+        } else {
+            title
+        }
+    }
+
+    fn filter(&self) -> Option<String> {
+        self.view.filter()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
+
+pub struct Detail {
+    view: TabbedView,
+}
+
+#[bon::bon]
+impl Detail {
+    #[builder]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn new(client: kube::Client, pv: Arc<PersistentVolume>) -> Self {
+        WIDGET_VIEWS.pv.detail.inc();
+
+        let view = TabbedView::builder()
+            .tabs(vec![
+                Yaml::tab("YAML".to_string(), pv.clone()),
+                Events::tab("Events".to_string(), client.clone(), pv.clone()),
+                Metadata::tab("Metadata".to_string(), client, pv.clone()),
+            ])
+            .yank(Yank::from_obj(pv.as_ref()))
+            .title(vec!["persistentvolumes".to_string(), pv.name_any()])
+            .build();
+
+        Self { view }
+    }
+
+    pub fn from_store(
+        client: kube::Client,
+        store: Arc<Store<PersistentVolume>>,
+    ) -> table::DetailFn {
+        Box::new(move |idx, filter| {
+            let pv = store
+                .get(idx, filter)
+                .ok_or_else(|| eyre!("persistent volume not found"))?;
+
+            Ok(Detail::builder()
+                .client(client.clone())
+                .pv(pv)
+                .build()
+                .boxed())
+        })
+    }
+}
+
+impl Widget for Detail {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        1
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        self.view.title()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
@@ -0,0 +1,413 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use k8s_openapi::api::{
+    apps::v1::{Deployment, ReplicaSet},
+    core::v1::Event as KubeEvent,
+};
+use kube::{api::DynamicObject, runtime::reflector::ObjectRef, Api, ResourceExt};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{palette::tailwind, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use serde::Serialize;
+use tokio::task::JoinHandle;
+
+use super::{
+    events::last_seen,
+    preview::{self, Preview},
+    tabs::Tab,
+    Widget, WIDGET_VIEWS_VEC,
+};
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    resources::{
+        age::Age,
+        policy::Verb,
+        preview::{diff, elide, Outcome},
+        rollout::{self, Revision},
+        store::Store,
+    },
+};
+
+// Events about the deployment shown once a rollback has started.
+static EVENTS: u16 = 5;
+
+enum Status {
+    Idle,
+    Invalid(String),
+    Running(i64, JoinHandle<Result<Deployment>>),
+    Done(Result<i64>),
+}
+
+/// The revisions of a deployment, with what rolling back to each would change
+/// in the pod template. Rolling back is always previewed first, then the
+/// deployment's events are followed so that the rollout can be watched.
+pub struct History {
+    client: kube::Client,
+    deployment: ObjectRef<Deployment>,
+    original: Arc<Deployment>,
+    deployments: Arc<Store<Deployment>>,
+    replicasets: Arc<Store<ReplicaSet>>,
+    events: Option<(Arc<Store<KubeEvent>>, DateTime<Utc>)>,
+
+    idx: usize,
+    scroll: u16,
+    status: Status,
+    preview: Option<(Preview, Revision)>,
+}
+
+impl History {
+    pub fn new(client: kube::Client, deployment: Arc<Deployment>) -> Self {
+        WIDGET_VIEWS_VEC
+            .with_label_values(&["deployment", "history"])
+            .inc();
+
+        let (deployments, _) = Store::<Deployment>::new(client.clone());
+        let (replicasets, _) = Store::<ReplicaSet>::new(client.clone());
+
+        Self {
+            client,
+            deployment: ObjectRef::from_obj(deployment.as_ref()),
+            original: deployment,
+            deployments,
+            replicasets,
+            events: None,
+
+            idx: 0,
+            scroll: 0,
+            status: Status::Idle,
+            preview: None,
+        }
+    }
+
+    pub fn tab(name: String, client: kube::Client, deployment: Arc<Deployment>) -> Tab {
+        Tab::builder()
+            .name(name)
+            .verb(Verb::Patch)
+            .constructor(Box::new(move || {
+                Self::new(client.clone(), deployment.clone()).boxed().into()
+            }))
+            .build()
+    }
+
+    // The store only has the deployment once it has synced, until then what the
+    // tab was opened with is as good as it gets.
+    fn latest(&self) -> Arc<Deployment> {
+        self.deployments
+            .find(&self.deployment)
+            .unwrap_or_else(|| self.original.clone())
+    }
+
+    fn api(&self) -> Api<Deployment> {
+        match self.original.namespace() {
+            Some(ns) => Api::namespaced(self.client.clone(), ns.as_str()),
+            None => Api::default_namespaced(self.client.clone()),
+        }
+    }
+
+    fn select(&mut self) {
+        if matches!(self.status, Status::Running(..)) {
+            return;
+        }
+
+        let deployment = self.latest();
+
+        let Some(revision) = rollout::revisions(&deployment, &self.replicasets)
+            .get(self.idx)
+            .cloned()
+        else {
+            return;
+        };
+
+        if rollout::current(&deployment) == Some(revision.number) {
+            self.status = Status::Invalid(format!("Already on revision {}", revision.number));
+
+            return;
+        }
+
+        let api = self.api();
+        let dry_revision = revision.clone();
+
+        let preview = Preview::new(
+            format!(
+                "roll back {} to revision {}",
+                deployment.name_any(),
+                revision.number
+            ),
+            async move {
+                let after = rollout::rollback(&api, &deployment, &dry_revision, true).await?;
+
+                Ok(vec![Outcome {
+                    target: format!(
+                        "{}/{}",
+                        deployment.namespace().unwrap_or_default(),
+                        deployment.name_any()
+                    ),
+                    before: Some(dynamic(deployment.as_ref())?),
+                    after: Some(dynamic(&after)?),
+                }])
+            },
+        );
+
+        self.preview = Some((preview, revision));
+    }
+
+    fn run(&mut self, revision: Revision) {
+        let api = self.api();
+        let deployment = self.latest();
+        let number = revision.number;
+
+        // Only events from now on are about the rollback, anything older is still
+        // in the events tab.
+        let (events, _) = Store::<KubeEvent>::scoped(
+            self.client.clone(),
+            &format!(
+                "involvedObject.uid={}",
+                deployment.uid().unwrap_or_default()
+            ),
+        );
+        self.events = Some((events, Utc::now()));
+
+        self.status = Status::Running(
+            number,
+            tokio::spawn(
+                async move { rollout::rollback(&api, &deployment, &revision, false).await },
+            ),
+        );
+    }
+
+    fn update(&mut self) {
+        let Status::Running(number, task) = &mut self.status else {
+            return;
+        };
+
+        if !task.is_finished() {
+            return;
+        }
+
+        let number = *number;
+        let result = futures::executor::block_on(async move { task.await? });
+
+        self.status = Status::Done(result.map(|_| number));
+    }
+
+    fn template(&self, revision: &Revision) -> Vec<Line<'static>> {
+        let current = self
+            .latest()
+            .spec
+            .as_ref()
+            .map(|spec| spec.template.clone());
+
+        let (Some(before), Some(after)) = (current, revision.template()) else {
+            return vec![Line::from("No pod template")];
+        };
+
+        let (Ok(before), Ok(after)) = (
+            serde_yaml::to_string(&before),
+            serde_yaml::to_string(&after),
+        ) else {
+            return vec![Line::from("Unable to read the pod template")];
+        };
+
+        let lines = elide(diff(&before, &after));
+
+        if lines.is_empty() {
+            return vec![Line::from("Same pod template as the current revision")
+                .style(Style::default().fg(tailwind::GRAY.c500))];
+        }
+
+        preview::styled(lines)
+    }
+
+    fn events(&self) -> Vec<Line<'static>> {
+        let Some((store, since)) = self.events.as_ref() else {
+            return Vec::new();
+        };
+
+        store
+            .items(None)
+            .iter()
+            .filter(|ev| last_seen(ev).is_some_and(|seen| seen >= *since))
+            .take(EVENTS.into())
+            .map(|ev| {
+                let line = Line::from(format!(
+                    "{:<20}{}",
+                    ev.reason.clone().unwrap_or_default(),
+                    ev.message.clone().unwrap_or_default()
+                ));
+
+                if ev.type_.as_deref() == Some("Warning") {
+                    line.style(Style::default().fg(tailwind::RED.c300))
+                } else {
+                    line
+                }
+            })
+            .collect()
+    }
+}
+
+fn dynamic<K: Serialize>(obj: &K) -> Result<DynamicObject> {
+    Ok(serde_json::from_value(serde_json::to_value(obj)?)?)
+}
+
+impl Widget for History {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        if let Some((preview, _)) = self.preview.as_mut() {
+            match preview.dispatch(event, buffer, area)? {
+                Broadcast::Selected(_) => {
+                    if let Some((_, revision)) = self.preview.take() {
+                        self.run(revision);
+                    }
+                }
+                Broadcast::Exited => self.preview = None,
+                _ => {}
+            }
+
+            return Ok(Broadcast::Consumed);
+        }
+
+        match key {
+            Keypress::CursorUp | Keypress::Printable('k') => {
+                self.idx = self.idx.saturating_sub(1);
+                self.scroll = 0;
+            }
+            Keypress::CursorDown | Keypress::Printable('j') => {
+                self.idx = self.idx.saturating_add(1);
+                self.scroll = 0;
+            }
+            Keypress::Printable('K') => self.scroll = self.scroll.saturating_sub(1),
+            Keypress::Printable('J') => self.scroll = self.scroll.saturating_add(1),
+            Keypress::Enter => self.select(),
+            _ => return Ok(Broadcast::Ignored),
+        }
+
+        Ok(Broadcast::Consumed)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.update();
+
+        let block = Block::default().borders(Borders::ALL);
+
+        let deployment = self.latest();
+        let current = rollout::current(&deployment);
+        let revisions = rollout::revisions(&deployment, &self.replicasets);
+        self.idx = self.idx.min(revisions.len().saturating_sub(1));
+
+        let events = self.events();
+
+        let [body, status, feedback] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(u16::try_from(events.len()).unwrap_or_default()),
+        ])
+        .areas(block.inner(area));
+
+        let [list, template] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Fill(1)]).areas(body);
+
+        let lines: Vec<Line> = revisions
+            .iter()
+            .enumerate()
+            .map(|(i, revision)| {
+                let marker = if current == Some(revision.number) {
+                    "*"
+                } else {
+                    " "
+                };
+
+                let line = Line::from(format!(
+                    "{marker}{:<5}{:<4}{:<8}{}",
+                    revision.number,
+                    revision.replicas(),
+                    revision.age().to_age(),
+                    revision
+                        .cause()
+                        .unwrap_or_else(|| revision.images().join(", ")),
+                ));
+
+                if i == self.idx {
+                    line.style(Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    line
+                }
+            })
+            .collect();
+
+        if lines.is_empty() {
+            frame.render_widget(Paragraph::new("No revisions"), list);
+        } else {
+            frame.render_widget(
+                Paragraph::new(lines).scroll((
+                    u16::try_from(
+                        self.idx
+                            .saturating_sub(list.height.saturating_sub(1).into()),
+                    )
+                    .unwrap_or_default(),
+                    0,
+                )),
+                list,
+            );
+        }
+
+        if let Some(revision) = revisions.get(self.idx) {
+            let lines = self.template(revision);
+
+            let last = u16::try_from(lines.len())
+                .unwrap_or(u16::MAX)
+                .saturating_sub(template.height);
+            self.scroll = self.scroll.min(last);
+
+            frame.render_widget(
+                Paragraph::new(lines)
+                    .block(Block::default().borders(Borders::LEFT))
+                    .scroll((self.scroll, 0)),
+                template,
+            );
+        }
+
+        let status_line = match &self.status {
+            Status::Idle => Paragraph::new(
+                "enter: roll back to the selected revision, J/K: scroll the template, *: current",
+            ),
+            Status::Invalid(err) => {
+                Paragraph::new(err.as_str()).style(Style::default().fg(tailwind::RED.c300))
+            }
+            Status::Running(number, _) => {
+                Paragraph::new(format!("Rolling back to revision {number}..."))
+            }
+            Status::Done(Ok(number)) => Paragraph::new(format!("Rolled back to revision {number}"))
+                .style(Style::default().fg(tailwind::GREEN.c300)),
+            Status::Done(Err(err)) => Paragraph::new(format!("Rejected: {err}"))
+                .style(Style::default().fg(tailwind::RED.c300)),
+        };
+
+        frame.render_widget(status_line, status);
+        frame.render_widget(Paragraph::new(events), feedback);
+        frame.render_widget(block, area);
+
+        if let Some((preview, _)) = self.preview.as_mut() {
+            preview.draw(frame, area)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for History {
+    fn drop(&mut self) {
+        if let Status::Running(_, task) = &self.status {
+            task.abort();
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use eyre::{eyre, Result};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    widgets::Paragraph,
+    Frame,
+};
+use tokio::sync::oneshot;
+
+use super::{
+    input,
+    link::Kind,
+    loading::Loading,
+    nested, propagate, table,
+    tabs::Tab,
+    view::{Element, View},
+    BoxWidget, Widget, WIDGET_VIEWS,
+};
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    history::History,
+    resources::{
+        saved::{expand, variables, SavedView},
+        store::Store,
+    },
+};
+
+pub type Variables = Arc<BTreeMap<String, String>>;
+
+/// Views that have been saved to the cluster as `SavedView` resources.
+pub struct List {
+    view: View,
+    is_ready: oneshot::Receiver<()>,
+}
+
+impl List {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(skip_all, fields(activity = "view.list"))]
+    pub fn new(client: kube::Client, history: Arc<dyn History>, variables: Variables) -> Self {
+        WIDGET_VIEWS.view.list.inc();
+
+        let (views, is_ready) = Store::<SavedView>::new(client.clone());
+        let table = table::Filtered::builder()
+            .table(table::Table::builder().items(views.clone()).build())
+            .constructor(Box::new(move |idx, filter| {
+                let view = views
+                    .get(idx, filter)
+                    .ok_or_else(|| eyre!("view not found"))?;
+
+                Ok(Open::new(client.clone(), history.clone(), &view, &variables)?.boxed())
+            }))
+            .build();
+
+        let widgets = vec![
+            table.boxed().into(),
+            Element::builder()
+                .widget(Loading.boxed())
+                .ignore(true)
+                .build(),
+        ];
+
+        Self {
+            view: View::builder().widgets(widgets).build(),
+            is_ready,
+        }
+    }
+
+    pub fn tab(
+        name: String,
+        client: kube::Client,
+        history: Arc<dyn History>,
+        variables: Variables,
+        terminal: bool,
+    ) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Element::builder()
+                    .widget(Self::new(client.clone(), history.clone(), variables.clone()).boxed())
+                    .terminal(terminal)
+                    .build()
+            }))
+            .build()
+    }
+}
+
+impl Widget for List {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if let Ok(()) = self.is_ready.try_recv() {
+            self.view.pop();
+        }
+
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        self.view.zindex()
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        let title = self.view.title();
+
+        if title.is_empty() {
+            vec!["views".to_string()]
+        } else {
+            title
+        }
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
+
+/// Opens a saved view. Variables that can't be filled in from the user's
+/// identity are asked for one at a time before the list is shown.
+struct Open {
+    client: kube::Client,
+    history: Arc<dyn History>,
+    kind: Kind,
+    filter: String,
+    values: BTreeMap<String, String>,
+
+    missing: Vec<String>,
+    input: Option<input::Text>,
+    list: Option<BoxWidget>,
+}
+
+impl Open {
+    fn new(
+        client: kube::Client,
+        history: Arc<dyn History>,
+        view: &SavedView,
+        known: &BTreeMap<String, String>,
+    ) -> Result<Self> {
+        let kind: Kind = view.spec.resource.parse()?;
+
+        // Prompted for in reverse, so that they can be popped off in order.
+        let mut missing: Vec<_> = variables(&view.spec.filter)
+            .into_iter()
+            .filter(|name| !known.contains_key(name))
+            .collect();
+        missing.reverse();
+
+        let mut open = Self {
+            client,
+            history,
+            kind,
+            filter: view.spec.filter.clone(),
+            values: known.clone(),
+
+            missing,
+            input: None,
+            list: None,
+        };
+
+        open.next();
+
+        Ok(open)
+    }
+
+    fn next(&mut self) {
+        if let Some(name) = self.missing.last() {
+            self.input = Some(
+                input::Text::builder()
+                    .title(format!("Value for ${{{name}}}"))
+                    .build(),
+            );
+
+            return;
+        }
+
+        let filter = expand(&self.filter, &self.values);
+
+        self.input = None;
+        self.list = Some(self.kind.list(
+            self.client.clone(),
+            self.history.clone(),
+            (!filter.is_empty()).then_some(filter),
+        ));
+    }
+
+    fn submit(&mut self) {
+        let (Some(name), Some(text)) = (self.missing.pop(), self.input.as_ref()) else {
+            return;
+        };
+
+        let value = text
+            .content()
+            .try_borrow()
+            .map(|content| content.clone().unwrap_or_default())
+            .unwrap_or_default();
+
+        self.values.insert(name, value);
+        self.next();
+    }
+}
+
+impl Widget for Open {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        if let Some(list) = self.list.as_mut() {
+            return list.dispatch(event, buffer, area);
+        }
+
+        let Some(text) = self.input.as_mut() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        if matches!(event.key(), Some(Keypress::Enter)) {
+            self.submit();
+
+            return Ok(Broadcast::Consumed);
+        }
+
+        if matches!(text.dispatch(event, buffer, area)?, Broadcast::Exited) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Consumed)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if let Some(list) = self.list.as_mut() {
+            return list.draw(frame, area);
+        }
+
+        let Some(text) = self.input.as_mut() else {
+            return Ok(());
+        };
+
+        let [editor, help] =
+            Layout::vertical([Constraint::Length(3), Constraint::Length(1)]).areas(area);
+
+        text.draw(frame, editor)?;
+        frame.render_widget(Paragraph::new(format!("filter: {}", self.filter)), help);
+
+        Ok(())
+    }
+
+    fn zindex(&self) -> u16 {
+        1
+    }
+
+    fn title(&self) -> Vec<String> {
+        self.list
+            .as_ref()
+            .map(|list| list.title())
+            .unwrap_or_default()
+    }
+
+    fn dirty(&self) -> bool {
+        self.list.as_ref().is_some_and(|list| list.dirty())
+    }
+}
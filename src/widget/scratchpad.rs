@@ -0,0 +1,127 @@
+use std::{cell::RefCell, rc::Rc};
+
+use eyre::Result;
+use k8s_openapi::api::core::v1::ConfigMap;
+use kube::{api::PostParams, Api, ResourceExt};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Position, Rect},
+    style::{palette::tailwind, Style},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use super::{Widget, WIDGET_VIEWS};
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    resources::{notes, policy::Verb},
+};
+
+/// What has been written in the scratchpad. This outlives the pane so that
+/// it can be saved once the session ends, see [`save`].
+#[derive(Debug, Default)]
+pub struct Note {
+    pub text: String,
+    /// Save to a `ConfigMap` when the session ends.
+    pub export: bool,
+}
+
+pub type Notes = Rc<RefCell<Note>>;
+
+/// Create a `ConfigMap` in the client's default namespace with the notes,
+/// returning where it ended up.
+pub async fn save(client: kube::Client, text: &str, user: Option<&str>) -> Result<String> {
+    let cm = Api::<ConfigMap>::default_namespaced(client)
+        .create(&PostParams::default(), &notes::config_map(text, user))
+        .await?;
+
+    Ok(format!(
+        "{}/{}",
+        cm.namespace().unwrap_or_default(),
+        cm.name_any()
+    ))
+}
+
+/// A pane to jot down findings during a session. Text is only ever added to
+/// or removed from the end.
+pub struct Scratchpad {
+    notes: Notes,
+}
+
+impl Scratchpad {
+    pub fn new(notes: Notes) -> Self {
+        WIDGET_VIEWS.view.scratchpad.inc();
+
+        Self { notes }
+    }
+}
+
+impl Widget for Scratchpad {
+    fn dispatch(&mut self, event: &Event, _: &Buffer, _: Rect) -> Result<Broadcast> {
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        let mut note = self.notes.try_borrow_mut()?;
+
+        match key {
+            Keypress::Escape => return Ok(Broadcast::Exited),
+            Keypress::Control('y') => return Ok(Broadcast::Clipboard(note.text.clone())),
+            Keypress::Control('s') if Verb::Create.allowed() => note.export = !note.export,
+            Keypress::Enter => note.text.push('\n'),
+            Keypress::Backspace | Keypress::Delete => {
+                note.text.pop();
+            }
+            Keypress::Printable(c) => note.text.push(*c),
+            _ => return Ok(Broadcast::Ignored),
+        }
+
+        Ok(Broadcast::Consumed)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let note = self.notes.try_borrow()?;
+
+        let block = Block::default().borders(Borders::ALL).title(" Notes ");
+        let [text, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(2)]).areas(block.inner(area));
+
+        let paragraph = Paragraph::new(note.text.as_str()).wrap(Wrap { trim: false });
+
+        // Keep the end, where text is being added, in view. A trailing newline
+        // isn't counted as a line by the paragraph but the cursor is on it.
+        #[allow(clippy::cast_possible_truncation)]
+        let lines = paragraph.line_count(text.width) as u16 + u16::from(note.text.ends_with('\n'));
+        let scroll = lines.saturating_sub(text.height);
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(paragraph.scroll((scroll, 0)), text);
+
+        let last = note.text.rsplit('\n').next().unwrap_or_default();
+        #[allow(clippy::cast_possible_truncation)]
+        frame.set_cursor_position(Position::new(
+            text.x + (last.chars().count() as u16 % text.width.max(1)),
+            text.y + lines.saturating_sub(1).min(text.height.saturating_sub(1)),
+        ));
+
+        let (export, style) = if note.export {
+            (
+                "saved to a ConfigMap when you exit",
+                Style::default().fg(tailwind::GREEN.c300),
+            )
+        } else {
+            ("not saved", Style::default().fg(tailwind::GRAY.c500))
+        };
+
+        frame.render_widget(
+            Paragraph::new(format!(
+                "{export}\nctrl-y: copy, ctrl-s: toggle save, esc: hide"
+            ))
+            .style(style),
+            status,
+        );
+        frame.render_widget(block, area);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use std::sync::LazyLock;
+
+use ansi_to_tui::IntoText;
+use eyre::Result;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    text::{Line, Text},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use regex::bytes::Regex;
+
+use super::{
+    nav::{exit_keys, Motion, Movement, Nav},
+    Widget, WIDGET_VIEWS,
+};
+use crate::events::{Broadcast, Event, Keypress};
+
+// Escape sequences that move the cursor, clear the screen or set the title.
+// There's no terminal to apply them to, only colors (`m`) are kept.
+static CONTROL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\x1b\[[0-9;?]*[A-Za-ln-z]|\x1b\][^\x07\x1b]*(\x07|\x1b\\)|\x1b[()][A-Za-z0-9]|\x1b[=>78]|\r")
+        .expect("valid regex")
+});
+
+/// What a raw mode widget, such as a shell, wrote before it exited. Starts at
+/// the bottom so that the last thing written is where the user left it.
+pub struct Scrollback {
+    text: Text<'static>,
+    // Lines up from the bottom.
+    offset: u16,
+    nav: Nav,
+}
+
+impl Scrollback {
+    pub fn new(output: &[u8]) -> Result<Self> {
+        WIDGET_VIEWS.view.scrollback.inc();
+
+        Ok(Self {
+            text: CONTROL.replace_all(output, b"".as_slice()).into_text()?,
+            offset: 0,
+            nav: Nav::default(),
+        })
+    }
+}
+
+impl Widget for Scrollback {
+    fn dispatch(&mut self, event: &Event, _: &Buffer, area: Rect) -> Result<Broadcast> {
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        if matches!(
+            key,
+            exit_keys!() | Keypress::Enter | Keypress::Printable('q')
+        ) {
+            return Ok(Broadcast::Exited);
+        }
+
+        if let Motion::Move(Movement::Y(y)) = self.nav.motion(key, area) {
+            self.offset = self.offset.saturating_add_signed(-y);
+        }
+
+        Ok(Broadcast::Consumed)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Output")
+            .title_bottom(Line::from("esc, q or enter to return").right_aligned());
+
+        let height = block.inner(area).height;
+        let lines = u16::try_from(self.text.lines.len()).unwrap_or(u16::MAX);
+        let bottom = lines.saturating_sub(height);
+
+        self.offset = self.offset.min(bottom);
+
+        frame.render_widget(
+            Paragraph::new(self.text.clone())
+                .block(block)
+                .scroll((bottom - self.offset, 0)),
+            area,
+        );
+
+        Ok(())
+    }
+
+    fn title(&self) -> Vec<String> {
+        vec!["output".to_string()]
+    }
+}
@@ -0,0 +1,236 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use eyre::Result;
+use ratatui::{
+    layout::Rect,
+    style::{palette::tailwind, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use super::{
+    locale::{t, t_with},
+    Widget,
+};
+use crate::{
+    dashboard::context::Context,
+    resources::{age::Age, selector::Selector},
+};
+
+// Slow calls to the API server are called out for this long after they finish.
+static SLOW_SHOWN_FOR: Duration = Duration::from_secs(30);
+
+// Terminals without OSC 52 drop what was copied, this is how long there is to
+// select it from the status bar instead.
+static COPIED_SHOWN_FOR: Duration = Duration::from_secs(10);
+
+// Expiring access is called out once it gets this close.
+static EXPIRES_WARNING: TimeDelta = TimeDelta::minutes(15);
+
+/// Something running in the background, such as a bulk operation, shown in the
+/// status bar until it is dropped. It can be moved into a task, it counts
+/// against the dashboard it was created on.
+pub struct Pending(Arc<AtomicUsize>);
+
+impl Pending {
+    pub fn start() -> Self {
+        Context::with(|context| {
+            context.pending.fetch_add(1, Ordering::Relaxed);
+
+            Self(context.pending.clone())
+        })
+    }
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Show `text` in the status bar for a little while after it has been put on
+/// the clipboard.
+pub fn copied(text: String) {
+    Context::with(|context| *context.copied.lock().unwrap() = Some((Instant::now(), text)));
+}
+
+/// The last call to the API server that took unusually long, shown in the
+/// status bar so that it's clear the cluster is what's slow. It is shared
+/// between a dashboard and the client that it makes requests with.
+#[derive(Clone, Default)]
+pub struct Slow(Arc<Mutex<Option<(Instant, String, Duration)>>>);
+
+impl Slow {
+    pub fn record(&self, call: String, elapsed: Duration) {
+        *self.0.lock().unwrap() = Some((Instant::now(), call, elapsed));
+    }
+
+    fn recent(&self) -> Option<(String, Duration)> {
+        self.0
+            .lock()
+            .unwrap()
+            .as_ref()
+            .filter(|(at, ..)| at.elapsed() < SLOW_SHOWN_FOR)
+            .map(|(_, call, elapsed)| (call.clone(), *elapsed))
+    }
+}
+
+/// The bottom line of the dashboard. Where the session is connected to and as
+/// who, what is being looked at and anything still going on in the background.
+pub struct StatusBar {
+    cluster: Option<String>,
+    user: Option<String>,
+    started: Instant,
+    filter: Option<String>,
+    expires: Option<DateTime<Utc>>,
+}
+
+impl StatusBar {
+    pub fn new(cluster: Option<String>, user: Option<String>) -> Self {
+        Self {
+            cluster,
+            user,
+            started: Instant::now(),
+            filter: None,
+            expires: None,
+        }
+    }
+
+    /// The filter of whatever is being shown, see [`Widget::filter`].
+    pub fn update(&mut self, filter: Option<String>) {
+        self.filter = filter;
+    }
+
+    /// When the user's access runs out, see `Event::Expires`.
+    pub fn expires(&mut self, at: DateTime<Utc>) {
+        self.expires = Some(at);
+    }
+
+    fn segments(&self) -> Vec<Span<'static>> {
+        let mut segments = Vec::new();
+
+        // First, so that long commands are cut off as little as possible.
+        if let Some(text) = Context::with(|context| {
+            context
+                .copied
+                .lock()
+                .unwrap()
+                .as_ref()
+                .filter(|(at, _)| at.elapsed() < COPIED_SHOWN_FOR)
+                .map(|(_, text)| text.clone())
+        }) {
+            segments.push(Span::styled(
+                t_with("status.copied", &[("text", &text)]),
+                Style::default().fg(tailwind::EMERALD.c300),
+            ));
+        }
+
+        if let Some(cluster) = self.cluster.as_ref() {
+            segments.push(Span::styled(
+                cluster.clone(),
+                Style::default().fg(tailwind::BLUE.c300),
+            ));
+        }
+
+        if let Some(user) = self.user.as_ref() {
+            segments.push(Span::raw(user.clone()));
+        }
+
+        let filter = self.filter.as_deref().unwrap_or_default();
+        let namespace = Selector::from(filter)
+            .namespace()
+            .map_or_else(|| t("status.all"), ToString::to_string);
+
+        segments.push(Span::raw(t_with(
+            "status.namespace",
+            &[("namespace", &namespace)],
+        )));
+
+        if !filter.is_empty() {
+            segments.push(Span::raw(t_with("status.filter", &[("filter", &filter)])));
+        }
+
+        segments.push(Span::raw(
+            TimeDelta::from_std(self.started.elapsed())
+                .unwrap_or_default()
+                .to_age(),
+        ));
+
+        let pending = Context::with(|context| context.pending.load(Ordering::Relaxed));
+        if pending > 0 {
+            segments.push(Span::styled(
+                t_with("status.pending", &[("count", &pending)]),
+                Style::default().fg(tailwind::AMBER.c300),
+            ));
+        }
+
+        if let Some((call, elapsed)) = Context::with(|context| context.slow.recent()) {
+            segments.push(Span::styled(
+                t_with(
+                    "status.slow",
+                    &[
+                        ("call", &call),
+                        ("elapsed", &format!("{:.1}s", elapsed.as_secs_f64())),
+                    ],
+                ),
+                Style::default().fg(tailwind::ORANGE.c300),
+            ));
+        }
+
+        if Context::with(|context| context.keys_read_only) {
+            segments.push(Span::styled(
+                t("status.keys_read_only"),
+                Style::default().fg(tailwind::AMBER.c300),
+            ));
+        }
+
+        if let Some(expires) = self.expires {
+            let remaining = expires - Utc::now();
+
+            segments.push(Span::styled(
+                t_with("status.expires", &[("remaining", &remaining.to_age())]),
+                Style::default().fg(if remaining < EXPIRES_WARNING {
+                    tailwind::YELLOW.c300
+                } else {
+                    tailwind::SLATE.c400
+                }),
+            ));
+        }
+
+        segments
+    }
+}
+
+impl Widget for StatusBar {
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let separator = Span::styled(" │ ", Style::default().fg(tailwind::SLATE.c500));
+
+        let mut spans = vec![Span::raw(" ")];
+        for (i, segment) in self.segments().into_iter().enumerate() {
+            if i > 0 {
+                spans.push(separator.clone());
+            }
+
+            spans.push(segment);
+        }
+
+        frame.render_widget(
+            Paragraph::new(Line::from(spans)).style(
+                Style::default()
+                    .bg(tailwind::SLATE.c800)
+                    .fg(tailwind::SLATE.c200),
+            ),
+            area,
+        );
+
+        Ok(())
+    }
+}
@@ -1,4 +1,10 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    iter,
+    rc::Rc,
+};
 
 use eyre::Result;
 use lazy_static::lazy_static;
@@ -8,23 +14,35 @@ use ratatui::{
     layout::{Constraint, Rect},
     style,
     style::{palette::tailwind, Modifier, Stylize},
+    text::{Line, Span},
     widgets::{self, Block, Borders, TableState},
     Frame,
 };
 use tachyonfx::{fx, EffectTimer, Interpolation};
 
 use super::{
+    caps::Capabilities,
     error::Error,
-    input::Text,
-    nav::{move_cursor, Movement},
+    filter::FilterBuilder,
+    input::{self, Text},
+    keys::Action,
+    locale::{t, t_with},
+    narration,
+    nav::{Motion, Movement, Nav},
+    nested,
     view::{Element, View},
+    yank::Yank,
     BoxWidget, Widget,
 };
 use crate::{
     events::{Broadcast, Event, Keypress},
     fx::Animated,
+    resources::selector::Fields,
 };
 
+// Key of the group for rows that don't have one, this is what `kubectl` shows.
+static NO_GROUP: &str = "<none>";
+
 lazy_static! {
     static ref TABLE_FILTER: IntCounter = register_int_counter!(
         "table_filter_total",
@@ -41,12 +59,170 @@ pub trait Row {
     }
 
     fn row(&self, style: &RowStyle) -> widgets::Row;
+
+    /// Stable identity of the row, used to mark it for bulk actions. Rows
+    /// without one can't be marked.
+    fn id(&self) -> Option<String> {
+        None
+    }
+
+    /// Key of the group the row is shown under, see [`Group`]. Rows without
+    /// one end up together in `<none>`.
+    fn group(&self, _: Group) -> Option<String> {
+        None
+    }
+
+    /// What can be copied about the row, see [`Yank`].
+    fn yank(&self) -> Option<Yank> {
+        None
+    }
+
+    /// Used to summarize the rows of a group in its header.
+    fn health(&self) -> Health {
+        Health::Normal
+    }
+
+    /// Where the row goes relative to `other` when sorted by `sort`. Rows that
+    /// are equal keep the order of their items.
+    fn compare(&self, _: &Self, _: Sort) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+/// What the rows of a table can be collapsed under.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Group {
+    Node,
+    Namespace,
+    /// The workload, such as a deployment, that created the row.
+    Owner,
+}
+
+impl Group {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Node => "node",
+            Self::Namespace => "namespace",
+            Self::Owner => "owner",
+        }
+    }
+}
+
+/// Orders rows can be shown in instead of the order of their items, which is
+/// usually by namespace and name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sort {
+    /// Problems first, such as pods that are crashing.
+    Severity,
+    /// The most restarts first.
+    Restarts,
+    /// The newest first.
+    Age,
+}
+
+impl Sort {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Severity => "severity",
+            Self::Restarts => "restarts",
+            Self::Age => "age",
+        }
+    }
+}
+
+struct Header {
+    key: String,
+    collapsed: bool,
+    count: usize,
+    healthy: usize,
+    unhealthy: usize,
+}
+
+impl Header {
+    fn line(&self, style: &RowStyle) -> Line<'_> {
+        let mut spans = vec![Span::from(format!(
+            "{} {} ({})",
+            if self.collapsed { '▸' } else { '▾' },
+            self.key,
+            self.count
+        ))
+        .bold()];
+
+        for (health, count) in [
+            (Health::Healthy, self.healthy),
+            (Health::Unhealthy, self.unhealthy),
+        ] {
+            if count > 0 {
+                spans.push(Span::styled(
+                    format!("  {} {count}", health.symbol()),
+                    style.get(health),
+                ));
+            }
+        }
+
+        Line::from(spans)
+    }
+}
+
+// A line of the table, when grouped the items are interleaved with headers.
+enum Entry {
+    Header(Header),
+    Item(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Health {
+    Healthy,
+    Unhealthy,
+    Normal,
+}
+
+impl Health {
+    fn symbol(self) -> char {
+        match self {
+            Self::Healthy => '✓',
+            Self::Unhealthy => '✗',
+            Self::Normal => '·',
+        }
+    }
 }
 
 pub struct RowStyle {
     pub healthy: style::Style,
     pub unhealthy: style::Style,
     pub normal: style::Style,
+    symbols: bool,
+}
+
+impl RowStyle {
+    fn patch(&self, style: style::Style) -> Self {
+        Self {
+            healthy: self.healthy.patch(style),
+            unhealthy: self.unhealthy.patch(style),
+            normal: self.normal.patch(style),
+            symbols: self.symbols,
+        }
+    }
+
+    pub fn get(&self, health: Health) -> style::Style {
+        match health {
+            Health::Healthy => self.healthy,
+            Health::Unhealthy => self.unhealthy,
+            Health::Normal => self.normal,
+        }
+    }
+
+    /// Text for a status cell. When the client asked for symbols, they are
+    /// added in front so that the status doesn't depend on color alone.
+    pub fn status(&self, health: Health, text: impl Into<String>) -> String {
+        let text = text.into();
+
+        if self.symbols {
+            format!("{} {text}", health.symbol())
+        } else {
+            text
+        }
+    }
 }
 
 impl Default for RowStyle {
@@ -55,6 +231,7 @@ impl Default for RowStyle {
             healthy: style::Style::default().fg(tailwind::GREEN.c300),
             unhealthy: style::Style::default().fg(tailwind::RED.c300),
             normal: style::Style::default().fg(tailwind::INDIGO.c300),
+            symbols: Capabilities::current().symbols,
         }
     }
 }
@@ -63,6 +240,7 @@ pub struct Style {
     pub border: style::Style,
     pub header: style::Style,
     pub selected: style::Style,
+    pub marked: style::Style,
     pub row: RowStyle,
 }
 
@@ -74,6 +252,7 @@ impl Default for Style {
             selected: style::Style::default()
                 .add_modifier(Modifier::REVERSED)
                 .bg(tailwind::GRAY.c700),
+            marked: style::Style::default().bg(tailwind::INDIGO.c950),
             row: RowStyle::default(),
         }
     }
@@ -86,8 +265,39 @@ where
     type Item;
 
     fn items(&self, filter: Option<String>) -> Vec<Self::Item>;
+
+    /// Column widths, for items that only know their columns at runtime.
+    fn widths(&self) -> Vec<Constraint> {
+        Self::Item::constraints()
+    }
+
+    /// The header, see [`Items::widths`].
+    fn heading(&self) -> Option<widgets::Row<'static>> {
+        Self::Item::header()
+    }
+
+    /// Goes up whenever the items change, such as with [`Store::version`].
+    /// Tables only read the items again when it does or their filter changes,
+    /// items without a version are read on every frame.
+    ///
+    /// [`Store::version`]: crate::resources::store::Store::version
+    fn version(&self) -> Option<u64> {
+        None
+    }
+}
+
+// Items as of the last time they were read, see [`Items::version`].
+struct Rows<T> {
+    version: u64,
+    filter: Option<String>,
+    items: Rc<Vec<T>>,
 }
 
+/// IDs of the rows that have been marked, see [`Row::id`].
+pub type Marked = Rc<RefCell<BTreeSet<String>>>;
+/// ID of a row to move the cursor to once it shows up, see [`Row::id`].
+pub type Focus = Rc<RefCell<Option<String>>>;
+
 pub struct Table<S>
 where
     S: Items,
@@ -101,7 +311,18 @@ where
     // Internal state
     items: S,
     view: TableState,
+    nav: Nav,
     filter: Rc<RefCell<Option<String>>>,
+    marked: Marked,
+    focus: Focus,
+    groups: &'static [Group],
+    group: Option<Group>,
+    collapsed: BTreeSet<String>,
+    sorts: &'static [Sort],
+    sort: Option<Sort>,
+    rows: RefCell<Option<Rows<S::Item>>>,
+    // The order of the last items that were sorted, see `Table::order`.
+    sorted: RefCell<Option<(Rc<Vec<S::Item>>, Option<Sort>, Rc<Vec<usize>>)>>,
 
     _phantom: std::marker::PhantomData<S>,
 }
@@ -119,7 +340,13 @@ where
         #[builder(default = true)] selected: bool,
         items: S,
         #[builder(default)] filter: Rc<RefCell<Option<String>>>,
+        #[builder(default)] marked: Marked,
+        #[builder(default)] focus: Focus,
         #[builder(default = Borders::ALL)] border: Borders,
+        #[builder(default)] groups: &'static [Group],
+        /// The first one is what the table starts out sorted by.
+        #[builder(default)]
+        sorts: &'static [Sort],
     ) -> Self {
         let view = if selected {
             TableState::default().with_selected(0)
@@ -133,8 +360,18 @@ where
             highlight,
             items,
             view,
+            nav: Nav::default(),
             filter,
+            marked,
+            focus,
             border,
+            groups,
+            group: None,
+            collapsed: BTreeSet::new(),
+            sorts,
+            sort: sorts.first().copied(),
+            rows: RefCell::default(),
+            sorted: RefCell::default(),
             _phantom: std::marker::PhantomData,
         }
     }
@@ -142,6 +379,248 @@ where
     pub fn filter(&self) -> Rc<RefCell<Option<String>>> {
         self.filter.clone()
     }
+
+    pub fn marked(&self) -> Marked {
+        self.marked.clone()
+    }
+
+    fn items(&self) -> Result<Rc<Vec<S::Item>>> {
+        let filter = self.filter.try_borrow()?.clone();
+
+        let Some(version) = self.items.version() else {
+            return Ok(Rc::new(self.items.items(filter)));
+        };
+
+        let mut rows = self.rows.try_borrow_mut()?;
+
+        if let Some(rows) = rows
+            .as_ref()
+            .filter(|rows| rows.version == version && rows.filter == filter)
+        {
+            return Ok(rows.items.clone());
+        }
+
+        let items = Rc::new(self.items.items(filter.clone()));
+
+        *rows = Some(Rows {
+            version,
+            filter,
+            items: items.clone(),
+        });
+
+        Ok(items)
+    }
+
+    // Indices of `items` in the order they're shown. Sorting only happens again
+    // once the items or the sort change.
+    fn order(&self, items: &Rc<Vec<S::Item>>) -> Rc<Vec<usize>> {
+        if let Some((sorted, sort, order)) = self.sorted.borrow().as_ref() {
+            if Rc::ptr_eq(sorted, items) && *sort == self.sort {
+                return order.clone();
+            }
+        }
+
+        let mut order: Vec<_> = (0..items.len()).collect();
+
+        if let Some(sort) = self.sort {
+            order.sort_by(|a, b| items[*a].compare(&items[*b], sort));
+        }
+
+        let order = Rc::new(order);
+
+        *self.sorted.borrow_mut() = Some((items.clone(), self.sort, order.clone()));
+
+        order
+    }
+
+    // Headers sort by key, rows keep their order within each group.
+    fn entries(&self, items: &Rc<Vec<S::Item>>) -> Vec<Entry> {
+        let order = self.order(items);
+
+        let Some(group) = self.group else {
+            return order.iter().copied().map(Entry::Item).collect();
+        };
+
+        let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+
+        for &i in order.iter() {
+            groups
+                .entry(
+                    items[i]
+                        .group(group)
+                        .unwrap_or_else(|| NO_GROUP.to_string()),
+                )
+                .or_default()
+                .push(i);
+        }
+
+        groups
+            .into_iter()
+            .flat_map(|(key, rows)| {
+                let collapsed = self.collapsed.contains(&key);
+                let count = |health| {
+                    rows.iter()
+                        .filter(|i| items[**i].health() == health)
+                        .count()
+                };
+
+                let header = Header {
+                    collapsed,
+                    count: rows.len(),
+                    healthy: count(Health::Healthy),
+                    unhealthy: count(Health::Unhealthy),
+                    key,
+                };
+
+                iter::once(Entry::Header(header)).chain(
+                    rows.into_iter()
+                        .filter(move |_| !collapsed)
+                        .map(Entry::Item),
+                )
+            })
+            .collect()
+    }
+
+    fn selected(&self) -> Result<(Rc<Vec<S::Item>>, Option<Entry>)> {
+        let items = self.items()?;
+        let entry = self
+            .entries(&items)
+            .into_iter()
+            .nth(self.view.selected().unwrap_or_default());
+
+        Ok((items, entry))
+    }
+
+    fn cycle_group(&mut self) {
+        self.group = match self.group {
+            None => self.groups.first().copied(),
+            Some(group) => self
+                .groups
+                .iter()
+                .skip_while(|g| **g != group)
+                .nth(1)
+                .copied(),
+        };
+
+        self.collapsed.clear();
+        self.view.select(Some(0));
+    }
+
+    fn cycle_sort(&mut self) {
+        self.sort = match self.sort {
+            None => self.sorts.first().copied(),
+            Some(sort) => self
+                .sorts
+                .iter()
+                .skip_while(|s| **s != sort)
+                .nth(1)
+                .copied(),
+        };
+
+        self.view.select(Some(0));
+    }
+
+    // Folds the group that is selected, or the one the selected row is in.
+    fn toggle_group(&mut self) -> Result<()> {
+        let Some(group) = self.group else {
+            return Ok(());
+        };
+
+        let key = match self.selected()? {
+            (_, Some(Entry::Header(header))) => header.key,
+            (items, Some(Entry::Item(idx))) => items[idx]
+                .group(group)
+                .unwrap_or_else(|| NO_GROUP.to_string()),
+            (_, None) => return Ok(()),
+        };
+
+        if !self.collapsed.remove(&key) {
+            self.collapsed.insert(key.clone());
+        }
+
+        // Keep the cursor on the group instead of whatever moved under it.
+        let items = self.items()?;
+        let position = self
+            .entries(&items)
+            .iter()
+            .position(|entry| matches!(entry, Entry::Header(header) if header.key == key));
+
+        self.view.select(position);
+
+        Ok(())
+    }
+
+    fn toggle_groups(&mut self) -> Result<()> {
+        let items = self.items()?;
+        let keys: BTreeSet<_> = self
+            .entries(&items)
+            .into_iter()
+            .filter_map(|entry| match entry {
+                Entry::Header(header) => Some(header.key),
+                Entry::Item(_) => None,
+            })
+            .collect();
+
+        if keys.is_subset(&self.collapsed) {
+            self.collapsed.clear();
+        } else {
+            self.collapsed = keys;
+        }
+
+        self.view.select(Some(0));
+
+        Ok(())
+    }
+
+    // Whether the selected row could be marked, not every row has an ID.
+    fn toggle_mark(&mut self) -> Result<bool> {
+        let idx = self.view.selected().unwrap_or_default();
+        let (items, entry) = self.selected()?;
+
+        let Some(Entry::Item(item)) = entry else {
+            return Ok(false);
+        };
+
+        let Some(id) = items.get(item).and_then(Row::id) else {
+            return Ok(false);
+        };
+
+        let mut marked = self.marked.try_borrow_mut()?;
+
+        if !marked.remove(&id) {
+            marked.insert(id);
+        }
+
+        self.view.select(Some(idx.saturating_add(1)));
+
+        Ok(true)
+    }
+
+    // What the selected row copies for `key`, when it is one of the yank keys.
+    fn yank(&self, key: &Keypress) -> Result<Option<String>> {
+        let (items, entry) = self.selected()?;
+
+        let Some(Entry::Item(item)) = entry else {
+            return Ok(None);
+        };
+
+        Ok(items
+            .get(item)
+            .and_then(Row::yank)
+            .and_then(|yank| yank.text(key)))
+    }
+
+    // Marks every row that matches the current filter, for example everything
+    // in a namespace.
+    fn mark_all(&mut self) -> Result<()> {
+        let items = self.items()?;
+
+        self.marked
+            .try_borrow_mut()?
+            .extend(items.iter().filter_map(Row::id));
+
+        Ok(())
+    }
 }
 
 impl<S> Widget for Table<S>
@@ -153,35 +632,141 @@ where
             return Ok(Broadcast::Ignored);
         };
 
-        if let Some(Movement::Y(y)) = move_cursor(key, area) {
-            self.view.select(Some(
-                self.view
-                    .selected()
-                    .unwrap_or_default()
-                    .saturating_add_signed(y.into()),
-            ));
-
+        // Rows that can't be marked leave space for paging down. Inputs, such as
+        // the filter, are pushed on top of the table and get space before this.
+        if Action::Mark.matches(key) && self.toggle_mark()? {
             return Ok(Broadcast::Consumed);
         }
 
-        if matches!(key, Keypress::Enter) {
-            return Ok(Broadcast::Selected(
-                self.view.selected().unwrap_or_default(),
-            ));
+        match self.nav.motion(key, area) {
+            Motion::Move(Movement::Y(y)) => {
+                self.view.select(Some(
+                    self.view
+                        .selected()
+                        .unwrap_or_default()
+                        .saturating_add_signed(y.into()),
+                ));
+
+                return Ok(Broadcast::Consumed);
+            }
+            Motion::Pending => return Ok(Broadcast::Consumed),
+            _ => {}
+        }
+
+        if let Some(text) = self.yank(key)? {
+            return Ok(Broadcast::Clipboard(text));
+        }
+
+        match key {
+            key if Action::MarkAll.matches(key) => {
+                self.mark_all()?;
+
+                return Ok(Broadcast::Consumed);
+            }
+            key if Action::ClearMarks.matches(key) && !self.marked.try_borrow()?.is_empty() => {
+                self.marked.try_borrow_mut()?.clear();
+
+                return Ok(Broadcast::Consumed);
+            }
+            key if Action::Group.matches(key) && !self.groups.is_empty() => {
+                self.cycle_group();
+
+                return Ok(Broadcast::Consumed);
+            }
+            key if Action::Sort.matches(key) && !self.sorts.is_empty() => {
+                self.cycle_sort();
+
+                return Ok(Broadcast::Consumed);
+            }
+            key if Action::Fold.matches(key) && self.group.is_some() => {
+                self.toggle_group()?;
+
+                return Ok(Broadcast::Consumed);
+            }
+            key if Action::FoldAll.matches(key) && self.group.is_some() => {
+                self.toggle_groups()?;
+
+                return Ok(Broadcast::Consumed);
+            }
+            _ => {}
+        }
+
+        if Action::Open.matches(key) {
+            // Selections are always the index of the item, not the line, so
+            // that grouping is invisible to whatever opens the detail.
+            return match self.selected()? {
+                (_, Some(Entry::Header(_))) => {
+                    self.toggle_group()?;
+
+                    Ok(Broadcast::Consumed)
+                }
+                (_, Some(Entry::Item(idx))) => Ok(Broadcast::Selected(idx)),
+                (_, None) => Ok(Broadcast::Selected(
+                    self.view.selected().unwrap_or_default(),
+                )),
+            };
         }
 
         Ok(Broadcast::Ignored)
     }
 
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
-        let items = self.items.items(self.filter.borrow().clone());
+        let items = self.items()?;
+
+        // Newly created resources take a little while to show up in the store.
+        let focus = self.focus.borrow_mut().take();
+        let focus = focus.and_then(|id| {
+            let idx = items
+                .iter()
+                .position(|item| item.id().as_deref() == Some(id.as_str()));
+
+            if idx.is_none() {
+                *self.focus.borrow_mut() = Some(id);
+            }
+
+            idx
+        });
 
-        let rows = items
+        // The row being focused can't be hidden away in a folded group.
+        if let (Some(idx), Some(group)) = (focus, self.group) {
+            self.collapsed.remove(
+                &items[idx]
+                    .group(group)
+                    .unwrap_or_else(|| NO_GROUP.to_string()),
+            );
+        }
+
+        let entries = self.entries(&items);
+
+        if let Some(idx) = focus {
+            self.view.select(
+                entries
+                    .iter()
+                    .position(|entry| matches!(entry, Entry::Item(i) if *i == idx)),
+            );
+        }
+
+        let marked = self.marked.borrow();
+        let marked_style = self.style.row.patch(self.style.marked);
+
+        let rows = entries
             .iter()
-            .map(|item| item.row(&self.style.row))
+            .map(|entry| match entry {
+                // Headers span every column, they're drawn over the table below.
+                Entry::Header(_) => widgets::Row::default(),
+                Entry::Item(idx) => {
+                    let item = &items[*idx];
+
+                    if item.id().is_some_and(|id| marked.contains(&id)) {
+                        item.row(&marked_style)
+                    } else {
+                        item.row(&self.style.row)
+                    }
+                }
+            })
             .collect::<Vec<_>>();
 
-        let mut table = widgets::Table::new(rows, S::Item::constraints());
+        let mut table = widgets::Table::new(rows, self.items.widths());
         let mut border = Block::default()
             .borders(self.border)
             .style(self.style.border);
@@ -190,48 +775,148 @@ where
             table = table.highlight_style(self.style.selected);
         }
 
-        if let Some(header) = S::Item::header() {
+        let has_header = if let Some(header) = self.items.heading() {
             table = table.header(header).style(self.style.header);
+
+            true
+        } else {
+            false
         };
 
         if let Some(title) = self.title.as_ref() {
             border = border.title(title.as_str());
         };
 
+        if !marked.is_empty() {
+            border = border.title_bottom(format!(
+                " {} marked, enter for bulk actions, X to clear ",
+                marked.len()
+            ));
+        } else {
+            let mut hints = Vec::new();
+
+            if let Some(group) = self.group {
+                hints.push(format!("by {}, o to change, z/Z to fold", group.name()));
+            }
+
+            // Without a sort, rows are in the order of their items.
+            if !self.sorts.is_empty() {
+                hints.push(format!(
+                    "sorted by {}, s to change",
+                    self.sort.map_or("name", Sort::name)
+                ));
+            }
+
+            if !hints.is_empty() {
+                border = border.title_bottom(format!(" {} ", hints.join(" │ ")));
+            }
+        }
+
+        let mut body = area;
+
         if self.border != Borders::NONE {
+            body = border.inner(area);
             table = table.block(border);
         }
 
         frame.render_stateful_widget(table, area, &mut self.view);
 
+        if has_header {
+            body.y = body.y.saturating_add(1);
+            body.height = body.height.saturating_sub(1);
+        }
+
+        let offset = self.view.offset();
+
+        for (y, entry) in entries
+            .iter()
+            .skip(offset)
+            .take(body.height as usize)
+            .enumerate()
+        {
+            let Entry::Header(header) = entry else {
+                continue;
+            };
+
+            #[allow(clippy::cast_possible_truncation)]
+            frame.buffer_mut().set_line(
+                body.x,
+                body.y + y as u16,
+                &header.line(&self.style.row),
+                body.width,
+            );
+        }
+
+        if let Some(selected) = self.view.selected().filter(|_| self.highlight) {
+            let buffer: &Buffer = frame.buffer_mut();
+
+            narration::focus(
+                || has_header.then(|| narration::line(buffer, body, body.y.saturating_sub(1))),
+                || {
+                    if entries.is_empty() {
+                        return t("narration.empty");
+                    }
+
+                    #[allow(clippy::cast_possible_truncation)]
+                    let y = body.y + selected.saturating_sub(offset) as u16;
+
+                    t_with(
+                        "narration.row",
+                        &[
+                            ("index", &(selected + 1)),
+                            ("count", &entries.len()),
+                            ("text", &narration::line(buffer, body, y)),
+                        ],
+                    )
+                },
+            );
+        }
+
         Ok(())
     }
 }
 
 pub type DetailFn = Box<dyn Fn(usize, Option<String>) -> Result<BoxWidget>>;
+pub type BulkFn = Box<dyn Fn(&BTreeSet<String>) -> Result<BoxWidget>>;
+pub type FieldsFn = Box<dyn Fn() -> Fields>;
 
 pub struct Filtered {
-    constructor: DetailFn,
+    constructor: Rc<DetailFn>,
+    bulk: Option<BulkFn>,
+    fields: Option<FieldsFn>,
     filter: Rc<RefCell<Option<String>>>,
+    marked: Marked,
     view: View,
+    // A detail that failed to open and then succeeded on retry, it is shown on
+    // the next draw.
+    retried: Rc<RefCell<Option<BoxWidget>>>,
 }
 
 #[bon::bon]
 impl Filtered {
     #[builder]
-    pub fn new<S>(table: Table<S>, constructor: DetailFn) -> Self
+    pub fn new<S>(
+        table: Table<S>,
+        constructor: DetailFn,
+        bulk: Option<BulkFn>,
+        fields: Option<FieldsFn>,
+    ) -> Self
     where
         S: Items + 'static,
     {
         Self {
-            constructor,
+            constructor: Rc::new(constructor),
+            bulk,
+            fields,
             filter: table.filter(),
+            marked: table.marked(),
             view: View::builder()
                 .widgets(vec![Element::builder()
                     .widget(table.boxed())
                     .terminal(true)
                     .build()])
                 .build(),
+            retried: Rc::default(),
         }
     }
 
@@ -257,17 +942,73 @@ impl Filtered {
 
         Ok(())
     }
+
+    // Details usually fail to open because an API call did, those can be
+    // retried from the error.
+    fn open(&mut self, idx: usize, buffer: &Buffer) {
+        let Err(err) = self.select_with(idx, Some(buffer)) else {
+            return;
+        };
+
+        let constructor = self.constructor.clone();
+        let filter = self.filter.borrow().clone();
+        let retried = self.retried.clone();
+
+        self.view.error(Error::from(err).retry(Box::new(move || {
+            *retried.try_borrow_mut()? = Some(constructor(idx, filter.clone())?);
+
+            Ok(())
+        })));
+    }
+
+    // With rows marked, selecting opens bulk actions for them instead of the
+    // detail of a single row.
+    fn bulk(&mut self) -> Result<bool> {
+        let Some(bulk) = self.bulk.as_ref() else {
+            return Ok(false);
+        };
+
+        let marked = self.marked.try_borrow()?;
+
+        if marked.is_empty() {
+            return Ok(false);
+        }
+
+        let widget = bulk(&marked)?;
+        drop(marked);
+
+        self.view.push(widget.into());
+
+        Ok(true)
+    }
 }
 
 impl Widget for Filtered {
     fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
-        if let Some(Keypress::Printable('/')) = event.key() {
+        // Popups, such as the filter builder, take their own input.
+        if event.key().is_some_and(|key| Action::Filter.matches(key)) && self.view.zindex() == 0 {
             TABLE_FILTER.inc();
 
+            // Completes `key=value` terms from what's currently in the list.
+            let complete = self.fields.as_ref().map(|fields| {
+                input::complete_words(
+                    fields()
+                        .into_iter()
+                        .flat_map(|(key, values)| {
+                            values
+                                .into_iter()
+                                .map(move |value| format!("{key}={value}"))
+                        })
+                        .collect(),
+                )
+            });
+
             self.view.push(
                 Text::builder()
                     .title("Filter")
                     .content(self.filter.clone())
+                    .history("filter")
+                    .maybe_complete(complete)
                     .build()
                     .boxed()
                     .into(),
@@ -276,15 +1017,36 @@ impl Widget for Filtered {
             return Ok(Broadcast::Consumed);
         }
 
+        // Only from the table itself, everything else on top gets the key.
+        if let (true, Some(fields), 0) = (
+            event
+                .key()
+                .is_some_and(|key| Action::BuildFilter.matches(key)),
+            self.fields.as_ref(),
+            self.view.zindex(),
+        ) {
+            TABLE_FILTER.inc();
+
+            self.view.push(
+                FilterBuilder::new(fields(), self.filter.clone())
+                    .boxed()
+                    .into(),
+            );
+
+            return Ok(Broadcast::Consumed);
+        }
+
         match self.view.dispatch(event, buffer, area) {
             Ok(Broadcast::Selected(idx)) => {
-                self.select_with(idx, Some(buffer))?;
+                if !self.bulk()? {
+                    self.open(idx, buffer);
+                }
 
                 Ok(Broadcast::Consumed)
             }
             Ok(x) => Ok(x),
             Err(e) => {
-                self.view.push(Error::from(e).boxed().into());
+                self.view.error(e);
 
                 Ok(Broadcast::Consumed)
             }
@@ -292,10 +1054,33 @@ impl Widget for Filtered {
     }
 
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if let Some(widget) = self.retried.try_borrow_mut()?.take() {
+            self.view.push(widget.into());
+        }
+
         self.view.draw(frame, area)
     }
 
     fn zindex(&self) -> u16 {
         self.view.zindex()
     }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        self.view.title()
+    }
+
+    fn filter(&self) -> Option<String> {
+        self.filter
+            .borrow()
+            .clone()
+            .filter(|filter| !filter.is_empty())
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
 }
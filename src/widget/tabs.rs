@@ -1,60 +1,122 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    time::Instant,
+};
+
 use bon::Builder;
 use eyre::Result;
+use lazy_static::lazy_static;
+use prometheus::{histogram_opts, register_histogram_vec, HistogramVec};
 use ratatui::{
     buffer::Buffer,
-    layout::{Constraint, Layout, Rect},
+    layout::{Constraint, Flex, Layout, Rect},
     style::{Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, Borders},
+    widgets::{Block, Borders, Clear, Paragraph},
     Frame,
 };
 use tachyonfx::{fx, EffectTimer, Interpolation};
 
 use super::{
     error::Error,
+    keys::Action,
+    locale::t_with,
+    narration,
+    nav::exit_keys,
+    nested,
     view::{Element, View},
-    Placement, Widget,
+    yank::Yank,
+    BoxWidget, Placement, Widget,
 };
 use crate::{
-    events::{Broadcast, Event},
+    events::{Broadcast, Event, Keypress},
     fx::{horizontal_wipe, Start},
+    resources::policy::Verb,
     widget::nav::{move_cursor, Movement},
 };
 
+lazy_static! {
+    // Only the name of the tab is recorded, never who was looking at it.
+    static ref TAB_DURATION: HistogramVec = register_histogram_vec!(
+        histogram_opts!(
+            "tab_duration_seconds",
+            "Time spent on a tab before switching away from it",
+            vec!(1.0, 5.0, 15.0, 60.0, 300.0, 900.0, 3600.0),
+        ),
+        &["tab"],
+    )
+    .unwrap();
+}
+
 #[derive(Builder)]
 pub struct Tab {
     name: String,
     constructor: Box<dyn Fn() -> Element + Send>,
+    /// What the tab lets users do, tabs are hidden when the session's policy
+    /// doesn't allow it.
+    verb: Option<Verb>,
+    /// Name the tab can be opened again with, see [`Editable`].
+    kind: Option<String>,
 }
 
 impl Tab {
     pub fn widget(&self) -> Element {
+        if let Some(verb) = self.verb.filter(|verb| verb.disabled()) {
+            return Disabled(verb).boxed().into();
+        }
+
         (self.constructor)()
     }
-}
 
-struct Bar {
-    items: Vec<String>,
-    title: Vec<String>,
-    style: Style,
+    pub fn with_kind(mut self, kind: impl Into<String>) -> Self {
+        self.kind = Some(kind.into());
+        self
+    }
 
-    idx: usize,
+    fn allowed(&self) -> bool {
+        self.verb
+            .map_or(true, |verb| verb.allowed() || verb.disabled())
+    }
 }
 
-#[bon::bon]
-impl Bar {
-    #[builder]
-    fn new(items: &[Tab], style: Style, title: Vec<String>) -> Self {
-        Self {
-            items: items.iter().map(|tab| tab.name.clone()).collect(),
-            title,
-            style,
+// Shown in place of a tab whose verb has been disabled by an administrator, the
+// tab is kept so that it's clear it exists but can't be used.
+struct Disabled(Verb);
 
-            idx: 0,
-        }
+impl Widget for Disabled {
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let text = t_with("disabled", &[("verb", &self.0.name())]);
+        let [area] = Layout::vertical([Constraint::Length(1)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        frame.render_widget(Paragraph::new(text.as_str()).centered(), area);
+
+        narration::focus(|| None, || text);
+
+        Ok(())
     }
 }
 
+/// Lets users open, close, reorder and duplicate tabs while the view is open.
+pub struct Editable {
+    /// Kinds of tabs that can be opened, see [`Tab::with_kind`].
+    pub kinds: Vec<String>,
+    /// Builds a tab of a kind, starting with the filter when there is one.
+    pub tab: Box<dyn Fn(&str, Option<String>) -> Tab>,
+}
+
+// The names and selection are shared with `TabbedView` so that tabs can change
+// without rebuilding the bar.
+struct Bar {
+    items: Rc<RefCell<Vec<String>>>,
+    title: Vec<String>,
+    style: Style,
+
+    idx: Rc<Cell<usize>>,
+}
+
 impl Widget for Bar {
     fn dispatch(&mut self, event: &Event, _: &Buffer, area: Rect) -> Result<Broadcast> {
         let Some(key) = event.key() else {
@@ -62,12 +124,15 @@ impl Widget for Bar {
         };
 
         if let Some(Movement::X(x)) = move_cursor(key, area) {
-            self.idx = self
+            let idx = self
                 .idx
+                .get()
                 .wrapping_add_signed(x.into())
-                .clamp(0, self.items.len().saturating_sub(1));
+                .clamp(0, self.items.borrow().len().saturating_sub(1));
 
-            return Ok(Broadcast::Selected(self.idx));
+            self.idx.set(idx);
+
+            return Ok(Broadcast::Selected(idx));
         }
 
         Ok(Broadcast::Ignored)
@@ -84,13 +149,14 @@ impl Widget for Bar {
                 .collect::<Vec<_>>(),
             ));
 
-        let layout =
-            Layout::horizontal(std::iter::repeat(Constraint::Fill(1)).take(self.items.len()))
-                .spacing(1)
-                .split(border.inner(area));
+        let items = self.items.borrow();
+
+        let layout = Layout::horizontal(std::iter::repeat(Constraint::Fill(1)).take(items.len()))
+            .spacing(1)
+            .split(border.inner(area));
 
-        for (i, (area, txt)) in layout.iter().zip(self.items.iter()).enumerate() {
-            let style = if i == self.idx {
+        for (i, (area, txt)) in layout.iter().zip(items.iter()).enumerate() {
+            let style = if i == self.idx.get() {
                 self.style
             } else {
                 Style::default()
@@ -116,6 +182,20 @@ pub struct TabbedView {
     items: Vec<Tab>,
     current: usize,
     view: View,
+    title: Vec<String>,
+    // Set while `initial` is being shown in place of the current tab.
+    pending: bool,
+    // When the current tab was selected, see `TAB_DURATION`.
+    shown: Instant,
+
+    names: Rc<RefCell<Vec<String>>>,
+    idx: Rc<Cell<usize>>,
+    editable: Option<Editable>,
+    picker: Option<Picker>,
+    // Set by retrying an error from the current tab, it is rebuilt on the next
+    // draw which restarts anything it was watching.
+    restart: Rc<Cell<bool>>,
+    yank: Option<Yank>,
 }
 
 #[bon::bon]
@@ -125,28 +205,74 @@ impl TabbedView {
         tabs: Vec<Tab>,
         #[builder(default = Style::default().add_modifier(Modifier::REVERSED))] style: Style,
         #[builder(default = Vec::new())] title: Vec<String>,
+        #[builder(default)] selected: usize,
+        // Shown in place of the selected tab until it exits. The tab itself is only
+        // constructed afterwards, so anything it needs to load is deferred.
+        initial: Option<BoxWidget>,
+        editable: Option<Editable>,
+        // The resource being shown, for copying its name. Keys the current tab
+        // doesn't use fall through to this.
+        yank: Option<Yank>,
     ) -> Self {
-        let mut widgets = vec![Bar::builder()
-            .items(&tabs)
-            .style(style)
-            .title(title)
-            .build()
-            .boxed()
-            .into()];
+        let tabs: Vec<_> = tabs.into_iter().filter(Tab::allowed).collect();
+        let selected = selected.min(tabs.len().saturating_sub(1));
 
-        if !tabs.is_empty() {
-            widgets.push(tabs[0].widget());
+        let names = Rc::new(RefCell::new(
+            tabs.iter().map(|tab| tab.name.clone()).collect(),
+        ));
+        let idx = Rc::new(Cell::new(selected));
+
+        let mut widgets = vec![Bar {
+            items: names.clone(),
+            title: title.clone(),
+            style,
+            idx: idx.clone(),
+        }
+        .boxed()
+        .into()];
+
+        let pending = initial.is_some();
+
+        if let Some(initial) = initial {
+            widgets.push(Element::builder().widget(initial).terminal(true).build());
+        } else if !tabs.is_empty() {
+            widgets.push(tabs[selected].widget());
         }
 
         Self {
             items: tabs,
 
-            current: 0,
+            current: selected,
             view: View::builder().widgets(widgets).build(),
+            title,
+            pending,
+            shown: Instant::now(),
+
+            names,
+            idx,
+            editable,
+            picker: None,
+            restart: Rc::default(),
+            yank,
         }
     }
 
+    fn record(&self) {
+        let Some(tab) = self.items.get(self.current) else {
+            return;
+        };
+
+        TAB_DURATION
+            .with_label_values(&[tab.name.to_lowercase().as_str()])
+            .observe(self.shown.elapsed().as_secs_f64());
+    }
+
     fn select(&mut self, idx: usize, buffer: &Buffer) {
+        self.record();
+        self.show(idx, buffer);
+    }
+
+    fn show(&mut self, idx: usize, buffer: &Buffer) {
         let start = if self.current < idx {
             Start::Left
         } else {
@@ -154,6 +280,9 @@ impl TabbedView {
         };
 
         self.current = idx;
+        self.pending = false;
+        self.shown = Instant::now();
+        self.sync();
 
         // TODO: this is *probably* a valid assumption, but it might need to be actually
         // checked.
@@ -171,21 +300,172 @@ impl TabbedView {
     }
 }
 
+// Managing tabs, these only do anything when the view is editable.
+impl TabbedView {
+    fn sync(&self) {
+        *self.names.borrow_mut() = self.items.iter().map(|tab| tab.name.clone()).collect();
+        self.idx.set(self.current);
+    }
+
+    fn open(&mut self, tab: Tab, buffer: &Buffer) {
+        let idx = (self.current + 1).min(self.items.len());
+
+        self.items.insert(idx, tab);
+        self.select(idx, buffer);
+    }
+
+    // Opens the kind picked from the picker, the first choice is always to
+    // duplicate the current tab.
+    fn pick(&mut self, choice: usize, buffer: &Buffer) {
+        let Some(editable) = self.editable.as_ref() else {
+            return;
+        };
+
+        let tab = if choice == 0 {
+            let Some(kind) = self
+                .items
+                .get(self.current)
+                .and_then(|tab| tab.kind.clone())
+            else {
+                return;
+            };
+
+            (editable.tab)(&kind, self.view.filter())
+        } else {
+            let Some(kind) = editable.kinds.get(choice - 1) else {
+                return;
+            };
+
+            (editable.tab)(kind, None)
+        };
+
+        self.open(tab, buffer);
+    }
+
+    fn close(&mut self, buffer: &Buffer) {
+        if self.items.len() <= 1 {
+            return;
+        }
+
+        self.record();
+        self.items.remove(self.current);
+        self.show(self.current.min(self.items.len() - 1), buffer);
+    }
+
+    // Tabs move without being rebuilt, only the order changes.
+    fn shift(&mut self, offset: isize) {
+        let idx = self
+            .current
+            .saturating_add_signed(offset)
+            .min(self.items.len().saturating_sub(1));
+
+        self.items.swap(self.current, idx);
+        self.current = idx;
+        self.sync();
+    }
+
+    fn manage(&mut self, key: &Keypress, buffer: &Buffer) -> bool {
+        let Some(editable) = self.editable.as_ref() else {
+            return false;
+        };
+
+        // Anything open on top of the tab, such as a pod, gets these instead.
+        if self.view.zindex() > 0 {
+            return false;
+        }
+
+        match key {
+            key if Action::NewTab.matches(key) => {
+                self.picker = Some(Picker::new(
+                    std::iter::once("duplicate".to_string())
+                        .chain(editable.kinds.iter().cloned())
+                        .collect(),
+                ));
+            }
+            key if Action::CloseTab.matches(key) => self.close(buffer),
+            key if Action::MoveTabLeft.matches(key) => self.shift(-1),
+            key if Action::MoveTabRight.matches(key) => self.shift(1),
+            _ => return false,
+        }
+
+        true
+    }
+}
+
 impl Widget for TabbedView {
     fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        if let Some(picker) = self.picker.as_mut() {
+            match picker.dispatch(event, buffer, area)? {
+                Broadcast::Selected(choice) => {
+                    self.picker = None;
+                    self.pick(choice, buffer);
+
+                    return Ok(Broadcast::Consumed);
+                }
+                Broadcast::Exited => {
+                    self.picker = None;
+
+                    return Ok(Broadcast::Consumed);
+                }
+                Broadcast::Ignored => {}
+                broadcast => return Ok(broadcast),
+            }
+        }
+
         match self.view.dispatch(event, buffer, area)? {
             Broadcast::Selected(idx) => {
                 self.select(idx, buffer);
 
                 Ok(Broadcast::Consumed)
             }
+            Broadcast::Exited if self.pending => {
+                self.pending = false;
+
+                self.view.pop();
+                self.view.push(self.items[self.current].widget());
+
+                Ok(Broadcast::Consumed)
+            }
+            Broadcast::Ignored if event.key().is_some_and(|key| self.manage(key, buffer)) => {
+                Ok(Broadcast::Consumed)
+            }
+            Broadcast::Ignored => Ok(event
+                .key()
+                .and_then(|key| self.yank.as_ref()?.text(key))
+                .map_or(Broadcast::Ignored, Broadcast::Clipboard)),
             broadcast => Ok(broadcast),
         }
     }
 
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if self.restart.take() && !self.pending {
+            self.view.pop();
+            self.view.push(self.items[self.current].widget());
+        }
+
+        narration::tab(|| {
+            t_with(
+                "narration.tab",
+                &[
+                    ("name", &self.items[self.current].name),
+                    ("index", &(self.current + 1)),
+                    ("count", &self.items.len()),
+                ],
+            )
+        });
+
         if let Err(err) = self.view.draw(frame, area) {
-            self.view.push(Error::from(err).boxed().into());
+            let restart = self.restart.clone();
+
+            self.view.error(Error::from(err).retry(Box::new(move || {
+                restart.set(true);
+
+                Ok(())
+            })));
+        }
+
+        if let Some(picker) = self.picker.as_mut() {
+            picker.draw(frame, area)?;
         }
 
         Ok(())
@@ -194,4 +474,99 @@ impl Widget for TabbedView {
     fn zindex(&self) -> u16 {
         self.view.zindex()
     }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        let title = self.view.title();
+
+        if title.is_empty() {
+            self.title.clone()
+        } else {
+            title
+        }
+    }
+
+    fn filter(&self) -> Option<String> {
+        self.view.filter()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
+
+/// Popup for picking what kind of tab to open.
+struct Picker {
+    choices: Vec<String>,
+    idx: usize,
+}
+
+impl Picker {
+    fn new(choices: Vec<String>) -> Self {
+        Self { choices, idx: 0 }
+    }
+}
+
+impl Widget for Picker {
+    fn dispatch(&mut self, event: &Event, _: &Buffer, _: Rect) -> Result<Broadcast> {
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        match key {
+            exit_keys!() => return Ok(Broadcast::Exited),
+            Keypress::CursorUp | Keypress::Printable('k') => {
+                self.idx = self.idx.saturating_sub(1);
+            }
+            Keypress::CursorDown | Keypress::Printable('j') => {
+                self.idx = (self.idx + 1).min(self.choices.len().saturating_sub(1));
+            }
+            Keypress::Enter => return Ok(Broadcast::Selected(self.idx)),
+            _ => {}
+        }
+
+        Ok(Broadcast::Consumed)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        #[allow(clippy::cast_possible_truncation)]
+        let [area] = Layout::vertical([Constraint::Length(self.choices.len() as u16 + 2)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Length(30)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        let lines: Vec<Line> = self
+            .choices
+            .iter()
+            .enumerate()
+            .map(|(i, choice)| {
+                let line = Line::from(choice.as_str());
+
+                if i == self.idx {
+                    line.style(Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    line
+                }
+            })
+            .collect();
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" Open tab ")),
+            area,
+        );
+
+        Ok(())
+    }
+}
+
+impl Drop for TabbedView {
+    fn drop(&mut self) {
+        self.record();
+    }
 }
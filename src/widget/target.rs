@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use eyre::{eyre, Result};
+use ratatui::{buffer::Buffer, layout::Rect, Frame};
+use tokio::sync::oneshot;
+
+use super::{
+    loading::Loading,
+    nested, pod, table,
+    tabs::Tab,
+    view::{Element, View},
+    Widget, WIDGET_VIEWS,
+};
+use crate::{
+    events::{Broadcast, Event},
+    history::History,
+    resources::{
+        store::Store,
+        target::{Source, Targets as Items},
+    },
+};
+
+/// Pods that a route or network policy ends up sending traffic to (or
+/// restricting), or that run an image. Selecting one opens the pod.
+pub struct Targets {
+    view: View,
+    is_ready: Vec<oneshot::Receiver<()>>,
+}
+
+impl Targets {
+    pub fn new(client: kube::Client, history: Arc<dyn History>, source: Source) -> Self {
+        match source {
+            Source::Route(_) => WIDGET_VIEWS.gateway.targets.inc(),
+            Source::Policy(_) => WIDGET_VIEWS.netpol.targets.inc(),
+            Source::Image(_) => WIDGET_VIEWS.image.targets.inc(),
+        }
+
+        let (pods, pods_ready) = Store::new(client.clone());
+        let (services, services_ready) = Store::new(client.clone());
+
+        let items = Items::new(source, pods, services);
+
+        let table = table::Filtered::builder()
+            .table(table::Table::builder().items(items.clone()).build())
+            .constructor(detail(client, items, history))
+            .build();
+
+        let widgets = vec![
+            table.boxed().into(),
+            Element::builder()
+                .widget(Loading.boxed())
+                .ignore(true)
+                .build(),
+        ];
+
+        Self {
+            view: View::builder().widgets(widgets).build(),
+            is_ready: vec![pods_ready, services_ready],
+        }
+    }
+
+    pub fn tab(
+        name: String,
+        client: kube::Client,
+        history: Arc<dyn History>,
+        source: Source,
+    ) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Self::new(client.clone(), history.clone(), source.clone())
+                    .boxed()
+                    .into()
+            }))
+            .build()
+    }
+}
+
+fn detail(client: kube::Client, items: Items, history: Arc<dyn History>) -> table::DetailFn {
+    Box::new(move |idx, filter| {
+        let pod = items
+            .get(idx, filter)
+            .ok_or_else(|| eyre!("pod not found"))?;
+
+        Ok(pod::Detail::builder()
+            .client(&client)
+            .pod(pod)
+            .history(history.clone())
+            .build()
+            .boxed())
+    })
+}
+
+impl Widget for Targets {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        self.view.dispatch(event, buffer, area)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        // Both stores are needed before anything can be resolved, the loading
+        // indicator stays until then.
+        let waiting = !self.is_ready.is_empty();
+        self.is_ready.retain_mut(|rx| rx.try_recv().is_err());
+
+        if waiting && self.is_ready.is_empty() {
+            self.view.pop();
+        }
+
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        self.view.zindex()
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        self.view.title()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
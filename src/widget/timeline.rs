@@ -0,0 +1,218 @@
+use std::{borrow::Borrow, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use k8s_openapi::api::core::v1::Event as KubeEvent;
+use kube::{runtime::reflector::ObjectRef, Resource, ResourceExt};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{palette::tailwind, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{
+    clock::Clock,
+    events::last_seen,
+    nav::{move_cursor, Movement},
+    tabs::Tab,
+    Widget, WIDGET_VIEWS_VEC,
+};
+use crate::{
+    events::{Broadcast, Event},
+    resources::{store::Store, Compare, Filter},
+};
+
+// Something that happened to the resource, either an event or an update that
+// the store saw.
+struct Entry {
+    at: DateTime<Utc>,
+    label: &'static str,
+    style: Style,
+    lines: Vec<String>,
+}
+
+/// Updates to a resource and its events, newest first. Updates are only known
+/// from when the dashboard started watching the resource's kind, which is
+/// usually when its list was opened, so this shows what changed leading up to
+/// something like a crash without needing anything outside of kty.
+pub struct Timeline<K>
+where
+    K: Resource<DynamicType = ()>
+        + Clone
+        + std::fmt::Debug
+        + Send
+        + Sync
+        + DeserializeOwned
+        + 'static,
+{
+    obj: ObjectRef<K>,
+    store: Arc<Store<K>>,
+    events: Arc<Store<KubeEvent>>,
+    offset: usize,
+}
+
+impl<K> Timeline<K>
+where
+    K: Filter
+        + Resource<DynamicType = ()>
+        + Clone
+        + std::fmt::Debug
+        + Send
+        + Sync
+        + DeserializeOwned
+        + Serialize
+        + 'static,
+    Arc<K>: Compare,
+{
+    pub fn new(client: kube::Client, resource: &Arc<K>) -> Self {
+        WIDGET_VIEWS_VEC
+            .with_label_values(&[K::kind(&()).borrow(), "timeline"])
+            .inc();
+
+        // Detail views are opened from a list, so this is usually the store that
+        // has been watching all along.
+        let (store, _) = Store::<K>::new(client.clone());
+        let (events, _) = Store::<KubeEvent>::scoped(
+            client,
+            &format!("involvedObject.uid={}", resource.uid().unwrap_or_default()),
+        );
+
+        Self {
+            obj: ObjectRef::from_obj(resource.as_ref()),
+            store,
+            events,
+            offset: 0,
+        }
+    }
+
+    pub fn tab(name: String, client: kube::Client, resource: Arc<K>) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Self::new(client.clone(), &resource).boxed().into()
+            }))
+            .build()
+    }
+
+    fn entries(&self) -> Vec<Entry> {
+        let updates = self
+            .store
+            .updates(&self.obj)
+            .into_iter()
+            .map(|update| Entry {
+                at: update.at,
+                label: "Changed",
+                style: Style::default().fg(tailwind::INDIGO.c300),
+                lines: update.changes,
+            });
+
+        let events = self.events.items(None).into_iter().filter_map(|ev| {
+            let warning = ev.type_.as_deref() == Some("Warning");
+
+            Some(Entry {
+                at: last_seen(&ev)?,
+                label: if warning { "Warning" } else { "Event" },
+                style: if warning {
+                    Style::default().fg(tailwind::RED.c300)
+                } else {
+                    Style::default()
+                },
+                lines: vec![format!(
+                    "{}: {}",
+                    ev.reason.clone().unwrap_or_default(),
+                    ev.message.clone().unwrap_or_default()
+                )],
+            })
+        });
+
+        let mut entries: Vec<_> = updates.chain(events).collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.at));
+
+        entries
+    }
+}
+
+impl<K> Widget for Timeline<K>
+where
+    K: Filter
+        + Resource<DynamicType = ()>
+        + Clone
+        + std::fmt::Debug
+        + Send
+        + Sync
+        + DeserializeOwned
+        + Serialize
+        + 'static,
+    Arc<K>: Compare,
+{
+    fn dispatch(&mut self, event: &Event, _: &Buffer, area: Rect) -> Result<Broadcast> {
+        let Some(key) = event.key() else {
+            return Ok(Broadcast::Ignored);
+        };
+
+        if let Some(Movement::Y(y)) = move_cursor(key, area) {
+            self.offset = self.offset.saturating_add_signed(y.into());
+
+            return Ok(Broadcast::Consumed);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let block = Block::default().borders(Borders::ALL);
+        let clock = Clock::current();
+
+        let [body, legend] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(block.inner(area));
+
+        let indent = " ".repeat(usize::from(clock.format.width()) + 10);
+
+        let lines: Vec<Line> = self
+            .entries()
+            .into_iter()
+            .flat_map(|entry| {
+                let mut lines = entry.lines.into_iter();
+
+                let first = Line::from(vec![
+                    Span::from(format!(
+                        "{:<width$}  ",
+                        clock.format(entry.at),
+                        width = usize::from(clock.format.width())
+                    )),
+                    Span::styled(format!("{:<8}", entry.label), entry.style),
+                    Span::from(lines.next().unwrap_or_default()),
+                ]);
+
+                let indent = indent.clone();
+
+                std::iter::once(first)
+                    .chain(lines.map(move |line| Line::from(format!("{indent}{line}"))))
+            })
+            .collect();
+
+        self.offset = self.offset.min(lines.len().saturating_sub(1));
+
+        if lines.is_empty() {
+            frame.render_widget(Line::from("Nothing has happened yet").centered(), body);
+        } else {
+            frame.render_widget(
+                Paragraph::new(lines).scroll((u16::try_from(self.offset).unwrap_or(u16::MAX), 0)),
+                body,
+            );
+        }
+
+        frame.render_widget(
+            Line::from("Changes are only seen while the session is open")
+                .style(Style::default().fg(tailwind::GRAY.c500)),
+            legend,
+        );
+        frame.render_widget(block, area);
+
+        Ok(())
+    }
+}
@@ -8,7 +8,7 @@ use ratatui::{
 };
 use tachyonfx::Effect;
 
-use super::{propagate, BoxWidget, Placement, Widget};
+use super::{error::Error, nested, propagate, BoxWidget, Placement, Widget};
 use crate::{
     events::{Broadcast, Event},
     fx::Animated,
@@ -63,6 +63,22 @@ impl Widget for Element {
     fn zindex(&self) -> u16 {
         self.zindex.unwrap_or(self.widget.zindex())
     }
+
+    fn title(&self) -> Vec<String> {
+        self.widget.title()
+    }
+
+    fn filter(&self) -> Option<String> {
+        self.widget.filter()
+    }
+
+    fn dirty(&self) -> bool {
+        self.widget.dirty()
+    }
+
+    fn tree(&self) -> Vec<String> {
+        self.widget.tree()
+    }
 }
 
 impl From<BoxWidget> for Element {
@@ -83,6 +99,10 @@ pub struct View {
 
     #[builder(default)]
     show_all: bool,
+
+    // Errors stack in one place on top of everything else, see `View::error`.
+    #[builder(skip)]
+    errors: Option<Error>,
 }
 
 impl View {
@@ -90,6 +110,14 @@ impl View {
         self.widgets.push(widget);
     }
 
+    /// Show `error` on top of the view, along with any that are already there.
+    pub fn error(&mut self, error: impl Into<Error>) {
+        match self.errors.as_mut() {
+            Some(errors) => errors.extend(error.into()),
+            None => self.errors = Some(error.into()),
+        }
+    }
+
     pub fn pop(&mut self) -> Option<BoxWidget> {
         self.widgets.pop().map(|element| element.widget)
     }
@@ -118,6 +146,10 @@ impl View {
 
 impl Widget for View {
     fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        if let Some(errors) = self.errors.as_mut() {
+            propagate!(errors.dispatch(event, buffer, area), self.errors = None);
+        }
+
         for (i, el) in self.widgets.iter_mut().enumerate().rev() {
             propagate!(el.dispatch(event, buffer, area), {
                 if el.terminal {
@@ -148,6 +180,14 @@ impl Widget for View {
             }
         }
 
+        if let Some(errors) = self.errors.as_mut() {
+            errors.draw(frame, area)?;
+
+            if errors.is_empty() {
+                self.errors = None;
+            }
+        }
+
         Ok(())
     }
 
@@ -155,7 +195,38 @@ impl Widget for View {
         self.widgets
             .iter()
             .filter_map(|w| if w.ignore { None } else { Some(w.zindex()) })
+            .chain(self.errors.as_ref().map(Widget::zindex))
             .max()
             .unwrap_or_default()
     }
+
+    // The top-most widget that knows where it is wins, popups such as errors
+    // don't change the title.
+    fn title(&self) -> Vec<String> {
+        self.widgets
+            .iter()
+            .rev()
+            .filter(|w| !w.ignore)
+            .map(Widget::title)
+            .find(|title| !title.is_empty())
+            .unwrap_or_default()
+    }
+
+    fn filter(&self) -> Option<String> {
+        self.widgets.iter().rev().find_map(Widget::filter)
+    }
+
+    fn dirty(&self) -> bool {
+        self.widgets.iter().any(Widget::dirty) || self.errors.as_ref().is_some_and(Error::aging)
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(
+            self._name(),
+            self.widgets
+                .iter()
+                .map(|w| w as &dyn Widget)
+                .chain(self.errors.iter().map(|e| e as &dyn Widget)),
+        )
+    }
 }
@@ -0,0 +1,339 @@
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use eyre::{eyre, Result};
+use kube::{Resource, ResourceExt};
+use ratatui::{buffer::Buffer, layout::Rect, Frame};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::oneshot;
+
+use super::{
+    events::Events,
+    gitops::GitOps,
+    loading::Loading,
+    log::Aggregated,
+    nested, pod, propagate, table,
+    tabs::{Tab, TabbedView},
+    view::{Element, View},
+    yaml::Yaml,
+    Widget, WIDGET_VIEWS,
+};
+use crate::{
+    events::{Broadcast, Event, Keypress},
+    history::History,
+    resources::{
+        gitops,
+        store::Store,
+        workload::{Controller, Owned, Workloads},
+    },
+};
+
+/// Deployments, stateful sets, daemon sets and jobs in one list. Selecting one
+/// drills into the pods it owns.
+pub struct List {
+    view: View,
+    is_ready: Vec<oneshot::Receiver<()>>,
+    ready: bool,
+}
+
+#[bon::bon]
+impl List {
+    #[allow(clippy::blocks_in_conditions)]
+    #[tracing::instrument(skip(client, history), fields(activity = "workload.list"))]
+    #[builder]
+    pub fn new(client: kube::Client, history: Arc<dyn History>, filter: Option<String>) -> Self {
+        WIDGET_VIEWS.workload.list.inc();
+
+        let (deployments, deployments_ready) = Store::new(client.clone());
+        let (statefulsets, statefulsets_ready) = Store::new(client.clone());
+        let (daemonsets, daemonsets_ready) = Store::new(client.clone());
+        let (jobs, jobs_ready) = Store::new(client.clone());
+        let (replicasets, replicasets_ready) = Store::new(client.clone());
+        let (pods, pods_ready) = Store::new(client.clone());
+
+        let workloads = Workloads::new(
+            deployments,
+            statefulsets,
+            daemonsets,
+            jobs,
+            replicasets,
+            pods,
+        );
+
+        let table = table::Filtered::builder()
+            .table(
+                table::Table::builder()
+                    .items(workloads.clone())
+                    .filter(Rc::new(RefCell::new(filter)))
+                    .build(),
+            )
+            .constructor(Detail::from_workloads(client, history, workloads))
+            .build();
+
+        let widgets = vec![
+            table.boxed().into(),
+            Element::builder()
+                .widget(Loading.boxed())
+                .ignore(true)
+                .build(),
+        ];
+
+        Self {
+            view: View::builder().widgets(widgets).build(),
+            is_ready: vec![
+                deployments_ready,
+                statefulsets_ready,
+                daemonsets_ready,
+                jobs_ready,
+                replicasets_ready,
+                pods_ready,
+            ],
+            ready: false,
+        }
+    }
+
+    pub fn tab(
+        name: String,
+        client: kube::Client,
+        history: Arc<dyn History>,
+        terminal: bool,
+    ) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Element::builder()
+                    .widget(
+                        Self::builder()
+                            .client(client.clone())
+                            .history(history.clone())
+                            .build()
+                            .boxed(),
+                    )
+                    .terminal(terminal)
+                    .build()
+            }))
+            .build()
+    }
+}
+
+impl Widget for List {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.is_ready.retain_mut(|rx| rx.try_recv().is_err());
+
+        // Pod counts are wrong until every store is ready.
+        if self.is_ready.is_empty() && !self.ready {
+            self.view.pop();
+            self.ready = true;
+        }
+
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        self.view.zindex()
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        let title = self.view.title();
+
+        if title.is_empty() {
+            vec!["workloads".to_string()]
+        } else {
+            title
+        }
+    }
+
+    fn filter(&self) -> Option<String> {
+        self.view.filter()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
+
+pub struct Detail {
+    view: TabbedView,
+}
+
+impl Detail {
+    fn new<K>(
+        client: kube::Client,
+        history: Arc<dyn History>,
+        workloads: Workloads,
+        resource: Arc<K>,
+    ) -> Self
+    where
+        K: Resource<DynamicType = ()>
+            + Serialize
+            + Clone
+            + std::fmt::Debug
+            + Send
+            + Sync
+            + DeserializeOwned
+            + 'static,
+    {
+        WIDGET_VIEWS.workload.detail.inc();
+
+        let title = vec![
+            K::plural(&()).to_string(),
+            resource.namespace().unwrap_or_default(),
+            resource.name_any(),
+        ];
+
+        let owned = Owned::new(workloads, resource.uid().unwrap_or_default());
+
+        let mut tabs = vec![
+            Pods::tab("Pods".to_string(), client.clone(), history, owned.clone()),
+            Aggregated::tab("Logs".to_string(), client.clone(), move || {
+                table::Items::items(&owned, None)
+            }),
+            Yaml::tab("YAML".to_string(), resource.clone()),
+            Events::tab("Events".to_string(), client, resource.clone()),
+        ];
+
+        if gitops::manager(resource.as_ref()).is_some() {
+            tabs.push(GitOps::tab("GitOps".to_string(), resource));
+        }
+
+        let view = TabbedView::builder().tabs(tabs).title(title).build();
+
+        Self { view }
+    }
+
+    fn from_workloads(
+        client: kube::Client,
+        history: Arc<dyn History>,
+        workloads: Workloads,
+    ) -> table::DetailFn {
+        Box::new(move |idx, filter| {
+            let workload = workloads
+                .get(idx, filter)
+                .ok_or_else(|| eyre!("workload not found"))?;
+
+            let client = client.clone();
+            let history = history.clone();
+            let workloads = workloads.clone();
+
+            Ok(match workload.controller {
+                Controller::Deployment(d) => Self::new(client, history, workloads, d),
+                Controller::StatefulSet(s) => Self::new(client, history, workloads, s),
+                Controller::DaemonSet(d) => Self::new(client, history, workloads, d),
+                Controller::Job(j) => Self::new(client, history, workloads, j),
+            }
+            .boxed())
+        })
+    }
+}
+
+impl Widget for Detail {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        propagate!(self.view.dispatch(event, buffer, area));
+
+        if matches!(event.key(), Some(Keypress::Escape)) {
+            return Ok(Broadcast::Exited);
+        }
+
+        Ok(Broadcast::Ignored)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        1
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        self.view.title()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
+
+/// The pods a workload owns right now, they come and go as it scales or rolls
+/// out. Selecting one opens the pod.
+struct Pods {
+    view: View,
+}
+
+impl Pods {
+    fn new(client: kube::Client, history: Arc<dyn History>, owned: Owned) -> Self {
+        let table = table::Filtered::builder()
+            .table(table::Table::builder().items(owned.clone()).build())
+            .constructor(Box::new(move |idx, filter| {
+                let pod = owned
+                    .get(idx, filter)
+                    .ok_or_else(|| eyre!("pod not found"))?;
+
+                Ok(pod::Detail::builder()
+                    .client(&client)
+                    .pod(pod)
+                    .history(history.clone())
+                    .build()
+                    .boxed())
+            }))
+            .build();
+
+        Self {
+            view: View::builder().widgets(vec![table.boxed().into()]).build(),
+        }
+    }
+
+    fn tab(name: String, client: kube::Client, history: Arc<dyn History>, owned: Owned) -> Tab {
+        Tab::builder()
+            .name(name)
+            .constructor(Box::new(move || {
+                Self::new(client.clone(), history.clone(), owned.clone())
+                    .boxed()
+                    .into()
+            }))
+            .build()
+    }
+}
+
+impl Widget for Pods {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
+        self.view.dispatch(event, buffer, area)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        self.view.draw(frame, area)
+    }
+
+    fn zindex(&self) -> u16 {
+        self.view.zindex()
+    }
+
+    fn tree(&self) -> Vec<String> {
+        nested(self._name(), [&self.view as &dyn Widget])
+    }
+
+    fn title(&self) -> Vec<String> {
+        self.view.title()
+    }
+
+    fn dirty(&self) -> bool {
+        self.view.dirty()
+    }
+}
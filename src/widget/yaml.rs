@@ -1,18 +1,22 @@
 use std::{
     borrow::Borrow,
+    collections::HashSet,
     sync::{Arc, LazyLock},
+    time::{Duration, Instant},
 };
 
 use eyre::Result;
-use kube::Resource;
+use itertools::Itertools;
+use kube::{runtime::reflector::ObjectRef, Resource, ResourceExt};
 use ratatui::{
     buffer::Buffer,
-    layout::{Position, Rect},
-    text::Line,
+    layout::{Constraint, Layout, Position, Rect},
+    style::{palette::tailwind, Style},
+    text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
     Frame,
 };
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Serialize};
 use syntect::{
     easy::HighlightLines,
     highlighting::{Theme, ThemeSet},
@@ -22,15 +26,21 @@ use syntect::{
 use syntect_tui::into_span;
 
 use super::{
-    nav::{move_cursor, Movement},
+    input,
+    locale::t_with,
+    narration,
+    nav::{Motion, Movement, Nav},
     Widget, WIDGET_VIEWS_VEC,
 };
 use crate::{
-    events::{Broadcast, Event},
-    resources::Yaml as YamlResource,
+    events::{Broadcast, Event, Keypress},
+    resources::{store::Store, Yaml as YamlResource},
     widget::tabs::Tab,
 };
 
+// How long lines that changed stay highlighted after an update.
+static HIGHLIGHT_FOR: Duration = Duration::from_secs(2);
+
 static THEME: LazyLock<Theme> = LazyLock::new(|| {
     let ts = ThemeSet::load_defaults();
     let mut theme = ts.themes["base16-ocean.dark"].clone();
@@ -44,36 +54,273 @@ static THEME: LazyLock<Theme> = LazyLock::new(|| {
     theme
 });
 
-fn to_lines(txt: &str) -> Vec<Line> {
-    let ps = SyntaxSet::load_defaults_newlines();
-    let syntax = ps.find_syntax_by_extension("yaml").unwrap();
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+
+/// Highlights a document a line at a time, only as far down as has been asked
+/// for. Syntect carries state from one line to the next, so everything above a
+/// line has to be highlighted before it can be, but nothing below it ever is.
+struct Highlighter {
+    source: Vec<String>,
+    state: HighlightLines<'static>,
+    lines: Vec<Line<'static>>,
+}
+
+impl Highlighter {
+    fn new(txt: &str) -> Self {
+        let syntax = SYNTAXES.find_syntax_by_extension("yaml").unwrap();
+
+        Self {
+            source: LinesWithEndings::from(txt)
+                .map(ToString::to_string)
+                .collect(),
+            state: HighlightLines::new(syntax, &THEME),
+            lines: Vec::new(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.source.len()
+    }
+
+    fn line(&mut self, idx: usize) -> Option<&Line<'static>> {
+        while self.lines.len() <= idx {
+            let line = highlight(&mut self.state, self.source.get(self.lines.len())?);
+
+            self.lines.push(line);
+        }
+
+        self.lines.get(idx)
+    }
+}
+
+fn highlight(state: &mut HighlightLines, line: &str) -> Line<'static> {
+    let Ok(segments) = state.highlight_line(line, &SYNTAXES) else {
+        return Line::raw(line.trim_end_matches(['\n', '\r']).to_string());
+    };
 
-    let mut highlighter = HighlightLines::new(syntax, &THEME);
+    segments
+        .into_iter()
+        .filter_map(|segment| into_span(segment).ok())
+        .map(|span| {
+            Span::styled(
+                span.content.trim_end_matches(['\n', '\r']).to_string(),
+                span.style,
+            )
+        })
+        .collect()
+}
+
+pub fn to_lines(txt: &str) -> Vec<Line<'static>> {
+    let mut highlighter = Highlighter::new(txt);
+
+    (0..highlighter.len())
+        .filter_map(|i| highlighter.line(i).cloned())
+        .collect()
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
 
-    LinesWithEndings::from(txt)
+// The key for a line such as `name: foo` or `metadata:`, anything else (list
+// values, multi-line strings) has no key.
+fn key(content: &str) -> Option<&str> {
+    let key = match content.split_once(": ") {
+        Some((key, _)) => key,
+        None => content.strip_suffix(':')?,
+    };
+
+    Some(key.trim_matches(|c| c == '\'' || c == '"'))
+}
+
+struct Entry {
+    indent: usize,
+    segment: String,
+    item: bool,
+    items: usize,
+}
+
+/// The dot separated path of every line, for example `spec.containers.0.image`.
+/// This only understands the subset of YAML that `serde_yaml` generates.
+fn to_paths(txt: &str) -> Vec<String> {
+    let mut stack: Vec<Entry> = Vec::new();
+    let mut scalar: Option<usize> = None;
+
+    txt.lines()
         .map(|line| {
-            highlighter
-                .highlight_line(line, &ps)
-                .unwrap()
-                .into_iter()
-                .filter_map(|segment| into_span(segment).ok())
-                .collect()
+            let mut col = indent(line);
+            let mut content = line.trim_start();
+
+            // Lines of a multi-line string are part of the key that started it.
+            if let Some(parent) = scalar {
+                if col > parent || content.is_empty() {
+                    return path(&stack);
+                }
+
+                scalar = None;
+            }
+
+            while let Some(rest) = content
+                .strip_prefix("- ")
+                .or_else(|| (content == "-").then_some(""))
+            {
+                // Sequences aren't indented under their key, so an item at the same
+                // column as a key belongs to it.
+                while stack
+                    .last()
+                    .is_some_and(|e| e.indent > col || (e.indent == col && e.item))
+                {
+                    stack.pop();
+                }
+
+                let idx = stack.last_mut().map_or(0, |parent| {
+                    parent.items += 1;
+
+                    parent.items - 1
+                });
+
+                stack.push(Entry {
+                    indent: col,
+                    segment: idx.to_string(),
+                    item: true,
+                    items: 0,
+                });
+
+                col += 2;
+                content = rest.trim_start();
+            }
+
+            if let Some(key) = key(content) {
+                while stack.last().is_some_and(|e| e.indent >= col) {
+                    stack.pop();
+                }
+
+                stack.push(Entry {
+                    indent: col,
+                    segment: key.to_string(),
+                    item: false,
+                    items: 0,
+                });
+
+                if content
+                    .split_once(": ")
+                    .is_some_and(|(_, value)| value.starts_with(['|', '>']))
+                {
+                    scalar = Some(col);
+                }
+            }
+
+            path(&stack)
+        })
+        .collect()
+}
+
+fn path(stack: &[Entry]) -> String {
+    stack.iter().map(|e| e.segment.as_str()).join(".")
+}
+
+/// A top level key, such as `metadata` or `status`, and the lines it covers.
+struct Section {
+    start: usize,
+    end: usize,
+}
+
+fn to_sections(txt: &str) -> Vec<Section> {
+    let starts: Vec<_> = txt
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| indent(line) == 0 && key(line).is_some())
+        .map(|(i, _)| i)
+        .collect();
+
+    let len = txt.lines().count();
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, start)| Section {
+            start: *start,
+            end: starts.get(i + 1).copied().unwrap_or(len),
         })
         .collect()
 }
 
-// TODO:
-// - Need to cache the lines.
-// - See logs for performance improvements (eg. only render visible lines).
+/// Where updates to the resource being shown come from. Type erased so that
+/// `Yaml` doesn't need to be generic over the resource.
+trait Source {
+    /// `resourceVersion` of the latest version, `None` once it has been
+    /// deleted.
+    fn version(&self) -> Option<String>;
+    fn to_yaml(&self) -> Option<String>;
+}
+
+struct Watched<K>
+where
+    K: Resource<DynamicType = ()>
+        + Clone
+        + std::fmt::Debug
+        + Send
+        + Sync
+        + DeserializeOwned
+        + 'static,
+{
+    store: Arc<Store<K>>,
+    obj: ObjectRef<K>,
+}
+
+impl<K> Source for Watched<K>
+where
+    K: Resource<DynamicType = ()>
+        + Serialize
+        + Clone
+        + std::fmt::Debug
+        + Send
+        + Sync
+        + DeserializeOwned
+        + 'static,
+{
+    fn version(&self) -> Option<String> {
+        self.store.find(&self.obj)?.resource_version()
+    }
+
+    fn to_yaml(&self) -> Option<String> {
+        self.store.find(&self.obj)?.to_yaml().ok()
+    }
+}
+
+/// Syntax highlighted YAML for a resource. Top level sections can be folded
+/// and `:` jumps to a path such as `spec.containers.0.image`. Lines are only
+/// highlighted once they've been scrolled to. While the resource is being
+/// watched by another widget, updates are shown as they happen with the lines
+/// that changed highlighted for a moment.
 pub struct Yaml {
-    txt: String,
+    highlighter: Highlighter,
+    paths: Vec<String>,
+    sections: Vec<Section>,
+    folded: HashSet<usize>,
+
+    source: Option<Box<dyn Source>>,
+    version: Option<String>,
+    changed: HashSet<usize>,
+    changed_at: Instant,
+
     position: Position,
+    nav: Nav,
+    input: Option<input::Text>,
+    status: Option<String>,
 }
 
 impl Yaml {
     pub fn new<K>(resource: &Arc<K>) -> Self
     where
-        K: Resource<DynamicType = ()> + Serialize + Send + Sync + 'static,
+        K: Resource<DynamicType = ()>
+            + Serialize
+            + Clone
+            + std::fmt::Debug
+            + Send
+            + Sync
+            + DeserializeOwned
+            + 'static,
     {
         WIDGET_VIEWS_VEC
             .with_label_values(&[K::kind(&()).borrow(), "yaml"])
@@ -81,54 +328,357 @@ impl Yaml {
 
         let txt = resource.to_yaml().unwrap();
 
+        // Detail views are opened from a list, which keeps a store of the kind
+        // around. Nothing is watched just for this.
+        let source = Store::<K>::shared().map(|store| {
+            Box::new(Watched {
+                store,
+                obj: ObjectRef::from_obj(resource.as_ref()),
+            }) as Box<dyn Source>
+        });
+
         Self {
-            txt,
+            highlighter: Highlighter::new(&txt),
+            paths: to_paths(&txt),
+            sections: to_sections(&txt),
+            folded: HashSet::new(),
+
+            source,
+            version: resource.resource_version(),
+            changed: HashSet::new(),
+            changed_at: Instant::now(),
+
             position: Position::default(),
+            nav: Nav::default(),
+            input: None,
+            status: None,
         }
     }
 
     pub fn tab<K>(name: String, resource: Arc<K>) -> Tab
     where
-        K: Resource<DynamicType = ()> + Serialize + Send + Sync + 'static,
+        K: Resource<DynamicType = ()>
+            + Serialize
+            + Clone
+            + std::fmt::Debug
+            + Send
+            + Sync
+            + DeserializeOwned
+            + 'static,
     {
         Tab::builder()
             .name(name)
             .constructor(Box::new(move || Self::new(&resource).boxed().into()))
             .build()
     }
+
+    // Deleted resources keep showing the last version that was seen.
+    fn refresh(&mut self) {
+        let Some(source) = self.source.as_ref() else {
+            return;
+        };
+
+        let version = source.version();
+        if version.is_none() || version == self.version {
+            return;
+        }
+
+        let Some(txt) = source.to_yaml() else {
+            return;
+        };
+
+        self.version = version;
+        self.update(&txt);
+    }
+
+    // Lines are compared by path and content, so that something being added
+    // above doesn't mark everything below it as changed. Folds follow the
+    // section they were on.
+    fn update(&mut self, txt: &str) {
+        let paths = to_paths(txt);
+        let sections = to_sections(txt);
+
+        let before: HashSet<_> = self
+            .paths
+            .iter()
+            .map(String::as_str)
+            .zip(
+                self.highlighter
+                    .source
+                    .iter()
+                    .map(|line| line.trim_end_matches(['\n', '\r'])),
+            )
+            .collect();
+
+        self.changed = paths
+            .iter()
+            .map(String::as_str)
+            .zip(txt.lines())
+            .enumerate()
+            .filter(|(_, line)| !before.contains(line))
+            .map(|(i, _)| i)
+            .collect();
+        self.changed_at = Instant::now();
+
+        let folded: HashSet<_> = self
+            .folded
+            .iter()
+            .filter_map(|start| self.paths.get(*start))
+            .collect();
+
+        self.folded = sections
+            .iter()
+            .filter(|s| paths.get(s.start).is_some_and(|path| folded.contains(path)))
+            .map(|s| s.start)
+            .collect();
+
+        self.highlighter = Highlighter::new(txt);
+        self.paths = paths;
+        self.sections = sections;
+    }
+
+    // Indices of the lines that aren't hidden by a fold.
+    fn visible(&self) -> Vec<usize> {
+        let mut hidden = self
+            .sections
+            .iter()
+            .filter(|s| self.folded.contains(&s.start))
+            .flat_map(|s| s.start + 1..s.end)
+            .peekable();
+
+        (0..self.highlighter.len())
+            .filter(|i| {
+                while hidden.next_if(|h| h < i).is_some() {}
+
+                hidden.next_if_eq(i).is_none()
+            })
+            .collect()
+    }
+
+    fn section(&self, line: usize) -> Option<&Section> {
+        self.sections
+            .iter()
+            .find(|s| (s.start..s.end).contains(&line))
+    }
+
+    fn scroll_to(&mut self, line: usize) {
+        let row = self
+            .visible()
+            .iter()
+            .position(|i| *i == line)
+            .unwrap_or_default();
+
+        self.position.y = u16::try_from(row).unwrap_or(u16::MAX);
+    }
+
+    fn toggle_fold(&mut self) {
+        let top = self
+            .visible()
+            .get(self.position.y as usize)
+            .copied()
+            .unwrap_or_default();
+
+        let Some(start) = self.section(top).map(|s| s.start) else {
+            return;
+        };
+
+        if !self.folded.remove(&start) {
+            self.folded.insert(start);
+        }
+
+        self.scroll_to(start);
+    }
+
+    fn toggle_all(&mut self) {
+        if self.folded.len() == self.sections.len() {
+            self.folded.clear();
+        } else {
+            self.folded = self.sections.iter().map(|s| s.start).collect();
+        }
+
+        self.position.y = 0;
+    }
+
+    fn jump(&mut self) {
+        let Some(text) = self.input.take() else {
+            return;
+        };
+
+        let path = text.value();
+        let path = path.trim().trim_start_matches('.');
+
+        // Exact paths win, otherwise the first line that ends with the path
+        // matches. This allows `image` to find the first container's image.
+        let suffix = format!(".{path}");
+        let Some(line) = self
+            .paths
+            .iter()
+            .position(|p| p == path)
+            .or_else(|| self.paths.iter().position(|p| p.ends_with(suffix.as_str())))
+        else {
+            self.status = Some(format!("{path} not found"));
+
+            return;
+        };
+
+        if let Some(start) = self.section(line).map(|s| s.start) {
+            self.folded.remove(&start);
+        }
+
+        self.status = None;
+        self.scroll_to(line);
+    }
 }
 
 impl Widget for Yaml {
-    fn dispatch(&mut self, event: &Event, _: &Buffer, area: Rect) -> Result<Broadcast> {
+    fn dispatch(&mut self, event: &Event, buffer: &Buffer, area: Rect) -> Result<Broadcast> {
         let Some(key) = event.key() else {
             return Ok(Broadcast::Ignored);
         };
 
-        if let Some(Movement::Y(y)) = move_cursor(key, area) {
-            self.position.y = self.position.y.saturating_add_signed(y);
+        if let Some(text) = self.input.as_mut() {
+            if text.submit(key) {
+                self.jump();
+            } else if matches!(text.dispatch(event, buffer, area)?, Broadcast::Exited) {
+                self.input = None;
+            }
 
             return Ok(Broadcast::Consumed);
         }
 
-        Ok(Broadcast::Ignored)
+        match self.nav.motion(key, area) {
+            Motion::Move(Movement::Y(y)) => {
+                self.position.y = self.position.y.saturating_add_signed(y);
+
+                return Ok(Broadcast::Consumed);
+            }
+            Motion::Pending => return Ok(Broadcast::Consumed),
+            _ => {}
+        }
+
+        match key {
+            Keypress::Printable('z') => self.toggle_fold(),
+            Keypress::Printable('Z') => self.toggle_all(),
+            Keypress::Printable(':') => {
+                self.input = Some(
+                    input::Text::builder()
+                        .title("Jump to path")
+                        .history("path")
+                        .complete(input::complete_words(
+                            self.paths
+                                .iter()
+                                .filter(|path| !path.is_empty())
+                                .unique()
+                                .cloned()
+                                .collect(),
+                        ))
+                        .build(),
+                );
+            }
+            _ => return Ok(Broadcast::Ignored),
+        }
+
+        Ok(Broadcast::Consumed)
+    }
+
+    fn dirty(&self) -> bool {
+        !self.changed.is_empty()
+            || self
+                .source
+                .as_ref()
+                .is_some_and(|source| source.version().is_some_and(|v| Some(v) != self.version))
     }
 
     #[allow(clippy::cast_possible_truncation)]
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
-        let lines = to_lines(self.txt.as_str());
+        self.refresh();
 
-        self.position.y = self
-            .position
-            .y
-            .clamp(0, (lines.len() as u16).saturating_sub(area.height));
+        if self.changed_at.elapsed() > HIGHLIGHT_FOR {
+            self.changed.clear();
+        }
+
+        let mut block = Block::default().borders(Borders::ALL);
+
+        if let Some(status) = self.status.as_ref() {
+            block = block.title_bottom(
+                Line::from(format!(" {status} ")).style(Style::default().fg(tailwind::RED.c300)),
+            );
+        }
+
+        let [content, editor] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(if self.input.is_some() { 3 } else { 0 }),
+        ])
+        .areas(area);
+
+        let visible = self.visible();
+        let height = block.inner(content).height;
+
+        // Lines are read out one at a time in linear mode, every one of them can be
+        // scrolled to.
+        let last = if narration::enabled() {
+            (visible.len() as u16).saturating_sub(1)
+        } else {
+            (visible.len() as u16).saturating_sub(height)
+        };
+
+        self.position.y = self.position.y.clamp(0, last);
+
+        // Only the lines on screen are highlighted, cloned and rendered.
+        let lines: Vec<Line> = visible
+            .iter()
+            .skip(self.position.y as usize)
+            .take(height as usize)
+            .map(|i| {
+                let mut line = self.highlighter.line(*i).cloned().unwrap_or_default();
+
+                if self.changed.contains(i) {
+                    line = line.style(Style::default().bg(tailwind::YELLOW.c900));
+                }
+
+                if let Some(section) = self
+                    .sections
+                    .iter()
+                    .find(|s| s.start == *i && self.folded.contains(i))
+                {
+                    line.spans.push(Span::styled(
+                        format!(" … {} lines", section.end - section.start - 1),
+                        Style::default().fg(tailwind::GRAY.c500),
+                    ));
+                }
+
+                line
+            })
+            .collect();
+
+        narration::focus(
+            || None,
+            || {
+                t_with(
+                    "narration.line",
+                    &[
+                        ("index", &(self.position.y + 1)),
+                        ("count", &visible.len()),
+                        (
+                            "text",
+                            &lines.first().map(ToString::to_string).unwrap_or_default(),
+                        ),
+                    ],
+                )
+            },
+        );
 
         frame.render_widget(
             Paragraph::new(lines)
-                .scroll((self.position.y, self.position.x))
-                .block(Block::default().borders(Borders::ALL)),
-            area,
+                .scroll((0, self.position.x))
+                .block(block),
+            content,
         );
 
+        if let Some(text) = self.input.as_mut() {
+            text.draw(frame, editor)?;
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,73 @@
+use kube::{Resource, ResourceExt};
+
+use super::keys::Action;
+use crate::events::Keypress;
+
+/// What can be copied about a resource from the table or detail view showing
+/// it, see [`Action::YankName`], [`Action::YankPath`] and
+/// [`Action::YankCommand`].
+#[derive(Clone, Debug)]
+pub struct Yank {
+    kind: String,
+    namespace: Option<String>,
+    name: String,
+}
+
+impl Yank {
+    /// `kind` is the same as a resource's, eg. `Deployment`.
+    pub fn new(kind: impl Into<String>, namespace: Option<String>, name: String) -> Self {
+        Self {
+            kind: kind.into(),
+            namespace,
+            name,
+        }
+    }
+
+    pub fn from_obj<K>(obj: &K) -> Self
+    where
+        K: Resource<DynamicType = ()>,
+    {
+        Self::new(K::kind(&()), obj.namespace(), obj.name_any())
+    }
+
+    /// `namespace/name`, or only the name for cluster scoped resources.
+    pub fn path(&self) -> String {
+        match &self.namespace {
+            Some(namespace) => format!("{namespace}/{}", self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// The `kubectl` command someone looking at the resource is most likely to
+    /// want next. Logs for pods, `describe` for everything else.
+    pub fn command(&self) -> String {
+        let namespace = self
+            .namespace
+            .as_ref()
+            .map(|namespace| format!(" -n {namespace}"))
+            .unwrap_or_default();
+
+        if self.kind == "Pod" {
+            format!("kubectl{namespace} logs {}", self.name)
+        } else {
+            format!(
+                "kubectl{namespace} describe {}/{}",
+                self.kind.to_lowercase(),
+                self.name
+            )
+        }
+    }
+
+    /// What `key` copies, if it is one of the yank keys.
+    pub fn text(&self, key: &Keypress) -> Option<String> {
+        if Action::YankName.matches(key) {
+            Some(self.name.clone())
+        } else if Action::YankPath.matches(key) {
+            Some(self.path())
+        } else if Action::YankCommand.matches(key) {
+            Some(self.command())
+        } else {
+            None
+        }
+    }
+}